@@ -0,0 +1,57 @@
+#!/bin/sh
+# Checks that headers-ext builds with only some of its header groups.
+#
+# The tests use headers from every group, so they only run with the
+# default `full` feature.
+set -e
+
+cd "$(dirname "$0")/.."
+
+for features in \
+    "" \
+    auth \
+    caching \
+    conditional \
+    cors \
+    proxy \
+    security \
+    websocket \
+    file-extensions \
+    cors,caching \
+    conditional,proxy \
+    auth,security,websocket \
+    chrono,time03 \
+    md5 \
+    tower \
+    conditional,chrono
+do
+    echo "checking headers-ext with features: [$features]"
+    RUSTFLAGS="-D warnings" cargo check -p headers-ext --no-default-features --features "$features"
+done
+
+# The date conversions have their own tests.
+echo "testing headers-ext with features: [chrono,time03]"
+cargo test -p headers-ext --features chrono,time03 --lib
+
+# The body digests of Content-MD5.
+echo "testing headers-ext with features: [md5]"
+cargo test -p headers-ext --features md5 --lib content_md5
+
+# The tower layer, next to the insertion it wraps.
+echo "testing headers-ext with features: [tower]"
+cargo test -p headers-ext --features tower --lib middleware
+cargo test -p headers-ext --features tower --doc middleware
+
+# The lists encode sorted, so the tests check the order as built only
+# without it.
+echo "testing headers-ext with features: [canonical]"
+cargo test -p headers-ext --features canonical
+
+# headers-core without `std`, from a `no_std` crate and for its own tests.
+echo "checking headers-core without std"
+cargo build -p headers-no-std-check
+cargo test -p headers-core --no-default-features
+
+# The decode failure logging, with a logger of its own.
+echo "testing headers-core with features: [log]"
+cargo test -p headers-core --features log --lib trace
@@ -0,0 +1,40 @@
+//! Implements a header in a `#![no_std]` crate, to check that
+//! `headers-core` doesn't need `std` without its `std` feature.
+//!
+//! `http` and `bytes` still depend on `std` themselves, so this only checks
+//! what `headers-core` uses.
+
+#![no_std]
+#![deny(warnings)]
+
+extern crate headers_core;
+
+use headers_core::{Header, HeaderName, HeaderValue, ToValues, Values};
+
+/// A header that's a single digit.
+#[derive(Debug, PartialEq)]
+pub struct Digit(pub u8);
+
+static NAME: HeaderName = headers_core::header::CONTENT_LENGTH;
+
+impl Header for Digit {
+    fn name() -> &'static HeaderName {
+        &NAME
+    }
+
+    fn decode(values: &mut Values) -> Option<Self> {
+        match values.next()?.as_bytes() {
+            &[d] if d.is_ascii_digit() => Some(Digit(d - b'0')),
+            _ => None,
+        }
+    }
+
+    fn encode(&self, values: &mut ToValues) {
+        values.append_fmt(format_args!("{}", self.0));
+    }
+}
+
+/// Round trip a `Digit` through its header value.
+pub fn round_trip(value: &HeaderValue) -> Option<Digit> {
+    headers_core::decode::from_value(value).ok()
+}
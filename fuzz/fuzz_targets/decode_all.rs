@@ -0,0 +1,15 @@
+//! Decode the input with every typed header.
+//!
+//! The first byte picks a single header to decode, or all of them if it's
+//! out of range. The rest is split on newlines into the header's values.
+
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate headers_ext;
+
+fuzz_target!(|data: &[u8]| {
+    if let Some((&name_hint, values)) = data.split_first() {
+        headers_ext::fuzz_decode_all(name_hint, values);
+    }
+});
@@ -2,6 +2,8 @@
 
 use http::header::HeaderValue;
 
+use {DecodeError, DecodeLimits, Header, HeaderMapExt, InvalidHeader, ParseMode};
+
 /// A helper trait for use when deriving `Header`.
 pub trait TryFromValues: Sized {
     /// Try to convert from the values into an instance of `Self`.
@@ -17,12 +19,19 @@ impl TryFromValues for HeaderValue {
 }
 
 /// Reads a comma-delimited raw header into a Vec.
+///
+/// Empty elements are skipped, and optional whitespace (spaces and tabs) is
+/// trimmed from each element. Having more elements than
+/// `DecodeLimits::max_list_items` fails, without parsing the rest.
 pub fn from_comma_delimited<T, E>(values: &mut ::Values) -> Option<E>
 where
     T: ::std::str::FromStr,
     E: ::std::iter::FromIterator<T>,
 {
-    values
+    let max = values.limits().max_list_items;
+    let mut too_large = false;
+    let list = values
+        .by_ref()
         .flat_map(|value| {
             value
                 .to_str()
@@ -30,14 +39,64 @@ where
                 .flat_map(|string| {
                     string
                         .split(',')
-                        .filter_map(|x| match x.trim() {
+                        .filter_map(|x| match x.trim_matches(|c| c == ' ' || c == '\t') {
                             "" => None,
                             y => Some(y)
                         })
-                        .map(|x| x.parse().map_err(|_| ()))
                 })
         })
-        .collect::<Result<E, ()>>()
-        .ok()
+        .enumerate()
+        .map(|(i, x)| {
+            if i == max {
+                too_large = true;
+                return Err(());
+            }
+            x.parse().map_err(|_| ())
+        })
+        .collect::<Result<E, ()>>();
+    if too_large {
+        values.set_too_large();
+    }
+    list.ok()
+}
+
+
+/// Decode a header from a single string, as if it were the only value.
+///
+/// This is meant for implementing `FromStr` for headers.
+pub fn from_str<H: Header>(s: &str) -> Result<H, InvalidHeader> {
+    let value = s.parse::<HeaderValue>().map_err(|_| InvalidHeader(()))?;
+    from_value(&value)
 }
 
+/// Decode a header from a single value, as if it were the only value.
+///
+/// This is meant for implementing `TryFrom<&HeaderValue>` for headers.
+pub fn from_value<H: Header>(value: &HeaderValue) -> Result<H, InvalidHeader> {
+    from_value_with(value, ParseMode::default())
+}
+
+/// Decode a header from a single value, as if it were the only value,
+/// with the given `ParseMode`.
+pub fn from_value_with<H: Header>(value: &HeaderValue, mode: ParseMode) -> Result<H, InvalidHeader> {
+    let mut map = ::http::HeaderMap::new();
+    map.insert(H::name(), value.clone());
+    map.typed_get_with(mode).ok_or(InvalidHeader(()))
+}
+
+/// Decode a header from the values of all its field lines, in order, with
+/// the given `ParseMode` and `DecodeLimits`.
+///
+/// This is meant for wrappers that need to look at the values before
+/// decoding them.
+pub fn from_values_with<H: Header>(
+    values: &[HeaderValue],
+    mode: ParseMode,
+    limits: DecodeLimits,
+) -> Result<H, DecodeError> {
+    let mut map = ::http::HeaderMap::new();
+    for value in values {
+        map.append(H::name(), value.clone());
+    }
+    ::decode_from(&map, mode, limits)
+}
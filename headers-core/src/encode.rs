@@ -1,6 +1,14 @@
 //! Encoding utilities.
 
-use std::fmt;
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+use std::fmt::{self, Write};
+
+use http::HeaderValue;
+
+use {Header, SingleValueHeader, ToValues};
 
 /// Format an array into a comma-delimited string.
 pub fn comma_delimited<T: fmt::Display>(f: &mut fmt::Formatter, mut iter: impl Iterator<Item=T>) -> fmt::Result {
@@ -13,3 +21,73 @@ pub fn comma_delimited<T: fmt::Display>(f: &mut fmt::Formatter, mut iter: impl I
     }
     Ok(())
 }
+
+/// Append all the items as a single comma-delimited value.
+///
+/// Some clients and intermediaries mishandle list headers that are split
+/// over several field lines, so list headers should be encoded with this
+/// rather than appending each item separately.
+pub fn encode_comma_joined<I>(values: &mut ToValues, iter: I)
+where
+    I: IntoIterator,
+    I::Item: fmt::Display,
+{
+    let mut joined = String::new();
+    for (i, part) in iter.into_iter().enumerate() {
+        if i > 0 {
+            joined.push_str(", ");
+        }
+        write!(joined, "{}", part).expect("writing to a String can't fail");
+    }
+    values.append_fmt(joined);
+}
+
+/// Encode a header to its single value.
+///
+/// This is meant for implementing `From<&H> for HeaderValue`.
+///
+/// # Panics
+///
+/// Panics if the header doesn't encode to any value. In debug builds, this
+/// also panics if it encodes to more than one value.
+pub fn to_value<H: SingleValueHeader>(header: &H) -> HeaderValue {
+    let mut map = encode_to_map(header);
+    debug_assert_eq!(
+        map.get_all(H::name()).iter().count(),
+        1,
+        "SingleValueHeader {:?} encoded to several values",
+        H::name(),
+    );
+    map.remove(H::name())
+        .expect("SingleValueHeader encoded to no value")
+}
+
+/// Encode a header to the values of all its field lines, in order.
+pub fn to_values<H: Header>(header: &H) -> Vec<HeaderValue> {
+    let map = encode_to_map(header);
+    map.get_all(H::name()).iter().cloned().collect()
+}
+
+pub(crate) fn encode_to_map<H: Header>(header: &H) -> ::http::HeaderMap {
+    let mut map = ::http::HeaderMap::new();
+    {
+        let entry = map
+            .entry(H::name())
+            .expect("HeaderName is always valid");
+        let mut values = ToValues::new(entry);
+        header.encode(&mut values);
+    }
+    map
+}
+
+/// Format the values a header encodes to, joined with `, `.
+///
+/// This is meant for implementing `Display` for headers. Values that aren't
+/// valid UTF-8 are formatted lossily.
+pub fn fmt_header<H: Header>(header: &H, f: &mut fmt::Formatter) -> fmt::Result {
+    let map = encode_to_map(header);
+    comma_delimited(
+        f,
+        map.get_all(H::name()).iter().map(|value| String::from_utf8_lossy(value.as_bytes())),
+    )
+}
@@ -1,21 +1,80 @@
 #![deny(missing_docs)]
 #![deny(missing_debug_implementations)]
 #![cfg_attr(test, deny(warnings))]
+#![cfg_attr(not(feature = "std"), no_std)]
 
 //! # headers-core
 //!
 //! This is the core crate of the typed HTTP headers system, providing only
 //! the relevant traits. All actual header implementations are in other crates.
+//!
+//! # Features
+//!
+//! - `std` (default): implement `std::error::Error` for the error types.
+//!   Without it, this crate only uses `core` and `alloc` itself.
+//! - `log`: log a debug event, with the `headers` target, when
+//!   `HeaderMapExt` fails to decode a header that's in the map. It has the
+//!   header's name and its first 256 bytes of values, escaped, except for
+//!   credentials, which are redacted as `RedactedHeaders` does.
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+#[cfg(not(feature = "std"))]
+extern crate core as std;
 
 extern crate bytes;
 extern crate http;
+#[cfg(feature = "log")]
+#[macro_use]
+extern crate log;
 
 use std::fmt;
 
+use bytes::BytesMut;
+
 pub use http::header::{self, HeaderName, HeaderValue};
 
+/// Get a `&'static HeaderName` for a name that isn't a constant in
+/// `http::header`, for implementing `Header::name`.
+///
+/// The name is made with `HeaderName::from_static` the first time it's
+/// used, so it must be lowercase, and panics if it's invalid.
+///
+/// # Example
+///
+/// ```
+/// #[macro_use]
+/// extern crate headers_core;
+///
+/// use headers_core::HeaderName;
+///
+/// fn name() -> &'static HeaderName {
+///     static_header_name!("permissions-policy")
+/// }
+///
+/// # fn main() {
+/// assert_eq!(name(), "permissions-policy");
+/// assert!(::std::ptr::eq(name(), name()));
+/// # }
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! static_header_name {
+    ($name:expr) => {{
+        static NAME: ::std::sync::OnceLock<$crate::HeaderName> = ::std::sync::OnceLock::new();
+        NAME.get_or_init(|| $crate::HeaderName::from_static($name))
+    }};
+}
+
 pub mod decode;
 pub mod encode;
+pub mod quoted;
+mod redact;
+pub mod test_helpers;
+#[cfg(feature = "log")]
+mod trace;
+
+pub use self::redact::RedactedHeaders;
 
 /// A trait for any object that will represent a header field and value.
 ///
@@ -23,9 +82,12 @@ pub mod encode;
 /// and contains trait-object unsafe methods.
 pub trait Header {
     /// The name of this header.
-    const NAME: &'static HeaderName;
+    fn name() -> &'static HeaderName;
 
     /// Decode this type from a `HeaderValue`.
+    ///
+    /// The values usually come from a peer, so this must not panic
+    /// whatever their bytes, returning `None` for anything it can't parse.
     fn decode(values: &mut Values) -> Option<Self>
     where
         Self: Sized;
@@ -35,17 +97,186 @@ pub trait Header {
     /// This function should be infallible. Any errors converting to a
     /// `HeaderValue` should have been caught when parsing or constructing
     /// this value.
+    ///
+    /// Appending no values at all means the header is absent, and
+    /// `HeaderMapExt::typed_insert` removes any values the map had for it.
+    /// This is only meant for headers made of whole field lines, such as
+    /// a `Set-Cookie` with no cookies. An empty comma-separated list is
+    /// still one field line, and encodes as an empty value.
     fn encode(&self, values: &mut ToValues);
+
+    /// Check that this value can be encoded.
+    ///
+    /// Headers that can be constructed from arbitrary strings, without
+    /// checking them, should check here that they only encode to valid
+    /// `HeaderValue`s, such as not containing a CR or LF. This is called
+    /// by `HeaderMapExt::typed_insert` and `typed_try_insert` before
+    /// `encode`.
+    ///
+    /// The default accepts every value.
+    fn validate(&self) -> Result<(), InvalidHeader> {
+        Ok(())
+    }
+}
+
+/// A header that always encodes to exactly one `HeaderValue`.
+///
+/// This allows converting the header to and from a single value, and should
+/// not be implemented for headers that need several field lines, such as
+/// `Set-Cookie`.
+pub trait SingleValueHeader: Header {}
+
+/// A header that can be combined with another of the same kind, such as
+/// a list combined by taking the union of both.
+///
+/// This allows `HeaderMapExt::typed_merge` to add to a header that may
+/// already be set.
+pub trait MergeableHeader: Header {
+    /// Combine `other` into this header.
+    fn merge(&mut self, other: Self);
+}
+
+/// A view of a header that borrows from the `HeaderMap` it's decoded from,
+/// instead of copying its values.
+///
+/// This allows `HeaderMapExt::typed_get_borrowed`, for headers that are
+/// decoded on every request and only read, such as `Cookie`. A view
+/// usually has an owned `Header` counterpart, for storing and encoding.
+pub trait BorrowedHeader<'a>: Sized {
+    /// The name of this header.
+    fn name() -> &'static HeaderName;
+
+    /// Decode this view from the values, borrowing from them.
+    ///
+    /// Like `Header::decode`, this must not panic whatever their bytes.
+    fn decode_borrowed(values: &mut Values<'a>) -> Option<Self>;
 }
 
+/// An error returned when a header can't be decoded from a string or a
+/// single value.
+#[derive(Debug)]
+pub struct InvalidHeader(());
+
+impl InvalidHeader {
+    /// Create an `InvalidHeader`, such as for returning from
+    /// `Header::validate`.
+    pub fn new() -> InvalidHeader {
+        InvalidHeader(())
+    }
+}
+
+impl Default for InvalidHeader {
+    fn default() -> InvalidHeader {
+        InvalidHeader::new()
+    }
+}
+
+impl fmt::Display for InvalidHeader {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid header value")
+    }
+}
+
+#[cfg(feature = "std")]
+impl ::std::error::Error for InvalidHeader {}
+
+/// How strictly `Header::decode` follows the grammar of a header.
+///
+/// Decoders only differ between the modes where real clients and servers
+/// are known to deviate from the specification.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ParseMode {
+    /// Reject anything that doesn't follow the grammar exactly.
+    Strict,
+    /// Accept common deviations, as long as their meaning is clear.
+    ///
+    /// This is the default, used by `HeaderMapExt::typed_get`.
+    #[default]
+    Lenient,
+}
+
+/// Limits on the size of a header, past which it isn't decoded.
+///
+/// The defaults are generous, and only meant to stop pathological values
+/// from being parsed, such as a list of thousands of elements.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DecodeLimits {
+    /// The most bytes a header can have, over all its field lines.
+    ///
+    /// Defaults to 64KiB.
+    pub max_value_len: usize,
+    /// The most elements a list header can have, or field lines for a
+    /// header such as `Set-Cookie`.
+    ///
+    /// Defaults to 1024.
+    pub max_list_items: usize,
+}
+
+impl Default for DecodeLimits {
+    fn default() -> DecodeLimits {
+        DecodeLimits {
+            max_value_len: 64 * 1024,
+            max_list_items: 1024,
+        }
+    }
+}
+
+/// An error returned by `HeaderMapExt::typed_get_with_limits`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The header isn't in the map.
+    Missing,
+    /// The header failed to decode.
+    Invalid,
+    /// The header is larger than the `DecodeLimits` allow.
+    TooLarge,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            DecodeError::Missing => "missing header",
+            DecodeError::Invalid => "invalid header value",
+            DecodeError::TooLarge => "header too large",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl ::std::error::Error for DecodeError {}
+
 /// An iterator of `HeaderValue`s supplied to `Header::decode`.
 #[derive(Debug)]
 pub struct Values<'a> {
     inner: http::header::ValueIter<'a, http::header::HeaderValue>,
     should_exhaust: bool,
+    mode: ParseMode,
+    limits: DecodeLimits,
+    too_large: bool,
 }
 
 impl<'a> Values<'a> {
+    /// The `ParseMode` the header is being decoded with.
+    pub fn mode(&self) -> ParseMode {
+        self.mode
+    }
+
+    /// The `DecodeLimits` the header is being decoded with.
+    ///
+    /// The length of the values is checked before decoding, but a decoder
+    /// collecting a list should check `max_list_items` itself.
+    pub fn limits(&self) -> DecodeLimits {
+        self.limits
+    }
+
+    /// Record that the header goes over its `DecodeLimits`.
+    ///
+    /// The header then fails to decode, whatever `Header::decode` returns,
+    /// and `typed_get_with_limits` reports it as `DecodeError::TooLarge`.
+    pub fn set_too_large(&mut self) {
+        self.too_large = true;
+    }
+
     /// Skip the exhaustive check for this header.
     ///
     /// By default, the iterator will be checked that it was exhausted
@@ -56,6 +287,50 @@ impl<'a> Values<'a> {
     pub fn skip_exhaustive_iter_check(&mut self) {
         self.should_exhaust = false;
     }
+
+    /// Get the last value, for headers where a later value overrides the
+    /// ones before it.
+    ///
+    /// This consumes all the values, so the ones skipped don't fail the
+    /// exhaustive check.
+    ///
+    /// # Example
+    ///
+    // `static_header_name!` needs `std`.
+    #[cfg_attr(feature = "std", doc = "```")]
+    #[cfg_attr(not(feature = "std"), doc = "```ignore")]
+    /// # #[macro_use] extern crate headers_core;
+    /// # extern crate http;
+    /// use headers_core::{Header, HeaderMapExt, HeaderName, ToValues, Values};
+    /// use http::HeaderMap;
+    ///
+    /// struct XMode(String);
+    ///
+    /// impl Header for XMode {
+    ///     fn name() -> &'static HeaderName {
+    ///         static_header_name!("x-mode")
+    ///     }
+    ///
+    ///     fn decode(values: &mut Values) -> Option<Self> {
+    ///         let value = values.last_value()?.to_str().ok()?;
+    ///         Some(XMode(value.to_owned()))
+    ///     }
+    ///
+    ///     fn encode(&self, values: &mut ToValues) {
+    ///         values.append_fmt(&self.0);
+    ///     }
+    /// }
+    ///
+    /// # fn main() {
+    /// let mut map = HeaderMap::new();
+    /// map.append("x-mode", "fast".parse().unwrap());
+    /// map.append("x-mode", "safe".parse().unwrap());
+    /// assert_eq!(map.typed_get::<XMode>().unwrap().0, "safe");
+    /// # }
+    /// ```
+    pub fn last_value(&mut self) -> Option<&'a HeaderValue> {
+        self.inner.by_ref().last()
+    }
 }
 
 impl<'a> Iterator for Values<'a> {
@@ -72,6 +347,9 @@ impl<'a> Iterator for Values<'a> {
     }
 }
 
+/// Values taken from the back are consumed as from the front, but any left
+/// before them still fail the exhaustive check. Use `Values::last_value`
+/// to take only the last, or `skip_exhaustive_iter_check` to stop early.
 impl<'a> DoubleEndedIterator for Values<'a> {
     #[inline]
     fn next_back(&mut self) -> Option<&'a HeaderValue> {
@@ -83,6 +361,7 @@ impl<'a> DoubleEndedIterator for Values<'a> {
 #[derive(Debug)]
 pub struct ToValues<'a> {
     state: State<'a>,
+    appended: usize,
 }
 
 #[derive(Debug)]
@@ -93,6 +372,32 @@ enum State<'a> {
 }
 
 impl<'a> ToValues<'a> {
+    fn new(entry: http::header::Entry<'a, HeaderValue>) -> ToValues<'a> {
+        ToValues {
+            state: State::First(entry),
+            appended: 0,
+        }
+    }
+
+    /// The number of values appended so far.
+    ///
+    /// A header that must have a value can assert this is not zero at the
+    /// end of its `encode`.
+    pub fn appended_count(&self) -> usize {
+        self.appended
+    }
+
+    // Remove the values the map had if none were appended, since the
+    // first `append` would have replaced them.
+    fn finish(self) {
+        if let State::First(http::header::Entry::Occupied(mut entry)) = self.state {
+            // Removing an entry with several values can panic in http 0.1,
+            // so it's replaced by a single one first.
+            entry.insert(HeaderValue::from_static(""));
+            entry.remove();
+        }
+    }
+
     /// Append the `HeaderValue` to the existing list of headers.
     ///
     /// While this can be called multiple times, *most* headers should only
@@ -111,6 +416,7 @@ impl<'a> ToValues<'a> {
             State::Tmp => unreachable!("ToValues State::Tmp"),
         };
         self.state = State::Latter(entry);
+        self.appended += 1;
     }
 
     /// Append the `impl Display` to the list of headers.
@@ -121,26 +427,115 @@ impl<'a> ToValues<'a> {
     /// all UTF-8 sequences are valid for a `HeaderValue`. The type passed
     /// here must ensure that its resulting string is a valid `HeaderValue`.
     pub fn append_fmt<T: fmt::Display>(&mut self, fmt: T) {
-        let s = fmt.to_string();
-        let value = match HeaderValue::from_shared(s.into()) {
-            Ok(val) => val,
-            Err(err) => panic!("illegal HeaderValue; error = {:?}, fmt = \"{}\"", err, fmt),
+        use std::fmt::Write;
+
+        let mut writer = ValueWriter {
+            buf: BytesMut::new(),
+            valid: true,
+        };
+        write!(writer, "{}", fmt)
+            .expect("a Display implementation returned an error unexpectedly");
+
+        let bytes = writer.buf.freeze();
+        let value = if writer.valid {
+            // Safe because every byte was checked as it was written.
+            unsafe { HeaderValue::from_shared_unchecked(bytes) }
+        } else {
+            match HeaderValue::from_shared(bytes) {
+                Ok(val) => val,
+                Err(err) => panic!("illegal HeaderValue; error = {:?}, fmt = \"{}\"", err, fmt),
+            }
         };
         self.append(value);
     }
 }
 
+/// Formats straight into the buffer of a `HeaderValue`, checking that each
+/// byte is allowed in one along the way.
+struct ValueWriter {
+    buf: BytesMut,
+    valid: bool,
+}
+
+impl fmt::Write for ValueWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.valid &= s.bytes().all(|b| b >= 32 && b != 127 || b == b'\t');
+        self.buf.extend_from_slice(s.as_bytes());
+        Ok(())
+    }
+}
+
 /// An extension trait adding "typed" methods to `http::HeaderMap`.
 pub trait HeaderMapExt: self::sealed::Sealed {
     /// Inserts the typed `Header` into this `HeaderMap`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `Header::validate` fails. Use `typed_try_insert` for
+    /// headers built from untrusted input.
     fn typed_insert<H>(&mut self, header: H)
     where
         H: Header;
 
+    /// Inserts the typed `Header` into this `HeaderMap`, if it's valid.
+    ///
+    /// If `Header::validate` fails, the map is left unchanged.
+    fn typed_try_insert<H>(&mut self, header: H) -> Result<(), InvalidHeader>
+    where
+        H: Header;
+
     /// Tries to find the header by name, and then decode it into `H`.
     fn typed_get<H>(&self) -> Option<H>
     where
         H: Header;
+
+    /// Tries to find the header by name, and then decode it into the view
+    /// `H`, which borrows from this map.
+    fn typed_get_borrowed<'a, H>(&'a self) -> Option<H>
+    where
+        H: BorrowedHeader<'a>;
+
+    /// Like `typed_get`, but decoding with the given `ParseMode`.
+    fn typed_get_with<H>(&self, mode: ParseMode) -> Option<H>
+    where
+        H: Header;
+
+    /// Like `typed_get`, but decoding with the given `DecodeLimits`, and
+    /// reporting why it failed.
+    ///
+    /// `typed_get` and `typed_get_with` use the default limits.
+    fn typed_get_with_limits<H>(&self, limits: DecodeLimits) -> Result<H, DecodeError>
+    where
+        H: Header;
+
+    /// Decodes the header `H` from this map and inserts it into `dst`,
+    /// replacing any values `dst` had.
+    ///
+    /// Returns whether it was copied, which it isn't if it's missing or
+    /// fails to decode.
+    fn typed_clone_into<H>(&self, dst: &mut http::HeaderMap) -> bool
+    where
+        H: Header;
+
+    /// Merges the typed `Header` into the one already in this `HeaderMap`,
+    /// or inserts it if there isn't one.
+    ///
+    /// An existing value that fails to decode is replaced.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `Header::validate` fails for the merged header, like
+    /// `typed_insert`.
+    fn typed_merge<H>(&mut self, header: H)
+    where
+        H: MergeableHeader;
+
+    /// Format this map for logs, one `name: value` line per value, with
+    /// the values of credentials like `Authorization` and `Cookie`
+    /// redacted.
+    ///
+    /// See `RedactedHeaders` for the names redacted, and adding more.
+    fn typed_debug(&self) -> RedactedHeaders<'_>;
 }
 
 impl HeaderMapExt for http::HeaderMap {
@@ -148,37 +543,384 @@ impl HeaderMapExt for http::HeaderMap {
     where
         H: Header,
     {
-        let entry = self
-            .entry(H::NAME)
-            .expect("HeaderName is always valid");
-        let mut values = ToValues {
-            state: State::First(entry),
-        };
-        header.encode(&mut values);
+        if let Err(err) = header.validate() {
+            panic!("{} for {:?}", err, H::name());
+        }
+        encode_into(self, header);
+    }
+
+    fn typed_try_insert<H>(&mut self, header: H) -> Result<(), InvalidHeader>
+    where
+        H: Header,
+    {
+        header.validate()?;
+        encode_into(self, header);
+        Ok(())
     }
 
     fn typed_get<H>(&self) -> Option<H>
     where
         H: Header,
     {
-        let mut values = Values {
-            inner: self.get_all(H::NAME).iter(),
-            should_exhaust: true,
+        self.typed_get_with(ParseMode::default())
+    }
+
+    fn typed_get_borrowed<'a, H>(&'a self) -> Option<H>
+    where
+        H: BorrowedHeader<'a>,
+    {
+        let limits = DecodeLimits::default();
+        decode_values(self, H::name(), ParseMode::default(), limits, H::decode_borrowed).ok()
+    }
+
+    fn typed_get_with<H>(&self, mode: ParseMode) -> Option<H>
+    where
+        H: Header,
+    {
+        decode_from(self, mode, DecodeLimits::default()).ok()
+    }
+
+    fn typed_get_with_limits<H>(&self, limits: DecodeLimits) -> Result<H, DecodeError>
+    where
+        H: Header,
+    {
+        decode_from(self, ParseMode::default(), limits)
+    }
+
+    fn typed_clone_into<H>(&self, dst: &mut http::HeaderMap) -> bool
+    where
+        H: Header,
+    {
+        match self.typed_get::<H>() {
+            Some(header) => {
+                dst.typed_insert(header);
+                true
+            },
+            None => false,
+        }
+    }
+
+    fn typed_merge<H>(&mut self, header: H)
+    where
+        H: MergeableHeader,
+    {
+        let merged = match self.typed_get::<H>() {
+            Some(mut existing) => {
+                existing.merge(header);
+                existing
+            },
+            None => header,
         };
-        let header = H::decode(&mut values)?;
-        // Check the iterator was consumed. Various headers are only
-        // allowed to have a single value, so if there were extra
-        // values that the implementation didn't use, it's safer
-        // to error out.
-        if !values.should_exhaust || values.next().is_none() {
-            Some(header)
-        } else {
-            None
+        self.typed_insert(merged);
+    }
+
+    fn typed_debug(&self) -> RedactedHeaders<'_> {
+        RedactedHeaders::new(self)
+    }
+}
+
+fn decode_from<H: Header>(
+    map: &http::HeaderMap,
+    mode: ParseMode,
+    limits: DecodeLimits,
+) -> Result<H, DecodeError> {
+    decode_values(map, H::name(), mode, limits, H::decode)
+}
+
+fn decode_values<'a, T, F>(
+    map: &'a http::HeaderMap,
+    name: &HeaderName,
+    mode: ParseMode,
+    limits: DecodeLimits,
+    decode: F,
+) -> Result<T, DecodeError>
+where
+    F: FnOnce(&mut Values<'a>) -> Option<T>,
+{
+    let result = try_decode_values(map, name, mode, limits, decode);
+    #[cfg(feature = "log")]
+    match result {
+        Err(DecodeError::Missing) | Ok(_) => (),
+        Err(error) => trace::decode_failed(map, name, error),
+    }
+    result
+}
+
+fn try_decode_values<'a, T, F>(
+    map: &'a http::HeaderMap,
+    name: &HeaderName,
+    mode: ParseMode,
+    limits: DecodeLimits,
+    decode: F,
+) -> Result<T, DecodeError>
+where
+    F: FnOnce(&mut Values<'a>) -> Option<T>,
+{
+    let all = map.get_all(name);
+    let mut len = 0;
+    for value in all.iter() {
+        len += value.len();
+        if len > limits.max_value_len {
+            return Err(DecodeError::TooLarge);
         }
     }
+
+    let mut values = Values {
+        inner: all.iter(),
+        should_exhaust: true,
+        mode,
+        limits,
+        too_large: false,
+    };
+    let header = decode(&mut values);
+    if values.too_large {
+        return Err(DecodeError::TooLarge);
+    }
+    let header = match header {
+        Some(header) => header,
+        None if map.contains_key(name) => return Err(DecodeError::Invalid),
+        None => return Err(DecodeError::Missing),
+    };
+    // Check the iterator was consumed. Various headers are only
+    // allowed to have a single value, so if there were extra
+    // values that the implementation didn't use, it's safer
+    // to error out.
+    if !values.should_exhaust || values.next().is_none() {
+        Ok(header)
+    } else {
+        Err(DecodeError::Invalid)
+    }
+}
+
+fn encode_into<H: Header>(map: &mut http::HeaderMap, header: H) {
+    let entry = map
+        .entry(H::name())
+        .expect("HeaderName is always valid");
+    let mut values = ToValues::new(entry);
+    header.encode(&mut values);
+    values.finish();
 }
 
 mod sealed {
     pub trait Sealed {}
     impl Sealed for ::http::HeaderMap {}
 }
+
+// These use `std`, which `no_std` crates don't have.
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::fmt;
+
+    use http::HeaderMap;
+
+    use super::{BorrowedHeader, Header, HeaderMapExt, HeaderName, InvalidHeader, ToValues, Values};
+
+    fn append_fmt<T: fmt::Display>(fmt: T) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        {
+            let entry = map
+                .entry(::http::header::CONTENT_LENGTH)
+                .expect("HeaderName is always valid");
+            let mut values = ToValues::new(entry);
+            values.append_fmt(fmt);
+        }
+        map
+    }
+
+    #[test]
+    fn append_fmt_joins_fragments() {
+        let long = "x".repeat(100);
+        let map = append_fmt(format_args!("{}; {}={}, {}", "a", "b", 3u8, long));
+        assert_eq!(map["content-length"], format!("a; b=3, {}", long).as_str());
+
+        let map = append_fmt(format_args!("caf\u{e9}"));
+        assert_eq!(map["content-length"].as_bytes(), "caf\u{e9}".as_bytes());
+    }
+
+    #[test]
+    #[should_panic(expected = "illegal HeaderValue")]
+    fn append_fmt_panics_on_invalid() {
+        append_fmt(format_args!("{}\n{}", "a", "b"));
+    }
+
+    struct XNote(&'static str);
+
+    impl Header for XNote {
+        fn name() -> &'static HeaderName {
+            static_header_name!("x-note")
+        }
+
+        fn decode(_: &mut Values) -> Option<Self> {
+            None
+        }
+
+        fn encode(&self, values: &mut ToValues) {
+            values.append_fmt(self.0);
+        }
+
+        fn validate(&self) -> Result<(), InvalidHeader> {
+            if self.0.contains(['\r', '\n']) {
+                Err(InvalidHeader::new())
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    #[test]
+    fn typed_try_insert_validates() {
+        let mut map = HeaderMap::new();
+        assert!(map.typed_try_insert(XNote("ok")).is_ok());
+        assert!(map.typed_try_insert(XNote("a\r\nset-cookie: b=c")).is_err());
+        assert_eq!(map.len(), 1);
+        assert_eq!(map["x-note"], "ok");
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid header value for \"x-note\"")]
+    fn typed_insert_panics_on_invalid() {
+        HeaderMap::new().typed_insert(XNote("a\nb"));
+    }
+
+    struct XLines(&'static [&'static str]);
+
+    impl Header for XLines {
+        fn name() -> &'static HeaderName {
+            static_header_name!("x-note")
+        }
+
+        fn decode(_: &mut Values) -> Option<Self> {
+            None
+        }
+
+        fn encode(&self, values: &mut ToValues) {
+            for (i, line) in self.0.iter().enumerate() {
+                assert_eq!(values.appended_count(), i);
+                values.append_fmt(line);
+            }
+        }
+    }
+
+    #[test]
+    fn typed_insert_without_values() {
+        let mut map = HeaderMap::new();
+        map.typed_insert(XLines(&[]));
+        assert!(map.is_empty());
+
+        map.typed_insert(XLines(&["a", "b"]));
+        assert_eq!(map.get_all("x-note").iter().collect::<Vec<_>>(), ["a", "b"]);
+
+        // Nothing appended replaces the old values with nothing.
+        map.typed_insert(XLines(&[]));
+        assert!(!map.contains_key("x-note"));
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct XLast<'a>(&'a str);
+
+    impl<'a> BorrowedHeader<'a> for XLast<'a> {
+        fn name() -> &'static HeaderName {
+            static_header_name!("x-note")
+        }
+
+        fn decode_borrowed(values: &mut Values<'a>) -> Option<Self> {
+            values.last_value()?.to_str().ok().map(XLast)
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct XBack<'a>(&'a str);
+
+    impl<'a> BorrowedHeader<'a> for XBack<'a> {
+        fn name() -> &'static HeaderName {
+            static_header_name!("x-note")
+        }
+
+        fn decode_borrowed(values: &mut Values<'a>) -> Option<Self> {
+            values.next_back()?.to_str().ok().map(XBack)
+        }
+    }
+
+    #[test]
+    fn last_value_wins() {
+        let mut map = HeaderMap::new();
+        assert_eq!(map.typed_get_borrowed::<XLast>(), None);
+
+        map.insert("x-note", "a".parse().unwrap());
+        assert_eq!(map.typed_get_borrowed::<XLast>(), Some(XLast("a")));
+        assert_eq!(map.typed_get_borrowed::<XBack>(), Some(XBack("a")));
+
+        map.append("x-note", "b".parse().unwrap());
+        map.append("x-note", "c".parse().unwrap());
+        assert_eq!(map.typed_get_borrowed::<XLast>(), Some(XLast("c")));
+        // `next_back` leaves the others, which fail the exhaustive check.
+        assert_eq!(map.typed_get_borrowed::<XBack>(), None);
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct XNoteView<'a>(&'a str);
+
+    impl<'a> BorrowedHeader<'a> for XNoteView<'a> {
+        fn name() -> &'static HeaderName {
+            static_header_name!("x-note")
+        }
+
+        fn decode_borrowed(values: &mut Values<'a>) -> Option<Self> {
+            values.next()?.to_str().ok().map(XNoteView)
+        }
+    }
+
+    #[test]
+    fn typed_get_borrowed() {
+        let mut map = HeaderMap::new();
+        assert_eq!(map.typed_get_borrowed::<XNoteView>(), None);
+
+        map.insert("x-note", "hello".parse().unwrap());
+        let view = map.typed_get_borrowed::<XNoteView>().unwrap();
+        assert_eq!(view, XNoteView("hello"));
+        assert!(::std::ptr::eq(view.0.as_ptr(), map["x-note"].as_bytes().as_ptr()));
+
+        // Like `typed_get`, unused values fail the decode.
+        map.append("x-note", "again".parse().unwrap());
+        assert_eq!(map.typed_get_borrowed::<XNoteView>(), None);
+    }
+
+    #[derive(Debug)]
+    struct XIds(Vec<u32>);
+
+    impl Header for XIds {
+        fn name() -> &'static HeaderName {
+            static_header_name!("x-ids")
+        }
+
+        fn decode(values: &mut Values) -> Option<Self> {
+            ::decode::from_comma_delimited(values).map(XIds)
+        }
+
+        fn encode(&self, _: &mut ToValues) {}
+    }
+
+    #[test]
+    fn from_comma_delimited_limits() {
+        use {DecodeError, DecodeLimits};
+
+        let ids = (0..10_000).map(|i| i.to_string()).collect::<Vec<_>>().join(",");
+        let mut map = HeaderMap::new();
+        map.insert("x-ids", ids.parse().unwrap());
+
+        assert!(map.typed_get::<XIds>().is_none());
+        assert_eq!(map.typed_get_with_limits::<XIds>(DecodeLimits::default()).unwrap_err(), DecodeError::TooLarge);
+
+        let limits = DecodeLimits {
+            max_list_items: 10_000,
+            ..DecodeLimits::default()
+        };
+        assert_eq!(map.typed_get_with_limits::<XIds>(limits).unwrap().0.len(), 10_000);
+
+        let limits = DecodeLimits {
+            max_value_len: 100,
+            max_list_items: 10_000,
+        };
+        assert_eq!(map.typed_get_with_limits::<XIds>(limits).unwrap_err(), DecodeError::TooLarge);
+    }
+}
@@ -0,0 +1,280 @@
+//! Quoted strings, for headers whose value is a single quoted-string,
+//! such as `SOAPAction`, and for the `token / quoted-string` parameters
+//! of others.
+//!
+//! `decode`, `encode` and `validate` back `#[header(quoted)]` in
+//! `derive(Header)`, and can be used to implement such a header by hand.
+//!
+//! ```text
+//! quoted-string  = DQUOTE *( qdtext / quoted-pair ) DQUOTE
+//! qdtext         = HTAB / SP / %x21 / %x23-5B / %x5D-7E / obs-text
+//! quoted-pair    = "\" ( HTAB / SP / VCHAR / obs-text )
+//! ```
+
+#[cfg(not(feature = "std"))]
+use alloc::borrow::{Cow, ToOwned};
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+#[cfg(feature = "std")]
+use std::borrow::Cow;
+
+use http::HeaderValue;
+
+use {InvalidHeader, ParseMode};
+
+/// Decode the single value of a quoted-string header, removing the quotes
+/// and escapes.
+///
+/// In `ParseMode::Lenient`, a value without quotes is taken as it is,
+/// since some clients leave them out.
+pub fn decode(values: &mut ::Values) -> Option<String> {
+    let value = ::std::str::from_utf8(values.next()?.as_bytes()).ok()?;
+    let value = value.trim_matches(|c| c == ' ' || c == '\t');
+    if value.starts_with('"') {
+        unquote(value).map(Cow::into_owned)
+    } else if values.mode() == ParseMode::Lenient && !value.contains('"') {
+        Some(value.to_owned())
+    } else {
+        None
+    }
+}
+
+/// Encode `s` as a quoted-string, escaping `"` and `\`.
+///
+/// Returns `None` if `s` contains control characters other than HTAB,
+/// which can't be sent even when quoted.
+pub fn encode(s: &str) -> Option<HeaderValue> {
+    validate(s).ok()?;
+    HeaderValue::from_str(&quote_always(s)).ok()
+}
+
+/// Check that `s` can be encoded, for `Header::validate`.
+pub fn validate(s: &str) -> Result<(), InvalidHeader> {
+    if s.chars().all(is_text) {
+        Ok(())
+    } else {
+        Err(InvalidHeader::new())
+    }
+}
+
+/// Get the value of a `token / quoted-string`.
+///
+/// Values that don't start with a `"` are returned as they are. Quoted
+/// values only allocate if they contain escapes.
+///
+/// Returns `None` if a quoted value isn't terminated, has trailing data,
+/// a dangling escape, or contains control characters other than HTAB.
+pub fn unquote(s: &str) -> Option<Cow<'_, str>> {
+    let inner = match s.strip_prefix('"') {
+        Some(inner) => inner,
+        None => return Some(Cow::Borrowed(s)),
+    };
+
+    let mut owned: Option<String> = None;
+    let mut chars = inner.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => {
+                if i + 1 != inner.len() {
+                    return None;
+                }
+                return Some(match owned {
+                    Some(owned) => Cow::Owned(owned),
+                    None => Cow::Borrowed(&inner[..i]),
+                });
+            }
+            '\\' => {
+                let (_, escaped) = chars.next()?;
+                if !is_text(escaped) {
+                    return None;
+                }
+                owned
+                    .get_or_insert_with(|| inner[..i].to_owned())
+                    .push(escaped);
+            }
+            c if is_text(c) => {
+                if let Some(ref mut owned) = owned {
+                    owned.push(c);
+                }
+            }
+            _ => return None,
+        }
+    }
+
+    // Never saw the closing quote.
+    None
+}
+
+/// Check if `s` has to be sent as a quoted-string, as it isn't a token.
+pub fn needs_quoting(s: &str) -> bool {
+    s.is_empty() || !s.bytes().all(is_tchar)
+}
+
+/// Format `s` as a `token / quoted-string`, quoting and escaping it only if
+/// it isn't a valid token.
+///
+/// Control characters other than HTAB can't be represented, so `s` should
+/// not contain any.
+pub fn quote(s: &str) -> Cow<'_, str> {
+    if needs_quoting(s) {
+        Cow::Owned(quote_always(s))
+    } else {
+        Cow::Borrowed(s)
+    }
+}
+
+fn quote_always(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        if c == '"' || c == '\\' {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out.push('"');
+    out
+}
+
+/// Any character but the control characters, except for HTAB.
+fn is_text(c: char) -> bool {
+    c == '\t' || !c.is_control()
+}
+
+fn is_tchar(b: u8) -> bool {
+    match b {
+        b'!' | b'#' | b'$' | b'%' | b'&' | b'\'' | b'*' | b'+' | b'-' | b'.' | b'^' | b'_'
+        | b'`' | b'|' | b'~' => true,
+        _ => b.is_ascii_alphanumeric(),
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::borrow::Cow;
+
+    use super::{decode, encode, needs_quoting, quote, unquote};
+    use decode::from_value_with;
+    use {Header, HeaderName, HeaderValue, ParseMode, ToValues, Values};
+
+    struct Quoted(String);
+
+    impl Header for Quoted {
+        fn name() -> &'static HeaderName {
+            &::http::header::SERVER
+        }
+
+        fn decode(values: &mut Values) -> Option<Quoted> {
+            decode(values).map(Quoted)
+        }
+
+        fn encode(&self, values: &mut ToValues) {
+            values.append(encode(&self.0).unwrap());
+        }
+    }
+
+    fn check(value: &[u8], mode: ParseMode) -> Option<String> {
+        let value = HeaderValue::from_bytes(value).unwrap();
+        from_value_with::<Quoted>(&value, mode).ok().map(|quoted| quoted.0)
+    }
+
+    #[test]
+    fn strips_quotes() {
+        assert_eq!(check(b"\"urn:a#b\"", ParseMode::Strict).unwrap(), "urn:a#b");
+        assert_eq!(check(b" \"\" ", ParseMode::Strict).unwrap(), "");
+        assert_eq!(check(b"\"a \\\"b\\\" \\\\c\"", ParseMode::Strict).unwrap(), "a \"b\" \\c");
+        assert_eq!(check(b"\"caf\xc3\xa9\"", ParseMode::Strict).unwrap(), "caf\u{e9}");
+    }
+
+    #[test]
+    fn invalid() {
+        for &value in &[&b"\""[..], b"\"a", b"a\"", b"\"a\"b\"", b"\"a\\\"", b"\"\xff\""] {
+            assert_eq!(check(value, ParseMode::Strict), None, "{:?}", value);
+            assert_eq!(check(value, ParseMode::Lenient), None, "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn lenient_without_quotes() {
+        assert_eq!(check(b"urn:a", ParseMode::Strict), None);
+        assert_eq!(check(b" urn:a ", ParseMode::Lenient).unwrap(), "urn:a");
+    }
+
+    #[test]
+    fn encodes_escapes() {
+        assert_eq!(encode("a \"b\" \\c").unwrap(), "\"a \\\"b\\\" \\\\c\"");
+        assert_eq!(encode("").unwrap(), "\"\"");
+        assert_eq!(encode("a\r\nb"), None);
+    }
+
+    #[test]
+    fn unquote_only_allocates_for_escapes() {
+        assert!(matches!(unquote("token"), Some(Cow::Borrowed("token"))));
+        assert!(matches!(unquote("\"a b\""), Some(Cow::Borrowed("a b"))));
+        assert!(matches!(unquote("\"\""), Some(Cow::Borrowed(""))));
+        assert_eq!(unquote("\"a \\\"b\\\" \\\\c\"").unwrap(), "a \"b\" \\c");
+        assert!(matches!(unquote("\"\\a\""), Some(Cow::Owned(_))));
+    }
+
+    #[test]
+    fn unquote_allows_htab_and_obs_text() {
+        assert_eq!(unquote("\"a\tb\"").unwrap(), "a\tb");
+        assert_eq!(unquote("\"\\\tcafé\"").unwrap(), "\tcafé");
+    }
+
+    #[test]
+    fn unquote_invalid() {
+        assert_eq!(unquote("\""), None);
+        assert_eq!(unquote("\"abc"), None);
+        assert_eq!(unquote("\"abc\\\""), None);
+        assert_eq!(unquote("\"a\"b\""), None);
+        assert_eq!(unquote("\"a\" "), None);
+        assert_eq!(unquote("\"a\nb\""), None);
+        assert_eq!(unquote("\"a\\\x7fb\""), None);
+        assert_eq!(unquote("\"\0\""), None);
+    }
+
+    #[test]
+    fn pathological_escapes() {
+        // Runs of backslashes alternate between an escaped backslash and
+        // escaping the closing quote.
+        for n in 0..16 {
+            let s = format!("\"{}\"", "\\".repeat(n));
+            let unquoted = unquote(&s);
+            if n % 2 == 0 {
+                assert_eq!(unquoted.unwrap(), "\\".repeat(n / 2));
+            } else {
+                assert_eq!(unquoted, None);
+            }
+        }
+
+        for s in &["\"\\\"\\\"\"", "\"\\\\\\\"\"", "\"\\\"", "\"\\\\\"\"", "\"\u{0}\\\"\""] {
+            let unquoted = unquote(s);
+            if let Some(ref unquoted) = unquoted {
+                assert_eq!(unquote(&quote(unquoted)).as_deref(), Some(&**unquoted));
+            }
+        }
+        assert_eq!(unquote("\"\\\"\\\"\"").unwrap(), "\"\"");
+        assert_eq!(unquote("\"\\\\\\\"\"").unwrap(), "\\\"");
+        assert_eq!(unquote("\"\\\\\"\""), None);
+    }
+
+    #[test]
+    fn quote_only_non_tokens() {
+        assert!(!needs_quoting("gzip"));
+        assert!(needs_quoting(""));
+        assert!(needs_quoting("a b"));
+        assert!(needs_quoting("a,b"));
+
+        assert!(matches!(quote("gzip"), Cow::Borrowed("gzip")));
+        assert_eq!(quote(""), "\"\"");
+        assert_eq!(quote("say \"hi\" \\o/"), "\"say \\\"hi\\\" \\\\o/\"");
+    }
+
+    #[test]
+    fn round_trips() {
+        for s in &["", "token", "a b", "\"", "\\", "\\\"", "\"\\\"", "tab\there", "ünïcode, too"] {
+            assert_eq!(unquote(&quote(s)).as_deref(), Some(*s));
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use std::fmt;
+use std::str;
+
+use http::header::{AUTHORIZATION, COOKIE, PROXY_AUTHORIZATION, SET_COOKIE};
+use http::HeaderMap;
+
+use {HeaderName, HeaderValue};
+
+/// Formats a `HeaderMap` for logs, with the values of credentials replaced
+/// by `[redacted]`.
+///
+/// `Display` writes one `name: value` line per value. Values that aren't
+/// UTF-8 are written with their other bytes escaped, like `\xff`. `Debug`
+/// writes the map, as `HeaderMap` itself does.
+///
+/// `Authorization`, `Proxy-Authorization`, `Cookie`, `Set-Cookie` and
+/// `X-Api-Key` are always redacted, and `with_redacted` adds more.
+///
+/// Nothing is allocated while formatting.
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_core;
+/// # extern crate http;
+/// use headers_core::{HeaderMapExt, HeaderName};
+/// use http::HeaderMap;
+///
+/// # fn main() {
+/// let mut map = HeaderMap::new();
+/// map.insert("authorization", "Bearer secret".parse().unwrap());
+/// map.insert("x-session", "1234".parse().unwrap());
+/// map.insert("accept", "*/*".parse().unwrap());
+///
+/// assert_eq!(
+///     map.typed_debug().to_string(),
+///     "authorization: [redacted]\nx-session: 1234\naccept: */*",
+/// );
+///
+/// let session = [HeaderName::from_static("x-session")];
+/// assert_eq!(
+///     map.typed_debug().with_redacted(&session).to_string(),
+///     "authorization: [redacted]\nx-session: [redacted]\naccept: */*",
+/// );
+/// # }
+/// ```
+#[derive(Clone, Copy)]
+pub struct RedactedHeaders<'a> {
+    map: &'a HeaderMap,
+    extra: &'a [HeaderName],
+}
+
+impl<'a> RedactedHeaders<'a> {
+    /// Format `map`, redacting the built-in names.
+    pub fn new(map: &'a HeaderMap) -> RedactedHeaders<'a> {
+        RedactedHeaders { map, extra: &[] }
+    }
+
+    /// Redact the values of `names` too, replacing any given before.
+    pub fn with_redacted(self, names: &'a [HeaderName]) -> RedactedHeaders<'a> {
+        RedactedHeaders { extra: names, ..self }
+    }
+
+    /// Check if the values of `name` are redacted.
+    pub fn is_redacted(&self, name: &HeaderName) -> bool {
+        is_sensitive(name, self.extra)
+    }
+
+    fn entries(&self) -> impl Iterator<Item = (&'a HeaderName, Value<'a>)> + '_ {
+        self.map.iter().map(move |(name, value)| {
+            let value = if self.is_redacted(name) {
+                Value::Redacted
+            } else {
+                Value::Plain(value)
+            };
+            (name, value)
+        })
+    }
+}
+
+impl<'a> fmt::Display for RedactedHeaders<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, (name, value)) in self.entries().enumerate() {
+            if i > 0 {
+                f.write_str("\n")?;
+            }
+            write!(f, "{}: {}", name, value)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> fmt::Debug for RedactedHeaders<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.entries()).finish()
+    }
+}
+
+/// Check if `name` is one of the built-in credentials, or in `extra`.
+pub(crate) fn is_sensitive(name: &HeaderName, extra: &[HeaderName]) -> bool {
+    *name == AUTHORIZATION
+        || *name == PROXY_AUTHORIZATION
+        || *name == COOKIE
+        || *name == SET_COOKIE
+        || name.as_str() == "x-api-key"
+        || extra.contains(name)
+}
+
+enum Value<'a> {
+    Plain(&'a HeaderValue),
+    Redacted,
+}
+
+impl<'a> fmt::Display for Value<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Value::Plain(value) => match str::from_utf8(value.as_bytes()) {
+                Ok(s) => f.write_str(s),
+                Err(_) => fmt::Display::fmt(&value.as_bytes().escape_ascii(), f),
+            },
+            Value::Redacted => f.write_str("[redacted]"),
+        }
+    }
+}
+
+impl<'a> fmt::Debug for Value<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Value::Plain(value) => fmt::Debug::fmt(value, f),
+            Value::Redacted => f.write_str("[redacted]"),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use http::HeaderMap;
+
+    use {HeaderMapExt, HeaderName, HeaderValue};
+
+    fn map(pairs: &[(&'static str, &'static [u8])]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for &(name, value) in pairs {
+            map.append(name, HeaderValue::from_bytes(value).unwrap());
+        }
+        map
+    }
+
+    #[test]
+    fn redacts_credentials() {
+        let map = map(&[
+            ("authorization", b"Basic dXNlcjpwYXNz"),
+            ("proxy-authorization", b"Bearer abc"),
+            ("cookie", b"sid=1"),
+            ("set-cookie", b"sid=1; Secure"),
+            ("set-cookie", b"lang=en"),
+            ("x-api-key", b"k3y"),
+            ("host", b"example.com"),
+        ]);
+        assert_eq!(
+            map.typed_debug().to_string(),
+            "authorization: [redacted]\n\
+             proxy-authorization: [redacted]\n\
+             cookie: [redacted]\n\
+             set-cookie: [redacted]\n\
+             set-cookie: [redacted]\n\
+             x-api-key: [redacted]\n\
+             host: example.com",
+        );
+        assert_eq!(HeaderMap::new().typed_debug().to_string(), "");
+    }
+
+    #[test]
+    fn with_redacted() {
+        let map = map(&[("x-token", b"t"), ("x-other", b"o")]);
+        let extra = [HeaderName::from_static("x-token")];
+
+        let debug = map.typed_debug().with_redacted(&extra);
+        assert!(debug.is_redacted(&extra[0]));
+        assert!(debug.is_redacted(&::http::header::COOKIE));
+        assert!(!debug.is_redacted(&HeaderName::from_static("x-other")));
+        assert_eq!(debug.to_string(), "x-token: [redacted]\nx-other: o");
+        assert_eq!(format!("{:?}", debug), "{\"x-token\": [redacted], \"x-other\": \"o\"}");
+    }
+
+    #[test]
+    fn escapes_bytes_that_arent_utf8() {
+        let map = map(&[("x-name", b"caf\xc3\xa9"), ("x-latin1", b"caf\xe9 \"a\"")]);
+        assert_eq!(
+            map.typed_debug().to_string(),
+            "x-name: caf\u{e9}\nx-latin1: caf\\xe9 \\\"a\\\"",
+        );
+        assert_eq!(
+            format!("{:?}", map.typed_debug()),
+            "{\"x-name\": \"caf\\xc3\\xa9\", \"x-latin1\": \"caf\\xe9 \\\"a\\\"\"}",
+        );
+    }
+}
@@ -0,0 +1,90 @@
+//! Helpers for testing `Header` implementations.
+//!
+//! These decode and encode through a `HeaderMap`, just like
+//! `HeaderMapExt::typed_get` and `typed_insert`, so a decode that doesn't
+//! use every value fails here too.
+//!
+//! # Example
+//!
+// `static_header_name!` needs `std`.
+#![cfg_attr(feature = "std", doc = "```")]
+#![cfg_attr(not(feature = "std"), doc = "```ignore")]
+//! #[macro_use]
+//! extern crate headers_core;
+//!
+//! use headers_core::{Header, HeaderName, HeaderValue, ToValues, Values};
+//! use headers_core::test_helpers::{assert_roundtrip, decode_from_strs, encode_to_map};
+//!
+//! #[derive(Debug, PartialEq)]
+//! struct XApiVersion(u32);
+//!
+//! impl Header for XApiVersion {
+//!     fn name() -> &'static HeaderName {
+//!         static_header_name!("x-api-version")
+//!     }
+//!
+//!     fn decode(values: &mut Values) -> Option<Self> {
+//!         values.next()?.to_str().ok()?.parse().ok().map(XApiVersion)
+//!     }
+//!
+//!     fn encode(&self, values: &mut ToValues) {
+//!         values.append(HeaderValue::from(self.0));
+//!     }
+//! }
+//!
+//! # fn main() {
+//! assert_eq!(decode_from_strs::<XApiVersion>(&["2"]), Some(XApiVersion(2)));
+//! assert_eq!(decode_from_strs::<XApiVersion>(&["two"]), None);
+//! // Only the first value was used, so this fails.
+//! assert_eq!(decode_from_strs::<XApiVersion>(&["2", "3"]), None);
+//!
+//! assert_eq!(encode_to_map(XApiVersion(2))["x-api-version"], "2");
+//! assert_roundtrip(XApiVersion(7));
+//! # }
+//! ```
+
+use std::fmt::Debug;
+
+use http::{HeaderMap, HeaderValue};
+
+use {Header, HeaderMapExt, ParseMode};
+
+/// Decode a header from `values`, in order.
+///
+/// # Panics
+///
+/// If any of `values` isn't a valid `HeaderValue`.
+pub fn decode_from_strs<H: Header>(values: &[&str]) -> Option<H> {
+    decode_from_strs_with(values, ParseMode::default())
+}
+
+/// Like `decode_from_strs`, but decoding with the given `ParseMode`.
+pub fn decode_from_strs_with<H: Header>(values: &[&str], mode: ParseMode) -> Option<H> {
+    let mut map = HeaderMap::new();
+    for value in values {
+        let value = value
+            .parse::<HeaderValue>()
+            .unwrap_or_else(|_| panic!("invalid header value: {:?}", value));
+        map.append(H::name(), value);
+    }
+    map.typed_get_with(mode)
+}
+
+/// Encode a header into a new `HeaderMap`.
+pub fn encode_to_map<H: Header>(header: H) -> HeaderMap {
+    ::encode::encode_to_map(&header)
+}
+
+/// Assert that encoding `header` and decoding it again gives an equal
+/// value.
+pub fn assert_roundtrip<H: Header + PartialEq + Debug>(header: H) {
+    let map = ::encode::encode_to_map(&header);
+    let decoded = map.typed_get::<H>();
+    assert_eq!(
+        decoded.as_ref(),
+        Some(&header),
+        "{} encoded as {:?}",
+        H::name(),
+        map.get_all(H::name()),
+    );
+}
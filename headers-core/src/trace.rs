@@ -0,0 +1,165 @@
+use std::fmt::{self, Write};
+
+use http::header::GetAll;
+use http::HeaderMap;
+
+use redact::is_sensitive;
+use {DecodeError, HeaderName, HeaderValue};
+
+/// How much of the values is logged.
+const MAX_LOGGED: usize = 256;
+
+/// Log a header failing to decode, at debug level, with its values.
+///
+/// The values of credentials are redacted, as `RedactedHeaders` does.
+pub(crate) fn decode_failed(map: &HeaderMap, name: &HeaderName, error: DecodeError) {
+    debug!(
+        target: "headers",
+        "failed to decode {}: {} ({})",
+        name,
+        Rendered {
+            values: map.get_all(name),
+            redacted: is_sensitive(name, &[]),
+        },
+        error,
+    );
+}
+
+/// The values joined by `, `, with the bytes that aren't printable ASCII
+/// escaped, cut at `MAX_LOGGED` bytes.
+struct Rendered<'a> {
+    values: GetAll<'a, HeaderValue>,
+    redacted: bool,
+}
+
+impl<'a> fmt::Display for Rendered<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.redacted {
+            return f.write_str("[redacted]");
+        }
+        let mut written = 0;
+        for (i, value) in self.values.iter().enumerate() {
+            let sep = if i > 0 { ", " } else { "" };
+            for c in sep.chars().chain(value.as_bytes().escape_ascii().map(char::from)) {
+                if written == MAX_LOGGED {
+                    return f.write_str("...");
+                }
+                f.write_char(c)?;
+                written += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::cell::RefCell;
+    use std::sync::Once;
+
+    use http::HeaderMap;
+    use log::{self, Level, Log, Metadata, Record};
+
+    use {Header, HeaderMapExt, HeaderName, ToValues, Values};
+
+    thread_local! {
+        static LOGGED: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+
+    // Keeps what each test thread logs apart.
+    struct Capture;
+
+    impl Log for Capture {
+        fn enabled(&self, metadata: &Metadata) -> bool {
+            metadata.target() == "headers" && metadata.level() <= Level::Debug
+        }
+
+        fn log(&self, record: &Record) {
+            if self.enabled(record.metadata()) {
+                LOGGED.with(|logged| logged.borrow_mut().push(record.args().to_string()));
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    fn logged<F: FnOnce()>(f: F) -> Vec<String> {
+        static INIT: Once = Once::new();
+        INIT.call_once(|| {
+            log::set_logger(&Capture).unwrap();
+            log::set_max_level(log::LevelFilter::Debug);
+        });
+        LOGGED.with(|logged| logged.borrow_mut().clear());
+        f();
+        LOGGED.with(|logged| logged.borrow_mut().split_off(0))
+    }
+
+    #[derive(Debug)]
+    struct ContentLength(u64);
+
+    impl Header for ContentLength {
+        fn name() -> &'static HeaderName {
+            &::http::header::CONTENT_LENGTH
+        }
+
+        fn decode(values: &mut Values) -> Option<Self> {
+            values.next()?.to_str().ok()?.parse().ok().map(ContentLength)
+        }
+
+        fn encode(&self, values: &mut ToValues) {
+            values.append_fmt(self.0);
+        }
+    }
+
+    struct Authorization;
+
+    impl Header for Authorization {
+        fn name() -> &'static HeaderName {
+            &::http::header::AUTHORIZATION
+        }
+
+        fn decode(_: &mut Values) -> Option<Self> {
+            None
+        }
+
+        fn encode(&self, _: &mut ToValues) {}
+    }
+
+    #[test]
+    fn logs_invalid_values() {
+        let mut map = HeaderMap::new();
+        map.insert("content-length", "12a".parse().unwrap());
+        map.append("content-length", ::http::HeaderValue::from_bytes(b"\xff\"").unwrap());
+
+        let lines = logged(|| assert!(map.typed_get::<ContentLength>().is_none()));
+        assert_eq!(lines, ["failed to decode content-length: 12a, \\xff\\\" (invalid header value)"]);
+    }
+
+    #[test]
+    fn decoded_and_missing_are_not_logged() {
+        let mut map = HeaderMap::new();
+        assert!(logged(|| assert!(map.typed_get::<ContentLength>().is_none())).is_empty());
+
+        map.insert("content-length", "12".parse().unwrap());
+        assert!(logged(|| assert!(map.typed_get::<ContentLength>().is_some())).is_empty());
+    }
+
+    #[test]
+    fn truncates_long_values() {
+        let mut map = HeaderMap::new();
+        map.insert("content-length", "9".repeat(1000).parse().unwrap());
+
+        let lines = logged(|| assert!(map.typed_get::<ContentLength>().is_none()));
+        let expected = format!("failed to decode content-length: {}... (invalid header value)", "9".repeat(256));
+        assert_eq!(lines, [expected]);
+    }
+
+    #[test]
+    fn redacts_credentials() {
+        let mut map = HeaderMap::new();
+        map.insert("authorization", "Bearer secret".parse().unwrap());
+
+        let lines = logged(|| assert!(map.typed_get::<Authorization>().is_none()));
+        assert_eq!(lines, ["failed to decode authorization: [redacted] (invalid header value)"]);
+    }
+}
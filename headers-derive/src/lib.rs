@@ -1,3 +1,134 @@
+//! `#[derive(Header)]`
+//!
+//! # Structs
+//!
+//! A struct with a single field decodes that field with `TryFromValues`,
+//! and encodes it with `From<&Field> for HeaderValue`. With
+//! `#[header(csv)]`, the field is instead collected from, and joined into,
+//! a comma-separated list. With `#[header(quoted)]`, the header is a single
+//! quoted-string, and the field is its unquoted `String`, which is checked
+//! by `Header::validate` before encoding.
+//!
+//! ```
+//! extern crate headers_core;
+//! #[macro_use]
+//! extern crate headers_derive;
+//!
+//! use headers_core::header::HeaderMap;
+//! use headers_core::HeaderMapExt;
+//!
+//! #[derive(Debug, PartialEq, Header)]
+//! #[header(name = "x-signature", quoted)]
+//! struct Signature(String);
+//!
+//! # fn main() {
+//! let mut map = HeaderMap::new();
+//! map.typed_insert(Signature("v1 \"abc\"".to_owned()));
+//! assert_eq!(map["x-signature"], "\"v1 \\\"abc\\\"\"");
+//! assert_eq!(map.typed_get(), Some(Signature("v1 \"abc\"".to_owned())));
+//! # }
+//! ```
+//!
+//! # Enums
+//!
+//! A fieldless enum is a header that is one of a set of tokens. Each variant
+//! names its token with `#[header(value = "..")]`, which is what it is
+//! encoded as. Decoding trims the value and compares it to each token
+//! case-insensitively. A variant can also accept other spellings with
+//! `#[header(alias = "..")]`, which are never encoded.
+//!
+//! By default the header has a single token. With `#[header(list)]` on the
+//! type, it's a comma-separated list, and the last token that is known
+//! wins, as with `Referrer-Policy`.
+//!
+//! One variant may be marked `#[header(other)]`, with a single `HeaderValue`
+//! field. It holds a value that didn't match any token, and is encoded back
+//! as is. Without it, an unknown token fails to decode.
+//!
+//! An enum also gets `TryFromValues` and `From<&Enum> for HeaderValue`, so
+//! it can be the field of a derived struct.
+//!
+//! ```
+//! extern crate headers_core;
+//! #[macro_use]
+//! extern crate headers_derive;
+//!
+//! use headers_core::HeaderValue;
+//! use headers_core::header::HeaderMap;
+//! use headers_core::HeaderMapExt;
+//!
+//! #[derive(Debug, PartialEq, Header)]
+//! #[header(name = "cross-origin-resource-policy")]
+//! enum Corp {
+//!     #[header(value = "same-site")]
+//!     SameSite,
+//!     #[header(value = "same-origin")]
+//!     SameOrigin,
+//!     #[header(other)]
+//!     Other(HeaderValue),
+//! }
+//!
+//! # fn main() {
+//! let mut map = HeaderMap::new();
+//! map.insert("cross-origin-resource-policy", HeaderValue::from_static(" Same-Origin"));
+//! assert_eq!(map.typed_get(), Some(Corp::SameOrigin));
+//!
+//! map.insert("cross-origin-resource-policy", HeaderValue::from_static("cross-origin"));
+//! assert_eq!(map.typed_get(), Some(Corp::Other(HeaderValue::from_static("cross-origin"))));
+//!
+//! map.typed_insert(Corp::SameSite);
+//! assert_eq!(map["cross-origin-resource-policy"], "same-site");
+//! # }
+//! ```
+//!
+//! Variants with fields, other than the `other` one, are rejected:
+//!
+//! ```compile_fail
+//! extern crate headers_core;
+//! #[macro_use]
+//! extern crate headers_derive;
+//!
+//! #[derive(Header)]
+//! #[header(name = "x-mode")]
+//! enum Mode {
+//!     #[header(value = "fast")]
+//!     Fast(u8),
+//! }
+//! # fn main() {}
+//! ```
+//!
+//! ```compile_fail
+//! extern crate headers_core;
+//! #[macro_use]
+//! extern crate headers_derive;
+//!
+//! #[derive(Header)]
+//! #[header(name = "x-mode")]
+//! enum Mode {
+//!     #[header(value = "fast")]
+//!     Fast { level: u8 },
+//! }
+//! # fn main() {}
+//! ```
+//!
+//! As is an `other` variant that doesn't hold exactly one value:
+//!
+//! ```compile_fail
+//! extern crate headers_core;
+//! #[macro_use]
+//! extern crate headers_derive;
+//!
+//! #[derive(Header)]
+//! #[header(name = "x-mode")]
+//! enum Mode {
+//!     #[header(value = "fast")]
+//!     Fast,
+//!     #[header(other)]
+//!     Other,
+//! }
+//! # fn main() {}
+//! ```
+
 extern crate proc_macro;
 extern crate proc_macro2;
 #[macro_use]
@@ -6,7 +137,7 @@ extern crate syn;
 
 use proc_macro::TokenStream;
 use proc_macro2::Span;
-use syn::{Data, Fields, Ident, Lit, Meta, NestedMeta};
+use syn::{Attribute, Data, DataEnum, DataStruct, Fields, Ident, Lit, Meta, NestedMeta};
 
 #[proc_macro_derive(Header, attributes(header))]
 pub fn derive_header(input: TokenStream) -> TokenStream {
@@ -15,27 +146,63 @@ pub fn derive_header(input: TokenStream) -> TokenStream {
 }
 
 fn impl_header(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
-    let fns = match impl_fns(ast) {
-        Ok(fns) => fns,
+    let impls = match impl_all(ast) {
+        Ok(impls) => impls,
         Err(msg) => {
             return quote! {
                 compile_error!(#msg);
-            }.into();
+            };
+        }
+    };
+
+    quote! {
+        const _: () = {
+            extern crate headers_core as __hc;
+            #impls
+        };
+    }
+}
+
+fn impl_all(ast: &syn::DeriveInput) -> Result<proc_macro2::TokenStream, String> {
+    let attrs = type_attrs(&ast.attrs)?;
+
+    let (fns, extra) = match ast.data {
+        Data::Struct(ref st) => (impl_struct_fns(ast, st, &attrs)?, quote!()),
+        Data::Enum(ref en) => impl_enum(ast, en, &attrs)?,
+        Data::Union(_) => {
+            return Err("derive(Header) only works on structs and enums".into())
         }
     };
 
     let decode = fns.decode;
     let encode = fns.encode;
+    let validate = fns.validate;
 
     let ty = &ast.ident;
-    let hname = fns.name.unwrap_or_else(|| {
-        to_header_name(&ty.to_string())
-    });
-    let hname_ident = Ident::new(&hname, Span::call_site());
-    let dummy_const = Ident::new(&format!("_IMPL_HEADER_FOR_{}", hname), Span::call_site());
-    let impl_block = quote! {
+    let name = match attrs.name {
+        Some(Name::Const(ref name)) => {
+            let hname_ident = Ident::new(name, Span::call_site());
+            quote! {
+                &__hc::header::#hname_ident
+            }
+        },
+        Some(Name::Str(ref name)) => quote! {
+            __hc::static_header_name!(#name)
+        },
+        None => {
+            let hname_ident = Ident::new(&to_header_name(&ty.to_string()), Span::call_site());
+            quote! {
+                &__hc::header::#hname_ident
+            }
+        },
+    };
+
+    Ok(quote! {
         impl __hc::Header for #ty {
-            const NAME: &'static __hc::HeaderName = &__hc::header::#hname_ident;
+            fn name() -> &'static __hc::HeaderName {
+                #name
+            }
+
             fn decode(values: &mut __hc::Values) -> Option<Self> {
                 #decode
             }
@@ -43,39 +210,88 @@ fn impl_header(ast: &syn::DeriveInput) -> proc_macro2::TokenStream {
             fn encode(&self, values: &mut __hc::ToValues) {
                 #encode
             }
+
+            #validate
         }
-    };
 
-    quote! {
-        const #dummy_const: () = {
-            extern crate headers_core as __hc;
-            #impl_block
-        };
-    }
+        #extra
+    })
 }
 
 struct Fns {
     encode: proc_macro2::TokenStream,
     decode: proc_macro2::TokenStream,
-    name: Option<String>,
+    validate: Option<proc_macro2::TokenStream>,
 }
 
-fn impl_fns(ast: &syn::DeriveInput) -> Result<Fns, String> {
-    let ty = &ast.ident;
+enum Name {
+    /// `name_const = "X_FOO"`, a constant of `http::header`.
+    Const(String),
+    /// `name = "x-foo"`.
+    Str(String),
+}
 
-    // Only structs are allowed...
-    let st = match ast.data {
-        Data::Struct(ref st) => st,
-        _ => {
-            return Err("derive(Header) only works on structs".into())
-        }
+struct TypeAttrs {
+    csv: bool,
+    list: bool,
+    quoted: bool,
+    name: Option<Name>,
+}
+
+/// Check attributes for `#[header(...)]` that may influence the code
+/// that is generated...
+fn type_attrs(attrs: &[Attribute]) -> Result<TypeAttrs, String> {
+    let mut out = TypeAttrs {
+        csv: false,
+        list: false,
+        quoted: false,
+        name: None,
     };
+    for meta in header_metas(attrs)? {
+        match meta {
+            NestedMeta::Meta(Meta::Word(ref word)) if word == "csv" => {
+                out.csv = true;
+            },
+
+            NestedMeta::Meta(Meta::Word(ref word)) if word == "list" => {
+                out.list = true;
+            },
+
+            NestedMeta::Meta(Meta::Word(ref word)) if word == "quoted" => {
+                out.quoted = true;
+            },
+
+            NestedMeta::Meta(Meta::NameValue(ref kv)) if kv.ident == "name_const" || kv.ident == "name" => {
+                if out.name.is_some() {
+                    return Err("repeated name option in #[header] attribute".into());
+                }
+                let value = match kv.lit {
+                    Lit::Str(ref s) => s.value(),
+                    _ => {
+                        return Err("illegal literal in #[header(name = ..)] attribute".into());
+                    }
+                };
+                out.name = Some(if kv.ident == "name" {
+                    if !is_header_name(&value) {
+                        return Err("#[header(name = ..)] must be a lowercase header name".into());
+                    }
+                    Name::Str(value)
+                } else {
+                    Name::Const(value)
+                });
+            }
+            _ => {
+                return Err("illegal option in #[header(..)] attribute".into())
+            }
+        }
+    }
+    Ok(out)
+}
 
-    // Check attributes for `#[header(...)]` that may influence the code
-    // that is generated...
-    let mut is_csv = false;
-    let mut name = None;
-    for attr in &ast.attrs {
+/// The options of every `#[header(..)]` in `attrs`.
+fn header_metas(attrs: &[Attribute]) -> Result<Vec<NestedMeta>, String> {
+    let mut metas = Vec::new();
+    for attr in attrs {
         if attr.path.segments.len() != 1 {
             continue;
         }
@@ -85,30 +301,7 @@ fn impl_fns(ast: &syn::DeriveInput) -> Result<Fns, String> {
 
         match attr.interpret_meta() {
             Some(Meta::List(list)) => {
-                for meta in &list.nested {
-                    match meta {
-                        NestedMeta::Meta(Meta::Word(ref word)) if word == "csv" => {
-                            is_csv = true;
-                        },
-
-                        NestedMeta::Meta(Meta::NameValue(ref kv)) if kv.ident == "name_const" => {
-                            if name.is_some() {
-                                return Err("repeated 'name_const' option in #[header] attribute".into());
-                            }
-                            name = match kv.lit {
-                                Lit::Str(ref s) => Some(s.value()),
-                                _ => {
-                                    return Err("illegal literal in #[header(name_const = ..)] attribute".into());
-                                }
-                            };
-                        }
-                        _ => {
-                            return Err("illegal option in #[header(..)] attribute".into())
-                        }
-
-                    }
-                }
-
+                metas.extend(list.nested);
             },
             Some(Meta::NameValue(_)) => {
                 return Err("illegal #[header = ..] attribute".into())
@@ -122,11 +315,27 @@ fn impl_fns(ast: &syn::DeriveInput) -> Result<Fns, String> {
             }
         }
     }
+    Ok(metas)
+}
+
+fn impl_struct_fns(ast: &syn::DeriveInput, st: &DataStruct, attrs: &TypeAttrs) -> Result<Fns, String> {
+    let ty = &ast.ident;
+    let is_csv = attrs.csv;
+    if attrs.list {
+        return Err("#[header(list)] only works on enums, use #[header(csv)]".into());
+    }
+    if is_csv && attrs.quoted {
+        return Err("#[header(csv)] and #[header(quoted)] can't be used together".into());
+    }
 
     let decode_res = if is_csv {
         quote! {
             __hc::decode::from_comma_delimited(values)
         }
+    } else if attrs.quoted {
+        quote! {
+            __hc::quoted::decode(values).map(::std::convert::From::from)
+        }
     } else {
         quote! {
             __hc::decode::TryFromValues::try_from_values(values)
@@ -173,35 +382,32 @@ fn impl_fns(ast: &syn::DeriveInput) -> Result<Fns, String> {
         }
     };
 
+    let field = if let Value::Named(field) = encode_name {
+        quote! {
+            (&self.#field)
+        }
+    } else {
+        quote! {
+            (&self.0)
+        }
+    };
+    let mut validate = None;
     let encode = if is_csv {
-        let field = if let Value::Named(field) = encode_name {
-            quote! {
-                (&(self.0).#field)
-            }
-        } else {
-            quote! {
-                (&(self.0).0)
-            }
-        };
         quote! {
-            struct __HeaderFmt<'hfmt>(&'hfmt #ty);
-            impl<'hfmt> ::std::fmt::Display for __HeaderFmt<'hfmt> {
-                fn fmt(&self, hfmt: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
-                    __hc::encode::comma_delimited(hfmt, (#field).into_iter())
-                }
+            __hc::encode::encode_comma_joined(values, (#field).into_iter());
+        }
+    } else if attrs.quoted {
+        validate = Some(quote! {
+            fn validate(&self) -> Result<(), __hc::InvalidHeader> {
+                __hc::quoted::validate(::std::convert::AsRef::<str>::as_ref(#field))
             }
-            values.append_fmt(&__HeaderFmt(self));
+        });
+        quote! {
+            let value = __hc::quoted::encode(::std::convert::AsRef::<str>::as_ref(#field))
+                .expect("quoted header was validated");
+            values.append(value);
         }
     } else {
-        let field = if let Value::Named(field) = encode_name {
-            quote! {
-                (&self.#field)
-            }
-        } else {
-            quote! {
-                (&self.0)
-            }
-        };
         quote! {
             values.append((#field).into());
         }
@@ -210,10 +416,213 @@ fn impl_fns(ast: &syn::DeriveInput) -> Result<Fns, String> {
     Ok(Fns {
         decode,
         encode,
-        name,
+        validate,
     })
 }
 
+struct Variant<'a> {
+    ident: &'a Ident,
+    value: String,
+    aliases: Vec<String>,
+}
+
+fn impl_enum(
+    ast: &syn::DeriveInput,
+    en: &DataEnum,
+    attrs: &TypeAttrs,
+) -> Result<(Fns, proc_macro2::TokenStream), String> {
+    let ty = &ast.ident;
+    if attrs.csv {
+        return Err("#[header(csv)] only works on structs, use #[header(list)]".into());
+    }
+    if attrs.quoted {
+        return Err("#[header(quoted)] only works on structs".into());
+    }
+
+    let mut variants = Vec::new();
+    let mut other = None;
+    for variant in &en.variants {
+        let mut value = None;
+        let mut aliases = Vec::new();
+        let mut is_other = false;
+        for meta in header_metas(&variant.attrs)? {
+            match meta {
+                NestedMeta::Meta(Meta::Word(ref word)) if word == "other" => {
+                    is_other = true;
+                },
+                NestedMeta::Meta(Meta::NameValue(ref kv)) if kv.ident == "value" || kv.ident == "alias" => {
+                    let token = match kv.lit {
+                        Lit::Str(ref s) => s.value(),
+                        _ => {
+                            return Err("illegal literal in #[header(value = ..)] attribute".into());
+                        }
+                    };
+                    if !is_token(&token) {
+                        return Err(format!("{:?} isn't a valid header token", token));
+                    }
+                    if kv.ident == "alias" {
+                        aliases.push(token);
+                    } else if value.is_some() {
+                        return Err("repeated 'value' option in #[header] attribute".into());
+                    } else {
+                        value = Some(token);
+                    }
+                },
+                _ => {
+                    return Err("illegal option in #[header(..)] attribute".into())
+                }
+            }
+        }
+
+        if is_other {
+            if value.is_some() || !aliases.is_empty() {
+                return Err("#[header(other)] variants can't have a value".into());
+            }
+            if other.is_some() {
+                return Err("derive(Header) only supports one #[header(other)] variant".into());
+            }
+            if attrs.list {
+                return Err("#[header(other)] can't be used with #[header(list)]".into());
+            }
+            match variant.fields {
+                Fields::Unnamed(ref fields) if fields.unnamed.len() == 1 => (),
+                _ => {
+                    return Err("#[header(other)] variants must have a single HeaderValue field".into())
+                }
+            }
+            other = Some(&variant.ident);
+            continue;
+        }
+
+        match variant.fields {
+            Fields::Unit => (),
+            _ => {
+                return Err("derive(Header) doesn't support enum variants with fields, except for #[header(other)]".into())
+            }
+        }
+        let value = value.ok_or_else(|| {
+            format!("missing #[header(value = ..)] on variant {}", variant.ident)
+        })?;
+        variants.push(Variant {
+            ident: &variant.ident,
+            value,
+            aliases,
+        });
+    }
+
+    if variants.is_empty() {
+        return Err("derive(Header) needs at least one enum variant with a value".into());
+    }
+
+    let matches = variants.iter().map(|variant| {
+        let ident = variant.ident;
+        let tokens = ::std::iter::once(&variant.value).chain(variant.aliases.iter());
+        let tokens = tokens.map(|token| {
+            let lower = proc_macro2::Literal::byte_string(token.to_ascii_lowercase().as_bytes());
+            quote!(token.eq_ignore_ascii_case(#lower))
+        });
+        quote! {
+            if #(#tokens)||* {
+                return Some(#ty::#ident);
+            }
+        }
+    });
+
+    let try_from_values = if attrs.list {
+        quote! {
+            // The last known token wins.
+            values.skip_exhaustive_iter_check();
+            for value in values.rev() {
+                for token in value.as_bytes().rsplit(|&b| b == b',') {
+                    if let Some(found) = from_token(trim(token)) {
+                        return Some(found);
+                    }
+                }
+            }
+            None
+        }
+    } else {
+        let unknown = match other {
+            Some(other) => quote! {
+                if trim(value.as_bytes()).is_empty() {
+                    None
+                } else {
+                    Some(#ty::#other(value.clone()))
+                }
+            },
+            None => quote!(None),
+        };
+        quote! {
+            let value = values.next()?;
+            from_token(trim(value.as_bytes())).or_else(|| #unknown)
+        }
+    };
+
+    let encodes = variants.iter().map(|variant| {
+        let ident = variant.ident;
+        let value = &variant.value;
+        quote! {
+            #ty::#ident => __hc::HeaderValue::from_static(#value),
+        }
+    });
+    let encode_other = other.map(|other| quote! {
+        #ty::#other(ref value) => value.clone(),
+    });
+
+    let extra = quote! {
+        impl __hc::decode::TryFromValues for #ty {
+            fn try_from_values(values: &mut __hc::Values) -> Option<Self> {
+                fn trim(token: &[u8]) -> &[u8] {
+                    let ows = |b: &u8| *b != b' ' && *b != b'\t';
+                    let start = token.iter().position(&ows).unwrap_or(token.len());
+                    let end = token.iter().rposition(&ows).map_or(start, |i| i + 1);
+                    &token[start..end]
+                }
+
+                fn from_token(token: &[u8]) -> Option<#ty> {
+                    #(#matches)*
+                    None
+                }
+
+                #try_from_values
+            }
+        }
+
+        impl<'a> From<&'a #ty> for __hc::HeaderValue {
+            fn from(value: &'a #ty) -> __hc::HeaderValue {
+                match *value {
+                    #(#encodes)*
+                    #encode_other
+                }
+            }
+        }
+    };
+
+    let fns = Fns {
+        decode: quote! {
+            __hc::decode::TryFromValues::try_from_values(values)
+        },
+        encode: quote! {
+            values.append(self.into());
+        },
+        validate: None,
+    };
+
+    Ok((fns, extra))
+}
+
+/// A token that can be used as-is in a list of values.
+fn is_token(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b > b' ' && b < 0x7f && b != b',')
+}
+
+fn is_header_name(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| matches!(b,
+        b'a'..=b'z' | b'0'..=b'9' |
+        b'!' | b'#' | b'$' | b'%' | b'&' | b'\'' | b'*' | b'+' | b'-' | b'.' | b'^' | b'_' | b'`' | b'|' | b'~'
+    ))
+}
+
 fn to_header_name(ty_name: &str) -> String {
     let mut out = String::new();
     let mut first = true;
@@ -235,4 +644,3 @@ enum Value {
     Named(Ident),
     Unnamed,
 }
-
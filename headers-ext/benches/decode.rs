@@ -0,0 +1,285 @@
+//! Decodes typical request and response headers, reporting the time and
+//! allocations it takes.
+//!
+//! The hottest headers are also parsed by hand, the way a server without
+//! typed headers would, to compare with.
+//!
+//! Run with `cargo bench -p headers-ext --bench decode`.
+
+extern crate headers_ext as headers;
+extern crate mime;
+extern crate time;
+
+use headers::{HeaderMap, HeaderMapExt, HeaderValue};
+
+mod support;
+
+fn bench(name: &str, map: &HeaderMap, f: impl Fn(&HeaderMap)) {
+    support::run(name, || f(map));
+}
+
+fn main() {
+    let mut preflight = HeaderMap::new();
+    preflight.insert("access-control-request-method", HeaderValue::from_static("PUT"));
+    preflight.insert(
+        "access-control-request-headers",
+        HeaderValue::from_static("content-type, x-requested-with"),
+    );
+    bench("cors preflight", &preflight, |map| {
+        assert!(map.typed_get::<headers::AccessControlRequestMethod>().is_some());
+        assert!(map.typed_get::<headers::AccessControlRequestHeaders>().is_some());
+    });
+
+    let mut cookies = HeaderMap::new();
+    cookies.append("set-cookie", HeaderValue::from_static("id=a3fWa; Max-Age=2592000"));
+    cookies.append("set-cookie", HeaderValue::from_static("lang=en-US; Path=/"));
+    bench("set-cookie", &cookies, |map| {
+        assert!(map.typed_get::<headers::SetCookie>().is_some());
+    });
+
+    let mut cookie = HeaderMap::new();
+    let pairs = (0..50).map(|i| format!("cookie{}=value{}", i, i)).collect::<Vec<_>>();
+    cookie.insert("cookie", HeaderValue::from_str(&pairs.join("; ")).unwrap());
+    bench("cookie get (50)", &cookie, |map| {
+        let cookie = map.typed_get::<headers::Cookie>().unwrap();
+        assert_eq!(cookie.get("cookie42"), Some("value42"));
+    });
+    bench("cookie get_raw (50)", &cookie, |map| {
+        assert_eq!(headers::Cookie::get_raw(&map["cookie"], "cookie42"), Some("value42"));
+    });
+
+    bench("cookie view get (50)", &cookie, |map| {
+        let cookie = map.typed_get_borrowed::<headers::CookieView>().unwrap();
+        assert_eq!(cookie.get("cookie42"), Some("value42"));
+    });
+
+    let mut request = HeaderMap::new();
+    request.insert(
+        "user-agent",
+        HeaderValue::from_str("Mozilla/5.0 (X11; Linux x86_64; rv:125.0) Gecko/20100101 Firefox/125.0").unwrap(),
+    );
+    request.insert("referer", HeaderValue::from_str("https://www.example.com/search?q=headers").unwrap());
+    bench("user-agent, referer", &request, |map| {
+        assert!(map.typed_get::<headers::UserAgent>().is_some());
+        assert!(map.typed_get::<headers::Referer>().is_some());
+    });
+    bench("user-agent, referer views", &request, |map| {
+        assert!(map.typed_get_borrowed::<headers::UserAgentView>().is_some());
+        assert!(map.typed_get_borrowed::<headers::RefererView>().is_some());
+    });
+
+    let mut numbers = HeaderMap::new();
+    numbers.insert("content-length", HeaderValue::from_static("1048576"));
+    numbers.insert("age", HeaderValue::from_static("3600"));
+    numbers.insert("access-control-max-age", HeaderValue::from_static("86400"));
+    bench("content-length", &numbers, |map| {
+        assert!(map.typed_get::<headers::ContentLength>().is_some());
+    });
+    bench("age", &numbers, |map| {
+        assert!(map.typed_get::<headers::Age>().is_some());
+    });
+    bench("access-control-max-age", &numbers, |map| {
+        assert!(map.typed_get::<headers::AccessControlMaxAge>().is_some());
+    });
+
+    hot();
+    browser_request();
+}
+
+fn hot() {
+    let map = support::hot();
+    let codings = [headers::ContentCoding::Br, headers::ContentCoding::Gzip];
+
+    // Typed, it's benched with the other numbers above.
+    bench("content-length by hand", &map, |map| {
+        assert!(by_hand::content_length(map).is_some());
+    });
+
+    bench("content-type", &map, |map| {
+        assert!(map.typed_get::<headers::ContentType>().is_some());
+    });
+    bench("content-type by hand", &map, |map| {
+        assert!(by_hand::content_type(map).is_some());
+    });
+
+    bench("date", &map, |map| {
+        assert!(map.typed_get::<headers::Date>().is_some());
+    });
+    bench("date by hand", &map, |map| {
+        assert!(by_hand::date(map).is_some());
+    });
+
+    bench("cache-control max-age", &map, |map| {
+        let cc = map.typed_get::<headers::CacheControl>().unwrap();
+        assert!(cc.max_age().is_some());
+    });
+    bench("cache-control max-age by hand", &map, |map| {
+        assert!(by_hand::max_age(map).is_some());
+    });
+
+    // There's no typed `Accept-Encoding`, `negotiate` reads it directly.
+    bench("accept-encoding br", &map, |map| {
+        assert_eq!(headers::negotiate::encoding(map, &codings), headers::ContentCoding::Br);
+    });
+    bench("accept-encoding br by hand", &map, |map| {
+        assert!(by_hand::accepts(map, "br"));
+    });
+
+    bench("cookie get", &map, |map| {
+        let cookie = map.typed_get::<headers::Cookie>().unwrap();
+        assert!(cookie.get("session").is_some());
+    });
+    bench("cookie view get", &map, |map| {
+        let cookie = map.typed_get_borrowed::<headers::CookieView>().unwrap();
+        assert!(cookie.get("session").is_some());
+    });
+    bench("cookie get by hand", &map, |map| {
+        assert!(by_hand::cookie(map, "session").is_some());
+    });
+
+    bench("host", &map, |map| {
+        let host = map.typed_get::<headers::Host>().unwrap();
+        assert_eq!(host.hostname(), "www.example.com");
+    });
+    bench("host by hand", &map, |map| {
+        assert_eq!(by_hand::hostname(map), Some("www.example.com"));
+    });
+
+    bench("connection keep-alive", &map, |map| {
+        let connection = map.typed_get::<headers::Connection>().unwrap();
+        assert!(connection.contains("keep-alive"));
+    });
+    bench("connection keep-alive by hand", &map, |map| {
+        assert!(by_hand::has_option(map, "keep-alive"));
+    });
+
+    bench("transfer-encoding chunked", &map, |map| {
+        let te = map.typed_get::<headers::TransferEncoding>().unwrap();
+        assert!(te.is_chunked());
+    });
+    bench("transfer-encoding by hand", &map, |map| {
+        assert!(by_hand::is_chunked(map));
+    });
+
+    bench("etag", &map, |map| {
+        assert!(map.typed_get::<headers::ETag>().is_some());
+    });
+    bench("etag by hand", &map, |map| {
+        assert!(by_hand::etag(map).is_some());
+    });
+}
+
+// What a server handling a page load would look at.
+fn browser_request() {
+    let map = support::browser_request();
+    let types = [mime::TEXT_HTML, mime::APPLICATION_JSON];
+    let codings = [headers::ContentCoding::Br, headers::ContentCoding::Gzip];
+
+    bench("browser request", &map, |map| {
+        assert!(map.typed_get::<headers::Host>().is_some());
+        assert!(map.typed_get::<headers::Connection>().is_some());
+        assert!(map.typed_get::<headers::CacheControl>().is_some());
+        assert!(map.typed_get::<headers::UserAgent>().is_some());
+        assert!(map.typed_get::<headers::Referer>().is_some());
+        assert!(map.typed_get::<headers::SecChUa>().is_some());
+        assert!(map.typed_get::<headers::SecChUaMobile>().is_some());
+        assert!(map.typed_get::<headers::SecChUaPlatform>().is_some());
+        assert!(map.typed_get::<headers::Cookie>().is_some());
+        assert!(map.typed_get::<headers::IfNoneMatch>().is_some());
+        assert!(map.typed_get::<headers::IfModifiedSince>().is_some());
+        assert!(headers::negotiate::content_type(map, &types).is_some());
+        assert_eq!(headers::negotiate::encoding(map, &codings), headers::ContentCoding::Br);
+        assert!(headers::negotiate::language(map, &["en", "fr"]).is_some());
+    });
+    bench("browser request validate", &map, |map| {
+        assert!(headers::validate::scan(map).is_empty());
+    });
+}
+
+/// Parsing the hot headers without typed headers, checking about as much.
+mod by_hand {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use headers::HeaderMap;
+    use mime::Mime;
+    use time;
+
+    fn get<'a>(map: &'a HeaderMap, name: &str) -> Option<&'a str> {
+        map.get(name)?.to_str().ok()
+    }
+
+    fn items<'a>(map: &'a HeaderMap, name: &str) -> impl Iterator<Item = &'a str> {
+        map.get_all(name)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+    }
+
+    pub fn content_length(map: &HeaderMap) -> Option<u64> {
+        get(map, "content-length")?.parse().ok()
+    }
+
+    pub fn content_type(map: &HeaderMap) -> Option<Mime> {
+        get(map, "content-type")?.parse().ok()
+    }
+
+    pub fn date(map: &HeaderMap) -> Option<SystemTime> {
+        let tm = time::strptime(get(map, "date")?, "%a, %d %b %Y %T GMT").ok()?;
+        let secs = tm.to_timespec().sec;
+        Some(UNIX_EPOCH + Duration::from_secs(secs as u64))
+    }
+
+    pub fn max_age(map: &HeaderMap) -> Option<u64> {
+        items(map, "cache-control")
+            .find_map(|item| item.strip_prefix("max-age="))?
+            .parse()
+            .ok()
+    }
+
+    pub fn accepts(map: &HeaderMap, coding: &str) -> bool {
+        items(map, "accept-encoding").any(|item| {
+            let mut parts = item.split(';').map(str::trim);
+            let name = parts.next().unwrap_or("");
+            let q = parts
+                .find_map(|param| param.strip_prefix("q="))
+                .and_then(|q| q.parse::<f32>().ok())
+                .unwrap_or(1.0);
+            name.eq_ignore_ascii_case(coding) && q > 0.0
+        })
+    }
+
+    pub fn cookie<'a>(map: &'a HeaderMap, name: &str) -> Option<&'a str> {
+        get(map, "cookie")?
+            .split(';')
+            .filter_map(|pair| pair.trim().split_once('='))
+            .find(|&(key, _)| key == name)
+            .map(|(_, value)| value)
+    }
+
+    pub fn hostname(map: &HeaderMap) -> Option<&str> {
+        let host = get(map, "host")?;
+        Some(host.rsplit_once(':').map_or(host, |(name, _)| name))
+    }
+
+    pub fn has_option(map: &HeaderMap, option: &str) -> bool {
+        items(map, "connection").any(|item| item.eq_ignore_ascii_case(option))
+    }
+
+    pub fn is_chunked(map: &HeaderMap) -> bool {
+        items(map, "transfer-encoding")
+            .last()
+            .is_some_and(|coding| coding.eq_ignore_ascii_case("chunked"))
+    }
+
+    pub fn etag(map: &HeaderMap) -> Option<&str> {
+        let tag = get(map, "etag")?;
+        let opaque = tag.strip_prefix("W/").unwrap_or(tag);
+        if opaque.len() >= 2 && opaque.starts_with('"') && opaque.ends_with('"') {
+            Some(tag)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,88 @@
+//! Encodes a typical bundle of response headers, reporting the time and
+//! allocations it takes.
+//!
+//! Run with `cargo bench -p headers-ext --bench encode`.
+
+extern crate headers_ext as headers;
+
+use std::time::{Duration, SystemTime};
+
+use headers::{HeaderMap, HeaderMapExt};
+
+mod support;
+
+fn bench(name: &str, f: impl Fn(&mut HeaderMap)) {
+    let mut map = HeaderMap::with_capacity(32);
+    support::run(name, || {
+        map.clear();
+        f(&mut map);
+    });
+}
+
+fn main() {
+    bench("security headers", |map| {
+        map.typed_insert(headers::ReferrerPolicy::STRICT_ORIGIN_WHEN_CROSS_ORIGIN);
+        map.typed_insert(headers::CacheControl::new().with_no_store());
+        map.typed_insert(headers::Pragma::no_cache());
+        map.typed_insert(headers::AccessControlAllowOrigin::ANY);
+        map.typed_insert(headers::AccessControlAllowCredentials);
+        map.typed_insert(headers::Vary::any());
+    });
+
+    // These should all be 0 allocs/iter.
+    bench("static constants", |map| {
+        map.typed_insert(headers::ContentType::JSON.clone());
+        map.typed_insert(headers::CacheControl::NO_STORE.clone());
+        map.typed_insert(headers::XContentTypeOptions::NOSNIFF);
+        map.typed_insert(headers::Connection::close());
+    });
+
+    bench("content-length", |map| {
+        map.typed_insert(headers::ContentLength(1_048_576));
+    });
+
+    let now = SystemTime::now();
+    bench("date", |map| {
+        map.typed_insert(headers::Date::from(now));
+    });
+
+    bench("with hsts", |map| {
+        map.typed_insert(headers::ReferrerPolicy::NO_REFERRER);
+        map.typed_insert(headers::StrictTransportSecurity::including_subdomains(
+            Duration::from_secs(31_536_000),
+        ));
+    });
+
+    hot();
+}
+
+fn hot() {
+    let map = support::hot();
+
+    let length = map.typed_get::<headers::ContentLength>().unwrap();
+    bench("content-length (decoded)", |map| map.typed_insert(length));
+    let content_type = map.typed_get::<headers::ContentType>().unwrap();
+    bench("content-type (decoded)", |map| map.typed_insert(content_type.clone()));
+    bench("content-type (constant)", |map| map.typed_insert(headers::ContentType::HTML.clone()));
+    let date = map.typed_get::<headers::Date>().unwrap();
+    bench("date (decoded)", |map| map.typed_insert(date));
+    let cc = map.typed_get::<headers::CacheControl>().unwrap();
+    bench("cache-control (decoded)", |map| map.typed_insert(cc.clone()));
+    bench("cache-control (built)", |map| {
+        map.typed_insert(
+            headers::CacheControl::new()
+                .with_public()
+                .with_max_age(Duration::from_secs(31_536_000)),
+        );
+    });
+    let cookie = map.typed_get::<headers::Cookie>().unwrap();
+    bench("cookie", |map| map.typed_insert(cookie.clone()));
+    let host = map.typed_get::<headers::Host>().unwrap();
+    bench("host", |map| map.typed_insert(host.clone()));
+    bench("connection keep-alive", |map| map.typed_insert(headers::Connection::keep_alive()));
+    bench("transfer-encoding chunked", |map| {
+        map.typed_insert(headers::TransferEncoding::chunked());
+    });
+    let etag = map.typed_get::<headers::ETag>().unwrap();
+    bench("etag", |map| map.typed_insert(etag.clone()));
+}
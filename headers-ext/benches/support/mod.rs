@@ -0,0 +1,122 @@
+//! The harness and fixtures the benches share.
+
+// Each bench only uses some of it.
+#![allow(dead_code)]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use headers::{HeaderMap, HeaderName, HeaderValue};
+
+struct Counting;
+
+static ALLOCS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+const ITERS: u32 = 100_000;
+
+/// Run `f` many times, printing the time and allocations of each run.
+pub fn run(name: &str, mut f: impl FnMut()) {
+    let allocs = ALLOCS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for _ in 0..ITERS {
+        f();
+    }
+    let elapsed = start.elapsed();
+    let allocs = ALLOCS.load(Ordering::Relaxed) - allocs;
+    println!(
+        "{:<32} {:>8} ns/iter {:>6} allocs/iter",
+        name,
+        elapsed.as_nanos() / u128::from(ITERS),
+        allocs as f64 / f64::from(ITERS),
+    );
+}
+
+/// The headers most often decoded and encoded, with values as browsers
+/// and servers send them.
+pub const HOT: &[(&str, &str)] = &[
+    ("content-length", "48213"),
+    ("content-type", "text/html; charset=utf-8"),
+    ("date", "Tue, 14 Oct 2025 09:12:45 GMT"),
+    ("cache-control", "public, max-age=31536000, immutable"),
+    ("accept-encoding", "gzip, deflate, br, zstd"),
+    (
+        "cookie",
+        "_ga=GA1.1.1234567890.1700000000; session=3f2a9c1e8b7d4f6a0c5e; theme=dark; \
+         _gid=GA1.2.987654321.1700000000",
+    ),
+    ("host", "www.example.com"),
+    ("connection", "keep-alive"),
+    ("transfer-encoding", "chunked"),
+    ("etag", "\"33a64df551425fcc55e4d42a148795d9f25f89d4\""),
+];
+
+/// A map of the `HOT` headers.
+pub fn hot() -> HeaderMap {
+    map(HOT)
+}
+
+/// The headers of a browser navigating to a page it has visited before.
+pub const BROWSER_REQUEST: &[(&str, &str)] = &[
+    ("host", "www.example.com"),
+    ("connection", "keep-alive"),
+    ("cache-control", "max-age=0"),
+    ("sec-ch-ua", "\"Chromium\";v=\"128\", \"Not;A=Brand\";v=\"24\", \"Google Chrome\";v=\"128\""),
+    ("sec-ch-ua-mobile", "?0"),
+    ("sec-ch-ua-platform", "\"Linux\""),
+    ("upgrade-insecure-requests", "1"),
+    (
+        "user-agent",
+        "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) \
+         Chrome/128.0.0.0 Safari/537.36",
+    ),
+    (
+        "accept",
+        "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,\
+         image/apng,*/*;q=0.8,application/signed-exchange;v=b3;q=0.7",
+    ),
+    ("sec-fetch-site", "same-origin"),
+    ("sec-fetch-mode", "navigate"),
+    ("sec-fetch-user", "?1"),
+    ("sec-fetch-dest", "document"),
+    ("referer", "https://www.example.com/search?q=headers"),
+    ("accept-encoding", "gzip, deflate, br, zstd"),
+    ("accept-language", "en-US,en;q=0.9,fr;q=0.8"),
+    (
+        "cookie",
+        "_ga=GA1.1.1234567890.1700000000; session=3f2a9c1e8b7d4f6a0c5e; theme=dark; \
+         _gid=GA1.2.987654321.1700000000",
+    ),
+    ("if-none-match", "W/\"2b4f-18c1a7e3d5c\""),
+    ("if-modified-since", "Mon, 13 Oct 2025 17:03:11 GMT"),
+];
+
+/// A map of the `BROWSER_REQUEST` headers.
+pub fn browser_request() -> HeaderMap {
+    map(BROWSER_REQUEST)
+}
+
+fn map(pairs: &[(&str, &str)]) -> HeaderMap {
+    let mut map = HeaderMap::new();
+    for &(name, value) in pairs {
+        map.append(
+            HeaderName::from_bytes(name.as_bytes()).unwrap(),
+            HeaderValue::from_str(value).unwrap(),
+        );
+    }
+    map
+}
@@ -0,0 +1,190 @@
+//! Helpers for the framing of a message body, combining `Content-Length`
+//! and `Transfer-Encoding`.
+//!
+//! [`framing`](fn.framing.html) and
+//! [`request_framing`](fn.request_framing.html) follow the rules of
+//! [RFC7230 section 3.3.3](https://tools.ietf.org/html/rfc7230#section-3.3.3).
+//! A message with both headers is read as chunked and its `Content-Length`
+//! is ignored, since disagreeing about which one wins is how requests get
+//! smuggled past a proxy.
+
+use http::header::{CONTENT_LENGTH, TRANSFER_ENCODING};
+
+use {ContentLength, HeaderMap, HeaderMapExt, TransferEncoding};
+
+/// How the end of a message body is found.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Framing {
+    /// The body is exactly this many bytes.
+    ContentLength(u64),
+    /// The body uses the chunked transfer coding.
+    Chunked,
+    /// The body ends when the connection is closed.
+    ///
+    /// Only a response can be framed this way.
+    Close,
+    /// The framing can't be determined, and the message must be rejected.
+    ///
+    /// A server should respond with `400 Bad Request` and close the
+    /// connection, and a client should close the connection and discard
+    /// the response.
+    Invalid,
+}
+
+/// Find the framing of a response body from its headers.
+///
+/// In order:
+///
+/// - A `Transfer-Encoding` ending in `chunked` means `Chunked`, whatever
+///   the `Content-Length`. Any other `Transfer-Encoding` means `Close`.
+/// - A `Content-Length` means `ContentLength`, as long as every value of
+///   it is the same valid length, and `Invalid` otherwise.
+/// - With neither header, the body lasts until the connection closes.
+///
+/// Responses that never have a body, those to a `HEAD` request or with a
+/// `1xx`, `204` or `304` status, and `2xx` responses to `CONNECT`, ignore
+/// these headers, so check for them before calling this.
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// extern crate http;
+///
+/// use headers::{ContentLength, HeaderMapExt, TransferEncoding};
+/// use headers::body::{framing, Framing};
+/// use http::HeaderMap;
+///
+/// # fn main() {
+/// let mut res = HeaderMap::new();
+/// res.typed_insert(ContentLength(13));
+/// assert_eq!(framing(&res), Framing::ContentLength(13));
+///
+/// res.typed_insert(TransferEncoding::chunked());
+/// assert_eq!(framing(&res), Framing::Chunked);
+/// # }
+/// ```
+pub fn framing(headers: &HeaderMap) -> Framing {
+    match transfer_encoding(headers) {
+        Some(true) => Framing::Chunked,
+        Some(false) => Framing::Close,
+        None => content_length(headers).unwrap_or(Framing::Close),
+    }
+}
+
+/// Find the framing of a request body from its headers.
+///
+/// This is like [`framing`](fn.framing.html), except that a request can't
+/// be ended by closing the connection. A `Transfer-Encoding` that doesn't
+/// end in `chunked` is `Invalid`, and a request with neither header has
+/// no body, which is `ContentLength(0)`.
+pub fn request_framing(headers: &HeaderMap) -> Framing {
+    match transfer_encoding(headers) {
+        Some(true) => Framing::Chunked,
+        Some(false) => Framing::Invalid,
+        None => content_length(headers).unwrap_or(Framing::ContentLength(0)),
+    }
+}
+
+/// Whether `Transfer-Encoding` ends in `chunked`, if it was sent at all.
+fn transfer_encoding(headers: &HeaderMap) -> Option<bool> {
+    if !headers.contains_key(TRANSFER_ENCODING) {
+        return None;
+    }
+    Some(
+        headers
+            .typed_get::<TransferEncoding>()
+            .is_some_and(|te| te.is_chunked()),
+    )
+}
+
+fn content_length(headers: &HeaderMap) -> Option<Framing> {
+    if !headers.contains_key(CONTENT_LENGTH) {
+        return None;
+    }
+    match headers.typed_get::<ContentLength>() {
+        Some(ContentLength(len)) => Some(Framing::ContentLength(len)),
+        None => Some(Framing::Invalid),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::HeaderMap;
+    use super::*;
+
+    fn headers(pairs: &[(&str, &str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for &(name, value) in pairs {
+            map.append(
+                ::HeaderName::from_bytes(name.as_bytes()).unwrap(),
+                value.parse().unwrap(),
+            );
+        }
+        map
+    }
+
+    #[test]
+    fn content_length() {
+        let map = headers(&[("content-length", "42")]);
+        assert_eq!(framing(&map), Framing::ContentLength(42));
+        assert_eq!(request_framing(&map), Framing::ContentLength(42));
+
+        let repeated = headers(&[("content-length", "42"), ("content-length", "42")]);
+        assert_eq!(framing(&repeated), Framing::ContentLength(42));
+        assert_eq!(framing(&headers(&[("content-length", "42, 42")])), Framing::ContentLength(42));
+    }
+
+    #[test]
+    fn content_length_invalid() {
+        let differing = headers(&[("content-length", "42"), ("content-length", "43")]);
+        assert_eq!(framing(&differing), Framing::Invalid);
+        assert_eq!(request_framing(&differing), Framing::Invalid);
+
+        assert_eq!(framing(&headers(&[("content-length", "42, 43")])), Framing::Invalid);
+        assert_eq!(framing(&headers(&[("content-length", "-1")])), Framing::Invalid);
+        assert_eq!(request_framing(&headers(&[("content-length", "")])), Framing::Invalid);
+    }
+
+    #[test]
+    fn chunked() {
+        let map = headers(&[("transfer-encoding", "gzip, chunked")]);
+        assert_eq!(framing(&map), Framing::Chunked);
+        assert_eq!(request_framing(&map), Framing::Chunked);
+
+        let lines = headers(&[("transfer-encoding", "gzip"), ("transfer-encoding", "Chunked")]);
+        assert_eq!(request_framing(&lines), Framing::Chunked);
+    }
+
+    #[test]
+    fn transfer_encoding_overrides_content_length() {
+        let map = headers(&[("transfer-encoding", "chunked"), ("content-length", "42")]);
+        assert_eq!(framing(&map), Framing::Chunked);
+        assert_eq!(request_framing(&map), Framing::Chunked);
+
+        // Even an invalid one.
+        let map = headers(&[("content-length", "42, 43"), ("transfer-encoding", "chunked")]);
+        assert_eq!(request_framing(&map), Framing::Chunked);
+
+        let map = headers(&[("transfer-encoding", "gzip"), ("content-length", "42")]);
+        assert_eq!(framing(&map), Framing::Close);
+        assert_eq!(request_framing(&map), Framing::Invalid);
+    }
+
+    #[test]
+    fn chunked_not_final() {
+        let map = headers(&[("transfer-encoding", "chunked, gzip")]);
+        assert_eq!(framing(&map), Framing::Close);
+        assert_eq!(request_framing(&map), Framing::Invalid);
+
+        let empty = headers(&[("transfer-encoding", "")]);
+        assert_eq!(framing(&empty), Framing::Close);
+        assert_eq!(request_framing(&empty), Framing::Invalid);
+    }
+
+    #[test]
+    fn neither() {
+        assert_eq!(framing(&HeaderMap::new()), Framing::Close);
+        assert_eq!(request_framing(&HeaderMap::new()), Framing::ContentLength(0));
+    }
+}
@@ -0,0 +1,439 @@
+//! Helpers for caches, combining several typed headers into one decision.
+
+use std::time::{Duration, SystemTime};
+
+use http::header::EXPIRES;
+
+use {Age, CacheControl, Date, Expires, HeaderMap, HeaderMapExt, Pragma};
+
+/// Check if a stored response is still fresh, following
+/// [RFC7234 section 4.2](https://tools.ietf.org/html/rfc7234#section-4.2).
+///
+/// The freshness lifetime comes from `s-maxage` or `max-age` (see
+/// [`CacheControl::freshness_lifetime`](../struct.CacheControl.html#method.freshness_lifetime)),
+/// falling back to the distance between `Expires` and `Date`. `Date` is
+/// taken as the time the response was received, so the current age is the
+/// `Age` header value plus the time elapsed since `Date`, as in
+/// [section 4.2.3](https://tools.ietf.org/html/rfc7234#section-4.2.3).
+///
+/// If the response didn't have a `Date` header, pass a `Date` made from
+/// the time the response was received, as a recipient is required to
+/// do. If `date` is `None`, `now` is used in its place.
+///
+/// A response with `no-store` or `no-cache` is never fresh, and neither
+/// is one that has no explicit freshness lifetime at all, since no
+/// heuristic freshness is applied. A `no-cache` limited to some headers
+/// doesn't count, as long as they're stripped (see
+/// [`CacheControl::should_strip`](../struct.CacheControl.html#method.should_strip)).
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use std::time::{Duration, SystemTime};
+/// use headers::{CacheControl, Date};
+///
+/// let now = SystemTime::now();
+/// let cc = CacheControl::new().with_max_age(Duration::from_secs(60));
+/// let date = Date::from(now - Duration::from_secs(30));
+///
+/// assert!(headers::cache::is_fresh(&cc, None, Some(&date), None, now));
+/// ```
+pub fn is_fresh(
+    cache_control: &CacheControl,
+    age: Option<&Age>,
+    date: Option<&Date>,
+    expires: Option<&Expires>,
+    now: SystemTime,
+) -> bool {
+    let no_cache = cache_control.no_cache() && cache_control.no_cache_headers().is_empty();
+    if cache_control.no_store() || no_cache {
+        return false;
+    }
+
+    let date = date.map(|&date| SystemTime::from(date)).unwrap_or(now);
+
+    let lifetime = match cache_control.freshness_lifetime() {
+        Some(lifetime) => lifetime,
+        None => match expires {
+            Some(&expires) => until_expires(expires, date),
+            None => return false,
+        },
+    };
+
+    let age = age.map(|&age| Duration::from(age)).unwrap_or_else(|| Duration::from_secs(0));
+    let resident_time = now.duration_since(date).unwrap_or_else(|_| Duration::from_secs(0));
+    let current_age = age.saturating_add(resident_time);
+
+    lifetime > current_age
+}
+
+// An `Expires` at or before `Date` means already expired.
+fn until_expires(expires: Expires, date: SystemTime) -> Duration {
+    SystemTime::from(expires)
+        .duration_since(date)
+        .unwrap_or_else(|_| Duration::from_secs(0))
+}
+
+/// What the caching headers of a response allow a cache to do with it,
+/// from [`effective_policy`](fn.effective_policy.html).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CachePolicy {
+    /// Whether the response may be stored.
+    pub cacheable: bool,
+    /// How long the response is fresh for, counting from its `Date`.
+    ///
+    /// This is `None` if the headers don't give a lifetime, which leaves
+    /// it to heuristics, and zero if the response is already stale.
+    pub freshness_lifetime: Option<Duration>,
+    /// When a stored response must be revalidated before it's reused.
+    pub revalidation: Revalidation,
+}
+
+/// When a stored response must be revalidated with the origin server, as
+/// part of a [`CachePolicy`](struct.CachePolicy.html).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Revalidation {
+    /// When stale, though a stale response may be reused without it where
+    /// that's allowed, such as when disconnected from the origin server.
+    Optional,
+    /// Every time it's stale, as `must-revalidate` says.
+    WhenStale,
+    /// Every time, as `no-cache` says.
+    Always,
+}
+
+/// Resolve the caching headers of a response into what a cache may do
+/// with it, following
+/// [RFC7234 section 5](https://tools.ietf.org/html/rfc7234#section-5).
+///
+/// `shared` is whether this is for a shared cache, such as a proxy,
+/// rather than a private one, such as a browser's.
+///
+/// - `Cache-Control` overrides `Expires`: its `max-age` is the freshness
+///   lifetime, or `s-maxage` for a shared cache. Without either, the
+///   lifetime is the distance between `Expires` and `Date`, and an
+///   `Expires` that isn't a valid date means the response is already
+///   stale. If there's no `Date`, the response is taken as just received.
+/// - `no-store` makes the response not cacheable, and so does `private`
+///   for a shared cache, unless it's limited to some headers.
+/// - `no-cache` requires revalidating every time, unless it's limited to
+///   some headers, and `must-revalidate` every time it's stale. For a
+///   shared cache, so do `proxy-revalidate` and `s-maxage`.
+/// - `Pragma` is ignored, as its `no-cache` only has a meaning in
+///   requests. See [`request_no_cache`](fn.request_no_cache.html).
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use std::time::Duration;
+/// use headers::{CacheControl, HeaderMap, HeaderMapExt};
+/// use headers::cache::{effective_policy, Revalidation};
+///
+/// let mut headers = HeaderMap::new();
+/// headers.typed_insert(
+///     CacheControl::new()
+///         .with_max_age(Duration::from_secs(60))
+///         .with_s_max_age(Duration::from_secs(600)),
+/// );
+///
+/// let private = effective_policy(&headers, false);
+/// assert_eq!(private.freshness_lifetime, Some(Duration::from_secs(60)));
+/// assert_eq!(private.revalidation, Revalidation::Optional);
+///
+/// let shared = effective_policy(&headers, true);
+/// assert_eq!(shared.freshness_lifetime, Some(Duration::from_secs(600)));
+/// assert_eq!(shared.revalidation, Revalidation::WhenStale);
+/// ```
+pub fn effective_policy(headers: &HeaderMap, shared: bool) -> CachePolicy {
+    let cc = headers.typed_get::<CacheControl>().unwrap_or_default();
+
+    let private = cc.private() && cc.private_headers().is_empty();
+    let cacheable = !(cc.no_store() || (shared && private));
+
+    let s_max_age = if shared { cc.s_max_age() } else { None };
+    let freshness_lifetime = s_max_age.or_else(|| cc.max_age()).or_else(|| {
+        if !headers.contains_key(EXPIRES) {
+            return None;
+        }
+        let expires = match headers.typed_get::<Expires>() {
+            Some(expires) => expires,
+            None => return Some(Duration::from_secs(0)),
+        };
+        let date = headers
+            .typed_get::<Date>()
+            .map(SystemTime::from)
+            .unwrap_or_else(SystemTime::now);
+        Some(until_expires(expires, date))
+    });
+
+    let revalidation = if cc.no_cache() && cc.no_cache_headers().is_empty() {
+        Revalidation::Always
+    } else if cc.must_revalidate() || (shared && (cc.proxy_revalidate() || s_max_age.is_some())) {
+        Revalidation::WhenStale
+    } else {
+        Revalidation::Optional
+    };
+
+    CachePolicy {
+        cacheable,
+        freshness_lifetime,
+        revalidation,
+    }
+}
+
+/// Check if a request forbids answering it with a stored response without
+/// revalidating it first.
+///
+/// That's `Cache-Control: no-cache`, or `Pragma: no-cache` if there's no
+/// `Cache-Control`, following
+/// [RFC7234 section 5.4](https://tools.ietf.org/html/rfc7234#section-5.4).
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::{HeaderMap, HeaderMapExt, Pragma};
+///
+/// let mut headers = HeaderMap::new();
+/// headers.typed_insert(Pragma::no_cache());
+/// assert!(headers::cache::request_no_cache(&headers));
+/// ```
+pub fn request_no_cache(headers: &HeaderMap) -> bool {
+    match headers.typed_get::<CacheControl>() {
+        Some(cc) => cc.no_cache(),
+        None => headers
+            .typed_get::<Pragma>()
+            .is_some_and(|pragma| pragma.is_no_cache()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use super::{effective_policy, is_fresh, request_no_cache, CachePolicy, Revalidation};
+    use {Age, CacheControl, Date, Expires, HeaderMap};
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn max_age() {
+        let now = SystemTime::now();
+        let cc = CacheControl::new().with_max_age(secs(60));
+
+        let date = Date::from(now - secs(30));
+        assert!(is_fresh(&cc, None, Some(&date), None, now));
+
+        let date = Date::from(now - secs(90));
+        assert!(!is_fresh(&cc, None, Some(&date), None, now));
+    }
+
+    #[test]
+    fn age_header_counts() {
+        let now = SystemTime::now();
+        let cc = CacheControl::new().with_max_age(secs(60));
+        let date = Date::from(now);
+
+        assert!(is_fresh(&cc, Some(&Age::from(secs(59))), Some(&date), None, now));
+        assert!(!is_fresh(&cc, Some(&Age::from(secs(60))), Some(&date), None, now));
+    }
+
+    #[test]
+    fn age_adds_to_time_since_date() {
+        let now = SystemTime::now();
+        let cc = CacheControl::new().with_max_age(secs(60));
+        let date = Date::from(now - secs(20));
+
+        assert!(is_fresh(&cc, Some(&Age::from(secs(30))), Some(&date), None, now));
+        assert!(!is_fresh(&cc, Some(&Age::from(secs(50))), Some(&date), None, now));
+    }
+
+    #[test]
+    fn s_max_age_overrides_expires() {
+        let now = SystemTime::now();
+        let cc = CacheControl::new().with_s_max_age(secs(10));
+        let expires = Expires::from(now + secs(3600));
+
+        assert!(!is_fresh(&cc, Some(&Age::from(secs(20))), None, Some(&expires), now));
+    }
+
+    #[test]
+    fn expires() {
+        let now = SystemTime::now();
+        let cc = CacheControl::new();
+        let date = Date::from(now - secs(10));
+
+        let expires = Expires::from(now + secs(60));
+        assert!(is_fresh(&cc, None, Some(&date), Some(&expires), now));
+
+        let expires = Expires::from(now - secs(60));
+        assert!(!is_fresh(&cc, None, Some(&date), Some(&expires), now));
+        assert!(!is_fresh(&cc, None, None, Some(&expires), now));
+    }
+
+    #[test]
+    fn never_fresh() {
+        let now = SystemTime::now();
+        let date = Date::from(now);
+
+        let cc = CacheControl::new().with_max_age(secs(60)).with_no_cache();
+        assert!(!is_fresh(&cc, None, Some(&date), None, now));
+
+        let cc = CacheControl::new().with_max_age(secs(60)).with_no_store();
+        assert!(!is_fresh(&cc, None, Some(&date), None, now));
+
+        assert!(!is_fresh(&CacheControl::new(), None, Some(&date), None, now));
+    }
+
+    #[test]
+    fn no_cache_limited_to_headers() {
+        let now = SystemTime::now();
+        let date = Date::from(now);
+
+        let cc = CacheControl::new()
+            .with_max_age(secs(60))
+            .with_no_cache_headers(vec![::http::header::SET_COOKIE]);
+        assert!(is_fresh(&cc, None, Some(&date), None, now));
+        assert!(!is_fresh(&cc.with_no_cache(), None, Some(&date), None, now));
+    }
+
+    fn headers(pairs: &[(&str, &str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for &(name, value) in pairs {
+            map.append(
+                ::HeaderName::from_bytes(name.as_bytes()).unwrap(),
+                value.parse().unwrap(),
+            );
+        }
+        map
+    }
+
+    fn policy(pairs: &[(&str, &str)], shared: bool) -> CachePolicy {
+        effective_policy(&headers(pairs), shared)
+    }
+
+    #[test]
+    fn policy_without_headers() {
+        let expected = CachePolicy {
+            cacheable: true,
+            freshness_lifetime: None,
+            revalidation: Revalidation::Optional,
+        };
+        assert_eq!(policy(&[], false), expected);
+        assert_eq!(policy(&[], true), expected);
+    }
+
+    #[test]
+    fn policy_expires() {
+        // The example of RFC7234 section 5.3, an hour after `Date`.
+        let pairs = [
+            ("date", "Thu, 01 Dec 1994 15:00:00 GMT"),
+            ("expires", "Thu, 01 Dec 1994 16:00:00 GMT"),
+        ];
+        assert_eq!(policy(&pairs, false).freshness_lifetime, Some(secs(3600)));
+
+        let pairs = [
+            ("date", "Thu, 01 Dec 1994 16:00:00 GMT"),
+            ("expires", "Thu, 01 Dec 1994 15:00:00 GMT"),
+        ];
+        assert_eq!(policy(&pairs, false).freshness_lifetime, Some(secs(0)));
+    }
+
+    #[test]
+    fn policy_invalid_expires_is_stale() {
+        // "especially the value "0"", as RFC7234 section 5.3 says.
+        let pairs = [
+            ("date", "Thu, 01 Dec 1994 15:00:00 GMT"),
+            ("expires", "0"),
+        ];
+        assert_eq!(policy(&pairs, false).freshness_lifetime, Some(secs(0)));
+    }
+
+    #[test]
+    fn policy_max_age_overrides_expires() {
+        let pairs = [
+            ("cache-control", "max-age=60"),
+            ("date", "Thu, 01 Dec 1994 15:00:00 GMT"),
+            ("expires", "0"),
+        ];
+        assert_eq!(policy(&pairs, false).freshness_lifetime, Some(secs(60)));
+        assert_eq!(policy(&pairs, true).freshness_lifetime, Some(secs(60)));
+    }
+
+    #[test]
+    fn policy_s_max_age_is_for_shared_caches() {
+        let pairs = [
+            ("cache-control", "s-maxage=600"),
+            ("date", "Thu, 01 Dec 1994 15:00:00 GMT"),
+            ("expires", "Thu, 01 Dec 1994 16:00:00 GMT"),
+        ];
+        let shared = policy(&pairs, true);
+        assert_eq!(shared.freshness_lifetime, Some(secs(600)));
+        assert_eq!(shared.revalidation, Revalidation::WhenStale);
+
+        // A private cache falls back to `Expires`.
+        let private = policy(&pairs, false);
+        assert_eq!(private.freshness_lifetime, Some(secs(3600)));
+        assert_eq!(private.revalidation, Revalidation::Optional);
+    }
+
+    #[test]
+    fn policy_private() {
+        // The example of RFC7234 section 5.2.3.
+        let pairs = [("cache-control", "private, community=\"UCI\"")];
+        assert!(!policy(&pairs, true).cacheable);
+        assert!(policy(&pairs, false).cacheable);
+
+        // A shared cache strips the header instead.
+        let pairs = [("cache-control", "private=\"set-cookie\"")];
+        assert!(policy(&pairs, true).cacheable);
+    }
+
+    #[test]
+    fn policy_no_store() {
+        let pairs = [("cache-control", "no-store, max-age=60")];
+        assert!(!policy(&pairs, false).cacheable);
+        assert!(!policy(&pairs, true).cacheable);
+    }
+
+    #[test]
+    fn policy_revalidation() {
+        let pairs = [("cache-control", "no-cache, max-age=60")];
+        let no_cache = policy(&pairs, false);
+        assert!(no_cache.cacheable);
+        assert_eq!(no_cache.revalidation, Revalidation::Always);
+
+        let pairs = [("cache-control", "no-cache=\"set-cookie\"")];
+        assert_eq!(policy(&pairs, false).revalidation, Revalidation::Optional);
+
+        let pairs = [("cache-control", "must-revalidate")];
+        assert_eq!(policy(&pairs, false).revalidation, Revalidation::WhenStale);
+
+        let pairs = [("cache-control", "proxy-revalidate")];
+        assert_eq!(policy(&pairs, true).revalidation, Revalidation::WhenStale);
+        assert_eq!(policy(&pairs, false).revalidation, Revalidation::Optional);
+    }
+
+    #[test]
+    fn policy_ignores_pragma() {
+        let pairs = [("pragma", "no-cache")];
+        assert_eq!(policy(&pairs, false).revalidation, Revalidation::Optional);
+    }
+
+    #[test]
+    fn request_pragma_without_cache_control() {
+        assert!(request_no_cache(&headers(&[("pragma", "no-cache")])));
+        assert!(!request_no_cache(&headers(&[("pragma", "dexter")])));
+        assert!(!request_no_cache(&headers(&[])));
+
+        // Cache-Control wins, when there is one.
+        let pairs = [("cache-control", "max-age=60"), ("pragma", "no-cache")];
+        assert!(!request_no_cache(&headers(&pairs)));
+        let pairs = [("cache-control", "no-cache")];
+        assert!(request_no_cache(&headers(&pairs)));
+    }
+}
@@ -0,0 +1,128 @@
+use std::iter::FromIterator;
+
+use headers_core::decode::TryFromValues;
+
+use {HeaderName, HeaderValue};
+use util::{parse_item, ParsedCsv};
+
+/// `Accept-CH` header, defined in
+/// [RFC8942](https://tools.ietf.org/html/rfc8942#section-3.1)
+///
+/// The `Accept-CH` response header field lists the client hints a server
+/// would like the client to send on its next requests.
+///
+/// # ABNF
+///
+/// ```text
+/// Accept-CH = #field-name
+/// ```
+///
+/// # Example values
+///
+/// * `Sec-CH-UA, DPR`
+/// * `Sec-CH-UA-Platform`
+///
+/// # Notes
+///
+/// * Names that aren't valid header names are skipped.
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::{AcceptCh, HeaderName};
+///
+/// let accept_ch = vec![
+///     HeaderName::from_static("sec-ch-ua"),
+///     HeaderName::from_static("dpr"),
+/// ].into_iter().collect::<AcceptCh>();
+/// assert!(accept_ch.contains_str("DPR"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct AcceptCh(ParsedCsv<HeaderName>);
+
+impl ::Header for AcceptCh {
+    fn name() -> &'static ::HeaderName {
+        static_header_name!("accept-ch")
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        TryFromValues::try_from_values(values).map(AcceptCh)
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        values.append((&self.0).into());
+    }
+}
+
+impl AcceptCh {
+    /// Returns an iterator over the `HeaderName`s of the hints.
+    pub fn iter(&self) -> ::list::Iter<'_, HeaderName> {
+        ::list::Iter::new(self.0.iter())
+    }
+
+    fn into_items(self) -> ::list::IntoIter<HeaderName> {
+        ::list::IntoIter::new(self.0.into_iter())
+    }
+
+    /// Returns an iterator over clones of the `HeaderName`s of the hints.
+    pub fn iter_cloned<'a>(&'a self) -> impl Iterator<Item = HeaderName> + 'a {
+        self.iter().cloned()
+    }
+
+    /// Check if the hint `name` is accepted.
+    pub fn contains(&self, name: &HeaderName) -> bool {
+        self.iter().any(|have| have == name)
+    }
+
+    /// Check if the hint named `name` is accepted, in any case, like
+    /// `contains`.
+    pub fn contains_str(&self, name: &str) -> bool {
+        parse_item(name).is_some_and(|name| self.contains(&name))
+    }
+}
+
+impl_collection!(AcceptCh, HeaderName);
+
+impl FromIterator<HeaderName> for AcceptCh {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = HeaderName>,
+    {
+        AcceptCh(ParsedCsv::from_items(iter, name_value))
+    }
+}
+
+fn name_value(name: &HeaderName) -> HeaderValue {
+    name.clone().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{test_decode, test_encode};
+
+    #[test]
+    fn decode() {
+        let accept_ch = test_decode::<AcceptCh>(&["Sec-CH-UA, DPR", "Viewport-Width"]).unwrap();
+        assert_eq!(accept_ch.iter().collect::<Vec<_>>(), ["sec-ch-ua", "dpr", "viewport-width"]);
+        assert!(accept_ch.contains(&HeaderName::from_static("dpr")));
+        assert!(accept_ch.contains_str("sec-ch-ua"));
+        assert!(!accept_ch.contains_str("sec-ch-ua-mobile"));
+
+        let skipped = test_decode::<AcceptCh>(&["Sec-CH-UA, b@d, DPR"]).unwrap();
+        assert_eq!(skipped.iter().collect::<Vec<_>>(), ["sec-ch-ua", "dpr"]);
+    }
+
+    #[test]
+    fn encode() {
+        let accept_ch: AcceptCh = vec![
+            HeaderName::from_static("sec-ch-ua"),
+            HeaderName::from_static("dpr"),
+        ].into_iter().collect();
+
+        let headers = test_encode(accept_ch);
+        assert_eq!(headers["accept-ch"], "sec-ch-ua, dpr");
+        assert_eq!(headers.get_all("accept-ch").iter().count(), 1);
+    }
+}
@@ -0,0 +1,136 @@
+use std::iter::FromIterator;
+
+use mime::Mime;
+
+use util::MimeList;
+
+/// `Accept-Patch` header, defined in
+/// [RFC5789](https://tools.ietf.org/html/rfc5789#section-3.1)
+///
+/// The `Accept-Patch` header field advertises which patch document
+/// formats the server is able to apply to the target resource, as a
+/// list of media types.
+///
+/// # ABNF
+///
+/// ```text
+/// Accept-Patch = 1#media-type
+/// ```
+///
+/// # Example values
+///
+/// * `application/json-patch+json, application/merge-patch+json`
+/// * `text/example;charset=utf-8`
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// extern crate mime;
+/// use headers::AcceptPatch;
+///
+/// let accept_patch = vec![
+///     "application/json-patch+json".parse::<mime::Mime>().unwrap(),
+///     "application/merge-patch+json".parse().unwrap(),
+/// ].into_iter().collect::<AcceptPatch>();
+///
+/// assert!(accept_patch.contains(&"application/merge-patch+json".parse().unwrap()));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct AcceptPatch(MimeList);
+
+impl AcceptPatch {
+    /// Returns an iterator over the `Mime`s contained within.
+    pub fn iter(&self) -> ::list::Iter<'_, Mime> {
+        ::list::Iter::new(self.0.iter())
+    }
+
+    fn into_items(self) -> ::list::IntoIter<Mime> {
+        ::list::IntoIter::new(self.0.into_iter())
+    }
+
+    /// Checks if a media type matching `mime` is accepted.
+    ///
+    /// Parameters are only compared if they appear in `mime`, so
+    /// `text/example` matches a listed `text/example;charset=utf-8`.
+    pub fn contains(&self, mime: &Mime) -> bool {
+        self.0.contains(mime)
+    }
+}
+
+impl_collection!(AcceptPatch, Mime);
+
+impl ::Header for AcceptPatch {
+    fn name() -> &'static ::HeaderName {
+        static_header_name!("accept-patch")
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        ::headers_core::decode::TryFromValues::try_from_values(values)
+            .map(AcceptPatch)
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        values.append_fmt(&self.0);
+    }
+}
+
+impl FromIterator<Mime> for AcceptPatch {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = Mime>,
+    {
+        AcceptPatch(iter.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{test_decode, test_encode};
+
+    #[test]
+    fn decode_multiple_lines() {
+        let accept_patch = test_decode::<AcceptPatch>(&[
+            "application/json-patch+json, application/merge-patch+json",
+            "text/example;charset=utf-8",
+        ]).unwrap();
+
+        let as_vec = accept_patch.iter().collect::<Vec<_>>();
+        assert_eq!(as_vec.len(), 3);
+        assert_eq!(*as_vec[0], "application/json-patch+json");
+        assert_eq!(*as_vec[1], "application/merge-patch+json");
+        assert_eq!(*as_vec[2], "text/example;charset=utf-8");
+
+        assert!(accept_patch.contains(&"text/example".parse().unwrap()));
+    }
+
+    #[test]
+    fn decode_quoted_param_with_comma() {
+        let accept_patch = test_decode::<AcceptPatch>(&[
+            "text/example; note=\"a, b\", text/plain",
+        ]).unwrap();
+
+        assert_eq!(accept_patch.iter().count(), 2);
+    }
+
+    #[test]
+    fn decode_invalid() {
+        assert_eq!(test_decode::<AcceptPatch>(&["application/json, lol"]), None);
+        assert_eq!(test_decode::<AcceptPatch>(&[""]), None);
+    }
+
+    #[test]
+    fn encode() {
+        let accept_patch = vec![
+            "application/json-patch+json".parse::<Mime>().unwrap(),
+            "application/merge-patch+json".parse().unwrap(),
+        ].into_iter().collect::<AcceptPatch>();
+
+        let headers = test_encode(accept_patch);
+        assert_eq!(
+            headers["accept-patch"],
+            "application/json-patch+json, application/merge-patch+json",
+        );
+    }
+}
@@ -0,0 +1,123 @@
+use std::iter::FromIterator;
+
+use mime::Mime;
+
+use util::MimeList;
+
+/// `Accept-Post` header, defined in
+/// [Linked Data Platform 1.0](https://www.w3.org/TR/ldp/#header-accept-post)
+///
+/// The `Accept-Post` header field advertises which media types the
+/// server accepts in the body of a `POST` request to the target
+/// resource.
+///
+/// # ABNF
+///
+/// ```text
+/// Accept-Post = 1#media-type
+/// ```
+///
+/// # Example values
+///
+/// * `text/turtle, application/ld+json`
+/// * `image/png`
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// extern crate mime;
+/// use headers::AcceptPost;
+///
+/// let accept_post = vec![
+///     "text/turtle".parse::<mime::Mime>().unwrap(),
+///     "application/ld+json".parse().unwrap(),
+/// ].into_iter().collect::<AcceptPost>();
+///
+/// assert!(accept_post.contains(&"text/turtle".parse().unwrap()));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct AcceptPost(MimeList);
+
+impl AcceptPost {
+    /// Returns an iterator over the `Mime`s contained within.
+    pub fn iter(&self) -> ::list::Iter<'_, Mime> {
+        ::list::Iter::new(self.0.iter())
+    }
+
+    fn into_items(self) -> ::list::IntoIter<Mime> {
+        ::list::IntoIter::new(self.0.into_iter())
+    }
+
+    /// Checks if a media type matching `mime` is accepted.
+    ///
+    /// Parameters are only compared if they appear in `mime`.
+    pub fn contains(&self, mime: &Mime) -> bool {
+        self.0.contains(mime)
+    }
+}
+
+impl_collection!(AcceptPost, Mime);
+
+impl ::Header for AcceptPost {
+    fn name() -> &'static ::HeaderName {
+        static_header_name!("accept-post")
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        ::headers_core::decode::TryFromValues::try_from_values(values)
+            .map(AcceptPost)
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        values.append_fmt(&self.0);
+    }
+}
+
+impl FromIterator<Mime> for AcceptPost {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = Mime>,
+    {
+        AcceptPost(iter.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{test_decode, test_encode};
+
+    #[test]
+    fn decode_multiple_lines() {
+        let accept_post = test_decode::<AcceptPost>(&[
+            "text/turtle, application/ld+json",
+            "image/png",
+        ]).unwrap();
+
+        let as_vec = accept_post.iter().collect::<Vec<_>>();
+        assert_eq!(as_vec.len(), 3);
+        assert_eq!(*as_vec[0], "text/turtle");
+        assert_eq!(*as_vec[1], "application/ld+json");
+        assert_eq!(*as_vec[2], "image/png");
+
+        assert!(!accept_post.contains(&"image/jpeg".parse().unwrap()));
+    }
+
+    #[test]
+    fn decode_invalid() {
+        assert_eq!(test_decode::<AcceptPost>(&["application/json, lol"]), None);
+        assert_eq!(test_decode::<AcceptPost>(&[""]), None);
+    }
+
+    #[test]
+    fn encode() {
+        let accept_post = vec![
+            "text/turtle".parse::<Mime>().unwrap(),
+            "application/ld+json".parse().unwrap(),
+        ].into_iter().collect::<AcceptPost>();
+
+        let headers = test_encode(accept_post);
+        assert_eq!(headers["accept-post"], "text/turtle, application/ld+json");
+    }
+}
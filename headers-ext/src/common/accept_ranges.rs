@@ -1,4 +1,6 @@
-use util::FlatCsv;
+use std::iter::FromIterator;
+
+use util::{FlatCsv, RangeUnit};
 
 /// `Accept-Ranges` header, defined in
 /// [RFC7233](http://tools.ietf.org/html/rfc7233#section-2.3)
@@ -20,6 +22,14 @@ use util::FlatCsv;
 ///
 /// # Examples
 /// ```
+/// # extern crate headers_ext as headers;
+/// use headers::{AcceptRanges, RangeUnit};
+///
+/// let items = RangeUnit::Other("items".into());
+/// let accept_ranges = vec![items.clone()].into_iter().collect::<AcceptRanges>();
+/// assert!(accept_ranges.supports(&items));
+/// assert!(!accept_ranges.supports(&RangeUnit::Bytes));
+/// assert!(!AcceptRanges::none().supports(&RangeUnit::None));
 /// ```
 #[derive(Clone, Debug, Header, PartialEq)]
 pub struct AcceptRanges(FlatCsv);
@@ -29,4 +39,61 @@ impl AcceptRanges {
     pub fn bytes() -> Self {
         AcceptRanges(::HeaderValue::from_static("bytes").into())
     }
+
+    /// Create `Accept-Ranges: none`, stating no range requests are
+    /// supported.
+    pub fn none() -> Self {
+        AcceptRanges(::HeaderValue::from_static("none").into())
+    }
+
+    /// Iterate the units, skipping the ones that aren't tokens.
+    pub fn iter(&self) -> impl Iterator<Item = RangeUnit> + '_ {
+        self.0.iter().filter_map(|unit| unit.parse().ok())
+    }
+
+    /// Check if ranges of `unit` are supported.
+    ///
+    /// `none` is never supported, even when listed.
+    pub fn supports(&self, unit: &RangeUnit) -> bool {
+        *unit != RangeUnit::None && self.iter().any(|listed| listed == *unit)
+    }
+}
+
+impl FromIterator<RangeUnit> for AcceptRanges {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = RangeUnit>,
+    {
+        AcceptRanges(
+            iter.into_iter()
+                .map(|unit| ::HeaderValue::from_str(unit.as_str()).expect("range unit is a valid value"))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AcceptRanges;
+    use super::super::{test_decode, test_encode};
+    use RangeUnit;
+
+    #[test]
+    fn units() {
+        let accept_ranges = test_decode::<AcceptRanges>(&["bytes, Items"]).unwrap();
+        assert_eq!(accept_ranges.iter().collect::<Vec<_>>(), [RangeUnit::Bytes, RangeUnit::Other("items".into())]);
+        assert!(accept_ranges.supports(&RangeUnit::Other("ITEMS".into())));
+        assert!(!accept_ranges.supports(&RangeUnit::Other("pages".into())));
+
+        let none = test_decode::<AcceptRanges>(&["none"]).unwrap();
+        assert_eq!(none, AcceptRanges::none());
+        assert!(!none.supports(&RangeUnit::Bytes));
+    }
+
+    #[test]
+    fn encode() {
+        let units = vec![RangeUnit::Bytes, RangeUnit::Other("items".into())];
+        assert_eq!(test_encode(units.into_iter().collect::<AcceptRanges>())["accept-ranges"], "bytes, items");
+        assert_eq!(test_encode(AcceptRanges::bytes())["accept-ranges"], "bytes");
+    }
 }
@@ -1,7 +1,8 @@
 use std::iter::FromIterator;
 
 use {HeaderName, HeaderValue};
-use util::FlatCsv;
+use super::AccessControlRequestHeaders;
+use util::{join_wildcard, parse_item, split_wildcard, FlatCsv, ParsedCsv};
 
 /// `Access-Control-Allow-Headers` header, part of
 /// [CORS](http://www.w3.org/TR/cors/#access-control-allow-headers-response-header)
@@ -18,6 +19,8 @@ use util::FlatCsv;
 ///
 /// # Example values
 /// * `accept-language, date`
+/// * `*`
+/// * `*, Authorization`
 ///
 /// # Examples
 ///
@@ -30,19 +33,140 @@ use util::FlatCsv;
 /// let allow_headers = vec![CACHE_CONTROL, CONTENT_TYPE]
 ///     .into_iter()
 ///     .collect::<AccessControlAllowHeaders>();
+/// assert!(allow_headers.contains(&CONTENT_TYPE));
+/// assert!(allow_headers.contains_str("Cache-Control"));
+///
+/// let allow_any = AccessControlAllowHeaders::any();
+/// assert!(allow_any.contains_str("X-Custom"));
+/// assert!(!allow_any.contains_str("Authorization"));
 /// ```
-#[derive(Clone, Debug, PartialEq, Header)]
-pub struct AccessControlAllowHeaders(FlatCsv);
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccessControlAllowHeaders(Inner);
+
+// `Any` holds the names listed along with `*`, which it doesn't allow by
+// itself.
+#[derive(Clone, Debug, PartialEq)]
+enum Inner {
+    Any(ParsedCsv<HeaderName>),
+    Names(ParsedCsv<HeaderName>),
+}
 
 impl AccessControlAllowHeaders {
-    /// Returns an iterator over `HeaderName`s contained within.
-    pub fn iter<'a>(&'a self) -> impl Iterator<Item = HeaderName> + 'a {
-        self
-            .0
-            .iter()
-            .filter_map(|s| {
-                s.parse().ok()
-            })
+    /// Create an `Access-Control-Allow-Headers: *` header, allowing any
+    /// header in requests without credentials.
+    pub fn any() -> AccessControlAllowHeaders {
+        AccessControlAllowHeaders(Inner::Any(ParsedCsv::from_items(None, name_value)))
+    }
+
+    /// Check if this has the `*` wildcard, alone or with listed names.
+    pub fn is_any(&self) -> bool {
+        match self.0 {
+            Inner::Any(_) => true,
+            Inner::Names(_) => false,
+        }
+    }
+
+    fn names(&self) -> &ParsedCsv<HeaderName> {
+        match self.0 {
+            Inner::Any(ref names) | Inner::Names(ref names) => names,
+        }
+    }
+
+    /// Returns an iterator over the `HeaderName`s listed.
+    ///
+    /// A `*` wildcard isn't one of them, only the names listed along with
+    /// it, such as `Authorization` in `*, Authorization`.
+    pub fn iter(&self) -> ::list::Iter<'_, HeaderName> {
+        ::list::Iter::new(self.names().iter())
+    }
+
+    fn into_items(self) -> ::list::IntoIter<HeaderName> {
+        match self.0 {
+            Inner::Any(names) | Inner::Names(names) => ::list::IntoIter::new(names.into_iter()),
+        }
+    }
+
+    /// Returns an iterator over clones of the `HeaderName`s contained
+    /// within.
+    pub fn iter_cloned<'a>(&'a self) -> impl Iterator<Item = HeaderName> + 'a {
+        self.iter().cloned()
+    }
+
+    /// Check if `name` is allowed.
+    ///
+    /// `*` allows any name but `Authorization`, which must be listed even
+    /// then.
+    pub fn contains(&self, name: &HeaderName) -> bool {
+        let wildcard = self.is_any() && name != ::http::header::AUTHORIZATION;
+        wildcard || self.iter().any(|have| have == name)
+    }
+
+    /// Check if the name `name` is allowed, in any case, like `contains`.
+    ///
+    /// An invalid name is never allowed.
+    pub fn contains_str(&self, name: &str) -> bool {
+        parse_item(name).is_some_and(|name| self.contains(&name))
+    }
+
+    /// Check if every header of a preflight's `Access-Control-Request-Headers`
+    /// is allowed, so the actual request can be made.
+    ///
+    /// Requests with credentials take `*` as a literal name instead, so for
+    /// those the allowed names must be listed.
+    pub fn is_superset_of(&self, requested: &AccessControlRequestHeaders) -> bool {
+        requested.iter().all(|name| self.contains(name))
+    }
+
+    /// Allow `name` as well, returning whether it wasn't already allowed.
+    ///
+    /// `*` already allows any name but `Authorization`, which is listed
+    /// next to it.
+    pub fn insert(&mut self, name: HeaderName) -> bool {
+        let added = !self.contains(&name);
+        if added {
+            self.extend(Some(name));
+        }
+        added
+    }
+
+    /// Sort the names byte-wise and drop repeats, so equal sets always
+    /// encode the same. A `*` is kept in front.
+    ///
+    /// When the `canonical` feature is enabled, this is done on every
+    /// encode.
+    pub fn canonicalize(&mut self) {
+        match self.0 {
+            Inner::Any(ref mut names) | Inner::Names(ref mut names) => {
+                *names = names.canonical(HeaderName::as_str, name_value);
+            },
+        }
+    }
+}
+
+impl_collection!(AccessControlAllowHeaders, HeaderName, wildcard);
+
+impl ::Header for AccessControlAllowHeaders {
+    fn name() -> &'static ::HeaderName {
+        &::http::header::ACCESS_CONTROL_ALLOW_HEADERS
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        let csv: FlatCsv = FlatCsv::from_values(values)?;
+        let (any, csv) = split_wildcard(csv);
+
+        // A single invalid name fails the whole header, rather than
+        // silently allowing fewer headers than configured.
+        let names = ParsedCsv::strict(csv)?;
+        Some(AccessControlAllowHeaders(if any { Inner::Any(names) } else { Inner::Names(names) }))
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        let value = if cfg!(feature = "canonical") {
+            (&self.names().canonical(HeaderName::as_str, name_value)).into()
+        } else {
+            self.names().into()
+        };
+        values.append(if self.is_any() { join_wildcard(value) } else { value });
     }
 }
 
@@ -51,19 +175,75 @@ impl FromIterator<HeaderName> for AccessControlAllowHeaders {
     where
         I: IntoIterator<Item = HeaderName>,
     {
-        let flat = iter
-            .into_iter()
-            .map(HeaderValue::from)
-            .collect();
-        AccessControlAllowHeaders(flat)
+        let names = ParsedCsv::from_items(iter, name_value);
+        AccessControlAllowHeaders(Inner::Names(names))
+    }
+}
+
+impl From<AccessControlRequestHeaders> for AccessControlAllowHeaders {
+    /// Allow exactly the headers a preflight request asked for, echoing
+    /// them back.
+    fn from(requested: AccessControlRequestHeaders) -> AccessControlAllowHeaders {
+        requested.iter_cloned().collect()
+    }
+}
+
+impl Extend<HeaderName> for AccessControlAllowHeaders {
+    /// Allow the names of `iter` as well, dropping those already allowed.
+    ///
+    /// `*` already allows any name but `Authorization`, which is listed
+    /// next to it.
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = HeaderName>,
+    {
+        let mut added: Vec<HeaderName> = Vec::new();
+        for name in iter {
+            if !self.contains(&name) && !added.contains(&name) {
+                added.push(name);
+            }
+        }
+        if !added.is_empty() {
+            let all = self.iter().cloned().chain(added);
+            let names = ParsedCsv::from_items(all, name_value);
+            self.0 = if self.is_any() { Inner::Any(names) } else { Inner::Names(names) };
+        }
     }
 }
 
+fn name_value(name: &HeaderName) -> HeaderValue {
+    name.clone().into()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ::HeaderMapExt;
     use super::super::{test_decode, test_encode};
 
+    #[test]
+    fn iter_borrows() {
+        let allow_headers = test_decode::<AccessControlAllowHeaders>(
+            &["Content-Type, x-custom"]
+        ).unwrap();
+
+        let names = allow_headers.iter().collect::<Vec<&HeaderName>>();
+        assert_eq!(names, [&::http::header::CONTENT_TYPE, &HeaderName::from_static("x-custom")]);
+        assert!(allow_headers.iter().any(|name| name == ::http::header::CONTENT_TYPE));
+
+        let owned = allow_headers.iter_cloned().collect::<Vec<HeaderName>>();
+        assert_eq!(owned.len(), 2);
+    }
+
+    #[test]
+    fn from_requested() {
+        let requested = test_decode::<AccessControlRequestHeaders>(&["X-Token, content-type"]).unwrap();
+        let allowed = AccessControlAllowHeaders::from(requested.clone());
+        assert!(!allowed.is_any());
+        assert!(allowed.is_superset_of(&requested));
+        assert_eq!(allowed.iter().collect::<Vec<_>>(), ["x-token", "content-type"]);
+    }
+
     #[test]
     fn iter() {
         let allow_headers = test_decode::<AccessControlAllowHeaders>(
@@ -76,6 +256,119 @@ mod tests {
         assert_eq!(as_vec[1], "bar");
     }
 
+    #[test]
+    fn iter_multiple_lines() {
+        let allow_headers = test_decode::<AccessControlAllowHeaders>(
+            &["foo, bar", "baz,"]
+        ).unwrap();
+
+        let as_vec = allow_headers.iter().collect::<Vec<_>>();
+        assert_eq!(as_vec, ["foo", "bar", "baz"]);
+    }
+
+    #[test]
+    fn iter_mixed_lines() {
+        let allow_headers = test_decode::<AccessControlAllowHeaders>(
+            &["content-type, X-Custom", "accept", "x-a,x-b"]
+        ).unwrap();
+
+        let as_vec = allow_headers.iter().collect::<Vec<_>>();
+        assert_eq!(as_vec, ["content-type", "x-custom", "accept", "x-a", "x-b"]);
+    }
+
+    #[test]
+    fn decode_from_single_header_map_value() {
+        let mut map = ::HeaderMap::new();
+        map.insert(
+            ::http::header::ACCESS_CONTROL_ALLOW_HEADERS,
+            "foo, bar".parse().unwrap(),
+        );
+
+        let allow_headers: AccessControlAllowHeaders = map.typed_get().unwrap();
+        assert_eq!(allow_headers.iter().collect::<Vec<_>>(), ["foo", "bar"]);
+    }
+
+    #[test]
+    fn decode_invalid_name_fails() {
+        assert_eq!(
+            test_decode::<AccessControlAllowHeaders>(&["foo, b@r"]),
+            None,
+        );
+        assert_eq!(
+            test_decode::<AccessControlAllowHeaders>(&["foo", "bar baz"]),
+            None,
+        );
+    }
+
+    #[test]
+    fn any() {
+        let allow_headers = test_decode::<AccessControlAllowHeaders>(&["*"]).unwrap();
+        assert!(allow_headers.is_any());
+        assert_eq!(allow_headers.iter().count(), 0);
+
+        let headers = test_encode(AccessControlAllowHeaders::any());
+        assert_eq!(headers["access-control-allow-headers"], "*");
+
+        let names: AccessControlAllowHeaders = vec![::http::header::ACCEPT].into_iter().collect();
+        assert!(!names.is_any());
+
+        // A `*` member is the wildcard, with the other names listed.
+        let mixed = test_decode::<AccessControlAllowHeaders>(&["*, Authorization"]).unwrap();
+        assert!(mixed.is_any());
+        assert_eq!(mixed.iter().collect::<Vec<_>>(), ["authorization"]);
+        assert!(mixed.contains_str("x-custom"));
+        assert!(mixed.contains(&::http::header::AUTHORIZATION));
+        #[cfg(not(feature = "canonical"))]
+        assert_eq!(test_encode(mixed)["access-control-allow-headers"], "*, Authorization");
+
+        let split = test_decode::<AccessControlAllowHeaders>(&["x-a", "*"]).unwrap();
+        assert!(split.is_any());
+        assert_eq!(test_encode(split)["access-control-allow-headers"], "*, x-a");
+        assert!(test_decode::<AccessControlAllowHeaders>(&["*, b@d"]).is_none());
+    }
+
+    #[test]
+    fn contains() {
+        let allowed = test_decode::<AccessControlAllowHeaders>(&["X-Custom, Content-Type"]).unwrap();
+
+        assert!(allowed.contains(&HeaderName::from_static("x-custom")));
+        assert!(allowed.contains(&::http::header::CONTENT_TYPE));
+        assert!(!allowed.contains(&::http::header::ACCEPT));
+
+        assert!(allowed.contains_str("x-custom"));
+        assert!(allowed.contains_str("X-CUSTOM"));
+        assert!(allowed.contains_str("content-type"));
+        assert!(!allowed.contains_str("x-other"));
+        assert!(!allowed.contains_str("x custom"));
+        assert!(!allowed.contains_str(""));
+        assert!(!allowed.contains_str(&"x".repeat(1 << 16)));
+
+        let any = AccessControlAllowHeaders::any();
+        assert!(any.contains(&HeaderName::from_static("x-custom")));
+        assert!(any.contains_str("X-Anything"));
+        assert!(!any.contains(&::http::header::AUTHORIZATION));
+        assert!(!any.contains_str("Authorization"));
+        assert!(!any.contains_str("b@d"));
+    }
+
+    #[test]
+    fn is_superset_of() {
+        let requested = |s| test_decode::<AccessControlRequestHeaders>(&[s]).unwrap();
+        let allowed = test_decode::<AccessControlAllowHeaders>(&["X-Custom, Content-Type"]).unwrap();
+
+        assert!(allowed.is_superset_of(&requested("content-type")));
+        assert!(allowed.is_superset_of(&requested("x-custom,CONTENT-TYPE")));
+        assert!(allowed.is_superset_of(&Vec::new().into_iter().collect()));
+        assert!(!allowed.is_superset_of(&requested("content-type, x-other")));
+
+        let any = AccessControlAllowHeaders::any();
+        assert!(any.is_superset_of(&requested("x-custom, x-other")));
+        assert!(!any.is_superset_of(&requested("x-custom, authorization")));
+
+        let with_auth = test_decode::<AccessControlAllowHeaders>(&["Authorization"]).unwrap();
+        assert!(with_auth.is_superset_of(&requested("authorization")));
+    }
+
     #[test]
     fn from_iter() {
         let allow: AccessControlAllowHeaders = vec![
@@ -85,6 +378,59 @@ mod tests {
 
         let headers = test_encode(allow);
         assert_eq!(headers["access-control-allow-headers"], "cache-control, if-range");
+        assert_eq!(headers.get_all("access-control-allow-headers").iter().count(), 1);
     }
-}
 
+    #[test]
+    fn extend() {
+        let mut allowed = test_decode::<AccessControlAllowHeaders>(&["X-Custom,  content-type"]).unwrap();
+        allowed.extend(vec![
+            HeaderName::from_static("x-custom"),
+            ::http::header::ACCEPT,
+            ::http::header::ACCEPT,
+        ]);
+        assert_eq!(allowed.iter().collect::<Vec<_>>(), ["x-custom", "content-type", "accept"]);
+        #[cfg(not(feature = "canonical"))]
+        assert_eq!(test_encode(allowed.clone())["access-control-allow-headers"], "x-custom, content-type, accept");
+
+        assert!(allowed.insert(::http::header::AUTHORIZATION));
+        assert!(!allowed.insert(::http::header::CONTENT_TYPE));
+        assert!(allowed.contains(&::http::header::AUTHORIZATION));
+
+        let mut unchanged = test_decode::<AccessControlAllowHeaders>(&["X-Custom,  content-type"]).unwrap();
+        unchanged.extend(vec![::http::header::CONTENT_TYPE]);
+        #[cfg(not(feature = "canonical"))]
+        assert_eq!(test_encode(unchanged)["access-control-allow-headers"], "X-Custom,  content-type");
+
+        let mut any = AccessControlAllowHeaders::any();
+        any.extend(vec![::http::header::ACCEPT]);
+        assert_eq!(any, AccessControlAllowHeaders::any());
+
+        // Authorization is listed next to `*`, which doesn't allow it.
+        assert!(any.insert(::http::header::AUTHORIZATION));
+        assert!(!any.insert(::http::header::AUTHORIZATION));
+        assert!(any.is_any());
+        assert!(any.contains(&::http::header::AUTHORIZATION));
+        assert!(any.contains_str("x-custom"));
+        assert_eq!(test_encode(any)["access-control-allow-headers"], "*, authorization");
+    }
+
+    #[test]
+    fn canonicalize() {
+        let mut allowed = test_decode::<AccessControlAllowHeaders>(&["X-Custom, content-type,x-custom"]).unwrap();
+        allowed.canonicalize();
+        assert_eq!(test_encode(allowed.clone())["access-control-allow-headers"], "content-type, x-custom");
+
+        let once = allowed.clone();
+        allowed.canonicalize();
+        assert_eq!(allowed, once);
+
+        let mut any = AccessControlAllowHeaders::any();
+        any.canonicalize();
+        assert_eq!(test_encode(any)["access-control-allow-headers"], "*");
+
+        let mut mixed = test_decode::<AccessControlAllowHeaders>(&["x-b, *, x-a"]).unwrap();
+        mixed.canonicalize();
+        assert_eq!(test_encode(mixed)["access-control-allow-headers"], "*, x-a, x-b");
+    }
+}
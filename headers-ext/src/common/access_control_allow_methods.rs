@@ -2,7 +2,9 @@ use std::iter::FromIterator;
 
 use http::Method;
 
-use util::FlatCsv;
+use util::{join_wildcard, split_wildcard, FlatCsv, ParsedCsv};
+use super::AccessControlRequestMethod;
+use super::allow::method_value;
 
 /// `Access-Control-Allow-Methods` header, part of
 /// [CORS](http://www.w3.org/TR/cors/#access-control-allow-methods-response-header)
@@ -19,6 +21,8 @@ use util::FlatCsv;
 ///
 /// # Example values
 /// * `PUT, DELETE, XMODIFY`
+/// * `*`
+/// * `*, DELETE`
 ///
 /// # Examples
 ///
@@ -31,19 +35,137 @@ use util::FlatCsv;
 /// let allow_methods = vec![Method::GET, Method::PUT]
 ///     .into_iter()
 ///     .collect::<AccessControlAllowMethods>();
+///
+/// let allow_any = AccessControlAllowMethods::any();
+/// assert!(allow_any.contains(&Method::DELETE));
+/// assert_eq!(allow_any.intersection(&allow_methods), allow_methods);
 /// ```
-#[derive(Clone, Debug, PartialEq, Header)]
-pub struct AccessControlAllowMethods(FlatCsv);
+///
+/// Methods are compared exactly, and repeated ones are dropped, keeping
+/// the first. In set operations `*` stands for every method.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccessControlAllowMethods(Inner);
+
+// `Any` holds the methods listed along with `*`, which requests with
+// credentials take as a literal method.
+#[derive(Clone, Debug, PartialEq)]
+enum Inner {
+    Any(ParsedCsv<Method>),
+    Methods(ParsedCsv<Method>),
+}
 
 impl AccessControlAllowMethods {
-    /// Returns an iterator over `Method`s contained within.
-    pub fn iter<'a>(&'a self) -> impl Iterator<Item = Method> + 'a {
-        self
-            .0
-            .iter()
-            .filter_map(|s| {
-                s.parse().ok()
-            })
+    /// Create an `Access-Control-Allow-Methods: *` header, allowing any
+    /// method in requests without credentials.
+    pub fn any() -> AccessControlAllowMethods {
+        AccessControlAllowMethods(Inner::Any(ParsedCsv::from_items(None, method_value)))
+    }
+
+    fn with_methods(any: bool, methods: ParsedCsv<Method>) -> AccessControlAllowMethods {
+        AccessControlAllowMethods(if any { Inner::Any(methods) } else { Inner::Methods(methods) })
+    }
+
+    /// Check if this has the `*` wildcard, alone or with listed methods.
+    pub fn is_any(&self) -> bool {
+        match self.0 {
+            Inner::Any(_) => true,
+            Inner::Methods(_) => false,
+        }
+    }
+
+    fn methods(&self) -> &ParsedCsv<Method> {
+        match self.0 {
+            Inner::Any(ref methods) | Inner::Methods(ref methods) => methods,
+        }
+    }
+
+    /// Returns an iterator over the `Method`s listed.
+    ///
+    /// A `*` wildcard isn't one of them, only the methods listed along
+    /// with it.
+    pub fn iter(&self) -> ::list::Iter<'_, Method> {
+        ::list::Iter::new(self.methods().iter())
+    }
+
+    fn into_items(self) -> ::list::IntoIter<Method> {
+        match self.0 {
+            Inner::Any(methods) | Inner::Methods(methods) => ::list::IntoIter::new(methods.into_iter()),
+        }
+    }
+
+    /// Returns an iterator over clones of the `Method`s contained within.
+    pub fn iter_cloned<'a>(&'a self) -> impl Iterator<Item = Method> + 'a {
+        self.iter().cloned()
+    }
+
+    /// Check if `method` is allowed, which is always true for `*`.
+    pub fn contains(&self, method: &Method) -> bool {
+        self.is_any() || self.iter().any(|m| m == method)
+    }
+
+    /// Sort the methods byte-wise and drop repeats, so equal sets always
+    /// encode the same. A `*` is kept in front.
+    ///
+    /// When the `canonical` feature is enabled, this is done on every
+    /// encode.
+    pub fn canonicalize(&mut self) {
+        match self.0 {
+            Inner::Any(ref mut methods) | Inner::Methods(ref mut methods) => {
+                *methods = methods.canonical(Method::as_str, method_value);
+            },
+        }
+    }
+
+    /// The methods allowed by both, in the order of `self`.
+    ///
+    /// `*` intersected with a list is that list.
+    pub fn intersection(&self, other: &AccessControlAllowMethods) -> AccessControlAllowMethods {
+        if self.is_any() {
+            other.clone()
+        } else {
+            self.iter().filter(|m| other.contains(m)).cloned().collect()
+        }
+    }
+
+    /// The methods allowed by either, those of `self` first.
+    ///
+    /// Anything joined with `*` has `*`, along with the methods both list.
+    pub fn union(&self, other: &AccessControlAllowMethods) -> AccessControlAllowMethods {
+        let methods = self.iter().chain(other.iter()).cloned();
+        let methods = ParsedCsv::from_items(methods, method_value).dedup(method_value);
+        AccessControlAllowMethods::with_methods(self.is_any() || other.is_any(), methods)
+    }
+}
+
+impl_collection!(AccessControlAllowMethods, Method, wildcard);
+
+impl ::Header for AccessControlAllowMethods {
+    fn name() -> &'static ::HeaderName {
+        &::http::header::ACCESS_CONTROL_ALLOW_METHODS
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        let csv: FlatCsv = FlatCsv::from_values(values)?;
+        let (any, csv) = split_wildcard(csv);
+        let methods = ParsedCsv::lossy(csv).dedup(method_value);
+        Some(AccessControlAllowMethods::with_methods(any, methods))
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        let value = if cfg!(feature = "canonical") {
+            (&self.methods().canonical(Method::as_str, method_value)).into()
+        } else {
+            self.methods().into()
+        };
+        values.append(if self.is_any() { join_wildcard(value) } else { value });
+    }
+}
+
+impl From<AccessControlRequestMethod> for AccessControlAllowMethods {
+    /// Allow only the method a preflight request asked for, echoing it
+    /// back.
+    fn from(requested: AccessControlRequestMethod) -> AccessControlAllowMethods {
+        Some(Method::from(requested)).into_iter().collect()
     }
 }
 
@@ -52,17 +174,8 @@ impl FromIterator<Method> for AccessControlAllowMethods {
     where
         I: IntoIterator<Item=Method>,
     {
-        let methods = iter
-            .into_iter()
-            .map(|method| {
-                method
-                    .as_str()
-                    .parse::<::HeaderValue>()
-                    .expect("Method is a valid HeaderValue")
-            })
-            .collect();
-
-        AccessControlAllowMethods(methods)
+        let methods = ParsedCsv::from_items(iter, method_value).dedup(method_value);
+        AccessControlAllowMethods(Inner::Methods(methods))
     }
 }
 
@@ -83,6 +196,23 @@ mod tests {
         assert_eq!(as_vec[1], Method::PUT);
     }
 
+    #[test]
+    fn any() {
+        let allowed = test_decode::<AccessControlAllowMethods>(&["*"]).unwrap();
+        assert!(allowed.is_any());
+        assert_eq!(allowed.iter().count(), 0);
+
+        let headers = test_encode(AccessControlAllowMethods::any());
+        assert_eq!(headers["access-control-allow-methods"], "*");
+
+        // A `*` member is the wildcard, with the other methods listed.
+        let mixed = test_decode::<AccessControlAllowMethods>(&["*, DELETE"]).unwrap();
+        assert!(mixed.is_any());
+        assert!(mixed.contains(&Method::PATCH));
+        assert_eq!(mixed.iter().collect::<Vec<_>>(), [Method::DELETE]);
+        assert_eq!(test_encode(mixed)["access-control-allow-methods"], "*, DELETE");
+    }
+
     #[test]
     fn from_iter() {
         let allow: AccessControlAllowMethods = vec![
@@ -92,6 +222,55 @@ mod tests {
 
         let headers = test_encode(allow);
         assert_eq!(headers["access-control-allow-methods"], "GET, PUT");
+        assert_eq!(headers.get_all("access-control-allow-methods").iter().count(), 1);
     }
-}
 
+    #[test]
+    fn dedup() {
+        let allowed = test_decode::<AccessControlAllowMethods>(&["GET, GET", "PUT, GET"]).unwrap();
+        assert_eq!(test_encode(allowed)["access-control-allow-methods"], "GET, PUT");
+    }
+
+    #[test]
+    fn set_operations() {
+        let xmodify = Method::from_bytes(b"XMODIFY").unwrap();
+        let decode = |s| test_decode::<AccessControlAllowMethods>(&[s]).unwrap();
+        let methods = |allowed: &AccessControlAllowMethods| {
+            allowed.iter().map(|m| m.as_str().to_owned()).collect::<Vec<_>>()
+        };
+
+        let ours = decode("PUT, XMODIFY, DELETE");
+        let theirs = decode("xmodify, DELETE, GET");
+        assert!(ours.contains(&xmodify));
+        assert!(!theirs.contains(&xmodify));
+
+        assert_eq!(methods(&ours.intersection(&theirs)), ["DELETE"]);
+        assert_eq!(methods(&ours.union(&theirs)), ["PUT", "XMODIFY", "DELETE", "xmodify", "GET"]);
+        assert!(ours.intersection(&decode("GET")).is_empty());
+        assert!(!ours.is_empty());
+
+        let any = AccessControlAllowMethods::any();
+        assert!(any.contains(&xmodify));
+        assert!(!any.is_empty());
+        assert_eq!(any.intersection(&ours), ours);
+        assert_eq!(ours.intersection(&any), ours);
+        assert!(any.union(&ours).is_any());
+        assert!(ours.union(&any).is_any());
+        assert_eq!(methods(&ours.union(&any)), ["PUT", "XMODIFY", "DELETE"]);
+    }
+
+    #[test]
+    fn canonicalize() {
+        let mut allowed = test_decode::<AccessControlAllowMethods>(&["PUT, GET", "PUT"]).unwrap();
+        allowed.canonicalize();
+        assert_eq!(test_encode(allowed.clone())["access-control-allow-methods"], "GET, PUT");
+
+        let once = allowed.clone();
+        allowed.canonicalize();
+        assert_eq!(allowed, once);
+
+        let mut any = AccessControlAllowMethods::any();
+        any.canonicalize();
+        assert!(any.is_any());
+    }
+}
@@ -1,6 +1,6 @@
 use headers_core::decode::TryFromValues;
 use ::{HeaderValue};
-use super::origin::{Origin};
+use super::origin::{Origin, OriginPattern};
 
 /// The `Access-Control-Allow-Origin` response header,
 /// part of [CORS](http://www.w3.org/TR/cors/#access-control-allow-origin-response-header)
@@ -44,6 +44,43 @@ impl AccessControlAllowOrigin {
     pub const ANY: AccessControlAllowOrigin = AccessControlAllowOrigin(OriginOrAny::Any);
     /// `Access-Control-Allow-Origin: null`
     pub const NULL: AccessControlAllowOrigin = AccessControlAllowOrigin(OriginOrAny::Origin(Origin::NULL));
+
+    /// Allow `origin`, echoing it back, if it matches any of `patterns`.
+    ///
+    /// The `null` origin is never allowed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # extern crate headers_ext as headers;
+    /// use headers::{AccessControlAllowOrigin, Origin, OriginPattern};
+    ///
+    /// let allowed = [OriginPattern::parse("https://*.example.com").unwrap()];
+    ///
+    /// let origin = "https://app.example.com".parse::<Origin>().unwrap();
+    /// let allow = AccessControlAllowOrigin::echo_if_allowed(&origin, &allowed).unwrap();
+    /// assert_eq!(allow.to_string(), "https://app.example.com");
+    ///
+    /// let origin = "https://evil.com".parse::<Origin>().unwrap();
+    /// assert_eq!(AccessControlAllowOrigin::echo_if_allowed(&origin, &allowed), None);
+    /// ```
+    pub fn echo_if_allowed(
+        origin: &Origin,
+        patterns: &[OriginPattern],
+    ) -> Option<AccessControlAllowOrigin> {
+        if patterns.iter().any(|pattern| origin.matches(pattern)) {
+            Some(AccessControlAllowOrigin(OriginOrAny::Origin(origin.clone())))
+        } else {
+            None
+        }
+    }
+}
+
+impl From<Origin> for AccessControlAllowOrigin {
+    /// Allow `origin` only, echoing it back.
+    fn from(origin: Origin) -> AccessControlAllowOrigin {
+        AccessControlAllowOrigin(OriginOrAny::Origin(origin))
+    }
 }
 
 impl TryFromValues for OriginOrAny {
@@ -62,9 +99,45 @@ impl TryFromValues for OriginOrAny {
 impl<'a> From<&'a OriginOrAny> for HeaderValue {
     fn from(origin: &'a OriginOrAny) -> HeaderValue {
         match origin {
-            OriginOrAny::Origin(ref origin) => origin.into_value(),
+            OriginOrAny::Origin(ref origin) => origin.to_value(),
             OriginOrAny::Any => HeaderValue::from_static("*"),
         }
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::AccessControlAllowOrigin;
+    use super::super::{test_decode, test_encode};
+    use {Origin, OriginPattern};
+
+    #[test]
+    fn decode() {
+        assert_eq!(test_decode::<AccessControlAllowOrigin>(&["*"]), Some(AccessControlAllowOrigin::ANY));
+        assert_eq!(test_decode::<AccessControlAllowOrigin>(&["null"]), Some(AccessControlAllowOrigin::NULL));
+        let allow = test_decode::<AccessControlAllowOrigin>(&["https://example.com"]).unwrap();
+        assert_eq!(test_encode(allow)["access-control-allow-origin"], "https://example.com");
+    }
+
+    #[test]
+    fn echo_if_allowed() {
+        let patterns = ["https://example.com", "https://*.example.com", "http://localhost:3000"]
+            .iter()
+            .map(|p| OriginPattern::parse(p).unwrap())
+            .collect::<Vec<_>>();
+        let echo = |s: &str| {
+            let origin = s.parse::<Origin>().unwrap();
+            AccessControlAllowOrigin::echo_if_allowed(&origin, &patterns).map(|allow| allow.to_string())
+        };
+
+        assert_eq!(echo("https://example.com").as_deref(), Some("https://example.com"));
+        assert_eq!(echo("https://a.example.com:443").as_deref(), Some("https://a.example.com:443"));
+        assert_eq!(echo("http://localhost:3000").as_deref(), Some("http://localhost:3000"));
+        assert_eq!(echo("http://localhost"), None);
+        assert_eq!(echo("http://example.com"), None);
+        assert_eq!(echo("null"), None);
+        assert_eq!(AccessControlAllowOrigin::echo_if_allowed(&Origin::NULL, &patterns), None);
+        assert_eq!(AccessControlAllowOrigin::echo_if_allowed(&"https://example.com".parse().unwrap(), &[]), None);
+    }
+}
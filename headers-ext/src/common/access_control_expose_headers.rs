@@ -1,7 +1,7 @@
 use std::iter::FromIterator;
 
 use {HeaderName, HeaderValue};
-use util::FlatCsv;
+use util::{join_wildcard, parse_item, split_wildcard, FlatCsv, ParsedCsv};
 
 /// `Access-Control-Expose-Headers` header, part of
 /// [CORS](http://www.w3.org/TR/cors/#access-control-expose-headers-response-header)
@@ -17,6 +17,8 @@ use util::FlatCsv;
 ///
 /// # Example values
 /// * `ETag, Content-Length`
+/// * `*`
+/// * `*, ETag`
 ///
 /// # Examples
 ///
@@ -30,20 +32,144 @@ use util::FlatCsv;
 /// let expose = vec![CONTENT_LENGTH, ETAG]
 ///     .into_iter()
 ///     .collect::<AccessControlExposeHeaders>();
+/// assert!(expose.contains_str("ETag"));
+///
+/// let expose_any = AccessControlExposeHeaders::any();
 /// # }
 /// ```
-#[derive(Clone, Debug, Header)]
-pub struct AccessControlExposeHeaders(FlatCsv);
+#[derive(Clone, Debug)]
+pub struct AccessControlExposeHeaders(Inner);
+
+// `Any` holds the names listed along with `*`, which requests with
+// credentials take as a literal name.
+#[derive(Clone, Debug)]
+enum Inner {
+    Any(ParsedCsv<HeaderName>),
+    Names(ParsedCsv<HeaderName>),
+}
 
 impl AccessControlExposeHeaders {
-    /// Returns an iterator over `HeaderName`s contained within.
-    pub fn iter<'a>(&'a self) -> impl Iterator<Item = HeaderName> + 'a {
-        self
-            .0
-            .iter()
-            .filter_map(|s| {
-                s.parse().ok()
-            })
+    /// Create an `Access-Control-Expose-Headers: *` header, exposing all
+    /// headers of responses to requests without credentials.
+    pub fn any() -> AccessControlExposeHeaders {
+        AccessControlExposeHeaders(Inner::Any(ParsedCsv::from_items(None, name_value)))
+    }
+
+    fn with_names(any: bool, names: ParsedCsv<HeaderName>) -> AccessControlExposeHeaders {
+        AccessControlExposeHeaders(if any { Inner::Any(names) } else { Inner::Names(names) })
+    }
+
+    /// Check if this has the `*` wildcard, alone or with listed names.
+    pub fn is_any(&self) -> bool {
+        match self.0 {
+            Inner::Any(_) => true,
+            Inner::Names(_) => false,
+        }
+    }
+
+    fn names(&self) -> &ParsedCsv<HeaderName> {
+        match self.0 {
+            Inner::Any(ref names) | Inner::Names(ref names) => names,
+        }
+    }
+
+    /// Returns an iterator over the `HeaderName`s listed.
+    ///
+    /// A `*` wildcard isn't one of them, only the names listed along with
+    /// it.
+    pub fn iter(&self) -> ::list::Iter<'_, HeaderName> {
+        ::list::Iter::new(self.names().iter())
+    }
+
+    fn into_items(self) -> ::list::IntoIter<HeaderName> {
+        match self.0 {
+            Inner::Any(names) | Inner::Names(names) => ::list::IntoIter::new(names.into_iter()),
+        }
+    }
+
+    /// Returns an iterator over clones of the `HeaderName`s contained
+    /// within.
+    pub fn iter_cloned<'a>(&'a self) -> impl Iterator<Item = HeaderName> + 'a {
+        self.iter().cloned()
+    }
+
+    /// Check if `name` is exposed, which is always true for `*`.
+    pub fn contains(&self, name: &HeaderName) -> bool {
+        self.is_any() || self.iter().any(|have| have == name)
+    }
+
+    /// Check if the name `name` is exposed, in any case, like `contains`.
+    ///
+    /// An invalid name is never exposed.
+    pub fn contains_str(&self, name: &str) -> bool {
+        parse_item(name).is_some_and(|name| self.contains(&name))
+    }
+
+    /// Sort the names byte-wise and drop repeats, so equal sets always
+    /// encode the same. A `*` is kept in front.
+    ///
+    /// When the `canonical` feature is enabled, this is done on every
+    /// encode.
+    pub fn canonicalize(&mut self) {
+        match self.0 {
+            Inner::Any(ref mut names) | Inner::Names(ref mut names) => {
+                *names = names.canonical(HeaderName::as_str, name_value);
+            },
+        }
+    }
+
+    /// Expose `name` as well, returning whether it wasn't already.
+    ///
+    /// `*` absorbs it.
+    pub fn insert(&mut self, name: HeaderName) -> bool {
+        if self.contains(&name) {
+            return false;
+        }
+        self.merge(Some(name).into_iter().collect());
+        true
+    }
+
+    /// Expose the headers of `other` as well, keeping the order of both.
+    ///
+    /// The result has `*` if either has it, and the names listed by both.
+    pub fn merge(&mut self, other: AccessControlExposeHeaders) {
+        let any = self.is_any() || other.is_any();
+        let names = if other.iter().all(|name| self.iter().any(|have| have == name)) {
+            self.names().clone()
+        } else {
+            let names = self.iter().chain(other.iter()).cloned();
+            ParsedCsv::from_items(names, name_value).dedup(name_value)
+        };
+        *self = AccessControlExposeHeaders::with_names(any, names);
+    }
+}
+
+impl_collection!(AccessControlExposeHeaders, HeaderName, wildcard);
+
+impl ::MergeableHeader for AccessControlExposeHeaders {
+    fn merge(&mut self, other: AccessControlExposeHeaders) {
+        AccessControlExposeHeaders::merge(self, other);
+    }
+}
+
+impl ::Header for AccessControlExposeHeaders {
+    fn name() -> &'static ::HeaderName {
+        &::http::header::ACCESS_CONTROL_EXPOSE_HEADERS
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        let csv: FlatCsv = FlatCsv::from_values(values)?;
+        let (any, csv) = split_wildcard(csv);
+        Some(AccessControlExposeHeaders::with_names(any, ParsedCsv::lossy(csv)))
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        let value = if cfg!(feature = "canonical") {
+            (&self.names().canonical(HeaderName::as_str, name_value)).into()
+        } else {
+            self.names().into()
+        };
+        values.append(if self.is_any() { join_wildcard(value) } else { value });
     }
 }
 
@@ -52,14 +178,25 @@ impl FromIterator<HeaderName> for AccessControlExposeHeaders {
     where
         I: IntoIterator<Item = HeaderName>,
     {
-        let flat = iter
-            .into_iter()
-            .map(HeaderValue::from)
-            .collect();
-        AccessControlExposeHeaders(flat)
+        let names = ParsedCsv::from_items(iter, name_value);
+        AccessControlExposeHeaders(Inner::Names(names))
     }
 }
 
+impl Extend<HeaderName> for AccessControlExposeHeaders {
+    /// Expose the names of `iter` as well, like `merge`.
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = HeaderName>,
+    {
+        self.merge(iter.into_iter().collect());
+    }
+}
+
+fn name_value(name: &HeaderName) -> HeaderValue {
+    name.clone().into()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -77,6 +214,40 @@ mod tests {
         assert_eq!(as_vec[1], "bar");
     }
 
+    #[test]
+    fn any() {
+        let expose_headers = test_decode::<AccessControlExposeHeaders>(&["*"]).unwrap();
+        assert!(expose_headers.is_any());
+        assert_eq!(expose_headers.iter().count(), 0);
+
+        let headers = test_encode(AccessControlExposeHeaders::any());
+        assert_eq!(headers["access-control-expose-headers"], "*");
+
+        // A `*` member is the wildcard, with the other names listed.
+        let mixed = test_decode::<AccessControlExposeHeaders>(&["etag, *"]).unwrap();
+        assert!(mixed.is_any());
+        assert!(mixed.contains(&::http::header::SERVER));
+        assert_eq!(mixed.iter().collect::<Vec<_>>(), ["etag"]);
+        assert_eq!(test_encode(mixed)["access-control-expose-headers"], "*, etag");
+    }
+
+    #[test]
+    fn contains() {
+        let expose = test_decode::<AccessControlExposeHeaders>(&["ETag, X-Request-Id"]).unwrap();
+        assert!(expose.contains(&::http::header::ETAG));
+        assert!(expose.contains(&HeaderName::from_static("x-request-id")));
+        assert!(!expose.contains(&::http::header::SERVER));
+        assert!(expose.contains_str("x-request-id"));
+        assert!(expose.contains_str("X-REQUEST-ID"));
+        assert!(!expose.contains_str("x-request"));
+        assert!(!expose.contains_str("e tag"));
+
+        let any = AccessControlExposeHeaders::any();
+        assert!(any.contains(&::http::header::SERVER));
+        assert!(any.contains_str("Server"));
+        assert!(!any.contains_str(""));
+    }
+
     #[test]
     fn from_iter() {
         let expose: AccessControlExposeHeaders = vec![
@@ -86,6 +257,60 @@ mod tests {
 
         let headers = test_encode(expose);
         assert_eq!(headers["access-control-expose-headers"], "cache-control, if-range");
+        assert_eq!(headers.get_all("access-control-expose-headers").iter().count(), 1);
     }
-}
 
+    #[test]
+    fn merge() {
+        let decode = |s| test_decode::<AccessControlExposeHeaders>(&[s]).unwrap();
+        let encode = |expose| test_encode(expose)["access-control-expose-headers"].clone();
+
+        let mut expose = decode("etag, x-request-id");
+        expose.merge(decode("X-Request-Id, x-trace, x-trace"));
+        assert_eq!(encode(expose), "etag, x-request-id, x-trace");
+
+        // Canonical encoding joins the names again.
+        let mut unchanged = decode("etag,  x-request-id");
+        unchanged.merge(decode("etag"));
+        #[cfg(not(feature = "canonical"))]
+        assert_eq!(encode(unchanged), "etag,  x-request-id");
+
+        // The names listed along with `*` are kept.
+        let mut any = AccessControlExposeHeaders::any();
+        any.merge(decode("etag"));
+        assert!(any.is_any());
+        assert_eq!(encode(any), "*, etag");
+
+        let mut names = decode("etag");
+        names.merge(AccessControlExposeHeaders::any());
+        assert!(names.is_any());
+        assert_eq!(encode(names), "*, etag");
+    }
+
+    #[test]
+    fn extend() {
+        let mut expose = test_decode::<AccessControlExposeHeaders>(&["ETag"]).unwrap();
+        expose.extend(vec![::http::header::SERVER, ::http::header::ETAG, ::http::header::SERVER]);
+        assert_eq!(test_encode(expose.clone())["access-control-expose-headers"], "etag, server");
+
+        assert!(expose.insert(HeaderName::from_static("x-request-id")));
+        assert!(!expose.insert(::http::header::SERVER));
+        assert_eq!(expose.iter().collect::<Vec<_>>(), ["etag", "server", "x-request-id"]);
+
+        let mut any = AccessControlExposeHeaders::any();
+        any.extend(vec![::http::header::ETAG]);
+        assert!(!any.insert(::http::header::SERVER));
+        assert!(any.is_any());
+    }
+
+    #[test]
+    fn canonicalize() {
+        let mut expose = test_decode::<AccessControlExposeHeaders>(&["x-b, etag", "X-B"]).unwrap();
+        expose.canonicalize();
+        assert_eq!(test_encode(expose.clone())["access-control-expose-headers"], "etag, x-b");
+
+        let once = expose.clone();
+        expose.canonicalize();
+        assert_eq!(expose.iter().collect::<Vec<_>>(), once.iter().collect::<Vec<_>>());
+    }
+}
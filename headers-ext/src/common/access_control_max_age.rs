@@ -26,6 +26,7 @@ use util::Seconds;
 /// use headers::AccessControlMaxAge;
 ///
 /// let max_age = AccessControlMaxAge::from(Duration::from_secs(531));
+/// assert_eq!(Duration::from(max_age), Duration::from_secs(531));
 /// ```
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Header)]
 pub struct AccessControlMaxAge(Seconds);
@@ -35,3 +36,9 @@ impl From<Duration> for AccessControlMaxAge {
         AccessControlMaxAge(dur.into())
     }
 }
+
+impl From<AccessControlMaxAge> for Duration {
+    fn from(acma: AccessControlMaxAge) -> Duration {
+        acma.0.into()
+    }
+}
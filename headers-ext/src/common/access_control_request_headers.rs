@@ -1,7 +1,9 @@
 use std::iter::FromIterator;
 
+use headers_core::decode::TryFromValues;
+
 use {HeaderName, HeaderValue};
-use util::FlatCsv;
+use util::{parse_item, ParsedCsv};
 
 /// `Access-Control-Request-Headers` header, part of
 /// [CORS](http://www.w3.org/TR/cors/#access-control-request-headers-request-header)
@@ -31,36 +33,82 @@ use util::FlatCsv;
 /// let req_headers = vec![ACCEPT_LANGUAGE, DATE]
 ///     .into_iter()
 ///     .collect::<AccessControlRequestHeaders>();
+/// assert!(req_headers.contains(&DATE));
 /// # }
 /// ```
-#[derive(Clone, Debug, Header)]
-pub struct AccessControlRequestHeaders(FlatCsv);
+#[derive(Clone, Debug)]
+pub struct AccessControlRequestHeaders(ParsedCsv<HeaderName>);
+
+impl ::Header for AccessControlRequestHeaders {
+    fn name() -> &'static ::HeaderName {
+        &::http::header::ACCESS_CONTROL_REQUEST_HEADERS
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        TryFromValues::try_from_values(values).map(AccessControlRequestHeaders)
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        if cfg!(feature = "canonical") {
+            values.append((&self.0.canonical(HeaderName::as_str, name_value)).into());
+        } else {
+            values.append((&self.0).into());
+        }
+    }
+}
 
 impl AccessControlRequestHeaders {
     /// Returns an iterator over `HeaderName`s contained within.
-    pub fn iter<'a>(&'a self) -> impl Iterator<Item = HeaderName> + 'a {
-        self
-            .0
-            .iter()
-            .filter_map(|s| {
-                s.parse().ok()
-            })
+    pub fn iter(&self) -> ::list::Iter<'_, HeaderName> {
+        ::list::Iter::new(self.0.iter())
+    }
+
+    fn into_items(self) -> ::list::IntoIter<HeaderName> {
+        ::list::IntoIter::new(self.0.into_iter())
+    }
+
+    /// Returns an iterator over clones of the `HeaderName`s contained
+    /// within.
+    pub fn iter_cloned<'a>(&'a self) -> impl Iterator<Item = HeaderName> + 'a {
+        self.iter().cloned()
+    }
+
+    /// Check if `name` will be used in the actual request.
+    pub fn contains(&self, name: &HeaderName) -> bool {
+        self.iter().any(|have| have == name)
+    }
+
+    /// Check if the name `name` will be used in the actual request, in any
+    /// case, like `contains`.
+    pub fn contains_str(&self, name: &str) -> bool {
+        parse_item(name).is_some_and(|name| self.contains(&name))
+    }
+
+    /// Sort the names byte-wise and drop repeats, so equal sets always
+    /// encode the same.
+    ///
+    /// When the `canonical` feature is enabled, this is done on every
+    /// encode.
+    pub fn canonicalize(&mut self) {
+        self.0 = self.0.canonical(HeaderName::as_str, name_value);
     }
 }
 
+impl_collection!(AccessControlRequestHeaders, HeaderName);
+
 impl FromIterator<HeaderName> for AccessControlRequestHeaders {
     fn from_iter<I>(iter: I) -> Self
     where
         I: IntoIterator<Item = HeaderName>,
     {
-        let flat = iter
-            .into_iter()
-            .map(HeaderValue::from)
-            .collect();
-        AccessControlRequestHeaders(flat)
+        AccessControlRequestHeaders(ParsedCsv::from_items(iter, name_value))
     }
 }
 
+fn name_value(name: &HeaderName) -> HeaderValue {
+    name.clone().into()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -78,6 +126,16 @@ mod tests {
         assert_eq!(as_vec[1], "bar");
     }
 
+    #[test]
+    fn contains() {
+        let req_headers = test_decode::<AccessControlRequestHeaders>(&["X-Custom, Content-Type"]).unwrap();
+        assert!(req_headers.contains(&::http::header::CONTENT_TYPE));
+        assert!(!req_headers.contains(&::http::header::ACCEPT));
+        assert!(req_headers.contains_str("x-custom"));
+        assert!(req_headers.contains_str("Content-TYPE"));
+        assert!(!req_headers.contains_str("x-custom,"));
+    }
+
     #[test]
     fn from_iter() {
         let req_headers: AccessControlRequestHeaders = vec![
@@ -87,6 +145,20 @@ mod tests {
 
         let headers = test_encode(req_headers);
         assert_eq!(headers["access-control-request-headers"], "cache-control, if-range");
+        assert_eq!(headers.get_all("access-control-request-headers").iter().count(), 1);
     }
-}
 
+    #[test]
+    fn canonicalize() {
+        let mut req_headers = test_decode::<AccessControlRequestHeaders>(&["x-b, Content-Type, x-a, x-b"]).unwrap();
+        req_headers.canonicalize();
+        assert_eq!(
+            test_encode(req_headers.clone())["access-control-request-headers"],
+            "content-type, x-a, x-b",
+        );
+
+        let once = req_headers.iter_cloned().collect::<Vec<_>>();
+        req_headers.canonicalize();
+        assert_eq!(req_headers.iter_cloned().collect::<Vec<_>>(), once);
+    }
+}
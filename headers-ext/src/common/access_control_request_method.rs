@@ -24,12 +24,15 @@ use ::{Header, HeaderName, HeaderValue};
 /// use http::Method;
 ///
 /// let req_method = AccessControlRequestMethod::from(Method::GET);
+/// assert_eq!(req_method.method(), &Method::GET);
 /// ```
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct AccessControlRequestMethod(Method);
 
 impl Header for AccessControlRequestMethod {
-    const NAME: &'static HeaderName = &::http::header::ACCESS_CONTROL_REQUEST_METHOD;
+    fn name() -> &'static HeaderName {
+        &::http::header::ACCESS_CONTROL_REQUEST_METHOD
+    }
 
     fn decode(values: &mut ::Values) -> Option<Self> {
         values.next()
@@ -58,9 +61,22 @@ impl Header for AccessControlRequestMethod {
     }
 }
 
+impl AccessControlRequestMethod {
+    /// Get the method the actual request will use.
+    pub fn method(&self) -> &Method {
+        &self.0
+    }
+}
+
 impl From<Method> for AccessControlRequestMethod {
     fn from(method: Method) -> AccessControlRequestMethod {
         AccessControlRequestMethod(method)
     }
 }
 
+impl From<AccessControlRequestMethod> for Method {
+    fn from(header: AccessControlRequestMethod) -> Method {
+        header.0
+    }
+}
+
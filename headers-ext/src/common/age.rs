@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+use util::Seconds;
+
+/// `Age` header, defined in [RFC7234](https://tools.ietf.org/html/rfc7234#section-5.1)
+///
+/// The `Age` header field conveys the sender's estimate of the amount of
+/// time since the response was generated or successfully validated at
+/// the origin server.
+///
+/// # ABNF
+///
+/// ```text
+/// Age = delta-seconds
+/// ```
+///
+/// # Example values
+///
+/// * `60`
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use std::time::Duration;
+/// use headers::Age;
+///
+/// let age = Age::from(Duration::from_secs(60));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Header)]
+pub struct Age(Seconds);
+
+impl From<Duration> for Age {
+    fn from(dur: Duration) -> Age {
+        Age(dur.into())
+    }
+}
+
+impl From<Age> for Duration {
+    fn from(age: Age) -> Duration {
+        age.0.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use super::Age;
+    use super::super::{test_decode, test_encode};
+
+    #[test]
+    fn decode() {
+        assert_eq!(
+            test_decode::<Age>(&["60"]),
+            Some(Age::from(Duration::from_secs(60))),
+        );
+        assert_eq!(test_decode::<Age>(&["-1"]), None);
+        assert_eq!(test_decode::<Age>(&["+60"]), None);
+        assert_eq!(
+            test_decode::<Age>(&["99999999999999999999"]),
+            Some(Age::from(Duration::from_secs(u64::MAX))),
+        );
+    }
+
+    #[test]
+    fn encode() {
+        let headers = test_encode(Age::from(Duration::from_secs(3600)));
+        assert_eq!(headers["age"], "3600");
+    }
+}
@@ -2,7 +2,7 @@ use std::iter::FromIterator;
 
 use http::Method;
 
-use util::FlatCsv;
+use util::{FlatCsv, ParsedCsv};
 
 /// `Allow` header, defined in [RFC7231](http://tools.ietf.org/html/rfc7231#section-7.4.1)
 ///
@@ -33,19 +33,98 @@ use util::FlatCsv;
 /// let allow = vec![Method::GET, Method::POST]
 ///     .into_iter()
 ///     .collect::<Allow>();
+///
+/// let route = vec![Method::GET, Method::PUT].into_iter().collect::<Allow>();
+/// assert!(allow.contains(&Method::POST));
+/// assert_eq!(allow.intersection(&route).iter().collect::<Vec<_>>(), [Method::GET]);
 /// ```
-#[derive(Clone, Debug, PartialEq, Header)]
-pub struct Allow(FlatCsv);
+///
+/// Methods are compared exactly, so `get` is an extension method distinct
+/// from `GET`. Repeated methods are dropped, keeping the first.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Allow(ParsedCsv<Method>);
+
+impl ::Header for Allow {
+    fn name() -> &'static ::HeaderName {
+        &::http::header::ALLOW
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        // Unlike most lists, an empty `Allow` means no methods are allowed.
+        let csv: FlatCsv = FlatCsv::from_values(values)?;
+        Some(Allow(ParsedCsv::lossy(csv).dedup(method_value)))
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        if cfg!(feature = "canonical") {
+            values.append((&self.0.canonical(Method::as_str, method_value)).into());
+        } else {
+            values.append((&self.0).into());
+        }
+    }
+}
 
 impl Allow {
     /// Returns an iterator over `Method`s contained within.
-    pub fn iter<'a>(&'a self) -> impl Iterator<Item = Method> + 'a {
-        self
-            .0
-            .iter()
-            .filter_map(|s| {
-                s.parse().ok()
-            })
+    pub fn iter(&self) -> ::list::Iter<'_, Method> {
+        ::list::Iter::new(self.0.iter())
+    }
+
+    fn into_items(self) -> ::list::IntoIter<Method> {
+        ::list::IntoIter::new(self.0.into_iter())
+    }
+
+    /// Returns an iterator over clones of the `Method`s contained within.
+    pub fn iter_cloned<'a>(&'a self) -> impl Iterator<Item = Method> + 'a {
+        self.iter().cloned()
+    }
+
+    /// Check if `method` is allowed.
+    pub fn contains(&self, method: &Method) -> bool {
+        self.iter().any(|m| m == method)
+    }
+
+    /// The methods allowed by both, in the order of `self`.
+    pub fn intersection(&self, other: &Allow) -> Allow {
+        self.iter().filter(|m| other.contains(m)).cloned().collect()
+    }
+
+    /// The methods allowed by either, those of `self` first.
+    pub fn union(&self, other: &Allow) -> Allow {
+        self.iter().chain(other.iter()).cloned().collect()
+    }
+
+    /// Sort the methods byte-wise and drop repeats, so equal sets always
+    /// encode the same.
+    ///
+    /// When the `canonical` feature is enabled, this is done on every
+    /// encode.
+    pub fn canonicalize(&mut self) {
+        self.0 = self.0.canonical(Method::as_str, method_value);
+    }
+
+    /// Allow `method` as well, returning whether it wasn't already.
+    pub fn insert(&mut self, method: Method) -> bool {
+        if self.contains(&method) {
+            return false;
+        }
+        self.merge(Some(method).into_iter().collect());
+        true
+    }
+
+    /// Allow the methods of `other` as well, like `union`.
+    pub fn merge(&mut self, other: Allow) {
+        if !other.iter().all(|m| self.contains(m)) {
+            *self = self.union(&other);
+        }
+    }
+}
+
+impl_collection!(Allow, Method);
+
+impl ::MergeableHeader for Allow {
+    fn merge(&mut self, other: Allow) {
+        Allow::merge(self, other);
     }
 }
 
@@ -54,16 +133,124 @@ impl FromIterator<Method> for Allow {
     where
         I: IntoIterator<Item = Method>,
     {
-        let flat = iter
-            .into_iter()
-            .map(|method| {
-                method
-                    .as_str()
-                    .parse::<::HeaderValue>()
-                    .expect("Method is a valid HeaderValue")
-            })
-            .collect();
-        Allow(flat)
+        Allow(ParsedCsv::from_items(iter, method_value).dedup(method_value))
+    }
+}
+
+impl Extend<Method> for Allow {
+    /// Allow the methods of `iter` as well, dropping those already allowed.
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = Method>,
+    {
+        self.merge(iter.into_iter().collect());
     }
 }
 
+pub(super) fn method_value(method: &Method) -> ::HeaderValue {
+    method
+        .as_str()
+        .parse()
+        .expect("Method is a valid HeaderValue")
+}
+
+#[cfg(test)]
+mod tests {
+    use http::Method;
+    use super::Allow;
+    use super::super::{test_decode, test_encode};
+
+    fn allow(s: &str) -> Allow {
+        test_decode(&[s]).unwrap()
+    }
+
+    fn methods(allow: &Allow) -> Vec<&str> {
+        allow.iter().map(Method::as_str).collect()
+    }
+
+    #[test]
+    fn iter_borrows() {
+        let allow: Allow = vec![Method::GET, Method::HEAD].into_iter().collect();
+        assert_eq!(allow.iter().collect::<Vec<&Method>>(), [&Method::GET, &Method::HEAD]);
+        assert!(allow.iter().any(|m| m == Method::HEAD));
+        assert_eq!(allow.iter_cloned().collect::<Vec<Method>>(), [Method::GET, Method::HEAD]);
+    }
+
+    #[test]
+    fn from_iter_encodes_one_line() {
+        let allow: Allow = vec![Method::GET, Method::HEAD, Method::OPTIONS].into_iter().collect();
+
+        let headers = test_encode(allow);
+        assert_eq!(headers["allow"], "GET, HEAD, OPTIONS");
+        assert_eq!(headers.get_all("allow").iter().count(), 1);
+    }
+
+    #[test]
+    fn dedup() {
+        assert_eq!(test_encode(allow("GET, GET"))["allow"], "GET");
+        assert_eq!(test_encode(allow("GET, HEAD, GET, get"))["allow"], "GET, HEAD, get");
+
+        let collected = vec![Method::GET, Method::GET, Method::PUT].into_iter().collect::<Allow>();
+        assert_eq!(test_encode(collected)["allow"], "GET, PUT");
+    }
+
+    #[test]
+    fn set_operations() {
+        let route = allow("GET, HEAD, PURGE, get");
+        let config = allow("GET, POST, PURGE");
+
+        assert!(route.contains(&Method::GET));
+        assert!(route.contains(&Method::from_bytes(b"PURGE").unwrap()));
+        assert!(route.contains(&Method::from_bytes(b"get").unwrap()));
+        assert!(!route.contains(&Method::from_bytes(b"purge").unwrap()));
+        assert!(!route.contains(&Method::POST));
+
+        assert_eq!(methods(&route.intersection(&config)), ["GET", "PURGE"]);
+        assert_eq!(methods(&config.intersection(&route)), ["GET", "PURGE"]);
+        assert_eq!(methods(&route.union(&config)), ["GET", "HEAD", "PURGE", "get", "POST"]);
+        #[cfg(not(feature = "canonical"))]
+        assert_eq!(test_encode(route.union(&config))["allow"], "GET, HEAD, PURGE, get, POST");
+
+        let none = route.intersection(&allow("PUT"));
+        assert!(none.is_empty());
+        assert_eq!(test_encode(none)["allow"], "");
+        assert!(allow("").is_empty());
+        assert!(!route.is_empty());
+    }
+
+    #[test]
+    fn extend() {
+        let mut allowed = allow("GET, HEAD");
+        allowed.extend(vec![Method::POST, Method::GET, Method::POST, Method::from_bytes(b"get").unwrap()]);
+        assert_eq!(test_encode(allowed.clone())["allow"], "GET, HEAD, POST, get");
+
+        assert!(allowed.insert(Method::PUT));
+        assert!(!allowed.insert(Method::HEAD));
+        assert_eq!(methods(&allowed), ["GET", "HEAD", "POST", "get", "PUT"]);
+
+        let mut unchanged = allow("GET,  HEAD");
+        unchanged.extend(vec![Method::HEAD]);
+        #[cfg(not(feature = "canonical"))]
+        assert_eq!(test_encode(unchanged)["allow"], "GET,  HEAD");
+
+        let mut empty = allow("");
+        assert!(empty.insert(Method::GET));
+        assert_eq!(test_encode(empty)["allow"], "GET");
+    }
+
+    #[test]
+    fn canonicalize() {
+        let mut methods = allow("PUT, get, GET,PUT, DELETE");
+        methods.canonicalize();
+        assert_eq!(test_encode(methods.clone())["allow"], "DELETE, GET, PUT, get");
+
+        let once = methods.clone();
+        methods.canonicalize();
+        assert_eq!(methods, once);
+
+        let mut empty = allow("");
+        empty.canonicalize();
+        assert!(empty.is_empty());
+        assert_eq!(test_encode(empty)["allow"], "");
+    }
+}
@@ -2,7 +2,7 @@ use base64;
 use bytes::Bytes;
 
 use {HeaderValue};
-use util::HeaderValueString;
+use util::{constant_time, HeaderString};
 
 /// `Authorization` header, defined in [RFC7235](https://tools.ietf.org/html/rfc7235#section-4.2)
 ///
@@ -30,15 +30,26 @@ use util::HeaderValueString;
 ///
 /// let basic = Authorization::basic("Aladdin", "open sesame");
 /// let bearer = Authorization::bearer("some-opaque-token").unwrap();
+///
+/// assert!(basic.secure_eq("Aladdin", "open sesame"));
+/// assert!(bearer.secure_eq("some-opaque-token"));
 /// ```
 ///
+/// # Comparing secrets
+///
+/// Checking credentials against the expected ones with `==` on `&str`
+/// takes longer the more of them match, which can leak a secret to a
+/// client timing its attempts. `secure_eq` compares in time that only
+/// depends on the lengths, and `==` on `Basic` and `Bearer` does the same.
+/// This is best-effort, as compilers and CPUs give no guarantees.
+///
 #[derive(Clone, PartialEq, Debug)]
 pub struct Authorization<C: Credentials>(pub C);
 
 impl Authorization<Basic> {
     /// Create a `Basic` authorization header.
     pub fn basic(username: &str, password: &str) -> Self {
-        let colon_pos = username.len() + 1;
+        let colon_pos = username.len();
         let decoded = format!("{}:{}", username, password);
 
         Authorization(Basic {
@@ -46,19 +57,33 @@ impl Authorization<Basic> {
             colon_pos,
         })
     }
+
+    /// Check if the username and password are the expected ones, in
+    /// constant time, like `Basic::secure_eq`.
+    pub fn secure_eq(&self, username: &str, password: &str) -> bool {
+        self.0.secure_eq(username, password)
+    }
 }
 
 impl Authorization<Bearer> {
     /// Try to create a `Bearer` authorization header.
     pub fn bearer(token: &str) -> Result<Self, InvalidBearerToken>  {
-        HeaderValueString::from_string(format!("Bearer {}", token))
+        HeaderString::from_string(format!("Bearer {}", token))
             .map(|val| Authorization(Bearer(val)))
-            .ok_or_else(|| InvalidBearerToken(()))
+            .ok_or(InvalidBearerToken(()))
+    }
+
+    /// Check if the token is `expected`, in constant time, like
+    /// `Bearer::secure_eq`.
+    pub fn secure_eq(&self, expected: &str) -> bool {
+        self.0.secure_eq(expected)
     }
 }
 
 impl<C: Credentials> ::Header for Authorization<C> {
-    const NAME: &'static ::HeaderName = &::http::header::AUTHORIZATION;
+    fn name() -> &'static ::HeaderName {
+        &::http::header::AUTHORIZATION
+    }
 
     fn decode(values: &mut ::Values) -> Option<Self> {
         let val = values.next()?;
@@ -87,6 +112,22 @@ impl<C: Credentials> ::Header for Authorization<C> {
     }
 }
 
+impl<C: Credentials> ::SingleValueHeader for Authorization<C> {}
+
+impl<'a, C: Credentials> ::std::convert::TryFrom<&'a HeaderValue> for Authorization<C> {
+    type Error = ::InvalidHeader;
+
+    fn try_from(value: &'a HeaderValue) -> Result<Self, ::InvalidHeader> {
+        ::headers_core::decode::from_value(value)
+    }
+}
+
+impl<'a, C: Credentials> From<&'a Authorization<C>> for HeaderValue {
+    fn from(header: &'a Authorization<C>) -> HeaderValue {
+        ::headers_core::encode::to_value(header)
+    }
+}
+
 /// Credentials to be used in the `Authorization` header.
 pub trait Credentials: Sized {
     /// The scheme identify the format of these credentials.
@@ -107,7 +148,9 @@ pub trait Credentials: Sized {
 }
 
 /// Credential holder for Basic Authentication
-#[derive(Clone, PartialEq, Debug)]
+///
+/// `==` compares in constant time, like `secure_eq`.
+#[derive(Clone, Debug)]
 pub struct Basic {
     decoded: String,
     colon_pos: usize,
@@ -123,6 +166,24 @@ impl Basic {
     pub fn password(&self) -> &str {
         &self.decoded[self.colon_pos + 1..]
     }
+
+    /// Check if the username and password are the expected ones.
+    ///
+    /// Both are compared in full, in time that depends on their lengths
+    /// but not their bytes, so a mismatch doesn't tell how much of them was
+    /// right. Whether the lengths match is leaked, and the timing is
+    /// best-effort.
+    pub fn secure_eq(&self, username: &str, password: &str) -> bool {
+        // `&` rather than `&&`, to compare the password either way.
+        constant_time::eq(self.username().as_bytes(), username.as_bytes())
+            & constant_time::eq(self.password().as_bytes(), password.as_bytes())
+    }
+}
+
+impl PartialEq for Basic {
+    fn eq(&self, other: &Basic) -> bool {
+        self.secure_eq(other.username(), other.password())
+    }
 }
 
 impl Credentials for Basic {
@@ -156,15 +217,32 @@ impl Credentials for Basic {
     }
 }
 
-#[derive(Clone, PartialEq, Debug)]
 /// Token holder for Bearer Authentication, most often seen with oauth
-pub struct Bearer(HeaderValueString);
+///
+/// `==` compares in constant time, like `secure_eq`.
+#[derive(Clone, Debug)]
+pub struct Bearer(HeaderString);
 
 impl Bearer {
     /// View the token part as a `&str`.
     pub fn token(&self) -> &str {
         &self.0.as_str()["Bearer ".len() ..]
     }
+
+    /// Check if the token is `expected`.
+    ///
+    /// It's compared in time that depends on the lengths but not the
+    /// bytes, so a mismatch doesn't tell how much of the token was right.
+    /// Whether the lengths match is leaked, and the timing is best-effort.
+    pub fn secure_eq(&self, expected: &str) -> bool {
+        constant_time::eq(self.token().as_bytes(), expected.as_bytes())
+    }
+}
+
+impl PartialEq for Bearer {
+    fn eq(&self, other: &Bearer) -> bool {
+        self.secure_eq(other.token())
+    }
 }
 
 impl Credentials for Bearer {
@@ -177,7 +255,7 @@ impl Credentials for Bearer {
             value,
         );
 
-        HeaderValueString::from_val(value)
+        HeaderString::from_val(value)
             .map(Bearer)
     }
 
@@ -198,6 +276,8 @@ mod tests {
     #[test]
     fn basic_encode() {
         let auth = Authorization::basic("Aladdin", "open sesame");
+        assert_eq!(auth.0.username(), "Aladdin");
+        assert_eq!(auth.0.password(), "open sesame");
         let headers = test_encode(auth);
 
         assert_eq!(
@@ -246,7 +326,28 @@ mod tests {
     #[test]
     fn bearer_decode() {
         let auth: Authorization<Bearer> = test_decode(&["Bearer fpKL54jvWmEGVoRdCNjG"]).unwrap();
-        assert_eq!(auth.0.token(), b"fpKL54jvWmEGVoRdCNjG");
+        assert_eq!(auth.0.token(), "fpKL54jvWmEGVoRdCNjG");
+    }
+
+    #[test]
+    fn secure_eq() {
+        let auth = Authorization::basic("Aladdin", "open sesame");
+        assert!(auth.secure_eq("Aladdin", "open sesame"));
+        assert!(!auth.secure_eq("Aladdin", "open sesamE"));
+        assert!(!auth.secure_eq("aladdin", "open sesame"));
+        assert!(!auth.secure_eq("Aladdin", ""));
+        // The split between username and password matters.
+        assert!(!auth.secure_eq("Aladdin:open", "sesame"));
+        assert_eq!(auth, Authorization::basic("Aladdin", "open sesame"));
+        assert_ne!(auth, Authorization::basic("Aladdin", "open"));
+
+        let auth = Authorization::bearer("fpKL54jvWmEGVoRdCNjG").unwrap();
+        assert!(auth.secure_eq("fpKL54jvWmEGVoRdCNjG"));
+        assert!(!auth.secure_eq("fpKL54jvWmEGVoRdCNjg"));
+        assert!(!auth.secure_eq("fpKL54jvWmEGVoRdCNj"));
+        assert!(!auth.secure_eq(""));
+        assert_eq!(auth, test_decode(&["Bearer fpKL54jvWmEGVoRdCNjG"]).unwrap());
+        assert_ne!(auth, Authorization::bearer("other").unwrap());
     }
 }
 
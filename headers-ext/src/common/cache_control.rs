@@ -1,9 +1,11 @@
+use std::cmp;
 use std::fmt;
 use std::iter::FromIterator;
 use std::str::FromStr;
 use std::time::Duration;
 
-use util::Seconds;
+use HeaderName;
+use util::{parse_item, quoted, token, FlatCsv, Seconds};
 
 /// `Cache-Control` header, defined in [RFC7234](https://tools.ietf.org/html/rfc7234#section-5.2)
 ///
@@ -33,13 +35,49 @@ use util::Seconds;
 ///
 /// let cc = CacheControl::new();
 /// ```
-#[derive(PartialEq, Clone, Debug)]
+///
+/// Directives this doesn't know are kept as extensions, so they're
+/// encoded again, in order, after the known ones.
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::CacheControl;
+///
+/// let cc = CacheControl::new()
+///     .with_extension("stale-while-revalidate", Some("30"))
+///     .unwrap();
+/// assert_eq!(cc.extensions().collect::<Vec<_>>(), [("stale-while-revalidate", Some("30"))]);
+/// ```
+///
+/// `private` and `no-cache` can be limited to some headers of the
+/// response, which a shared cache strips instead of the whole response
+/// being affected.
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// extern crate http;
+/// use http::header::{SET_COOKIE, ETAG};
+/// use headers::CacheControl;
+///
+/// let cc = CacheControl::new().with_private_headers(vec![SET_COOKIE]);
+/// assert!(cc.should_strip(&SET_COOKIE));
+/// assert!(!cc.should_strip(&ETAG));
+/// ```
+#[derive(Clone, Debug)]
 pub struct CacheControl {
     flags: Flags,
     max_age: Option<Seconds>,
     max_stale: Option<Seconds>,
     min_fresh: Option<Seconds>,
     s_max_age: Option<Seconds>,
+    // The headers `no-cache` and `private` are limited to, empty when they
+    // apply to the whole response.
+    no_cache_headers: Vec<HeaderName>,
+    private_headers: Vec<HeaderName>,
+    extensions: Vec<Extension>,
+    // The value this was decoded from, encoded again as long as no
+    // directives are changed.
+    raw: Option<::HeaderValue>,
 }
 
 bitflags! {
@@ -56,17 +94,31 @@ bitflags! {
 }
 
 impl CacheControl {
-    /// Construct a new empty `CacheControl` header.
-    pub fn new() -> Self {
+    /// `no-cache`
+    pub const NO_CACHE: CacheControl = CacheControl::with_flags(Flags::NO_CACHE);
+
+    /// `no-store`
+    pub const NO_STORE: CacheControl = CacheControl::with_flags(Flags::NO_STORE);
+
+    const fn with_flags(flags: Flags) -> CacheControl {
         CacheControl {
-            flags: Flags::empty(),
+            flags,
             max_age: None,
             max_stale: None,
             min_fresh: None,
             s_max_age: None,
+            no_cache_headers: Vec::new(),
+            private_headers: Vec::new(),
+            extensions: Vec::new(),
+            raw: None,
         }
     }
 
+    /// Construct a new empty `CacheControl` header.
+    pub fn new() -> Self {
+        CacheControl::with_flags(Flags::empty())
+    }
+
     // getters
 
     /// Check if the `no-cache` directive is set.
@@ -89,6 +141,16 @@ impl CacheControl {
         self.flags.contains(Flags::ONLY_IF_CACHED)
     }
 
+    /// Check if the `must-revalidate` directive is set.
+    pub fn must_revalidate(&self) -> bool {
+        self.flags.contains(Flags::MUST_REVALIDATE)
+    }
+
+    /// Check if the `proxy-revalidate` directive is set.
+    pub fn proxy_revalidate(&self) -> bool {
+        self.flags.contains(Flags::PROXY_REVALIDATE)
+    }
+
     /// Check if the `public` directive is set.
     pub fn public(&self) -> bool {
         self.flags.contains(Flags::PUBLIC)
@@ -99,6 +161,30 @@ impl CacheControl {
         self.flags.contains(Flags::PRIVATE)
     }
 
+    /// Get the headers the `no-cache` directive is limited to.
+    ///
+    /// This is empty if `no-cache` is unset, or set for the whole response.
+    pub fn no_cache_headers(&self) -> &[HeaderName] {
+        &self.no_cache_headers
+    }
+
+    /// Get the headers the `private` directive is limited to.
+    ///
+    /// This is empty if `private` is unset, or set for the whole response.
+    pub fn private_headers(&self) -> &[HeaderName] {
+        &self.private_headers
+    }
+
+    /// Check if a shared cache must strip the `name` header from the
+    /// response, because `private` or `no-cache` are limited to it.
+    ///
+    /// `private` and `no-cache` for the whole response aren't checked here:
+    /// the first forbids a shared cache from storing it at all, and the
+    /// second to reuse it without revalidating.
+    pub fn should_strip(&self, name: &HeaderName) -> bool {
+        self.private_headers.contains(name) || self.no_cache_headers.contains(name)
+    }
+
     /// Get the value of the `max-age` directive if set.
     pub fn max_age(&self) -> Option<Duration> {
         self.max_age.map(Into::into)
@@ -120,80 +206,364 @@ impl CacheControl {
         self.s_max_age.map(Into::into)
     }
 
+    /// Get the freshness lifetime these directives give a response, as seen
+    /// by a shared cache.
+    ///
+    /// This is `s-maxage` if set, falling back to `max-age`. A private cache
+    /// should ignore `s-maxage` and use `max_age` directly.
+    pub fn freshness_lifetime(&self) -> Option<Duration> {
+        self.s_max_age().or_else(|| self.max_age())
+    }
+
+    /// Iterate the extension directives, as names and optional values, in
+    /// the order they were decoded or set.
+    ///
+    /// A quoted value is returned without its quotes.
+    pub fn extensions(&self) -> impl Iterator<Item = (&str, Option<&str>)> {
+        self.extensions
+            .iter()
+            .map(|ext| (ext.name.as_str(), ext.value.as_deref()))
+    }
+
     // setters
 
-    /// Set the `no-cache` directive.
+    /// Set the `no-cache` directive, for the whole response.
     pub fn with_no_cache(mut self) -> Self {
+        self.raw = None;
         self.flags.insert(Flags::NO_CACHE);
+        self.no_cache_headers.clear();
+        self
+    }
+
+    /// Set the `no-cache` directive, limited to the `names` headers.
+    ///
+    /// If `names` is empty, this is the same as `with_no_cache`.
+    pub fn with_no_cache_headers<I>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = HeaderName>,
+    {
+        self.raw = None;
+        self.flags.insert(Flags::NO_CACHE);
+        self.no_cache_headers = dedup(names);
         self
     }
 
     /// Set the `no-store` directive.
     pub fn with_no_store(mut self) -> Self {
+        self.raw = None;
         self.flags.insert(Flags::NO_STORE);
         self
     }
 
     /// Set the `no-transform` directive.
     pub fn with_no_transform(mut self) -> Self {
+        self.raw = None;
         self.flags.insert(Flags::NO_TRANSFORM);
         self
     }
 
     /// Set the `only-if-cached` directive.
     pub fn with_only_if_cached(mut self) -> Self {
+        self.raw = None;
         self.flags.insert(Flags::ONLY_IF_CACHED);
         self
     }
 
-    /// Set the `private` directive.
+    /// Set the `must-revalidate` directive.
+    pub fn with_must_revalidate(mut self) -> Self {
+        self.raw = None;
+        self.flags.insert(Flags::MUST_REVALIDATE);
+        self
+    }
+
+    /// Set the `proxy-revalidate` directive.
+    pub fn with_proxy_revalidate(mut self) -> Self {
+        self.raw = None;
+        self.flags.insert(Flags::PROXY_REVALIDATE);
+        self
+    }
+
+    /// Set the `private` directive, for the whole response.
     pub fn with_private(mut self) -> Self {
+        self.raw = None;
+        self.flags.insert(Flags::PRIVATE);
+        self.private_headers.clear();
+        self
+    }
+
+    /// Set the `private` directive, limited to the `names` headers.
+    ///
+    /// If `names` is empty, this is the same as `with_private`.
+    pub fn with_private_headers<I>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = HeaderName>,
+    {
+        self.raw = None;
         self.flags.insert(Flags::PRIVATE);
+        self.private_headers = dedup(names);
         self
     }
 
     /// Set the `public` directive.
     pub fn with_public(mut self) -> Self {
+        self.raw = None;
         self.flags.insert(Flags::PUBLIC);
         self
     }
 
     /// Set the `max-age` directive.
     pub fn with_max_age(mut self, seconds: Duration) -> Self {
+        self.raw = None;
         self.max_age = Some(seconds.into());
         self
     }
 
     /// Set the `max-stale` directive.
     pub fn with_max_stale(mut self, seconds: Duration) -> Self {
+        self.raw = None;
         self.max_stale = Some(seconds.into());
         self
     }
 
     /// Set the `min-fresh` directive.
     pub fn with_min_fresh(mut self, seconds: Duration) -> Self {
+        self.raw = None;
         self.min_fresh = Some(seconds.into());
         self
     }
 
     /// Set the `s-maxage` directive.
     pub fn with_s_max_age(mut self, seconds: Duration) -> Self {
+        self.raw = None;
         self.s_max_age = Some(seconds.into());
         self
     }
+
+    /// Set an extension directive, replacing any with the same name.
+    ///
+    /// The value is quoted when encoded if it isn't a token. Fails if the
+    /// name isn't a token or is a directive this already knows, or if the
+    /// value contains control characters.
+    pub fn with_extension(
+        mut self,
+        name: &str,
+        value: Option<&str>,
+    ) -> Result<Self, InvalidCacheDirective> {
+        let valid_value = value.is_none_or(|v| v.chars().all(|c| c == '\t' || !c.is_control()));
+        if !token::is_token(name.as_bytes()) || is_known(name) || !valid_value {
+            return Err(InvalidCacheDirective(()));
+        }
+
+        self.raw = None;
+        let ext = Extension {
+            name: name.to_owned(),
+            value: value.map(str::to_owned),
+        };
+        match self.extensions.iter_mut().find(|e| e.name.eq_ignore_ascii_case(name)) {
+            Some(existing) => *existing = ext,
+            None => self.extensions.push(ext),
+        }
+        Ok(self)
+    }
+
+    /// Add the directives of `other`.
+    ///
+    /// When both set the same directive with different values, the more
+    /// restrictive one is kept: the shorter `max-age`, `s-maxage` and
+    /// `max-stale`, and the longer `min-fresh`. An extension already set
+    /// keeps its value. `no-cache` and `private` are limited to the
+    /// headers of both, unless either sets them for the whole response.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # extern crate headers_ext as headers;
+    /// use std::time::Duration;
+    /// use headers::CacheControl;
+    ///
+    /// let mut cc = CacheControl::new().with_max_age(Duration::from_secs(60));
+    /// cc.merge(CacheControl::NO_STORE.with_max_age(Duration::from_secs(600)));
+    /// assert!(cc.no_store());
+    /// assert_eq!(cc.max_age(), Some(Duration::from_secs(60)));
+    /// ```
+    pub fn merge(&mut self, other: CacheControl) {
+        fn pick(a: Option<Seconds>, b: Option<Seconds>, f: fn(Seconds, Seconds) -> Seconds) -> Option<Seconds> {
+            match (a, b) {
+                (Some(a), Some(b)) => Some(f(a, b)),
+                (a, b) => a.or(b),
+            }
+        }
+
+        let before = self.clone();
+        self.no_cache_headers = merge_fields(
+            self.field_list(Flags::NO_CACHE, &self.no_cache_headers),
+            other.field_list(Flags::NO_CACHE, &other.no_cache_headers),
+        );
+        self.private_headers = merge_fields(
+            self.field_list(Flags::PRIVATE, &self.private_headers),
+            other.field_list(Flags::PRIVATE, &other.private_headers),
+        );
+        self.flags.insert(other.flags);
+        self.max_age = pick(self.max_age, other.max_age, cmp::min);
+        self.max_stale = pick(self.max_stale, other.max_stale, cmp::min);
+        self.min_fresh = pick(self.min_fresh, other.min_fresh, cmp::max);
+        self.s_max_age = pick(self.s_max_age, other.s_max_age, cmp::min);
+        for ext in other.extensions {
+            if !self.extensions.iter().any(|e| e.name.eq_ignore_ascii_case(&ext.name)) {
+                self.extensions.push(ext);
+            }
+        }
+        if *self != before {
+            self.raw = None;
+        }
+    }
+
+    /// The headers the `flag` directive is limited to, `None` if it isn't
+    /// set, and empty if set for the whole response.
+    fn field_list<'a>(&self, flag: Flags, names: &'a [HeaderName]) -> Option<&'a [HeaderName]> {
+        if self.flags.contains(flag) {
+            Some(names)
+        } else {
+            None
+        }
+    }
+}
+
+fn merge_fields(a: Option<&[HeaderName]>, b: Option<&[HeaderName]>) -> Vec<HeaderName> {
+    match (a, b) {
+        (Some(a), Some(b)) if !a.is_empty() && !b.is_empty() => dedup(a.iter().chain(b).cloned()),
+        (Some(a), None) | (None, Some(a)) => a.to_vec(),
+        _ => Vec::new(),
+    }
+}
+
+fn dedup<I: IntoIterator<Item = HeaderName>>(names: I) -> Vec<HeaderName> {
+    let mut list: Vec<HeaderName> = Vec::new();
+    for name in names {
+        if !list.contains(&name) {
+            list.push(name);
+        }
+    }
+    list
+}
+
+impl ::MergeableHeader for CacheControl {
+    fn merge(&mut self, other: CacheControl) {
+        CacheControl::merge(self, other);
+    }
+}
+
+/// An error when setting an invalid `Cache-Control` extension directive.
+#[derive(Debug)]
+pub struct InvalidCacheDirective(());
+
+impl fmt::Display for InvalidCacheDirective {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid cache directive")
+    }
+}
+
+impl ::std::error::Error for InvalidCacheDirective {}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Extension {
+    name: String,
+    value: Option<String>,
+}
+
+impl fmt::Display for Extension {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.name)?;
+        match self.value {
+            Some(ref value) => write!(f, "={}", quoted::quote(value)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl PartialEq for CacheControl {
+    fn eq(&self, other: &CacheControl) -> bool {
+        self.flags == other.flags
+            && self.max_age == other.max_age
+            && self.max_stale == other.max_stale
+            && self.min_fresh == other.min_fresh
+            && self.s_max_age == other.s_max_age
+            && self.no_cache_headers == other.no_cache_headers
+            && self.private_headers == other.private_headers
+            && self.extensions == other.extensions
+    }
+}
+
+impl Default for CacheControl {
+    fn default() -> Self {
+        CacheControl::new()
+    }
 }
 
 impl ::Header for CacheControl {
-    const NAME: &'static ::HeaderName = &::http::header::CACHE_CONTROL;
+    fn name() -> &'static ::HeaderName {
+        &::http::header::CACHE_CONTROL
+    }
 
     fn decode(values: &mut ::Values) -> Option<Self> {
-        ::headers_core::decode::from_comma_delimited(values)
-            .map(|FromIter(cc)| cc)
-
+        // Malformed directives are ignored, unless strict.
+        let strict = values.mode() == ::ParseMode::Strict;
+        let csv: FlatCsv = FlatCsv::from_values(values)?;
+        // Whether there are any items, found in the same pass as parsing
+        // them.
+        let mut empty = true;
+        let FromIter(cc) = csv
+            .iter()
+            .map(|item| {
+                empty = false;
+                match item.parse() {
+                    Ok(KnownDirective::Unknown) if strict => Err(()),
+                    dir => dir,
+                }
+            })
+            .collect::<Result<FromIter, ()>>()
+            .ok()?;
+        if empty {
+            return None;
+        }
+        Some(CacheControl {
+            raw: Some(csv.value),
+            ..cc
+        })
     }
 
     fn encode(&self, values: &mut ::ToValues) {
-        values.append_fmt(&Fmt(self));
+        if let Some(ref raw) = self.raw {
+            return values.append(raw.clone());
+        }
+        match self.single_flag() {
+            Some(name) => values.append(::HeaderValue::from_static(name)),
+            None => values.append_fmt(Fmt(self)),
+        }
+    }
+}
+
+impl CacheControl {
+    /// If only one flag directive is set, its name.
+    ///
+    /// This is the common case, and can be encoded without allocating.
+    fn single_flag(&self) -> Option<&'static str> {
+        let bits = self.flags.bits();
+        if !bits.is_power_of_two()
+            || self.max_age.is_some()
+            || self.max_stale.is_some()
+            || self.min_fresh.is_some()
+            || self.s_max_age.is_some()
+            || !self.no_cache_headers.is_empty()
+            || !self.private_headers.is_empty()
+            || !self.extensions.is_empty()
+        {
+            return None;
+        }
+        // `FLAG_DIRECTIVES` is in the same order as the bits of `Flags`.
+        FLAG_DIRECTIVES
+            .get(bits.trailing_zeros() as usize)
+            .map(|&(name, _)| name)
     }
 }
 
@@ -206,12 +576,22 @@ impl FromIterator<KnownDirective> for FromIter {
         I: IntoIterator<Item=KnownDirective>,
     {
         let mut cc = CacheControl::new();
+        let mut extensions = Vec::new();
+        let mut fields = Vec::new();
 
-        // ignore all unknown directives
+        // ignore anything that isn't a valid directive
         let iter = iter
             .into_iter()
             .filter_map(|dir| match dir {
                 KnownDirective::Known(dir) => Some(dir),
+                KnownDirective::Extension(ext) => {
+                    extensions.push(ext);
+                    None
+                },
+                KnownDirective::Fields(flag, names) => {
+                    fields.push((flag, names));
+                    None
+                },
                 KnownDirective::Unknown => None,
             });
 
@@ -242,20 +622,41 @@ impl FromIterator<KnownDirective> for FromIter {
                     cc.flags.insert(Flags::PROXY_REVALIDATE);
                 },
                 Directive::MaxAge(secs) => {
-                    cc.max_age = Some(Duration::from_secs(secs.into()).into());
+                    cc.max_age = Some(secs);
                 },
                 Directive::MaxStale(secs) => {
-                    cc.max_stale = Some(Duration::from_secs(secs.into()).into());
+                    cc.max_stale = Some(secs);
                 },
                 Directive::MinFresh(secs) => {
-                    cc.min_fresh = Some(Duration::from_secs(secs.into()).into());
+                    cc.min_fresh = Some(secs);
                 },
                 Directive::SMaxAge(secs) => {
-                    cc.s_max_age = Some(Duration::from_secs(secs.into()).into());
+                    cc.s_max_age = Some(secs);
                 },
             }
         }
 
+        // Once set for the whole response, a directive stays so, even if
+        // also limited to some headers.
+        let whole = cc.flags;
+        for (flag, names) in fields {
+            if whole.contains(flag) {
+                continue;
+            }
+            cc.flags.insert(flag);
+            let list = if flag == Flags::PRIVATE {
+                &mut cc.private_headers
+            } else {
+                &mut cc.no_cache_headers
+            };
+            for name in names {
+                if !list.contains(&name) {
+                    list.push(name);
+                }
+            }
+        }
+
+        cc.extensions = extensions;
         FromIter(cc)
     }
 }
@@ -272,32 +673,64 @@ impl<'a> fmt::Display for Fmt<'a> {
             }
         };
 
+        let with_fields = |f: Flags, dir: Directive, names: &'a [HeaderName]| {
+            if_flag(f, dir).map(|dir| WithFields(dir, names))
+        };
+        let plain = |dir: Option<Directive>| dir.map(|dir| WithFields(dir, &[]));
+
         let slice = &[
-            if_flag(Flags::NO_CACHE, Directive::NoCache),
-            if_flag(Flags::NO_STORE, Directive::NoStore),
-            if_flag(Flags::NO_TRANSFORM, Directive::NoTransform),
-            if_flag(Flags::ONLY_IF_CACHED, Directive::OnlyIfCached),
-            if_flag(Flags::MUST_REVALIDATE, Directive::MustRevalidate),
-            if_flag(Flags::PUBLIC, Directive::Public),
-            if_flag(Flags::PRIVATE, Directive::Private),
-            if_flag(Flags::PROXY_REVALIDATE, Directive::ProxyRevalidate),
-            self.0.max_age.as_ref().map(|s| Directive::MaxAge(s.as_u64())),
-            self.0.max_stale.as_ref().map(|s| Directive::MaxStale(s.as_u64())),
-            self.0.min_fresh.as_ref().map(|s| Directive::MinFresh(s.as_u64())),
-            self.0.s_max_age.as_ref().map(|s| Directive::SMaxAge(s.as_u64())),
+            with_fields(Flags::NO_CACHE, Directive::NoCache, &self.0.no_cache_headers),
+            plain(if_flag(Flags::NO_STORE, Directive::NoStore)),
+            plain(if_flag(Flags::NO_TRANSFORM, Directive::NoTransform)),
+            plain(if_flag(Flags::ONLY_IF_CACHED, Directive::OnlyIfCached)),
+            plain(if_flag(Flags::MUST_REVALIDATE, Directive::MustRevalidate)),
+            plain(if_flag(Flags::PUBLIC, Directive::Public)),
+            with_fields(Flags::PRIVATE, Directive::Private, &self.0.private_headers),
+            plain(if_flag(Flags::PROXY_REVALIDATE, Directive::ProxyRevalidate)),
+            plain(self.0.max_age.map(Directive::MaxAge)),
+            plain(self.0.max_stale.map(Directive::MaxStale)),
+            plain(self.0.min_fresh.map(Directive::MinFresh)),
+            plain(self.0.s_max_age.map(Directive::SMaxAge)),
         ];
 
         let iter = slice
             .iter()
-            .filter_map(|o| *o);
+            .filter_map(Option::as_ref);
+
+        ::headers_core::encode::comma_delimited(f, iter)?;
 
-        ::headers_core::encode::comma_delimited(f, iter)
+        let mut needs_comma = slice.iter().any(Option::is_some);
+        for ext in &self.0.extensions {
+            if needs_comma {
+                f.write_str(", ")?;
+            }
+            fmt::Display::fmt(ext, f)?;
+            needs_comma = true;
+        }
+        Ok(())
+    }
+}
+
+// A directive, and the headers it's limited to, quoted as a list.
+struct WithFields<'a>(Directive, &'a [HeaderName]);
+
+impl<'a> fmt::Display for WithFields<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)?;
+        if self.1.is_empty() {
+            return Ok(());
+        }
+        f.write_str("=\"")?;
+        ::headers_core::encode::comma_delimited(f, self.1.iter().map(HeaderName::as_str))?;
+        f.write_str("\"")
     }
 }
 
-#[derive(Clone, Copy)]
 enum KnownDirective {
     Known(Directive),
+    // `no-cache` or `private`, limited to some headers.
+    Fields(Flags, Vec<HeaderName>),
+    Extension(Extension),
     Unknown,
 }
 
@@ -309,16 +742,16 @@ enum Directive {
     OnlyIfCached,
 
     // request directives
-    MaxAge(u64),
-    MaxStale(u64),
-    MinFresh(u64),
+    MaxAge(Seconds),
+    MaxStale(Seconds),
+    MinFresh(Seconds),
 
     // response directives
     MustRevalidate,
     Public,
     Private,
     ProxyRevalidate,
-    SMaxAge(u64),
+    SMaxAge(Seconds),
 }
 
 impl fmt::Display for Directive {
@@ -342,33 +775,109 @@ impl fmt::Display for Directive {
     }
 }
 
+// Ordered by the bits of `Flags`.
+const FLAG_DIRECTIVES: &[(&str, Directive)] = &[
+    ("no-cache", Directive::NoCache),
+    ("no-store", Directive::NoStore),
+    ("no-transform", Directive::NoTransform),
+    ("only-if-cached", Directive::OnlyIfCached),
+    ("must-revalidate", Directive::MustRevalidate),
+    ("public", Directive::Public),
+    ("private", Directive::Private),
+    ("proxy-revalidate", Directive::ProxyRevalidate),
+];
+
+// The directives that can be limited to some headers.
+const FIELDS_DIRECTIVES: &[(&str, Flags, Directive)] = &[
+    ("no-cache", Flags::NO_CACHE, Directive::NoCache),
+    ("private", Flags::PRIVATE, Directive::Private),
+];
+
+type SecondsDirective = fn(Seconds) -> Directive;
+
+const SECONDS_DIRECTIVES: &[(&str, SecondsDirective)] = &[
+    ("max-age", Directive::MaxAge),
+    ("max-stale", Directive::MaxStale),
+    ("min-fresh", Directive::MinFresh),
+    ("s-maxage", Directive::SMaxAge),
+];
+
+// Directive names are case-insensitive.
+fn eq(a: &str, b: &str) -> bool {
+    token::eq_ignore_ascii_case_trimmed(a.as_bytes(), b)
+}
+
+fn is_known(name: &str) -> bool {
+    FLAG_DIRECTIVES.iter().any(|&(known, _)| eq(name, known))
+        || SECONDS_DIRECTIVES.iter().any(|&(known, _)| eq(name, known))
+}
+
 impl FromStr for KnownDirective {
     type Err = ();
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(KnownDirective::Known(match s {
-            "no-cache" => Directive::NoCache,
-            "no-store" => Directive::NoStore,
-            "no-transform" => Directive::NoTransform,
-            "only-if-cached" => Directive::OnlyIfCached,
-            "must-revalidate" => Directive::MustRevalidate,
-            "public" => Directive::Public,
-            "private" => Directive::Private,
-            "proxy-revalidate" => Directive::ProxyRevalidate,
-            "" => return Err(()),
-            _ => match s.find('=') {
-                Some(idx) if idx+1 < s.len() => match (&s[..idx], (&s[idx+1..]).trim_matches('"')) {
-                    ("max-age" , secs) => secs.parse().map(Directive::MaxAge).map_err(|_| ())?,
-                    ("max-stale", secs) => secs.parse().map(Directive::MaxStale).map_err(|_| ())?,
-                    ("min-fresh", secs) => secs.parse().map(Directive::MinFresh).map_err(|_| ())?,
-                    ("s-maxage", secs) => secs.parse().map(Directive::SMaxAge).map_err(|_| ())?,
-                    _unknown => return Ok(KnownDirective::Unknown),
-                },
-                Some(_) | None => return Ok(KnownDirective::Unknown),
-            }
+        if s.is_empty() {
+            return Err(());
+        }
+        // The name is trimmed once, rather than by each comparison with
+        // the tables.
+        let idx = s.find('=');
+        let known = token::trim_ows(idx.map_or(s, |idx| &s[..idx]));
+        let is = |name: &&str| known.eq_ignore_ascii_case(name);
+
+        let (name, value) = match idx {
+            None => {
+                if let Some(&(_, dir)) = FLAG_DIRECTIVES.iter().find(|(name, _)| is(name)) {
+                    return Ok(KnownDirective::Known(dir));
+                }
+                (s, None)
+            },
+            Some(idx) if idx+1 < s.len() => {
+                let value = match quoted::unquote(&s[idx+1..]) {
+                    Some(value) => value,
+                    None => return Ok(KnownDirective::Unknown),
+                };
+                if let Some(&(_, dir)) = SECONDS_DIRECTIVES.iter().find(|(name, _)| is(name)) {
+                    return Seconds::parse(value.as_bytes()).map(dir).map(KnownDirective::Known).ok_or(());
+                }
+                if let Some(&(_, flag, dir)) = FIELDS_DIRECTIVES.iter().find(|(name, ..)| is(name)) {
+                    return Ok(parse_fields(&value).map_or(
+                        KnownDirective::Known(dir),
+                        |names| KnownDirective::Fields(flag, names),
+                    ));
+                }
+                (&s[..idx], Some(value.into_owned()))
+            },
+            Some(_) => return Ok(KnownDirective::Unknown),
+        };
+
+        if !token::is_token(name.as_bytes()) {
+            return Ok(KnownDirective::Unknown);
+        }
+        Ok(KnownDirective::Extension(Extension {
+            name: name.to_owned(),
+            value,
         }))
     }
 }
 
+/// Parse the headers a directive is limited to, `None` if there are none.
+///
+/// A list with an invalid name is `None` too, making the directive apply
+/// to the whole response, which is the safer reading.
+fn parse_fields(list: &str) -> Option<Vec<HeaderName>> {
+    let names = list
+        .split(',')
+        .map(|name| name.trim_matches(|c| c == ' ' || c == '\t'))
+        .filter(|name| !name.is_empty())
+        .map(parse_item)
+        .collect::<Option<Vec<HeaderName>>>()?;
+    if names.is_empty() {
+        None
+    } else {
+        Some(names)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -405,14 +914,104 @@ mod tests {
 
     #[test]
     fn test_parse_extension() {
+        let cc = test_decode::<CacheControl>(&["foo, no-cache, bar=baz"]).unwrap();
+        assert!(cc.no_cache());
+        assert_eq!(cc.extensions().collect::<Vec<_>>(), [("foo", None), ("bar", Some("baz"))]);
+    }
+
+    #[test]
+    fn test_parse_quoted_extension() {
+        let cc = test_decode::<CacheControl>(&["foo=\"bar, max-age=lolz\", max-age=5"]).unwrap();
+        assert_eq!(cc.max_age(), Some(Duration::from_secs(5)));
+        assert_eq!(cc.extensions().collect::<Vec<_>>(), [("foo", Some("bar, max-age=lolz"))]);
+    }
+
+    #[test]
+    fn proxy_keeps_extensions_when_changing_max_age() {
+        let cc = test_decode::<CacheControl>(&[
+            "public, stale-while-revalidate=30, max-age=60, no-transform-cdn, community=\"U C\"",
+        ]).unwrap();
+
+        let cc = cc.with_max_age(Duration::from_secs(120));
         assert_eq!(
-            test_decode::<CacheControl>(&["foo, no-cache, bar=baz"]).unwrap(),
-            CacheControl::new()
-                .with_no_cache(),
-            "unknown extensions are ignored but shouldn't fail parsing",
+            test_encode(cc)["cache-control"],
+            "public, max-age=120, stale-while-revalidate=30, no-transform-cdn, community=\"U C\"",
         );
     }
 
+    #[test]
+    fn with_extension() {
+        let cc = CacheControl::new()
+            .with_no_cache()
+            .with_extension("stale-if-error", Some("60")).unwrap()
+            .with_extension("x-cdn", None).unwrap()
+            .with_extension("Stale-If-Error", Some("a b")).unwrap();
+        assert_eq!(
+            cc.extensions().collect::<Vec<_>>(),
+            [("Stale-If-Error", Some("a b")), ("x-cdn", None)],
+        );
+        assert_eq!(test_encode(cc)["cache-control"], "no-cache, Stale-If-Error=\"a b\", x-cdn");
+
+        assert!(CacheControl::new().with_extension("no good", None).is_err());
+        assert!(CacheControl::new().with_extension("MAX-AGE", Some("5")).is_err());
+        assert!(CacheControl::new().with_extension("x", Some("a\nb")).is_err());
+    }
+
+    #[test]
+    fn test_parse_field_lists() {
+        let cc = test_decode::<CacheControl>(&["private=\"Set-Cookie, X-User\", no-cache=etag, max-age=60"]).unwrap();
+        assert!(cc.private());
+        assert!(cc.no_cache());
+        assert_eq!(cc.private_headers(), [::http::header::SET_COOKIE, HeaderName::from_static("x-user")]);
+        assert_eq!(cc.no_cache_headers(), [::http::header::ETAG]);
+        assert_eq!(cc.max_age(), Some(Duration::from_secs(60)));
+        assert!(cc.extensions().next().is_none());
+
+        assert!(cc.should_strip(&::http::header::SET_COOKIE));
+        assert!(cc.should_strip(&HeaderName::from_static("x-user")));
+        assert!(cc.should_strip(&::http::header::ETAG));
+        assert!(!cc.should_strip(&::http::header::CONTENT_TYPE));
+
+        // Repeats are combined, unless one applies to the whole response.
+        let cc = test_decode::<CacheControl>(&["private=\"a\"", "Private=\"b, a\""]).unwrap();
+        assert_eq!(cc.private_headers(), ["a", "b"]);
+        let cc = test_decode::<CacheControl>(&["private=\"a\", private"]).unwrap();
+        assert!(cc.private());
+        assert!(cc.private_headers().is_empty());
+        assert!(!cc.should_strip(&HeaderName::from_static("a")));
+
+        // Empty or invalid lists apply to the whole response.
+        for &value in &["no-cache=\"\"", "no-cache=\" , \"", "no-cache=\"etag, b@d\""] {
+            let cc = test_decode::<CacheControl>(&[value]).unwrap();
+            assert!(cc.no_cache(), "{:?}", value);
+            assert!(cc.no_cache_headers().is_empty(), "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn encode_field_lists() {
+        let cc = CacheControl::new()
+            .with_no_cache_headers(vec![::http::header::SET_COOKIE, ::http::header::SET_COOKIE])
+            .with_private_headers(vec![HeaderName::from_static("x-a"), HeaderName::from_static("x-b")])
+            .with_max_age(Duration::from_secs(5));
+        assert_eq!(cc.no_cache_headers(), [::http::header::SET_COOKIE]);
+        assert_eq!(
+            test_encode(cc.clone())["cache-control"],
+            "no-cache=\"set-cookie\", private=\"x-a, x-b\", max-age=5",
+        );
+        assert_eq!(test_decode::<CacheControl>(&["no-cache=\"set-cookie\", private=\"x-a, x-b\", max-age=5"]).unwrap(), cc);
+
+        let cc = CacheControl::new().with_private_headers(vec![::http::header::SET_COOKIE]);
+        assert_eq!(test_encode(cc.clone())["cache-control"], "private=\"set-cookie\"");
+
+        let whole = cc.with_private();
+        assert!(whole.private_headers().is_empty());
+        assert_eq!(test_encode(whole)["cache-control"], "private");
+
+        let empty = CacheControl::new().with_no_cache_headers(Vec::new());
+        assert_eq!(test_encode(empty)["cache-control"], "no-cache");
+    }
+
     #[test]
     fn test_parse_bad_syntax() {
         assert_eq!(
@@ -421,6 +1020,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn freshness_lifetime_prefers_s_max_age() {
+        let cc = CacheControl::new().with_max_age(Duration::from_secs(60));
+        assert_eq!(cc.freshness_lifetime(), Some(Duration::from_secs(60)));
+
+        let cc = cc.with_s_max_age(Duration::from_secs(10));
+        assert_eq!(cc.freshness_lifetime(), Some(Duration::from_secs(10)));
+
+        assert_eq!(CacheControl::new().freshness_lifetime(), None);
+    }
+
+    #[test]
+    fn constants() {
+        assert!(CacheControl::NO_STORE.no_store());
+        assert_eq!(test_encode(CacheControl::NO_STORE)["cache-control"], "no-store");
+        assert!(CacheControl::NO_CACHE.no_cache());
+        assert_eq!(test_encode(CacheControl::NO_CACHE)["cache-control"], "no-cache");
+    }
+
     #[test]
     fn encode_one_flag_directive() {
         let cc = CacheControl::new()
@@ -430,6 +1048,38 @@ mod tests {
         assert_eq!(headers["cache-control"], "no-cache");
     }
 
+    #[test]
+    fn encode_each_flag_directive() {
+        let flags = [
+            (Flags::NO_CACHE, "no-cache"),
+            (Flags::NO_STORE, "no-store"),
+            (Flags::NO_TRANSFORM, "no-transform"),
+            (Flags::ONLY_IF_CACHED, "only-if-cached"),
+            (Flags::MUST_REVALIDATE, "must-revalidate"),
+            (Flags::PUBLIC, "public"),
+            (Flags::PRIVATE, "private"),
+            (Flags::PROXY_REVALIDATE, "proxy-revalidate"),
+        ];
+        for &(flags, expected) in &flags {
+            let cc = CacheControl {
+                flags,
+                ..CacheControl::new()
+            };
+            let headers = test_encode(cc);
+            assert_eq!(headers["cache-control"], expected);
+        }
+    }
+
+    #[test]
+    fn decoded_value_is_kept_until_changed() {
+        let cc = test_decode::<CacheControl>(&["PUBLIC,max-age=\"60\""]).unwrap();
+        assert_eq!(cc, CacheControl::new().with_public().with_max_age(Duration::from_secs(60)));
+        assert_eq!(test_encode(cc.clone())["cache-control"], "PUBLIC,max-age=\"60\"");
+
+        let changed = cc.with_no_store();
+        assert_eq!(test_encode(changed)["cache-control"], "no-store, public, max-age=60");
+    }
+
     #[test]
     fn encode_one_param_directive() {
         let cc = CacheControl::new()
@@ -455,5 +1105,39 @@ mod tests {
         );
         assert_eq!(headers["cache-control"], "no-cache, max-age=100");
     }
-}
 
+    #[test]
+    fn test_parse_mixed_case_directives() {
+        let cc = test_decode::<CacheControl>(&["No-Cache, MAX-AGE=60, S-MaxAge=\"30\""]).unwrap();
+        assert_eq!(
+            cc,
+            CacheControl::new()
+                .with_no_cache()
+                .with_max_age(Duration::from_secs(60))
+                .with_s_max_age(Duration::from_secs(30))
+        );
+    }
+
+    #[test]
+    fn merge() {
+        let mut cc = test_decode::<CacheControl>(&["max-age=60, min-fresh=5, foo=1"]).unwrap();
+        cc.merge(test_decode(&["no-cache, max-age=30, s-maxage=10, min-fresh=1, FOO=2, bar"]).unwrap());
+        assert_eq!(
+            test_encode(cc)["cache-control"],
+            "no-cache, max-age=30, min-fresh=5, s-maxage=10, foo=1, bar",
+        );
+
+        let mut cc = test_decode::<CacheControl>(&["private=\"a\", no-cache=\"a\""]).unwrap();
+        cc.merge(test_decode(&["private=\"b\", no-cache"]).unwrap());
+        assert_eq!(cc.private_headers(), ["a", "b"]);
+        assert!(cc.no_cache_headers().is_empty());
+        assert_eq!(test_encode(cc.clone())["cache-control"], "no-cache, private=\"a, b\"");
+        cc.merge(CacheControl::new().with_max_age(Duration::from_secs(1)));
+        assert_eq!(cc.private_headers(), ["a", "b"]);
+
+        // Merging nothing new keeps the original value.
+        let mut cc = test_decode::<CacheControl>(&["Max-Age=60"]).unwrap();
+        cc.merge(CacheControl::new().with_max_age(Duration::from_secs(120)));
+        assert_eq!(test_encode(cc)["cache-control"], "Max-Age=60");
+    }
+}
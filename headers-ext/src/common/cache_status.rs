@@ -0,0 +1,412 @@
+use std::fmt;
+use std::iter::FromIterator;
+use std::time::Duration;
+
+use http::StatusCode;
+
+use util::SmallList;
+use util::structured::{self, identifier, BareItem, Item};
+
+/// `Cache-Status` header, defined in
+/// [RFC9211](https://tools.ietf.org/html/rfc9211)
+///
+/// The `Cache-Status` response header field indicates how caches have
+/// handled that response and its corresponding request. Each cache that
+/// handles the response appends an entry, so the last entry is the cache
+/// closest to the client.
+///
+/// # ABNF
+///
+/// ```text
+/// Cache-Status   = sf-list
+/// ; each member is a cache identifier (sf-string or sf-token) with
+/// ; parameters: hit, fwd, fwd-status, ttl, stored, collapsed, key, detail
+/// ```
+///
+/// # Example values
+///
+/// * `ExampleCache; hit; ttl=300`
+/// * `OriginCache; hit; ttl=1100, "CDN Company Here"; hit; ttl=545`
+/// * `ExampleCache; fwd=uri-miss; stored`
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use std::time::Duration;
+/// use headers::{CacheStatus, CacheStatusEntry};
+///
+/// let status = vec![
+///     CacheStatusEntry::new("ExampleCache")
+///         .with_hit()
+///         .with_ttl(Duration::from_secs(300)),
+/// ].into_iter().collect::<CacheStatus>();
+///
+/// assert!(status.last().unwrap().is_hit());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct CacheStatus(SmallList<CacheStatusEntry>);
+
+/// A single cache's entry in a `Cache-Status` header.
+///
+/// Parameters this type doesn't know about are kept, and encoded again
+/// unchanged.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CacheStatusEntry(Item);
+
+/// The reason a cache forwarded a request, from the `fwd` parameter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ForwardReason {
+    /// The cache was configured to not handle this request.
+    Bypass,
+    /// The request method's semantics require the request to be forwarded.
+    Method,
+    /// The cache did not contain any responses that matched the request URI.
+    UriMiss,
+    /// The cache contained a response that matched the request URI, but it
+    /// could not select a response based upon this request's header fields
+    /// and stored `Vary` header fields.
+    VaryMiss,
+    /// The cache did not contain any responses that could be used.
+    Miss,
+    /// The cache was able to select a fresh response, but request
+    /// semantics prevented it from being used.
+    Request,
+    /// The cache was able to select a response, but it was stale.
+    Stale,
+    /// The cache was able to select a partial response, but it did not
+    /// contain all of the requested ranges.
+    Partial,
+}
+
+impl CacheStatus {
+    /// Returns an iterator over the entries, in the order the caches
+    /// handled the response.
+    pub fn iter(&self) -> ::list::Iter<'_, CacheStatusEntry> {
+        ::list::Iter::new(self.0.iter())
+    }
+
+    fn into_items(self) -> ::list::IntoIter<CacheStatusEntry> {
+        ::list::IntoIter::new(self.0.into_iter())
+    }
+
+    /// Returns the entry of the cache closest to the client.
+    pub fn last(&self) -> Option<&CacheStatusEntry> {
+        self.0.last()
+    }
+}
+
+impl_collection!(CacheStatus, CacheStatusEntry, ordered);
+
+impl CacheStatusEntry {
+    /// Create an entry for the cache identified by `cache`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cache` contains characters other than printable ASCII.
+    pub fn new(cache: &str) -> CacheStatusEntry {
+        CacheStatusEntry(Item::new(identifier(cache)))
+    }
+
+    /// Get the identifier of the cache that added this entry.
+    pub fn cache(&self) -> &str {
+        match self.0.bare {
+            BareItem::String(ref s) |
+            BareItem::Token(ref s) => s,
+            _ => unreachable!("CacheStatusEntry identifier is checked on decode"),
+        }
+    }
+
+    /// Check if the `hit` parameter is set.
+    pub fn is_hit(&self) -> bool {
+        self.flag("hit")
+    }
+
+    /// Get the reason the request was forwarded, if the `fwd` parameter
+    /// is set.
+    ///
+    /// Returns `None` for reasons not defined by RFC 9211.
+    pub fn fwd(&self) -> Option<ForwardReason> {
+        ForwardReason::from_token(self.0.params.get("fwd")?.as_token()?)
+    }
+
+    /// Get the status code of the forwarded response, from the
+    /// `fwd-status` parameter.
+    pub fn fwd_status(&self) -> Option<StatusCode> {
+        let code = self.0.params.get("fwd-status")?.as_integer()?;
+        if code < 0 || code > i64::from(u16::MAX) {
+            return None;
+        }
+        StatusCode::from_u16(code as u16).ok()
+    }
+
+    /// Get the response's remaining freshness lifetime, from the `ttl`
+    /// parameter.
+    ///
+    /// Returns `None` if the parameter is missing, or if it is negative,
+    /// meaning the response is stale.
+    pub fn ttl(&self) -> Option<Duration> {
+        let secs = self.0.params.get("ttl")?.as_integer()?;
+        if secs >= 0 {
+            Some(Duration::from_secs(secs as u64))
+        } else {
+            None
+        }
+    }
+
+    /// Check if the `stored` parameter is set.
+    pub fn is_stored(&self) -> bool {
+        self.flag("stored")
+    }
+
+    /// Check if the `collapsed` parameter is set.
+    pub fn is_collapsed(&self) -> bool {
+        self.flag("collapsed")
+    }
+
+    /// Get the cache key, from the `key` parameter.
+    pub fn key(&self) -> Option<&str> {
+        self.0.params.get("key")?.as_str()
+    }
+
+    /// Get the implementation-specific `detail` parameter.
+    pub fn detail(&self) -> Option<&str> {
+        match *self.0.params.get("detail")? {
+            BareItem::String(ref s) |
+            BareItem::Token(ref s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Set the `hit` parameter.
+    pub fn with_hit(self) -> Self {
+        self.with_param("hit", BareItem::Boolean(true))
+    }
+
+    /// Set the `fwd` parameter.
+    pub fn with_fwd(self, reason: ForwardReason) -> Self {
+        self.with_param("fwd", BareItem::Token(reason.as_str().into()))
+    }
+
+    /// Set the `fwd-status` parameter.
+    pub fn with_fwd_status(self, status: StatusCode) -> Self {
+        self.with_param("fwd-status", BareItem::Integer(status.as_u16().into()))
+    }
+
+    /// Set the `ttl` parameter.
+    pub fn with_ttl(self, ttl: Duration) -> Self {
+        let secs = ttl.as_secs().min(999_999_999_999_999) as i64;
+        self.with_param("ttl", BareItem::Integer(secs))
+    }
+
+    /// Set the `stored` parameter.
+    pub fn with_stored(self) -> Self {
+        self.with_param("stored", BareItem::Boolean(true))
+    }
+
+    /// Set the `collapsed` parameter.
+    pub fn with_collapsed(self) -> Self {
+        self.with_param("collapsed", BareItem::Boolean(true))
+    }
+
+    /// Set the `key` parameter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` contains characters other than printable ASCII.
+    pub fn with_key(self, key: &str) -> Self {
+        assert!(structured::is_string(key), "invalid Cache-Status key: {:?}", key);
+        self.with_param("key", BareItem::String(key.into()))
+    }
+
+    /// Set the `detail` parameter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `detail` contains characters other than printable ASCII.
+    pub fn with_detail(self, detail: &str) -> Self {
+        self.with_param("detail", identifier(detail))
+    }
+
+    fn flag(&self, key: &str) -> bool {
+        self.0.params.get(key).and_then(BareItem::as_bool).unwrap_or(false)
+    }
+
+    fn with_param(mut self, key: &str, value: BareItem) -> Self {
+        self.0.params.insert(key, value);
+        self
+    }
+
+    fn from_item(item: Item) -> Option<CacheStatusEntry> {
+        match item.bare {
+            BareItem::String(_) |
+            BareItem::Token(_) => Some(CacheStatusEntry(item)),
+            _ => None,
+        }
+    }
+}
+
+impl ForwardReason {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            ForwardReason::Bypass => "bypass",
+            ForwardReason::Method => "method",
+            ForwardReason::UriMiss => "uri-miss",
+            ForwardReason::VaryMiss => "vary-miss",
+            ForwardReason::Miss => "miss",
+            ForwardReason::Request => "request",
+            ForwardReason::Stale => "stale",
+            ForwardReason::Partial => "partial",
+        }
+    }
+
+    fn from_token(s: &str) -> Option<ForwardReason> {
+        Some(match s {
+            "bypass" => ForwardReason::Bypass,
+            "method" => ForwardReason::Method,
+            "uri-miss" => ForwardReason::UriMiss,
+            "vary-miss" => ForwardReason::VaryMiss,
+            "miss" => ForwardReason::Miss,
+            "request" => ForwardReason::Request,
+            "stale" => ForwardReason::Stale,
+            "partial" => ForwardReason::Partial,
+            _ => return None,
+        })
+    }
+}
+
+impl fmt::Display for CacheStatusEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl ::Header for CacheStatus {
+    fn name() -> &'static ::HeaderName {
+        static_header_name!("cache-status")
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        let entries = structured::parse_list(values)?
+            .into_iter()
+            .map(|member| member.into_item().and_then(CacheStatusEntry::from_item))
+            .collect::<Option<SmallList<_>>>()?;
+
+        if entries.is_empty() {
+            None
+        } else {
+            Some(CacheStatus(entries))
+        }
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        values.append_fmt(structured::List(&self.0));
+    }
+}
+
+impl FromIterator<CacheStatusEntry> for CacheStatus {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = CacheStatusEntry>,
+    {
+        CacheStatus(iter.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{test_decode, test_encode};
+
+    #[test]
+    fn decode_hit() {
+        let status = test_decode::<CacheStatus>(&["ExampleCache; hit; ttl=300"]).unwrap();
+        let entry = status.last().unwrap();
+        assert_eq!(entry.cache(), "ExampleCache");
+        assert!(entry.is_hit());
+        assert_eq!(entry.ttl(), Some(Duration::from_secs(300)));
+        assert_eq!(entry.fwd(), None);
+        assert!(!entry.is_stored());
+    }
+
+    #[test]
+    fn decode_multiple_caches() {
+        let status = test_decode::<CacheStatus>(&[
+            "OriginCache; hit; ttl=1100",
+            "\"CDN Company Here\"; fwd=uri-miss; fwd-status=200; stored; collapsed",
+        ]).unwrap();
+
+        let caches = status.iter().map(|e| e.cache()).collect::<Vec<_>>();
+        assert_eq!(caches, ["OriginCache", "CDN Company Here"]);
+
+        let last = status.last().unwrap();
+        assert!(!last.is_hit());
+        assert_eq!(last.fwd(), Some(ForwardReason::UriMiss));
+        assert_eq!(last.fwd_status(), Some(StatusCode::OK));
+        assert!(last.is_stored());
+        assert!(last.is_collapsed());
+    }
+
+    #[test]
+    fn decode_stale_ttl_and_details() {
+        let status = test_decode::<CacheStatus>(&[
+            "ExampleCache; hit; ttl=-412; key=\"/a\"; detail=memory",
+        ]).unwrap();
+        let entry = status.last().unwrap();
+        assert_eq!(entry.ttl(), None);
+        assert_eq!(entry.key(), Some("/a"));
+        assert_eq!(entry.detail(), Some("memory"));
+    }
+
+    #[test]
+    fn decode_invalid() {
+        assert_eq!(test_decode::<CacheStatus>(&["ExampleCache; hit,"]), None);
+        assert_eq!(test_decode::<CacheStatus>(&["42; hit"]), None);
+        assert_eq!(test_decode::<CacheStatus>(&[""]), None);
+    }
+
+    #[test]
+    fn unknown_params_round_trip() {
+        let status = test_decode::<CacheStatus>(&[
+            "ExampleCache; hit; x-vendor=\"abc\"; ttl=30; x-flag",
+        ]).unwrap();
+        let headers = test_encode(status);
+        assert_eq!(
+            headers["cache-status"],
+            "ExampleCache;hit;x-vendor=\"abc\";ttl=30;x-flag",
+        );
+    }
+
+    #[test]
+    fn encode() {
+        let status = vec![
+            CacheStatusEntry::new("OriginCache")
+                .with_hit()
+                .with_ttl(Duration::from_secs(1100)),
+            CacheStatusEntry::new("CDN Company Here")
+                .with_fwd(ForwardReason::Stale)
+                .with_fwd_status(StatusCode::NOT_MODIFIED)
+                .with_stored(),
+        ].into_iter().collect::<CacheStatus>();
+
+        let headers = test_encode(status);
+        assert_eq!(
+            headers["cache-status"],
+            "OriginCache;hit;ttl=1100, \"CDN Company Here\";fwd=stale;fwd-status=304;stored",
+        );
+    }
+
+    #[test]
+    fn many_caches() {
+        let value = (0..10)
+            .map(|i| format!("Cache{};hit", i))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let status = test_decode::<CacheStatus>(&[&value]).unwrap();
+        assert_eq!(status.iter().count(), 10);
+        assert_eq!(status.last().unwrap().cache(), "Cache9");
+
+        let headers = test_encode(status);
+        assert_eq!(headers["cache-status"], value.as_str());
+    }
+}
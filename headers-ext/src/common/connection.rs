@@ -1,6 +1,6 @@
 use std::iter::FromIterator;
 
-use util::FlatCsv;
+use util::{token, FlatCsv};
 use ::{HeaderName, HeaderValue};
 use self::sealed::AsConnectionOption;
 
@@ -84,8 +84,20 @@ impl Connection {
         self
             .0
             .iter()
-            .find(|&opt| opt == s)
-            .is_some()
+            .any(|opt| token::eq_ignore_ascii_case_trimmed(opt.as_bytes(), s))
+    }
+}
+
+impl Connection {
+    /// Add the connection options of `other` that aren't already listed.
+    pub fn merge(&mut self, other: Connection) {
+        self.0 = self.0.union(&other.0, str::eq_ignore_ascii_case);
+    }
+}
+
+impl ::MergeableHeader for Connection {
+    fn merge(&mut self, other: Connection) {
+        Connection::merge(self, other);
     }
 }
 
@@ -108,22 +120,22 @@ mod sealed {
     }
     pub trait Sealed {}
 
-    impl<'a> AsConnectionOption for &'a str {
+    impl AsConnectionOption for &str {
         fn as_connection_option(&self) -> &str {
-            *self
+            self
         }
     }
 
-    impl<'a> Sealed for &'a str {}
+    impl Sealed for &str {}
 
 
-    impl<'a> AsConnectionOption for &'a ::HeaderName {
+    impl AsConnectionOption for &::HeaderName {
         fn as_connection_option(&self) -> &str {
             self.as_ref()
         }
     }
 
-    impl<'a> Sealed for &'a ::HeaderName {}
+    impl Sealed for &::HeaderName {}
 
     impl AsConnectionOption for ::HeaderName {
         fn as_connection_option(&self) -> &str {
@@ -133,3 +145,29 @@ mod sealed {
 
     impl Sealed for ::HeaderName {}
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Connection;
+    use super::super::{test_decode, test_encode};
+
+    #[test]
+    fn contains_is_case_insensitive() {
+        let conn = test_decode::<Connection>(&["Keep-Alive, UPGRADE"]).unwrap();
+        assert!(conn.contains("keep-alive"));
+        assert!(conn.contains(::http::header::UPGRADE));
+        assert!(!conn.contains("close"));
+    }
+
+    #[test]
+    fn from_iter_encodes_one_line() {
+        let conn: Connection = vec![
+            ::http::header::UPGRADE,
+            ::http::header::HeaderName::from_static("keep-alive"),
+        ].into_iter().collect();
+
+        let headers = test_encode(conn);
+        assert_eq!(headers["connection"], "upgrade, keep-alive");
+        assert_eq!(headers.get_all("connection").iter().count(), 1);
+    }
+}
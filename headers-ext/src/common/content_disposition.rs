@@ -6,6 +6,10 @@
 // Browser conformance tests at: http://greenbytes.de/tech/tc2231/
 // IANA assignment: http://www.iana.org/assignments/cont-disp/cont-disp.xhtml
 
+use std::str;
+
+use util::{ext_value, quoted, split_quoted, token};
+
 /// A `Content-Disposition` header, (re)defined in [RFC6266](https://tools.ietf.org/html/rfc6266).
 ///
 /// The Content-Disposition response header field is used to convey
@@ -14,7 +18,6 @@
 /// to use when saving the response payload locally.
 ///
 /// # ABNF
-
 /// ```text
 /// content-disposition = "Content-Disposition" ":"
 ///                       disposition-type *( ";" disposition-parm )
@@ -42,7 +45,17 @@
 /// use headers::ContentDisposition;
 ///
 /// let cd = ContentDisposition::inline();
+///
+/// let cd = ContentDisposition::attachment("£ rates.csv");
+/// assert_eq!(cd.filename().unwrap(), "£ rates.csv");
+///
+/// let cd = ContentDisposition::form_data("avatar");
+/// assert!(cd.is_form_data());
+/// assert_eq!(cd.name().unwrap(), "avatar");
 /// ```
+///
+/// Values are read as UTF-8 rather than only ASCII, since browsers send
+/// the names and filenames of `multipart/form-data` parts unencoded.
 #[derive(Clone, Debug)]
 pub struct ContentDisposition(::HeaderValue);
 
@@ -52,46 +65,118 @@ impl ContentDisposition {
         ContentDisposition(::HeaderValue::from_static("inline"))
     }
 
-    /*
+    /// Construct a `Content-Disposition: attachment` header with a filename.
+    ///
+    /// Filenames that aren't printable ASCII are sent in the `filename*`
+    /// parameter, using the `UTF-8` charset.
     pub fn attachment(filename: &str) -> ContentDisposition {
-        let full = Bytes::from(format!("attachment; filename={}", filename));
-        match ::HeaderValue::from_shared(full) {
-            Ok(val) => ContentDisposition(val),
-            Err(_) => {
-                unimplemented!("filename that isn't ASCII");
+        let full = if filename.bytes().all(|b| (0x20..0x7f).contains(&b)) {
+            format!("attachment; filename={}", quoted::quote(filename))
+        } else {
+            format!("attachment; filename*={}", ext_value::encode(filename))
+        };
+        let val = ::HeaderValue::from_str(&full)
+            .expect("escaped and ext-value filenames are valid header values");
+        ContentDisposition(val)
+    }
+
+    /// Construct a `Content-Disposition: form-data` header, for the part of
+    /// a `multipart/form-data` body holding the field `name`.
+    ///
+    /// As browsers do, `"` and control characters such as CR and LF are
+    /// percent-encoded in the name, and everything else is sent as is.
+    pub fn form_data(name: &str) -> ContentDisposition {
+        let mut full = String::from("form-data; name=\"");
+        for c in name.chars() {
+            if c == '"' || c.is_control() {
+                let mut buf = [0; 4];
+                for b in c.encode_utf8(&mut buf).bytes() {
+                    full.push_str(&format!("%{:02X}", b));
+                }
+            } else {
+                full.push(c);
             }
         }
+        full.push('"');
+        let val = ::HeaderValue::from_bytes(full.as_bytes())
+            .expect("escaped form-data names are valid header values");
+        ContentDisposition(val)
     }
-    */
 
     /// Check if the disposition-type is `inline`.
     pub fn is_inline(&self) -> bool {
-        self.get_type() == "inline"
+        self.get_type().eq_ignore_ascii_case("inline")
     }
 
     /// Check if the disposition-type is `attachment`.
     pub fn is_attachment(&self) -> bool {
-        self.get_type() == "attachment"
+        self.get_type().eq_ignore_ascii_case("attachment")
     }
 
     /// Check if the disposition-type is `form-data`.
     pub fn is_form_data(&self) -> bool {
-        self.get_type() == "form-data"
+        self.get_type().eq_ignore_ascii_case("form-data")
+    }
+
+    /// Get the `name` parameter, the name of the form field a part of a
+    /// `multipart/form-data` body holds.
+    ///
+    /// The name is unquoted, but any percent-encoding is left as is, since
+    /// it can't be told apart from a name containing `%`.
+    pub fn name(&self) -> Option<String> {
+        self.params()
+            .find(|&(name, _)| name.eq_ignore_ascii_case("name"))
+            .and_then(|(_, value)| quoted::unquote(value).map(Into::into))
+    }
+
+    /// Get the `filename` parameter, if any.
+    ///
+    /// `filename*` is preferred over `filename` when both are present and
+    /// it can be decoded.
+    pub fn filename(&self) -> Option<String> {
+        let mut plain = None;
+        for (name, value) in self.params() {
+            if name.eq_ignore_ascii_case("filename*") {
+                if let Some(decoded) = ext_value::decode(value) {
+                    return Some(decoded);
+                }
+            } else if name.eq_ignore_ascii_case("filename") && plain.is_none() {
+                plain = quoted::unquote(value).map(Into::into);
+            }
+        }
+        plain
+    }
+
+    fn as_str(&self) -> &str {
+        str::from_utf8(self.0.as_bytes()).unwrap_or("")
     }
 
     fn get_type(&self) -> &str {
-        self
-            .0
-            .to_str()
-            .unwrap_or("")
-            .split(';')
-            .next()
-            .expect("split always has at least 1 item")
+        token::trim_ows(
+            self.as_str()
+                .split(';')
+                .next()
+                .expect("split always has at least 1 item")
+        )
+    }
+
+    /// The `name=value` parameters after the type, trimmed.
+    fn params(&self) -> impl Iterator<Item = (&str, &str)> {
+        split_quoted(self.as_str(), b';')
+            .skip(1)
+            .filter_map(|param| {
+                let mut parts = param.splitn(2, '=');
+                let name = token::trim_ows(parts.next()?);
+                let value = token::trim_ows(parts.next()?);
+                Some((name, value))
+            })
     }
 }
 
 impl ::Header for ContentDisposition {
-    const NAME: &'static ::HeaderName = &::http::header::CONTENT_DISPOSITION;
+    fn name() -> &'static ::HeaderName {
+        &::http::header::CONTENT_DISPOSITION
+    }
 
     fn decode(values: &mut ::Values) -> Option<Self> {
         //TODO: parse harder
@@ -105,6 +190,89 @@ impl ::Header for ContentDisposition {
         values.append(self.0.clone());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ContentDisposition;
+    use super::super::{test_decode, test_encode};
+
+    #[test]
+    fn filename() {
+        let cd = test_decode::<ContentDisposition>(&[
+            "form-data; name=upload; filename=\"sample; \\\"1\\\".png\"",
+        ]).unwrap();
+        assert!(cd.is_form_data());
+        assert_eq!(cd.filename().unwrap(), "sample; \"1\".png");
+
+        let cd = test_decode::<ContentDisposition>(&["inline"]).unwrap();
+        assert_eq!(cd.filename(), None);
+    }
+
+    #[test]
+    fn form_data() {
+        let cd = test_decode::<ContentDisposition>(&["Form-Data ; NAME=\"field\";filename=x.txt"]).unwrap();
+        assert!(cd.is_form_data());
+        assert!(!cd.is_attachment());
+        assert_eq!(cd.name().unwrap(), "field");
+        assert_eq!(cd.filename().unwrap(), "x.txt");
+
+        // A bare token, and `name` is only matched exactly.
+        let cd = test_decode::<ContentDisposition>(&["form-data; filename=a; name=b"]).unwrap();
+        assert_eq!(cd.name().unwrap(), "b");
+        let cd = test_decode::<ContentDisposition>(&["form-data; names=a; name*=b"]).unwrap();
+        assert_eq!(cd.name(), None);
+
+        // Browsers send UTF-8 as is.
+        let value = ::HeaderValue::from_bytes("form-data; name=\"prénom\"; filename=\"été.png\"".as_bytes()).unwrap();
+        let cd = ::headers_core::decode::from_value::<ContentDisposition>(&value).unwrap();
+        assert_eq!(cd.name().unwrap(), "prénom");
+        assert_eq!(cd.filename().unwrap(), "été.png");
+    }
+
+    #[test]
+    fn form_data_encode() {
+        let headers = test_encode(ContentDisposition::form_data("user[name]"));
+        assert_eq!(headers["content-disposition"], "form-data; name=\"user[name]\"");
+
+        let cd = ContentDisposition::form_data("a \"b\"\r\nc; ü");
+        assert_eq!(cd.name().unwrap(), "a %22b%22%0D%0Ac; ü");
+        assert!(cd.is_form_data());
+    }
+
+    #[test]
+    fn filename_prefers_ext_value() {
+        let cd = test_decode::<ContentDisposition>(&[
+            "attachment; filename=\"EURO rates\"; filename*=utf-8''%e2%82%ac%20rates",
+        ]).unwrap();
+        assert_eq!(cd.filename().unwrap(), "€ rates");
+
+        let cd = test_decode::<ContentDisposition>(&[
+            "attachment; filename*=ISO-8859-1''%A3; filename=fallback",
+        ]).unwrap();
+        assert_eq!(cd.filename().unwrap(), "fallback");
+    }
+
+    #[test]
+    fn attachment() {
+        let headers = test_encode(ContentDisposition::attachment("black and \"white\".csv"));
+        assert_eq!(
+            headers["content-disposition"],
+            "attachment; filename=\"black and \\\"white\\\".csv\"",
+        );
+
+        let headers = test_encode(ContentDisposition::attachment("100% €.csv"));
+        assert_eq!(
+            headers["content-disposition"],
+            "attachment; filename*=UTF-8''100%25%20%E2%82%AC.csv",
+        );
+
+        for name in &["plain.txt", "a\\b\"c", "日本語 %.pdf"] {
+            let cd = ContentDisposition::attachment(name);
+            assert!(cd.is_attachment());
+            assert_eq!(cd.filename().as_deref(), Some(*name));
+        }
+    }
+}
 /*
 use language_tags::LanguageTag;
 use std::fmt;
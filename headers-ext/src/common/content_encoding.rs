@@ -1,5 +1,5 @@
 use util::FlatCsv;
-use {HeaderValue};
+use {ContentCoding, HeaderValue};
 use self::sealed::AsCoding;
 
 /// `Content-Encoding` header, defined in
@@ -46,40 +46,56 @@ impl ContentEncoding {
     /// This can be used with these argument types:
     ///
     /// - `&str`
+    /// - `ContentCoding`, which also matches the `x-gzip` and `x-compress`
+    ///   aliases
     ///
     /// # Example
     ///
     /// ```
     /// # extern crate headers_ext as headers;
-    /// use headers::ContentEncoding;
+    /// use headers::{ContentCoding, ContentEncoding};
     ///
     /// let content_enc = ContentEncoding::gzip();
     ///
     /// assert!(content_enc.contains("gzip"));
+    /// assert!(content_enc.contains(ContentCoding::Gzip));
     /// assert!(!content_enc.contains("br"));
     /// ```
     pub fn contains(&self, coding: impl AsCoding) -> bool {
-        let s = coding.as_coding();
-        self
-            .0
-            .iter()
-            .find(|&opt| opt == s)
-            .is_some()
+        self.0.iter().any(|opt| coding.is_coding(opt))
+    }
+
+    /// Iterate the codings, in the order they were applied.
+    ///
+    /// Elements that aren't tokens are skipped.
+    pub fn iter<'a>(&'a self) -> impl Iterator<Item = ContentCoding> + 'a {
+        self.0.iter().filter_map(|opt| opt.parse().ok())
     }
 }
 
 mod sealed {
+    use ContentCoding;
+    use util::token;
+
     pub trait AsCoding: Sealed {}
 
     pub trait Sealed {
-        fn as_coding(&self) -> &str;
+        fn is_coding(&self, opt: &str) -> bool;
+    }
+
+    impl AsCoding for &str {}
+
+    impl Sealed for &str {
+        fn is_coding(&self, opt: &str) -> bool {
+            token::eq_ignore_ascii_case_trimmed(opt.as_bytes(), self)
+        }
     }
 
-    impl<'a> AsCoding for &'a str {}
+    impl AsCoding for ContentCoding {}
 
-    impl<'a> Sealed for &'a str {
-        fn as_coding(&self) -> &str {
-            *self
+    impl Sealed for ContentCoding {
+        fn is_coding(&self, opt: &str) -> bool {
+            opt.parse::<ContentCoding>().is_ok_and(|coding| coding == *self)
         }
     }
 }
@@ -1,4 +1,5 @@
-use {Header, ToValues, Values};
+use {Header, ParseMode, ToValues, Values};
+use util::{digits, token};
 
 /// `Content-Length` header, defined in
 /// [RFC7230](http://tools.ietf.org/html/rfc7230#section-3.3.2)
@@ -40,27 +41,47 @@ use {Header, ToValues, Values};
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct ContentLength(pub u64);
 
+impl ContentLength {
+    /// Create a `ContentLength` from the size hint of a body, if it knows
+    /// its exact length.
+    ///
+    /// The hint is a lower bound and an optional upper bound, like
+    /// `Iterator::size_hint`. A body with an unknown length needs a
+    /// different framing, such as chunked.
+    pub fn from_body_hint(lower: u64, upper: Option<u64>) -> Option<ContentLength> {
+        match upper {
+            Some(upper) if upper == lower => Some(ContentLength(lower)),
+            _ => None,
+        }
+    }
+}
+
 impl Header for ContentLength {
-    const NAME: &'static ::http::header::HeaderName = &::http::header::CONTENT_LENGTH;
+    fn name() -> &'static ::http::header::HeaderName {
+        &::http::header::CONTENT_LENGTH
+    }
 
     fn decode(values: &mut Values) -> Option<Self> {
+        if values.mode() == ParseMode::Strict {
+            // Only a single value is allowed, checked by `typed_get`.
+            return digits::parse_u64(values.next()?.as_bytes()).map(ContentLength);
+        }
+
         // If multiple Content-Length headers were sent, everything can still
         // be alright if they all contain the same value, and all parse
-        // correctly. If not, then it's an error.
+        // correctly. If not, then it's an error. Some proxies also join
+        // them into a list, which is treated the same way.
         let mut len = None;
         for value in values {
-            let parsed = value
-                .to_str()
-                .ok()?
-                .parse::<u64>()
-                .ok()?;
-
-            if let Some(prev) = len {
-                if prev != parsed {
+            let value = value.to_str().ok()?;
+            let items = value.split(',');
+            let trim = value.contains(',');
+            for item in items {
+                let item = if trim { token::trim_ows(item) } else { item };
+                let n = digits::parse_u64(item.as_bytes())?;
+                if *len.get_or_insert(n) != n {
                     return None;
                 }
-            } else {
-                len = Some(parsed);
             }
         }
 
@@ -72,6 +93,42 @@ impl Header for ContentLength {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::ContentLength;
+    use super::super::test_decode;
+
+    #[test]
+    fn decode() {
+        assert_eq!(test_decode::<ContentLength>(&["3495"]), Some(ContentLength(3495)));
+        assert_eq!(test_decode::<ContentLength>(&["0"]), Some(ContentLength(0)));
+    }
+
+    #[test]
+    fn decode_only_digits() {
+        assert_eq!(test_decode::<ContentLength>(&["+5"]), None);
+        assert_eq!(test_decode::<ContentLength>(&["-5"]), None);
+        assert_eq!(test_decode::<ContentLength>(&[" 5"]), None);
+        assert_eq!(test_decode::<ContentLength>(&[""]), None);
+        assert_eq!(test_decode::<ContentLength>(&["18446744073709551616"]), None);
+    }
+
+    #[test]
+    fn from_body_hint() {
+        assert_eq!(ContentLength::from_body_hint(5, Some(5)), Some(ContentLength(5)));
+        assert_eq!(ContentLength::from_body_hint(0, Some(0)), Some(ContentLength(0)));
+        assert_eq!(ContentLength::from_body_hint(5, Some(6)), None);
+        assert_eq!(ContentLength::from_body_hint(5, None), None);
+    }
+
+    #[test]
+    fn decode_repeated() {
+        assert_eq!(test_decode::<ContentLength>(&["5", "5"]), Some(ContentLength(5)));
+        assert_eq!(test_decode::<ContentLength>(&["5", "6"]), None);
+        assert_eq!(test_decode::<ContentLength>(&["5", "x"]), None);
+    }
+}
+
 /*
 __hyper__tm!(ContentLength, tests {
     // Testcase from RFC
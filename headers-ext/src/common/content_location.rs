@@ -1,4 +1,7 @@
-use ::HeaderValue;
+use http::Uri;
+
+use util::HeaderString;
+use util::uri_reference::{parse_reference, UriReference};
 
 /// `Content-Location` header, defined in
 /// [RFC7231](https://tools.ietf.org/html/rfc7231#section-3.1.4.2)
@@ -24,9 +27,44 @@ use ::HeaderValue;
 ///
 /// # Examples
 ///
-//TODO: make this a `Uri`?
+/// ```
+/// # extern crate headers_ext as headers;
+/// # extern crate http;
+/// use headers::{ContentLocation, HeaderMap, HeaderMapExt};
+///
+/// let mut map = HeaderMap::new();
+/// map.insert("content-location", "./Overview.html".parse().unwrap());
+///
+/// let location = map.typed_get::<ContentLocation>().unwrap();
+/// let request = "http://www.example.org/hypertext/Overview.html".parse().unwrap();
+/// assert!(location.refers_to(&request));
+/// ```
 #[derive(Clone, Debug, PartialEq, Header)]
-pub struct ContentLocation(HeaderValue);
+pub struct ContentLocation(HeaderString);
+
+impl ContentLocation {
+    /// Resolve the location against `base`, usually the URI of the request
+    /// the response answers.
+    ///
+    /// Returns `None` if the location isn't a valid URI reference.
+    pub fn resolve(&self, base: &Uri) -> Option<Uri> {
+        self.reference()?.resolve(base)
+    }
+
+    /// Check if this refers to `request_uri` itself, once resolved against
+    /// it, meaning the payload is a representation of the target resource.
+    ///
+    /// Both are normalized first, so case differences in the scheme and
+    /// host, default ports and dot segments don't matter.
+    pub fn refers_to(&self, request_uri: &Uri) -> bool {
+        self.reference()
+            .is_some_and(|r| r.resolves_to(request_uri, request_uri))
+    }
+
+    fn reference(&self) -> Option<UriReference> {
+        parse_reference(&self.0)
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -38,7 +76,7 @@ mod tests {
         let s = "http://www.example.net/index.html";
         let loc = test_decode::<ContentLocation>(&[s]).unwrap();
 
-        assert_eq!(loc, ContentLocation(HeaderValue::from_static(s)));
+        assert_eq!(loc, ContentLocation(HeaderString::from_static(s)));
     }
 
     #[test]
@@ -46,6 +84,26 @@ mod tests {
         let s = "/People.html#tim";
         let loc = test_decode::<ContentLocation>(&[s]).unwrap();
 
-        assert_eq!(loc, ContentLocation(HeaderValue::from_static(s)));
+        assert_eq!(loc, ContentLocation(HeaderString::from_static(s)));
+    }
+
+    #[test]
+    fn resolve() {
+        let loc = test_decode::<ContentLocation>(&["../docs/a.json"]).unwrap();
+        let base = "https://example.com/api/v1/a".parse().unwrap();
+        assert_eq!(loc.resolve(&base).unwrap(), "https://example.com/api/docs/a.json");
+    }
+
+    #[test]
+    fn refers_to() {
+        let request = "http://www.example.org/hypertext/Overview.html".parse().unwrap();
+        let refers_to = |s| test_decode::<ContentLocation>(&[s]).unwrap().refers_to(&request);
+
+        assert!(refers_to("/hypertext/Overview.html"));
+        assert!(refers_to("HTTP://WWW.example.org:80/hypertext/./Overview.html"));
+        assert!(refers_to("Overview.html#top"));
+        assert!(!refers_to("/hypertext/Overview.html?lang=de"));
+        assert!(!refers_to("https://www.example.org/hypertext/Overview.html"));
+        assert!(!refers_to("not a uri"));
     }
 }
@@ -0,0 +1,210 @@
+use base64;
+#[cfg(feature = "md5")]
+use md5::{Digest, Md5};
+
+use util::token;
+
+/// `Content-MD5` header, defined in
+/// [RFC1864](https://tools.ietf.org/html/rfc1864)
+///
+/// The `Content-MD5` header field holds the MD5 digest of the body, in
+/// base64, for checking that it wasn't altered on the way. HTTP removed it
+/// in [RFC7231](https://tools.ietf.org/html/rfc7231#appendix-B), but older
+/// gateways and S3-compatible APIs still expect it.
+///
+/// # ABNF
+///
+/// ```text
+/// Content-MD5 = <base64 of 128 bit MD5 digest as per RFC 1864>
+/// ```
+///
+/// # Example values
+///
+/// * `Q2hlY2sgSW50ZWdyaXR5IQ==`
+///
+/// # Notes
+///
+/// * Whitespace around the value is trimmed, but not inside it.
+/// * The padding can only be left out when lenient.
+/// * With the `md5` feature, `ContentMd5::of` digests a body, and `verify`
+///   checks one.
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::{ContentMd5, HeaderMap, HeaderMapExt};
+///
+/// // The MD5 digest of an empty body.
+/// let md5 = ContentMd5::from([
+///     0xd4, 0x1d, 0x8c, 0xd9, 0x8f, 0x00, 0xb2, 0x04,
+///     0xe9, 0x80, 0x09, 0x98, 0xec, 0xf8, 0x42, 0x7e,
+/// ]);
+///
+/// let mut map = HeaderMap::new();
+/// map.typed_insert(md5);
+/// assert_eq!(map["content-md5"], "1B2M2Y8AsgTpgAmY7PhCfg==");
+/// assert_eq!(map.typed_get(), Some(md5));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ContentMd5([u8; 16]);
+
+impl ContentMd5 {
+    /// Digest `body`.
+    #[cfg(feature = "md5")]
+    pub fn of(body: &[u8]) -> ContentMd5 {
+        let mut md5 = Md5::default();
+        md5.input(body);
+        let mut digest = [0; 16];
+        digest.copy_from_slice(&md5.result());
+        ContentMd5(digest)
+    }
+
+    /// Check if `body` has this digest.
+    #[cfg(feature = "md5")]
+    pub fn verify(&self, body: &[u8]) -> bool {
+        ContentMd5::of(body) == *self
+    }
+
+    /// Get the 16 bytes of the digest.
+    pub fn as_bytes(&self) -> &[u8; 16] {
+        &self.0
+    }
+
+    fn encoded(&self) -> String {
+        base64::encode(&self.0)
+    }
+}
+
+impl From<[u8; 16]> for ContentMd5 {
+    fn from(digest: [u8; 16]) -> ContentMd5 {
+        ContentMd5(digest)
+    }
+}
+
+impl From<ContentMd5> for [u8; 16] {
+    fn from(md5: ContentMd5) -> [u8; 16] {
+        md5.0
+    }
+}
+
+impl ::Header for ContentMd5 {
+    fn name() -> &'static ::HeaderName {
+        static_header_name!("content-md5")
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        let value = token::trim_ows(values.next()?.to_str().ok()?);
+        // 16 bytes are 22 characters, and two of padding.
+        let unpadded = match values.mode() {
+            ::ParseMode::Strict => value.strip_suffix("==")?,
+            ::ParseMode::Lenient => value.strip_suffix("==").unwrap_or(value),
+        };
+        if unpadded.len() != 22 || !unpadded.bytes().all(is_base64) {
+            return None;
+        }
+        let bytes = base64::decode(unpadded).ok()?;
+        let mut digest = [0; 16];
+        digest.copy_from_slice(&bytes);
+        let md5 = ContentMd5(digest);
+        // The bits left over in the last character must be zero.
+        if values.mode() == ::ParseMode::Strict && md5.encoded() != value {
+            return None;
+        }
+        Some(md5)
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        values.append_fmt(self.encoded());
+    }
+}
+
+fn is_base64(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'+' || b == b'/'
+}
+
+#[cfg(test)]
+mod tests {
+    use headers_core::test_helpers::decode_from_strs_with;
+
+    use super::ContentMd5;
+    use super::super::{test_decode, test_encode};
+    use ParseMode;
+
+    const EMPTY: [u8; 16] = [
+        0xd4, 0x1d, 0x8c, 0xd9, 0x8f, 0x00, 0xb2, 0x04,
+        0xe9, 0x80, 0x09, 0x98, 0xec, 0xf8, 0x42, 0x7e,
+    ];
+
+    fn strict(value: &str) -> Option<ContentMd5> {
+        decode_from_strs_with(&[value], ParseMode::Strict)
+    }
+
+    #[test]
+    fn decode() {
+        assert_eq!(test_decode::<ContentMd5>(&["1B2M2Y8AsgTpgAmY7PhCfg=="]), Some(ContentMd5::from(EMPTY)));
+        assert_eq!(strict("1B2M2Y8AsgTpgAmY7PhCfg=="), Some(ContentMd5::from(EMPTY)));
+        // RFC1864 has no example, this is the one of S3's docs.
+        let md5 = test_decode::<ContentMd5>(&["Q2hlY2sgSW50ZWdyaXR5IQ=="]).unwrap();
+        assert_eq!(md5.as_bytes(), b"Check Integrity!");
+    }
+
+    #[test]
+    fn padding() {
+        // Only lenient parsing allows it to be left out.
+        assert_eq!(test_decode::<ContentMd5>(&["1B2M2Y8AsgTpgAmY7PhCfg"]), Some(ContentMd5::from(EMPTY)));
+        assert_eq!(strict("1B2M2Y8AsgTpgAmY7PhCfg"), None);
+        for value in &["1B2M2Y8AsgTpgAmY7PhCfg=", "1B2M2Y8AsgTpgAmY7PhCfg===", "1B2M2Y8AsgTpgAmY7PhC=fg="] {
+            assert_eq!(test_decode::<ContentMd5>(&[value]), None, "{:?}", value);
+            assert_eq!(strict(value), None, "{:?}", value);
+        }
+        // Bits past the 128th are set, which only lenient parsing ignores.
+        assert_eq!(test_decode::<ContentMd5>(&["1B2M2Y8AsgTpgAmY7PhCfh=="]), Some(ContentMd5::from(EMPTY)));
+        assert_eq!(strict("1B2M2Y8AsgTpgAmY7PhCfh=="), None);
+    }
+
+    #[test]
+    fn whitespace() {
+        for value in &[" 1B2M2Y8AsgTpgAmY7PhCfg==", "1B2M2Y8AsgTpgAmY7PhCfg==\t", " \t1B2M2Y8AsgTpgAmY7PhCfg== "] {
+            assert_eq!(test_decode::<ContentMd5>(&[value]), Some(ContentMd5::from(EMPTY)), "{:?}", value);
+            assert_eq!(strict(value), Some(ContentMd5::from(EMPTY)), "{:?}", value);
+        }
+        for value in &["1B2M2Y8A sgTpgAmY7PhCfg==", "1B2M2Y8AsgTpgAmY7PhCfg ==", "1B2M2Y8AsgTpgAmY\t7PhCfg"] {
+            assert_eq!(test_decode::<ContentMd5>(&[value]), None, "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn decode_invalid() {
+        for value in &[
+            "",
+            // 15 and 17 bytes
+            "1B2M2Y8AsgTpgAmY7PhC",
+            "1B2M2Y8AsgTpgAmY7PhCfn4=",
+            "1B2M2Y8AsgTpgAmY7PhCfn5+",
+            // hex, and the URL-safe alphabet
+            "d41d8cd98f00b204e9800998ecf8427e",
+            "1B2M2Y8AsgTpgAmY7PhC_g==",
+        ] {
+            assert_eq!(test_decode::<ContentMd5>(&[value]), None, "{:?}", value);
+        }
+        assert_eq!(test_decode::<ContentMd5>(&["1B2M2Y8AsgTpgAmY7PhCfg==", "1B2M2Y8AsgTpgAmY7PhCfg=="]), None);
+    }
+
+    #[test]
+    fn encode() {
+        let headers = test_encode(ContentMd5::from(*b"Check Integrity!"));
+        assert_eq!(headers["content-md5"], "Q2hlY2sgSW50ZWdyaXR5IQ==");
+        assert_eq!(<[u8; 16]>::from(ContentMd5::from(EMPTY)), EMPTY);
+    }
+
+    #[cfg(feature = "md5")]
+    #[test]
+    fn verify() {
+        let md5 = ContentMd5::of(b"");
+        assert_eq!(md5, ContentMd5::from(EMPTY));
+        assert!(md5.verify(b""));
+        assert!(!md5.verify(b"tampered"));
+        assert_eq!(test_encode(ContentMd5::of(b"hello"))["content-md5"], "XUFAKrxLKna5cZ2REBfFkg==");
+    }
+}
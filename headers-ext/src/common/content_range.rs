@@ -1,5 +1,9 @@
 use std::fmt;
 
+use util::{HeaderString, RangeUnit};
+
+use super::range::InvalidRange;
+
 /// Content-Range, described in [RFC7233](https://tools.ietf.org/html/rfc7233#section-4.2)
 ///
 /// # ABNF
@@ -25,20 +29,34 @@ use std::fmt;
 ///
 /// ```
 /// # extern crate headers_ext as headers;
-/// use headers::ContentRange;
+/// use headers::{ContentRange, RangeUnit};
 ///
 /// // 100 bytes (included byte 199), with a full length of 3,400
-/// let cr = ContentRange(100, 199, 3400);
+/// let cr = ContentRange::bytes(100, 199, 3400);
+///
+/// // The first page of a collection of 200 items, in a unit of the API.
+/// let cr = ContentRange::other(RangeUnit::Other("items".into()), "0-49/200").unwrap();
+/// assert_eq!(cr.to_string(), "items 0-49/200");
+/// assert_eq!(cr.bytes_range(), None);
 /// ```
-//NOTE: only supporting bytes-content-range, YAGNI the extension
 #[derive(Clone, Debug, PartialEq)]
-pub struct ContentRange {
-    /// First and last bytes of the range, omitted if request could not be
-    /// satisfied
-    range: Option<(u64, u64)>,
+pub struct ContentRange(Spec);
 
-    /// Total length of the instance, can be omitted if unknown
-    complete_length: Option<u64>,
+#[derive(Clone, Debug, PartialEq)]
+enum Spec {
+    Bytes {
+        /// First and last bytes of the range, omitted if request could not be
+        /// satisfied
+        range: Option<(u64, u64)>,
+
+        /// Total length of the instance, can be omitted if unknown
+        complete_length: Option<u64>,
+    },
+    /// A unit other than `bytes`, with its range passed through as is.
+    Other {
+        unit: RangeUnit,
+        resp: HeaderString,
+    },
 }
 
 impl ContentRange {
@@ -48,19 +66,44 @@ impl ContentRange {
     pub fn bytes(first_byte: u64, last_byte: u64, complete_length: impl Into<Option<u64>>) -> ContentRange {
         let complete_length = complete_length.into();
 
-        ContentRange {
+        ContentRange(Spec::Bytes {
             range: Some((first_byte, last_byte)),
             complete_length,
-        }
+        })
     }
 
     /// Create a new `ContentRange` stating the range could not be satisfied.
     ///
     /// The passed argument is the complete length of the entity.
     pub fn unsatisfied_bytes(complete_length: u64) -> Self {
-        ContentRange {
+        ContentRange(Spec::Bytes {
             range: None,
             complete_length: Some(complete_length),
+        })
+    }
+
+    /// Construct a `Content-Range` of another unit than `bytes`, such as
+    /// `items 0-49/200`, with `resp` sent as is.
+    ///
+    /// Fails for the `bytes` and `none` units, or if `resp` isn't a valid
+    /// header value.
+    pub fn other(unit: RangeUnit, resp: &str) -> Result<ContentRange, InvalidRange> {
+        if let RangeUnit::Other(_) = unit {
+            let resp = resp.parse().map_err(|_| InvalidRange(()))?;
+            Ok(ContentRange(Spec::Other {
+                unit,
+                resp,
+            }))
+        } else {
+            Err(InvalidRange(()))
+        }
+    }
+
+    /// Get the unit of the range.
+    pub fn unit(&self) -> RangeUnit {
+        match self.0 {
+            Spec::Bytes { .. } => RangeUnit::Bytes,
+            Spec::Other { ref unit, .. } => unit.clone(),
         }
     }
 
@@ -68,17 +111,34 @@ impl ContentRange {
     ///
     /// Note that these byte ranges are inclusive on both ends.
     pub fn bytes_range(&self) -> Option<(u64, u64)> {
-        self.range
+        match self.0 {
+            Spec::Bytes { range, .. } => range,
+            Spec::Other { .. } => None,
+        }
     }
 
     /// Get the bytes complete length if available.
     pub fn bytes_len(&self) -> Option<u64> {
-        self.complete_length
+        match self.0 {
+            Spec::Bytes { complete_length, .. } => complete_length,
+            Spec::Other { .. } => None,
+        }
+    }
+
+    /// Get the range of another unit than `bytes`, as it was sent, such as
+    /// `0-49/200` of `items 0-49/200`.
+    pub fn other_resp(&self) -> Option<&str> {
+        match self.0 {
+            Spec::Bytes { .. } => None,
+            Spec::Other { ref resp, .. } => Some(resp),
+        }
     }
 }
 
 impl ::Header for ContentRange {
-    const NAME: &'static ::HeaderName = &::http::header::CONTENT_RANGE;
+    fn name() -> &'static ::HeaderName {
+        &::http::header::CONTENT_RANGE
+    }
 
     fn decode(values: &mut ::Values) -> Option<Self> {
         let s = values
@@ -88,9 +148,10 @@ impl ::Header for ContentRange {
 
         let (unit, spec) = split_in_two(s, ' ')?;
 
-        if unit != "bytes" {
-            // For now, this only supports bytes-content-range. nani?
-            return None;
+        match unit.parse().ok()? {
+            RangeUnit::Bytes => (),
+            RangeUnit::None => return None,
+            unit => return ContentRange::other(unit, spec).ok(),
         }
 
         let (range, complete_length) = split_in_two(spec, '/')?;
@@ -113,22 +174,27 @@ impl ::Header for ContentRange {
             Some((first_byte, last_byte))
         };
 
-        Some(ContentRange {
+        Some(ContentRange(Spec::Bytes {
             range,
             complete_length,
-        })
+        }))
     }
 
     fn encode(&self, values: &mut ::ToValues) {
-        values.append_fmt(&Adapter(self));
+        values.append_fmt(Adapter(self));
 
         struct Adapter<'a>(&'a ContentRange);
 
         impl<'a> fmt::Display for Adapter<'a> {
             fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                let (range, complete_length) = match (self.0).0 {
+                    Spec::Bytes { range, complete_length } => (range, complete_length),
+                    Spec::Other { ref unit, ref resp } => return write!(f, "{} {}", unit, resp),
+                };
+
                 f.write_str("bytes ")?;
 
-                if let Some((first_byte, last_byte)) = self.0.range {
+                if let Some((first_byte, last_byte)) = range {
                     write!(f, "{}-{}", first_byte, last_byte)?;
                 } else {
                     f.write_str("*")?;
@@ -136,7 +202,7 @@ impl ::Header for ContentRange {
 
                 f.write_str("/")?;
 
-                if let Some(v) = self.0.complete_length {
+                if let Some(v) = complete_length {
                     write!(f, "{}", v)
                 } else {
                     f.write_str("*")
@@ -211,3 +277,36 @@ fn split_in_two(s: &str, separator: char) -> Option<(&str, &str)> {
             vec![b"bytes 1-2-3/500"],
             None::<ContentRange>);
 */
+
+#[cfg(test)]
+mod tests {
+    use super::ContentRange;
+    use super::super::{test_decode, test_encode};
+    use RangeUnit;
+
+    #[test]
+    fn decode_bytes() {
+        let cr = test_decode::<ContentRange>(&["bytes 0-499/500"]).unwrap();
+        assert_eq!(cr, ContentRange::bytes(0, 499, 500));
+        assert_eq!(cr.unit(), RangeUnit::Bytes);
+        assert_eq!(cr.other_resp(), None);
+        assert_eq!(test_decode::<ContentRange>(&["bytes */500"]), Some(ContentRange::unsatisfied_bytes(500)));
+        assert_eq!(test_decode::<ContentRange>(&["bytes 499-0/500"]), None);
+    }
+
+    #[test]
+    fn other_unit() {
+        let items = RangeUnit::Other("items".into());
+        let cr = test_decode::<ContentRange>(&["items 0-49/200"]).unwrap();
+        assert_eq!(cr.unit(), items);
+        assert_eq!(cr.other_resp(), Some("0-49/200"));
+        assert_eq!((cr.bytes_range(), cr.bytes_len()), (None, None));
+        assert_eq!(cr, ContentRange::other(items.clone(), "0-49/200").unwrap());
+        assert_eq!(test_encode(cr)["content-range"], "items 0-49/200");
+
+        assert!(ContentRange::other(RangeUnit::Bytes, "0-49/200").is_err());
+        assert!(ContentRange::other(RangeUnit::None, "0-49/200").is_err());
+        assert!(ContentRange::other(items, "0-49\r\n").is_err());
+        assert_eq!(test_decode::<ContentRange>(&["none 0-49/200"]), None);
+    }
+}
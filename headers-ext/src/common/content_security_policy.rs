@@ -0,0 +1,50 @@
+use util::HeaderString;
+
+/// `Content-Security-Policy` header, defined in
+/// [CSP Level 3](https://www.w3.org/TR/CSP3/#csp-header)
+///
+/// The `Content-Security-Policy` header field restricts what a document
+/// may load and run, such as which origins scripts come from, as a list of
+/// policy directives.
+///
+/// The policy is kept as a string. Only one value is decoded, since a
+/// browser enforces every policy it receives.
+///
+/// # ABNF
+///
+/// ```text
+/// Content-Security-Policy = 1#serialized-policy
+/// ```
+///
+/// # Example values
+///
+/// * `default-src 'self'`
+/// * `script-src https://example.com/; frame-ancestors 'none'`
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::ContentSecurityPolicy;
+///
+/// let csp = ContentSecurityPolicy::from_static("default-src 'self'");
+/// assert_eq!(csp.as_str(), "default-src 'self'");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Header)]
+pub struct ContentSecurityPolicy(HeaderString);
+
+impl ContentSecurityPolicy {
+    /// Create a `ContentSecurityPolicy` from a static string.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the static string is not a legal header value.
+    pub fn from_static(src: &'static str) -> ContentSecurityPolicy {
+        ContentSecurityPolicy(HeaderString::from_static(src))
+    }
+
+    /// View the policy as a `&str`.
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
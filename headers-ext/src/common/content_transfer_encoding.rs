@@ -0,0 +1,100 @@
+use HeaderValue;
+
+/// `Content-Transfer-Encoding` header, defined in
+/// [RFC2045](https://tools.ietf.org/html/rfc2045#section-6)
+///
+/// The `Content-Transfer-Encoding` header field gives the encoding used to
+/// send a MIME body over a transport that may not be 8-bit clean. HTTP
+/// doesn't use it, but it can appear in the headers of the parts of a
+/// `multipart` body.
+///
+/// Mechanisms are case-insensitive, and encode in lowercase.
+///
+/// # ABNF
+///
+/// ```text
+/// encoding  = "Content-Transfer-Encoding" ":" mechanism
+/// mechanism = "7bit" / "8bit" / "binary" /
+///             "quoted-printable" / "base64" /
+///             ietf-token / x-token
+/// ```
+///
+/// # Example values
+///
+/// * `base64`
+/// * `8bit`
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::ContentTransferEncoding;
+///
+/// let cte = ContentTransferEncoding::Base64;
+/// assert!(!cte.is_identity());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Header)]
+#[header(name = "content-transfer-encoding")]
+pub enum ContentTransferEncoding {
+    /// `7bit`, short lines of US-ASCII.
+    ///
+    /// This is the default, assumed when the header is missing.
+    #[default]
+    #[header(value = "7bit")]
+    SevenBit,
+    /// `8bit`, short lines that may contain other bytes than US-ASCII.
+    #[header(value = "8bit")]
+    EightBit,
+    /// `binary`, any sequence of bytes.
+    #[header(value = "binary")]
+    Binary,
+    /// `base64`
+    #[header(value = "base64")]
+    Base64,
+    /// `quoted-printable`
+    #[header(value = "quoted-printable")]
+    QuotedPrintable,
+    /// Any other mechanism, such as an `x-` token, as it was sent.
+    #[header(other)]
+    Other(HeaderValue),
+}
+
+impl ContentTransferEncoding {
+    /// Check if the body is sent as is, without an encoding to undo. This
+    /// is true of `7bit`, `8bit` and `binary`.
+    pub fn is_identity(&self) -> bool {
+        matches!(
+            *self,
+            ContentTransferEncoding::SevenBit |
+            ContentTransferEncoding::EightBit |
+            ContentTransferEncoding::Binary
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ContentTransferEncoding;
+    use super::super::{test_decode, test_encode};
+
+    #[test]
+    fn decode() {
+        assert_eq!(test_decode(&["base64"]), Some(ContentTransferEncoding::Base64));
+        assert_eq!(test_decode(&[" Quoted-Printable"]), Some(ContentTransferEncoding::QuotedPrintable));
+        assert_eq!(test_decode(&["8BIT"]), Some(ContentTransferEncoding::EightBit));
+        assert_eq!(test_decode::<ContentTransferEncoding>(&[""]), None);
+
+        let other = test_decode::<ContentTransferEncoding>(&["x-uuencode"]).unwrap();
+        assert_eq!(other, ContentTransferEncoding::Other("x-uuencode".parse().unwrap()));
+        assert!(!other.is_identity());
+    }
+
+    #[test]
+    fn encode() {
+        let headers = test_encode(ContentTransferEncoding::Binary);
+        assert_eq!(headers["content-transfer-encoding"], "binary");
+
+        let headers = test_encode(ContentTransferEncoding::Other("x-Token".parse().unwrap()));
+        assert_eq!(headers["content-transfer-encoding"], "x-Token");
+    }
+}
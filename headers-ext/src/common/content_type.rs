@@ -1,4 +1,6 @@
 use std::fmt;
+#[cfg(feature = "file-extensions")]
+use std::path::Path;
 
 use mime::{self, Mime};
 
@@ -35,91 +37,237 @@ use mime::{self, Mime};
 /// use headers::ContentType;
 ///
 /// let ct = ContentType::json();
+///
+/// // The constants are cheap to clone, and encode without allocating.
+/// let ct = ContentType::JSON.clone();
 /// ```
-#[derive(Clone, Debug, PartialEq)]
-pub struct ContentType(Mime);
+#[derive(Clone, Debug)]
+pub struct ContentType(Mime, Option<::HeaderValue>);
 
 impl ContentType {
+    /// `application/json`
+    pub const JSON: ContentType = ContentType(mime::APPLICATION_JSON, None);
+
+    /// `text/plain`
+    pub const TEXT: ContentType = ContentType(mime::TEXT_PLAIN, None);
+
+    /// `text/plain; charset=utf-8`
+    pub const TEXT_UTF8: ContentType = ContentType(mime::TEXT_PLAIN_UTF_8, None);
+
+    /// `text/html`
+    pub const HTML: ContentType = ContentType(mime::TEXT_HTML, None);
+
+    /// `text/xml`
+    pub const XML: ContentType = ContentType(mime::TEXT_XML, None);
+
+    /// `application/x-www-form-urlencoded`
+    pub const FORM_URL_ENCODED: ContentType = ContentType(mime::APPLICATION_WWW_FORM_URLENCODED, None);
+
+    /// `image/jpeg`
+    pub const JPEG: ContentType = ContentType(mime::IMAGE_JPEG, None);
+
+    /// `image/png`
+    pub const PNG: ContentType = ContentType(mime::IMAGE_PNG, None);
+
+    /// `application/octet-stream`
+    pub const OCTET_STREAM: ContentType = ContentType(mime::APPLICATION_OCTET_STREAM, None);
+
     /// A constructor  to easily create a `Content-Type: application/json` header.
     #[inline]
     pub fn json() -> ContentType {
-        ContentType(mime::APPLICATION_JSON)
+        ContentType::JSON
     }
 
     /// A constructor  to easily create a `Content-Type: text/plain` header.
     #[inline]
     pub fn text() -> ContentType {
-        ContentType(mime::TEXT_PLAIN)
+        ContentType::TEXT
     }
 
     /// A constructor  to easily create a `Content-Type: text/plain; charset=utf-8` header.
     #[inline]
     pub fn text_utf8() -> ContentType {
-        ContentType(mime::TEXT_PLAIN_UTF_8)
+        ContentType::TEXT_UTF8
     }
 
     /// A constructor  to easily create a `Content-Type: text/html` header.
     #[inline]
     pub fn html() -> ContentType {
-        ContentType(mime::TEXT_HTML)
+        ContentType::HTML
     }
 
     /// A constructor  to easily create a `Content-Type: text/xml` header.
     #[inline]
     pub fn xml() -> ContentType {
-        ContentType(mime::TEXT_XML)
+        ContentType::XML
     }
 
     /// A constructor  to easily create a `Content-Type: application/www-form-url-encoded` header.
     #[inline]
     pub fn form_url_encoded() -> ContentType {
-        ContentType(mime::APPLICATION_WWW_FORM_URLENCODED)
+        ContentType::FORM_URL_ENCODED
     }
     /// A constructor  to easily create a `Content-Type: image/jpeg` header.
     #[inline]
     pub fn jpeg() -> ContentType {
-        ContentType(mime::IMAGE_JPEG)
+        ContentType::JPEG
     }
 
     /// A constructor  to easily create a `Content-Type: image/png` header.
     #[inline]
     pub fn png() -> ContentType {
-        ContentType(mime::IMAGE_PNG)
+        ContentType::PNG
     }
 
     /// A constructor  to easily create a `Content-Type: application/octet-stream` header.
     #[inline]
     pub fn octet_stream() -> ContentType {
-        ContentType(mime::APPLICATION_OCTET_STREAM)
+        ContentType::OCTET_STREAM
+    }
+
+    /// Guess the `Content-Type` of a file from its extension, such as
+    /// `css` for `text/css; charset=utf-8`, ignoring ASCII case.
+    ///
+    /// This only knows the most common extensions on the web, and text
+    /// types are given the `utf-8` charset.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # extern crate headers_ext as headers;
+    /// use headers::ContentType;
+    ///
+    /// let ct = ContentType::from_extension("CSS").unwrap();
+    /// assert_eq!(ct.to_string(), "text/css; charset=utf-8");
+    /// assert_eq!(ContentType::from_extension("xyz"), None);
+    /// ```
+    #[cfg(feature = "file-extensions")]
+    pub fn from_extension(ext: &str) -> Option<ContentType> {
+        EXTENSIONS
+            .iter()
+            .find(|&&(known, _)| known.eq_ignore_ascii_case(ext))
+            .map(|&(_, mime)| {
+                ContentType(mime.parse().expect("EXTENSIONS are valid mimes"), None)
+            })
+    }
+
+    /// Guess the `Content-Type` of a file from the extension of its path,
+    /// as with `from_extension`.
+    #[cfg(feature = "file-extensions")]
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<ContentType> {
+        path.as_ref()
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(ContentType::from_extension)
     }
 }
 
+#[cfg(feature = "file-extensions")]
+static EXTENSIONS: &[(&str, &str)] = &[
+    // text
+    ("css", "text/css; charset=utf-8"),
+    ("csv", "text/csv; charset=utf-8"),
+    ("htm", "text/html; charset=utf-8"),
+    ("html", "text/html; charset=utf-8"),
+    ("ics", "text/calendar; charset=utf-8"),
+    ("js", "text/javascript; charset=utf-8"),
+    ("md", "text/markdown; charset=utf-8"),
+    ("mjs", "text/javascript; charset=utf-8"),
+    ("txt", "text/plain; charset=utf-8"),
+    // application
+    ("atom", "application/atom+xml"),
+    ("bin", "application/octet-stream"),
+    ("gz", "application/gzip"),
+    ("json", "application/json"),
+    ("map", "application/json"),
+    ("pdf", "application/pdf"),
+    ("rss", "application/rss+xml"),
+    ("tar", "application/x-tar"),
+    ("wasm", "application/wasm"),
+    ("webmanifest", "application/manifest+json"),
+    ("xml", "application/xml"),
+    ("yaml", "application/yaml"),
+    ("yml", "application/yaml"),
+    ("zip", "application/zip"),
+    // image
+    ("avif", "image/avif"),
+    ("bmp", "image/bmp"),
+    ("gif", "image/gif"),
+    ("ico", "image/x-icon"),
+    ("jpeg", "image/jpeg"),
+    ("jpg", "image/jpeg"),
+    ("png", "image/png"),
+    ("svg", "image/svg+xml"),
+    ("tif", "image/tiff"),
+    ("tiff", "image/tiff"),
+    ("webp", "image/webp"),
+    // font
+    ("eot", "application/vnd.ms-fontobject"),
+    ("otf", "font/otf"),
+    ("ttf", "font/ttf"),
+    ("woff", "font/woff"),
+    ("woff2", "font/woff2"),
+    // audio and video
+    ("aac", "audio/aac"),
+    ("flac", "audio/flac"),
+    ("m4a", "audio/mp4"),
+    ("mov", "video/quicktime"),
+    ("mp3", "audio/mpeg"),
+    ("mp4", "video/mp4"),
+    ("oga", "audio/ogg"),
+    ("ogg", "audio/ogg"),
+    ("ogv", "video/ogg"),
+    ("opus", "audio/opus"),
+    ("wav", "audio/wav"),
+    ("webm", "video/webm"),
+];
+
 impl ::Header for ContentType {
-    const NAME: &'static ::HeaderName = &::http::header::CONTENT_TYPE;
+    fn name() -> &'static ::HeaderName {
+        &::http::header::CONTENT_TYPE
+    }
 
     fn decode(values: &mut ::Values) -> Option<Self> {
-        values
-            .next()?
+        let value = values.next()?;
+        value
             .to_str()
             .ok()?
             .parse()
             .ok()
-            .map(ContentType)
+            .map(|mime| ContentType(mime, Some(value.clone())))
     }
 
     fn encode(&self, values: &mut ::ToValues) {
-        let value = self
-            .0
-            .as_ref()
-            .parse()
-            .expect("Mime is always a valid HeaderValue");
+        // Keep the exact value this was decoded from.
+        if let Some(ref raw) = self.1 {
+            return values.append(raw.clone());
+        }
+
+        let mime = self.0.as_ref();
+        let value = match STATIC_VALUES.iter().find(|&&value| value == mime) {
+            Some(value) => ::HeaderValue::from_static(value),
+            None => mime.parse().expect("Mime is always a valid HeaderValue"),
+        };
         values.append(value);
     }
 }
 
+/// The values of the constants, which are encoded without allocating.
+static STATIC_VALUES: &[&str] = &[
+    "application/json",
+    "text/plain",
+    "text/plain; charset=utf-8",
+    "text/html",
+    "text/xml",
+    "application/x-www-form-urlencoded",
+    "image/jpeg",
+    "image/png",
+    "application/octet-stream",
+];
+
 impl From<mime::Mime> for ContentType {
     fn from(m: mime::Mime) -> ContentType {
-        ContentType(m)
+        ContentType(m, None)
     }
 }
 
@@ -129,16 +277,25 @@ impl From<ContentType> for mime::Mime {
     }
 }
 
+impl PartialEq for ContentType {
+    fn eq(&self, other: &ContentType) -> bool {
+        self.0 == other.0
+    }
+}
+
 impl fmt::Display for ContentType {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        fmt::Display::fmt(&self.0, f)
+        match self.1.as_ref().and_then(|raw| raw.to_str().ok()) {
+            Some(raw) => f.write_str(raw),
+            None => fmt::Display::fmt(&self.0, f),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::ContentType;
-    use super::super::test_decode;
+    use super::super::{test_decode, test_encode};
 
     #[test]
     fn json() {
@@ -147,5 +304,82 @@ mod tests {
             Some(ContentType::json()),
         );
     }
+
+    #[test]
+    fn constants() {
+        let constants = [
+            ContentType::JSON,
+            ContentType::TEXT,
+            ContentType::TEXT_UTF8,
+            ContentType::HTML,
+            ContentType::XML,
+            ContentType::FORM_URL_ENCODED,
+            ContentType::JPEG,
+            ContentType::PNG,
+            ContentType::OCTET_STREAM,
+        ];
+        assert_eq!(constants.len(), super::STATIC_VALUES.len());
+        for (ct, &value) in constants.iter().zip(super::STATIC_VALUES) {
+            assert_eq!(ct.to_string(), value);
+            assert_eq!(test_encode(ct.clone())["content-type"], value);
+        }
+    }
+
+    #[test]
+    fn decoded_value_is_kept() {
+        let ct = test_decode::<ContentType>(&["Text/HTML;Charset=\"UTF-8\""]).unwrap();
+        assert_eq!(ct, ContentType::from(::mime::TEXT_HTML_UTF_8));
+        assert_eq!(ct.to_string(), "Text/HTML;Charset=\"UTF-8\"");
+
+        let headers = test_encode(ct);
+        assert_eq!(headers["content-type"], "Text/HTML;Charset=\"UTF-8\"");
+    }
+
+    #[cfg(feature = "file-extensions")]
+    #[test]
+    fn extension_table() {
+        use std::collections::HashSet;
+
+        let mut seen = HashSet::new();
+        for &(ext, mime) in super::EXTENSIONS {
+            assert!(seen.insert(ext), "duplicate extension {:?}", ext);
+            assert_eq!(ext, ext.to_ascii_lowercase(), "{:?}", ext);
+
+            let parsed = mime.parse::<::mime::Mime>().expect(mime);
+            if parsed.type_() == ::mime::TEXT {
+                assert_eq!(parsed.get_param(::mime::CHARSET), Some(::mime::UTF_8), "{:?}", ext);
+            }
+        }
+    }
+
+    #[cfg(feature = "file-extensions")]
+    #[test]
+    fn from_extension() {
+        assert_eq!(ContentType::from_extension("html"), Some(ContentType::from(::mime::TEXT_HTML_UTF_8)));
+        assert_eq!(ContentType::from_extension("PNG"), Some(ContentType::png()));
+        assert_eq!(ContentType::from_extension(".png"), None);
+        assert_eq!(ContentType::from_extension(""), None);
+
+        let ct = ContentType::from_extension("js").unwrap();
+        assert_eq!(test_encode(ct)["content-type"], "text/javascript; charset=utf-8");
+    }
+
+    #[cfg(feature = "file-extensions")]
+    #[test]
+    fn from_path() {
+        use std::path::Path;
+
+        assert_eq!(ContentType::from_path("static/app.min.css"), ContentType::from_extension("css"));
+        assert_eq!(ContentType::from_path(Path::new("/srv/www/Index.HTML")), ContentType::from_extension("html"));
+        assert_eq!(ContentType::from_path("archive.tar.gz"), ContentType::from_extension("gz"));
+        assert_eq!(ContentType::from_path("Makefile"), None);
+        assert_eq!(ContentType::from_path(".png"), None);
+    }
+
+    #[test]
+    fn constructed_value_is_canonical() {
+        let headers = test_encode(ContentType::from(::mime::TEXT_HTML_UTF_8));
+        assert_eq!(headers["content-type"], "text/html; charset=utf-8");
+    }
 }
 //bench_header!(bench, ContentType, { vec![b"application/json".to_vec()] });
@@ -1,5 +1,11 @@
+use std::fmt;
+use std::ops::Range;
+use std::sync::OnceLock;
 
-use util::{FlatCsv, SemiColon};
+use headers_core::decode::TryFromValues;
+
+use util::{split_quoted, token, FlatCsv, HeaderString, SemiColon};
+use ParseMode;
 
 /// `Cookie` header, defined in [RFC6265](http://tools.ietf.org/html/rfc6265#section-5.4)
 ///
@@ -14,10 +20,41 @@ use util::{FlatCsv, SemiColon};
 /// * `SID=31d4d96e407aad42`
 /// * `SID=31d4d96e407aad42; lang=en-US`
 ///
-#[derive(Clone, Debug, Header)]
-pub struct Cookie(FlatCsv<SemiColon>);
+/// # Notes
+///
+/// * Decoding only checks the value, the pairs are found the first time
+///   they're needed, by `get`, `iter` or `len`, and kept after that. To
+///   look up a single cookie without decoding at all, use `get_raw`.
+#[derive(Clone)]
+pub struct Cookie {
+    value: HeaderString,
+    index: OnceLock<Vec<(Range<usize>, Range<usize>)>>,
+}
 
 impl Cookie {
+    fn from_value(value: HeaderString) -> Cookie {
+        Cookie {
+            value,
+            index: OnceLock::new(),
+        }
+    }
+
+    // The caller makes sure the pairs are valid in a `HeaderValue`.
+    pub(crate) fn from_pairs<'a, I>(pairs: I) -> Option<Cookie>
+    where
+        I: IntoIterator<Item = (&'a str, &'a str)>,
+    {
+        let joined = pairs
+            .into_iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join("; ");
+        if joined.is_empty() {
+            return None;
+        }
+        HeaderString::from_string(joined).map(Cookie::from_value)
+    }
+
     /// Lookup a value for a cookie name.
     ///
     /// # Example
@@ -44,23 +81,194 @@ impl Cookie {
             .map(|(_, val)| val)
     }
 
+    /// Lookup a value for a cookie name in a raw `Cookie` value, like
+    /// `get`, without decoding it or allocating.
+    ///
+    /// This is meant for very large headers when only one cookie is
+    /// needed. The value isn't checked against the grammar, and a value
+    /// that isn't a valid `str` has no cookies.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # extern crate headers_ext as headers;
+    /// use headers::{Cookie, HeaderValue};
+    ///
+    /// let value = HeaderValue::from_static("SID=31d4d96e407aad42; lang=en-US");
+    /// assert_eq!(Cookie::get_raw(&value, "lang"), Some("en-US"));
+    /// assert_eq!(Cookie::get_raw(&value, "theme"), None);
+    /// ```
+    pub fn get_raw<'a>(value: &'a ::HeaderValue, name: &str) -> Option<&'a str> {
+        pairs(value.to_str().ok()?)
+            .find(|&(key, _)| key == name)
+            .map(|(_, val)| val)
+    }
+
     /// Get the number of key-value pairs this `Cookie` contains.
     pub fn len(&self) -> usize {
-        self.iter().count()
+        self.index().len()
+    }
+
+    /// Returns `true` if this `Cookie` contains no key-value pairs.
+    pub fn is_empty(&self) -> bool {
+        self.index().is_empty()
     }
 
     /// Iterator the key-value pairs of this `Cookie` header.
     pub fn iter(&self) -> impl Iterator<Item=(&str, &str)> {
-        self.0.iter()
-            .filter_map(|kv| {
-                let mut iter = kv.splitn(2, '=');
-                let key = iter.next()?.trim();
-                let val = iter.next()?.trim();
-                Some((key, val))
-            })
+        let value = self.value.as_str();
+        self.index()
+            .iter()
+            .map(move |(key, val)| (&value[key.clone()], &value[val.clone()]))
+    }
+
+    fn index(&self) -> &[(Range<usize>, Range<usize>)] {
+        self.index.get_or_init(|| {
+            let value = self.value.as_str();
+            let mut index = Vec::with_capacity(value.bytes().filter(|&b| b == b';').count() + 1);
+            index.extend(pairs(value).map(|(key, val)| (range_in(value, key), range_in(value, val))));
+            index
+        })
+    }
+}
+
+/// The trimmed name and value of each pair, skipping those without a `=`.
+fn pairs(value: &str) -> impl Iterator<Item = (&str, &str)> {
+    split_quoted(value, b';').filter_map(|kv| {
+        let mut iter = kv.splitn(2, '=');
+        let key = iter.next()?.trim();
+        let val = iter.next()?.trim();
+        Some((key, val))
+    })
+}
+
+// `part` is a sub-slice of `value`.
+fn range_in(value: &str, part: &str) -> Range<usize> {
+    let start = part.as_ptr() as usize - value.as_ptr() as usize;
+    start..start + part.len()
+}
+
+impl fmt::Debug for Cookie {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Cookie").field(&self.value).finish()
+    }
+}
+
+impl ::Header for Cookie {
+    fn name() -> &'static ::HeaderName {
+        &::http::header::COOKIE
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        let csv = if values.mode() == ParseMode::Lenient {
+            FlatCsv::<SemiColon>::try_from_values(values)?
+        } else {
+            // Strictly, there's a single line, and every pair must follow
+            // the grammar, so values can't contain spaces, even quoted.
+            let csv: FlatCsv<SemiColon> = values.next()?.clone().into();
+            if csv.iter().nth(values.limits().max_list_items).is_some() {
+                values.set_too_large();
+                return None;
+            }
+            if values.next().is_some() || csv.is_empty() || !csv.iter().all(is_cookie_pair) {
+                return None;
+            }
+            csv
+        };
+        HeaderString::from_val(&csv.value).map(Cookie::from_value)
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        values.append((&self.value).into());
     }
 }
 
+/// A view of a `Cookie` header that borrows from the `HeaderMap`, for
+/// `HeaderMapExt::typed_get_borrowed`.
+///
+/// It decodes like `Cookie`, except that even in `ParseMode::Lenient`
+/// there must be a single field line. Lookups scan the value each time,
+/// like `Cookie::get_raw`.
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::{CookieView, HeaderMap, HeaderMapExt, HeaderValue};
+///
+/// let mut headers = HeaderMap::new();
+/// headers.insert("cookie", HeaderValue::from_static("SID=31d4d96e407aad42; lang=en-US"));
+///
+/// let cookie = headers.typed_get_borrowed::<CookieView>().unwrap();
+/// assert_eq!(cookie.get("lang"), Some("en-US"));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CookieView<'a>(&'a str);
+
+impl<'a> CookieView<'a> {
+    /// Lookup a value for a cookie name.
+    pub fn get(&self, name: &str) -> Option<&'a str> {
+        self.iter()
+            .find(|&(key, _)| key == name)
+            .map(|(_, val)| val)
+    }
+
+    /// Iterator the key-value pairs of this `Cookie` header.
+    pub fn iter(&self) -> impl Iterator<Item=(&'a str, &'a str)> {
+        pairs(self.0)
+    }
+
+    /// Get the whole value.
+    pub fn as_str(&self) -> &'a str {
+        self.0
+    }
+}
+
+impl<'a> ::BorrowedHeader<'a> for CookieView<'a> {
+    fn name() -> &'static ::HeaderName {
+        &::http::header::COOKIE
+    }
+
+    fn decode_borrowed(values: &mut ::Values<'a>) -> Option<Self> {
+        let value = values.next()?.to_str().ok()?;
+        if split_quoted(value, b';').nth(values.limits().max_list_items).is_some() {
+            values.set_too_large();
+            return None;
+        }
+        let mut items = split_quoted(value, b';');
+        let strict = values.mode() == ParseMode::Strict;
+        match items.next() {
+            Some(first) if !strict || (is_cookie_pair(first) && items.all(is_cookie_pair)) => {
+                Some(CookieView(value))
+            },
+            _ => None,
+        }
+    }
+}
+
+/// `cookie-pair = cookie-name "=" cookie-value`, from RFC6265.
+fn is_cookie_pair(pair: &str) -> bool {
+    let mut iter = pair.splitn(2, '=');
+    let (name, value) = match (iter.next(), iter.next()) {
+        (Some(name), Some(value)) => (name, value),
+        _ => return false,
+    };
+    token::is_token(name.as_bytes()) && is_cookie_value(value)
+}
+
+/// `cookie-value = *cookie-octet / ( DQUOTE *cookie-octet DQUOTE )`
+pub(super) fn is_cookie_value(value: &str) -> bool {
+    let value = if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+        &value[1..value.len() - 1]
+    } else {
+        value
+    };
+
+    value.bytes().all(|b| matches!(
+        b,
+        b'\x21' | b'\x23'..=b'\x2b' | b'\x2d'..=b'\x3a' | b'\x3c'..=b'\x5b' | b'\x5d'..=b'\x7e'
+    ))
+}
 
 /*
 impl PartialEq for Cookie {
@@ -81,7 +289,7 @@ impl PartialEq for Cookie {
 
 #[cfg(test)]
 mod tests {
-    use super::Cookie;
+    use super::{Cookie, CookieView};
     use super::super::test_decode;
 
     #[test]
@@ -107,6 +315,45 @@ mod tests {
         assert_eq!(cookie.get("lol"), Some("cat"));
     }
 
+    #[test]
+    fn test_view() {
+        use {HeaderMap, HeaderMapExt, HeaderValue, ParseMode};
+
+        let mut map = HeaderMap::new();
+        map.insert("cookie", HeaderValue::from_static("foo=bar; lol = cat; foo=baz"));
+        let view = map.typed_get_borrowed::<CookieView>().unwrap();
+        let cookie = map.typed_get::<Cookie>().unwrap();
+        assert_eq!(view.iter().collect::<Vec<_>>(), cookie.iter().collect::<Vec<_>>());
+        assert_eq!(view.get("foo"), Some("bar"));
+        assert_eq!(view.get("nope"), None);
+
+        // Only a single line, even leniently.
+        map.append("cookie", HeaderValue::from_static("more=1"));
+        assert!(map.typed_get_with::<Cookie>(ParseMode::Lenient).is_some());
+        assert_eq!(map.typed_get_borrowed::<CookieView>(), None);
+
+        map.insert("cookie", HeaderValue::from_static(" ; ;"));
+        assert_eq!(map.typed_get_borrowed::<CookieView>(), None);
+    }
+
+    #[test]
+    fn test_get_raw() {
+        let value = ::HeaderValue::from_static("a=1; b = \"x;y\" ;novalue; a=2;; c=");
+        assert_eq!(Cookie::get_raw(&value, "a"), Some("1"));
+        assert_eq!(Cookie::get_raw(&value, "b"), Some("\"x;y\""));
+        assert_eq!(Cookie::get_raw(&value, "c"), Some(""));
+        assert_eq!(Cookie::get_raw(&value, "novalue"), None);
+
+        let cookie = test_decode::<Cookie>(&[value.to_str().unwrap()]).unwrap();
+        assert_eq!(cookie.len(), 4);
+        for name in &["a", "b", "c", "novalue", "d"] {
+            assert_eq!(cookie.get(name), Cookie::get_raw(&value, name));
+        }
+
+        let binary = ::HeaderValue::from_bytes(b"a=\xFF").unwrap();
+        assert_eq!(Cookie::get_raw(&binary, "a"), None);
+    }
+
     /*
     #[test]
     fn test_set_and_get() {
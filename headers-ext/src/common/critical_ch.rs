@@ -0,0 +1,139 @@
+use std::iter::FromIterator;
+
+use headers_core::decode::TryFromValues;
+
+use {AcceptCh, HeaderName, HeaderValue};
+use util::{parse_item, ParsedCsv};
+
+/// `Critical-CH` header, defined in
+/// [the Client Hint Reliability draft](https://tools.ietf.org/html/draft-davidben-http-client-hint-reliability-03#section-3)
+///
+/// The `Critical-CH` response header field lists the client hints without
+/// which the response may be wrong, so that a client that didn't send
+/// them retries the request with them.
+///
+/// Each of them must also be in `Accept-CH`, as `is_subset_of` checks.
+///
+/// # ABNF
+///
+/// ```text
+/// Critical-CH = #field-name
+/// ```
+///
+/// # Example values
+///
+/// * `Sec-CH-UA`
+/// * `Sec-CH-UA-Platform, DPR`
+///
+/// # Notes
+///
+/// * Names that aren't valid header names are skipped.
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::{AcceptCh, CriticalCh, HeaderName};
+///
+/// let ua = HeaderName::from_static("sec-ch-ua");
+/// let accept_ch = vec![ua.clone(), HeaderName::from_static("dpr")]
+///     .into_iter()
+///     .collect::<AcceptCh>();
+/// let critical_ch = Some(ua).into_iter().collect::<CriticalCh>();
+/// assert!(critical_ch.is_subset_of(&accept_ch));
+/// ```
+#[derive(Clone, Debug)]
+pub struct CriticalCh(ParsedCsv<HeaderName>);
+
+impl ::Header for CriticalCh {
+    fn name() -> &'static ::HeaderName {
+        static_header_name!("critical-ch")
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        TryFromValues::try_from_values(values).map(CriticalCh)
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        values.append((&self.0).into());
+    }
+}
+
+impl CriticalCh {
+    /// Returns an iterator over the `HeaderName`s of the hints.
+    pub fn iter(&self) -> ::list::Iter<'_, HeaderName> {
+        ::list::Iter::new(self.0.iter())
+    }
+
+    fn into_items(self) -> ::list::IntoIter<HeaderName> {
+        ::list::IntoIter::new(self.0.into_iter())
+    }
+
+    /// Returns an iterator over clones of the `HeaderName`s of the hints.
+    pub fn iter_cloned<'a>(&'a self) -> impl Iterator<Item = HeaderName> + 'a {
+        self.iter().cloned()
+    }
+
+    /// Check if the hint `name` is critical.
+    pub fn contains(&self, name: &HeaderName) -> bool {
+        self.iter().any(|have| have == name)
+    }
+
+    /// Check if the hint named `name` is critical, in any case, like
+    /// `contains`.
+    pub fn contains_str(&self, name: &str) -> bool {
+        parse_item(name).is_some_and(|name| self.contains(&name))
+    }
+
+    /// Check if every critical hint is also in `accept_ch`, as the server
+    /// must send them.
+    pub fn is_subset_of(&self, accept_ch: &AcceptCh) -> bool {
+        self.iter().all(|name| accept_ch.contains(name))
+    }
+}
+
+impl_collection!(CriticalCh, HeaderName);
+
+impl FromIterator<HeaderName> for CriticalCh {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = HeaderName>,
+    {
+        CriticalCh(ParsedCsv::from_items(iter, name_value))
+    }
+}
+
+fn name_value(name: &HeaderName) -> HeaderValue {
+    name.clone().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{test_decode, test_encode};
+
+    #[test]
+    fn decode() {
+        let critical_ch = test_decode::<CriticalCh>(&["Sec-CH-UA", "DPR, b@d"]).unwrap();
+        assert_eq!(critical_ch.iter().collect::<Vec<_>>(), ["sec-ch-ua", "dpr"]);
+        assert!(critical_ch.contains_str("Sec-CH-UA"));
+        assert!(!critical_ch.contains(&HeaderName::from_static("viewport-width")));
+    }
+
+    #[test]
+    fn encode() {
+        let critical_ch: CriticalCh = vec![HeaderName::from_static("sec-ch-ua")].into_iter().collect();
+        assert_eq!(test_encode(critical_ch)["critical-ch"], "sec-ch-ua");
+    }
+
+    #[test]
+    fn is_subset_of() {
+        let accept_ch = test_decode::<AcceptCh>(&["Sec-CH-UA, DPR"]).unwrap();
+
+        let critical_ch = test_decode::<CriticalCh>(&["dpr, SEC-CH-UA"]).unwrap();
+        assert!(critical_ch.is_subset_of(&accept_ch));
+
+        let missing = test_decode::<CriticalCh>(&["DPR, Viewport-Width"]).unwrap();
+        assert!(!missing.is_subset_of(&accept_ch));
+    }
+}
@@ -1,5 +1,5 @@
 use std::time::SystemTime;
-use util::HttpDate;
+use util::{clock, HttpDate};
 
 /// `Date` header, defined in [RFC7231](http://tools.ietf.org/html/rfc7231#section-7.1.1.2)
 ///
@@ -23,11 +23,20 @@ use util::HttpDate;
 /// use headers::Date;
 /// use std::time::SystemTime;
 ///
-/// let date = Date::from(SystemTime::now());
+/// let date = Date::now();
+/// let same = Date::from(SystemTime::now());
 /// ```
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Header)]
 pub struct Date(HttpDate);
 
+impl Date {
+    /// Create a `Date` of the current time, as a message originating now
+    /// should have.
+    pub fn now() -> Date {
+        Date::from(clock::now())
+    }
+}
+
 impl From<SystemTime> for Date {
     fn from(time: SystemTime) -> Date {
         Date(time.into())
@@ -39,3 +48,5 @@ impl From<Date> for SystemTime {
         date.0.into()
     }
 }
+
+impl_date_header!(Date);
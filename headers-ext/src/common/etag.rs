@@ -26,59 +26,85 @@ use util::EntityTag;
 ///
 /// # Examples
 ///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::{EntityTag, ETag};
+///
+/// let etag = ETag::from(EntityTag::strong("xyzzy").unwrap());
+/// assert!(etag.entity_tag().strong_eq(&"\"xyzzy\"".parse().unwrap()));
+/// ```
 #[derive(Clone, Debug, PartialEq, Eq, Header)]
 #[header(name_const = "ETAG")]
 pub struct ETag(pub(super) EntityTag);
 
-    /*
-    test_etag {
+impl ETag {
+    /// Get the entity tag.
+    pub fn entity_tag(&self) -> &EntityTag {
+        &self.0
+    }
+}
+
+impl From<EntityTag> for ETag {
+    fn from(tag: EntityTag) -> ETag {
+        ETag(tag)
+    }
+}
+
+impl From<ETag> for EntityTag {
+    fn from(etag: ETag) -> EntityTag {
+        etag.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use util::EntityTag;
+    use super::ETag;
+    use super::super::{test_decode, test_encode};
+    use headers_core::test_helpers::decode_from_strs_with;
+    use ParseMode;
+
+    #[test]
+    fn decode() {
         // From the RFC
-        test_header!(test1,
-            vec![b"\"xyzzy\""],
-            Some(ETag(EntityTag::new(false, "xyzzy".to_owned()))));
-        test_header!(test2,
-            vec![b"W/\"xyzzy\""],
-            Some(ETag(EntityTag::new(true, "xyzzy".to_owned()))));
-        test_header!(test3,
-            vec![b"\"\""],
-            Some(ETag(EntityTag::new(false, "".to_owned()))));
+        assert_eq!(
+            test_decode::<ETag>(&["\"xyzzy\""]),
+            Some(ETag(EntityTag::strong("xyzzy").unwrap())),
+        );
+        assert_eq!(
+            test_decode::<ETag>(&["W/\"xyzzy\""]),
+            Some(ETag(EntityTag::weak("xyzzy").unwrap())),
+        );
+        assert_eq!(
+            test_decode::<ETag>(&["\"\""]),
+            Some(ETag(EntityTag::strong("").unwrap())),
+        );
         // Own tests
-        test_header!(test4,
-            vec![b"\"foobar\""],
-            Some(ETag(EntityTag::new(false, "foobar".to_owned()))));
-        test_header!(test5,
-            vec![b"\"\""],
-            Some(ETag(EntityTag::new(false, "".to_owned()))));
-        test_header!(test6,
-            vec![b"W/\"weak-etag\""],
-            Some(ETag(EntityTag::new(true, "weak-etag".to_owned()))));
-        test_header!(test7,
-            vec![b"W/\"\x65\x62\""],
-            Some(ETag(EntityTag::new(true, "\u{0065}\u{0062}".to_owned()))));
-        test_header!(test8,
-            vec![b"W/\"\""],
-            Some(ETag(EntityTag::new(true, "".to_owned()))));
-        test_header!(test9,
-            vec![b"no-dquotes"],
-            None::<ETag>);
-        test_header!(test10,
-            vec![b"w/\"the-first-w-is-case-sensitive\""],
-            None::<ETag>);
-        test_header!(test11,
-            vec![b""],
-            None::<ETag>);
-        test_header!(test12,
-            vec![b"\"unmatched-dquotes1"],
-            None::<ETag>);
-        test_header!(test13,
-            vec![b"unmatched-dquotes2\""],
-            None::<ETag>);
-        test_header!(test14,
-            vec![b"matched-\"dquotes\""],
-            None::<ETag>);
-        test_header!(test15,
-            vec![b"\""],
-            None::<ETag>);
+        assert_eq!(
+            test_decode::<ETag>(&["W/\"\x65\x62\""]),
+            Some(ETag(EntityTag::weak("\u{0065}\u{0062}").unwrap())),
+        );
     }
-    */
 
+    #[test]
+    fn decode_invalid() {
+        for s in &["w/\"the-first-w-is-case-sensitive\"", "", "\"unmatched-dquotes1",
+                   "unmatched-dquotes2\"", "matched-\"dquotes\"", "\""] {
+            assert_eq!(test_decode::<ETag>(&[s]), None, "{:?}", s);
+        }
+
+        // Missing quotes are only accepted when lenient.
+        let strict = decode_from_strs_with::<ETag>(&["no-dquotes"], ParseMode::Strict);
+        assert_eq!(strict, None);
+        assert_eq!(
+            test_decode::<ETag>(&["no-dquotes"]),
+            Some(ETag(EntityTag::strong("no-dquotes").unwrap())),
+        );
+    }
+
+    #[test]
+    fn encode() {
+        let headers = test_encode(ETag::from(EntityTag::weak("weak-etag").unwrap()));
+        assert_eq!(headers["etag"], "W/\"weak-etag\"");
+    }
+}
@@ -1,5 +1,7 @@
 use std::fmt;
 
+use util::token;
+
 /// The `Expect` header.
 ///
 /// > The "Expect" header field in a request indicates a certain set of
@@ -26,10 +28,12 @@ impl Expect {
 }
 
 impl ::Header for Expect {
-    const NAME: &'static ::HeaderName = &::http::header::EXPECT;
+    fn name() -> &'static ::HeaderName {
+        &::http::header::EXPECT
+    }
 
     fn decode(values: &mut ::Values) -> Option<Expect> {
-        if values.next()? == "100-continue" {
+        if token::eq_ignore_ascii_case_trimmed(values.next()?.as_bytes(), "100-continue") {
             Some(Expect::CONTINUE)
         } else {
             None
@@ -77,4 +81,12 @@ mod tests {
             None,
         );
     }
+
+    #[test]
+    fn expect_continue_mixed_case() {
+        assert_eq!(
+            test_decode::<Expect>(&["100-Continue"]),
+            Some(Expect::CONTINUE),
+        );
+    }
 }
@@ -0,0 +1,204 @@
+use std::fmt;
+use std::time::Duration;
+
+use http::Uri;
+
+use util::{quoted, FlatCsv, Seconds};
+
+/// `Expect-CT` header, defined in
+/// [RFC9163](https://tools.ietf.org/html/rfc9163)
+///
+/// The `Expect-CT` response header allows a site to opt in to reporting
+/// and/or enforcement of Certificate Transparency requirements.
+///
+/// # ABNF
+///
+/// ```text
+/// Expect-CT           = #expect-ct-directive
+/// expect-ct-directive = directive-name [ "=" directive-value ]
+/// directive-name      = token
+/// directive-value     = token / quoted-string
+/// ```
+///
+/// # Example values
+///
+/// * `max-age=86400`
+/// * `max-age=86400, enforce, report-uri="https://foo.example/report"`
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use std::time::Duration;
+/// use headers::ExpectCt;
+///
+/// let expect_ct = ExpectCt::new(Duration::from_secs(86_400))
+///     .with_enforce(true);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExpectCt {
+    max_age: Seconds,
+    enforce: bool,
+    report_uri: Option<Uri>,
+}
+
+impl ExpectCt {
+    /// Create an `Expect-CT` header with the given `max-age`, that only
+    /// reports failures.
+    pub fn new(max_age: Duration) -> ExpectCt {
+        ExpectCt {
+            max_age: max_age.into(),
+            enforce: false,
+            report_uri: None,
+        }
+    }
+
+    /// Set whether the user agent should refuse connections that violate
+    /// the Certificate Transparency policy.
+    pub fn with_enforce(mut self, enforce: bool) -> Self {
+        self.enforce = enforce;
+        self
+    }
+
+    /// Set the URI that failures should be reported to.
+    pub fn with_report_uri(mut self, report_uri: Uri) -> Self {
+        self.report_uri = Some(report_uri);
+        self
+    }
+
+    /// Get how long the user agent should remember this policy.
+    pub fn max_age(&self) -> Duration {
+        self.max_age.into()
+    }
+
+    /// Get whether the policy is enforced.
+    pub fn enforce(&self) -> bool {
+        self.enforce
+    }
+
+    /// Get the URI that failures should be reported to, if any.
+    pub fn report_uri(&self) -> Option<&Uri> {
+        self.report_uri.as_ref()
+    }
+}
+
+fn from_csv(csv: &FlatCsv) -> Option<ExpectCt> {
+    let mut max_age = None;
+    let mut enforce = false;
+    let mut report_uri = None;
+
+    for directive in csv.iter() {
+        let mut parts = directive.splitn(2, '=');
+        let name = parts.next()?.trim();
+        let value = match parts.next() {
+            Some(value) => Some(quoted::unquote(value.trim())?),
+            None => None,
+        };
+
+        // Each directive may only appear once.
+        if name.eq_ignore_ascii_case("max-age") {
+            if max_age.is_some() {
+                return None;
+            }
+            max_age = Some(Seconds::parse(value?.as_bytes())?);
+        } else if name.eq_ignore_ascii_case("enforce") {
+            if enforce || value.is_some() {
+                return None;
+            }
+            enforce = true;
+        } else if name.eq_ignore_ascii_case("report-uri") {
+            if report_uri.is_some() {
+                return None;
+            }
+            report_uri = Some(value?.parse::<Uri>().ok()?);
+        }
+    }
+
+    Some(ExpectCt {
+        max_age: max_age?,
+        enforce,
+        report_uri,
+    })
+}
+
+impl ::Header for ExpectCt {
+    fn name() -> &'static ::HeaderName {
+        static_header_name!("expect-ct")
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        from_csv(&FlatCsv::from_values(values)?)
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        struct Adapter<'a>(&'a ExpectCt);
+
+        impl<'a> fmt::Display for Adapter<'a> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "max-age={}", self.0.max_age)?;
+                if self.0.enforce {
+                    f.write_str(", enforce")?;
+                }
+                if let Some(ref uri) = self.0.report_uri {
+                    write!(f, ", report-uri=\"{}\"", uri)?;
+                }
+                Ok(())
+            }
+        }
+
+        values.append_fmt(Adapter(self));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use super::ExpectCt;
+    use super::super::{test_decode, test_encode};
+
+    #[test]
+    fn decode_max_age() {
+        let expect_ct = test_decode::<ExpectCt>(&["max-age=86400"]).unwrap();
+        assert_eq!(expect_ct, ExpectCt::new(Duration::from_secs(86400)));
+    }
+
+    #[test]
+    fn decode_quoted_report_uri_with_commas() {
+        let expect_ct = test_decode::<ExpectCt>(&[
+            "enforce, report-uri=\"https://foo.example/report?a=1,2\", max-age=\"30\"",
+        ]).unwrap();
+        assert_eq!(expect_ct.max_age(), Duration::from_secs(30));
+        assert!(expect_ct.enforce());
+        assert_eq!(
+            expect_ct.report_uri().unwrap(),
+            "https://foo.example/report?a=1,2",
+        );
+    }
+
+    #[test]
+    fn decode_ignores_unknown_directives() {
+        let expect_ct = test_decode::<ExpectCt>(&["max-age=0, foo=\"a, b\", bar"]).unwrap();
+        assert_eq!(expect_ct, ExpectCt::new(Duration::from_secs(0)));
+    }
+
+    #[test]
+    fn decode_invalid() {
+        assert_eq!(test_decode::<ExpectCt>(&["enforce"]), None);
+        assert_eq!(test_decode::<ExpectCt>(&["max-age=soon"]), None);
+        assert_eq!(test_decode::<ExpectCt>(&["max-age=1, max-age=2"]), None);
+        assert_eq!(test_decode::<ExpectCt>(&["max-age=1, report-uri=\"https://foo"]), None);
+    }
+
+    #[test]
+    fn encode() {
+        let expect_ct = ExpectCt::new(Duration::from_secs(86400))
+            .with_enforce(true)
+            .with_report_uri("https://foo.example/report".parse().unwrap());
+
+        let headers = test_encode(expect_ct);
+        assert_eq!(
+            headers["expect-ct"],
+            "max-age=86400, enforce, report-uri=\"https://foo.example/report\"",
+        );
+    }
+}
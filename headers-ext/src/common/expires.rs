@@ -1,5 +1,5 @@
-use std::time::SystemTime;
-use util::HttpDate;
+use std::time::{Duration, SystemTime};
+use util::{clock, HttpDate};
 
 /// `Expires` header, defined in [RFC7234](http://tools.ietf.org/html/rfc7234#section-5.3)
 ///
@@ -26,12 +26,29 @@ use util::HttpDate;
 /// use headers::Expires;
 /// use std::time::{SystemTime, Duration};
 ///
+/// let expires = Expires::after(Duration::from_secs(60 * 60 * 24));
+///
 /// let time = SystemTime::now() + Duration::from_secs(60 * 60 * 24);
 /// let expires = Expires::from(time);
 /// ```
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Header)]
 pub struct Expires(HttpDate);
 
+impl Expires {
+    /// Create an `Expires` of `duration` from now.
+    ///
+    /// Dates after `Fri, 31 Dec 9999 23:59:59 GMT` saturate to it.
+    pub fn after(duration: Duration) -> Expires {
+        Expires::after_at(duration, clock::now())
+    }
+
+    /// Create an `Expires` of `duration` after `now`, like `after` with
+    /// the current time given.
+    pub fn after_at(duration: Duration, now: SystemTime) -> Expires {
+        Expires(HttpDate::from(now).saturating_add(duration))
+    }
+}
+
 impl From<SystemTime> for Expires {
     fn from(time: SystemTime) -> Expires {
         Expires(time.into())
@@ -43,3 +60,27 @@ impl From<Expires> for SystemTime {
         date.0.into()
     }
 }
+
+impl_date_header!(Expires);
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::Expires;
+    use super::super::test_encode;
+    use util::clock;
+
+    #[test]
+    fn after() {
+        let now = UNIX_EPOCH + Duration::from_secs(784_111_777);
+        let _frozen = clock::freeze(now);
+
+        let expires = Expires::after(Duration::from_secs(60));
+        assert_eq!(expires, Expires::after_at(Duration::from_secs(60), now));
+        assert_eq!(test_encode(expires)["expires"], "Sun, 06 Nov 1994 08:50:37 GMT");
+
+        let never = Expires::after(Duration::from_secs(u64::MAX));
+        assert_eq!(test_encode(never)["expires"], "Fri, 31 Dec 9999 23:59:59 GMT");
+    }
+}
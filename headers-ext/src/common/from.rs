@@ -1,29 +1,124 @@
-header! {
-    /// `From` header, defined in [RFC7231](http://tools.ietf.org/html/rfc7231#section-5.5.1)
-    ///
-    /// The `From` header field contains an Internet email address for a
-    /// human user who controls the requesting user agent.  The address ought
-    /// to be machine-usable.
-    ///
-    /// # ABNF
-    ///
-    /// ```text
-    /// From    = mailbox
-    /// mailbox = <mailbox, see [RFC5322], Section 3.4>
-    /// ```
-    ///
-    /// # Example
+use util::HeaderString;
+
+/// `From` header, defined in [RFC7231](http://tools.ietf.org/html/rfc7231#section-5.5.1)
+///
+/// The `From` header field contains an Internet email address for a
+/// human user who controls the requesting user agent.  The address ought
+/// to be machine-usable.
+///
+/// The type is named `FromEmail` so it doesn't collide with the
+/// `std::convert::From` trait.
+///
+/// # ABNF
+///
+/// ```text
+/// From    = mailbox
+/// mailbox = <mailbox, see [RFC5322], Section 3.4>
+/// ```
+///
+/// # Example values
+///
+/// * `webmaster@example.org`
+/// * `Webmaster <webmaster@example.org>`
+///
+/// # Notes
+///
+/// * The mailbox is only loosely checked: it must contain an `@` with
+///   something on both sides of it.
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::FromEmail;
+///
+/// let from = FromEmail::from_static("webmaster@example.org");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FromEmail(HeaderString);
+
+impl FromEmail {
+    /// Create a `FromEmail` from a static string.
     ///
-    /// ```
-    /// use headers::{Headers, From};
+    /// # Panic
     ///
-    /// let mut headers = Headers::new();
-    /// headers.set(From("webmaster@example.org".to_owned()));
-    /// ```
-    // FIXME: Maybe use mailbox?
-    (From, FROM) => [String]
-
-    test_from {
-        test_header!(test1, vec![b"webmaster@example.org"]);
+    /// Panics if the string is not a legal header value, or doesn't look
+    /// like a mailbox.
+    pub fn from_static(src: &'static str) -> FromEmail {
+        assert!(is_mailbox(src), "static str is not a mailbox: {:?}", src);
+        FromEmail(HeaderString::from_static(src))
+    }
+
+    /// View this `FromEmail` as a `&str`.
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+fn is_mailbox(s: &str) -> bool {
+    let s = s.trim();
+    // `display-name <addr-spec>`
+    let addr = match (s.rfind('<'), s.ends_with('>')) {
+        (Some(start), true) => &s[start + 1..s.len() - 1],
+        (None, false) => s,
+        _ => return false,
+    };
+
+    match addr.rfind('@') {
+        Some(at) => {
+            let (local, domain) = (&addr[..at], &addr[at + 1..]);
+            !local.is_empty()
+                && !domain.is_empty()
+                && !addr.bytes().any(|b| b == b' ' || b == b'\t')
+        },
+        None => false,
+    }
+}
+
+impl ::Header for FromEmail {
+    fn name() -> &'static ::HeaderName {
+        &::http::header::FROM
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        let value = HeaderString::from_val(values.next()?)?;
+        if is_mailbox(value.as_str()) {
+            Some(FromEmail(value))
+        } else {
+            None
+        }
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        values.append((&self.0).into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FromEmail;
+    use super::super::{test_decode, test_encode};
+
+    #[test]
+    fn decode() {
+        let from = test_decode::<FromEmail>(&["webmaster@example.org"]).unwrap();
+        assert_eq!(from.as_str(), "webmaster@example.org");
+
+        let from = test_decode::<FromEmail>(&["Web Master <webmaster@example.org>"]).unwrap();
+        assert_eq!(from.as_str(), "Web Master <webmaster@example.org>");
+    }
+
+    #[test]
+    fn decode_invalid() {
+        assert_eq!(test_decode::<FromEmail>(&["webmaster"]), None);
+        assert_eq!(test_decode::<FromEmail>(&["@example.org"]), None);
+        assert_eq!(test_decode::<FromEmail>(&["webmaster@"]), None);
+        assert_eq!(test_decode::<FromEmail>(&["Web Master <webmaster@example.org"]), None);
+    }
+
+    #[test]
+    fn encode() {
+        let headers = test_encode(FromEmail::from_static("webmaster@example.org"));
+        assert_eq!(headers["from"], "webmaster@example.org");
     }
 }
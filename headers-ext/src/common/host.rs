@@ -1,4 +1,5 @@
 use std::fmt;
+use std::net::IpAddr;
 
 use bytes::Bytes;
 use http::uri::Authority;
@@ -15,12 +16,126 @@ impl Host {
 
     /// Get the optional port number.
     pub fn port(&self) -> Option<u16> {
-        self.0.port()
+        self.0.port_part().map(|p| p.as_u16())
+    }
+
+    /// Check if this host matches an allowed host `pattern`, such as to
+    /// protect against DNS rebinding.
+    ///
+    /// The pattern can be:
+    ///
+    /// - a hostname, compared ignoring ASCII case, such as `example.com`,
+    /// - a wildcard for a single label, such as `*.example.com`, which
+    ///   matches `api.example.com` but neither `example.com` nor
+    ///   `a.b.example.com`,
+    /// - an IP address, compared as an address, so `::1` and `[::1]` are
+    ///   the same.
+    ///
+    /// The port is ignored, unless the pattern has one, as in
+    /// `example.com:8080`, which then must be the port of the host. A
+    /// trailing dot, as in `example.com.`, is ignored on both sides.
+    /// Hosts and patterns that aren't ASCII never match, so
+    /// internationalized names must be given in their `xn--` form.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # extern crate headers_ext as headers;
+    /// use headers::Host;
+    ///
+    /// let host = "API.example.com:8443".parse::<Host>().unwrap();
+    /// assert!(host.matches("*.example.com"));
+    /// assert!(host.matches("api.example.com:8443"));
+    /// assert!(!host.matches("api.example.com:443"));
+    /// ```
+    pub fn matches(&self, pattern: &str) -> bool {
+        let (pattern, port) = match split_port(pattern) {
+            Some(split) => split,
+            None => return false,
+        };
+        if port.is_some() && port != self.port() {
+            return false;
+        }
+
+        hostname_matches(self.hostname(), pattern)
+    }
+
+    /// Check if this host matches any of the `patterns`, as with `matches`.
+    pub fn matches_any(&self, patterns: &[&str]) -> bool {
+        patterns.iter().any(|pattern| self.matches(pattern))
     }
 }
 
+// Compare a hostname, or IP literal, to a host pattern, both without a
+// port, as described for `Host::matches`.
+pub(super) fn hostname_matches(host: &str, pattern: &str) -> bool {
+    let host = unbracket(host);
+    let pattern = unbracket(pattern);
+    if !host.is_ascii() || !pattern.is_ascii() {
+        return false;
+    }
+
+    match (host.parse::<IpAddr>(), pattern.parse::<IpAddr>()) {
+        (Ok(host), Ok(pattern)) => return host == pattern,
+        (Ok(_), Err(_)) | (Err(_), Ok(_)) => return false,
+        (Err(_), Err(_)) => (),
+    }
+
+    let host = trim_dot(host);
+    let pattern = trim_dot(pattern);
+    if host.is_empty() || pattern.is_empty() {
+        return false;
+    }
+    if let Some(suffix) = pattern.strip_prefix('*').filter(|s| s.starts_with('.')) {
+        if suffix.len() < 2 || suffix.contains('*') || host.len() <= suffix.len() {
+            return false;
+        }
+        let (label, rest) = host.split_at(host.len() - suffix.len());
+        !label.contains('.') && rest.eq_ignore_ascii_case(suffix)
+    } else {
+        !pattern.contains('*') && host.eq_ignore_ascii_case(pattern)
+    }
+}
+
+// Split a host pattern into the host, without brackets, and the optional
+// port. Returns `None` if the port is invalid.
+pub(super) fn split_port(pattern: &str) -> Option<(&str, Option<u16>)> {
+    let (host, port) = if pattern.starts_with('[') {
+        let end = pattern.find(']')?;
+        let port = &pattern[end + 1..];
+        if !port.is_empty() && !port.starts_with(':') {
+            return None;
+        }
+        (&pattern[1..end], port.get(1..))
+    } else if pattern.matches(':').count() > 1 {
+        // An IPv6 address without brackets can't have a port.
+        (pattern, None)
+    } else {
+        match pattern.find(':') {
+            Some(i) => (&pattern[..i], Some(&pattern[i + 1..])),
+            None => (pattern, None),
+        }
+    };
+    match port {
+        Some(port) => port.parse().ok().map(|port| (host, Some(port))),
+        None => Some((host, None)),
+    }
+}
+
+fn unbracket(host: &str) -> &str {
+    host.strip_prefix('[')
+        .and_then(|host| host.strip_suffix(']'))
+        .unwrap_or(host)
+}
+
+fn trim_dot(host: &str) -> &str {
+    host.strip_suffix('.').unwrap_or(host)
+}
+
 impl ::Header for Host {
-    const NAME: &'static ::HeaderName = &::http::header::HOST;
+    fn name() -> &'static ::HeaderName {
+        &::http::header::HOST
+    }
 
     fn decode(values: &mut ::Values) -> Option<Self> {
         let value = Bytes::from(values.next()?.clone());
@@ -52,3 +167,96 @@ impl fmt::Display for Host {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::Host;
+    use super::super::test_decode;
+
+    fn host(s: &str) -> Host {
+        test_decode(&[s]).unwrap()
+    }
+
+    #[test]
+    fn matches_hostname() {
+        assert!(host("example.com").matches("example.com"));
+        assert!(host("Example.COM").matches("example.com"));
+        assert!(host("example.com").matches("EXAMPLE.com"));
+        assert!(!host("example.com").matches("example.org"));
+        assert!(!host("example.com").matches("www.example.com"));
+        assert!(!host("www.example.com").matches("example.com"));
+        assert!(!host("notexample.com").matches("example.com"));
+        assert!(!host("example.com").matches(""));
+    }
+
+    #[test]
+    fn matches_port() {
+        assert!(host("example.com:8080").matches("example.com"));
+        assert!(host("example.com:8080").matches("example.com:8080"));
+        assert!(!host("example.com:8080").matches("example.com:80"));
+        assert!(!host("example.com").matches("example.com:80"));
+        assert!(!host("example.com:80").matches("example.com:http"));
+        assert!(!host("example.com:80").matches("example.com:"));
+    }
+
+    #[test]
+    fn matches_wildcard() {
+        assert!(host("api.example.com").matches("*.example.com"));
+        assert!(host("API.Example.com:443").matches("*.example.com"));
+        assert!(!host("example.com").matches("*.example.com"));
+        assert!(!host("a.b.example.com").matches("*.example.com"));
+        assert!(!host("apiexample.com").matches("*.example.com"));
+        assert!(!host(".example.com").matches("*.example.com"));
+        assert!(!host("api.example.com").matches("*"));
+        assert!(!host("api.example.com").matches("*."));
+        assert!(!host("api.example.com").matches("api.*.com"));
+        assert!(!host("api.example.com").matches("*.*.com"));
+        assert!(host("api.example.com:8080").matches("*.example.com:8080"));
+    }
+
+    #[test]
+    fn matches_trailing_dot() {
+        assert!(host("example.com.").matches("example.com"));
+        assert!(host("example.com").matches("example.com."));
+        assert!(host("api.example.com.").matches("*.example.com"));
+        assert!(!host("example.com..").matches("example.com"));
+    }
+
+    #[test]
+    fn matches_ip() {
+        assert!(host("127.0.0.1").matches("127.0.0.1"));
+        assert!(host("127.0.0.1:8080").matches("127.0.0.1"));
+        assert!(!host("127.0.0.1").matches("127.0.0.2"));
+        assert!(!host("127.0.0.1").matches("*.0.0.1"));
+
+        assert!(host("[::1]").matches("::1"));
+        assert!(host("[::1]:8080").matches("[::1]"));
+        assert!(host("[::1]:8080").matches("[::1]:8080"));
+        assert!(host("[::1]").matches("0:0:0:0:0:0:0:1"));
+        assert!(!host("[::1]:8080").matches("[::1]:80"));
+        assert!(!host("[::1]").matches("[::2]"));
+        assert!(!host("[::1]").matches("[::1"));
+        assert!(!host("[::1]").matches("[::1]x"));
+
+        // An address isn't a hostname.
+        assert!(!host("127.0.0.1").matches("localhost"));
+        assert!(!host("localhost").matches("127.0.0.1"));
+    }
+
+    #[test]
+    fn non_ascii_never_matches() {
+        assert!(!host("example.com").matches("exämple.com"));
+        assert!(!host("example.com").matches("*.exämple.com"));
+        assert!(host("xn--exmple-cua.com").matches("xn--exmple-cua.com"));
+    }
+
+    #[test]
+    fn matches_any() {
+        let allowed = ["localhost", "*.example.com", "[::1]"];
+        assert!(host("localhost:3000").matches_any(&allowed));
+        assert!(host("www.example.com").matches_any(&allowed));
+        assert!(host("[::1]:3000").matches_any(&allowed));
+        assert!(!host("evil.com").matches_any(&allowed));
+        assert!(!host("localhost").matches_any(&[]));
+    }
+}
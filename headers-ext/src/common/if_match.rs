@@ -1,6 +1,5 @@
-use {HeaderValue};
 use super::ETag;
-use util::FlatCsv;
+use util::{EntityTag, EntityTagRange};
 
 /// `If-Match` header, defined in
 /// [RFC7232](https://tools.ietf.org/html/rfc7232#section-3.1)
@@ -26,7 +25,12 @@ use util::FlatCsv;
 /// # Example values
 ///
 /// * `"xyzzy"`
-/// * "xyzzy", "r2d2xxxx", "c3piozzzz"
+/// * `"xyzzy", "r2d2xxxx", "c3piozzzz"`
+/// * `*`
+///
+/// The tags can be on one field line or spread over several, which decode
+/// the same. A value that isn't a valid tag, or `*` along with tags,
+/// fails to decode.
 ///
 /// # Examples
 ///
@@ -36,19 +40,72 @@ use util::FlatCsv;
 ///
 /// let if_match = IfMatch::any();
 /// ```
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::{HeaderMap, HeaderMapExt, IfMatch};
+///
+/// let mut map = HeaderMap::new();
+/// map.append("if-match", "\"xyzzy\"".parse().unwrap());
+/// map.append("if-match", "W/\"r2d2\"".parse().unwrap());
+///
+/// let if_match = map.typed_get::<IfMatch>().unwrap();
+/// let tags = if_match.tags().map(|tag| tag.to_string()).collect::<Vec<_>>();
+/// assert_eq!(tags, ["\"xyzzy\"", "W/\"r2d2\""]);
+/// ```
 #[derive(Clone, Debug, PartialEq, Header)]
-pub struct IfMatch(FlatCsv);
+pub struct IfMatch(EntityTagRange);
 
 impl IfMatch {
     /// Create a new `If-Match: *` header.
     pub fn any() -> IfMatch {
-        IfMatch(HeaderValue::from_static("*").into())
+        IfMatch(EntityTagRange::Any)
+    }
+
+    /// Returns whether this is `If-Match: *`, matching any current
+    /// representation.
+    pub fn is_any(&self) -> bool {
+        self.0.is_any()
+    }
+
+    /// Iterate the listed entity tags, in order.
+    ///
+    /// There are none for `*`.
+    pub fn tags(&self) -> ::list::Iter<'_, EntityTag> {
+        self.0.tags()
+    }
+
+    /// Checks whether the `ETag` of the current representation passes this
+    /// precondition, using the strong comparison.
+    pub fn precondition_passes(&self, etag: &ETag) -> bool {
+        self.is_any() || self.tags().any(|tag| tag.strong_eq(&etag.0))
     }
 }
 
 impl From<ETag> for IfMatch {
     fn from(etag: ETag) -> IfMatch {
-        IfMatch(HeaderValue::from(etag.0).into())
+        IfMatch(etag.0.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IfMatch;
+    use super::super::{test_decode, ETag};
+
+    fn etag(s: &str) -> ETag {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn precondition_uses_strong_comparison() {
+        let if_match = test_decode::<IfMatch>(&["\"a\", W/\"b\""]).unwrap();
+        assert!(if_match.precondition_passes(&etag("\"a\"")));
+        assert!(!if_match.precondition_passes(&etag("W/\"a\"")));
+        assert!(!if_match.precondition_passes(&etag("W/\"b\"")));
+        assert!(!if_match.precondition_passes(&etag("\"c\"")));
+
+        assert!(IfMatch::any().precondition_passes(&etag("\"c\"")));
     }
 }
 
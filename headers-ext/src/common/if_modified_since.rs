@@ -35,7 +35,7 @@ pub struct IfModifiedSince(HttpDate);
 impl IfModifiedSince {
     /// Check if the supplied time means the resource has been modified.
     pub fn is_modified(&self, last_modified: SystemTime) -> bool {
-        self.0 < last_modified.into()
+        self.0 < last_modified
     }
 }
 
@@ -51,6 +51,8 @@ impl From<IfModifiedSince> for SystemTime {
     }
 }
 
+impl_date_header!(IfModifiedSince);
+
 #[cfg(test)]
 mod tests {
     use std::time::Duration;
@@ -67,4 +69,33 @@ mod tests {
         assert!(!if_mod.is_modified(exact));
         assert!(!if_mod.is_modified(older));
     }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono() {
+        use std::convert::TryFrom;
+        use std::time::UNIX_EPOCH;
+        use chrono::{DateTime, TimeZone, Utc};
+
+        let dt = Utc.with_ymd_and_hms(1994, 11, 7, 8, 48, 37).unwrap();
+        let if_mod = IfModifiedSince::try_from(dt).unwrap();
+        assert_eq!(SystemTime::from(if_mod), UNIX_EPOCH + Duration::from_secs(784_198_117));
+        assert_eq!(DateTime::<Utc>::from(if_mod), dt);
+
+        let far = Utc.with_ymd_and_hms(10_000, 1, 1, 0, 0, 0).unwrap();
+        assert!(IfModifiedSince::try_from(far).is_err());
+    }
+
+    #[cfg(feature = "time03")]
+    #[test]
+    fn time03() {
+        use std::convert::TryFrom;
+        use std::time::UNIX_EPOCH;
+        use time03::OffsetDateTime;
+
+        let dt = OffsetDateTime::from_unix_timestamp_nanos(784_198_117_500_000_000).unwrap();
+        let if_mod = IfModifiedSince::try_from(dt).unwrap();
+        assert_eq!(SystemTime::from(if_mod), UNIX_EPOCH + Duration::from_secs(784_198_117));
+        assert_eq!(OffsetDateTime::from(if_mod), dt.replace_nanosecond(0).unwrap());
+    }
 }
@@ -1,6 +1,7 @@
-use util::{FlatCsv};
+use std::convert::TryFrom;
+
+use util::{EntityTag, EntityTagRange};
 use super::ETag;
-use {HeaderValue};
 
 /// `If-None-Match` header, defined in
 /// [RFC7232](https://tools.ietf.org/html/rfc7232#section-3.2)
@@ -30,6 +31,10 @@ use {HeaderValue};
 /// * `W/"xyzzy", W/"r2d2xxxx", W/"c3piozzzz"`
 /// * `*`
 ///
+/// The tags can be on one field line or spread over several, which decode
+/// the same. A value that isn't a valid tag, including a weak tag with a
+/// lowercase `w/`, or `*` along with tags, fails to decode.
+///
 /// # Examples
 ///
 /// ```
@@ -38,54 +43,193 @@ use {HeaderValue};
 ///
 /// let if_none_match = IfNoneMatch::any();
 /// ```
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::IfNoneMatch;
+///
+/// let if_none_match = "\"xyzzy\", W/\"r2d2\"".parse::<IfNoneMatch>().unwrap();
+/// assert!(!if_none_match.is_any());
+/// assert_eq!(if_none_match.tags().len(), 2);
+///
+/// assert!("*, \"xyzzy\"".parse::<IfNoneMatch>().is_err());
+/// assert!("w/\"xyzzy\"".parse::<IfNoneMatch>().is_err());
+/// ```
+///
+/// A single strong tag converts to an `ETag`:
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use std::convert::TryFrom;
+/// use headers::{ETag, IfNoneMatch};
+///
+/// let if_none_match = "\"xyzzy\"".parse::<IfNoneMatch>().unwrap();
+/// assert_eq!(ETag::try_from(if_none_match).unwrap().to_string(), "\"xyzzy\"");
+///
+/// for value in &["*", "W/\"xyzzy\"", "\"xyzzy\", \"r2d2\""] {
+///     let if_none_match = value.parse::<IfNoneMatch>().unwrap();
+///     assert!(ETag::try_from(if_none_match).is_err());
+/// }
+/// ```
 #[derive(Clone, Debug, PartialEq, Header)]
-pub struct IfNoneMatch(FlatCsv);
+pub struct IfNoneMatch(EntityTagRange);
 
 impl IfNoneMatch {
     /// Create a new `If-None-Match: *` header.
     pub fn any() -> IfNoneMatch {
-        IfNoneMatch(HeaderValue::from_static("*").into())
+        IfNoneMatch(EntityTagRange::Any)
+    }
+
+    /// Returns whether this is `If-None-Match: *`, matching any current
+    /// representation.
+    pub fn is_any(&self) -> bool {
+        self.0.is_any()
+    }
+
+    /// Iterate the listed entity tags, in order.
+    ///
+    /// There are none for `*`.
+    pub fn tags(&self) -> ::list::Iter<'_, EntityTag> {
+        self.0.tags()
+    }
+
+    /// Checks whether the `ETag` of the current representation passes this
+    /// precondition, meaning it matches none of the listed tags using the
+    /// weak comparison.
+    pub fn precondition_passes(&self, etag: &ETag) -> bool {
+        !self.is_any() && !self.tags().any(|tag| tag.weak_eq(&etag.0))
     }
 }
 
 impl From<ETag> for IfNoneMatch {
     fn from(etag: ETag) -> IfNoneMatch {
-        IfNoneMatch(HeaderValue::from(etag.0).into())
+        IfNoneMatch(etag.0.into())
     }
 }
 
-    /*
-    test_if_none_match {
-        test_header!(test1, vec![b"\"xyzzy\""]);
-        test_header!(test2, vec![b"W/\"xyzzy\""]);
-        test_header!(test3, vec![b"\"xyzzy\", \"r2d2xxxx\", \"c3piozzzz\""]);
-        test_header!(test4, vec![b"W/\"xyzzy\", W/\"r2d2xxxx\", W/\"c3piozzzz\""]);
-        test_header!(test5, vec![b"*"]);
+impl TryFrom<IfNoneMatch> for ETag {
+    type Error = IfNoneMatch;
+
+    /// Get the tag of an `If-None-Match` that lists exactly one strong
+    /// entity tag, such as to look up the cached representation it names.
+    ///
+    /// Fails with the header unchanged otherwise, such as for `*`, for
+    /// several tags, or for a weak one.
+    fn try_from(if_none_match: IfNoneMatch) -> Result<ETag, IfNoneMatch> {
+        let tag = {
+            let mut tags = if_none_match.tags();
+            match (tags.next(), tags.next()) {
+                (Some(tag), None) if !tag.is_weak() => Some(tag.clone()),
+                _ => None,
+            }
+        };
+        tag.map(ETag).ok_or(if_none_match)
     }
-    */
+}
 
-/*
 #[cfg(test)]
 mod tests {
+    use ::{HeaderMap, HeaderMapExt, HeaderValue, ParseMode};
     use super::IfNoneMatch;
-    use Header;
-    use EntityTag;
+    use super::super::{test_decode, test_encode, ETag, IfMatch};
+
+    fn etag(s: &str) -> ETag {
+        s.parse().unwrap()
+    }
+
+    fn tags(if_none_match: &IfNoneMatch) -> Vec<String> {
+        if_none_match.tags().map(|tag| tag.to_string()).collect()
+    }
+
+    #[test]
+    fn one_line_or_several() {
+        let one = test_decode::<IfNoneMatch>(&["\"a\", \"b\""]).unwrap();
+        let two = test_decode::<IfNoneMatch>(&["\"a\"", "\"b\""]).unwrap();
+        let spaced = test_decode::<IfNoneMatch>(&["\"a\",\"b\""]).unwrap();
+        assert_eq!(tags(&one), ["\"a\"", "\"b\""]);
+        assert_eq!(one, two);
+        assert_eq!(one, spaced);
+        assert_ne!(one, test_decode::<IfNoneMatch>(&["\"b\", \"a\""]).unwrap());
+
+        // Empty elements are skipped, and a comma in a tag doesn't split it.
+        let commas = test_decode::<IfNoneMatch>(&[", \"a,b\",,", "W/\"c\""]).unwrap();
+        assert_eq!(tags(&commas), ["\"a,b\"", "W/\"c\""]);
+    }
 
     #[test]
-    fn test_if_none_match() {
-        let mut if_none_match: ::Result<IfNoneMatch>;
-
-        if_none_match = Header::parse_header(&b"*".as_ref().into());
-        assert_eq!(if_none_match.ok(), Some(IfNoneMatch::Any));
-
-        if_none_match = Header::parse_header(&b"\"foobar\", W/\"weak-etag\"".as_ref().into());
-        let mut entities: Vec<EntityTag> = Vec::new();
-        let foobar_etag = EntityTag::new(false, "foobar".to_owned());
-        let weak_etag = EntityTag::new(true, "weak-etag".to_owned());
-        entities.push(foobar_etag);
-        entities.push(weak_etag);
-        assert_eq!(if_none_match.ok(), Some(IfNoneMatch::Items(entities)));
+    fn any() {
+        let any = test_decode::<IfNoneMatch>(&["*"]).unwrap();
+        assert!(any.is_any());
+        assert_eq!(any, IfNoneMatch::any());
+        assert_eq!(any.tags().count(), 0);
+        assert_eq!(test_decode::<IfNoneMatch>(&[" * ,"]), Some(IfNoneMatch::any()));
+
+        let tag = test_decode::<IfNoneMatch>(&["\"*\""]).unwrap();
+        assert!(!tag.is_any());
+        assert_eq!(tags(&tag), ["\"*\""]);
     }
-}
-*/
 
+    #[test]
+    fn malformed() {
+        let cases: &[&[&str]] = &[
+            // `*` along with anything.
+            &["*, \"a\""],
+            &["\"a\", *"],
+            &["*", "\"a\""],
+            &["*", "*"],
+            &["*, *"],
+            // Unterminated quotes.
+            &["\"a"],
+            &["\"a\", \"b"],
+            &["\"a, \"b\""],
+            &["a\""],
+            &["\""],
+            // Weak tags, of which only `W/` is one.
+            &["w/\"a\""],
+            &["\"a\", w/\"b\""],
+            &["W/a"],
+            &["W/ \"a\""],
+            &["W/"],
+            // Anything else.
+            &["a"],
+            &["\"a\" \"b\""],
+            &["\"a b\""],
+            &["\"a\"", "b"],
+        ];
+        for &values in cases {
+            for &mode in &[ParseMode::Lenient, ParseMode::Strict] {
+                let mut map = HeaderMap::new();
+                for &value in values {
+                    map.append("if-none-match", HeaderValue::from_str(value).unwrap());
+                    map.append("if-match", HeaderValue::from_str(value).unwrap());
+                }
+                assert_eq!(map.typed_get_with::<IfNoneMatch>(mode), None, "{:?}", values);
+                assert_eq!(map.typed_get_with::<IfMatch>(mode), None, "{:?}", values);
+            }
+        }
+    }
+
+    #[test]
+    fn encode() {
+        let headers = test_encode(IfNoneMatch::any());
+        assert_eq!(headers["if-none-match"], "*");
+
+        let headers = test_encode(IfNoneMatch::from(etag("W/\"a\"")));
+        assert_eq!(headers["if-none-match"], "W/\"a\"");
+
+        // Several lines are joined.
+        let mut map = HeaderMap::new();
+        map.typed_insert(test_decode::<IfNoneMatch>(&["\"a\"", "\"b\""]).unwrap());
+        assert_eq!(map["if-none-match"], "\"a\", \"b\"");
+    }
+
+    #[test]
+    fn precondition_uses_weak_comparison() {
+        let if_none_match = test_decode::<IfNoneMatch>(&["\"a\", W/\"b\""]).unwrap();
+        assert!(!if_none_match.precondition_passes(&etag("W/\"a\"")));
+        assert!(!if_none_match.precondition_passes(&etag("\"b\"")));
+        assert!(if_none_match.precondition_passes(&etag("\"c\"")));
+
+        assert!(!IfNoneMatch::any().precondition_passes(&etag("\"c\"")));
+    }
+}
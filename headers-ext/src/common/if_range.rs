@@ -56,10 +56,13 @@ impl IfRange {
 
     /// Checks if the resource has been modified, or if the range request
     /// can be served.
-    pub fn is_modified(&self, _etag: Option<&ETag>, last_modified: Option<&LastModified>) -> bool {
+    ///
+    /// An entity tag only matches using the strong comparison, and a date
+    /// only if it's exactly the `Last-Modified` date.
+    pub fn is_modified(&self, etag: Option<&ETag>, last_modified: Option<&LastModified>) -> bool {
         match self.0 {
-            IfRange_::Date(since) => last_modified.map(|time| since < time.0).unwrap_or(true),
-            IfRange_::EntityTag(_) => true,
+            IfRange_::Date(since) => last_modified.map(|time| since != time.0).unwrap_or(true),
+            IfRange_::EntityTag(ref tag) => etag.map(|etag| !tag.strong_eq(&etag.0)).unwrap_or(true),
         }
     }
 }
@@ -36,7 +36,7 @@ pub struct IfUnmodifiedSince(HttpDate);
 impl IfUnmodifiedSince {
     /// Check if the supplied time passes the precondtion.
     pub fn precondition_passes(&self, last_modified: SystemTime) -> bool {
-        self.0 >= last_modified.into()
+        self.0 >= last_modified
     }
 }
 
@@ -52,6 +52,8 @@ impl From<IfUnmodifiedSince> for SystemTime {
     }
 }
 
+impl_date_header!(IfUnmodifiedSince);
+
 #[cfg(test)]
 mod tests {
     use std::time::Duration;
@@ -0,0 +1,201 @@
+use std::fmt;
+use std::time::Duration;
+
+use util::{quoted, FlatCsv, Seconds};
+
+/// `Keep-Alive` header, defined in
+/// [RFC2068](https://tools.ietf.org/html/rfc2068#section-19.7.1.1)
+///
+/// The `Keep-Alive` header allows the sender of a persistent connection
+/// to hint at how long the connection may be left idle, and how many
+/// more requests it will accept on it.
+///
+/// The header is only meaningful alongside `Connection: keep-alive`, and
+/// is ignored in HTTP/2 and later.
+///
+/// # ABNF
+///
+/// ```text
+/// Keep-Alive           = #keep-alive-info
+/// keep-alive-info      = "timeout" "=" delta-seconds
+///                      / "max" "=" 1*DIGIT
+///                      / keep-alive-extension
+/// keep-alive-extension = token [ "=" ( token / quoted-string ) ]
+/// ```
+///
+/// # Example values
+///
+/// * `timeout=5, max=1000`
+/// * `timeout="30"`
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use std::time::Duration;
+/// use headers::KeepAlive;
+///
+/// let keep_alive = KeepAlive::new()
+///     .with_timeout(Duration::from_secs(5))
+///     .with_max(1000);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct KeepAlive {
+    timeout: Option<Seconds>,
+    max: Option<u32>,
+    /// Parameters other than `timeout` and `max`, with their value kept
+    /// exactly as received.
+    extensions: Vec<(String, Option<String>)>,
+}
+
+impl KeepAlive {
+    /// Create a `KeepAlive` header without any parameters.
+    pub fn new() -> KeepAlive {
+        KeepAlive::default()
+    }
+
+    /// Set the `timeout` parameter.
+    ///
+    /// Only whole seconds are sent, the fractional part is dropped.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout.into());
+        self
+    }
+
+    /// Set the `max` parameter.
+    pub fn with_max(mut self, max: u32) -> Self {
+        self.max = Some(max);
+        self
+    }
+
+    /// Get the idle time the connection may be kept open for, if set.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout.map(Into::into)
+    }
+
+    /// Get the number of further requests allowed on the connection, if set.
+    pub fn max(&self) -> Option<u32> {
+        self.max
+    }
+}
+
+impl ::Header for KeepAlive {
+    fn name() -> &'static ::HeaderName {
+        static_header_name!("keep-alive")
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        // A missing header doesn't decode, but an empty one does.
+        if values.size_hint().1 == Some(0) {
+            return None;
+        }
+
+        let csv: FlatCsv = FlatCsv::from_values(values)?;
+        let mut keep_alive = KeepAlive::new();
+
+        // Junk is common for this header, so anything that doesn't parse
+        // is skipped rather than failing the whole header.
+        for param in csv.iter() {
+            let mut parts = param.splitn(2, '=');
+            let name = parts.next().unwrap_or("").trim();
+            let value = parts.next().map(str::trim);
+
+            if name.eq_ignore_ascii_case("timeout") {
+                if keep_alive.timeout.is_none() {
+                    keep_alive.timeout = value
+                        .and_then(quoted::unquote)
+                        .and_then(|v| Seconds::parse(v.as_bytes()));
+                }
+            } else if name.eq_ignore_ascii_case("max") {
+                if keep_alive.max.is_none() {
+                    keep_alive.max = value
+                        .and_then(quoted::unquote)
+                        .and_then(|v| v.parse().ok());
+                }
+            } else if !name.is_empty() {
+                keep_alive.extensions.push((name.to_owned(), value.map(ToOwned::to_owned)));
+            }
+        }
+
+        Some(keep_alive)
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        values.append_fmt(self);
+    }
+}
+
+impl fmt::Display for KeepAlive {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut first = true;
+        let mut sep = |f: &mut fmt::Formatter| {
+            if first {
+                first = false;
+                Ok(())
+            } else {
+                f.write_str(", ")
+            }
+        };
+
+        if let Some(timeout) = self.timeout {
+            sep(f)?;
+            write!(f, "timeout={}", timeout)?;
+        }
+        if let Some(max) = self.max {
+            sep(f)?;
+            write!(f, "max={}", max)?;
+        }
+        for (name, value) in &self.extensions {
+            sep(f)?;
+            f.write_str(name)?;
+            if let Some(value) = value {
+                write!(f, "={}", value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use super::KeepAlive;
+    use super::super::{test_decode, test_encode};
+
+    #[test]
+    fn decode() {
+        let keep_alive = test_decode::<KeepAlive>(&["timeout=5, max=1000"]).unwrap();
+        assert_eq!(keep_alive.timeout(), Some(Duration::from_secs(5)));
+        assert_eq!(keep_alive.max(), Some(1000));
+    }
+
+    #[test]
+    fn decode_quoted_over_multiple_lines() {
+        let keep_alive = test_decode::<KeepAlive>(&["timeout=\"30\"", "max = 10"]).unwrap();
+        assert_eq!(keep_alive.timeout(), Some(Duration::from_secs(30)));
+        assert_eq!(keep_alive.max(), Some(10));
+    }
+
+    #[test]
+    fn decode_ignores_junk() {
+        let keep_alive = test_decode::<KeepAlive>(&["timeout=soon, max=-1, ,="]).unwrap();
+        assert_eq!(keep_alive, KeepAlive::new());
+    }
+
+    #[test]
+    fn unknown_params_round_trip() {
+        let keep_alive = test_decode::<KeepAlive>(&["foo=\"bar\", max=3, baz"]).unwrap();
+        assert_eq!(keep_alive.max(), Some(3));
+
+        let headers = test_encode(keep_alive);
+        assert_eq!(headers["keep-alive"], "max=3, foo=\"bar\", baz");
+    }
+
+    #[test]
+    fn encode() {
+        let keep_alive = KeepAlive::new().with_timeout(Duration::from_secs(5));
+
+        let headers = test_encode(keep_alive);
+        assert_eq!(headers["keep-alive"], "timeout=5");
+    }
+}
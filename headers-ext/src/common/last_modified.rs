@@ -1,5 +1,5 @@
 use std::time::SystemTime;
-use util::HttpDate;
+use util::{clock, HttpDate};
 
 /// `Last-Modified` header, defined in
 /// [RFC7232](http://tools.ietf.org/html/rfc7232#section-2.2)
@@ -33,6 +33,14 @@ use util::HttpDate;
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Header)]
 pub struct LastModified(pub(super) HttpDate);
 
+impl LastModified {
+    /// Create a `LastModified` of the current time, for a representation
+    /// that was just changed.
+    pub fn now() -> LastModified {
+        LastModified::from(clock::now())
+    }
+}
+
 impl From<SystemTime> for LastModified {
     fn from(time: SystemTime) -> LastModified {
         LastModified(time.into())
@@ -44,3 +52,5 @@ impl From<LastModified> for SystemTime {
         date.0.into()
     }
 }
+
+impl_date_header!(LastModified);
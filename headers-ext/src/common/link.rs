@@ -1,82 +1,77 @@
-use std::fmt;
 use std::borrow::Cow;
-use std::str::FromStr;
-#[allow(unused, deprecated)]
-use std::ascii::AsciiExt;
+use std::fmt;
 
 use mime::Mime;
-use language_tags::LanguageTag;
 
-use parsing;
-use {Header, Raw};
+use util::{ext_value, quoted, split_elements, split_quoted, token};
 
 /// The `Link` header, defined in
-/// [RFC5988](http://tools.ietf.org/html/rfc5988#section-5)
+/// [RFC8288](https://tools.ietf.org/html/rfc8288#section-3)
+///
+/// The `Link` header field serializes links to other resources, such as
+/// the next page of a collection, or the stylesheets and scripts a page
+/// will need, sent early in a `103 Early Hints` response.
 ///
 /// # ABNF
 ///
 /// ```text
-/// Link           = "Link" ":" #link-value
-/// link-value     = "<" URI-Reference ">" *( ";" link-param )
-/// link-param     = ( ( "rel" "=" relation-types )
-///                | ( "anchor" "=" <"> URI-Reference <"> )
-///                | ( "rev" "=" relation-types )
-///                | ( "hreflang" "=" Language-Tag )
-///                | ( "media" "=" ( MediaDesc | ( <"> MediaDesc <"> ) ) )
-///                | ( "title" "=" quoted-string )
-///                | ( "title*" "=" ext-value )
-///                | ( "type" "=" ( media-type | quoted-mt ) )
-///                | ( link-extension ) )
-/// link-extension = ( parmname [ "=" ( ptoken | quoted-string ) ] )
-///                | ( ext-name-star "=" ext-value )
-/// ext-name-star  = parmname "*" ; reserved for RFC2231-profiled
-/// ; extensions.  Whitespace NOT
-/// ; allowed in between.
-/// ptoken         = 1*ptokenchar
-/// ptokenchar     = "!" | "#" | "$" | "%" | "&" | "'" | "("
-///                | ")" | "*" | "+" | "-" | "." | "/" | DIGIT
-///                | ":" | "<" | "=" | ">" | "?" | "@" | ALPHA
-///                | "[" | "]" | "^" | "_" | "`" | "{" | "|"
-///                | "}" | "~"
-/// media-type     = type-name "/" subtype-name
-/// quoted-mt      = <"> media-type <">
-/// relation-types = relation-type
-///                | <"> relation-type *( 1*SP relation-type ) <">
-/// relation-type  = reg-rel-type | ext-rel-type
-/// reg-rel-type   = LOALPHA *( LOALPHA | DIGIT | "." | "-" )
-/// ext-rel-type   = URI
+/// Link       = #link-value
+/// link-value = "<" URI-Reference ">" *( OWS ";" OWS link-param )
+/// link-param = token BWS [ "=" BWS ( token / quoted-string ) ]
 /// ```
 ///
 /// # Example values
 ///
-/// `Link: <http://example.com/TheBook/chapter2>; rel="previous";
-///        title="previous chapter"`
+/// * `<http://example.com/TheBook/chapter2>; rel="previous"; title="previous chapter"`
+/// * `</TheBook/chapter2>; rel="previous"; title*=UTF-8'de'letztes%20Kapitel,
+///   </TheBook/chapter4>; rel="next"; title*=UTF-8'de'n%c3%a4chstes%20Kapitel`
+/// * `</style.css>; rel=preload; as=style, <https://cdn.example.com>; rel=preconnect`
+///
+/// # Notes
 ///
-/// `Link: </TheBook/chapter2>; rel="previous"; title*=UTF-8'de'letztes%20Kapitel,
-///        </TheBook/chapter4>; rel="next"; title*=UTF-8'de'n%c3%a4chstes%20Kapitel`
+/// * Parameters that can't be repeated, such as `rel` and `title`, keep
+///   their first occurrence.
+/// * Parameters without a typed accessor, such as `nopush`, are kept with
+///   `LinkValue::param`, and encoded back as they were.
+/// * The link-values are encoded on one field line. Wrap the header in
+///   [`MultiLine`](list/struct.MultiLine.html) for a line per link-value,
+///   as some clients only read the first value of a line of a
+///   `103 Early Hints` response.
 ///
-/// # Examples
+/// # Example
 ///
 /// ```
-/// use headers::{Headers, Link, LinkValue, RelationType};
+/// # extern crate headers_ext as headers;
+/// use headers::{AsAttribute, HeaderMap, HeaderMapExt, Link, LinkValue};
+/// use headers::list::MultiLine;
 ///
-/// let link_value = LinkValue::new("http://example.com/TheBook/chapter2")
-///     .push_rel(RelationType::Previous)
-///     .set_title("previous chapter");
+/// let mut link = Link::new(vec![LinkValue::preconnect("https://cdn.example.com")]);
+/// link.extend_preloads(vec![
+///     ("/style.css", AsAttribute::Style),
+///     ("/font.woff2", AsAttribute::Font),
+/// ]);
 ///
-/// let mut headers = Headers::new();
-/// headers.set(
-///     Link::new(vec![link_value])
+/// let mut map = HeaderMap::new();
+/// map.typed_insert(link.clone());
+/// assert_eq!(
+///     map["link"],
+///     "<https://cdn.example.com>; rel=preconnect, \
+///      </style.css>; rel=preload; as=style, \
+///      </font.woff2>; rel=preload; as=font; crossorigin",
 /// );
+///
+/// let mut map = HeaderMap::new();
+/// map.typed_insert(MultiLine(link));
+/// assert_eq!(map.get_all("link").iter().count(), 3);
 /// ```
 #[derive(Clone, PartialEq, Debug)]
 pub struct Link {
     /// A list of the `link-value`s of the Link entity-header.
-    values: Vec<LinkValue>
+    values: Vec<LinkValue>,
 }
 
 /// A single `link-value` of a `Link` header, based on:
-/// [RFC5988](http://tools.ietf.org/html/rfc5988#section-5)
+/// [RFC8288](https://tools.ietf.org/html/rfc8288#section-3)
 #[derive(Clone, PartialEq, Debug)]
 pub struct LinkValue {
     /// Target IRI: `link-value`.
@@ -93,7 +88,7 @@ pub struct LinkValue {
 
     /// Hint on the language of the result of dereferencing
     /// the link: `hreflang`.
-    href_lang: Option<Vec<LanguageTag>>,
+    href_lang: Option<Vec<String>>,
 
     /// Destination medium or media: `media`.
     media_desc: Option<Vec<MediaDesc>>,
@@ -107,6 +102,18 @@ pub struct LinkValue {
     /// Hint on the media type of the result of dereferencing
     /// the link: `type`.
     media_type: Option<Mime>,
+
+    /// The kind of resource a preload fetches: `as`.
+    as_attribute: Option<AsAttribute>,
+
+    /// The CORS mode of a preload: `crossorigin`.
+    crossorigin: Option<CrossOrigin>,
+
+    /// The priority of a preload: `fetchpriority`.
+    fetch_priority: Option<FetchPriority>,
+
+    /// Any other `link-param`, unquoted.
+    params: Vec<(String, Option<String>)>,
 }
 
 /// A Media Descriptors Enum based on:
@@ -134,95 +141,193 @@ pub enum MediaDesc {
     /// all.
     All,
     /// Unrecognized media descriptor extension.
-    Extension(String)
+    Extension(String),
 }
 
-/// A Link Relation Type Enum based on:
-/// [RFC5988](https://tools.ietf.org/html/rfc5988#section-6.2.2)
-#[derive(Clone, PartialEq, Debug)]
-pub enum RelationType {
+/// Define `RelationType`, with the name each registered type is matched
+/// and formatted as.
+macro_rules! relation_types {
+    ($($(#[$doc:meta])* $variant:ident => $name:expr,)+) => {
+        /// A Link Relation Type Enum based on:
+        /// [RFC8288](https://tools.ietf.org/html/rfc8288#section-2.1)
+        #[derive(Clone, PartialEq, Debug)]
+        pub enum RelationType {
+            $(
+                $(#[$doc])*
+                $variant,
+            )+
+            /// ext-rel-type.
+            ExtRelType(String),
+        }
+
+        impl RelationType {
+            fn as_str(&self) -> &str {
+                match *self {
+                    $(RelationType::$variant => $name,)+
+                    RelationType::ExtRelType(ref uri) => uri,
+                }
+            }
+
+            // Registered types are case-insensitive, extension types are
+            // URIs and kept as they are.
+            fn parse(s: &str) -> RelationType {
+                $(
+                    if s.eq_ignore_ascii_case($name) {
+                        return RelationType::$variant;
+                    }
+                )+
+                RelationType::ExtRelType(s.to_owned())
+            }
+        }
+    };
+}
+
+relation_types! {
     /// alternate.
-    Alternate,
+    Alternate => "alternate",
     /// appendix.
-    Appendix,
+    Appendix => "appendix",
     /// bookmark.
-    Bookmark,
+    Bookmark => "bookmark",
     /// chapter.
-    Chapter,
+    Chapter => "chapter",
     /// contents.
-    Contents,
+    Contents => "contents",
     /// copyright.
-    Copyright,
+    Copyright => "copyright",
     /// current.
-    Current,
+    Current => "current",
     /// describedby.
-    DescribedBy,
+    DescribedBy => "describedby",
+    /// dns-prefetch.
+    DnsPrefetch => "dns-prefetch",
     /// edit.
-    Edit,
+    Edit => "edit",
     /// edit-media.
-    EditMedia,
+    EditMedia => "edit-media",
     /// enclosure.
-    Enclosure,
+    Enclosure => "enclosure",
     /// first.
-    First,
+    First => "first",
     /// glossary.
-    Glossary,
+    Glossary => "glossary",
     /// help.
-    Help,
+    Help => "help",
     /// hub.
-    Hub,
+    Hub => "hub",
     /// index.
-    Index,
+    Index => "index",
     /// last.
-    Last,
+    Last => "last",
     /// latest-version.
-    LatestVersion,
+    LatestVersion => "latest-version",
     /// license.
-    License,
+    License => "license",
+    /// modulepreload.
+    ModulePreload => "modulepreload",
     /// next.
-    Next,
+    Next => "next",
     /// next-archive.
-    NextArchive,
+    NextArchive => "next-archive",
     /// payment.
-    Payment,
+    Payment => "payment",
+    /// preconnect.
+    Preconnect => "preconnect",
+    /// prefetch.
+    Prefetch => "prefetch",
+    /// preload.
+    Preload => "preload",
     /// prev.
-    Prev,
+    Prev => "prev",
     /// predecessor-version.
-    PredecessorVersion,
+    PredecessorVersion => "predecessor-version",
     /// previous.
-    Previous,
+    Previous => "previous",
     /// prev-archive.
-    PrevArchive,
+    PrevArchive => "prev-archive",
     /// related.
-    Related,
+    Related => "related",
     /// replies.
-    Replies,
+    Replies => "replies",
     /// section.
-    Section,
+    Section => "section",
     /// self.
-    RelationTypeSelf,
+    RelationTypeSelf => "self",
     /// service.
-    Service,
+    Service => "service",
     /// start.
-    Start,
+    Start => "start",
     /// stylesheet.
-    Stylesheet,
+    Stylesheet => "stylesheet",
     /// subsection.
-    Subsection,
+    Subsection => "subsection",
     /// successor-version.
-    SuccessorVersion,
+    SuccessorVersion => "successor-version",
     /// up.
-    Up,
-    /// versionHistory.
-    VersionHistory,
+    Up => "up",
+    /// version-history.
+    VersionHistory => "version-history",
     /// via.
-    Via,
+    Via => "via",
     /// working-copy.
-    WorkingCopy,
+    WorkingCopy => "working-copy",
     /// working-copy-of.
-    WorkingCopyOf,
-    /// ext-rel-type.
-    ExtRelType(String)
+    WorkingCopyOf => "working-copy-of",
+}
+
+/// The `as` parameter of a preload, the kind of resource it fetches,
+/// defined in [HTML](https://html.spec.whatwg.org/multipage/links.html#link-type-preload).
+///
+/// The preload is only used if this matches the request that needs the
+/// resource.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AsAttribute {
+    /// audio.
+    Audio,
+    /// document.
+    Document,
+    /// embed.
+    Embed,
+    /// fetch, for `fetch()` and `XMLHttpRequest`.
+    Fetch,
+    /// font.
+    Font,
+    /// image.
+    Image,
+    /// object.
+    Object,
+    /// script.
+    Script,
+    /// style.
+    Style,
+    /// track.
+    Track,
+    /// video.
+    Video,
+    /// worker.
+    Worker,
+}
+
+/// The `crossorigin` parameter, the CORS mode of a preload.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CrossOrigin {
+    /// `crossorigin`, without credentials for other origins.
+    ///
+    /// Any value other than `use-credentials` decodes as this.
+    Anonymous,
+    /// `crossorigin=use-credentials`.
+    UseCredentials,
+}
+
+/// The `fetchpriority` parameter, a hint of how soon a preload is needed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FetchPriority {
+    /// high.
+    High,
+    /// low.
+    Low,
+    /// auto, the default.
+    Auto,
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -240,16 +345,43 @@ impl Link {
         self.values.as_ref()
     }
 
+    /// Returns an iterator over the `LinkValue`s, in order.
+    pub fn iter(&self) -> ::list::Iter<'_, LinkValue> {
+        ::list::Iter::new(self.values.iter())
+    }
+
+    fn into_items(self) -> ::list::IntoIter<LinkValue> {
+        ::list::IntoIter::new(self.values.into_iter())
+    }
+
     /// Add a `LinkValue` instance to the `Link` header's values.
     pub fn push_value(&mut self, link_value: LinkValue) {
         self.values.push(link_value);
     }
+
+    /// Add a `rel=preload` link-value for each URI and `as` parameter, as
+    /// `LinkValue::preload` makes them.
+    pub fn extend_preloads<I, T>(&mut self, preloads: I)
+    where
+        I: IntoIterator<Item = (T, AsAttribute)>,
+        T: Into<Cow<'static, str>>,
+    {
+        self.values.extend(
+            preloads
+                .into_iter()
+                .map(|(uri, as_attribute)| LinkValue::preload(uri, as_attribute)),
+        );
+    }
 }
 
+impl_collection!(Link, LinkValue, ordered);
+
 impl LinkValue {
     /// Create `LinkValue` from URI-Reference.
     pub fn new<T>(uri: T) -> LinkValue
-        where T: Into<Cow<'static, str>> {
+    where
+        T: Into<Cow<'static, str>>,
+    {
         LinkValue {
             link: uri.into(),
             rel: None,
@@ -260,9 +392,49 @@ impl LinkValue {
             title: None,
             title_star: None,
             media_type: None,
+            as_attribute: None,
+            crossorigin: None,
+            fetch_priority: None,
+            params: Vec::new(),
         }
     }
 
+    /// Create a `rel=preload` link-value, fetching `uri` early as the
+    /// kind of resource `as_attribute` says.
+    ///
+    /// Fonts and `fetch` resources are always requested with CORS, so
+    /// they're also marked `crossorigin`, or the preload wouldn't be used.
+    pub fn preload<T>(uri: T, as_attribute: AsAttribute) -> LinkValue
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        let link = LinkValue::new(uri)
+            .push_rel(RelationType::Preload)
+            .set_as_attribute(as_attribute);
+        match as_attribute {
+            AsAttribute::Font | AsAttribute::Fetch => link.set_crossorigin(CrossOrigin::Anonymous),
+            _ => link,
+        }
+    }
+
+    /// Create a `rel=preconnect` link-value, opening a connection to
+    /// `origin` before it's needed.
+    pub fn preconnect<T>(origin: T) -> LinkValue
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        LinkValue::new(origin).push_rel(RelationType::Preconnect)
+    }
+
+    /// Create a `rel=modulepreload` link-value, fetching the JavaScript
+    /// module at `uri` early.
+    pub fn modulepreload<T>(uri: T) -> LinkValue
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        LinkValue::new(uri).push_rel(RelationType::ModulePreload)
+    }
+
     /// Get the `LinkValue`'s value.
     pub fn link(&self) -> &str {
         self.link.as_ref()
@@ -284,7 +456,7 @@ impl LinkValue {
     }
 
     /// Get the `LinkValue`'s `hreflang` parameter(s).
-    pub fn href_lang(&self) -> Option<&[LanguageTag]> {
+    pub fn href_lang(&self) -> Option<&[String]> {
         self.href_lang.as_ref().map(AsRef::as_ref)
     }
 
@@ -298,7 +470,10 @@ impl LinkValue {
         self.title.as_ref().map(AsRef::as_ref)
     }
 
-    /// Get the `LinkValue`'s `title*` parameter.
+    /// Get the `LinkValue`'s `title*` parameter, decoded.
+    ///
+    /// A `title*` that doesn't decode, such as in a charset other than
+    /// UTF-8, is ignored.
     pub fn title_star(&self) -> Option<&str> {
         self.title_star.as_ref().map(AsRef::as_ref)
     }
@@ -308,324 +483,415 @@ impl LinkValue {
         self.media_type.as_ref()
     }
 
-    /// Add a `RelationType` to the `LinkValue`'s `rel` parameter.
-    pub fn push_rel(mut self, rel: RelationType) -> LinkValue {
-        let mut v = self.rel.take().unwrap_or(Vec::new());
+    /// Get the `LinkValue`'s `as` parameter.
+    ///
+    /// An `as` this doesn't know is kept as a `param` instead.
+    pub fn as_attribute(&self) -> Option<AsAttribute> {
+        self.as_attribute
+    }
+
+    /// Get the `LinkValue`'s `crossorigin` parameter.
+    pub fn crossorigin(&self) -> Option<CrossOrigin> {
+        self.crossorigin
+    }
 
-        v.push(rel);
+    /// Get the `LinkValue`'s `fetchpriority` parameter.
+    ///
+    /// A `fetchpriority` this doesn't know is kept as a `param` instead.
+    pub fn fetch_priority(&self) -> Option<FetchPriority> {
+        self.fetch_priority
+    }
 
-        self.rel = Some(v);
+    /// Get another parameter, by its case-insensitive name, unquoted.
+    ///
+    /// A parameter without a value, such as `nopush`, is `Some("")`.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|&(have, _)| have.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_ref().map_or("", AsRef::as_ref))
+    }
 
+    /// Add a `RelationType` to the `LinkValue`'s `rel` parameter.
+    pub fn push_rel(mut self, rel: RelationType) -> LinkValue {
+        self.rel.get_or_insert_with(Vec::new).push(rel);
         self
     }
 
     /// Set `LinkValue`'s `anchor` parameter.
     pub fn set_anchor<T: Into<String>>(mut self, anchor: T) -> LinkValue {
         self.anchor = Some(anchor.into());
-
         self
     }
 
     /// Add a `RelationType` to the `LinkValue`'s `rev` parameter.
     pub fn push_rev(mut self, rev: RelationType) -> LinkValue {
-        let mut v = self.rev.take().unwrap_or(Vec::new());
-
-        v.push(rev);
-
-        self.rev = Some(v);
-
+        self.rev.get_or_insert_with(Vec::new).push(rev);
         self
     }
 
-    /// Add a `LanguageTag` to the `LinkValue`'s `hreflang` parameter.
-    pub fn push_href_lang(mut self, language_tag: LanguageTag) -> LinkValue {
-        let mut v = self.href_lang.take().unwrap_or(Vec::new());
-
-        v.push(language_tag);
-
-        self.href_lang = Some(v);
-
+    /// Add a language tag, such as `de`, to the `LinkValue`'s `hreflang`
+    /// parameter.
+    pub fn push_href_lang<T: Into<String>>(mut self, language_tag: T) -> LinkValue {
+        self.href_lang.get_or_insert_with(Vec::new).push(language_tag.into());
         self
     }
 
     /// Add a `MediaDesc` to the `LinkValue`'s `media_desc` parameter.
     pub fn push_media_desc(mut self, media_desc: MediaDesc) -> LinkValue {
-        let mut v = self.media_desc.take().unwrap_or(Vec::new());
-
-        v.push(media_desc);
-
-        self.media_desc = Some(v);
-
+        self.media_desc.get_or_insert_with(Vec::new).push(media_desc);
         self
     }
 
     /// Set `LinkValue`'s `title` parameter.
     pub fn set_title<T: Into<String>>(mut self, title: T) -> LinkValue {
         self.title = Some(title.into());
-
         self
     }
 
-    /// Set `LinkValue`'s `title*` parameter.
+    /// Set `LinkValue`'s `title*` parameter, which is encoded as UTF-8.
     pub fn set_title_star<T: Into<String>>(mut self, title_star: T) -> LinkValue {
         self.title_star = Some(title_star.into());
-
         self
     }
 
     /// Set `LinkValue`'s `type` parameter.
     pub fn set_media_type(mut self, media_type: Mime) -> LinkValue {
         self.media_type = Some(media_type);
+        self
+    }
 
+    /// Set `LinkValue`'s `as` parameter.
+    pub fn set_as_attribute(mut self, as_attribute: AsAttribute) -> LinkValue {
+        self.as_attribute = Some(as_attribute);
         self
     }
+
+    /// Set `LinkValue`'s `crossorigin` parameter.
+    pub fn set_crossorigin(mut self, crossorigin: CrossOrigin) -> LinkValue {
+        self.crossorigin = Some(crossorigin);
+        self
+    }
+
+    /// Set `LinkValue`'s `fetchpriority` parameter.
+    pub fn set_fetch_priority(mut self, fetch_priority: FetchPriority) -> LinkValue {
+        self.fetch_priority = Some(fetch_priority);
+        self
+    }
+
+    fn parse(s: &str) -> Option<LinkValue> {
+        let s = s.strip_prefix('<')?;
+        let end = s.find('>')?;
+        let params = token::trim_ows(&s[end + 1..]);
+        if !params.is_empty() && !params.starts_with(';') {
+            return None;
+        }
+
+        let mut link_value = LinkValue::new(s[..end].to_owned());
+        for param in split_quoted(params, b';') {
+            let (name, value) = match param.find('=') {
+                Some(i) => (token::trim_ows(&param[..i]), Some(token::trim_ows(&param[i + 1..]))),
+                None => (param, None),
+            };
+            if !token::is_token(name.as_bytes()) {
+                return None;
+            }
+            let value = match value {
+                Some(value) => Some(quoted::unquote(value)?),
+                None => None,
+            };
+            link_value.parse_param(name, value)?;
+        }
+
+        // Such as a space in the URI, which couldn't be encoded back.
+        if link_value.is_valid() {
+            Some(link_value)
+        } else {
+            None
+        }
+    }
+
+    fn parse_param(&mut self, name: &str, value: Option<Cow<str>>) -> Option<()> {
+        // Only `hreflang` and extensions can be repeated, the other
+        // parameters keep their first occurrence.
+        let name_is = |known: &str| name.eq_ignore_ascii_case(known);
+
+        if name_is("crossorigin") {
+            if self.crossorigin.is_none() {
+                self.crossorigin = Some(match value {
+                    Some(ref value) if value.eq_ignore_ascii_case("use-credentials") => CrossOrigin::UseCredentials,
+                    _ => CrossOrigin::Anonymous,
+                });
+            }
+            return Some(());
+        }
+
+        let value = match value {
+            Some(value) => value,
+            None if is_known_param(name) => return None,
+            None => {
+                self.params.push((name.to_owned(), None));
+                return Some(());
+            },
+        };
+
+        if name_is("rel") || name_is("rev") {
+            let types = value
+                .split(' ')
+                .filter(|t| !t.is_empty())
+                .map(RelationType::parse)
+                .collect::<Vec<_>>();
+            if types.is_empty() {
+                return None;
+            }
+            let slot = if name_is("rel") { &mut self.rel } else { &mut self.rev };
+            slot.get_or_insert(types);
+        } else if name_is("anchor") {
+            self.anchor.get_or_insert_with(|| value.into_owned());
+        } else if name_is("hreflang") {
+            if !token::is_token(value.as_bytes()) {
+                return None;
+            }
+            self.href_lang.get_or_insert_with(Vec::new).push(value.into_owned());
+        } else if name_is("media") {
+            let descs = value
+                .split(',')
+                .map(token::trim_ows)
+                .filter(|d| !d.is_empty())
+                .map(MediaDesc::parse)
+                .collect::<Vec<_>>();
+            if descs.is_empty() {
+                return None;
+            }
+            self.media_desc.get_or_insert(descs);
+        } else if name_is("title") {
+            self.title.get_or_insert_with(|| value.into_owned());
+        } else if name_is("title*") {
+            if self.title_star.is_none() {
+                self.title_star = ext_value::decode(&value);
+            }
+        } else if name_is("type") {
+            if self.media_type.is_none() {
+                self.media_type = Some(value.parse().ok()?);
+            }
+        } else if name_is("as") && AsAttribute::parse(&value).is_some() {
+            self.as_attribute = self.as_attribute.or_else(|| AsAttribute::parse(&value));
+        } else if name_is("fetchpriority") && FetchPriority::parse(&value).is_some() {
+            self.fetch_priority = self.fetch_priority.or_else(|| FetchPriority::parse(&value));
+        } else {
+            self.params.push((name.to_owned(), Some(value.into_owned())));
+        }
+        Some(())
+    }
+
+    fn is_valid(&self) -> bool {
+        fn all<T>(list: &Option<Vec<T>>, valid: impl Fn(&T) -> bool) -> bool {
+            list.iter().flatten().all(valid)
+        }
+
+        is_uri(&self.link)
+            && all(&self.rel, RelationType::is_valid)
+            && self.anchor.as_ref().is_none_or(|anchor| is_uri(anchor))
+            && all(&self.rev, RelationType::is_valid)
+            && all(&self.href_lang, |tag| token::is_token(tag.as_bytes()))
+            && all(&self.media_desc, MediaDesc::is_valid)
+            && self.title.as_ref().is_none_or(|title| is_text(title))
+            && self.params.iter().all(|(name, value)| {
+                token::is_token(name.as_bytes()) && value.as_ref().is_none_or(|value| is_text(value))
+            })
+    }
+}
+
+impl RelationType {
+    fn is_valid(&self) -> bool {
+        match *self {
+            RelationType::ExtRelType(ref uri) => !uri.is_empty() && is_uri(uri),
+            _ => true,
+        }
+    }
+}
+
+impl MediaDesc {
+    fn parse(s: &str) -> MediaDesc {
+        match s {
+            "screen" => MediaDesc::Screen,
+            "tty" => MediaDesc::Tty,
+            "tv" => MediaDesc::Tv,
+            "projection" => MediaDesc::Projection,
+            "handheld" => MediaDesc::Handheld,
+            "print" => MediaDesc::Print,
+            "braille" => MediaDesc::Braille,
+            "aural" => MediaDesc::Aural,
+            "all" => MediaDesc::All,
+            _ => MediaDesc::Extension(s.to_owned()),
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        match *self {
+            MediaDesc::Extension(ref s) => {
+                !s.is_empty() && is_text(s) && !s.contains(',') && token::trim_ows(s) == s
+            },
+            _ => true,
+        }
+    }
+}
+
+impl AsAttribute {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            AsAttribute::Audio => "audio",
+            AsAttribute::Document => "document",
+            AsAttribute::Embed => "embed",
+            AsAttribute::Fetch => "fetch",
+            AsAttribute::Font => "font",
+            AsAttribute::Image => "image",
+            AsAttribute::Object => "object",
+            AsAttribute::Script => "script",
+            AsAttribute::Style => "style",
+            AsAttribute::Track => "track",
+            AsAttribute::Video => "video",
+            AsAttribute::Worker => "worker",
+        }
+    }
+
+    fn parse(s: &str) -> Option<AsAttribute> {
+        [
+            AsAttribute::Audio,
+            AsAttribute::Document,
+            AsAttribute::Embed,
+            AsAttribute::Fetch,
+            AsAttribute::Font,
+            AsAttribute::Image,
+            AsAttribute::Object,
+            AsAttribute::Script,
+            AsAttribute::Style,
+            AsAttribute::Track,
+            AsAttribute::Video,
+            AsAttribute::Worker,
+        ]
+            .iter()
+            .cloned()
+            .find(|as_attribute| s.eq_ignore_ascii_case(as_attribute.as_str()))
+    }
+}
+
+impl FetchPriority {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            FetchPriority::High => "high",
+            FetchPriority::Low => "low",
+            FetchPriority::Auto => "auto",
+        }
+    }
+
+    fn parse(s: &str) -> Option<FetchPriority> {
+        [FetchPriority::High, FetchPriority::Low, FetchPriority::Auto]
+            .iter()
+            .cloned()
+            .find(|priority| s.eq_ignore_ascii_case(priority.as_str()))
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////
 // Trait implementations
 ////////////////////////////////////////////////////////////////////////////////
 
-impl Header for Link {
-    fn header_name() -> &'static str {
-        static NAME: &'static str = "Link";
-        NAME
-    }
-
-    fn parse_header(raw: &Raw) -> ::Result<Link> {
-        // If more that one `Link` headers are present in a request's
-        // headers they are combined in a single `Link` header containing
-        // all the `link-value`s present in each of those `Link` headers.
-        raw.iter()
-            .map(parsing::from_raw_str::<Link>)
-            .fold(None, |p, c| {
-                match (p, c) {
-                    (None, c) => Some(c),
-                    (e @ Some(Err(_)), _) => e,
-                    (Some(Ok(mut p)), Ok(c)) => {
-                        p.values.extend(c.values);
-
-                        Some(Ok(p))
-                    },
-                    _ => Some(Err(::Error::Header)),
+impl ::Header for Link {
+    fn name() -> &'static ::HeaderName {
+        &::http::header::LINK
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        // The link-values of every field line are combined into one
+        // `Link`.
+        let max = values.limits().max_list_items;
+        let mut link_values = Vec::new();
+        while let Some(value) = values.next() {
+            for element in split_elements(value.to_str().ok()?) {
+                if link_values.len() == max {
+                    values.set_too_large();
+                    return None;
                 }
-            })
-            .unwrap_or(Err(::Error::Header))
+                link_values.push(LinkValue::parse(element)?);
+            }
+        }
+
+        if link_values.is_empty() {
+            None
+        } else {
+            Some(Link::new(link_values))
+        }
     }
 
-    fn fmt_header(&self, f: &mut ::Formatter) -> fmt::Result {
-        f.fmt_line(self)
+    fn encode(&self, values: &mut ::ToValues) {
+        let joined = self
+            .values
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        values.append_fmt(joined);
     }
-}
 
-impl fmt::Display for Link {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        fmt_delimited(f, self.values.as_slice(), ", ", ("", ""))
+    fn validate(&self) -> Result<(), ::InvalidHeader> {
+        if self.values.iter().all(LinkValue::is_valid) {
+            Ok(())
+        } else {
+            Err(::InvalidHeader::new())
+        }
     }
 }
 
 impl fmt::Display for LinkValue {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        try!(write!(f, "<{}>", self.link));
+        write!(f, "<{}>", self.link)?;
 
         if let Some(ref rel) = self.rel {
-            try!(fmt_delimited(f, rel.as_slice(), " ", ("; rel=\"", "\"")));
+            write!(f, "; rel={}", quoted::quote(&joined(rel, " ")))?;
         }
         if let Some(ref anchor) = self.anchor {
-            try!(write!(f, "; anchor=\"{}\"", anchor));
+            write!(f, "; anchor={}", quoted::quote(anchor))?;
         }
         if let Some(ref rev) = self.rev {
-            try!(fmt_delimited(f, rev.as_slice(), " ", ("; rev=\"", "\"")));
+            write!(f, "; rev={}", quoted::quote(&joined(rev, " ")))?;
         }
         if let Some(ref href_lang) = self.href_lang {
             for tag in href_lang {
-                try!(write!(f, "; hreflang={}", tag));
+                write!(f, "; hreflang={}", tag)?;
             }
         }
         if let Some(ref media_desc) = self.media_desc {
-            try!(fmt_delimited(f, media_desc.as_slice(), ", ", ("; media=\"", "\"")));
+            write!(f, "; media={}", quoted::quote(&joined(media_desc, ", ")))?;
         }
         if let Some(ref title) = self.title {
-            try!(write!(f, "; title=\"{}\"", title));
+            write!(f, "; title={}", quoted::quote(title))?;
         }
         if let Some(ref title_star) = self.title_star {
-            try!(write!(f, "; title*={}", title_star));
+            write!(f, "; title*={}", ext_value::encode(title_star))?;
         }
         if let Some(ref media_type) = self.media_type {
-            try!(write!(f, "; type=\"{}\"", media_type));
+            write!(f, "; type={}", quoted::quote(media_type.as_ref()))?;
         }
-
-        Ok(())
-    }
-}
-
-impl FromStr for Link {
-    type Err = ::Error;
-
-    fn from_str(s: &str) -> ::Result<Link> {
-        // Create a split iterator with delimiters: `;`, `,`
-        let link_split = SplitAsciiUnquoted::new(s, ";,");
-
-        let mut link_values: Vec<LinkValue> = Vec::new();
-
-        // Loop over the splits parsing the Link header into
-        // a `Vec<LinkValue>`
-        for segment in link_split {
-            // Parse the `Target IRI`
-            // https://tools.ietf.org/html/rfc5988#section-5.1
-            if segment.trim().starts_with('<') {
-                link_values.push(
-                    match verify_and_trim(segment.trim(), (b'<', b'>')) {
-                        Err(_) => return Err(::Error::Header),
-                        Ok(s) => {
-                            LinkValue {
-                                link: s.to_owned().into(),
-                                rel: None,
-                                anchor: None,
-                                rev: None,
-                                href_lang: None,
-                                media_desc: None,
-                                title: None,
-                                title_star: None,
-                                media_type: None,
-                            }
-                        },
-                    }
-                );
-            } else {
-                // Parse the current link-value's parameters
-                let mut link_param_split = segment.splitn(2, '=');
-
-                let link_param_name = match link_param_split.next() {
-                    None => return Err(::Error::Header),
-                    Some(p) => p.trim(),
-                };
-
-                let link_header = match link_values.last_mut() {
-                    None => return Err(::Error::Header),
-                    Some(l) => l,
-                };
-
-                if "rel".eq_ignore_ascii_case(link_param_name) {
-                    // Parse relation type: `rel`.
-                    // https://tools.ietf.org/html/rfc5988#section-5.3
-                    if link_header.rel.is_none() {
-                        link_header.rel = match link_param_split.next() {
-                            None | Some("") => return Err(::Error::Header),
-                            Some(s) => {
-                                s.trim_matches(|c: char| c == '"' || c.is_whitespace())
-                                    .split(' ')
-                                    .map(|t| t.trim().parse())
-                                    .collect::<Result<Vec<RelationType>, _>>()
-                                    .or_else(|_| Err(::Error::Header))
-                                    .ok()
-                            },
-                        };
-                    }
-                } else if "anchor".eq_ignore_ascii_case(link_param_name) {
-                    // Parse the `Context IRI`.
-                    // https://tools.ietf.org/html/rfc5988#section-5.2
-                    link_header.anchor = match link_param_split.next() {
-                        None | Some("") => return Err(::Error::Header),
-                        Some(s) => match verify_and_trim(s.trim(), (b'"', b'"')) {
-                            Err(_) => return Err(::Error::Header),
-                            Ok(a) => Some(String::from(a)),
-                        },
-                    };
-                } else if "rev".eq_ignore_ascii_case(link_param_name) {
-                    // Parse relation type: `rev`.
-                    // https://tools.ietf.org/html/rfc5988#section-5.3
-                    if link_header.rev.is_none() {
-                        link_header.rev = match link_param_split.next() {
-                            None | Some("") => return Err(::Error::Header),
-                            Some(s) => {
-                                s.trim_matches(|c: char| c == '"' || c.is_whitespace())
-                                    .split(' ')
-                                    .map(|t| t.trim().parse())
-                                    .collect::<Result<Vec<RelationType>, _>>()
-                                    .or_else(|_| Err(::Error::Header))
-                                    .ok()
-                            },
-                        }
-                    }
-                } else if "hreflang".eq_ignore_ascii_case(link_param_name) {
-                    // Parse target attribute: `hreflang`.
-                    // https://tools.ietf.org/html/rfc5988#section-5.4
-                    let mut v = link_header.href_lang.take().unwrap_or(Vec::new());
-
-                    v.push(
-                        match link_param_split.next() {
-                            None | Some("") => return Err(::Error::Header),
-                            Some(s) => match s.trim().parse() {
-                                Err(_) => return Err(::Error::Header),
-                                Ok(t) => t,
-                            },
-                        }
-                    );
-
-                    link_header.href_lang = Some(v);
-                } else if "media".eq_ignore_ascii_case(link_param_name) {
-                    // Parse target attribute: `media`.
-                    // https://tools.ietf.org/html/rfc5988#section-5.4
-                    if link_header.media_desc.is_none() {
-                        link_header.media_desc = match link_param_split.next() {
-                            None | Some("") => return Err(::Error::Header),
-                            Some(s) => {
-                                s.trim_matches(|c: char| c == '"' || c.is_whitespace())
-                                    .split(',')
-                                    .map(|t| t.trim().parse())
-                                    .collect::<Result<Vec<MediaDesc>, _>>()
-                                    .or_else(|_| Err(::Error::Header))
-                                    .ok()
-                            },
-                        };
-                    }
-                } else if "title".eq_ignore_ascii_case(link_param_name) {
-                    // Parse target attribute: `title`.
-                    // https://tools.ietf.org/html/rfc5988#section-5.4
-                    if link_header.title.is_none() {
-                        link_header.title = match link_param_split.next() {
-                            None | Some("") => return Err(::Error::Header),
-                            Some(s) => match verify_and_trim(s.trim(), (b'"', b'"')) {
-                                Err(_) => return Err(::Error::Header),
-                                Ok(t) => Some(String::from(t)),
-                            },
-                        };
-                    }
-                } else if "title*".eq_ignore_ascii_case(link_param_name) {
-                    // Parse target attribute: `title*`.
-                    // https://tools.ietf.org/html/rfc5988#section-5.4
-                    //
-                    // Definition of `ext-value`:
-                    //       https://tools.ietf.org/html/rfc5987#section-3.2.1
-                    if link_header.title_star.is_none() {
-                        link_header.title_star = match link_param_split.next() {
-                            None | Some("") => return Err(::Error::Header),
-                            Some(s) => Some(String::from(s.trim())),
-                        };
-                    }
-                } else if "type".eq_ignore_ascii_case(link_param_name) {
-                    // Parse target attribute: `type`.
-                    // https://tools.ietf.org/html/rfc5988#section-5.4
-                    if link_header.media_type.is_none() {
-                        link_header.media_type = match link_param_split.next() {
-                            None | Some("") => return Err(::Error::Header),
-                            Some(s) => match verify_and_trim(s.trim(), (b'"', b'"')) {
-                                Err(_) => return Err(::Error::Header),
-                                Ok(t) => match t.parse() {
-                                    Err(_) => return Err(::Error::Header),
-                                    Ok(m) => Some(m),
-                                },
-                            },
-
-                        };
-                    }
-                } else {
-                    return Err(::Error::Header);
-                }
+        if let Some(as_attribute) = self.as_attribute {
+            write!(f, "; as={}", as_attribute)?;
+        }
+        match self.crossorigin {
+            Some(CrossOrigin::Anonymous) => f.write_str("; crossorigin")?,
+            Some(CrossOrigin::UseCredentials) => f.write_str("; crossorigin=use-credentials")?,
+            None => (),
+        }
+        if let Some(fetch_priority) = self.fetch_priority {
+            write!(f, "; fetchpriority={}", fetch_priority)?;
+        }
+        for (name, value) in &self.params {
+            match *value {
+                Some(ref value) => write!(f, "; {}={}", name, quoted::quote(value))?,
+                None => write!(f, "; {}", name)?,
             }
         }
 
-        Ok(Link::new(link_values))
+        Ok(())
     }
 }
 
@@ -642,164 +908,25 @@ impl fmt::Display for MediaDesc {
             MediaDesc::Aural => write!(f, "aural"),
             MediaDesc::All => write!(f, "all"),
             MediaDesc::Extension(ref other) => write!(f, "{}", other),
-         }
+        }
     }
 }
 
-impl FromStr for MediaDesc {
-    type Err = ::Error;
-
-    fn from_str(s: &str) -> ::Result<MediaDesc> {
-        match s {
-            "screen" => Ok(MediaDesc::Screen),
-            "tty" => Ok(MediaDesc::Tty),
-            "tv" => Ok(MediaDesc::Tv),
-            "projection" => Ok(MediaDesc::Projection),
-            "handheld" => Ok(MediaDesc::Handheld),
-            "print" => Ok(MediaDesc::Print),
-            "braille" => Ok(MediaDesc::Braille),
-            "aural" => Ok(MediaDesc::Aural),
-            "all" => Ok(MediaDesc::All),
-             _ => Ok(MediaDesc::Extension(String::from(s))),
-        }
+impl fmt::Display for RelationType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
     }
 }
 
-impl fmt::Display for RelationType {
+impl fmt::Display for AsAttribute {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match *self {
-            RelationType::Alternate => write!(f, "alternate"),
-            RelationType::Appendix => write!(f, "appendix"),
-            RelationType::Bookmark => write!(f, "bookmark"),
-            RelationType::Chapter => write!(f, "chapter"),
-            RelationType::Contents => write!(f, "contents"),
-            RelationType::Copyright => write!(f, "copyright"),
-            RelationType::Current => write!(f, "current"),
-            RelationType::DescribedBy => write!(f, "describedby"),
-            RelationType::Edit => write!(f, "edit"),
-            RelationType::EditMedia => write!(f, "edit-media"),
-            RelationType::Enclosure => write!(f, "enclosure"),
-            RelationType::First => write!(f, "first"),
-            RelationType::Glossary => write!(f, "glossary"),
-            RelationType::Help => write!(f, "help"),
-            RelationType::Hub => write!(f, "hub"),
-            RelationType::Index => write!(f, "index"),
-            RelationType::Last => write!(f, "last"),
-            RelationType::LatestVersion => write!(f, "latest-version"),
-            RelationType::License => write!(f, "license"),
-            RelationType::Next => write!(f, "next"),
-            RelationType::NextArchive => write!(f, "next-archive"),
-            RelationType::Payment => write!(f, "payment"),
-            RelationType::Prev => write!(f, "prev"),
-            RelationType::PredecessorVersion => write!(f, "predecessor-version"),
-            RelationType::Previous => write!(f, "previous"),
-            RelationType::PrevArchive => write!(f, "prev-archive"),
-            RelationType::Related => write!(f, "related"),
-            RelationType::Replies => write!(f, "replies"),
-            RelationType::Section => write!(f, "section"),
-            RelationType::RelationTypeSelf => write!(f, "self"),
-            RelationType::Service => write!(f, "service"),
-            RelationType::Start => write!(f, "start"),
-            RelationType::Stylesheet => write!(f, "stylesheet"),
-            RelationType::Subsection => write!(f, "subsection"),
-            RelationType::SuccessorVersion => write!(f, "successor-version"),
-            RelationType::Up => write!(f, "up"),
-            RelationType::VersionHistory => write!(f, "version-history"),
-            RelationType::Via => write!(f, "via"),
-            RelationType::WorkingCopy => write!(f, "working-copy"),
-            RelationType::WorkingCopyOf => write!(f, "working-copy-of"),
-            RelationType::ExtRelType(ref uri) => write!(f, "{}", uri),
-         }
+        f.write_str(self.as_str())
     }
 }
 
-impl FromStr for RelationType {
-    type Err = ::Error;
-
-    fn from_str(s: &str) -> ::Result<RelationType> {
-        if "alternate".eq_ignore_ascii_case(s) {
-            Ok(RelationType::Alternate)
-        } else if "appendix".eq_ignore_ascii_case(s) {
-            Ok(RelationType::Appendix)
-        } else if "bookmark".eq_ignore_ascii_case(s) {
-            Ok(RelationType::Bookmark)
-        } else if "chapter".eq_ignore_ascii_case(s) {
-            Ok(RelationType::Chapter)
-        } else if "contents".eq_ignore_ascii_case(s) {
-            Ok(RelationType::Contents)
-        } else if "copyright".eq_ignore_ascii_case(s) {
-            Ok(RelationType::Copyright)
-        } else if "current".eq_ignore_ascii_case(s) {
-            Ok(RelationType::Current)
-        } else if "describedby".eq_ignore_ascii_case(s) {
-            Ok(RelationType::DescribedBy)
-        } else if "edit".eq_ignore_ascii_case(s) {
-            Ok(RelationType::Edit)
-        } else if "edit-media".eq_ignore_ascii_case(s) {
-            Ok(RelationType::EditMedia)
-        } else if "enclosure".eq_ignore_ascii_case(s) {
-            Ok(RelationType::Enclosure)
-        } else if "first".eq_ignore_ascii_case(s) {
-            Ok(RelationType::First)
-        } else if "glossary".eq_ignore_ascii_case(s) {
-            Ok(RelationType::Glossary)
-        } else if "help".eq_ignore_ascii_case(s) {
-            Ok(RelationType::Help)
-        } else if "hub".eq_ignore_ascii_case(s) {
-            Ok(RelationType::Hub)
-        } else if "index".eq_ignore_ascii_case(s) {
-            Ok(RelationType::Index)
-        } else if "last".eq_ignore_ascii_case(s) {
-            Ok(RelationType::Last)
-        } else if "latest-version".eq_ignore_ascii_case(s) {
-            Ok(RelationType::LatestVersion)
-        } else if "license".eq_ignore_ascii_case(s) {
-            Ok(RelationType::License)
-        } else if "next".eq_ignore_ascii_case(s) {
-            Ok(RelationType::Next)
-        } else if "next-archive".eq_ignore_ascii_case(s) {
-            Ok(RelationType::NextArchive)
-        } else if "payment".eq_ignore_ascii_case(s) {
-            Ok(RelationType::Payment)
-        } else if "prev".eq_ignore_ascii_case(s) {
-            Ok(RelationType::Prev)
-        } else if "predecessor-version".eq_ignore_ascii_case(s) {
-            Ok(RelationType::PredecessorVersion)
-        } else if "previous".eq_ignore_ascii_case(s) {
-            Ok(RelationType::Previous)
-        } else if "prev-archive".eq_ignore_ascii_case(s) {
-            Ok(RelationType::PrevArchive)
-        } else if "related".eq_ignore_ascii_case(s) {
-            Ok(RelationType::Related)
-        } else if "replies".eq_ignore_ascii_case(s) {
-            Ok(RelationType::Replies)
-        } else if "section".eq_ignore_ascii_case(s) {
-            Ok(RelationType::Section)
-        } else if "self".eq_ignore_ascii_case(s) {
-            Ok(RelationType::RelationTypeSelf)
-        } else if "service".eq_ignore_ascii_case(s) {
-            Ok(RelationType::Service)
-        } else if "start".eq_ignore_ascii_case(s) {
-            Ok(RelationType::Start)
-        } else if "stylesheet".eq_ignore_ascii_case(s) {
-            Ok(RelationType::Stylesheet)
-        } else if "subsection".eq_ignore_ascii_case(s) {
-            Ok(RelationType::Subsection)
-        } else if "successor-version".eq_ignore_ascii_case(s) {
-            Ok(RelationType::SuccessorVersion)
-        } else if "up".eq_ignore_ascii_case(s) {
-            Ok(RelationType::Up)
-        } else if "version-history".eq_ignore_ascii_case(s) {
-            Ok(RelationType::VersionHistory)
-        } else if "via".eq_ignore_ascii_case(s) {
-            Ok(RelationType::Via)
-        } else if "working-copy".eq_ignore_ascii_case(s) {
-            Ok(RelationType::WorkingCopy)
-        } else if "working-copy-of".eq_ignore_ascii_case(s) {
-            Ok(RelationType::WorkingCopyOf)
-        } else {
-            Ok(RelationType::ExtRelType(String::from(s)))
-        }
+impl fmt::Display for FetchPriority {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
     }
 }
 
@@ -807,82 +934,26 @@ impl FromStr for RelationType {
 // Utilities
 ////////////////////////////////////////////////////////////////////////////////
 
-struct SplitAsciiUnquoted<'a> {
-    src: &'a str,
-    pos: usize,
-    del: &'a str
-}
-
-impl<'a> SplitAsciiUnquoted<'a> {
-    fn new(s: &'a str, d: &'a str) -> SplitAsciiUnquoted<'a> {
-        SplitAsciiUnquoted{
-            src: s,
-            pos: 0,
-            del: d,
-        }
-    }
+// The parameters with a typed accessor, which can't be bare.
+fn is_known_param(name: &str) -> bool {
+    ["rel", "anchor", "rev", "hreflang", "media", "title", "title*", "type", "as", "fetchpriority"]
+        .iter()
+        .any(|known| name.eq_ignore_ascii_case(known))
 }
 
-impl<'a> Iterator for SplitAsciiUnquoted<'a> {
-    type Item = &'a str;
-
-    fn next(&mut self) -> Option<&'a str> {
-        if self.pos < self.src.len() {
-            let prev_pos = self.pos;
-            let mut pos = self.pos;
-
-            let mut in_quotes = false;
-
-            for c in self.src[prev_pos..].as_bytes().iter() {
-                in_quotes ^= *c == b'"';
-
-                // Ignore `c` if we're `in_quotes`.
-                if !in_quotes && self.del.as_bytes().contains(c) {
-                    break;
-                }
-
-                pos += 1;
-            }
-
-            self.pos = pos + 1;
-
-            Some(&self.src[prev_pos..pos])
-        } else {
-            None
-        }
-    }
+fn joined<T: fmt::Display>(list: &[T], sep: &str) -> String {
+    list.iter().map(ToString::to_string).collect::<Vec<_>>().join(sep)
 }
 
-fn fmt_delimited<T: fmt::Display>(f: &mut fmt::Formatter, p: &[T], d: &str, b: (&str, &str)) -> fmt::Result {
-    if p.len() != 0 {
-        // Write a starting string `b.0` before the first element
-        try!(write!(f, "{}{}", b.0, p[0]));
-
-        for i in &p[1..] {
-            // Write the next element preceded by the delimiter `d`
-            try!(write!(f, "{}{}", d, i));
-        }
-
-        // Write a ending string `b.1` before the first element
-        try!(write!(f, "{}", b.1));
-    }
-
-    Ok(())
+/// Printable ASCII, other than the characters that would end a
+/// `<URI-Reference>` or split a relation type list.
+fn is_uri(s: &str) -> bool {
+    s.bytes().all(|b| b.is_ascii_graphic() && b != b'<' && b != b'>' && b != b'"')
 }
 
-fn verify_and_trim(s: &str, b: (u8, u8)) -> ::Result<&str> {
-    let length = s.len();
-    let byte_array = s.as_bytes();
-
-    // Verify that `s` starts with `b.0` and ends with `b.1` and return
-    // the contained substring after trimming whitespace.
-    if length > 1 && b.0 == byte_array[0] && b.1 == byte_array[length - 1] {
-        Ok(s.trim_matches(
-            |c: char| c == b.0 as char || c == b.1 as char || c.is_whitespace())
-        )
-    } else {
-        Err(::Error::Header)
-    }
+/// Any character but the control characters, except for HTAB.
+fn is_text(s: &str) -> bool {
+    s.chars().all(|c| c == '\t' || !c.is_control())
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -891,19 +962,11 @@ fn verify_and_trim(s: &str, b: (u8, u8)) -> ::Result<&str> {
 
 #[cfg(test)]
 mod tests {
-    use std::fmt;
-    use std::fmt::Write;
-
-    use super::{Link, LinkValue, MediaDesc, RelationType, SplitAsciiUnquoted};
-    use super::{fmt_delimited, verify_and_trim};
-
-    use Header;
-
-    // use proto::ServerTransaction;
-    use bytes::BytesMut;
-
     use mime;
 
+    use super::{AsAttribute, CrossOrigin, FetchPriority, Link, LinkValue, MediaDesc, RelationType};
+    use super::super::{test_decode, test_encode};
+
     #[test]
     fn test_link() {
         let link_value = LinkValue::new("http://example.com/TheBook/chapter2")
@@ -911,34 +974,32 @@ mod tests {
             .push_rev(RelationType::Next)
             .set_title("previous chapter");
 
-        let link_header = b"<http://example.com/TheBook/chapter2>; \
-            rel=\"previous\"; rev=next; title=\"previous chapter\"";
-
-        let expected_link = Link::new(vec![link_value]);
-
-        let link = Header::parse_header(&vec![link_header.to_vec()].into());
-        assert_eq!(link.ok(), Some(expected_link));
+        let link = test_decode::<Link>(&["<http://example.com/TheBook/chapter2>; \
+            rel=\"previous\"; rev=next; title=\"previous chapter\""]);
+        assert_eq!(link, Some(Link::new(vec![link_value])));
     }
 
     #[test]
     fn test_link_multiple_values() {
         let first_link = LinkValue::new("/TheBook/chapter2")
             .push_rel(RelationType::Previous)
-            .set_title_star("UTF-8'de'letztes%20Kapitel");
+            .set_title_star("letztes Kapitel");
 
         let second_link = LinkValue::new("/TheBook/chapter4")
             .push_rel(RelationType::Next)
-            .set_title_star("UTF-8'de'n%c3%a4chstes%20Kapitel");
+            .set_title_star("nächstes Kapitel");
 
-        let link_header = b"</TheBook/chapter2>; \
-            rel=\"previous\"; title*=UTF-8'de'letztes%20Kapitel, \
-            </TheBook/chapter4>; \
-            rel=\"next\"; title*=UTF-8'de'n%c3%a4chstes%20Kapitel";
-
-        let expected_link = Link::new(vec![first_link, second_link]);
+        let third_link = LinkValue::new("http://example.com/TheBook/chapter2")
+            .push_rel(RelationType::Previous)
+            .push_rev(RelationType::Next)
+            .set_title("previous chapter");
 
-        let link = Header::parse_header(&vec![link_header.to_vec()].into());
-        assert_eq!(link.ok(), Some(expected_link));
+        let link = test_decode::<Link>(&[
+            "</TheBook/chapter2>; rel=\"previous\"; title*=UTF-8'de'letztes%20Kapitel, \
+             </TheBook/chapter4>; rel=\"next\"; title*=UTF-8'de'n%c3%a4chstes%20Kapitel",
+            "<http://example.com/TheBook/chapter2>; rel=\"previous\"; rev=next; title=\"previous chapter\"",
+        ]);
+        assert_eq!(link, Some(Link::new(vec![first_link, second_link, third_link])));
     }
 
     #[test]
@@ -947,159 +1008,170 @@ mod tests {
             .push_rel(RelationType::Previous)
             .set_anchor("../anchor/example/")
             .push_rev(RelationType::Next)
-            .push_href_lang("de".parse().unwrap())
+            .push_href_lang("de")
             .push_media_desc(MediaDesc::Screen)
-            .set_title("previous chapter")
-            .set_title_star("title* unparsed")
+            .push_media_desc(MediaDesc::Tty)
+            .set_title("previous, chapter")
+            .set_title_star("title*")
             .set_media_type(mime::TEXT_PLAIN);
 
-        let link_header = b"<http://example.com/TheBook/chapter2>; \
+        let link = test_decode::<Link>(&["<http://example.com/TheBook/chapter2>; \
             rel=\"previous\"; anchor=\"../anchor/example/\"; \
-            rev=\"next\"; hreflang=de; media=\"screen\"; \
-            title=\"previous chapter\"; title*=title* unparsed; \
-            type=\"text/plain\"";
-
-        let expected_link = Link::new(vec![link_value]);
-
-        let link = Header::parse_header(&vec![link_header.to_vec()].into());
-        assert_eq!(link.ok(), Some(expected_link));
+            rev=\"next\"; hreflang=de; media=\"screen, tty\"; \
+            title=\"previous, chapter\"; title*=UTF-8''title%2A; \
+            type=\"text/plain\""]);
+        assert_eq!(link, Some(Link::new(vec![link_value])));
     }
 
-    // TODO
-    // #[test]
-    // fn test_link_multiple_link_headers() {
-    //     let first_link = LinkValue::new("/TheBook/chapter2")
-    //         .push_rel(RelationType::Previous)
-    //         .set_title_star("UTF-8'de'letztes%20Kapitel");
-
-    //     let second_link = LinkValue::new("/TheBook/chapter4")
-    //         .push_rel(RelationType::Next)
-    //         .set_title_star("UTF-8'de'n%c3%a4chstes%20Kapitel");
-
-    //     let third_link = LinkValue::new("http://example.com/TheBook/chapter2")
-    //         .push_rel(RelationType::Previous)
-    //         .push_rev(RelationType::Next)
-    //         .set_title("previous chapter");
-
-    //     let expected_link = Link::new(vec![first_link, second_link, third_link]);
-
-    //     let mut raw = BytesMut::from(b"GET /super_short_uri/and_whatever HTTP/1.1\r\nHost: \
-    //                               hyper.rs\r\nAccept: a lot of things\r\nAccept-Charset: \
-    //                               utf8\r\nAccept-Encoding: *\r\nLink: </TheBook/chapter2>; \
-    //                               rel=\"previous\"; title*=UTF-8'de'letztes%20Kapitel, \
-    //                               </TheBook/chapter4>; rel=\"next\"; title*=\
-    //                               UTF-8'de'n%c3%a4chstes%20Kapitel\r\n\
-    //                               Access-Control-Allow-Credentials: None\r\nLink: \
-    //                               <http://example.com/TheBook/chapter2>; \
-    //                               rel=\"previous\"; rev=next; title=\"previous chapter\"\
-    //                               \r\n\r\n".to_vec());
-
-    //     let (mut res, _) = ServerTransaction::parse(&mut raw).unwrap().unwrap();
-
-    //     let link = res.headers.remove::<Link>().unwrap();
-
-    //     assert_eq!(link, expected_link);
-    // }
+    #[test]
+    fn test_link_first_occurrence() {
+        let link = test_decode::<Link>(&["</a>; rel=next; REL=prev; title=a; title=b; hreflang=de; hreflang=fr"]).unwrap();
+        let value = &link.values()[0];
+        assert_eq!(value.rel(), Some(&[RelationType::Next][..]));
+        assert_eq!(value.title(), Some("a"));
+        assert_eq!(value.href_lang(), Some(&["de".to_owned(), "fr".to_owned()][..]));
+    }
 
     #[test]
-    fn test_link_display() {
+    fn test_link_encode() {
         let link_value = LinkValue::new("http://example.com/TheBook/chapter2")
             .push_rel(RelationType::Previous)
+            .push_rel(RelationType::ExtRelType("http://example.net/foo".into()))
             .set_anchor("/anchor/example/")
-            .push_rev(RelationType::Next)
-            .push_href_lang("de".parse().unwrap())
+            .push_href_lang("de")
             .push_media_desc(MediaDesc::Screen)
-            .set_title("previous chapter")
-            .set_title_star("title* unparsed")
+            .set_title("previous \"chapter\"")
+            .set_title_star("€ rates")
             .set_media_type(mime::TEXT_PLAIN);
 
-        let link = Link::new(vec![link_value]);
-
-        let mut link_header = String::new();
-        write!(&mut link_header, "{}", link).unwrap();
-
-        let expected_link_header = "<http://example.com/TheBook/chapter2>; \
-            rel=\"previous\"; anchor=\"/anchor/example/\"; \
-            rev=\"next\"; hreflang=de; media=\"screen\"; \
-            title=\"previous chapter\"; title*=title* unparsed; \
-            type=\"text/plain\"";
+        let headers = test_encode(Link::new(vec![link_value.clone(), LinkValue::new("/")]));
+        assert_eq!(
+            headers["link"],
+            "<http://example.com/TheBook/chapter2>; \
+             rel=\"previous http://example.net/foo\"; anchor=\"/anchor/example/\"; \
+             hreflang=de; media=screen; title=\"previous \\\"chapter\\\"\"; \
+             title*=UTF-8''%E2%82%AC%20rates; type=\"text/plain\", </>",
+        );
+
+        let link = test_decode::<Link>(&[headers["link"].to_str().unwrap()]).unwrap();
+        assert_eq!(link.values(), &[link_value, LinkValue::new("/")][..]);
+    }
 
-        assert_eq!(link_header, expected_link_header);
+    #[test]
+    fn test_link_uri_with_separators() {
+        let link = test_decode::<Link>(&["<https://img.example.com/w_100,h_100;q=1/a.png>; rel=preload; as=image"]).unwrap();
+        assert_eq!(link.values().len(), 1);
+        assert_eq!(link.values()[0].link(), "https://img.example.com/w_100,h_100;q=1/a.png");
+        assert_eq!(link.values()[0].as_attribute(), Some(AsAttribute::Image));
     }
 
     #[test]
     fn test_link_parsing_errors() {
-        let link_a  = b"http://example.com/TheBook/chapter2; \
-            rel=\"previous\"; rev=next; title=\"previous chapter\"";
-
-        let mut err: Result<Link, _> = Header::parse_header(&vec![link_a.to_vec()].into());
-        assert_eq!(err.is_err(), true);
-
-        let link_b = b"<http://example.com/TheBook/chapter2>; \
-            =\"previous\"; rev=next; title=\"previous chapter\"";
-
-        err = Header::parse_header(&vec![link_b.to_vec()].into());
-        assert_eq!(err.is_err(), true);
-
-        let link_c = b"<http://example.com/TheBook/chapter2>; \
-            rel=; rev=next; title=\"previous chapter\"";
-
-        err = Header::parse_header(&vec![link_c.to_vec()].into());
-        assert_eq!(err.is_err(), true);
-
-        let link_d = b"<http://example.com/TheBook/chapter2>; \
-            rel=\"previous\"; rev=next; title=";
-
-        err = Header::parse_header(&vec![link_d.to_vec()].into());
-        assert_eq!(err.is_err(), true);
-
-        let link_e = b"<http://example.com/TheBook/chapter2>; \
-            rel=\"previous\"; rev=next; attr=unknown";
-
-        err = Header::parse_header(&vec![link_e.to_vec()].into());
-        assert_eq!(err.is_err(), true);
-     }
+        for &value in &[
+            "",
+            " , ",
+            "http://example.com/TheBook/chapter2; rel=\"previous\"",
+            "<http://example.com/TheBook/chapter2; rel=\"previous\"",
+            "<http://example.com/TheBook/chapter2> rel=\"previous\"",
+            "<http://example.com/TheBook/chapter2>; =\"previous\"; rev=next",
+            "<http://example.com/TheBook/chapter2>; rel=; rev=next",
+            "<http://example.com/TheBook/chapter2>; rel=\"\"",
+            "<http://example.com/TheBook/chapter2>; rel=\"previous\"; title",
+            "<http://example.com/TheBook/chapter2>; title=\"unterminated",
+            "<http://example.com/TheBook/chapter2>; type=nope",
+            "</a>, b",
+        ] {
+            assert_eq!(test_decode::<Link>(&[value]), None, "{:?}", value);
+        }
+    }
 
     #[test]
-    fn test_link_split_ascii_unquoted_iterator() {
-        let string = "some, text; \"and, more; in quotes\", or not";
-        let mut string_split = SplitAsciiUnquoted::new(string, ";,");
-
-        assert_eq!(Some("some"), string_split.next());
-        assert_eq!(Some(" text"), string_split.next());
-        assert_eq!(Some(" \"and, more; in quotes\""), string_split.next());
-        assert_eq!(Some(" or not"), string_split.next());
-        assert_eq!(None, string_split.next());
+    fn test_link_params() {
+        let link = test_decode::<Link>(&["</app.js>; rel=preload; as=script; nopush; Integrity=\"sha384-abc\"; as=style"]).unwrap();
+        let value = &link.values()[0];
+        assert_eq!(value.as_attribute(), Some(AsAttribute::Script));
+        assert_eq!(value.param("nopush"), Some(""));
+        assert_eq!(value.param("integrity"), Some("sha384-abc"));
+        assert_eq!(value.param("as"), None);
+        assert_eq!(
+            test_encode(link.clone())["link"],
+            "</app.js>; rel=preload; as=script; nopush; Integrity=sha384-abc",
+        );
+
+        // Unknown values stay as params.
+        let link = test_decode::<Link>(&["</x>; rel=preload; as=sprite; fetchpriority=urgent"]).unwrap();
+        let value = &link.values()[0];
+        assert_eq!(value.as_attribute(), None);
+        assert_eq!(value.fetch_priority(), None);
+        assert_eq!(value.param("as"), Some("sprite"));
+        assert_eq!(value.param("fetchpriority"), Some("urgent"));
     }
 
     #[test]
-    fn test_link_fmt_delimited() {
-        struct TestFormatterStruct<'a> { v: Vec<&'a str> };
-
-        impl<'a> fmt::Display for TestFormatterStruct<'a> {
-            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-                fmt_delimited(f, self.v.as_slice(), ", ", (">>", "<<"))
-            }
-        }
-
-        let test_formatter = TestFormatterStruct { v: vec!["first", "second"] };
-
-        let mut string = String::new();
-        write!(&mut string, "{}", test_formatter).unwrap();
-
-        let expected_string = ">>first, second<<";
-
-        assert_eq!(string, expected_string);
+    fn test_link_preloads() {
+        let mut link = Link::new(Vec::new());
+        link.extend_preloads(vec![("/style.css", AsAttribute::Style), ("/data.json", AsAttribute::Fetch)]);
+        link.push_value(LinkValue::preload("/hero.avif", AsAttribute::Image).set_fetch_priority(FetchPriority::High));
+        link.push_value(LinkValue::modulepreload("/app.mjs").set_crossorigin(CrossOrigin::UseCredentials));
+        link.push_value(LinkValue::preconnect("https://fonts.example.com").set_crossorigin(CrossOrigin::Anonymous));
+
+        let headers = test_encode(link.clone());
+        assert_eq!(
+            headers["link"],
+            "</style.css>; rel=preload; as=style, \
+             </data.json>; rel=preload; as=fetch; crossorigin, \
+             </hero.avif>; rel=preload; as=image; fetchpriority=high, \
+             </app.mjs>; rel=modulepreload; crossorigin=use-credentials, \
+             <https://fonts.example.com>; rel=preconnect; crossorigin",
+        );
+        assert_eq!(test_decode::<Link>(&[headers["link"].to_str().unwrap()]), Some(link));
+
+        let link = test_decode::<Link>(&["</f.woff2>; rel=preload; as=FONT; crossorigin=\"\""]).unwrap();
+        assert_eq!(link.values()[0], LinkValue::preload("/f.woff2", AsAttribute::Font));
+        assert_eq!(link.values()[0].crossorigin(), Some(CrossOrigin::Anonymous));
     }
 
     #[test]
-    fn test_link_verify_and_trim() {
-        let string = verify_and_trim(">  some string   <", (b'>', b'<'));
-        assert_eq!(string.ok(), Some("some string"));
+    fn test_link_encode_styles() {
+        use list::{Compact, MultiLine};
+        use {HeaderMap, HeaderMapExt};
+
+        let mut link = Link::new(Vec::new());
+        link.extend_preloads(vec![("/a,b.css", AsAttribute::Style), ("/c.js", AsAttribute::Script)]);
+
+        let mut map = HeaderMap::new();
+        map.typed_insert(MultiLine(link.clone()));
+        assert_eq!(
+            map.get_all("link").iter().collect::<Vec<_>>(),
+            ["</a,b.css>; rel=preload; as=style", "</c.js>; rel=preload; as=script"],
+        );
+        assert_eq!(map.typed_get(), Some(link.clone()));
+
+        let mut map = HeaderMap::new();
+        map.typed_insert(Compact(link.clone()));
+        assert_eq!(
+            map.get_all("link").iter().collect::<Vec<_>>(),
+            ["</a,b.css>; rel=preload; as=style, </c.js>; rel=preload; as=script"],
+        );
+        assert_eq!(map.typed_get(), Some(link));
+    }
 
-        let err = verify_and_trim(" >  some string   <", (b'>', b'<'));
-        assert_eq!(err.is_err(), true);
+    #[test]
+    fn test_link_rejects_injection() {
+        use {HeaderMap, HeaderMapExt};
+
+        let mut map = HeaderMap::new();
+        for value in &[
+            LinkValue::new("/a>; rel=preload"),
+            LinkValue::new("/a b"),
+            LinkValue::new("/a\r\nSet-Cookie: a=b"),
+            LinkValue::new("/a").set_title("a\nb"),
+            LinkValue::new("/a").push_rel(RelationType::ExtRelType("a b".into())),
+            LinkValue::new("/a").push_href_lang("de fr"),
+            LinkValue::new("/a").push_media_desc(MediaDesc::Extension("a, b".into())),
+        ] {
+            assert!(map.typed_try_insert(Link::new(vec![value.clone()])).is_err(), "{:?}", value);
+        }
+        assert!(map.is_empty());
     }
 }
-
-bench_header!(bench_link, Link, { vec![b"<http://example.com/TheBook/chapter2>; rel=\"previous\"; rev=next; title=\"previous chapter\"; type=\"text/html\"; media=\"screen, tty\"".to_vec()] });
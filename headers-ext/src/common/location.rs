@@ -1,4 +1,7 @@
-use ::HeaderValue;
+use http::Uri;
+
+use util::HeaderString;
+use util::uri_reference::{parse_reference, UriReference};
 
 /// `Location` header, defined in
 /// [RFC7231](http://tools.ietf.org/html/rfc7231#section-7.1.2)
@@ -20,21 +23,51 @@ use ::HeaderValue;
 ///
 /// # Examples
 ///
-//TODO: make this a `Uri`?
+/// ```
+/// # extern crate headers_ext as headers;
+/// # extern crate http;
+/// use headers::{HeaderMap, HeaderMapExt, Location};
+///
+/// let mut map = HeaderMap::new();
+/// map.insert("location", "../People.html#tim".parse().unwrap());
+///
+/// let location = map.typed_get::<Location>().unwrap();
+/// let request = "https://example.com/a/b".parse::<http::Uri>().unwrap();
+/// assert_eq!(location.resolve(&request).unwrap(), "https://example.com/People.html");
+/// ```
 #[derive(Clone, Debug, PartialEq, Header)]
-pub struct Location(HeaderValue);
+pub struct Location(HeaderString);
+
+impl Location {
+    /// Resolve the location against `base`, usually the URI of the request
+    /// it answers, as a client following a redirect would.
+    ///
+    /// Returns `None` if the location isn't a valid URI reference.
+    pub fn resolve(&self, base: &Uri) -> Option<Uri> {
+        self.reference()?.resolve(base)
+    }
+
+    fn reference(&self) -> Option<UriReference> {
+        parse_reference(&self.0)
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use super::super::test_decode;
 
+    fn resolve(location: &str, base: &str) -> Option<String> {
+        let loc = test_decode::<Location>(&[location]).unwrap();
+        loc.resolve(&base.parse().unwrap()).map(|uri| uri.to_string())
+    }
+
     #[test]
     fn absolute_uri() {
         let s = "http://www.example.net/index.html";
         let loc = test_decode::<Location>(&[s]).unwrap();
 
-        assert_eq!(loc, Location(HeaderValue::from_static(s)));
+        assert_eq!(loc, Location(HeaderString::from_static(s)));
     }
 
     #[test]
@@ -42,6 +75,22 @@ mod tests {
         let s = "/People.html#tim";
         let loc = test_decode::<Location>(&[s]).unwrap();
 
-        assert_eq!(loc, Location(HeaderValue::from_static(s)));
+        assert_eq!(loc, Location(HeaderString::from_static(s)));
+    }
+
+    #[test]
+    fn resolve_relative() {
+        let base = "http://www.example.net/a/b/c?q";
+        assert_eq!(resolve("../up/one", base).unwrap(), "http://www.example.net/a/up/one");
+        assert_eq!(resolve("/People.html#tim", base).unwrap(), "http://www.example.net/People.html");
+        assert_eq!(resolve("//other.example/x", base).unwrap(), "http://other.example/x");
+        assert_eq!(resolve("?page=2", base).unwrap(), "http://www.example.net/a/b/c?page=2");
+        assert_eq!(resolve("https://x.example", base).unwrap(), "https://x.example/");
+    }
+
+    #[test]
+    fn resolve_invalid() {
+        assert_eq!(resolve("/not a uri", "http://www.example.net/"), None);
+        assert_eq!(resolve("/100%", "http://www.example.net/"), None);
     }
 }
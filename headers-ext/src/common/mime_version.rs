@@ -0,0 +1,145 @@
+use util::{digits, token};
+
+/// `MIME-Version` header, defined in
+/// [RFC2045](https://tools.ietf.org/html/rfc2045#section-4)
+///
+/// The `MIME-Version` header field declares the version of MIME a message
+/// conforms to. HTTP doesn't use it, as described in
+/// [RFC7231](https://tools.ietf.org/html/rfc7231#appendix-A.1), but it
+/// appears in multipart bodies and on gateways to mail.
+///
+/// # ABNF
+///
+/// ```text
+/// MIME-Version = 1*DIGIT "." 1*DIGIT
+/// ```
+///
+/// # Example values
+///
+/// * `1.0`
+/// * `1.0 (produced by MetaSend Vx.x)`
+///
+/// # Notes
+///
+/// Whitespace around the version is trimmed. The comment mail clients
+/// often add after it is dropped when lenient, and invalid when strict.
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::{HeaderMap, HeaderMapExt, MimeVersion};
+///
+/// let mut map = HeaderMap::new();
+/// map.typed_insert(MimeVersion::default());
+/// assert_eq!(map["mime-version"], "1.0");
+///
+/// let version = map.typed_get::<MimeVersion>().unwrap();
+/// assert_eq!((version.major(), version.minor()), (1, 0));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MimeVersion {
+    major: u32,
+    minor: u32,
+}
+
+impl MimeVersion {
+    /// `MIME-Version: 1.0`, the only version there is.
+    pub const V1_0: MimeVersion = MimeVersion::new(1, 0);
+
+    /// Create a `MIME-Version` of `major.minor`.
+    pub const fn new(major: u32, minor: u32) -> MimeVersion {
+        MimeVersion {
+            major,
+            minor,
+        }
+    }
+
+    /// Get the major version.
+    pub fn major(&self) -> u32 {
+        self.major
+    }
+
+    /// Get the minor version.
+    pub fn minor(&self) -> u32 {
+        self.minor
+    }
+}
+
+impl Default for MimeVersion {
+    fn default() -> MimeVersion {
+        MimeVersion::V1_0
+    }
+}
+
+impl ::Header for MimeVersion {
+    fn name() -> &'static ::HeaderName {
+        static_header_name!("mime-version")
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        let mut value = token::trim_ows(values.next()?.to_str().ok()?);
+        if values.mode() == ::ParseMode::Lenient && value.ends_with(')') {
+            let open = value.find('(')?;
+            value = token::trim_ows(&value[..open]);
+        }
+        let (major, minor) = value.split_once('.')?;
+        Some(MimeVersion::new(parse_u32(major)?, parse_u32(minor)?))
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        values.append_fmt(format_args!("{}.{}", self.major, self.minor));
+    }
+}
+
+fn parse_u32(s: &str) -> Option<u32> {
+    let n = digits::parse_u64(s.as_bytes())?;
+    if n > u64::from(u32::MAX) {
+        return None;
+    }
+    Some(n as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use headers_core::test_helpers::decode_from_strs_with;
+
+    use super::MimeVersion;
+    use super::super::{test_decode, test_encode};
+    use ParseMode;
+
+    fn strict(value: &str) -> Option<MimeVersion> {
+        decode_from_strs_with(&[value], ParseMode::Strict)
+    }
+
+    #[test]
+    fn decode() {
+        assert_eq!(test_decode::<MimeVersion>(&["1.0"]), Some(MimeVersion::V1_0));
+        assert_eq!(strict("1.0"), Some(MimeVersion::V1_0));
+        assert_eq!(strict(" 1.0\t"), Some(MimeVersion::V1_0));
+        assert_eq!(test_decode::<MimeVersion>(&["2.12"]), Some(MimeVersion::new(2, 12)));
+    }
+
+    #[test]
+    fn decode_comment() {
+        let value = "1.0 (produced by MetaSend Vx.x)";
+        assert_eq!(test_decode::<MimeVersion>(&[value]), Some(MimeVersion::V1_0));
+        assert_eq!(test_decode::<MimeVersion>(&["1.0(Generated)"]), Some(MimeVersion::V1_0));
+        assert_eq!(strict(value), None);
+        assert_eq!(test_decode::<MimeVersion>(&["(no version) 1.0"]), None);
+    }
+
+    #[test]
+    fn decode_invalid() {
+        for value in &["", "1", "1.", ".0", "1.0.0", "1. 0", "v1.0", "+1.0", "1.-0", "4294967296.0"] {
+            assert_eq!(test_decode::<MimeVersion>(&[value]), None, "{:?}", value);
+        }
+        assert_eq!(test_decode::<MimeVersion>(&["1.0", "1.0"]), None);
+    }
+
+    #[test]
+    fn encode() {
+        assert_eq!(test_encode(MimeVersion::default())["mime-version"], "1.0");
+        assert_eq!(test_encode(MimeVersion::new(2, 12))["mime-version"], "2.12");
+    }
+}
@@ -5,145 +5,1321 @@
 //! Several header fields use MIME values for their contents. Keeping with the
 //! strongly-typed theme, the [mime](https://docs.rs/mime) crate
 //! is used, such as `ContentType(pub Mime)`.
+//!
+//! ## Lists
+//!
+//! Comma-separated list headers skip empty elements and trim optional
+//! whitespace (spaces and tabs) around each element, so ` , ,gzip, ` decodes
+//! the same as `gzip`.
+//!
+//! A value with no elements at all doesn't decode, just as if the header
+//! were missing. The exceptions are `Allow` and `TE`, where an empty value
+//! has its own meaning and decodes to an empty list.
+//!
+//! An empty list always encodes as a single empty value, so inserting
+//! one replaces what the map had. Only headers made of whole field lines,
+//! like `list::Repeated`, encode to no values at all, which removes the
+//! header from the map.
+//!
+//! Lists of typed elements, such as `Allow` or `Link`, have `len` and
+//! `is_empty`, and iterate by reference or by value with `for`. Those whose
+//! order matters, `Link`, `Cache-Status` and `Proxy-Status`, can also be
+//! indexed.
+//!
+//! ## Canonical order
+//!
+//! Some list headers are sets, where the order of elements means nothing:
+//! `Vary`, `Allow`, `Access-Control-Allow-Headers`,
+//! `Access-Control-Allow-Methods`, `Access-Control-Expose-Headers` and
+//! `Access-Control-Request-Headers`. Their `canonicalize` method sorts
+//! the elements byte-wise and drops repeats, so that equal sets always
+//! encode the same, such as for signing or caching a response. The
+//! `canonical` feature does this on every encode.
+//!
+//! Other lists are ordered, such as `Content-Encoding` and `Via`, which
+//! list what was applied in turn, or keep a meaningful order, like
+//! `Accept-*` preferences of equal quality. These aren't reordered.
+//!
+//! ## Untrusted input
+//!
+//! Decoding never panics, whatever the bytes of the values, so
+//! `typed_get` is safe to call on a request straight from a peer. Every
+//! header here is run over a corpus of malformed values in the tests, and
+//! by the fuzz targets in `fuzz/`.
+//!
+//! To reject a request listing all of its malformed headers, rather than
+//! the first `typed_get` to fail, see `validate::scan`.
 
 //pub use self::accept_charset::AcceptCharset;
 //pub use self::accept_encoding::AcceptEncoding;
 //pub use self::accept_language::AcceptLanguage;
+pub use self::accept_ch::AcceptCh;
+pub use self::accept_patch::AcceptPatch;
+pub use self::accept_post::AcceptPost;
 pub use self::accept_ranges::AcceptRanges;
 //pub use self::accept::Accept;
+#[cfg(feature = "cors")]
 pub use self::access_control_allow_credentials::AccessControlAllowCredentials;
+#[cfg(feature = "cors")]
 pub use self::access_control_allow_headers::AccessControlAllowHeaders;
+#[cfg(feature = "cors")]
 pub use self::access_control_allow_methods::AccessControlAllowMethods;
+#[cfg(feature = "cors")]
 pub use self::access_control_allow_origin::AccessControlAllowOrigin;
+#[cfg(feature = "cors")]
 pub use self::access_control_expose_headers::AccessControlExposeHeaders;
+#[cfg(feature = "cors")]
 pub use self::access_control_max_age::AccessControlMaxAge;
+#[cfg(feature = "cors")]
 pub use self::access_control_request_headers::AccessControlRequestHeaders;
+#[cfg(feature = "cors")]
 pub use self::access_control_request_method::AccessControlRequestMethod;
+#[cfg(feature = "caching")]
+pub use self::age::Age;
 pub use self::allow::Allow;
+#[cfg(feature = "auth")]
 pub use self::authorization::{Authorization, Credentials, Basic, Bearer};
-pub use self::cache_control::CacheControl;
+#[cfg(feature = "caching")]
+pub use self::cache_control::{CacheControl, InvalidCacheDirective};
+#[cfg(feature = "caching")]
+pub use self::cache_status::{CacheStatus, CacheStatusEntry, ForwardReason};
 pub use self::connection::Connection;
 pub use self::content_disposition::ContentDisposition;
 pub use self::content_encoding::ContentEncoding;
 //pub use self::content_language::ContentLanguage;
 pub use self::content_length::ContentLength;
 pub use self::content_location::ContentLocation;
+pub use self::content_md5::ContentMd5;
 pub use self::content_range::ContentRange;
+#[cfg(feature = "security")]
+pub use self::content_security_policy::ContentSecurityPolicy;
+pub use self::content_transfer_encoding::ContentTransferEncoding;
 pub use self::content_type::ContentType;
-pub use self::cookie::Cookie;
+pub use self::cookie::{Cookie, CookieView};
+pub use self::critical_ch::CriticalCh;
 pub use self::date::Date;
+#[cfg(feature = "conditional")]
 pub use self::etag::ETag;
 pub use self::expect::Expect;
+#[cfg(feature = "security")]
+pub use self::expect_ct::ExpectCt;
+#[cfg(feature = "caching")]
 pub use self::expires::Expires;
-//pub use self::from::From;
+pub use self::from::FromEmail;
 pub use self::host::Host;
+#[cfg(feature = "conditional")]
 pub use self::if_match::IfMatch;
+#[cfg(feature = "conditional")]
 pub use self::if_modified_since::IfModifiedSince;
+#[cfg(feature = "conditional")]
 pub use self::if_none_match::IfNoneMatch;
+#[cfg(feature = "conditional")]
 pub use self::if_range::IfRange;
+#[cfg(feature = "conditional")]
 pub use self::if_unmodified_since::IfUnmodifiedSince;
+pub use self::keep_alive::KeepAlive;
 //pub use self::last_event_id::LastEventId;
+#[cfg(feature = "conditional")]
 pub use self::last_modified::LastModified;
-//pub use self::link::{Link, LinkValue, RelationType, MediaDesc};
+pub use self::link::{AsAttribute, CrossOrigin, FetchPriority, Link, LinkValue, MediaDesc, RelationType};
 pub use self::location::Location;
-pub use self::origin::Origin;
+pub use self::mime_version::MimeVersion;
+pub use self::origin::{InvalidOriginPattern, Origin, OriginPattern};
+#[cfg(feature = "caching")]
 pub use self::pragma::Pragma;
+pub use self::priority::Priority;
 //pub use self::prefer::{Prefer, Preference};
 //pub use self::preference_applied::PreferenceApplied;
+#[cfg(feature = "auth")]
 pub use self::proxy_authorization::ProxyAuthorization;
-pub use self::range::Range;
-pub use self::referer::Referer;
+#[cfg(feature = "proxy")]
+pub use self::proxy_status::{ProxyStatus, ProxyStatusEntry};
+pub use self::range::{InvalidRange, Range};
+pub use self::referer::{InvalidReferer, Referer, RefererView};
+#[cfg(feature = "security")]
 pub use self::referrer_policy::ReferrerPolicy;
+pub use self::refresh::Refresh;
 pub use self::retry_after::RetryAfter;
+pub use self::sec_ch_ua::{SecChUa, SecChUaMobile, SecChUaPlatform};
+#[cfg(feature = "websocket")]
 pub use self::sec_websocket_accept::SecWebsocketAccept;
+#[cfg(feature = "websocket")]
 pub use self::sec_websocket_key::SecWebsocketKey;
+#[cfg(feature = "websocket")]
 pub use self::sec_websocket_version::SecWebsocketVersion;
 pub use self::server::Server;
-pub use self::set_cookie::SetCookie;
+pub use self::set_cookie::{CookieError, HostPrefixedBuilder, SameSite, SetCookie, SetCookieBuilder};
+pub(crate) use self::set_cookie::{check_prefix, SetCookieLine};
+#[cfg(feature = "auth")]
+pub use self::signature::Signature;
+#[cfg(feature = "auth")]
+pub use self::signature_input::{SignatureComponent, SignatureInput, SignatureParams};
+pub use self::soap_action::SoapAction;
+pub use self::source_map::{SourceMap, XSourceMap};
+#[cfg(feature = "security")]
 pub use self::strict_transport_security::StrictTransportSecurity;
 pub use self::te::Te;
 pub use self::transfer_encoding::TransferEncoding;
 pub use self::upgrade::Upgrade;
-pub use self::user_agent::UserAgent;
+pub use self::user_agent::{UserAgent, UserAgentView};
+#[cfg(feature = "caching")]
 pub use self::vary::Vary;
-//pub use self::warning::Warning;
+#[cfg(feature = "proxy")]
+pub use self::via::Via;
+#[cfg(feature = "caching")]
+pub use self::warning::Warning;
+#[cfg(feature = "proxy")]
+pub use self::x_accel::{InvalidRedirectPath, XAccelBuffering, XAccelExpires, XAccelLimitRate, XAccelRedirect};
+#[cfg(feature = "auth")]
+pub use self::x_api_key::{InvalidApiKey, XApiKey};
+#[cfg(feature = "security")]
+pub use self::x_content_type_options::XContentTypeOptions;
+#[cfg(feature = "security")]
+pub use self::x_frame_options::XFrameOptions;
+#[cfg(feature = "proxy")]
+pub use self::x_real_ip::XRealIp;
+#[cfg(feature = "proxy")]
+pub use self::x_sendfile::XSendfile;
 
 #[cfg(test)]
 fn test_decode<T: ::headers_core::Header>(values: &[&str]) -> Option<T> {
-    use ::headers_core::HeaderMapExt;
-    let mut map = ::http::HeaderMap::new();
-    for val in values {
-        map.append(T::NAME, val.parse().unwrap());
-    }
-    map.typed_get()
+    ::headers_core::test_helpers::decode_from_strs(values)
 }
 
 #[cfg(test)]
 fn test_encode<T: ::headers_core::Header>(header: T) -> ::http::HeaderMap {
-    use ::headers_core::HeaderMapExt;
-    let mut map = ::http::HeaderMap::new();
-    map.typed_insert(header);
-    map
+    ::headers_core::test_helpers::encode_to_map(header)
+}
+
+/// Implement `Display` and `FromStr` for headers in terms of their
+/// `encode` and `decode`, so they format and parse as their header values.
+macro_rules! impl_display_from_str {
+    (from_str: $($(#[$attr:meta])* $ty:ident,)+) => {
+        $(
+            $(#[$attr])*
+            impl ::std::str::FromStr for $ty {
+                type Err = ::InvalidHeader;
+
+                fn from_str(s: &str) -> Result<$ty, ::InvalidHeader> {
+                    ::headers_core::decode::from_str(s)
+                }
+            }
+        )+
+    };
+    ($($(#[$attr:meta])* $ty:ident,)+) => {
+        $(
+            $(#[$attr])*
+            impl ::std::fmt::Display for $ty {
+                fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                    ::headers_core::encode::fmt_header(self, f)
+                }
+            }
+        )+
+        impl_display_from_str!(from_str: $($(#[$attr])* $ty,)+);
+    };
+}
+
+/// Implement `SingleValueHeader`, `TryFrom<&HeaderValue>` and
+/// `From<&H> for HeaderValue` for headers that encode to exactly one value.
+macro_rules! impl_single_value {
+    (derived: $($(#[$attr:meta])* $ty:ident,)+) => {
+        $(
+            $(#[$attr])*
+            impl ::SingleValueHeader for $ty {}
+
+            $(#[$attr])*
+            impl<'a> ::std::convert::TryFrom<&'a ::HeaderValue> for $ty {
+                type Error = ::InvalidHeader;
+
+                fn try_from(value: &'a ::HeaderValue) -> Result<$ty, ::InvalidHeader> {
+                    ::headers_core::decode::from_value(value)
+                }
+            }
+        )+
+    };
+    ($($(#[$attr:meta])* $ty:ident,)+) => {
+        impl_single_value!(derived: $($(#[$attr])* $ty,)+);
+        $(
+            $(#[$attr])*
+            impl<'a> From<&'a $ty> for ::HeaderValue {
+                fn from(header: &'a $ty) -> ::HeaderValue {
+                    ::headers_core::encode::to_value(header)
+                }
+            }
+        )+
+    };
+}
+
+/// Implement `ListHeader` for comma-separated list headers, which decode
+/// the same whether their elements are on one field line or several.
+macro_rules! impl_list_header {
+    ($($(#[$attr:meta])* $ty:ident,)+) => {
+        $(
+            $(#[$attr])*
+            impl ::list::ListHeader for $ty {}
+        )+
+    };
+}
+
+/// Implement `len`, `is_empty`, and `IntoIterator` for the header and a
+/// reference to it, for a list header of `$item`s, along with
+/// `Index<usize>` if the order of the list is `ordered`. A list that can be
+/// `wildcard` lists nothing for `*`, but isn't empty then.
+///
+/// Invoked in each header's module, where it uses the header's `iter`,
+/// which returns a `list::Iter`, and a private `into_items`, which returns
+/// a `list::IntoIter`.
+macro_rules! impl_collection {
+    ($ty:ident, $item:ty) => {
+        impl $ty {
+            /// Returns the number of elements.
+            pub fn len(&self) -> usize {
+                self.iter().len()
+            }
+
+            /// Check if there are no elements.
+            pub fn is_empty(&self) -> bool {
+                self.len() == 0
+            }
+        }
+
+        impl_collection!(@iter $ty, $item);
+    };
+    ($ty:ident, $item:ty, ordered) => {
+        impl_collection!($ty, $item);
+
+        impl ::std::ops::Index<usize> for $ty {
+            type Output = $item;
+
+            /// Get the element at `index`.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `index` isn't less than `len`.
+            fn index(&self, index: usize) -> &$item {
+                let len = self.len();
+                self.iter().nth(index).unwrap_or_else(|| {
+                    panic!("index out of bounds: the len is {} but the index is {}", len, index)
+                })
+            }
+        }
+    };
+    ($ty:ident, $item:ty, wildcard) => {
+        impl $ty {
+            /// Returns the number of listed elements, which is 0 for `*`.
+            pub fn len(&self) -> usize {
+                self.iter().len()
+            }
+
+            /// Check if nothing is listed, and this isn't `*`.
+            pub fn is_empty(&self) -> bool {
+                !self.is_any() && self.len() == 0
+            }
+        }
+
+        impl_collection!(@iter $ty, $item);
+    };
+    (@iter $ty:ident, $item:ty) => {
+        impl IntoIterator for $ty {
+            type Item = $item;
+            type IntoIter = ::list::IntoIter<$item>;
+
+            fn into_iter(self) -> ::list::IntoIter<$item> {
+                self.into_items()
+            }
+        }
+
+        impl<'a> IntoIterator for &'a $ty {
+            type Item = &'a $item;
+            type IntoIter = ::list::Iter<'a, $item>;
+
+            fn into_iter(self) -> ::list::Iter<'a, $item> {
+                self.iter()
+            }
+        }
+    };
+}
+
+/// Implement conversions from and to `HttpDate`, and the `chrono` and
+/// `time` date types when those features are enabled, for a header that
+/// wraps one. Invoked in each header's module, since the field is private.
+macro_rules! impl_date_header {
+    ($ty:ident) => {
+        impl From<::util::HttpDate> for $ty {
+            fn from(date: ::util::HttpDate) -> $ty {
+                $ty(date)
+            }
+        }
+
+        impl From<$ty> for ::util::HttpDate {
+            fn from(header: $ty) -> ::util::HttpDate {
+                header.0
+            }
+        }
+
+        #[cfg(feature = "chrono")]
+        impl ::std::convert::TryFrom<::chrono::DateTime<::chrono::Utc>> for $ty {
+            type Error = ::util::DateOutOfRange;
+
+            /// Convert a `DateTime`, dropping its fractional seconds.
+            fn try_from(
+                date: ::chrono::DateTime<::chrono::Utc>,
+            ) -> Result<$ty, ::util::DateOutOfRange> {
+                ::std::convert::TryFrom::try_from(date).map($ty)
+            }
+        }
+
+        #[cfg(feature = "chrono")]
+        impl From<$ty> for ::chrono::DateTime<::chrono::Utc> {
+            fn from(header: $ty) -> ::chrono::DateTime<::chrono::Utc> {
+                header.0.into()
+            }
+        }
+
+        #[cfg(feature = "time03")]
+        impl ::std::convert::TryFrom<::time03::OffsetDateTime> for $ty {
+            type Error = ::util::DateOutOfRange;
+
+            /// Convert an `OffsetDateTime`, dropping its fractional seconds.
+            fn try_from(date: ::time03::OffsetDateTime) -> Result<$ty, ::util::DateOutOfRange> {
+                ::std::convert::TryFrom::try_from(date).map($ty)
+            }
+        }
+
+        #[cfg(feature = "time03")]
+        impl From<$ty> for ::time03::OffsetDateTime {
+            fn from(header: $ty) -> ::time03::OffsetDateTime {
+                header.0.into()
+            }
+        }
+    };
 }
 
 //mod accept_charset;
 //mod accept_encoding;
 //mod accept_language;
+mod accept_ch;
+mod accept_patch;
+mod accept_post;
 mod accept_ranges;
 //mod accept;
+#[cfg(feature = "cors")]
 mod access_control_allow_credentials;
+#[cfg(feature = "cors")]
 mod access_control_allow_headers;
+#[cfg(feature = "cors")]
 mod access_control_allow_methods;
+#[cfg(feature = "cors")]
 mod access_control_allow_origin;
+#[cfg(feature = "cors")]
 mod access_control_expose_headers;
+#[cfg(feature = "cors")]
 mod access_control_max_age;
+#[cfg(feature = "cors")]
 mod access_control_request_headers;
+#[cfg(feature = "cors")]
 mod access_control_request_method;
+#[cfg(feature = "caching")]
+mod age;
 mod allow;
+#[cfg(feature = "auth")]
 mod authorization;
+#[cfg(feature = "caching")]
 mod cache_control;
+#[cfg(feature = "caching")]
+mod cache_status;
 mod connection;
 mod content_disposition;
 mod content_encoding;
 //mod content_language;
 mod content_length;
 mod content_location;
+mod content_md5;
 mod content_range;
+#[cfg(feature = "security")]
+mod content_security_policy;
+mod content_transfer_encoding;
 mod content_type;
 mod cookie;
+mod critical_ch;
 mod date;
+#[cfg(feature = "conditional")]
 mod etag;
 mod expect;
+#[cfg(feature = "security")]
+mod expect_ct;
+#[cfg(feature = "caching")]
 mod expires;
-//mod from;
+mod from;
 mod host;
+#[cfg(feature = "conditional")]
 mod if_match;
+#[cfg(feature = "conditional")]
 mod if_modified_since;
+#[cfg(feature = "conditional")]
 mod if_none_match;
+#[cfg(feature = "conditional")]
 mod if_range;
+#[cfg(feature = "conditional")]
 mod if_unmodified_since;
+mod keep_alive;
 //mod last_event_id;
+#[cfg(feature = "conditional")]
 mod last_modified;
-//mod link;
+mod link;
 mod location;
+mod mime_version;
 mod origin;
+#[cfg(feature = "caching")]
 mod pragma;
+mod priority;
 //mod prefer;
 //mod preference_applied;
+#[cfg(feature = "auth")]
 mod proxy_authorization;
+#[cfg(feature = "proxy")]
+mod proxy_status;
 mod range;
 mod referer;
+#[cfg(feature = "security")]
 mod referrer_policy;
+mod refresh;
 mod retry_after;
+mod sec_ch_ua;
+#[cfg(feature = "websocket")]
 mod sec_websocket_accept;
+#[cfg(feature = "websocket")]
 mod sec_websocket_key;
+#[cfg(feature = "websocket")]
 mod sec_websocket_version;
 mod server;
 mod set_cookie;
+#[cfg(feature = "auth")]
+mod signature;
+#[cfg(feature = "auth")]
+mod signature_input;
+mod soap_action;
+mod source_map;
+#[cfg(feature = "security")]
 mod strict_transport_security;
 mod te;
 mod transfer_encoding;
 mod upgrade;
 mod user_agent;
+#[cfg(feature = "caching")]
 mod vary;
-//mod warning;
+#[cfg(feature = "proxy")]
+mod via;
+#[cfg(feature = "caching")]
+mod warning;
+#[cfg(feature = "proxy")]
+mod x_accel;
+#[cfg(feature = "auth")]
+mod x_api_key;
+#[cfg(feature = "security")]
+mod x_content_type_options;
+#[cfg(feature = "security")]
+mod x_frame_options;
+#[cfg(feature = "proxy")]
+mod x_real_ip;
+#[cfg(feature = "proxy")]
+mod x_sendfile;
+
+// `Authorization`, `ProxyAuthorization` and `XApiKey` are left out so
+// credentials aren't formatted by accident, and `SetCookie` as it can't be represented
+// as a single line.
+impl_display_from_str! {
+    AcceptCh,
+    AcceptPatch,
+    AcceptPost,
+    AcceptRanges,
+    #[cfg(feature = "cors")]
+    AccessControlAllowCredentials,
+    #[cfg(feature = "cors")]
+    AccessControlAllowHeaders,
+    #[cfg(feature = "cors")]
+    AccessControlAllowMethods,
+    #[cfg(feature = "cors")]
+    AccessControlAllowOrigin,
+    #[cfg(feature = "cors")]
+    AccessControlExposeHeaders,
+    #[cfg(feature = "cors")]
+    AccessControlMaxAge,
+    #[cfg(feature = "cors")]
+    AccessControlRequestHeaders,
+    #[cfg(feature = "cors")]
+    AccessControlRequestMethod,
+    #[cfg(feature = "caching")]
+    Age,
+    Allow,
+    #[cfg(feature = "caching")]
+    CacheControl,
+    #[cfg(feature = "caching")]
+    CacheStatus,
+    Connection,
+    ContentDisposition,
+    ContentEncoding,
+    ContentLength,
+    ContentLocation,
+    ContentMd5,
+    ContentRange,
+    #[cfg(feature = "security")]
+    ContentSecurityPolicy,
+    Cookie,
+    CriticalCh,
+    Date,
+    #[cfg(feature = "conditional")]
+    ETag,
+    Expect,
+    #[cfg(feature = "security")]
+    ExpectCt,
+    #[cfg(feature = "caching")]
+    Expires,
+    FromEmail,
+    #[cfg(feature = "conditional")]
+    IfMatch,
+    #[cfg(feature = "conditional")]
+    IfModifiedSince,
+    #[cfg(feature = "conditional")]
+    IfNoneMatch,
+    #[cfg(feature = "conditional")]
+    IfRange,
+    #[cfg(feature = "conditional")]
+    IfUnmodifiedSince,
+    #[cfg(feature = "conditional")]
+    LastModified,
+    Link,
+    Location,
+    MimeVersion,
+    Origin,
+    #[cfg(feature = "caching")]
+    Pragma,
+    Priority,
+    #[cfg(feature = "proxy")]
+    ProxyStatus,
+    Range,
+    Referer,
+    #[cfg(feature = "security")]
+    ReferrerPolicy,
+    Refresh,
+    RetryAfter,
+    SecChUa,
+    SecChUaMobile,
+    SecChUaPlatform,
+    #[cfg(feature = "websocket")]
+    SecWebsocketAccept,
+    #[cfg(feature = "websocket")]
+    SecWebsocketKey,
+    #[cfg(feature = "websocket")]
+    SecWebsocketVersion,
+    Server,
+    #[cfg(feature = "auth")]
+    Signature,
+    #[cfg(feature = "auth")]
+    SignatureInput,
+    SoapAction,
+    SourceMap,
+    #[cfg(feature = "security")]
+    StrictTransportSecurity,
+    Te,
+    TransferEncoding,
+    Upgrade,
+    UserAgent,
+    #[cfg(feature = "caching")]
+    Vary,
+    #[cfg(feature = "proxy")]
+    Via,
+    #[cfg(feature = "proxy")]
+    XAccelExpires,
+    #[cfg(feature = "proxy")]
+    XAccelLimitRate,
+    #[cfg(feature = "proxy")]
+    XAccelRedirect,
+    #[cfg(feature = "security")]
+    XContentTypeOptions,
+    #[cfg(feature = "security")]
+    XFrameOptions,
+    #[cfg(feature = "proxy")]
+    XRealIp,
+    #[cfg(feature = "proxy")]
+    XSendfile,
+    XSourceMap,
+}
+
+// These already implement `Display`, formatting as their header value.
+impl_display_from_str! {
+    from_str:
+    ContentType,
+    Host,
+    KeepAlive,
+    #[cfg(feature = "caching")]
+    Warning,
+}
+
+// `Authorization` and `ProxyAuthorization` are generic, and implement these
+// themselves. `SetCookie` needs a field line per cookie.
+impl_single_value! {
+    AcceptCh,
+    AcceptPatch,
+    AcceptPost,
+    AcceptRanges,
+    #[cfg(feature = "cors")]
+    AccessControlAllowCredentials,
+    #[cfg(feature = "cors")]
+    AccessControlAllowHeaders,
+    #[cfg(feature = "cors")]
+    AccessControlAllowMethods,
+    #[cfg(feature = "cors")]
+    AccessControlAllowOrigin,
+    #[cfg(feature = "cors")]
+    AccessControlExposeHeaders,
+    #[cfg(feature = "cors")]
+    AccessControlMaxAge,
+    #[cfg(feature = "cors")]
+    AccessControlRequestHeaders,
+    #[cfg(feature = "cors")]
+    AccessControlRequestMethod,
+    #[cfg(feature = "caching")]
+    Age,
+    Allow,
+    #[cfg(feature = "caching")]
+    CacheControl,
+    #[cfg(feature = "caching")]
+    CacheStatus,
+    Connection,
+    ContentDisposition,
+    ContentEncoding,
+    ContentLength,
+    ContentLocation,
+    ContentMd5,
+    ContentRange,
+    #[cfg(feature = "security")]
+    ContentSecurityPolicy,
+    ContentType,
+    Cookie,
+    CriticalCh,
+    Date,
+    #[cfg(feature = "conditional")]
+    ETag,
+    Expect,
+    #[cfg(feature = "security")]
+    ExpectCt,
+    #[cfg(feature = "caching")]
+    Expires,
+    FromEmail,
+    Host,
+    #[cfg(feature = "conditional")]
+    IfMatch,
+    #[cfg(feature = "conditional")]
+    IfModifiedSince,
+    #[cfg(feature = "conditional")]
+    IfNoneMatch,
+    #[cfg(feature = "conditional")]
+    IfRange,
+    #[cfg(feature = "conditional")]
+    IfUnmodifiedSince,
+    KeepAlive,
+    #[cfg(feature = "conditional")]
+    LastModified,
+    Link,
+    Location,
+    MimeVersion,
+    Origin,
+    #[cfg(feature = "caching")]
+    Pragma,
+    Priority,
+    #[cfg(feature = "proxy")]
+    ProxyStatus,
+    Range,
+    Referer,
+    #[cfg(feature = "security")]
+    ReferrerPolicy,
+    Refresh,
+    RetryAfter,
+    SecChUa,
+    SecChUaMobile,
+    SecChUaPlatform,
+    #[cfg(feature = "websocket")]
+    SecWebsocketAccept,
+    #[cfg(feature = "websocket")]
+    SecWebsocketKey,
+    #[cfg(feature = "websocket")]
+    SecWebsocketVersion,
+    Server,
+    #[cfg(feature = "auth")]
+    Signature,
+    #[cfg(feature = "auth")]
+    SignatureInput,
+    SoapAction,
+    SourceMap,
+    #[cfg(feature = "security")]
+    StrictTransportSecurity,
+    Te,
+    TransferEncoding,
+    Upgrade,
+    UserAgent,
+    #[cfg(feature = "caching")]
+    Vary,
+    #[cfg(feature = "proxy")]
+    Via,
+    #[cfg(feature = "caching")]
+    Warning,
+    #[cfg(feature = "proxy")]
+    XAccelExpires,
+    #[cfg(feature = "proxy")]
+    XAccelLimitRate,
+    #[cfg(feature = "proxy")]
+    XAccelRedirect,
+    #[cfg(feature = "auth")]
+    XApiKey,
+    #[cfg(feature = "security")]
+    XContentTypeOptions,
+    #[cfg(feature = "proxy")]
+    XRealIp,
+    #[cfg(feature = "proxy")]
+    XSendfile,
+    XSourceMap,
+}
+
+// Enums deriving `Header` already implement `From<&H> for HeaderValue`.
+impl_single_value! {
+    derived:
+    ContentTransferEncoding,
+    #[cfg(feature = "proxy")]
+    XAccelBuffering,
+    #[cfg(feature = "security")]
+    XFrameOptions,
+}
+
+impl_list_header! {
+    AcceptCh,
+    #[cfg(feature = "cors")]
+    AccessControlAllowHeaders,
+    #[cfg(feature = "cors")]
+    AccessControlAllowMethods,
+    #[cfg(feature = "cors")]
+    AccessControlExposeHeaders,
+    #[cfg(feature = "cors")]
+    AccessControlRequestHeaders,
+    Allow,
+    #[cfg(feature = "caching")]
+    CacheControl,
+    Connection,
+    ContentEncoding,
+    CriticalCh,
+    #[cfg(feature = "conditional")]
+    IfMatch,
+    #[cfg(feature = "conditional")]
+    IfNoneMatch,
+    Link,
+    Te,
+    TransferEncoding,
+    #[cfg(feature = "caching")]
+    Vary,
+    #[cfg(feature = "proxy")]
+    Via,
+}
+
+// These use headers from every group.
+#[cfg(all(test, feature = "full"))]
+mod tests {
+    use std::convert::TryFrom;
+    use std::fmt::Debug;
+    use std::str::FromStr;
+    use std::time::Duration;
+
+    use super::*;
+
+    fn round_trip<H>(header: H)
+    where
+        H: ::std::fmt::Display + FromStr + PartialEq + Debug,
+        H::Err: Debug,
+    {
+        let s = header.to_string();
+        assert_eq!(s.parse::<H>().unwrap(), header, "{:?}", s);
+    }
+
+    #[test]
+    fn display_is_the_header_value() {
+        assert_eq!(ContentLength(42).to_string(), "42");
+        assert_eq!(
+            CacheControl::new().with_no_cache().with_max_age(Duration::from_secs(10)).to_string(),
+            "no-cache, max-age=10",
+        );
+        assert_eq!(
+            vec![::http::Method::GET, ::http::Method::HEAD].into_iter().collect::<Allow>().to_string(),
+            "GET, HEAD",
+        );
+    }
+
+    #[test]
+    fn from_str_decodes_the_header_value() {
+        assert_eq!("42".parse::<ContentLength>().unwrap(), ContentLength(42));
+        assert!("forty-two".parse::<ContentLength>().is_err());
+        assert!("\n".parse::<ContentLength>().is_err());
+        assert_eq!(
+            "text/html".parse::<ContentType>().unwrap(),
+            ContentType::html(),
+        );
+    }
+
+    #[test]
+    fn display_from_str_round_trip() {
+        round_trip(Age::from(Duration::from_secs(60)));
+        round_trip(
+            vec![::http::header::ACCEPT, ::http::header::CONTENT_TYPE]
+                .into_iter()
+                .collect::<AccessControlAllowHeaders>(),
+        );
+        round_trip(CacheControl::new().with_no_store().with_private());
+        round_trip(ContentLength(1024));
+        round_trip(ContentRange::other(::RangeUnit::Other("items".into()), "0-49/200").unwrap());
+        round_trip("Sun, 06 Nov 1994 08:49:37 GMT".parse::<Date>().unwrap());
+        round_trip(ETag::from(::EntityTag::from_static("W/\"xyzzy\"")));
+        round_trip(Host::from(::http::uri::Authority::from_static("example.com:8080")));
+        round_trip(Link::new(vec![
+            LinkValue::preload("/style.css", AsAttribute::Style),
+            LinkValue::new("/next").push_rel(RelationType::Next).set_title("Next, page"),
+        ]));
+        round_trip(Range::new(::RangeUnit::Other("items".into()), "0-49").unwrap());
+        round_trip(Referer::from_static("https://example.com/page"));
+        round_trip(SecChUa::new().with_brand("Chromium", "124").with_brand("Not-A.Brand", "99"));
+        round_trip(SecChUaMobile(true));
+        round_trip(SecChUaPlatform::new("Windows"));
+        round_trip(Signature::new().with("sig1", &b"\x00\xff"[..]));
+        round_trip(SignatureInput::new().with(
+            "sig1",
+            SignatureParams::new(vec![SignatureComponent::query_param("id")]).with_created(1618884473),
+        ));
+        round_trip(StrictTransportSecurity::excluding_subdomains(Duration::from_secs(31536000)));
+        round_trip(Vary::any());
+        round_trip(XRealIp("2001:db8::1".parse().unwrap()));
+    }
+
+    #[test]
+    fn single_value_conversions() {
+        let value = ::HeaderValue::from_static("max-age=60, public");
+        let cc = CacheControl::try_from(&value).unwrap();
+        assert_eq!(cc, CacheControl::new().with_max_age(Duration::from_secs(60)).with_public());
+        assert_eq!(::HeaderValue::from(&cc), "max-age=60, public");
+
+        let allow = vec![::http::Method::GET, ::http::Method::POST]
+            .into_iter()
+            .collect::<Allow>();
+        assert_eq!(::HeaderValue::from(&allow), "GET, POST");
+
+        let bad = ::HeaderValue::from_static("nope");
+        assert!(ContentLength::try_from(&bad).is_err());
+
+        let auth = Authorization::bearer("abc").unwrap();
+        let value = ::HeaderValue::from(&auth);
+        assert_eq!(value, "Bearer abc");
+        assert_eq!(Authorization::<Bearer>::try_from(&value).unwrap(), auth);
+    }
+
+    #[test]
+    fn list_headers_skip_empty_elements() {
+        use http::header::{CONTENT_TYPE, ORIGIN};
+        use http::Method;
+
+        fn padded<H: ::Header>(item: &str) -> H {
+            let value = format!(" , ,{}, ", item);
+            test_decode::<H>(&[&value]).unwrap_or_else(|| panic!("{:?}", value))
+        }
+
+        let text = "text/plain".parse::<::mime::Mime>().unwrap();
+        assert_eq!(padded::<AcceptPatch>("text/plain").iter().collect::<Vec<_>>(), [&text]);
+        assert_eq!(padded::<AcceptPost>("text/plain").iter().collect::<Vec<_>>(), [&text]);
+        padded::<AcceptRanges>("bytes");
+        assert_eq!(
+            padded::<AccessControlAllowHeaders>("content-type").iter().collect::<Vec<_>>(),
+            [&CONTENT_TYPE],
+        );
+        assert_eq!(
+            padded::<AccessControlAllowMethods>("GET").iter().collect::<Vec<_>>(),
+            [&Method::GET],
+        );
+        assert_eq!(
+            padded::<AccessControlExposeHeaders>("origin").iter().collect::<Vec<_>>(),
+            [&ORIGIN],
+        );
+        assert_eq!(
+            padded::<AccessControlRequestHeaders>("origin").iter().collect::<Vec<_>>(),
+            [&ORIGIN],
+        );
+        assert_eq!(padded::<Allow>("GET").iter().collect::<Vec<_>>(), [&Method::GET]);
+        assert_eq!(padded::<CacheControl>("no-cache"), CacheControl::new().with_no_cache());
+        assert!(padded::<Connection>("close").contains("close"));
+        assert!(padded::<ContentEncoding>("gzip").contains("gzip"));
+        padded::<IfMatch>("\"xyzzy\"");
+        padded::<IfNoneMatch>("\"xyzzy\"");
+        padded::<Te>("trailers");
+        assert!(padded::<TransferEncoding>("chunked").is_chunked());
+        assert_eq!(padded::<Vary>("origin").iter_strs().collect::<Vec<_>>(), ["origin"]);
+    }
+
+    #[test]
+    fn list_headers_trim_only_ows() {
+        let cc = test_decode::<CacheControl>(&["\tmax-age=60 ,  no-cache\t"]).unwrap();
+        assert_eq!(cc, CacheControl::new().with_max_age(Duration::from_secs(60)).with_no_cache());
+        assert_eq!(
+            test_decode::<Vary>(&["\t accept-encoding\t"]).unwrap().iter_strs().collect::<Vec<_>>(),
+            ["accept-encoding"],
+        );
+    }
+
+    #[test]
+    fn list_headers_without_elements() {
+        fn empty<H: ::Header>() -> Vec<Option<H>> {
+            vec![test_decode::<H>(&[""]), test_decode::<H>(&[" , ,\t"]), test_decode::<H>(&["", ","])]
+        }
+
+        fn none<H: ::Header>() -> bool {
+            empty::<H>().iter().all(Option::is_none)
+        }
+
+        assert!(none::<AcceptCh>());
+        assert!(none::<AcceptPatch>());
+        assert!(none::<AcceptPost>());
+        assert!(none::<AcceptRanges>());
+        assert!(none::<AccessControlRequestHeaders>());
+        assert!(none::<CacheControl>());
+        assert!(none::<CacheStatus>());
+        assert!(none::<Connection>());
+        assert!(none::<CriticalCh>());
+        assert!(none::<ContentEncoding>());
+        assert!(none::<IfMatch>());
+        assert!(none::<IfNoneMatch>());
+        assert!(none::<Link>());
+        assert!(none::<ProxyStatus>());
+        assert!(none::<TransferEncoding>());
+        assert!(none::<Vary>());
+
+        for allow in empty::<Allow>() {
+            assert_eq!(allow.unwrap().iter().count(), 0);
+        }
+        for te in empty::<Te>() {
+            assert!(te.is_some());
+        }
+    }
+
+    #[test]
+    fn empty_lists_encode_an_empty_value() {
+        use std::iter::FromIterator;
+
+        use list::Repeated;
+        use HeaderMapExt;
+
+        fn empty<H: ::Header>(header: H) {
+            let map = test_encode(header);
+            assert_eq!(map.get_all(H::name()).iter().collect::<Vec<_>>(), [""], "{:?}", H::name());
+        }
+
+        fn none<H: FromIterator<T> + ::Header, T>() {
+            empty(H::from_iter(None));
+        }
+
+        none::<AcceptCh, _>();
+        none::<AcceptPatch, _>();
+        none::<AcceptPost, _>();
+        none::<AccessControlAllowHeaders, _>();
+        none::<AccessControlAllowMethods, _>();
+        none::<AccessControlExposeHeaders, _>();
+        none::<AccessControlRequestHeaders, _>();
+        none::<Allow, _>();
+        none::<CacheStatus, _>();
+        none::<Connection, _>();
+        none::<CriticalCh, _>();
+        none::<ProxyStatus, _>();
+        none::<Vary, _>();
+        empty(CacheControl::new());
+        empty(Link::new(Vec::new()));
+
+        let mut map = ::HeaderMap::new();
+        map.typed_insert(ContentLength(3));
+        map.typed_insert(Repeated::<ContentLength>::from(Vec::new()));
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn collections_agree() {
+        use std::iter::FromIterator;
+
+        fn check<H>(value: &'static str, len: usize)
+        where
+            H: ::Header + Clone + IntoIterator,
+            H::Item: Debug + PartialEq,
+            for<'a> &'a H: IntoIterator<Item = &'a H::Item>,
+        {
+            let header = test_decode::<H>(&[value]).unwrap();
+            let borrowed = (&header).into_iter().collect::<Vec<_>>();
+            assert_eq!(borrowed.len(), len, "{:?}", value);
+            let owned = header.clone().into_iter().collect::<Vec<_>>();
+            assert_eq!(borrowed, owned.iter().collect::<Vec<_>>(), "{:?}", value);
+        }
+
+        check::<AcceptCh>("sec-ch-ua, dpr", 2);
+        check::<AcceptPatch>("text/plain, application/json", 2);
+        check::<AcceptPost>("text/plain", 1);
+        check::<AccessControlAllowHeaders>("x-a, x-b, x-c", 3);
+        check::<AccessControlAllowHeaders>("*", 0);
+        check::<AccessControlAllowMethods>("GET, PUT", 2);
+        check::<AccessControlExposeHeaders>("etag", 1);
+        check::<AccessControlRequestHeaders>("x-a, x-b", 2);
+        check::<Allow>("GET, HEAD, POST, PUT, DELETE", 5);
+        check::<CacheStatus>("Origin; hit, CDN; fwd=miss", 2);
+        check::<CriticalCh>("dpr", 1);
+        check::<Link>("</a>; rel=next, </b>; rel=prev", 2);
+        check::<ProxyStatus>("r1, r2, r3", 3);
+
+        let allow = test_decode::<Allow>(&["GET, POST"]).unwrap();
+        assert_eq!(allow.len(), 2);
+        assert!(!allow.is_empty());
+        assert!(test_decode::<Allow>(&[""]).unwrap().is_empty());
+
+        let any = AccessControlAllowMethods::any();
+        assert_eq!(any.len(), 0);
+        assert!(!any.is_empty());
+        assert!(AccessControlAllowMethods::from_iter(None).is_empty());
+
+        let status = test_decode::<CacheStatus>(&["Origin; hit, CDN; fwd=miss"]).unwrap();
+        assert_eq!(status[1].cache(), "CDN");
+        let link = test_decode::<Link>(&["</a>, </b>"]).unwrap();
+        assert_eq!(link[1].link(), "/b");
+    }
+
+    #[test]
+    #[should_panic(expected = "the len is 1 but the index is 1")]
+    fn collection_index_out_of_bounds() {
+        let status = test_decode::<ProxyStatus>(&["r1"]).unwrap();
+        let _ = &status[1];
+    }
+
+    #[test]
+    fn static_header_names() {
+        use headers_core::Header;
+
+        // Each of these is checked by `HeaderName::from_static` the
+        // first time it's used.
+        assert_eq!(AcceptCh::name(), "accept-ch");
+        assert_eq!(AcceptPatch::name(), "accept-patch");
+        assert_eq!(AcceptPost::name(), "accept-post");
+        assert_eq!(CacheStatus::name(), "cache-status");
+        assert_eq!(ContentMd5::name(), "content-md5");
+        assert_eq!(ContentTransferEncoding::name(), "content-transfer-encoding");
+        assert_eq!(CriticalCh::name(), "critical-ch");
+        assert_eq!(ExpectCt::name(), "expect-ct");
+        assert_eq!(KeepAlive::name(), "keep-alive");
+        assert_eq!(MimeVersion::name(), "mime-version");
+        assert_eq!(Priority::name(), "priority");
+        assert_eq!(ProxyStatus::name(), "proxy-status");
+        assert_eq!(SecChUa::name(), "sec-ch-ua");
+        assert_eq!(SecChUaMobile::name(), "sec-ch-ua-mobile");
+        assert_eq!(SecChUaPlatform::name(), "sec-ch-ua-platform");
+        assert_eq!(Signature::name(), "signature");
+        assert_eq!(SignatureInput::name(), "signature-input");
+        assert_eq!(SoapAction::name(), "soapaction");
+        assert_eq!(SourceMap::name(), "sourcemap");
+        assert_eq!(XAccelBuffering::name(), "x-accel-buffering");
+        assert_eq!(XAccelExpires::name(), "x-accel-expires");
+        assert_eq!(XAccelLimitRate::name(), "x-accel-limit-rate");
+        assert_eq!(XAccelRedirect::name(), "x-accel-redirect");
+        assert_eq!(XApiKey::name(), "x-api-key");
+        assert_eq!(XRealIp::name(), "x-real-ip");
+        assert_eq!(XSendfile::name(), "x-sendfile");
+        assert_eq!(XSourceMap::name(), "x-sourcemap");
+    }
+
+    #[test]
+    fn parse_modes() {
+        use headers_core::test_helpers::decode_from_strs_with;
+        use ParseMode;
+
+        fn check<H: ::Header>(values: &[&str], strict: bool, lenient: bool) {
+            let decodes = |mode| decode_from_strs_with::<H>(values, mode).is_some();
+            assert_eq!(
+                (decodes(ParseMode::Strict), decodes(ParseMode::Lenient)),
+                (strict, lenient),
+                "{} {:?}",
+                H::name(),
+                values,
+            );
+        }
+
+        // Only IMF-fixdate is strict, the obsolete formats are lenient.
+        check::<Date>(&["Sun, 06 Nov 1994 08:49:37 GMT"], true, true);
+        check::<Date>(&["Sunday, 06-Nov-94 08:49:37 GMT"], false, true);
+        check::<LastModified>(&["Sun Nov  6 08:49:37 1994"], false, true);
+        check::<Expires>(&["Sun, 06 Nov 1994 08:49:37"], false, true);
+        check::<Date>(&["yesterday"], false, false);
+
+        check::<ContentLength>(&["5"], true, true);
+        check::<ContentLength>(&["5", "5"], false, true);
+        check::<ContentLength>(&["5, 5"], false, true);
+        check::<ContentLength>(&["5, 6"], false, false);
+        check::<ContentLength>(&[" 5"], false, false);
+
+        check::<CacheControl>(&["no-cache, foo=\"bar\""], true, true);
+        check::<CacheControl>(&["no-cache, foo="], false, true);
+        check::<CacheControl>(&["no-cache, foo=\"bar"], false, true);
+        check::<CacheControl>(&["max-age=lots"], false, false);
+
+        check::<ETag>(&["\"xyzzy\""], true, true);
+        check::<ETag>(&["xyzzy"], false, true);
+        check::<ETag>(&["W/xyzzy"], false, true);
+        check::<ETag>(&["xy zzy"], false, false);
+
+        check::<Cookie>(&["a=1; b=\"2\""], true, true);
+        check::<Cookie>(&["a=1 2"], false, true);
+        check::<Cookie>(&["a=\"1 2\""], false, true);
+        check::<Cookie>(&["a=1", "b=2"], false, true);
+
+        check::<ContentMd5>(&["1B2M2Y8AsgTpgAmY7PhCfg=="], true, true);
+        check::<ContentMd5>(&["1B2M2Y8AsgTpgAmY7PhCfg"], false, true);
+        check::<ContentMd5>(&["1B2M2Y8AsgTpgAmY7PhCfg="], false, false);
+
+        check::<MimeVersion>(&["1.0"], true, true);
+        check::<MimeVersion>(&["1.0 (produced by MetaSend Vx.x)"], false, true);
+        check::<MimeVersion>(&["1"], false, false);
+    }
+
+    #[test]
+    fn lenient_etag_adds_quotes() {
+        let etag = test_decode::<ETag>(&["W/xyzzy"]).unwrap();
+        assert!(etag.entity_tag().is_weak());
+        assert_eq!(etag.entity_tag().tag(), b"xyzzy");
+        assert_eq!(test_encode(etag)["etag"], "W/\"xyzzy\"");
+    }
+
+    #[test]
+    fn decode_limits() {
+        use {DecodeError, DecodeLimits, HeaderMap, HeaderMapExt, HeaderValue};
+        use list::Repeated;
+
+        fn map(name: &'static str, values: &[String]) -> HeaderMap {
+            let mut map = HeaderMap::new();
+            for value in values {
+                map.append(name, HeaderValue::from_str(value).unwrap());
+            }
+            map
+        }
+
+        fn check<H: ::Header + Debug>(map: &HeaderMap) {
+            assert!(map.typed_get::<H>().is_none(), "{}", H::name());
+            // Long enough, but too many elements.
+            let long = DecodeLimits {
+                max_value_len: 1 << 20,
+                ..DecodeLimits::default()
+            };
+            assert_eq!(
+                map.typed_get_with_limits::<H>(long).unwrap_err(),
+                DecodeError::TooLarge,
+                "{}",
+                H::name(),
+            );
+            let generous = DecodeLimits {
+                max_value_len: 1 << 20,
+                max_list_items: 100_000,
+            };
+            assert!(map.typed_get_with_limits::<H>(generous).is_ok(), "{}", H::name());
+        }
+
+        let list = |item: &str, sep: &str| {
+            (0..10_000).map(|i| format!("{}{}", item, i)).collect::<Vec<_>>().join(sep)
+        };
+
+        check::<Vary>(&map("vary", &[list("x-", ", ")]));
+        check::<Allow>(&map("allow", &[list("GET", ",")]));
+        check::<CacheControl>(&map("cache-control", &[list("ext", ", ")]));
+        check::<AcceptPatch>(&map("accept-patch", &[list("text/x-", ",")]));
+        check::<Cookie>(&map("cookie", &[list("a=", "; ")]));
+        check::<Via>(&map("via", &[list("1.1 proxy", ", ")]));
+
+        let lines = (0..10_000).map(|i| format!("a={}", i)).collect::<Vec<_>>();
+        check::<SetCookie>(&map("set-cookie", &lines));
+        check::<Repeated<SetCookie>>(&map("set-cookie", &lines));
+
+        // Spread over several lines, it's the same list.
+        let lines = (0..10_000).map(|i| format!("x-{}", i)).collect::<Vec<_>>();
+        check::<Vary>(&map("vary", &lines));
+
+        // Long values aren't decoded at all.
+        let cookie = map("cookie", &[format!("a={}", "x".repeat(1 << 20))]);
+        assert!(cookie.typed_get::<Cookie>().is_none());
+        assert_eq!(cookie.typed_get_with_limits::<Cookie>(DecodeLimits::default()).unwrap_err(), DecodeError::TooLarge);
+        let limits = DecodeLimits {
+            max_value_len: 2 << 20,
+            ..DecodeLimits::default()
+        };
+        assert_eq!(cookie.typed_get_with_limits::<Cookie>(limits).unwrap().get("a").unwrap().len(), 1 << 20);
+
+        // The other errors are reported too.
+        let limits = DecodeLimits::default();
+        assert_eq!(HeaderMap::new().typed_get_with_limits::<Vary>(limits), Err(DecodeError::Missing));
+        assert_eq!(
+            map("content-length", &["nope".to_owned()]).typed_get_with_limits::<ContentLength>(limits),
+            Err(DecodeError::Invalid),
+        );
+    }
+
+    #[test]
+    fn typed_merge() {
+        use {HeaderMap, HeaderMapExt, HeaderValue};
+
+        let mut map = HeaderMap::new();
+        map.typed_merge("accept-encoding".parse::<Vary>().unwrap());
+        assert_eq!(map["vary"], "accept-encoding");
+
+        // Several lines are merged into one.
+        map.append("vary", HeaderValue::from_static("cookie"));
+        map.typed_merge("Accept-Encoding, origin".parse::<Vary>().unwrap());
+        assert_eq!(map.get_all("vary").iter().collect::<Vec<_>>(), ["accept-encoding, cookie, origin"]);
+
+        map.typed_merge(Vary::any());
+        assert_eq!(map["vary"], "*");
+        map.typed_merge("origin".parse::<Vary>().unwrap());
+        assert_eq!(map["vary"], "*");
+
+        map.typed_merge(Connection::keep_alive());
+        map.typed_merge(Connection::upgrade());
+        assert_eq!(map["connection"], "keep-alive, upgrade");
+
+        map.typed_merge(CacheControl::NO_CACHE);
+        map.typed_merge(CacheControl::new().with_max_age(Duration::from_secs(60)));
+        assert_eq!(map["cache-control"], "no-cache, max-age=60");
+    }
+
+    #[test]
+    fn typed_merge_replaces_malformed() {
+        use {HeaderMap, HeaderMapExt, HeaderValue};
+
+        let mut map = HeaderMap::new();
+        map.insert("allow", HeaderValue::from_static("GET"));
+        map.append("allow", HeaderValue::from_bytes(b"\xff").unwrap());
+        map.typed_merge(vec![::http::Method::POST].into_iter().collect::<Allow>());
+        assert_eq!(map.get_all("allow").iter().collect::<Vec<_>>(), ["POST"]);
+
+        map.insert("cache-control", HeaderValue::from_static(""));
+        map.typed_merge(CacheControl::NO_STORE);
+        assert_eq!(map["cache-control"], "no-store");
+    }
+
+    #[cfg(feature = "canonical")]
+    #[test]
+    fn canonical_encode() {
+        use http::Method;
+        use {HeaderMap, HeaderMapExt};
+
+        let mut map = HeaderMap::new();
+        map.typed_insert("Origin, accept".parse::<Vary>().unwrap());
+        map.typed_insert(vec![Method::PUT, Method::GET, Method::PUT].into_iter().collect::<Allow>());
+        map.typed_insert("x-b, x-a".parse::<AccessControlExposeHeaders>().unwrap());
+        assert_eq!(map["vary"], "accept, origin");
+        assert_eq!(map["allow"], "GET, PUT");
+        assert_eq!(map["access-control-expose-headers"], "x-a, x-b");
+
+        // Ordered lists are left alone.
+        map.typed_insert("gzip, br".parse::<ContentEncoding>().unwrap());
+        assert_eq!(map["content-encoding"], "gzip, br");
+    }
+}
@@ -1,7 +1,12 @@
+use std::error::Error as StdError;
+use std::fmt;
+use std::str::FromStr;
+
 use bytes::Bytes;
 use headers_core::decode::TryFromValues;
 use http::uri::{self, Authority, Scheme, Uri};
 use ::{HeaderValue};
+use super::host::{hostname_matches, split_port};
 
 /// The `Origin` header.
 ///
@@ -36,19 +41,69 @@ impl Origin {
 
     /// Checks if `Origin` is `null`.
     pub fn is_null(&self) -> bool {
-        match self {
-            &Origin(OriginOrNull::Null) => true,
-            _ => false,
+        matches!(self.0, OriginOrNull::Null)
+    }
+
+    /// Get the scheme, such as `https`, or `""` for `null`.
+    pub fn scheme(&self) -> &str {
+        match self.0 {
+            OriginOrNull::Origin(ref scheme, _) => scheme.as_str(),
+            OriginOrNull::Null => "",
         }
     }
 
+    /// Get the hostname, such as `example.com`, or `""` for `null`.
+    pub fn hostname(&self) -> &str {
+        match self.0 {
+            OriginOrNull::Origin(_, ref auth) => auth.host(),
+            OriginOrNull::Null => "",
+        }
+    }
+
+    /// Get the port, if there's one.
+    pub fn port(&self) -> Option<u16> {
+        match self.0 {
+            OriginOrNull::Origin(_, ref auth) => auth.port_part().map(|p| p.as_u16()),
+            OriginOrNull::Null => None,
+        }
+    }
+
+    /// Check if this origin is allowed by `pattern`.
+    ///
+    /// The `null` origin never matches.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # extern crate headers_ext as headers;
+    /// use headers::{Origin, OriginPattern};
+    ///
+    /// let pattern = OriginPattern::parse("https://*.example.com").unwrap();
+    ///
+    /// assert!("https://api.example.com".parse::<Origin>().unwrap().matches(&pattern));
+    /// assert!("https://api.example.com:443".parse::<Origin>().unwrap().matches(&pattern));
+    /// assert!(!"http://api.example.com".parse::<Origin>().unwrap().matches(&pattern));
+    /// assert!(!Origin::NULL.matches(&pattern));
+    /// ```
+    pub fn matches(&self, pattern: &OriginPattern) -> bool {
+        if self.is_null() || !self.scheme().eq_ignore_ascii_case(&pattern.scheme) {
+            return false;
+        }
+        let default_port = default_port(&pattern.scheme);
+        let port = self.port().or(default_port);
+        let pattern_port = pattern.port.or(default_port);
+        port == pattern_port && hostname_matches(self.hostname(), &pattern.host)
+    }
+
     // Used in AccessControlAllowOrigin
+    #[cfg(feature = "cors")]
     pub(super) fn try_from_value(value: &HeaderValue) -> Option<Self> {
         OriginOrNull::try_from_value(value)
             .map(Origin)
     }
 
-    pub(super) fn into_value(&self) -> HeaderValue {
+    #[cfg(feature = "cors")]
+    pub(super) fn to_value(&self) -> HeaderValue {
         (&self.0).into()
     }
 }
@@ -63,19 +118,26 @@ impl OriginOrNull {
 
         let uri = Uri::from_shared(bytes).ok()?;
 
+        // The parsed URI gets a path of `/` even when there's none, so
+        // check nothing else was there by serializing it again.
         let (scheme, auth) = match uri.into_parts() {
             uri::Parts {
                 scheme: Some(scheme),
                 authority: Some(auth),
-                path_and_query: None,
                 ..
             } => (scheme, auth),
             _ => {
                 return None;
             }
         };
-
-        Some(OriginOrNull::Origin(scheme, auth))
+        let origin = OriginOrNull::Origin(scheme, auth);
+        if HeaderValue::from(&origin).as_bytes().eq_ignore_ascii_case(value.as_bytes())
+            && !origin_has_userinfo(&origin)
+        {
+            Some(origin)
+        } else {
+            None
+        }
     }
 }
 
@@ -103,6 +165,284 @@ impl<'a> From<&'a OriginOrNull> for HeaderValue {
     }
 }
 
+fn origin_has_userinfo(origin: &OriginOrNull) -> bool {
+    match *origin {
+        OriginOrNull::Origin(_, ref auth) => auth.as_str().contains('@'),
+        OriginOrNull::Null => false,
+    }
+}
+
+fn default_port(scheme: &str) -> Option<u16> {
+    match scheme {
+        "http" | "ws" => Some(80),
+        "https" | "wss" => Some(443),
+        _ => None,
+    }
+}
+
+/// A pattern of allowed origins, such as for CORS.
+///
+/// A pattern is a scheme, a host and an optional port, like an origin,
+/// such as `https://example.com` or `http://localhost:8080`. The host can
+/// also be a wildcard for a single label, such as `https://*.example.com`,
+/// or an IP address.
+///
+/// An origin matches if:
+///
+/// - its scheme is the same, so `http` and `https` don't match each
+///   other,
+/// - its host matches, as in [`Host::matches`](struct.Host.html#method.matches),
+/// - its port is the same, where a missing port is the default port of
+///   the scheme, so `https://example.com` and `https://example.com:443`
+///   match each other.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct OriginPattern {
+    scheme: String,
+    host: String,
+    port: Option<u16>,
+}
+
+/// An error returned when an `OriginPattern` is invalid.
+#[derive(Debug)]
+pub struct InvalidOriginPattern(&'static str);
+
+impl OriginPattern {
+    /// Parse an origin pattern.
+    ///
+    /// Patterns are ASCII, with no path, query or user info. The `null`
+    /// origin, and `*` for any origin, aren't patterns.
+    pub fn parse(s: &str) -> Result<OriginPattern, InvalidOriginPattern> {
+        if !s.is_ascii() {
+            return Err(InvalidOriginPattern("isn't ASCII"));
+        }
+        let i = s.find("://").ok_or(InvalidOriginPattern("has no scheme"))?;
+        let (scheme, rest) = (&s[..i], &s[i + 3..]);
+
+        let mut chars = scheme.chars();
+        let valid_scheme = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.');
+        if !valid_scheme {
+            return Err(InvalidOriginPattern("has an invalid scheme"));
+        }
+        if rest.contains(['/', '?', '#']) {
+            return Err(InvalidOriginPattern("has a path"));
+        }
+        if rest.contains('@') {
+            return Err(InvalidOriginPattern("has user info"));
+        }
+
+        let (host, port) = split_port(rest).ok_or(InvalidOriginPattern("has an invalid port"))?;
+        let label = |label: &str| {
+            !label.is_empty() && label.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+        };
+        let hostname = host.strip_prefix("*.").unwrap_or(host);
+        let valid_host = if host.parse::<::std::net::IpAddr>().is_ok() {
+            // IPv6 addresses need their brackets.
+            !host.contains(':') || rest.starts_with('[')
+        } else {
+            hostname.strip_suffix('.').unwrap_or(hostname).split('.').all(label)
+        };
+        if !valid_host {
+            return Err(InvalidOriginPattern("has an invalid host"));
+        }
+
+        Ok(OriginPattern {
+            scheme: scheme.to_ascii_lowercase(),
+            host: host.to_owned(),
+            port,
+        })
+    }
+}
+
+impl FromStr for OriginPattern {
+    type Err = InvalidOriginPattern;
+
+    fn from_str(s: &str) -> Result<OriginPattern, InvalidOriginPattern> {
+        OriginPattern::parse(s)
+    }
+}
+
+impl fmt::Display for OriginPattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}://", self.scheme)?;
+        if self.host.contains(':') {
+            write!(f, "[{}]", self.host)?;
+        } else {
+            f.write_str(&self.host)?;
+        }
+        match self.port {
+            Some(port) => write!(f, ":{}", port),
+            None => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for InvalidOriginPattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid origin pattern: {}", self.0)
+    }
+}
+
+impl StdError for InvalidOriginPattern {}
+
+#[cfg(test)]
+mod tests {
+    use super::{Origin, OriginPattern};
+    use super::super::{test_decode, test_encode};
+
+    fn origin(s: &str) -> Origin {
+        test_decode(&[s]).unwrap_or_else(|| panic!("{:?} should decode", s))
+    }
+
+    fn pattern(s: &str) -> OriginPattern {
+        OriginPattern::parse(s).unwrap()
+    }
+
+    #[test]
+    fn decode() {
+        let o = origin("https://example.com");
+        assert_eq!((o.scheme(), o.hostname(), o.port()), ("https", "example.com", None));
+        assert_eq!(test_encode(o)["origin"], "https://example.com");
+
+        let o = origin("http://[::1]:8080");
+        assert_eq!((o.scheme(), o.hostname(), o.port()), ("http", "[::1]", Some(8080)));
+
+        assert!(origin("null").is_null());
+        assert_eq!(Origin::NULL.scheme(), "");
+    }
+
+    #[test]
+    fn decode_invalid() {
+        for s in &["", "example.com", "https://", "https://example.com/", "https://example.com/a",
+                   "https://example.com?q", "https://user@example.com", "Null", "*"] {
+            assert_eq!(test_decode::<Origin>(&[s]), None, "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn parse_pattern() {
+        assert_eq!(pattern("HTTPS://Example.com").to_string(), "https://Example.com");
+        assert_eq!(pattern("https://*.example.com:8443").to_string(), "https://*.example.com:8443");
+        assert_eq!(pattern("http://[::1]:3000").to_string(), "http://[::1]:3000");
+        assert_eq!(pattern("http://127.0.0.1").to_string(), "http://127.0.0.1");
+        assert!("https://example.com".parse::<OriginPattern>().is_ok());
+    }
+
+    #[test]
+    fn parse_pattern_invalid() {
+        let cases = &[
+            ("*", "has no scheme"),
+            ("null", "has no scheme"),
+            ("example.com", "has no scheme"),
+            ("://example.com", "has an invalid scheme"),
+            ("1http://example.com", "has an invalid scheme"),
+            ("*://example.com", "has an invalid scheme"),
+            ("https://example.com/", "has a path"),
+            ("https://example.com?x", "has a path"),
+            ("https://user@example.com", "has user info"),
+            ("https://example.com:", "has an invalid port"),
+            ("https://example.com:99999", "has an invalid port"),
+            ("https://example.com:*", "has an invalid port"),
+            ("https://", "has an invalid host"),
+            ("https://*", "has an invalid host"),
+            ("https://*.", "has an invalid host"),
+            ("https://a.*.com", "has an invalid host"),
+            ("https://*.*.com", "has an invalid host"),
+            ("https://exa mple.com", "has an invalid host"),
+            ("https://a..com", "has an invalid host"),
+            ("http://::1", "has an invalid host"),
+            ("https://exämple.com", "isn't ASCII"),
+        ];
+        for &(s, reason) in cases {
+            let err = OriginPattern::parse(s).unwrap_err();
+            assert_eq!(err.to_string(), format!("invalid origin pattern: {}", reason), "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn matches_exact() {
+        let p = pattern("https://example.com");
+        assert!(origin("https://example.com").matches(&p));
+        assert!(origin("https://EXAMPLE.com").matches(&p));
+        assert!(!origin("https://example.org").matches(&p));
+        assert!(!origin("https://www.example.com").matches(&p));
+        assert!(!origin("https://example.com.evil.com").matches(&p));
+    }
+
+    #[test]
+    fn matches_scheme() {
+        let p = pattern("https://example.com");
+        assert!(!origin("http://example.com").matches(&p));
+        assert!(!origin("wss://example.com").matches(&p));
+        assert!(origin("HTTPS://example.com").matches(&p));
+        assert!(origin("http://example.com").matches(&pattern("HTTP://example.com")));
+    }
+
+    #[test]
+    fn matches_default_ports() {
+        let p = pattern("https://example.com");
+        assert!(origin("https://example.com:443").matches(&p));
+        assert!(!origin("https://example.com:80").matches(&p));
+        assert!(!origin("https://example.com:8443").matches(&p));
+
+        let p = pattern("https://example.com:443");
+        assert!(origin("https://example.com").matches(&p));
+        assert!(origin("https://example.com:443").matches(&p));
+
+        let p = pattern("http://example.com");
+        assert!(origin("http://example.com:80").matches(&p));
+        assert!(!origin("http://example.com:443").matches(&p));
+
+        let p = pattern("ws://example.com:80");
+        assert!(origin("ws://example.com").matches(&p));
+    }
+
+    #[test]
+    fn matches_other_ports() {
+        let p = pattern("http://localhost:3000");
+        assert!(origin("http://localhost:3000").matches(&p));
+        assert!(!origin("http://localhost").matches(&p));
+        assert!(!origin("http://localhost:3001").matches(&p));
+
+        // Without a default port, both must have the same one, or none.
+        let p = pattern("app://local");
+        assert!(origin("app://local").matches(&p));
+        assert!(!origin("app://local:1").matches(&p));
+        assert!(origin("app://local:1").matches(&pattern("app://local:1")));
+    }
+
+    #[test]
+    fn matches_wildcard() {
+        let p = pattern("https://*.example.com");
+        assert!(origin("https://api.example.com").matches(&p));
+        assert!(origin("https://API.example.com:443").matches(&p));
+        assert!(!origin("https://example.com").matches(&p));
+        assert!(!origin("https://a.b.example.com").matches(&p));
+        assert!(!origin("https://evilexample.com").matches(&p));
+        assert!(!origin("https://api.example.com.evil.com").matches(&p));
+        assert!(!origin("http://api.example.com").matches(&p));
+        assert!(!origin("https://api.example.com:8443").matches(&p));
+        assert!(origin("https://api.example.com:8443").matches(&pattern("https://*.example.com:8443")));
+    }
+
+    #[test]
+    fn matches_ip() {
+        assert!(origin("http://127.0.0.1:8080").matches(&pattern("http://127.0.0.1:8080")));
+        assert!(!origin("http://127.0.0.2:8080").matches(&pattern("http://127.0.0.1:8080")));
+        assert!(origin("http://[::1]").matches(&pattern("http://[0:0:0:0:0:0:0:1]")));
+        assert!(origin("http://[::1]:80").matches(&pattern("http://[::1]")));
+        assert!(!origin("http://localhost").matches(&pattern("http://127.0.0.1")));
+    }
+
+    #[test]
+    fn null_never_matches() {
+        for p in &["https://example.com", "https://*.example.com", "http://localhost"] {
+            assert!(!Origin::NULL.matches(&pattern(p)), "{:?}", p);
+            assert!(!origin("null").matches(&pattern(p)), "{:?}", p);
+        }
+    }
+}
+
 /*
 #[cfg(test)]
 mod tests {
@@ -1,4 +1,5 @@
 use ::HeaderValue;
+use util::token;
 
 /// The `Pragma` header defined by HTTP/1.0.
 ///
@@ -34,7 +35,7 @@ impl Pragma {
 
     /// Return whether this pragma is `no-cache`.
     pub fn is_no_cache(&self) -> bool {
-        self.0 == "no-cache"
+        token::eq_ignore_ascii_case_trimmed(self.0.as_bytes(), "no-cache")
     }
 }
 
@@ -0,0 +1,150 @@
+
+use util::structured::{self, BareItem, Dictionary, Item, Member};
+
+/// `Priority` header, defined in
+/// [RFC9218](https://tools.ietf.org/html/rfc9218#section-5)
+///
+/// The `Priority` header lets a client signal how it would like a
+/// response to be scheduled relative to others on the same connection,
+/// and lets a server tell intermediaries the priority it used.
+///
+/// # ABNF
+///
+/// ```text
+/// Priority   = sf-dictionary
+/// ; u = sf-integer between 0 and 7, defaults to 3
+/// ; i = sf-boolean, defaults to false
+/// ```
+///
+/// # Example values
+///
+/// * `u=0`
+/// * `u=5, i`
+///
+/// # Notes
+///
+/// * Unknown parameters, and known ones with an invalid value, are
+///   ignored.
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::Priority;
+///
+/// let priority = Priority::new(1).with_incremental(true);
+/// assert_eq!(priority.urgency(), 1);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Priority {
+    urgency: u8,
+    incremental: bool,
+}
+
+const DEFAULT_URGENCY: u8 = 3;
+const MAX_URGENCY: u8 = 7;
+
+impl Priority {
+    /// Create a non-incremental `Priority` with the given urgency.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `urgency` is greater than 7.
+    pub fn new(urgency: u8) -> Priority {
+        assert!(urgency <= MAX_URGENCY, "urgency must be between 0 and 7: {}", urgency);
+        Priority {
+            urgency,
+            incremental: false,
+        }
+    }
+
+    /// Set whether the response can be processed incrementally.
+    pub fn with_incremental(mut self, incremental: bool) -> Self {
+        self.incremental = incremental;
+        self
+    }
+
+    /// Get the urgency, from 0 (highest) to 7 (lowest).
+    pub fn urgency(&self) -> u8 {
+        self.urgency
+    }
+
+    /// Get whether the response can be processed incrementally.
+    pub fn incremental(&self) -> bool {
+        self.incremental
+    }
+}
+
+impl Default for Priority {
+    fn default() -> Priority {
+        Priority::new(DEFAULT_URGENCY)
+    }
+}
+
+impl ::Header for Priority {
+    fn name() -> &'static ::HeaderName {
+        static_header_name!("priority")
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        let dict = structured::parse_dictionary(values)?;
+        let bare = |key| dict.get(key).and_then(Member::as_item).map(|item| &item.bare);
+
+        let urgency = bare("u")
+            .and_then(BareItem::as_integer)
+            .filter(|&u| (0..=i64::from(MAX_URGENCY)).contains(&u))
+            .map(|u| u as u8)
+            .unwrap_or(DEFAULT_URGENCY);
+        let incremental = bare("i")
+            .and_then(BareItem::as_bool)
+            .unwrap_or(false);
+
+        Some(Priority {
+            urgency,
+            incremental,
+        })
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        let mut dict = Dictionary::default();
+        dict.insert("u", Member::Item(Item::new(BareItem::Integer(self.urgency.into()))));
+        if self.incremental {
+            dict.insert("i", Member::Item(Item::new(BareItem::Boolean(true))));
+        }
+        values.append_fmt(dict);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Priority;
+    use super::super::{test_decode, test_encode};
+
+    #[test]
+    fn decode() {
+        assert_eq!(test_decode::<Priority>(&["u=5, i"]), Some(Priority::new(5).with_incremental(true)));
+        assert_eq!(test_decode::<Priority>(&["i=?0", "u=1"]), Some(Priority::new(1)));
+        assert_eq!(test_decode::<Priority>(&[""]), Some(Priority::default()));
+    }
+
+    #[test]
+    fn decode_ignores_invalid_params() {
+        assert_eq!(test_decode::<Priority>(&["u=8, i=1, x=(a b)"]), Some(Priority::default()));
+        assert_eq!(test_decode::<Priority>(&["u=-1"]), Some(Priority::default()));
+    }
+
+    #[test]
+    fn decode_invalid_dictionary() {
+        assert_eq!(test_decode::<Priority>(&["u=1,"]), None);
+        assert_eq!(test_decode::<Priority>(&["U=1"]), None);
+    }
+
+    #[test]
+    fn encode() {
+        let headers = test_encode(Priority::new(0).with_incremental(true));
+        assert_eq!(headers["priority"], "u=0, i");
+
+        let headers = test_encode(Priority::default());
+        assert_eq!(headers["priority"], "u=3");
+    }
+}
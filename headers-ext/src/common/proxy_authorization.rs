@@ -1,4 +1,5 @@
-use super::authorization::{Authorization, Credentials};
+use {HeaderValue};
+use super::authorization::{Authorization, Basic, Bearer, Credentials};
 
 /// `Proxy-Authorization` header, defined in [RFC7235](https://tools.ietf.org/html/rfc7235#section-4.4)
 ///
@@ -21,11 +22,39 @@ use super::authorization::{Authorization, Credentials};
 ///
 /// # Examples
 ///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::{Authorization, ProxyAuthorization};
+///
+/// let proxy = ProxyAuthorization(Authorization::basic("Aladdin", "open sesame").0);
+/// assert!(proxy.secure_eq("Aladdin", "open sesame"));
+/// ```
+///
+/// Like `Authorization`, the credentials are compared in constant time by
+/// `secure_eq` and `==`, as best as can be done.
 #[derive(Clone, PartialEq, Debug)]
 pub struct ProxyAuthorization<C: Credentials>(pub C);
 
+impl ProxyAuthorization<Basic> {
+    /// Check if the username and password are the expected ones, in
+    /// constant time, like `Basic::secure_eq`.
+    pub fn secure_eq(&self, username: &str, password: &str) -> bool {
+        self.0.secure_eq(username, password)
+    }
+}
+
+impl ProxyAuthorization<Bearer> {
+    /// Check if the token is `expected`, in constant time, like
+    /// `Bearer::secure_eq`.
+    pub fn secure_eq(&self, expected: &str) -> bool {
+        self.0.secure_eq(expected)
+    }
+}
+
 impl<C: Credentials> ::Header for ProxyAuthorization<C> {
-    const NAME: &'static ::HeaderName = &::http::header::PROXY_AUTHORIZATION;
+    fn name() -> &'static ::HeaderName {
+        &::http::header::PROXY_AUTHORIZATION
+    }
 
     fn decode(values: &mut ::Values) -> Option<Self> {
         Authorization::decode(values)
@@ -45,3 +74,19 @@ impl<C: Credentials> ::Header for ProxyAuthorization<C> {
     }
 }
 
+impl<C: Credentials> ::SingleValueHeader for ProxyAuthorization<C> {}
+
+impl<'a, C: Credentials> ::std::convert::TryFrom<&'a HeaderValue> for ProxyAuthorization<C> {
+    type Error = ::InvalidHeader;
+
+    fn try_from(value: &'a HeaderValue) -> Result<Self, ::InvalidHeader> {
+        ::headers_core::decode::from_value(value)
+    }
+}
+
+impl<'a, C: Credentials> From<&'a ProxyAuthorization<C>> for HeaderValue {
+    fn from(header: &'a ProxyAuthorization<C>) -> HeaderValue {
+        ::headers_core::encode::to_value(header)
+    }
+}
+
@@ -0,0 +1,251 @@
+use std::fmt;
+use std::iter::FromIterator;
+
+use http::StatusCode;
+
+use util::SmallList;
+use util::structured::{self, identifier, BareItem, Item};
+
+/// `Proxy-Status` header, defined in
+/// [RFC9209](https://tools.ietf.org/html/rfc9209)
+///
+/// The `Proxy-Status` response header field allows an intermediary to
+/// convey additional information about its handling of a response and
+/// its associated request. Each intermediary appends an entry, so the
+/// last entry is the proxy closest to the client.
+///
+/// # ABNF
+///
+/// ```text
+/// Proxy-Status   = sf-list
+/// ; each member is a proxy identifier (sf-string or sf-token) with
+/// ; parameters: error, next-hop, next-protocol, received-status, details
+/// ```
+///
+/// # Example values
+///
+/// * `ExampleCDN`
+/// * `SomeReverseProxy; error=http_protocol_error; details="Malformed response header: space before colon"`
+/// * `proxy.example.net; next-hop=origin.example.com; received-status=503`
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::{ProxyStatus, ProxyStatusEntry};
+///
+/// let status = vec![
+///     ProxyStatusEntry::new("ExampleCDN")
+///         .with_error("connection_timeout"),
+/// ].into_iter().collect::<ProxyStatus>();
+///
+/// assert_eq!(status.last().unwrap().error(), Some("connection_timeout"));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProxyStatus(SmallList<ProxyStatusEntry>);
+
+/// A single intermediary's entry in a `Proxy-Status` header.
+///
+/// Parameters this type doesn't know about are kept, and encoded again
+/// unchanged.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProxyStatusEntry(Item);
+
+impl ProxyStatus {
+    /// Returns an iterator over the entries, in the order the
+    /// intermediaries handled the response.
+    pub fn iter(&self) -> ::list::Iter<'_, ProxyStatusEntry> {
+        ::list::Iter::new(self.0.iter())
+    }
+
+    fn into_items(self) -> ::list::IntoIter<ProxyStatusEntry> {
+        ::list::IntoIter::new(self.0.into_iter())
+    }
+
+    /// Returns the entry of the intermediary closest to the client.
+    pub fn last(&self) -> Option<&ProxyStatusEntry> {
+        self.0.last()
+    }
+}
+
+impl_collection!(ProxyStatus, ProxyStatusEntry, ordered);
+
+impl ProxyStatusEntry {
+    /// Create an entry for the intermediary identified by `proxy`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `proxy` contains characters other than printable ASCII.
+    pub fn new(proxy: &str) -> ProxyStatusEntry {
+        ProxyStatusEntry(Item::new(identifier(proxy)))
+    }
+
+    /// Get the identifier of the intermediary that added this entry.
+    pub fn proxy(&self) -> &str {
+        match self.0.bare {
+            BareItem::String(ref s) |
+            BareItem::Token(ref s) => s,
+            _ => unreachable!("ProxyStatusEntry identifier is checked on decode"),
+        }
+    }
+
+    /// Get the proxy error type, such as `dns_timeout`, from the `error`
+    /// parameter.
+    pub fn error(&self) -> Option<&str> {
+        self.0.params.get("error")?.as_token()
+    }
+
+    /// Get the `next-hop` parameter.
+    pub fn next_hop(&self) -> Option<&str> {
+        match *self.0.params.get("next-hop")? {
+            BareItem::String(ref s) |
+            BareItem::Token(ref s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Get the ALPN protocol identifier used with the next hop, from the
+    /// `next-protocol` parameter.
+    pub fn next_protocol(&self) -> Option<&str> {
+        self.0.params.get("next-protocol")?.as_token()
+    }
+
+    /// Get the status code received from the next hop, from the
+    /// `received-status` parameter.
+    pub fn received_status(&self) -> Option<StatusCode> {
+        let code = self.0.params.get("received-status")?.as_integer()?;
+        if code < 0 || code > i64::from(u16::MAX) {
+            return None;
+        }
+        StatusCode::from_u16(code as u16).ok()
+    }
+
+    /// Get the human-readable `details` parameter.
+    pub fn details(&self) -> Option<&str> {
+        self.0.params.get("details")?.as_str()
+    }
+
+    /// Set the `error` parameter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `error` is not a valid token.
+    pub fn with_error(mut self, error: &str) -> Self {
+        assert!(structured::is_token(error), "invalid Proxy-Status error: {:?}", error);
+        self.0.params.insert("error", BareItem::Token(error.into()));
+        self
+    }
+
+    /// Set the `next-hop` parameter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `next_hop` contains characters other than printable ASCII.
+    pub fn with_next_hop(mut self, next_hop: &str) -> Self {
+        self.0.params.insert("next-hop", identifier(next_hop));
+        self
+    }
+
+    /// Set the `received-status` parameter.
+    pub fn with_received_status(mut self, status: StatusCode) -> Self {
+        self.0.params.insert("received-status", BareItem::Integer(status.as_u16().into()));
+        self
+    }
+
+    /// Set the `details` parameter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `details` contains characters other than printable ASCII.
+    pub fn with_details(mut self, details: &str) -> Self {
+        assert!(structured::is_string(details), "invalid Proxy-Status details: {:?}", details);
+        self.0.params.insert("details", BareItem::String(details.into()));
+        self
+    }
+
+    fn from_item(item: Item) -> Option<ProxyStatusEntry> {
+        match item.bare {
+            BareItem::String(_) |
+            BareItem::Token(_) => Some(ProxyStatusEntry(item)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ProxyStatusEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl ::Header for ProxyStatus {
+    fn name() -> &'static ::HeaderName {
+        static_header_name!("proxy-status")
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        let entries = structured::parse_list(values)?
+            .into_iter()
+            .map(|member| member.into_item().and_then(ProxyStatusEntry::from_item))
+            .collect::<Option<SmallList<_>>>()?;
+
+        if entries.is_empty() {
+            None
+        } else {
+            Some(ProxyStatus(entries))
+        }
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        values.append_fmt(structured::List(&self.0));
+    }
+}
+
+impl FromIterator<ProxyStatusEntry> for ProxyStatus {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = ProxyStatusEntry>,
+    {
+        ProxyStatus(iter.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{test_decode, test_encode};
+
+    #[test]
+    fn decode() {
+        let status = test_decode::<ProxyStatus>(&[
+            "ExampleCDN",
+            "SomeReverseProxy; error=http_protocol_error; next-hop=\"10.0.0.1:80\"; received-status=502; details=\"bad header\"",
+        ]).unwrap();
+
+        let first = status.iter().next().unwrap();
+        assert_eq!(first.proxy(), "ExampleCDN");
+        assert_eq!(first.error(), None);
+
+        let last = status.last().unwrap();
+        assert_eq!(last.proxy(), "SomeReverseProxy");
+        assert_eq!(last.error(), Some("http_protocol_error"));
+        assert_eq!(last.next_hop(), Some("10.0.0.1:80"));
+        assert_eq!(last.received_status(), Some(StatusCode::BAD_GATEWAY));
+        assert_eq!(last.details(), Some("bad header"));
+    }
+
+    #[test]
+    fn encode() {
+        let status = vec![
+            ProxyStatusEntry::new("proxy.example.net")
+                .with_next_hop("origin.example.com")
+                .with_received_status(StatusCode::SERVICE_UNAVAILABLE),
+        ].into_iter().collect::<ProxyStatus>();
+
+        let headers = test_encode(status);
+        assert_eq!(
+            headers["proxy-status"],
+            "proxy.example.net;next-hop=origin.example.com;received-status=503",
+        );
+    }
+}
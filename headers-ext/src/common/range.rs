@@ -1,5 +1,9 @@
+use std::error::Error as StdError;
+use std::fmt;
 use std::ops::Bound;
 
+use util::RangeUnit;
+
 /// `Range` header, defined in [RFC7233](https://tools.ietf.org/html/rfc7233#section-3.1)
 ///
 /// The "Range" header field on a GET request modifies the method
@@ -10,7 +14,7 @@ use std::ops::Bound;
 /// # ABNF
 ///
 /// ```text
-/// Range =	byte-ranges-specifier / other-ranges-specifier
+/// Range =    byte-ranges-specifier / other-ranges-specifier
 /// other-ranges-specifier = other-range-unit "=" other-range-set
 /// other-range-set = 1*VCHAR
 ///
@@ -33,24 +37,82 @@ use std::ops::Bound;
 /// # Examples
 ///
 /// ```
+/// # extern crate headers_ext as headers;
+/// use headers::{Range, RangeUnit};
+///
+/// // A page of a collection, in a unit of the API.
+/// let range = Range::new(RangeUnit::Other("items".into()), "0-49").unwrap();
+/// assert_eq!(range.to_string(), "items=0-49");
+/// assert_eq!(range.specs(), "0-49");
+///
+/// let bytes = "bytes=0-499".parse::<Range>().unwrap();
+/// assert!(bytes.unit().is_bytes());
 /// ```
 #[derive(Clone, Debug, PartialEq)]
 pub struct Range(::HeaderValue);
 
+/// An error returned when a range isn't valid for its unit.
+#[derive(Debug)]
+pub struct InvalidRange(pub(crate) ());
+
 impl Range {
+    /// Create a `Range` of the range set `specs`, in `unit`.
+    ///
+    /// The specs of `bytes` must be a valid `byte-range-set`, such as
+    /// `0-499,-500`. Those of other units are kept as they are, and only
+    /// need to be visible ASCII. The `none` unit fails.
+    pub fn new(unit: RangeUnit, specs: &str) -> Result<Range, InvalidRange> {
+        let valid = match unit {
+            RangeUnit::Bytes => is_byte_range_set(specs),
+            RangeUnit::None => false,
+            RangeUnit::Other(_) => !specs.is_empty() && specs.bytes().all(|b| b.is_ascii_graphic()),
+        };
+        if !valid {
+            return Err(InvalidRange(()));
+        }
+        ::HeaderValue::from_str(&format!("{}={}", unit, specs))
+            .map(Range)
+            .map_err(|_| InvalidRange(()))
+    }
+
+    /// Get the unit of the ranges.
+    pub fn unit(&self) -> RangeUnit {
+        self.split()
+            .0
+            .parse()
+            .expect("valid unit checked in Header::decode()")
+    }
+
+    /// Get the range set, as it was sent, such as `0-499` of
+    /// `bytes=0-499`.
+    pub fn specs(&self) -> &str {
+        self.split().1
+    }
+
     /// Iterate the range sets as a tuple of bounds.
+    ///
+    /// Only `bytes` ranges are parsed, the ranges of other units give
+    /// nothing.
     pub fn iter<'a>(&'a self) -> impl Iterator<Item=(Bound<u64>, Bound<u64>)> + 'a {
-        let s = self.0
-            .to_str()
-            .expect("valid string checked in Header::decode()");
+        let (unit, specs) = self.split();
+        let bytes = unit.eq_ignore_ascii_case("bytes");
 
-        s["bytes=".len()..]
+        specs
             .split(',')
+            .filter(move |_| bytes)
             .filter_map(|spec| {
                 let mut iter = spec.trim().splitn(2, '-');
                 Some((parse_bound(iter.next()?)?, parse_bound(iter.next()?)?))
             })
     }
+
+    fn split(&self) -> (&str, &str) {
+        self.0
+            .to_str()
+            .expect("valid string checked in Header::decode()")
+            .split_once('=')
+            .expect("unit checked in Header::decode()")
+    }
 }
 
 fn parse_bound(s: &str) -> Option<Bound<u64>> {
@@ -61,16 +123,42 @@ fn parse_bound(s: &str) -> Option<Bound<u64>> {
     s.parse().ok().map(Bound::Included)
 }
 
+// Like the ranges `iter` keeps, but without skipping the invalid ones.
+fn is_byte_range_set(specs: &str) -> bool {
+    let mut any = false;
+    for spec in specs.split(',').map(str::trim).filter(|spec| !spec.is_empty()) {
+        let (first, last) = match spec.split_once('-') {
+            Some((first, last)) => (first, last),
+            None => return false,
+        };
+        let digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+        if !digits(first) || !digits(last) {
+            return false;
+        }
+        match (parse_bound(first), parse_bound(last)) {
+            (Some(Bound::Included(first)), Some(Bound::Included(last))) if first <= last => (),
+            (Some(Bound::Included(_)), Some(Bound::Unbounded)) => (),
+            (Some(Bound::Unbounded), Some(Bound::Included(_))) => (),
+            _ => return false,
+        }
+        any = true;
+    }
+    any
+}
+
 impl ::Header for Range {
-    const NAME: &'static ::HeaderName = &::http::header::RANGE;
+    fn name() -> &'static ::HeaderName {
+        &::http::header::RANGE
+    }
 
     fn decode(values: &mut ::Values) -> Option<Self> {
         let val = values.next()?;
 
-        if val.to_str().ok()?.starts_with("bytes=") {
-            Some(Range(val.clone()))
-        } else {
-            None
+        let (unit, specs) = val.to_str().ok()?.split_once('=')?;
+        match unit.parse().ok()? {
+            RangeUnit::None => None,
+            _ if specs.is_empty() => None,
+            _ => Some(Range(val.clone())),
         }
     }
 
@@ -79,6 +167,14 @@ impl ::Header for Range {
     }
 }
 
+impl fmt::Display for InvalidRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid range")
+    }
+}
+
+impl StdError for InvalidRange {}
+
 /*
 
 impl ByteRangeSpec {
@@ -389,3 +485,58 @@ fn test_byte_range_spec_to_satisfiable_range() {
 bench_header!(bytes_multi, Range, { vec![b"bytes=1-1001,2001-3001,10001-".to_vec()]});
 bench_header!(custom_unit, Range, { vec![b"other=0-100000".to_vec()]});
 */
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound;
+
+    use super::Range;
+    use super::super::{test_decode, test_encode};
+    use RangeUnit;
+
+    fn items() -> RangeUnit {
+        RangeUnit::Other("items".into())
+    }
+
+    #[test]
+    fn decode_bytes() {
+        let range = test_decode::<Range>(&["bytes=0-499,-500"]).unwrap();
+        assert_eq!(range.unit(), RangeUnit::Bytes);
+        assert_eq!(range.specs(), "0-499,-500");
+        assert_eq!(
+            range.iter().collect::<Vec<_>>(),
+            [(Bound::Included(0), Bound::Included(499)), (Bound::Unbounded, Bound::Included(500))],
+        );
+        assert_eq!(test_decode::<Range>(&["Bytes=1-"]).unwrap().iter().count(), 1);
+    }
+
+    #[test]
+    fn decode_other_unit() {
+        let range = test_decode::<Range>(&["items=0-49"]).unwrap();
+        assert_eq!(range.unit(), items());
+        assert_eq!(range.specs(), "0-49");
+        assert_eq!(range.iter().count(), 0);
+        assert_eq!(test_encode(range)["range"], "items=0-49");
+    }
+
+    #[test]
+    fn decode_invalid() {
+        for value in &["", "bytes", "bytes=", "=0-1", "none=0-1", "two words=0-1"] {
+            assert_eq!(test_decode::<Range>(&[value]), None, "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn new() {
+        let range = Range::new(RangeUnit::Bytes, "0-499, 1000-").unwrap();
+        assert_eq!(test_encode(range)["range"], "bytes=0-499, 1000-");
+        assert_eq!(Range::new(items(), "0-49").unwrap(), test_decode(&["items=0-49"]).unwrap());
+
+        for specs in &["", "-", "5-1", "a-b", "0-1,2", "+1-2"] {
+            assert!(Range::new(RangeUnit::Bytes, specs).is_err(), "{:?}", specs);
+        }
+        assert!(Range::new(items(), "").is_err());
+        assert!(Range::new(items(), "0 - 49").is_err());
+        assert!(Range::new(RangeUnit::None, "0-49").is_err());
+    }
+}
@@ -1,4 +1,9 @@
-use http::header::HeaderValue;
+use std::error::Error as StdError;
+use std::fmt;
+
+use http::Uri;
+use util::HeaderString;
+use util::uri_reference::{parse_reference, UriReference};
 
 /// `Referer` header, defined in
 /// [RFC7231](http://tools.ietf.org/html/rfc7231#section-5.5.2)
@@ -23,20 +28,192 @@ use http::header::HeaderValue;
 ///
 /// ```
 /// # extern crate headers_ext as headers;
+/// # extern crate http;
 /// use headers::Referer;
 ///
-/// let r = Referer::from_static("/People.html#tim");
+/// let r = Referer::from_static("/People.html");
+///
+/// let uri = "https://user@example.com/People.html#tim".parse::<http::Uri>().unwrap();
+/// let r = Referer::try_from_uri(&uri);
+/// assert_eq!(r.uri().unwrap(), "https://example.com/People.html");
 /// ```
 #[derive(Debug, Clone, PartialEq, Header)]
-pub struct Referer(HeaderValue);
+pub struct Referer(HeaderString);
+
+/// An error returned when a `Referer` isn't a valid URI reference.
+#[derive(Debug)]
+pub struct InvalidReferer(());
 
 impl Referer {
     /// Create a `Referer` with a static string.
     ///
+    /// The string is sent as is, so it shouldn't contain a fragment or
+    /// userinfo.
+    ///
     /// # Panic
     ///
-    /// Panics if the string is not a legal header value.
+    /// Panics if the string is not a legal header value, or in debug
+    /// builds if it isn't a URI reference.
     pub fn from_static(s: &'static str) -> Referer {
-        Referer(HeaderValue::from_static(s))
+        debug_assert!(
+            UriReference::parse(s).is_some(),
+            "Referer::from_static with invalid URI: {:?}",
+            s
+        );
+        Referer(HeaderString::from_static(s))
+    }
+
+    /// Create a `Referer` from a `Uri`, removing its userinfo.
+    ///
+    /// `Uri`s never contain a fragment.
+    pub fn try_from_uri(uri: &Uri) -> Referer {
+        let mut s = String::new();
+        if let Some(scheme) = uri.scheme_part() {
+            s.push_str(scheme.as_str());
+            s.push_str("://");
+        }
+        if let Some(authority) = uri.authority_part() {
+            let host_port = authority
+                .as_str()
+                .rsplit('@')
+                .next()
+                .expect("split always has at least 1 item");
+            s.push_str(host_port);
+        }
+        if let Some(path_and_query) = uri.path_and_query() {
+            s.push_str(path_and_query.as_str());
+        }
+
+        Referer(HeaderString::from_string(s).expect("Uri is a valid HeaderValue"))
+    }
+
+    /// Create a `Referer` from a string, removing its fragment and
+    /// userinfo.
+    ///
+    /// Fails if the string is empty or isn't a valid URI reference.
+    pub fn try_from_str(s: &str) -> Result<Referer, InvalidReferer> {
+        let reference = match UriReference::parse(s) {
+            Some(ref reference) if !s.is_empty() => reference.clone(),
+            _ => return Err(InvalidReferer(())),
+        };
+        let s = reference.without_userinfo().without_fragment().to_string();
+        Ok(Referer(HeaderString::from_string(s).expect("URI reference is a valid HeaderValue")))
+    }
+
+    /// Get the referrer as a `Uri`, if it is a valid one.
+    pub fn uri(&self) -> Option<Uri> {
+        self.0.parse().ok()
+    }
+
+    /// Resolve the referrer against `base`, usually the URI of the request
+    /// it was sent with.
+    ///
+    /// Returns `None` if the referrer isn't a valid URI reference.
+    pub fn resolve(&self, base: &Uri) -> Option<Uri> {
+        parse_reference(&self.0)?.resolve(base)
+    }
+}
+
+/// A view of a `Referer` header that borrows from the `HeaderMap`, for
+/// `HeaderMapExt::typed_get_borrowed`.
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::{HeaderMap, HeaderMapExt, HeaderValue, RefererView};
+///
+/// let mut headers = HeaderMap::new();
+/// headers.insert("referer", HeaderValue::from_static("https://example.com/People.html"));
+///
+/// let referer = headers.typed_get_borrowed::<RefererView>().unwrap();
+/// assert_eq!(referer.as_str(), "https://example.com/People.html");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RefererView<'a>(&'a str);
+
+impl<'a> RefererView<'a> {
+    /// View this referer as a `&str`.
+    pub fn as_str(&self) -> &'a str {
+        self.0
+    }
+
+    /// Get the referer as a `Uri`, if it's one, like `Referer::uri`.
+    pub fn uri(&self) -> Option<Uri> {
+        self.0.parse().ok()
+    }
+}
+
+impl<'a> ::BorrowedHeader<'a> for RefererView<'a> {
+    fn name() -> &'static ::HeaderName {
+        &::http::header::REFERER
+    }
+
+    fn decode_borrowed(values: &mut ::Values<'a>) -> Option<Self> {
+        values.next()?.to_str().ok().map(RefererView)
+    }
+}
+
+impl fmt::Display for InvalidReferer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid Referer URI")
+    }
+}
+
+impl StdError for InvalidReferer {}
+
+#[cfg(test)]
+mod tests {
+    use super::Referer;
+    use super::super::{test_decode, test_encode};
+
+    #[test]
+    fn try_from_str_strips_fragment_and_userinfo() {
+        let r = Referer::try_from_str("http://user:pw@example.com:8080/a/b?x=1#frag").unwrap();
+        let headers = test_encode(r);
+        assert_eq!(headers["referer"], "http://example.com:8080/a/b?x=1");
+
+        let r = Referer::try_from_str("/People.html#tim").unwrap();
+        assert_eq!(r, Referer::from_static("/People.html"));
+    }
+
+    #[test]
+    fn try_from_str_relative() {
+        let r = Referer::try_from_str("../up/one?x#frag").unwrap();
+        assert_eq!(r, Referer::from_static("../up/one?x"));
+
+        let base = "https://example.com/a/b/c".parse().unwrap();
+        assert_eq!(r.resolve(&base).unwrap(), "https://example.com/a/up/one?x");
+    }
+
+    #[test]
+    fn try_from_str_invalid() {
+        assert!(Referer::try_from_str("not a uri at all!").is_err());
+        assert!(Referer::try_from_str("").is_err());
+    }
+
+    #[test]
+    fn uri() {
+        let r = test_decode::<Referer>(&["http://www.example.org/hypertext/Overview.html"]).unwrap();
+        assert_eq!(r.uri().unwrap(), "http://www.example.org/hypertext/Overview.html");
+
+        let r = test_decode::<Referer>(&["not a uri at all!"]).unwrap();
+        assert_eq!(r.uri(), None);
+    }
+
+    #[test]
+    fn view() {
+        use {HeaderMap, HeaderMapExt, HeaderValue};
+        use super::RefererView;
+
+        let mut map = HeaderMap::new();
+        map.insert("referer", HeaderValue::from_static("/People.html"));
+        let view = map.typed_get_borrowed::<RefererView>().unwrap();
+        assert_eq!(view.as_str(), "/People.html");
+        assert_eq!(view.uri(), map.typed_get::<Referer>().unwrap().uri());
+
+        map.insert("referer", HeaderValue::from_bytes(b"/caf\xE9").unwrap());
+        assert!(map.typed_get::<Referer>().is_none());
+        assert_eq!(map.typed_get_borrowed::<RefererView>(), None);
     }
 }
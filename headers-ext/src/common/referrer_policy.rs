@@ -1,5 +1,3 @@
-use {HeaderValue};
-
 /// `Referrer-Policy` header, part of
 /// [Referrer Policy](https://www.w3.org/TR/referrer-policy/#referrer-policy-header)
 ///
@@ -33,15 +31,26 @@ use {HeaderValue};
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Header)]
 pub struct ReferrerPolicy(Policy);
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+// See https://www.w3.org/TR/referrer-policy/#determine-policy-for-token
+// tl;dr - Pick *last* known policy in the list
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Header)]
+#[header(name_const = "REFERRER_POLICY", list)]
 enum Policy {
+    #[header(value = "no-referrer", alias = "never")]
     NoReferrer,
+    #[header(value = "no-referrer-when-downgrade", alias = "default")]
     NoReferrerWhenDowngrade,
+    #[header(value = "same-origin")]
     SameOrigin,
+    #[header(value = "origin")]
     Origin,
+    #[header(value = "origin-when-cross-origin")]
     OriginWhenCrossOrigin,
+    #[header(value = "unsafe-url", alias = "always")]
     UnsafeUrl,
+    #[header(value = "strict-origin")]
     StrictOrigin,
+    #[header(value = "strict-origin-when-cross-origin")]
     StrictOriginWhenCrossOrigin,
 }
 
@@ -71,67 +80,10 @@ impl ReferrerPolicy {
     pub const STRICT_ORIGIN_WHEN_CROSS_ORIGIN: Self = ReferrerPolicy(Policy::StrictOriginWhenCrossOrigin);
 }
 
-impl ::headers_core::decode::TryFromValues for Policy {
-    fn try_from_values(values: &mut ::Values) -> Option<Self> {
-        // See https://www.w3.org/TR/referrer-policy/#determine-policy-for-token
-        // tl;dr - Pick *last* known policy in the list
-        values.skip_exhaustive_iter_check();
-        for s in reverse_csv(values) {
-            match s {
-                "no-referrer" | "never" => return Some(Policy::NoReferrer),
-                "no-referrer-when-downgrade" | "default" => return Some(Policy::NoReferrerWhenDowngrade),
-                "same-origin" => return Some(Policy::SameOrigin),
-                "origin" => return Some(Policy::Origin),
-                "origin-when-cross-origin" => return Some(Policy::OriginWhenCrossOrigin),
-                "strict-origin" => return Some(Policy::StrictOrigin),
-                "strict-origin-when-cross-origin" => return Some(Policy::StrictOriginWhenCrossOrigin),
-                "unsafe-url" | "always" => return Some(Policy::UnsafeUrl),
-                _ => (),
-            }
-        }
-
-        None
-    }
-}
-
-impl<'a> From<&'a Policy> for HeaderValue {
-    fn from(policy: &'a Policy) -> HeaderValue {
-        HeaderValue::from_static(match *policy {
-            Policy::NoReferrer => "no-referrer",
-            Policy::NoReferrerWhenDowngrade => "no-referrer-when-downgrade",
-            Policy::SameOrigin => "same-origin",
-            Policy::Origin => "origin",
-            Policy::OriginWhenCrossOrigin => "origin-when-cross-origin",
-            Policy::StrictOrigin => "strict-origin",
-            Policy::StrictOriginWhenCrossOrigin => "strict-origin-when-cross-origin",
-            Policy::UnsafeUrl => "unsafe-url",
-        })
-    }
-}
-
-fn reverse_csv<'a, 'b>(values: &'a mut ::Values<'b>) -> impl Iterator<Item=&'b str> + 'a {
-    values
-        .rev()
-        .flat_map(|value| {
-            value
-                .to_str()
-                .into_iter()
-                .flat_map(|string| {
-                    string
-                        .split(',')
-                        .rev()
-                        .filter_map(|x| match x.trim() {
-                            "" => None,
-                            y => Some(y),
-                        })
-                })
-        })
-}
-
 #[cfg(test)]
 mod tests {
     use super::ReferrerPolicy;
-    use super::super::test_decode;
+    use super::super::{test_decode, test_encode};
 
     #[test]
     fn decode_as_last_policy() {
@@ -169,6 +121,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn conflicting_lines() {
+        use {HeaderMap, HeaderMapExt, HeaderValue, ParseMode};
+
+        let mut map = HeaderMap::new();
+        map.append("referrer-policy", HeaderValue::from_static("no-referrer"));
+        map.append("referrer-policy", HeaderValue::from_static("unsafe-url"));
+        assert_eq!(map.typed_get(), Some(ReferrerPolicy::UNSAFE_URL));
+        assert_eq!(map.typed_get_with(ParseMode::Strict), Some(ReferrerPolicy::UNSAFE_URL));
+
+        // A later line only overrides with a policy that's known.
+        map.append("referrer-policy", HeaderValue::from_static("unsafe-url, strict-origin, nope"));
+        map.append("referrer-policy", HeaderValue::from_static("nope, "));
+        assert_eq!(map.typed_get(), Some(ReferrerPolicy::STRICT_ORIGIN));
+
+        // Encoding replaces every line.
+        map.typed_insert(ReferrerPolicy::SAME_ORIGIN);
+        assert_eq!(map.get_all("referrer-policy").iter().collect::<Vec<_>>(), ["same-origin"]);
+    }
+
+    #[test]
+    fn decode_alias() {
+        assert_eq!(
+            test_decode::<ReferrerPolicy>(&["Same-Origin, never"]),
+            Some(ReferrerPolicy::NO_REFERRER),
+        );
+
+        let headers = test_encode(ReferrerPolicy::UNSAFE_URL);
+        assert_eq!(headers["referrer-policy"], "unsafe-url");
+        assert_eq!(
+            test_decode::<ReferrerPolicy>(&["always"]),
+            Some(ReferrerPolicy::UNSAFE_URL),
+        );
+    }
+
     #[test]
     fn decode_unknown() {
         assert_eq!(
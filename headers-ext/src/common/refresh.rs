@@ -0,0 +1,205 @@
+use std::fmt;
+use std::time::Duration;
+
+use http::Uri;
+
+use util::{HeaderString, Seconds};
+
+/// `Refresh` header, a non-standard header described by the
+/// [HTML spec](https://html.spec.whatwg.org/multipage/semantics.html#attr-meta-http-equiv-refresh)
+///
+/// The `Refresh` header asks the user agent to reload the page, or to
+/// navigate to another URL, after a delay.
+///
+/// # ABNF
+///
+/// ```text
+/// Refresh = 1*DIGIT [ "." *DIGIT ] [ ( ";" / "," ) *WSP [ "url" *WSP "=" *WSP ] url ]
+/// ```
+///
+/// # Example values
+///
+/// * `5`
+/// * `5; url=https://example.com/next`
+/// * `0;URL='/login'`
+///
+/// # Notes
+///
+/// * Fractional delays are accepted, but truncated to whole seconds.
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use std::time::Duration;
+/// use headers::Refresh;
+///
+/// let refresh = Refresh::redirect(
+///     Duration::from_secs(5),
+///     "https://example.com/next".parse().unwrap(),
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Refresh {
+    delay: Seconds,
+    url: Option<HeaderString>,
+}
+
+impl Refresh {
+    /// Create a `Refresh` that reloads the current page after `delay`.
+    pub fn after(delay: Duration) -> Refresh {
+        Refresh {
+            delay: delay.into(),
+            url: None,
+        }
+    }
+
+    /// Create a `Refresh` that navigates to `url` after `delay`.
+    pub fn redirect(delay: Duration, url: Uri) -> Refresh {
+        Refresh {
+            delay: delay.into(),
+            url: Some(
+                HeaderString::from_string(url.to_string())
+                    .expect("Uri is a valid HeaderValue"),
+            ),
+        }
+    }
+
+    /// Get the delay before refreshing.
+    pub fn delay(&self) -> Duration {
+        self.delay.into()
+    }
+
+    /// Get the URL to navigate to, if any.
+    ///
+    /// The URL is returned as sent, and may be relative.
+    pub fn url(&self) -> Option<&str> {
+        self.url.as_ref().map(HeaderString::as_str)
+    }
+}
+
+fn from_str(s: &str) -> Option<Refresh> {
+    let s = s.trim();
+
+    let int_end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let delay = Seconds::parse(&s.as_bytes()[..int_end])?;
+    let mut rest = &s[int_end..];
+    if let Some(fraction) = rest.strip_prefix('.') {
+        rest = fraction.trim_start_matches(|c: char| c.is_ascii_digit());
+    }
+
+    let mut rest = rest.trim_start();
+    if let Some(after) = rest.strip_prefix(|c| c == ';' || c == ',') {
+        rest = after.trim_start();
+    } else if !rest.is_empty() {
+        return None;
+    }
+
+    if rest.get(..3).is_some_and(|url| url.eq_ignore_ascii_case("url")) {
+        if let Some(after) = rest[3..].trim_start().strip_prefix('=') {
+            rest = after.trim_start();
+        }
+    }
+
+    let rest = rest.trim_end();
+    let url = match rest.as_bytes().first() {
+        Some(&q) if q == b'"' || q == b'\'' => {
+            let inner = &rest[1..];
+            // A missing closing quote is tolerated, as browsers do.
+            if inner.ends_with(q as char) {
+                &inner[..inner.len() - 1]
+            } else {
+                inner
+            }
+        },
+        _ => rest,
+    };
+
+    let url = if url.is_empty() {
+        None
+    } else {
+        Some(url.parse().ok()?)
+    };
+
+    Some(Refresh {
+        delay,
+        url,
+    })
+}
+
+impl ::Header for Refresh {
+    fn name() -> &'static ::HeaderName {
+        &::http::header::REFRESH
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        values
+            .next()?
+            .to_str()
+            .ok()
+            .and_then(from_str)
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        struct Adapter<'a>(&'a Refresh);
+
+        impl<'a> fmt::Display for Adapter<'a> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                match self.0.url {
+                    Some(ref url) => write!(f, "{}; url={}", self.0.delay, url.as_str()),
+                    None => fmt::Display::fmt(&self.0.delay, f),
+                }
+            }
+        }
+
+        values.append_fmt(Adapter(self));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use super::Refresh;
+    use super::super::{test_decode, test_encode};
+
+    fn decode(s: &str) -> Option<(u64, Option<String>)> {
+        test_decode::<Refresh>(&[s])
+            .map(|r| (r.delay().as_secs(), r.url().map(ToOwned::to_owned)))
+    }
+
+    #[test]
+    fn decode_delay_only() {
+        assert_eq!(decode("5"), Some((5, None)));
+        assert_eq!(decode("5;"), Some((5, None)));
+        assert_eq!(decode("2.75"), Some((2, None)));
+    }
+
+    #[test]
+    fn decode_url_variants() {
+        let next = Some((5, Some("https://example.com/next".to_owned())));
+        assert_eq!(decode("5; url=https://example.com/next"), next);
+        assert_eq!(decode("5,URL = https://example.com/next"), next);
+        assert_eq!(decode("5;https://example.com/next"), next);
+        assert_eq!(decode("5; Url=\"https://example.com/next\""), next);
+        assert_eq!(decode("5.9; url='https://example.com/next'"), next);
+        assert_eq!(decode("5; url='https://example.com/next"), next);
+    }
+
+    #[test]
+    fn decode_invalid() {
+        assert_eq!(decode(""), None);
+        assert_eq!(decode("soon"), None);
+        assert_eq!(decode("-1"), None);
+        assert_eq!(decode("5 url=/next"), None);
+    }
+
+    #[test]
+    fn encode() {
+        let headers = test_encode(Refresh::after(Duration::from_secs(3)));
+        assert_eq!(headers["refresh"], "3");
+
+        let refresh = Refresh::redirect(Duration::from_secs(5), "/next".parse().unwrap());
+        let headers = test_encode(refresh);
+        assert_eq!(headers["refresh"], "5; url=/next");
+    }
+}
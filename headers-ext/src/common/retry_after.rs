@@ -1,4 +1,4 @@
-use std::time::{Duration, SystemTime};
+use std::time::Duration;
 
 use util::{HttpDate, Seconds};
 use ::HeaderValue;
@@ -22,7 +22,7 @@ use ::HeaderValue;
 /// let delay = RetryAfter::delay(Duration::from_secs(300));
 /// let date = RetryAfter::date(SystemTime::now());
 /// ```
-
+///
 /// Retry-After header, defined in [RFC7231](http://tools.ietf.org/html/rfc7231#section-7.1.3)
 #[derive(Debug, Clone, PartialEq, Eq, Header)]
 pub struct RetryAfter(After);
@@ -37,7 +37,10 @@ enum After {
 
 impl RetryAfter {
     /// Create an `RetryAfter` header with a date value.
-    pub fn date(time: SystemTime) -> RetryAfter {
+    ///
+    /// This takes a `SystemTime` or an `HttpDate`. For a `chrono` or `time`
+    /// date, convert it to an `HttpDate` first.
+    pub fn date<T: Into<HttpDate>>(time: T) -> RetryAfter {
         RetryAfter(After::DateTime(time.into()))
     }
 
@@ -100,4 +103,16 @@ mod tests {
     test_retry_after_datetime!(date_decode_rfc1123, "Sun, 06 Nov 1994 08:49:37 GMT");
     test_retry_after_datetime!(date_decode_rfc850, "Sunday, 06-Nov-94 08:49:37 GMT");
     test_retry_after_datetime!(date_decode_asctime, "Sun Nov  6 08:49:37 1994");
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn date_from_chrono() {
+        use std::convert::TryFrom;
+        use chrono::{TimeZone, Utc};
+        use super::super::test_encode;
+
+        let dt = Utc.with_ymd_and_hms(1994, 11, 6, 8, 49, 37).unwrap();
+        let r = RetryAfter::date(HttpDate::try_from(dt).unwrap());
+        assert_eq!(test_encode(r)["retry-after"], "Sun, 06 Nov 1994 08:49:37 GMT");
+    }
 }
@@ -0,0 +1,294 @@
+use util::structured::{self, BareItem, Item};
+
+/// `Sec-CH-UA` header, defined in
+/// [User-Agent Client Hints](https://wicg.github.io/ua-client-hints/#sec-ch-ua)
+///
+/// The `Sec-CH-UA` header field lists the brands of a user agent, each
+/// with its significant version.
+///
+/// # ABNF
+///
+/// ```text
+/// Sec-CH-UA = sf-list
+/// ; each member is an sf-string brand, with an sf-string v parameter
+/// ```
+///
+/// # Example values
+///
+/// * `"Chromium";v="124", "Google Chrome";v="124", "Not-A.Brand";v="99"`
+/// * `"\"Not\\A;Brand";v="99", "Chromium";v="88"`
+///
+/// # Notes
+///
+/// * A brand that isn't a string with a string `v` parameter makes the
+///   whole header invalid.
+/// * User agents add made up brands with odd characters, known as
+///   GREASE, so servers don't rely on the exact list.
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::SecChUa;
+///
+/// let ua = SecChUa::new()
+///     .with_brand("Chromium", "124")
+///     .with_brand("Not-A.Brand", "99");
+/// assert!(ua.has_brand("Chromium"));
+/// assert_eq!(ua.to_string(), "\"Chromium\";v=\"124\", \"Not-A.Brand\";v=\"99\"");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SecChUa(Vec<Item>);
+
+/// `Sec-CH-UA-Mobile` header, defined in
+/// [User-Agent Client Hints](https://wicg.github.io/ua-client-hints/#sec-ch-ua-mobile)
+///
+/// The `Sec-CH-UA-Mobile` header field tells whether the user agent
+/// prefers a mobile experience.
+///
+/// # ABNF
+///
+/// ```text
+/// Sec-CH-UA-Mobile = sf-boolean
+/// ```
+///
+/// # Example values
+///
+/// * `?1`
+/// * `?0`
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::SecChUaMobile;
+///
+/// let mobile = SecChUaMobile(true);
+/// assert_eq!(mobile.to_string(), "?1");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SecChUaMobile(pub bool);
+
+/// `Sec-CH-UA-Platform` header, defined in
+/// [User-Agent Client Hints](https://wicg.github.io/ua-client-hints/#sec-ch-ua-platform)
+///
+/// The `Sec-CH-UA-Platform` header field gives the platform the user
+/// agent runs on.
+///
+/// # ABNF
+///
+/// ```text
+/// Sec-CH-UA-Platform = sf-string
+/// ```
+///
+/// # Example values
+///
+/// * `"Windows"`
+/// * `"Android"`
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::SecChUaPlatform;
+///
+/// let platform = SecChUaPlatform::new("macOS");
+/// assert_eq!(platform.as_str(), "macOS");
+/// assert_eq!(platform.to_string(), "\"macOS\"");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SecChUaPlatform(String);
+
+impl SecChUa {
+    /// Create an empty brand list.
+    pub fn new() -> SecChUa {
+        SecChUa::default()
+    }
+
+    /// Add a brand and its version.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `brand` or `version` contain characters other than
+    /// printable ASCII.
+    pub fn with_brand(mut self, brand: &str, version: &str) -> Self {
+        assert!(structured::is_string(brand), "invalid Sec-CH-UA brand: {:?}", brand);
+        assert!(structured::is_string(version), "invalid Sec-CH-UA version: {:?}", version);
+        let mut item = Item::new(BareItem::String(brand.into()));
+        item.params.insert("v", BareItem::String(version.into()));
+        self.0.push(item);
+        self
+    }
+
+    /// Iterate the brands and their versions, in order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|item| (brand(item), version(item)))
+    }
+
+    /// Check if `brand` is listed, with its exact name.
+    pub fn has_brand(&self, brand: &str) -> bool {
+        self.version(brand).is_some()
+    }
+
+    /// Get the version of `brand`, if it's listed.
+    pub fn version(&self, brand: &str) -> Option<&str> {
+        self.iter().find(|&(b, _)| b == brand).map(|(_, version)| version)
+    }
+}
+
+fn brand(item: &Item) -> &str {
+    item.bare.as_str().expect("Sec-CH-UA brands are strings")
+}
+
+fn version(item: &Item) -> &str {
+    item.params
+        .get("v")
+        .and_then(BareItem::as_str)
+        .expect("Sec-CH-UA brands have a version")
+}
+
+impl SecChUaPlatform {
+    /// Create a `Sec-CH-UA-Platform` header.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `platform` contains characters other than printable ASCII.
+    pub fn new(platform: &str) -> SecChUaPlatform {
+        assert!(structured::is_string(platform), "invalid Sec-CH-UA-Platform: {:?}", platform);
+        SecChUaPlatform(platform.into())
+    }
+
+    /// Get the platform.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl ::Header for SecChUa {
+    fn name() -> &'static ::HeaderName {
+        static_header_name!("sec-ch-ua")
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        let brands = structured::parse_list(values)?
+            .into_iter()
+            .map(|member| {
+                let item = member.into_item()?;
+                item.bare.as_str()?;
+                item.params.get("v")?.as_str()?;
+                Some(item)
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        if brands.is_empty() {
+            None
+        } else {
+            Some(SecChUa(brands))
+        }
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        values.append_fmt(structured::List(&self.0));
+    }
+}
+
+impl ::Header for SecChUaMobile {
+    fn name() -> &'static ::HeaderName {
+        static_header_name!("sec-ch-ua-mobile")
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        structured::parse_item(values)?.bare.as_bool().map(SecChUaMobile)
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        values.append_fmt(BareItem::Boolean(self.0));
+    }
+}
+
+impl ::Header for SecChUaPlatform {
+    fn name() -> &'static ::HeaderName {
+        static_header_name!("sec-ch-ua-platform")
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        match structured::parse_item(values)?.bare {
+            BareItem::String(platform) => Some(SecChUaPlatform(platform)),
+            _ => None,
+        }
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        values.append_fmt(BareItem::String(self.0.clone()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SecChUa, SecChUaMobile, SecChUaPlatform};
+    use super::super::{test_decode, test_encode};
+
+    #[test]
+    fn decode_brands() {
+        let ua = test_decode::<SecChUa>(&["\"Chromium\";v=\"124\", \"Google Chrome\";v=\"124\"", "\"Not-A.Brand\";v=\"99\""])
+            .unwrap();
+        assert_eq!(ua.iter().collect::<Vec<_>>(), [
+            ("Chromium", "124"),
+            ("Google Chrome", "124"),
+            ("Not-A.Brand", "99"),
+        ]);
+        assert!(ua.has_brand("Google Chrome"));
+        assert!(!ua.has_brand("chromium"));
+        assert_eq!(ua.version("Not-A.Brand"), Some("99"));
+        assert_eq!(ua.version("Firefox"), None);
+    }
+
+    #[test]
+    fn grease_brands_round_trip() {
+        for &value in &[
+            "\" Not A;Brand\";v=\"99\", \"Chromium\";v=\"96\"",
+            "\"\\\"Not\\\\A;Brand\";v=\"99\", \"Chromium\";v=\"88\"",
+            "\"Not(A:Brand\";v=\"24\", \"Chromium\";v=\"122\";x",
+            "\"Not_A Brand\";v=\"8\"",
+        ] {
+            let ua = test_decode::<SecChUa>(&[value]).unwrap();
+            assert_eq!(test_encode(ua)["sec-ch-ua"], value);
+        }
+
+        let ua = test_decode::<SecChUa>(&["\"\\\"Not\\\\A;Brand\";v=\"99\""]).unwrap();
+        assert!(ua.has_brand("\"Not\\A;Brand"));
+    }
+
+    #[test]
+    fn decode_invalid_brands() {
+        for &value in &["", "Chromium;v=\"124\"", "\"Chromium\"", "\"Chromium\";v=124", "(\"Chromium\");v=\"1\""] {
+            assert_eq!(test_decode::<SecChUa>(&[value]), None, "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn encode_brands() {
+        let ua = SecChUa::new().with_brand("\"Not\\A;Brand", "99").with_brand("Chromium", "88");
+        assert_eq!(test_encode(ua)["sec-ch-ua"], "\"\\\"Not\\\\A;Brand\";v=\"99\", \"Chromium\";v=\"88\"");
+    }
+
+    #[test]
+    fn mobile() {
+        assert_eq!(test_decode(&["?1"]), Some(SecChUaMobile(true)));
+        assert_eq!(test_decode(&["?0"]), Some(SecChUaMobile(false)));
+        for &value in &["", "1", "?1, ?0", "\"?1\""] {
+            assert_eq!(test_decode::<SecChUaMobile>(&[value]), None, "{:?}", value);
+        }
+        assert_eq!(test_encode(SecChUaMobile(false))["sec-ch-ua-mobile"], "?0");
+    }
+
+    #[test]
+    fn platform() {
+        assert_eq!(test_decode(&["\"Windows\""]), Some(SecChUaPlatform::new("Windows")));
+        assert_eq!(test_decode(&["\"Chrome \\\"OS\\\"\""]), Some(SecChUaPlatform::new("Chrome \"OS\"")));
+        for &value in &["", "Windows", "\"Windows\", \"Linux\""] {
+            assert_eq!(test_decode::<SecChUaPlatform>(&[value]), None, "{:?}", value);
+        }
+        assert_eq!(test_encode(SecChUaPlatform::new("Chrome \"OS\""))["sec-ch-ua-platform"], "\"Chrome \\\"OS\\\"\"");
+    }
+}
@@ -12,14 +12,16 @@ use super::SecWebsocketKey;
 ///
 /// # Example
 ///
-/// ```no_run
+/// ```
 /// # extern crate headers_ext as headers;
-/// use headers::{SecWebsocketAccept, SecWebsocketKey};
+/// use headers::{HeaderMap, HeaderMapExt, HeaderValue, SecWebsocketAccept, SecWebsocketKey};
 ///
-/// let sec_key: SecWebsocketKey = /* from request headers */
-/// #    unimplemented!();
+/// let mut req = HeaderMap::new();
+/// req.insert("sec-websocket-key", HeaderValue::from_static("dGhlIHNhbXBsZSBub25jZQ=="));
 ///
-/// let sec_accept = SecWebsocketAccept::from(sec_key);
+/// let mut res = HeaderMap::new();
+/// res.typed_insert(SecWebsocketAccept::from(req.typed_get::<SecWebsocketKey>().unwrap()));
+/// assert_eq!(res["sec-websocket-accept"], "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
 /// ```
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Header)]
 pub struct SecWebsocketAccept(::HeaderValue);
@@ -8,7 +8,9 @@ impl SecWebsocketVersion {
 }
 
 impl ::Header for SecWebsocketVersion {
-    const NAME: &'static ::HeaderName = &::http::header::SEC_WEBSOCKET_VERSION;
+    fn name() -> &'static ::HeaderName {
+        &::http::header::SEC_WEBSOCKET_VERSION
+    }
 
     fn decode(values: &mut ::Values) -> Option<Self> {
         let value = values.next()?;
@@ -1,3 +1,10 @@
+use std::error::Error as StdError;
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use util::{token, HttpDate, SmallList};
+use ParseMode;
+use super::cookie::is_cookie_value;
 
 /// `Set-Cookie` header, defined [RFC6265](http://tools.ietf.org/html/rfc6265#section-4.1)
 ///
@@ -51,20 +58,543 @@
 /// * `lang=; Expires=Sun, 06 Nov 1994 08:49:37 GMT`
 /// * `lang=en-US; Path=/; Domain=example.com`
 ///
+/// Each cookie is sent in its own field line, so unlike most headers,
+/// `SetCookie` can't be converted to a single `HeaderValue`:
+///
+/// ```compile_fail
+/// # extern crate headers_ext as headers;
+/// fn to_value(cookies: &headers::SetCookie) -> headers::HeaderValue {
+///     headers::HeaderValue::from(cookies)
+/// }
+/// # fn main() {}
+/// ```
+///
+/// # Cookie prefixes
+///
+/// Cookies whose names start with `__Secure-` must be `Secure`, and those
+/// that start with `__Host-` must also have `Path=/` and no `Domain`, as
+/// defined in
+/// [RFC6265bis](https://tools.ietf.org/html/draft-ietf-httpbis-rfc6265bis#section-4.1.3).
+/// The prefixes are matched ignoring ASCII case, as browsers do.
+///
+/// [`SetCookie::builder`](#method.builder) refuses to build cookies
+/// breaking these rules, and [`SetCookie::host_prefixed`](#method.host_prefixed)
+/// can't build them at all. Decoding accepts them, since they're seen
+/// in the wild, and [`validate`](#method.validate) reports them.
+///
 /// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use std::time::Duration;
+/// use headers::{HeaderMap, HeaderMapExt, SameSite, SetCookie};
+///
+/// let session = SetCookie::host_prefixed("session", "31d4d96e407aad42")
+///     .unwrap()
+///     .with_http_only()
+///     .with_same_site(SameSite::Lax)
+///     .build();
+///
+/// let mut map = HeaderMap::new();
+/// map.typed_insert(session);
+/// assert_eq!(
+///     map["set-cookie"],
+///     "__Host-session=31d4d96e407aad42; Path=/; Secure; HttpOnly; SameSite=Lax",
+/// );
+///
+/// let lang = SetCookie::builder("lang", "en-US")
+///     .with_path("/docs")
+///     .with_max_age(Duration::from_secs(3600))
+///     .build()
+///     .unwrap();
+///
+/// map.typed_insert(lang);
+/// assert_eq!(map["set-cookie"], "lang=en-US; Path=/docs; Max-Age=3600");
+/// ```
+#[derive(Clone, Debug)]
+pub struct SetCookie(SmallList<::HeaderValue>);
+
+impl SetCookie {
+    /// Start building a `Set-Cookie` with a single cookie.
+    pub fn builder(name: &str, value: &str) -> SetCookieBuilder {
+        SetCookieBuilder {
+            name: name.to_owned(),
+            value: value.to_owned(),
+            path: None,
+            domain: None,
+            max_age: None,
+            expires: None,
+            secure: false,
+            http_only: false,
+            same_site: None,
+        }
+    }
+
+    /// Start building a cookie named `name` with the `__Host-` prefix
+    /// added, so `session` becomes `__Host-session`.
+    ///
+    /// The cookie is always `Secure`, with `Path=/` and no `Domain`, and
+    /// the builder has no way to change that.
+    pub fn host_prefixed(name: &str, value: &str) -> Result<HostPrefixedBuilder, CookieError> {
+        let builder = SetCookie::builder(&format!("__Host-{}", name), value)
+            .with_path("/")
+            .with_secure();
+        builder.check_pair()?;
+        Ok(HostPrefixedBuilder(builder))
+    }
+
+    /// Start building a cookie that removes the one named `name`, with an
+    /// empty value, `Max-Age=0` and an `Expires` at the Unix epoch.
+    ///
+    /// User agents only remove a cookie with the same `Path` and `Domain`,
+    /// so they must be set as they were on the cookie being removed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # extern crate headers_ext as headers;
+    /// use headers::{HeaderMap, HeaderMapExt, SetCookie};
+    ///
+    /// let removal = SetCookie::removal("session").with_path("/app").build().unwrap();
+    ///
+    /// let mut map = HeaderMap::new();
+    /// map.typed_insert(removal);
+    /// assert_eq!(
+    ///     map["set-cookie"],
+    ///     "session=; Path=/app; Max-Age=0; Expires=Thu, 01 Jan 1970 00:00:00 GMT",
+    /// );
+    /// ```
+    pub fn removal(name: &str) -> SetCookieBuilder {
+        SetCookie::builder(name, "")
+            .with_max_age(Duration::from_secs(0))
+            .with_expires(UNIX_EPOCH)
+    }
+
+    /// When the first cookie expires, from its `Max-Age` if it has a valid
+    /// one, or else its `Expires`.
+    ///
+    /// A `Max-Age` of zero or less is the earliest time, the Unix epoch.
+    /// `None` if the cookie lasts for the session, or would expire too far
+    /// in the future to be represented.
+    ///
+    /// A `SetCookie` built by this crate has a single cookie. One decoded
+    /// from several lines is judged by its first.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # extern crate headers_ext as headers;
+    /// use std::time::{Duration, SystemTime};
+    /// use headers::SetCookie;
+    ///
+    /// let now = SystemTime::now();
+    /// let lang = SetCookie::builder("lang", "en-US")
+    ///     .with_max_age(Duration::from_secs(3600))
+    ///     .with_expires(now)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(lang.expires_at(now), Some(now + Duration::from_secs(3600)));
+    /// assert!(!lang.is_expired(now));
+    /// ```
+    pub fn expires_at(&self, now: SystemTime) -> Option<SystemTime> {
+        self.first().and_then(|line| line.expires_at(now))
+    }
+
+    /// Whether the first cookie has already expired when received at
+    /// `now`, as by `expires_at`.
+    ///
+    /// `Max-Age` counts from `now`, so only one of zero or less makes this
+    /// true, as a removal cookie has.
+    pub fn is_expired(&self, now: SystemTime) -> bool {
+        self.expires_at(now).is_some_and(|expires| expires <= now)
+    }
+
+    /// Whether the first cookie lasts only for the session, with neither
+    /// a valid `Max-Age` nor a valid `Expires`.
+    pub fn is_session_cookie(&self) -> bool {
+        self.first().is_some_and(|line| line.max_age().is_none() && line.expires().is_none())
+    }
+
+    fn first(&self) -> Option<SetCookieLine<'_>> {
+        self.0.iter().next()?.to_str().ok().and_then(SetCookieLine::parse)
+    }
+
+    /// Iterate the cookies, one per field line.
+    pub(crate) fn lines(&self) -> impl Iterator<Item = &::HeaderValue> {
+        self.0.iter()
+    }
+
+    /// Check each cookie against the rules of its name prefix, if it has
+    /// one, returning the first rule broken.
+    ///
+    /// Lines that can't be parsed as a cookie at all are reported as
+    /// `CookieError::Malformed`. The rest of a cookie's syntax isn't
+    /// checked.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # extern crate headers_ext as headers;
+    /// # use headers::{CookieError, HeaderMap, HeaderMapExt, HeaderValue, SetCookie};
+    /// let mut map = HeaderMap::new();
+    /// map.insert("set-cookie", HeaderValue::from_static("__Host-id=1; Secure; Path=/; Domain=example.com"));
+    ///
+    /// let set_cookie = map.typed_get::<SetCookie>().unwrap();
+    /// assert_eq!(set_cookie.validate(), Err(CookieError::HostPrefixWithDomain));
+    /// ```
+    pub fn validate(&self) -> Result<(), CookieError> {
+        for line in &self.0 {
+            let line = line
+                .to_str()
+                .ok()
+                .and_then(SetCookieLine::parse)
+                .ok_or(CookieError::Malformed)?;
+            check_prefix(
+                line.name,
+                line.attribute("secure").is_some(),
+                line.attribute("domain").is_some(),
+                line.attribute("path"),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// A builder for a `SetCookie`, created with `SetCookie::builder`.
+#[derive(Clone, Debug)]
+pub struct SetCookieBuilder {
+    name: String,
+    value: String,
+    path: Option<String>,
+    domain: Option<String>,
+    max_age: Option<Duration>,
+    expires: Option<HttpDate>,
+    secure: bool,
+    http_only: bool,
+    same_site: Option<SameSite>,
+}
+
+impl SetCookieBuilder {
+    /// Set the `Path` attribute.
+    pub fn with_path(mut self, path: &str) -> Self {
+        self.path = Some(path.to_owned());
+        self
+    }
+
+    /// Set the `Domain` attribute.
+    pub fn with_domain(mut self, domain: &str) -> Self {
+        self.domain = Some(domain.to_owned());
+        self
+    }
+
+    /// Set the `Max-Age` attribute, in whole seconds.
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Set the `Expires` attribute, in whole seconds.
+    pub fn with_expires(mut self, expires: SystemTime) -> Self {
+        self.expires = Some(expires.into());
+        self
+    }
+
+    /// Set the `Secure` attribute.
+    pub fn with_secure(mut self) -> Self {
+        self.secure = true;
+        self
+    }
+
+    /// Set the `HttpOnly` attribute.
+    pub fn with_http_only(mut self) -> Self {
+        self.http_only = true;
+        self
+    }
+
+    /// Set the `SameSite` attribute.
+    pub fn with_same_site(mut self, same_site: SameSite) -> Self {
+        self.same_site = Some(same_site);
+        self
+    }
+
+    /// Build the `SetCookie`.
+    ///
+    /// Fails if the name isn't a token, the value has characters not
+    /// allowed in a cookie, the path or domain contain a `;` or control
+    /// character, or the cookie breaks the rules of its name prefix.
+    pub fn build(self) -> Result<SetCookie, CookieError> {
+        self.check_pair()?;
+        let valid_attribute = |attr: &Option<String>| {
+            attr.as_ref()
+                .is_none_or(|attr| !attr.chars().any(|c| c == ';' || c.is_control()))
+        };
+        if !valid_attribute(&self.path) || !valid_attribute(&self.domain) {
+            return Err(CookieError::InvalidAttribute);
+        }
+        check_prefix(&self.name, self.secure, self.domain.is_some(), self.path.as_deref())?;
+
+        let value = ::HeaderValue::from_str(&self.to_string())
+            .expect("checked cookie is a valid HeaderValue");
+        Ok(SetCookie(::std::iter::once(value).collect()))
+    }
+
+    fn check_pair(&self) -> Result<(), CookieError> {
+        if !token::is_token(self.name.as_bytes()) {
+            Err(CookieError::InvalidName)
+        } else if !is_cookie_value(&self.value) {
+            Err(CookieError::InvalidValue)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl fmt::Display for SetCookieBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}={}", self.name, self.value)?;
+        if let Some(ref path) = self.path {
+            write!(f, "; Path={}", path)?;
+        }
+        if let Some(ref domain) = self.domain {
+            write!(f, "; Domain={}", domain)?;
+        }
+        if let Some(max_age) = self.max_age {
+            write!(f, "; Max-Age={}", max_age.as_secs())?;
+        }
+        if let Some(ref expires) = self.expires {
+            write!(f, "; Expires={}", expires)?;
+        }
+        if self.secure {
+            f.write_str("; Secure")?;
+        }
+        if self.http_only {
+            f.write_str("; HttpOnly")?;
+        }
+        if let Some(same_site) = self.same_site {
+            write!(f, "; SameSite={}", same_site.as_str())?;
+        }
+        Ok(())
+    }
+}
+
+/// A builder for a `__Host-` prefixed `SetCookie`, created with
+/// `SetCookie::host_prefixed`.
 #[derive(Clone, Debug)]
-pub struct SetCookie(Vec<::HeaderValue>);
+pub struct HostPrefixedBuilder(SetCookieBuilder);
+
+impl HostPrefixedBuilder {
+    /// Set the `Max-Age` attribute, in whole seconds.
+    pub fn with_max_age(self, max_age: Duration) -> Self {
+        HostPrefixedBuilder(self.0.with_max_age(max_age))
+    }
+
+    /// Set the `Expires` attribute, in whole seconds.
+    pub fn with_expires(self, expires: SystemTime) -> Self {
+        HostPrefixedBuilder(self.0.with_expires(expires))
+    }
+
+    /// Set the `HttpOnly` attribute.
+    pub fn with_http_only(self) -> Self {
+        HostPrefixedBuilder(self.0.with_http_only())
+    }
+
+    /// Set the `SameSite` attribute.
+    pub fn with_same_site(self, same_site: SameSite) -> Self {
+        HostPrefixedBuilder(self.0.with_same_site(same_site))
+    }
+
+    /// Build the `SetCookie`.
+    pub fn build(self) -> SetCookie {
+        self.0.build().expect("host prefixed cookie is valid")
+    }
+}
+
+/// The `SameSite` attribute of a cookie.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SameSite {
+    /// `SameSite=Strict`
+    Strict,
+    /// `SameSite=Lax`
+    Lax,
+    /// `SameSite=None`, which browsers only accept on `Secure` cookies.
+    None,
+}
+
+impl SameSite {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            SameSite::Strict => "Strict",
+            SameSite::Lax => "Lax",
+            SameSite::None => "None",
+        }
+    }
+}
+
+/// An error building a `SetCookie`, or a rule it breaks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CookieError {
+    /// The name isn't a token.
+    InvalidName,
+    /// The value has characters not allowed in a cookie.
+    InvalidValue,
+    /// An attribute value has a `;` or a control character.
+    InvalidAttribute,
+    /// A decoded line isn't a `name=value` cookie.
+    Malformed,
+    /// A `__Secure-` cookie isn't `Secure`.
+    SecurePrefixWithoutSecure,
+    /// A `__Host-` cookie isn't `Secure`.
+    HostPrefixWithoutSecure,
+    /// A `__Host-` cookie has a `Domain`.
+    HostPrefixWithDomain,
+    /// A `__Host-` cookie doesn't have `Path=/`.
+    HostPrefixWithoutRootPath,
+}
+
+impl fmt::Display for CookieError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            CookieError::InvalidName => "invalid cookie name",
+            CookieError::InvalidValue => "invalid cookie value",
+            CookieError::InvalidAttribute => "invalid cookie attribute",
+            CookieError::Malformed => "malformed Set-Cookie value",
+            CookieError::SecurePrefixWithoutSecure => "__Secure- cookie must be Secure",
+            CookieError::HostPrefixWithoutSecure => "__Host- cookie must be Secure",
+            CookieError::HostPrefixWithDomain => "__Host- cookie must not have a Domain",
+            CookieError::HostPrefixWithoutRootPath => "__Host- cookie must have Path=/",
+        })
+    }
+}
+
+impl StdError for CookieError {}
+
+fn has_prefix(name: &str, prefix: &str) -> bool {
+    name.len() >= prefix.len() && name.as_bytes()[..prefix.len()].eq_ignore_ascii_case(prefix.as_bytes())
+}
+
+pub(crate) fn check_prefix(name: &str, secure: bool, domain: bool, path: Option<&str>) -> Result<(), CookieError> {
+    if has_prefix(name, "__Secure-") && !secure {
+        Err(CookieError::SecurePrefixWithoutSecure)
+    } else if has_prefix(name, "__Host-") {
+        if !secure {
+            Err(CookieError::HostPrefixWithoutSecure)
+        } else if domain {
+            Err(CookieError::HostPrefixWithDomain)
+        } else if path != Some("/") {
+            Err(CookieError::HostPrefixWithoutRootPath)
+        } else {
+            Ok(())
+        }
+    } else {
+        Ok(())
+    }
+}
+
+/// A `Set-Cookie` line, split as user agents do in
+/// [RFC6265 section 5.2](https://tools.ietf.org/html/rfc6265#section-5.2).
+pub(crate) struct SetCookieLine<'a> {
+    pub(crate) name: &'a str,
+    pub(crate) value: &'a str,
+    attributes: &'a str,
+}
+
+impl<'a> SetCookieLine<'a> {
+    pub(crate) fn parse(line: &'a str) -> Option<SetCookieLine<'a>> {
+        let (pair, attributes) = match line.find(';') {
+            Some(i) => (&line[..i], &line[i..]),
+            None => (line, ""),
+        };
+        let eq = pair.find('=')?;
+        let name = token::trim_ows(&pair[..eq]);
+        if name.is_empty() {
+            return None;
+        }
+        Some(SetCookieLine {
+            name,
+            value: token::trim_ows(&pair[eq + 1..]),
+            attributes,
+        })
+    }
+
+    /// Iterate the attributes as `(name, value)`, with an empty value for
+    /// those without one.
+    pub(crate) fn attributes(&self) -> impl Iterator<Item = (&'a str, &'a str)> {
+        self.attributes
+            .split(';')
+            .skip(1)
+            .map(|attr| match attr.find('=') {
+                Some(i) => (token::trim_ows(&attr[..i]), token::trim_ows(&attr[i + 1..])),
+                None => (token::trim_ows(attr), ""),
+            })
+    }
+
+    /// Get the value of the last attribute named `name`, ignoring ASCII
+    /// case.
+    pub(crate) fn attribute(&self, name: &str) -> Option<&'a str> {
+        self.attributes()
+            .filter(|&(attr, _)| attr.eq_ignore_ascii_case(name))
+            .last()
+            .map(|(_, value)| value)
+    }
+
+    /// The last valid `Max-Age`, in seconds.
+    pub(crate) fn max_age(&self) -> Option<i64> {
+        self.attributes()
+            .filter(|&(attr, _)| attr.eq_ignore_ascii_case("max-age"))
+            .filter_map(|(_, value)| parse_max_age(value))
+            .last()
+    }
+
+    /// The last valid `Expires`.
+    pub(crate) fn expires(&self) -> Option<SystemTime> {
+        self.attributes()
+            .filter(|&(attr, _)| attr.eq_ignore_ascii_case("expires"))
+            .filter_map(|(_, value)| HttpDate::parse(value, ParseMode::Lenient))
+            .last()
+            .map(SystemTime::from)
+    }
+
+    /// When the cookie expires, as computed by user agents.
+    pub(crate) fn expires_at(&self, now: SystemTime) -> Option<SystemTime> {
+        // Max-Age has precedence over Expires, and zero or less means the
+        // earliest time.
+        match self.max_age() {
+            Some(seconds) if seconds <= 0 => Some(UNIX_EPOCH),
+            Some(seconds) => now.checked_add(Duration::from_secs(seconds as u64)),
+            None => self.expires(),
+        }
+    }
+}
+
+// `max-age-av`, allowing a leading `-` as user agents must.
+fn parse_max_age(value: &str) -> Option<i64> {
+    let digits = value.strip_prefix('-').unwrap_or(value);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    // Too many digits to fit is still a valid, very long, age.
+    Some(value.parse().unwrap_or(if digits.len() == value.len() { i64::MAX } else { i64::MIN }))
+}
 
 impl ::Header for SetCookie {
-    const NAME: &'static ::HeaderName = &::http::header::SET_COOKIE;
+    fn name() -> &'static ::HeaderName {
+        &::http::header::SET_COOKIE
+    }
 
     fn decode(values: &mut ::Values) -> Option<Self> {
-        let vec = values
+        let max = values.limits().max_list_items;
+        let list = values
+            .by_ref()
+            .take(max)
             .cloned()
-            .collect::<Vec<_>>();
+            .collect::<SmallList<_>>();
+        if values.next().is_some() {
+            values.set_too_large();
+            return None;
+        }
 
-        if !vec.is_empty() {
-            Some(SetCookie(vec))
+        if !list.is_empty() {
+            Some(SetCookie(list))
         } else {
             None
         }
@@ -85,9 +615,20 @@ mod tests {
     #[test]
     fn decode() {
         let set_cookie = test_decode::<SetCookie>(&["foo=bar", "baz=quux"]).unwrap();
-        assert_eq!(set_cookie.0.len(), 2);
-        assert_eq!(set_cookie.0[0], "foo=bar");
-        assert_eq!(set_cookie.0[1], "baz=quux");
+        let cookies = set_cookie.0.iter().collect::<Vec<_>>();
+        assert_eq!(cookies, ["foo=bar", "baz=quux"]);
+    }
+
+    #[test]
+    fn decode_many() {
+        let values = (0..12).map(|i| format!("c{}=v", i)).collect::<Vec<_>>();
+        let values = values.iter().map(String::as_str).collect::<Vec<_>>();
+        let set_cookie = test_decode::<SetCookie>(&values).unwrap();
+        assert_eq!(set_cookie.0.len(), 12);
+
+        let headers = test_encode(set_cookie);
+        let encoded = headers.get_all("set-cookie").iter().collect::<Vec<_>>();
+        assert_eq!(encoded, values);
     }
 
     #[test]
@@ -95,7 +636,7 @@ mod tests {
         let set_cookie = SetCookie(vec![
             ::HeaderValue::from_static("foo=bar"),
             ::HeaderValue::from_static("baz=quux"),
-        ]);
+        ].into_iter().collect());
 
         let headers = test_encode(set_cookie);
         let mut vals = headers.get_all("set-cookie").into_iter();
@@ -103,4 +644,189 @@ mod tests {
         assert_eq!(vals.next().unwrap(), "baz=quux");
         assert_eq!(vals.next(), None);
     }
+
+    fn validate(line: &str) -> Result<(), CookieError> {
+        test_decode::<SetCookie>(&[line]).unwrap().validate()
+    }
+
+    #[test]
+    fn validate_secure_prefix() {
+        assert_eq!(validate("__Secure-id=1; Secure"), Ok(()));
+        assert_eq!(validate("__Secure-id=1; Path=/; secure; Domain=example.com"), Ok(()));
+        assert_eq!(validate("__Secure-id=1"), Err(CookieError::SecurePrefixWithoutSecure));
+        assert_eq!(validate("__secure-id=1; HttpOnly"), Err(CookieError::SecurePrefixWithoutSecure));
+    }
+
+    #[test]
+    fn validate_host_prefix() {
+        assert_eq!(validate("__Host-id=1; Secure; Path=/"), Ok(()));
+        assert_eq!(validate("__Host-id=1; Path=/; HttpOnly; SECURE"), Ok(()));
+        assert_eq!(validate("__Host-id=1; Path=/"), Err(CookieError::HostPrefixWithoutSecure));
+        assert_eq!(validate("__Host-id=1; Secure; Path=/; Domain=example.com"), Err(CookieError::HostPrefixWithDomain));
+        assert_eq!(validate("__Host-id=1; Secure; Path=/; Domain="), Err(CookieError::HostPrefixWithDomain));
+        assert_eq!(validate("__Host-id=1; Secure"), Err(CookieError::HostPrefixWithoutRootPath));
+        assert_eq!(validate("__Host-id=1; Secure; Path=/docs"), Err(CookieError::HostPrefixWithoutRootPath));
+        // the last Path wins
+        assert_eq!(validate("__Host-id=1; Secure; Path=/docs; Path=/"), Ok(()));
+        assert_eq!(validate("__HOST-id=1; Path=/"), Err(CookieError::HostPrefixWithoutSecure));
+    }
+
+    #[test]
+    fn validate_unprefixed() {
+        assert_eq!(validate("id=1"), Ok(()));
+        assert_eq!(validate("id=1; Domain=example.com; Path=/docs"), Ok(()));
+        assert_eq!(validate("_Host-id=1"), Ok(()));
+        assert_eq!(validate("Host-id=1"), Ok(()));
+        assert_eq!(validate("id"), Err(CookieError::Malformed));
+        assert_eq!(validate("=1"), Err(CookieError::Malformed));
+    }
+
+    #[test]
+    fn validate_reports_first_violation() {
+        let set_cookie = test_decode::<SetCookie>(&["a=1", "__Secure-b=2", "__Host-c=3"]).unwrap();
+        assert_eq!(set_cookie.validate(), Err(CookieError::SecurePrefixWithoutSecure));
+    }
+
+    #[test]
+    fn builder() {
+        let set_cookie = SetCookie::builder("id", "\"a1\"")
+            .with_domain("example.com")
+            .with_path("/")
+            .with_expires(::std::time::UNIX_EPOCH + Duration::from_secs(784111777))
+            .with_max_age(Duration::from_millis(1500))
+            .with_secure()
+            .with_http_only()
+            .with_same_site(SameSite::Strict)
+            .build()
+            .unwrap();
+        assert_eq!(
+            test_encode(set_cookie)["set-cookie"],
+            "id=\"a1\"; Path=/; Domain=example.com; Max-Age=1; \
+             Expires=Sun, 06 Nov 1994 08:49:37 GMT; Secure; HttpOnly; SameSite=Strict",
+        );
+    }
+
+    #[test]
+    fn builder_invalid() {
+        assert_eq!(SetCookie::builder("a b", "1").build().unwrap_err(), CookieError::InvalidName);
+        assert_eq!(SetCookie::builder("", "1").build().unwrap_err(), CookieError::InvalidName);
+        assert_eq!(SetCookie::builder("a", "1;2").build().unwrap_err(), CookieError::InvalidValue);
+        assert_eq!(SetCookie::builder("a", "1 2").build().unwrap_err(), CookieError::InvalidValue);
+        assert_eq!(SetCookie::builder("a", "1").with_path("/;x").build().unwrap_err(), CookieError::InvalidAttribute);
+        assert_eq!(SetCookie::builder("a", "1").with_domain("a\nb").build().unwrap_err(), CookieError::InvalidAttribute);
+    }
+
+    #[test]
+    fn builder_checks_prefixes() {
+        let err = |b: SetCookieBuilder| b.build().unwrap_err();
+        assert_eq!(err(SetCookie::builder("__Secure-a", "1")), CookieError::SecurePrefixWithoutSecure);
+        assert!(SetCookie::builder("__Secure-a", "1").with_secure().build().is_ok());
+        assert_eq!(err(SetCookie::builder("__Host-a", "1").with_path("/")), CookieError::HostPrefixWithoutSecure);
+        assert_eq!(
+            err(SetCookie::builder("__Host-a", "1").with_secure().with_path("/").with_domain("example.com")),
+            CookieError::HostPrefixWithDomain,
+        );
+        assert_eq!(err(SetCookie::builder("__Host-a", "1").with_secure()), CookieError::HostPrefixWithoutRootPath);
+        assert!(SetCookie::builder("__Host-a", "1").with_secure().with_path("/").build().is_ok());
+        assert!(SetCookie::builder("a", "1").with_domain("example.com").build().is_ok());
+    }
+
+    #[test]
+    fn host_prefixed() {
+        let set_cookie = SetCookie::host_prefixed("id", "1")
+            .unwrap()
+            .with_max_age(Duration::from_secs(60))
+            .build();
+        assert_eq!(set_cookie.validate(), Ok(()));
+        assert_eq!(test_encode(set_cookie)["set-cookie"], "__Host-id=1; Path=/; Max-Age=60; Secure");
+
+        assert_eq!(SetCookie::host_prefixed("a b", "1").unwrap_err(), CookieError::InvalidName);
+        assert_eq!(SetCookie::host_prefixed("id", "1,2").unwrap_err(), CookieError::InvalidValue);
+    }
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn expires_at() {
+        let now = at(784111777);
+        let expires_at = |line| test_decode::<SetCookie>(&[line]).unwrap().expires_at(now);
+        assert_eq!(expires_at("a=1; Max-Age=60"), Some(at(784111837)));
+        assert_eq!(expires_at("a=1; Expires=Sun, 06 Nov 1994 08:50:37 GMT"), Some(at(784111837)));
+        assert_eq!(expires_at("a=1"), None);
+        // the last valid one wins
+        assert_eq!(expires_at("a=1; Max-Age=60; max-age=120; Max-Age=soon"), Some(at(784111897)));
+        assert_eq!(expires_at("a=1; Expires=never"), None);
+        assert_eq!(expires_at("a=1; Max-Age=99999999999999999999999"), None);
+    }
+
+    #[test]
+    fn max_age_has_precedence() {
+        let now = at(784111777);
+        let set_cookie = test_decode::<SetCookie>(&[
+            "a=1; Max-Age=60; Expires=Sun, 06 Nov 1994 09:49:37 GMT",
+        ]).unwrap();
+        assert_eq!(set_cookie.expires_at(now), Some(at(784111837)));
+
+        let set_cookie = test_decode::<SetCookie>(&[
+            "a=1; Expires=Sun, 06 Nov 1994 09:49:37 GMT; Max-Age=60",
+        ]).unwrap();
+        assert_eq!(set_cookie.expires_at(now), Some(at(784111837)));
+        assert!(!set_cookie.is_expired(now));
+
+        // Max-Age counts from when it's received, but Expires doesn't.
+        let set_cookie = test_decode::<SetCookie>(&["a=1; Expires=Sun, 06 Nov 1994 08:50:37 GMT"]).unwrap();
+        assert!(!set_cookie.is_expired(now));
+        assert!(set_cookie.is_expired(at(784111837)));
+    }
+
+    #[test]
+    fn negative_max_age_is_zero() {
+        let now = at(784111777);
+        for line in &["a=1; Max-Age=0", "a=1; Max-Age=-1", "a=1; Max-Age=-99999999999999999999999"] {
+            let set_cookie = test_decode::<SetCookie>(&[line]).unwrap();
+            assert_eq!(set_cookie.expires_at(now), Some(UNIX_EPOCH), "{}", line);
+            assert!(set_cookie.is_expired(now), "{}", line);
+        }
+        let set_cookie = test_decode::<SetCookie>(&["a=1; Max-Age=-1; Expires=Sun, 06 Nov 1994 09:49:37 GMT"]).unwrap();
+        assert_eq!(set_cookie.expires_at(now), Some(UNIX_EPOCH));
+    }
+
+    #[test]
+    fn session_cookie() {
+        let is_session = |line| test_decode::<SetCookie>(&[line]).unwrap().is_session_cookie();
+        assert!(is_session("a=1; Path=/; Secure"));
+        assert!(is_session("a=1; Max-Age=soon; Expires=never"));
+        assert!(!is_session("a=1; Max-Age=60"));
+        assert!(!is_session("a=1; Expires=Sun, 06 Nov 1994 08:49:37 GMT"));
+        assert!(!is_session("a=1; Max-Age=-1"));
+        // only the first cookie counts
+        assert!(test_decode::<SetCookie>(&["a=1", "b=2; Max-Age=60"]).unwrap().is_session_cookie());
+    }
+
+    #[test]
+    fn removal() {
+        let removal = SetCookie::removal("lang").build().unwrap();
+        assert!(removal.is_expired(SystemTime::now()));
+        assert!(!removal.is_session_cookie());
+        assert_eq!(test_encode(removal)["set-cookie"], "lang=; Max-Age=0; Expires=Thu, 01 Jan 1970 00:00:00 GMT");
+
+        let removal = SetCookie::removal("__Host-id")
+            .with_path("/")
+            .with_secure()
+            .build()
+            .unwrap();
+        assert_eq!(
+            test_encode(removal)["set-cookie"],
+            "__Host-id=; Path=/; Max-Age=0; Expires=Thu, 01 Jan 1970 00:00:00 GMT; Secure",
+        );
+
+        let removal = SetCookie::removal("lang").with_domain("example.com").with_path("/docs").build().unwrap();
+        assert_eq!(
+            test_encode(removal)["set-cookie"],
+            "lang=; Path=/docs; Domain=example.com; Max-Age=0; Expires=Thu, 01 Jan 1970 00:00:00 GMT",
+        );
+        assert_eq!(SetCookie::removal("a b").build().unwrap_err(), CookieError::InvalidName);
+    }
 }
@@ -0,0 +1,127 @@
+use util::structured::{self, BareItem, Dictionary, Item, Member};
+
+/// `Signature` header, defined in
+/// [RFC9421](https://tools.ietf.org/html/rfc9421#section-4.2)
+///
+/// The `Signature` header field holds the signatures of a message, each
+/// under the label of its parameters in the `Signature-Input` header.
+///
+/// # ABNF
+///
+/// ```text
+/// Signature = sf-dictionary
+/// ; each member is an sf-binary
+/// ```
+///
+/// # Example values
+///
+/// * `sig1=:wqcAqbmYJ2ji2glfAMaRy4gruYYnx2nEFN2HN6jrnDnQCK1u02Gb04v9EDgwUPiu4A0w6vuQv5lIp5WPpBKRCw==:`
+/// * `sig1=:dGVzdA==:, sig2=:dGVzdDI=:`
+///
+/// # Notes
+///
+/// * Parameters on a signature are ignored.
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::Signature;
+///
+/// let signature = Signature::new().with("sig1", b"\x00\x01".to_vec());
+/// assert_eq!(signature.to_string(), "sig1=:AAE=:");
+/// assert_eq!(signature.get("sig1"), Some(&b"\x00\x01"[..]));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Signature(Vec<(String, Vec<u8>)>);
+
+impl Signature {
+    /// Create an empty `Signature`.
+    pub fn new() -> Signature {
+        Signature::default()
+    }
+
+    /// Add the signature labeled `label`, replacing the one it had.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `label` isn't a structured field key: a lowercase letter
+    /// or `*`, followed by lowercase letters, digits, `_`, `-`, `.` or `*`.
+    pub fn with<B: Into<Vec<u8>>>(mut self, label: &str, signature: B) -> Self {
+        assert!(structured::is_key(label), "invalid signature label: {:?}", label);
+        let signature = signature.into();
+        match self.0.iter_mut().find(|(l, _)| l == label) {
+            Some(slot) => slot.1 = signature,
+            None => self.0.push((label.to_owned(), signature)),
+        }
+        self
+    }
+
+    /// Get the bytes of the signature labeled `label`.
+    pub fn get(&self, label: &str) -> Option<&[u8]> {
+        self.0.iter().find(|(l, _)| l == label).map(|(_, signature)| &signature[..])
+    }
+
+    /// Iterate the labels and bytes of each signature, in order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[u8])> {
+        self.0.iter().map(|(label, signature)| (label.as_str(), &signature[..]))
+    }
+}
+
+impl ::Header for Signature {
+    fn name() -> &'static ::HeaderName {
+        static_header_name!("signature")
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        let signatures = structured::parse_dictionary(values)?
+            .into_members()
+            .map(|(label, member)| match member.into_item()?.bare {
+                BareItem::ByteSequence(bytes) => Some((label, bytes)),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        if signatures.is_empty() {
+            None
+        } else {
+            Some(Signature(signatures))
+        }
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        let mut dict = Dictionary::default();
+        for (label, signature) in &self.0 {
+            dict.insert(label, Member::Item(Item::new(BareItem::ByteSequence(signature.clone()))));
+        }
+        values.append_fmt(dict);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Signature;
+    use super::super::{test_decode, test_encode};
+
+    #[test]
+    fn decode() {
+        let signature = test_decode::<Signature>(&["sig1=:aGVsbG8=:;x=1", "sig2=::"]).unwrap();
+        assert_eq!(signature.get("sig1"), Some(&b"hello"[..]));
+        assert_eq!(signature.get("sig2"), Some(&b""[..]));
+        assert_eq!(signature.get("sig3"), None);
+        assert_eq!(signature.iter().map(|(label, _)| label).collect::<Vec<_>>(), ["sig1", "sig2"]);
+
+        for &value in &["", "sig1", "sig1=aGVsbG8=", "sig1=\"aGVsbG8=\"", "sig1=(:aGVsbG8=:)", "sig1=:aGVsbG8="] {
+            assert_eq!(test_decode::<Signature>(&[value]), None, "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn encode() {
+        let signature = Signature::new()
+            .with("a", &b"hello"[..])
+            .with("b", Vec::new())
+            .with("a", &b"world"[..]);
+        assert_eq!(test_encode(signature)["signature"], "a=:d29ybGQ=:, b=::");
+    }
+}
@@ -0,0 +1,501 @@
+use std::fmt;
+
+use util::structured::{self, BareItem, Dictionary, InnerList, Item, Member, Parameters};
+
+/// `Signature-Input` header, defined in
+/// [RFC9421](https://tools.ietf.org/html/rfc9421#section-4.1)
+///
+/// The `Signature-Input` header field gives, for each signature in the
+/// `Signature` header, the components of the message it covers and its
+/// parameters, under the same label.
+///
+/// # ABNF
+///
+/// ```text
+/// Signature-Input = sf-dictionary
+/// ; each member is an inner list of component identifiers, with
+/// ; created, expires, nonce, alg, keyid and tag parameters
+/// ```
+///
+/// # Example values
+///
+/// * `sig1=("@method" "@path" "content-digest");created=1618884473;keyid="k1"`
+/// * `sig-b21=();created=1618884473;nonce="b3k2pp5k7z-50gnwp.yemd"`
+///
+/// # Notes
+///
+/// * A member that isn't an inner list of strings, or a known parameter
+///   with a value of the wrong type, makes the whole header invalid.
+/// * See [`signature::signature_base`](signature/fn.signature_base.html)
+///   for building the string that's signed.
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::{SignatureComponent, SignatureInput, SignatureParams};
+///
+/// let params = SignatureParams::new(vec![
+///     SignatureComponent::new("@method"),
+///     SignatureComponent::new("content-digest"),
+/// ])
+///     .with_created(1618884473)
+///     .with_keyid("k1");
+/// let input = SignatureInput::new().with("sig1", params);
+///
+/// assert_eq!(
+///     input.to_string(),
+///     "sig1=(\"@method\" \"content-digest\");created=1618884473;keyid=\"k1\"",
+/// );
+/// assert_eq!(input.get("sig1").unwrap().keyid(), Some("k1"));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SignatureInput(Vec<(String, SignatureParams)>);
+
+/// The covered components and parameters of one signature in a
+/// `SignatureInput`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignatureParams {
+    components: Vec<SignatureComponent>,
+    params: Parameters,
+}
+
+/// A component of a message covered by a signature, such as a header
+/// field or `@method`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignatureComponent(Item);
+
+impl SignatureInput {
+    /// Create an empty `SignatureInput`.
+    pub fn new() -> SignatureInput {
+        SignatureInput::default()
+    }
+
+    /// Add the parameters of the signature labeled `label`, replacing
+    /// those it had.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `label` isn't a structured field key: a lowercase letter
+    /// or `*`, followed by lowercase letters, digits, `_`, `-`, `.` or `*`.
+    pub fn with(mut self, label: &str, params: SignatureParams) -> Self {
+        assert!(structured::is_key(label), "invalid signature label: {:?}", label);
+        match self.0.iter_mut().find(|(l, _)| l == label) {
+            Some(slot) => slot.1 = params,
+            None => self.0.push((label.to_owned(), params)),
+        }
+        self
+    }
+
+    /// Get the parameters of the signature labeled `label`.
+    pub fn get(&self, label: &str) -> Option<&SignatureParams> {
+        self.0.iter().find(|(l, _)| l == label).map(|(_, params)| params)
+    }
+
+    /// Iterate the labels and parameters of each signature, in order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &SignatureParams)> {
+        self.0.iter().map(|(label, params)| (label.as_str(), params))
+    }
+}
+
+impl SignatureParams {
+    /// Create the parameters of a signature covering `components`, in
+    /// order.
+    pub fn new<I>(components: I) -> SignatureParams
+    where
+        I: IntoIterator<Item = SignatureComponent>,
+    {
+        SignatureParams {
+            components: components.into_iter().collect(),
+            params: Parameters::default(),
+        }
+    }
+
+    /// Get the covered components, in order.
+    pub fn components(&self) -> &[SignatureComponent] {
+        &self.components
+    }
+
+    /// Get the `created` parameter, in seconds since the Unix epoch.
+    pub fn created(&self) -> Option<u64> {
+        self.int("created")
+    }
+
+    /// Get the `expires` parameter, in seconds since the Unix epoch.
+    pub fn expires(&self) -> Option<u64> {
+        self.int("expires")
+    }
+
+    /// Get the `keyid` parameter.
+    pub fn keyid(&self) -> Option<&str> {
+        self.string("keyid")
+    }
+
+    /// Get the `alg` parameter.
+    pub fn alg(&self) -> Option<&str> {
+        self.string("alg")
+    }
+
+    /// Get the `nonce` parameter.
+    pub fn nonce(&self) -> Option<&str> {
+        self.string("nonce")
+    }
+
+    /// Get the `tag` parameter.
+    pub fn tag(&self) -> Option<&str> {
+        self.string("tag")
+    }
+
+    /// Set the `created` parameter, in seconds since the Unix epoch.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `created` has more than 15 digits.
+    pub fn with_created(self, created: u64) -> Self {
+        self.with_int("created", created)
+    }
+
+    /// Set the `expires` parameter, in seconds since the Unix epoch.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `expires` has more than 15 digits.
+    pub fn with_expires(self, expires: u64) -> Self {
+        self.with_int("expires", expires)
+    }
+
+    /// Set the `keyid` parameter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `keyid` contains characters other than printable ASCII.
+    pub fn with_keyid(self, keyid: &str) -> Self {
+        self.with_string("keyid", keyid)
+    }
+
+    /// Set the `alg` parameter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `alg` contains characters other than printable ASCII.
+    pub fn with_alg(self, alg: &str) -> Self {
+        self.with_string("alg", alg)
+    }
+
+    /// Set the `nonce` parameter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `nonce` contains characters other than printable ASCII.
+    pub fn with_nonce(self, nonce: &str) -> Self {
+        self.with_string("nonce", nonce)
+    }
+
+    /// Set the `tag` parameter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tag` contains characters other than printable ASCII.
+    pub fn with_tag(self, tag: &str) -> Self {
+        self.with_string("tag", tag)
+    }
+
+    fn int(&self, key: &str) -> Option<u64> {
+        self.params.get(key).and_then(BareItem::as_integer).map(|i| i as u64)
+    }
+
+    fn string(&self, key: &str) -> Option<&str> {
+        self.params.get(key).and_then(BareItem::as_str)
+    }
+
+    fn with_int(mut self, key: &str, value: u64) -> Self {
+        assert!(value <= 999_999_999_999_999, "invalid signature {}: {}", key, value);
+        self.params.insert(key, BareItem::Integer(value as i64));
+        self
+    }
+
+    fn with_string(mut self, key: &str, value: &str) -> Self {
+        assert!(structured::is_string(value), "invalid signature {}: {:?}", key, value);
+        self.params.insert(key, BareItem::String(value.into()));
+        self
+    }
+
+    fn to_inner_list(&self) -> InnerList {
+        InnerList {
+            items: self.components.iter().map(|component| component.0.clone()).collect(),
+            params: self.params.clone(),
+        }
+    }
+
+    fn from_member(member: Member) -> Option<SignatureParams> {
+        let list = match member {
+            Member::InnerList(list) => list,
+            Member::Item(_) => return None,
+        };
+        let valid_params = list.params.iter().all(|(key, value)| match key {
+            "created" | "expires" => value.as_integer().is_some_and(|i| i >= 0),
+            "keyid" | "alg" | "nonce" | "tag" => value.as_str().is_some(),
+            _ => true,
+        });
+        if !valid_params {
+            return None;
+        }
+        let components = list
+            .items
+            .into_iter()
+            .map(SignatureComponent::from_item)
+            .collect::<Option<_>>()?;
+        Some(SignatureParams {
+            components,
+            params: list.params,
+        })
+    }
+}
+
+impl SignatureComponent {
+    /// Create a component from its name: a lowercase header field name,
+    /// or a derived component starting with `@`, like `@method`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` contains uppercase letters, or characters other
+    /// than printable ASCII.
+    pub fn new(name: &str) -> SignatureComponent {
+        assert!(
+            structured::is_string(name) && !name.bytes().any(|b| b.is_ascii_uppercase()),
+            "invalid signature component: {:?}",
+            name,
+        );
+        SignatureComponent(Item::new(BareItem::String(name.into())))
+    }
+
+    /// Create an `@query-param` component, for the query parameter
+    /// named `name`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` contains characters other than printable ASCII.
+    pub fn query_param(name: &str) -> SignatureComponent {
+        assert!(structured::is_string(name), "invalid query parameter name: {:?}", name);
+        SignatureComponent::new("@query-param").with_param("name", BareItem::String(name.into()))
+    }
+
+    /// Get the name of the component.
+    pub fn name(&self) -> &str {
+        self.0.bare.as_str().expect("SignatureComponent is always a string")
+    }
+
+    /// Check if this is a derived component, like `@method`, rather than
+    /// a header field.
+    pub fn is_derived(&self) -> bool {
+        self.name().starts_with('@')
+    }
+
+    /// Get the `key` parameter, the member of a dictionary field that's
+    /// covered.
+    pub fn key(&self) -> Option<&str> {
+        self.0.params.get("key").and_then(BareItem::as_str)
+    }
+
+    /// Get the `name` parameter of an `@query-param` component.
+    pub fn query_param_name(&self) -> Option<&str> {
+        self.0.params.get("name").and_then(BareItem::as_str)
+    }
+
+    /// Check if the `sf` parameter is set, to cover a structured field
+    /// in its canonical form.
+    pub fn is_sf(&self) -> bool {
+        self.flag("sf")
+    }
+
+    /// Check if the `bs` parameter is set, to cover each field line as a
+    /// byte sequence.
+    pub fn is_bs(&self) -> bool {
+        self.flag("bs")
+    }
+
+    /// Check if the `req` parameter is set, to cover a field of the
+    /// request in a response's signature.
+    pub fn is_req(&self) -> bool {
+        self.flag("req")
+    }
+
+    /// Check if the `tr` parameter is set, to cover a trailer field.
+    pub fn is_tr(&self) -> bool {
+        self.flag("tr")
+    }
+
+    /// Set the `key` parameter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` isn't a structured field key.
+    pub fn with_key(self, key: &str) -> Self {
+        assert!(structured::is_key(key), "invalid dictionary key: {:?}", key);
+        self.with_param("key", BareItem::String(key.into()))
+    }
+
+    /// Set the `sf` parameter.
+    pub fn with_sf(self) -> Self {
+        self.with_param("sf", BareItem::Boolean(true))
+    }
+
+    /// Set the `bs` parameter.
+    pub fn with_bs(self) -> Self {
+        self.with_param("bs", BareItem::Boolean(true))
+    }
+
+    /// Set the `req` parameter.
+    pub fn with_req(self) -> Self {
+        self.with_param("req", BareItem::Boolean(true))
+    }
+
+    /// Set the `tr` parameter.
+    pub fn with_tr(self) -> Self {
+        self.with_param("tr", BareItem::Boolean(true))
+    }
+
+    pub(crate) fn params(&self) -> impl Iterator<Item = (&str, &BareItem)> {
+        self.0.params.iter()
+    }
+
+    fn flag(&self, key: &str) -> bool {
+        self.0.params.get(key).and_then(BareItem::as_bool).unwrap_or(false)
+    }
+
+    fn with_param(mut self, key: &str, value: BareItem) -> Self {
+        self.0.params.insert(key, value);
+        self
+    }
+
+    fn from_item(item: Item) -> Option<SignatureComponent> {
+        match item.bare {
+            BareItem::String(_) => Some(SignatureComponent(item)),
+            _ => None,
+        }
+    }
+}
+
+/// Formats the parameters as the inner list they're serialized to, which
+/// is also the value of `@signature-params` in the signature base.
+impl fmt::Display for SignatureParams {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.to_inner_list(), f)
+    }
+}
+
+/// Formats the component identifier, like `"@query-param";name="id"`.
+impl fmt::Display for SignatureComponent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl ::Header for SignatureInput {
+    fn name() -> &'static ::HeaderName {
+        static_header_name!("signature-input")
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        let signatures = structured::parse_dictionary(values)?
+            .into_members()
+            .map(|(label, member)| Some((label, SignatureParams::from_member(member)?)))
+            .collect::<Option<Vec<_>>>()?;
+
+        if signatures.is_empty() {
+            None
+        } else {
+            Some(SignatureInput(signatures))
+        }
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        let mut dict = Dictionary::default();
+        for (label, params) in &self.0 {
+            dict.insert(label, Member::InnerList(params.to_inner_list()));
+        }
+        values.append_fmt(dict);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SignatureComponent, SignatureInput, SignatureParams};
+    use super::super::{test_decode, test_encode};
+
+    #[test]
+    fn decode() {
+        let input = test_decode::<SignatureInput>(&[
+            "sig1=(\"@method\" \"@path\" \"content-digest\";bs);created=1618884473;keyid=\"k1\"",
+            "sig2=(\"@query-param\";name=\"Pet\" \"example-dict\";key=\"a\");alg=\"ed25519\";x=1",
+        ]).unwrap();
+        assert_eq!(input.iter().map(|(label, _)| label).collect::<Vec<_>>(), ["sig1", "sig2"]);
+
+        let sig1 = input.get("sig1").unwrap();
+        assert_eq!(sig1.created(), Some(1618884473));
+        assert_eq!(sig1.expires(), None);
+        assert_eq!(sig1.keyid(), Some("k1"));
+        let names = sig1.components().iter().map(SignatureComponent::name).collect::<Vec<_>>();
+        assert_eq!(names, ["@method", "@path", "content-digest"]);
+        assert!(sig1.components()[0].is_derived());
+        assert!(sig1.components()[2].is_bs());
+        assert!(!sig1.components()[2].is_sf());
+
+        let sig2 = input.get("sig2").unwrap();
+        assert_eq!(sig2.alg(), Some("ed25519"));
+        assert_eq!(sig2.components()[0].query_param_name(), Some("Pet"));
+        assert_eq!(sig2.components()[1].key(), Some("a"));
+        assert!(input.get("sig3").is_none());
+
+        let empty = test_decode::<SignatureInput>(&["sig=();nonce=\"n\""]).unwrap();
+        assert!(empty.get("sig").unwrap().components().is_empty());
+    }
+
+    #[test]
+    fn decode_invalid() {
+        for &value in &[
+            "",
+            "sig1=\"@method\"",
+            "sig1=(@method)",
+            "sig1=(\"@method\" 1)",
+            "sig1=(\"@method\");created=\"now\"",
+            "sig1=(\"@method\");created=-1",
+            "sig1=(\"@method\");keyid=k1",
+            "SIG=(\"@method\")",
+        ] {
+            assert_eq!(test_decode::<SignatureInput>(&[value]), None, "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn encode() {
+        let params = SignatureParams::new(vec![
+            SignatureComponent::new("@authority"),
+            SignatureComponent::new("example-dict").with_key("a"),
+            SignatureComponent::query_param("Pet"),
+            SignatureComponent::new("digest").with_sf(),
+        ])
+            .with_created(1)
+            .with_expires(2)
+            .with_nonce("n")
+            .with_alg("hmac-sha256")
+            .with_tag("t");
+        let input = SignatureInput::new()
+            .with("a", SignatureParams::new(None))
+            .with("b", params)
+            .with("a", SignatureParams::new(None).with_keyid("k"));
+
+        assert_eq!(
+            test_encode(input)["signature-input"],
+            "a=();keyid=\"k\", \
+             b=(\"@authority\" \"example-dict\";key=\"a\" \"@query-param\";name=\"Pet\" \"digest\";sf)\
+             ;created=1;expires=2;nonce=\"n\";alg=\"hmac-sha256\";tag=\"t\"",
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid signature component")]
+    fn component_names_are_lowercase() {
+        SignatureComponent::new("Content-Digest");
+    }
+}
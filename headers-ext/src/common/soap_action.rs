@@ -0,0 +1,72 @@
+/// `SOAPAction` header, defined in
+/// [SOAP 1.1](https://www.w3.org/TR/2000/NOTE-SOAP-20000508/#_Toc478383528)
+///
+/// The `SOAPAction` header field indicates the intent of a SOAP request,
+/// as a URI. An empty one means the intent is given by the request target.
+///
+/// # ABNF
+///
+/// ```text
+/// soapaction    = "SOAPAction" ":" [ <"> URI-reference <"> ]
+/// ```
+///
+/// # Example values
+///
+/// * `"http://electrocommerce.org/abc#MyMessage"`
+/// * `""`
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::{HeaderMap, HeaderMapExt, SoapAction};
+///
+/// let mut map = HeaderMap::new();
+/// map.typed_insert(SoapAction::new("urn:example#GetStock"));
+/// assert_eq!(map["soapaction"], "\"urn:example#GetStock\"");
+/// assert_eq!(map.typed_get::<SoapAction>().unwrap().action(), "urn:example#GetStock");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Header)]
+#[header(name = "soapaction", quoted)]
+pub struct SoapAction(String);
+
+impl SoapAction {
+    /// Create a `SOAPAction` with the URI of the intent.
+    ///
+    /// Inserting it fails if `action` contains control characters, such
+    /// as CR or LF.
+    pub fn new<S: Into<String>>(action: S) -> SoapAction {
+        SoapAction(action.into())
+    }
+
+    /// The URI of the intent, without the quotes. It's empty when the
+    /// intent is the request target.
+    pub fn action(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SoapAction;
+    use super::super::{test_decode, test_encode};
+    use {HeaderMap, HeaderMapExt};
+
+    #[test]
+    fn decode() {
+        assert_eq!(test_decode(&["\"urn:a\\\"b\""]), Some(SoapAction::new("urn:a\"b")));
+        assert_eq!(test_decode(&["\"\""]), Some(SoapAction::new("")));
+        assert_eq!(test_decode::<SoapAction>(&["\"urn:a"]), None);
+
+        // Commonly sent without quotes.
+        assert_eq!(test_decode(&["urn:a"]), Some(SoapAction::new("urn:a")));
+    }
+
+    #[test]
+    fn encode() {
+        let headers = test_encode(SoapAction::new("urn:a\\b"));
+        assert_eq!(headers["soapaction"], "\"urn:a\\\\b\"");
+
+        assert!(HeaderMap::new().typed_try_insert(SoapAction::new("urn:\r\na")).is_err());
+    }
+}
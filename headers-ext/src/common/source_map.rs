@@ -0,0 +1,182 @@
+
+use http::{HeaderMap, Uri};
+
+use util::HeaderString;
+use ::HeaderMapExt;
+
+/// `SourceMap` header, defined in the
+/// [Source Map spec](https://tc39.es/source-map/#linking-through-http-headers)
+///
+/// The `SourceMap` header links a generated file, such as minified
+/// JavaScript, to the source map describing it. Older tools send the
+/// same value as [`XSourceMap`](struct.XSourceMap.html).
+///
+/// # ABNF
+///
+/// ```text
+/// SourceMap = URI-reference
+/// ```
+///
+/// # Example values
+///
+/// * `/static/app.js.map`
+/// * `https://cdn.example.com/app.js.map`
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::SourceMap;
+///
+/// let source_map = SourceMap::from_static("/static/app.js.map");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SourceMap(HeaderString);
+
+/// `X-SourceMap` header, the legacy name of [`SourceMap`](struct.SourceMap.html).
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::XSourceMap;
+///
+/// let source_map = XSourceMap::from_static("/static/app.js.map");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct XSourceMap(HeaderString);
+
+impl SourceMap {
+    /// Create a `SourceMap` from a static string.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the static string is not a legal header value.
+    pub fn from_static(src: &'static str) -> SourceMap {
+        SourceMap(HeaderString::from_static(src))
+    }
+
+    /// View the source map reference as a `&str`.
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    /// Parse the source map reference as a `Uri`.
+    ///
+    /// Returns `None` if the reference isn't something `Uri` can
+    /// represent, such as `../app.js.map`.
+    pub fn uri(&self) -> Option<Uri> {
+        self.as_str().parse().ok()
+    }
+
+    /// Get the source map from a `HeaderMap`, preferring `SourceMap` over
+    /// the legacy `X-SourceMap`.
+    pub fn from_either(headers: &HeaderMap) -> Option<SourceMap> {
+        headers
+            .typed_get::<SourceMap>()
+            .or_else(|| headers.typed_get::<XSourceMap>().map(SourceMap::from))
+    }
+}
+
+impl XSourceMap {
+    /// Create a `XSourceMap` from a static string.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the static string is not a legal header value.
+    pub fn from_static(src: &'static str) -> XSourceMap {
+        XSourceMap(HeaderString::from_static(src))
+    }
+
+    /// View the source map reference as a `&str`.
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    /// Parse the source map reference as a `Uri`.
+    pub fn uri(&self) -> Option<Uri> {
+        self.as_str().parse().ok()
+    }
+}
+
+impl From<XSourceMap> for SourceMap {
+    fn from(legacy: XSourceMap) -> SourceMap {
+        SourceMap(legacy.0)
+    }
+}
+
+impl From<SourceMap> for XSourceMap {
+    fn from(source_map: SourceMap) -> XSourceMap {
+        XSourceMap(source_map.0)
+    }
+}
+
+impl ::Header for SourceMap {
+    fn name() -> &'static ::HeaderName {
+        static_header_name!("sourcemap")
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        HeaderString::from_val(values.next()?).map(SourceMap)
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        values.append((&self.0).into());
+    }
+}
+
+impl ::Header for XSourceMap {
+    fn name() -> &'static ::HeaderName {
+        static_header_name!("x-sourcemap")
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        HeaderString::from_val(values.next()?).map(XSourceMap)
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        values.append((&self.0).into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::HeaderMap;
+
+    use super::*;
+    use super::super::{test_decode, test_encode};
+
+    #[test]
+    fn decode_relative() {
+        let source_map = test_decode::<SourceMap>(&["/static/app.js.map"]).unwrap();
+        assert_eq!(source_map.as_str(), "/static/app.js.map");
+        assert_eq!(source_map.uri().unwrap().path(), "/static/app.js.map");
+    }
+
+    #[test]
+    fn encode() {
+        let headers = test_encode(SourceMap::from_static("/app.js.map"));
+        assert_eq!(headers["sourcemap"], "/app.js.map");
+
+        let headers = test_encode(XSourceMap::from_static("/app.js.map"));
+        assert_eq!(headers["x-sourcemap"], "/app.js.map");
+    }
+
+    #[test]
+    fn from_either_prefers_standard_name() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(SourceMap::from_either(&headers), None);
+
+        headers.typed_insert(XSourceMap::from_static("/legacy.js.map"));
+        assert_eq!(
+            SourceMap::from_either(&headers),
+            Some(SourceMap::from_static("/legacy.js.map")),
+        );
+
+        headers.typed_insert(SourceMap::from_static("/app.js.map"));
+        assert_eq!(
+            SourceMap::from_either(&headers),
+            Some(SourceMap::from_static("/app.js.map")),
+        );
+    }
+}
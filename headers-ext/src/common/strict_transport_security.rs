@@ -1,7 +1,7 @@
 use std::fmt;
 use std::time::Duration;
 
-use util::Seconds;
+use util::{quoted, Seconds};
 
 /// `StrictTransportSecurity` header, defined in [RFC6797](https://tools.ietf.org/html/rfc6797)
 ///
@@ -70,10 +70,20 @@ impl StrictTransportSecurity {
             include_subdomains: false
         }
     }
+
+    /// Get how long the host should be regarded as a Known HSTS Host.
+    pub fn max_age(&self) -> Duration {
+        self.max_age.into()
+    }
+
+    /// Check if the policy also applies to subdomains.
+    pub fn include_subdomains(&self) -> bool {
+        self.include_subdomains
+    }
 }
 
 enum Directive {
-    MaxAge(u64),
+    MaxAge(Seconds),
     IncludeSubdomains,
     Unknown
 }
@@ -88,27 +98,22 @@ fn from_str(s: &str) -> Option<StrictTransportSecurity> {
             match (sub.next(), sub.next()) {
                 (Some(left), Some(right))
                 if left.trim().eq_ignore_ascii_case("max-age") => {
-                    right
-                        .trim()
-                        .trim_matches('"')
-                        .parse()
-                        .ok()
+                    Seconds::parse(quoted::unquote(right.trim())?.as_bytes())
                         .map(Directive::MaxAge)
                 },
                 _ => Some(Directive::Unknown)
             }
         })
-        .fold(Some((None, None)), |res, dir| match (res, dir) {
-            (Some((None, sub)), Some(Directive::MaxAge(age))) => Some((Some(age), sub)),
-            (Some((age, None)), Some(Directive::IncludeSubdomains)) => Some((age, Some(()))),
-            (Some((Some(_), _)), Some(Directive::MaxAge(_))) |
-            (Some((_, Some(_))), Some(Directive::IncludeSubdomains)) |
-            (_, None) => None,
-            (res, _) => res
+        .try_fold((None, None), |res, dir| match (res, dir?) {
+            ((None, sub), Directive::MaxAge(age)) => Some((Some(age), sub)),
+            ((age, None), Directive::IncludeSubdomains) => Some((age, Some(()))),
+            ((Some(_), _), Directive::MaxAge(_)) |
+            ((_, Some(_)), Directive::IncludeSubdomains) => None,
+            (res, _) => Some(res)
         })
         .and_then(|res| match res {
             (Some(age), sub) => Some(StrictTransportSecurity {
-                max_age: Duration::from_secs(age).into(),
+                max_age: age,
                 include_subdomains: sub.is_some()
             }),
             _ => None
@@ -116,7 +121,9 @@ fn from_str(s: &str) -> Option<StrictTransportSecurity> {
 }
 
 impl ::Header for StrictTransportSecurity {
-    const NAME: &'static ::HeaderName = &::http::header::STRICT_TRANSPORT_SECURITY;
+    fn name() -> &'static ::HeaderName {
+        &::http::header::STRICT_TRANSPORT_SECURITY
+    }
 
     fn decode(values: &mut ::Values) -> Option<Self> {
         values
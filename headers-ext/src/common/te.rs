@@ -25,9 +25,25 @@ use util::FlatCsv;
 ///
 /// # Examples
 ///
-#[derive(Clone, Debug, PartialEq, Header)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Te(FlatCsv);
 
+impl ::Header for Te {
+    fn name() -> &'static ::HeaderName {
+        &::http::header::TE
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        // Unlike most lists, an empty `TE` is meaningful: only `chunked`
+        // is accepted.
+        FlatCsv::from_values(values).map(Te)
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        values.append((&self.0).into());
+    }
+}
+
 impl Te {
     /// Create a `TE: trailers` header.
     pub fn trailers() -> Self {
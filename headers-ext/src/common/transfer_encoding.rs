@@ -51,17 +51,9 @@ impl TransferEncoding {
     pub fn is_chunked(&self) -> bool {
         self
             .0
-            .value
-            //TODO(perf): use split and trim (not an actual method) on &[u8]
-            .to_str()
-            .map(|s| s
-                .split(',')
-                .next_back()
-                .map(|encoding| {
-                    encoding.trim() == "chunked"
-                })
-                .expect("split always has at least 1 item")
-            )
+            .iter()
+            .last()
+            .map(|encoding| encoding.eq_ignore_ascii_case("chunked"))
             .unwrap_or(false)
     }
 }
@@ -99,4 +91,10 @@ mod tests {
         let te = test_decode::<TransferEncoding>(&["gzip", "chunked"]).unwrap();
         assert!(te.is_chunked());
     }
+
+    #[test]
+    fn decode_mixed_case_is_chunked() {
+        let te = test_decode::<TransferEncoding>(&["gzip, Chunked"]).unwrap();
+        assert!(te.is_chunked());
+    }
 }
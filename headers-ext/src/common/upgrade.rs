@@ -1,4 +1,5 @@
 use ::HeaderValue;
+use util::token;
 
 /// `Upgrade` header, defined in [RFC7230](http://tools.ietf.org/html/rfc7230#section-6.7)
 ///
@@ -47,4 +48,29 @@ impl Upgrade {
     pub fn websocket() -> Upgrade {
         Upgrade(HeaderValue::from_static("websocket"))
     }
+
+    /// Check if `websocket` is one of the offered protocols.
+    pub fn is_websocket(&self) -> bool {
+        self.0
+            .to_str()
+            .map(|s| s
+                .split(',')
+                .any(|protocol| token::eq_ignore_ascii_case_trimmed(protocol.as_bytes(), "websocket"))
+            )
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Upgrade;
+    use super::super::test_decode;
+
+    #[test]
+    fn is_websocket() {
+        assert!(Upgrade::websocket().is_websocket());
+        assert!(test_decode::<Upgrade>(&["WebSocket"]).unwrap().is_websocket());
+        assert!(test_decode::<Upgrade>(&["h2c, websocket"]).unwrap().is_websocket());
+        assert!(!test_decode::<Upgrade>(&["h2c"]).unwrap().is_websocket());
+    }
 }
@@ -1,4 +1,4 @@
-use util::HeaderValueString;
+use util::HeaderString;
 
 /// `User-Agent` header, defined in
 /// [RFC7231](http://tools.ietf.org/html/rfc7231#section-5.5.3)
@@ -27,6 +27,9 @@ use util::HeaderValueString;
 /// # Notes
 ///
 /// * The parser does not split the value
+/// * Runs of whitespace, such as obs-fold leaves, are collapsed into a
+///   single space, so they aren't encoded again. `UserAgentView` borrows
+///   the value as it is.
 ///
 /// # Example
 ///
@@ -34,15 +37,15 @@ use util::HeaderValueString;
 /// # extern crate headers_ext as headers;
 /// use headers::UserAgent;
 ///
-/// let ua = UserAgent::from_static("hyper/0.12.2"));
+/// let ua = UserAgent::from_static("hyper/0.12.2");
 /// ```
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Header)]
-pub struct UserAgent(HeaderValueString);
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UserAgent(HeaderString);
 
 impl UserAgent {
     /// Create a `UserAgent` from a static string.
     pub fn from_static(src: &'static str) -> UserAgent {
-        UserAgent(HeaderValueString::from_static(src))
+        UserAgent(HeaderString::from_static(src))
     }
 
     /// View this `UserAgent` as a `&str`.
@@ -50,3 +53,72 @@ impl UserAgent {
         self.0.as_str()
     }
 }
+
+impl ::Header for UserAgent {
+    fn name() -> &'static ::HeaderName {
+        &::http::header::USER_AGENT
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        HeaderString::from_val_unfolded(values.next()?).map(UserAgent)
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        values.append((&self.0).into());
+    }
+}
+
+/// A view of a `User-Agent` header that borrows from the `HeaderMap`, for
+/// `HeaderMapExt::typed_get_borrowed`.
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::{HeaderMap, HeaderMapExt, HeaderValue, UserAgentView};
+///
+/// let mut headers = HeaderMap::new();
+/// headers.insert("user-agent", HeaderValue::from_static("curl/8.5.0"));
+///
+/// let ua = headers.typed_get_borrowed::<UserAgentView>().unwrap();
+/// assert_eq!(ua.as_str(), "curl/8.5.0");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UserAgentView<'a>(&'a str);
+
+impl<'a> UserAgentView<'a> {
+    /// View this user agent as a `&str`.
+    pub fn as_str(&self) -> &'a str {
+        self.0
+    }
+}
+
+impl<'a> ::BorrowedHeader<'a> for UserAgentView<'a> {
+    fn name() -> &'static ::HeaderName {
+        &::http::header::USER_AGENT
+    }
+
+    fn decode_borrowed(values: &mut ::Values<'a>) -> Option<Self> {
+        values.next()?.to_str().ok().map(UserAgentView)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UserAgent;
+    use super::super::{test_decode, test_encode};
+
+    #[test]
+    fn decode_folded() {
+        let ua = test_decode::<UserAgent>(&["Mozilla/5.0\t(X11; Linux x86_64)  \tAppleWebKit/537.36 "]).unwrap();
+        assert_eq!(ua.as_str(), "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36");
+        assert_eq!(test_encode(ua)["user-agent"], "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36");
+
+        // Unfolded by a parser that only dropped the CRLF.
+        let ua = test_decode::<UserAgent>(&["Mozilla/5.0 \t(X11)"]).unwrap();
+        assert_eq!(test_encode(ua)["user-agent"], "Mozilla/5.0 (X11)");
+
+        let ua = test_decode::<UserAgent>(&["curl/8.5.0"]).unwrap();
+        assert_eq!(ua, UserAgent::from_static("curl/8.5.0"));
+    }
+}
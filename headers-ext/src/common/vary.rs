@@ -1,6 +1,10 @@
+use std::iter::FromIterator;
+
+use headers_core::decode::TryFromValues;
+
 use util::FlatCsv;
 
-use {HeaderValue};
+use {HeaderName, HeaderValue};
 
 /// `Vary` header, defined in [RFC7231](https://tools.ietf.org/html/rfc7231#section-7.1.4)
 ///
@@ -29,9 +33,40 @@ use {HeaderValue};
 ///
 /// let vary = Vary::any();
 /// ```
-#[derive(Debug, Clone, PartialEq, Header)]
+///
+/// A `Vary` can be built up as a response is negotiated:
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// extern crate http;
+/// use http::header::{ACCEPT, ACCEPT_ENCODING};
+/// use headers::Vary;
+///
+/// let mut vary = vec![ACCEPT].into_iter().collect::<Vary>();
+/// vary.extend(vec![ACCEPT_ENCODING, ACCEPT]);
+/// assert_eq!(vary.iter_strs().collect::<Vec<_>>(), ["accept", "accept-encoding"]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
 pub struct Vary(FlatCsv);
 
+impl ::Header for Vary {
+    fn name() -> &'static ::HeaderName {
+        &::http::header::VARY
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        TryFromValues::try_from_values(values).map(Vary)
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        if cfg!(feature = "canonical") {
+            values.append((&self.canonical()).into());
+        } else {
+            values.append((&self.0).into());
+        }
+    }
+}
+
 impl Vary {
     /// Create a new `Very: *` header.
     pub fn any() -> Vary {
@@ -47,6 +82,93 @@ impl Vary {
     pub fn iter_strs(&self) -> impl Iterator<Item = &str> {
         self.0.iter()
     }
+
+    /// Add `name` if it isn't already listed, returning whether it was
+    /// added.
+    ///
+    /// `*` absorbs it.
+    pub fn insert(&mut self, name: HeaderName) -> bool {
+        if self.is_any() || self.iter_strs().any(|have| have.eq_ignore_ascii_case(name.as_str())) {
+            return false;
+        }
+        self.extend(Some(name));
+        true
+    }
+
+    /// Lowercase the header names, sort them byte-wise and drop repeats,
+    /// so equal sets always encode the same. A `*` drops every name.
+    ///
+    /// When the `canonical` feature is enabled, this is done on every
+    /// encode.
+    pub fn canonicalize(&mut self) {
+        self.0 = self.canonical();
+    }
+
+    fn canonical(&self) -> FlatCsv {
+        if self.is_any() {
+            return HeaderValue::from_static("*").into();
+        }
+        let mut names = self.0.iter().map(str::to_ascii_lowercase).collect::<Vec<_>>();
+        names.sort();
+        names.dedup();
+        names
+            .iter()
+            .map(|name| HeaderValue::from_str(name).expect("a Vary item is a valid HeaderValue"))
+            .collect()
+    }
+
+    /// Add the header names of `other` that aren't already listed.
+    ///
+    /// `*` absorbs anything merged with it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # extern crate headers_ext as headers;
+    /// use headers::Vary;
+    ///
+    /// let mut vary = "accept".parse::<Vary>().unwrap();
+    /// vary.merge("Accept, accept-encoding".parse().unwrap());
+    /// assert_eq!(vary.iter_strs().collect::<Vec<_>>(), ["accept", "accept-encoding"]);
+    /// ```
+    pub fn merge(&mut self, other: Vary) {
+        if self.is_any() {
+            return;
+        }
+        if other.is_any() {
+            *self = Vary::any();
+            return;
+        }
+        self.0 = self.0.union(&other.0, str::eq_ignore_ascii_case);
+    }
+}
+
+impl ::MergeableHeader for Vary {
+    fn merge(&mut self, other: Vary) {
+        Vary::merge(self, other);
+    }
+}
+
+impl Extend<HeaderName> for Vary {
+    /// Add the names of `iter` that aren't already listed, like `merge`.
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = HeaderName>,
+    {
+        self.merge(Vary(iter.into_iter().map(HeaderValue::from).collect()));
+    }
+}
+
+impl FromIterator<HeaderName> for Vary {
+    /// Collect the names, dropping repeats.
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = HeaderName>,
+    {
+        let mut vary = Vary(HeaderValue::from_static("").into());
+        vary.extend(iter);
+        vary
+    }
 }
 
 /*
@@ -76,4 +198,57 @@ mod tests {
     fn any_is_any() {
         assert!(Vary::any().is_any());
     }
+
+    #[test]
+    fn canonicalize() {
+        let mut vary = "Origin, accept-encoding,,ORIGIN, Accept".parse::<Vary>().unwrap();
+        vary.canonicalize();
+        assert_eq!(vary.iter_strs().collect::<Vec<_>>(), ["accept", "accept-encoding", "origin"]);
+        assert_eq!(HeaderValue::from(&vary), "accept, accept-encoding, origin");
+
+        let once = vary.clone();
+        vary.canonicalize();
+        assert_eq!(vary, once);
+
+        let mut any = "accept, *".parse::<Vary>().unwrap();
+        any.canonicalize();
+        assert_eq!(HeaderValue::from(&any), "*");
+    }
+
+    #[test]
+    fn merge_any_absorbs() {
+        let names = ["accept".parse::<Vary>().unwrap(), Vary::any()];
+        for (a, b) in [(&names[0], &names[1]), (&names[1], &names[0])] {
+            let mut vary = a.clone();
+            vary.merge(b.clone());
+            assert!(vary.is_any());
+            assert_eq!(vary.iter_strs().collect::<Vec<_>>(), ["*"]);
+        }
+    }
+
+    #[test]
+    fn extend() {
+        let mut vary = "Accept,  Origin".parse::<Vary>().unwrap();
+        vary.extend(vec![::http::header::ORIGIN, ::http::header::COOKIE, ::http::header::COOKIE]);
+        #[cfg(not(feature = "canonical"))]
+        assert_eq!(HeaderValue::from(&vary), "Accept,  Origin, cookie");
+
+        assert!(vary.insert(::http::header::ACCEPT_LANGUAGE));
+        assert!(!vary.insert(::http::header::ACCEPT));
+        assert_eq!(vary.iter_strs().collect::<Vec<_>>(), ["Accept", "Origin", "cookie", "accept-language"]);
+
+        let mut unchanged = "Accept,  Origin".parse::<Vary>().unwrap();
+        unchanged.extend(vec![::http::header::ACCEPT]);
+        #[cfg(not(feature = "canonical"))]
+        assert_eq!(HeaderValue::from(&unchanged), "Accept,  Origin");
+
+        let mut any = Vary::any();
+        any.extend(vec![::http::header::ACCEPT]);
+        assert!(!any.insert(::http::header::COOKIE));
+        assert_eq!(HeaderValue::from(&any), "*");
+
+        let collected = vec![::http::header::ACCEPT, ::http::header::ACCEPT].into_iter().collect::<Vary>();
+        assert_eq!(HeaderValue::from(&collected), "accept");
+        assert_eq!(HeaderValue::from(&Vec::new().into_iter().collect::<Vary>()), "");
+    }
 }
@@ -0,0 +1,115 @@
+use util::{split_elements, token, FlatCsv};
+
+/// `Via` header, defined in
+/// [RFC7230](https://tools.ietf.org/html/rfc7230#section-5.7.1)
+///
+/// The `Via` header field is added by proxies and gateways, to indicate
+/// the intermediate protocols and recipients a message went through.
+///
+/// # ABNF
+///
+/// ```text
+/// Via = 1#( received-protocol RWS received-by [ RWS comment ] )
+///
+/// received-protocol = [ protocol-name "/" ] protocol-version
+/// received-by       = ( uri-host [ ":" port ] ) / pseudonym
+/// pseudonym         = token
+/// ```
+///
+/// # Example values
+///
+/// * `1.0 fred, 1.1 p.example.net`
+/// * `HTTP/1.1 proxy (squid/3.5)`
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::Via;
+///
+/// let via = "1.0 fred, 1.1 p.example.net".parse::<Via>().unwrap();
+/// assert_eq!(via.iter().collect::<Vec<_>>(), ["1.0 fred", "1.1 p.example.net"]);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Via(FlatCsv);
+
+impl Via {
+    /// Iterate the recipients, in the order the message went through them,
+    /// each as `received-protocol received-by [comment]`.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.0
+            .value
+            .to_str()
+            .ok()
+            .into_iter()
+            .flat_map(split_elements)
+    }
+}
+
+impl ::Header for Via {
+    fn name() -> &'static ::HeaderName {
+        &::http::header::VIA
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        let via = Via(FlatCsv::from_values(values)?);
+        if via.iter().next().is_some() && via.iter().all(is_element) {
+            Some(via)
+        } else {
+            None
+        }
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        values.append((&self.0).into());
+    }
+}
+
+fn is_element(s: &str) -> bool {
+    let (protocol, rest) = split_rws(s);
+    let (received_by, comment) = split_rws(rest);
+
+    let is_protocol = protocol.split('/').count() <= 2
+        && protocol.split('/').all(|part| token::is_token(part.as_bytes()));
+    // A host, possibly an IPv6 literal, with an optional port, or a token.
+    let is_received_by = !received_by.is_empty()
+        && received_by
+            .bytes()
+            .all(|b| b == b':' || b == b'[' || b == b']' || token::is_token(&[b]));
+    let is_comment = comment.is_empty()
+        || (comment.len() >= 2 && comment.starts_with('(') && comment.ends_with(')'));
+
+    is_protocol && is_received_by && is_comment
+}
+
+// Split `s` at its first whitespace, dropping the whitespace.
+fn split_rws(s: &str) -> (&str, &str) {
+    let is_ws = |c| c == ' ' || c == '\t';
+    match s.find(is_ws) {
+        Some(i) => (&s[..i], s[i..].trim_start_matches(is_ws)),
+        None => (s, ""),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Via;
+    use super::super::{test_decode, test_encode};
+
+    #[test]
+    fn decode() {
+        let via = test_decode::<Via>(&["1.0 fred, 1.1 p.example.net", "HTTP/2 [::1]:8080 (a, (b))"]).unwrap();
+        assert_eq!(
+            via.iter().collect::<Vec<_>>(),
+            ["1.0 fred", "1.1 p.example.net", "HTTP/2 [::1]:8080 (a, (b))"],
+        );
+        assert_eq!(test_encode(via)["via"], "1.0 fred, 1.1 p.example.net, HTTP/2 [::1]:8080 (a, (b))");
+    }
+
+    #[test]
+    fn decode_invalid() {
+        for s in &["", " , ", "1.1", "1.1 fred, proxy", "1.1 fr\"ed", "1.1 fred comment", "1.1/ fred", "1.1 fred ()x"] {
+            assert_eq!(test_decode::<Via>(&[s]), None, "{:?}", s);
+        }
+    }
+}
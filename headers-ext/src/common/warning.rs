@@ -1,7 +1,7 @@
 use std::fmt;
-use std::str::{FromStr};
-use {Header, HttpDate, Raw};
-use parsing::from_one_raw_str;
+use std::time::SystemTime;
+
+use util::{quoted, token, unfold, HttpDate};
 
 /// `Warning` header, defined in [RFC7234](https://tools.ietf.org/html/rfc7234#section-5.5)
 ///
@@ -10,6 +10,14 @@ use parsing::from_one_raw_str;
 /// in the status code. This header is sometimes used as backwards
 /// compatible way to notify of a deprecated API.
 ///
+/// [RFC9111](https://tools.ietf.org/html/rfc9111#appendix-B) obsoletes the
+/// `Warning` header, as it wasn't widely used, and caches no longer add the
+/// 1xx codes. Code 299 is still seen in the wild, for deprecation notices.
+///
+/// A `Warning` is a single `warning-value`. Use
+/// [`Repeated<Warning>`](list/struct.Repeated.html) for a header with one
+/// per field line.
+///
 /// # ABNF
 ///
 /// ```text
@@ -27,156 +35,352 @@ use parsing::from_one_raw_str;
 ///
 /// # Example values
 ///
-/// * `Warning: 112 - "network down" "Sat, 25 Aug 2012 23:34:45 GMT"`
-/// * `Warning: 299 - "Deprecated API " "Tue, 15 Nov 1994 08:12:31 GMT"`
-/// * `Warning: 299 api.hyper.rs:8080 "Deprecated API : use newapi.hyper.rs instead."`
-/// * `Warning: 299 api.hyper.rs:8080 "Deprecated API : use newapi.hyper.rs instead." "Tue, 15 Nov 1994 08:12:31 GMT"`
-///
-/// # Examples
-///
-/// ```
-/// use headers::{Headers, Warning};
-///
-/// let mut headers = Headers::new();
-/// headers.set(
-///     Warning{
-///         code: 299,
-///         agent: "api.hyper.rs".to_owned(),
-///         text: "Deprecated".to_owned(),
-///         date: None
-///     }
-/// );
-/// ```
+/// * `112 - "network down" "Sat, 25 Aug 2012 23:34:45 GMT"`
+/// * `299 - "Deprecated API " "Tue, 15 Nov 1994 08:12:31 GMT"`
+/// * `299 api.hyper.rs:8080 "Deprecated API : use newapi.hyper.rs instead."`
 ///
-/// ```
-/// use headers::{Headers, HttpDate, Warning};
+/// # Example
 ///
-/// let mut headers = Headers::new();
-/// headers.set(
-///     Warning{
-///         code: 299,
-///         agent: "api.hyper.rs".to_owned(),
-///         text: "Deprecated".to_owned(),
-///         date: "Tue, 15 Nov 1994 08:12:31 GMT".parse::<HttpDate>().ok()
-///     }
-/// );
 /// ```
+/// # extern crate headers_ext as headers;
+/// use headers::{HeaderMap, HeaderMapExt, Warning};
 ///
-/// ```
-/// use std::time::SystemTime;
-/// use headers::{Headers, Warning};
+/// let mut map = HeaderMap::new();
+/// map.typed_insert(Warning::misc_persistent("api.hyper.rs", "Deprecated API"));
+/// assert_eq!(map["warning"], "299 api.hyper.rs \"Deprecated API\"");
 ///
-/// let mut headers = Headers::new();
-/// headers.set(
-///     Warning{
-///         code: 199,
-///         agent: "api.hyper.rs".to_owned(),
-///         text: "Deprecated".to_owned(),
-///         date: Some(SystemTime::now().into())
-///     }
-/// );
+/// let warning = map.typed_get::<Warning>().unwrap();
+/// assert!(warning.is_persistent());
 /// ```
-#[derive(PartialEq, Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Warning {
-    /// The 3 digit warn code.
-    pub code: u16,
-    /// The name or pseudonym of the server adding this header.
-    pub agent: String,
-    /// The warning message describing the error.
-    pub text: String,
-    /// An optional warning date.
-    pub date: Option<HttpDate>
+    code: u16,
+    agent: String,
+    text: String,
+    date: Option<HttpDate>,
 }
 
-impl Header for Warning {
-    fn header_name() -> &'static str {
-        static NAME: &'static str = "Warning";
-        NAME
+impl Warning {
+    /// `110 Response is Stale`, added by a cache serving a stale response.
+    pub const RESPONSE_IS_STALE: u16 = 110;
+    /// `111 Revalidation Failed`, added by a cache that couldn't validate a
+    /// stale response.
+    pub const REVALIDATION_FAILED: u16 = 111;
+    /// `112 Disconnected Operation`, added by a cache cut from the network.
+    pub const DISCONNECTED_OPERATION: u16 = 112;
+    /// `113 Heuristic Expiration`, added by a cache that heuristically
+    /// chose a freshness lifetime over 24 hours.
+    pub const HEURISTIC_EXPIRATION: u16 = 113;
+    /// `199 Miscellaneous Warning`.
+    pub const MISCELLANEOUS_WARNING: u16 = 199;
+    /// `214 Transformation Applied`, added by a proxy that transformed the
+    /// content.
+    pub const TRANSFORMATION_APPLIED: u16 = 214;
+    /// `299 Miscellaneous Persistent Warning`.
+    pub const MISCELLANEOUS_PERSISTENT_WARNING: u16 = 299;
+
+    /// Create a `Warning` with a code, agent and text.
+    ///
+    /// These aren't checked until the header is inserted, by
+    /// `Header::validate`: the code must be 3 digits, the agent must not
+    /// be empty, or have whitespace, quotes or control characters, and the
+    /// text must not have control characters other than HTAB.
+    pub fn new(code: u16, agent: &str, text: &str) -> Warning {
+        Warning {
+            code,
+            agent: agent.to_owned(),
+            text: text.to_owned(),
+            date: None,
+        }
     }
 
-    fn parse_header(raw: &Raw) -> ::Result<Warning> {
-        from_one_raw_str(raw)
+    /// Create a `299 Miscellaneous Persistent Warning`, such as to notify
+    /// of a deprecated API.
+    pub fn misc_persistent(agent: &str, text: &str) -> Warning {
+        Warning::new(Warning::MISCELLANEOUS_PERSISTENT_WARNING, agent, text)
     }
 
-    fn fmt_header(&self, f: &mut ::Formatter) -> fmt::Result {
-        f.fmt_line(self)
+    /// Create a `110 Response is Stale` warning, with the agent as `-` if
+    /// unknown.
+    pub fn stale(agent: &str) -> Warning {
+        Warning::new(Warning::RESPONSE_IS_STALE, agent, "Response is Stale")
+    }
+
+    /// Set the date the warning was added.
+    pub fn with_date(mut self, date: SystemTime) -> Self {
+        self.date = Some(date.into());
+        self
+    }
+
+    /// Get the code of the warning.
+    pub fn code(&self) -> u16 {
+        self.code
+    }
+
+    /// Get the host or pseudonym of the agent that added the warning.
+    pub fn agent(&self) -> &str {
+        &self.agent
+    }
+
+    /// Get the text of the warning, unescaped.
+    ///
+    /// Runs of whitespace in it, such as obs-fold leaves, are collapsed
+    /// into a single space when decoded.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Get the date the warning was added, if any.
+    pub fn date(&self) -> Option<SystemTime> {
+        self.date.map(SystemTime::from)
+    }
+
+    /// Check if the warning is about the content or its transformation,
+    /// with a 2xx code, so it's kept with a stored response.
+    pub fn is_persistent(&self) -> bool {
+        (200..300).contains(&self.code)
+    }
+
+    /// Check if a cache must delete the warning from a stored response
+    /// once it's validated, as it's about the freshness of the response,
+    /// with a 1xx code.
+    ///
+    /// This follows [RFC7234](https://tools.ietf.org/html/rfc7234#section-4.3.4),
+    /// as RFC9111 no longer defines these codes.
+    pub fn should_be_deleted_on_revalidation(&self) -> bool {
+        (100..200).contains(&self.code)
+    }
+}
+
+impl ::Header for Warning {
+    fn name() -> &'static ::HeaderName {
+        &::http::header::WARNING
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        let mode = values.mode();
+        let s = values.next()?.to_str().ok()?;
+        // Unfolding obs-fold sloppily can leave whitespace at the ends.
+        let s = match mode {
+            ::ParseMode::Strict => s,
+            ::ParseMode::Lenient => token::trim_ows(s),
+        };
+
+        let (code, rest) = split_sp(s)?;
+        if code.len() != 3 || !code.bytes().all(|b| b.is_ascii_digit()) || code.starts_with('0') {
+            return None;
+        }
+        let (agent, rest) = split_sp(rest)?;
+        if !is_agent(agent) {
+            return None;
+        }
+        let end = quoted_end(rest)?;
+        let text = unfold(&quoted::unquote(&rest[..end])?).into_owned();
+        let date = match &rest[end..] {
+            "" => None,
+            date => {
+                let date = date.strip_prefix(" \"")?.strip_suffix('"')?;
+                Some(HttpDate::parse(date, mode)?)
+            },
+        };
+
+        Some(Warning {
+            code: code.parse().ok()?,
+            agent: agent.to_owned(),
+            text,
+            date,
+        })
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        values.append_fmt(self.to_string());
+    }
+
+    fn validate(&self) -> Result<(), ::InvalidHeader> {
+        if (100..=999).contains(&self.code) && is_agent(&self.agent) && is_text(&self.text) {
+            Ok(())
+        } else {
+            Err(::InvalidHeader::new())
+        }
     }
 }
 
 impl fmt::Display for Warning {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self.date {
-            Some(date) => write!(f, "{:03} {} \"{}\" \"{}\"", self.code, self.agent, self.text, date),
-            None => write!(f, "{:03} {} \"{}\"", self.code, self.agent, self.text)
+        write!(f, "{} {} \"", self.code, self.agent)?;
+        for c in self.text.chars() {
+            if c == '"' || c == '\\' {
+                f.write_str("\\")?;
+            }
+            write!(f, "{}", c)?;
+        }
+        f.write_str("\"")?;
+        if let Some(ref date) = self.date {
+            write!(f, " \"{}\"", date)?;
         }
+        Ok(())
     }
 }
 
-impl FromStr for Warning {
-    type Err = ::Error;
+fn is_agent(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_graphic() && b != b'"')
+}
 
-    fn from_str(s: &str) -> ::Result<Warning> {
-        let mut warning_split = s.split_whitespace();
-        let code = match warning_split.next() {
-            Some(c) => match c.parse::<u16>() {
-                Ok(c) => c,
-                Err(..) => return Err(::Error::Header)
-            },
-            None => return Err(::Error::Header)
-        };
-        let agent = match warning_split.next() {
-            Some(a) => a.to_string(),
-            None => return Err(::Error::Header)
-        };
+fn is_text(s: &str) -> bool {
+    s.chars().all(|c| c == '\t' || !c.is_control())
+}
 
-        let mut warning_split = s.split('"').skip(1);
-        let text = match warning_split.next() {
-            Some(t) => t.to_string(),
-            None => return Err(::Error::Header)
-        };
-        let date = match warning_split.skip(1).next() {
-            Some(d) => d.parse::<HttpDate>().ok(),
-            None => None // Optional
-        };
+fn split_sp(s: &str) -> Option<(&str, &str)> {
+    let i = s.find(' ')?;
+    Some((&s[..i], &s[i + 1..]))
+}
 
-        Ok(Warning {
-            code: code,
-            agent: agent,
-            text: text,
-            date: date
-        })
+// The length of the quoted-string at the start of `s`.
+fn quoted_end(s: &str) -> Option<usize> {
+    let mut bytes = s.bytes().enumerate();
+    if bytes.next()?.1 != b'"' {
+        return None;
+    }
+    while let Some((i, b)) = bytes.next() {
+        match b {
+            b'"' => return Some(i + 1),
+            b'\\' => {
+                bytes.next()?;
+            },
+            _ => (),
+        }
     }
+    None
 }
 
 #[cfg(test)]
 mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
     use super::Warning;
-    use {Header, HttpDate};
+    use super::super::{test_decode, test_encode};
+
+    #[test]
+    fn decode() {
+        let warning = test_decode::<Warning>(&["112 - \"network down\" \"Sat, 25 Aug 2012 23:34:45 GMT\""]).unwrap();
+        assert_eq!(warning.code(), 112);
+        assert_eq!(warning.agent(), "-");
+        assert_eq!(warning.text(), "network down");
+        assert_eq!(warning.date(), Some(UNIX_EPOCH + Duration::from_secs(1345937685)));
+
+        let warning = test_decode::<Warning>(&["299 api.hyper.rs:8080 \"Deprecated API : use \\\"newapi\\\" instead.\""]).unwrap();
+        assert_eq!(warning.code(), 299);
+        assert_eq!(warning.agent(), "api.hyper.rs:8080");
+        assert_eq!(warning.text(), "Deprecated API : use \"newapi\" instead.");
+        assert_eq!(warning.date(), None);
+    }
 
     #[test]
-    fn test_parsing() {
-        let warning = Header::parse_header(&vec![b"112 - \"network down\" \"Sat, 25 Aug 2012 23:34:45 GMT\"".to_vec()].into());
-        assert_eq!(warning.ok(), Some(Warning {
-            code: 112,
-            agent: "-".to_owned(),
-            text: "network down".to_owned(),
-            date: "Sat, 25 Aug 2012 23:34:45 GMT".parse::<HttpDate>().ok()
-        }));
-
-        let warning = Header::parse_header(&vec![b"299 api.hyper.rs:8080 \"Deprecated API : use newapi.hyper.rs instead.\"".to_vec()].into());
-        assert_eq!(warning.ok(), Some(Warning {
-            code: 299,
-            agent: "api.hyper.rs:8080".to_owned(),
-            text: "Deprecated API : use newapi.hyper.rs instead.".to_owned(),
-            date: None
-        }));
-
-        let warning = Header::parse_header(&vec![b"299 api.hyper.rs:8080 \"Deprecated API : use newapi.hyper.rs instead.\" \"Tue, 15 Nov 1994 08:12:31 GMT\"".to_vec()].into());
-        assert_eq!(warning.ok(), Some(Warning {
-            code: 299,
-            agent: "api.hyper.rs:8080".to_owned(),
-            text: "Deprecated API : use newapi.hyper.rs instead.".to_owned(),
-            date: "Tue, 15 Nov 1994 08:12:31 GMT".parse::<HttpDate>().ok()
-        }));
+    fn decode_folded() {
+        let mut map = ::HeaderMap::new();
+        map.insert(
+            "warning",
+            ::HeaderValue::from_static("299 - \"Deprecated API,\t\t use \tv2\" \"Sat, 25 Aug 2012 23:34:45 GMT\"\t"),
+        );
+
+        let warning = ::HeaderMapExt::typed_get::<Warning>(&map).unwrap();
+        assert_eq!(warning.text(), "Deprecated API, use v2");
+        assert_eq!(
+            test_encode(warning)["warning"],
+            "299 - \"Deprecated API, use v2\" \"Sat, 25 Aug 2012 23:34:45 GMT\"",
+        );
+
+        // Whitespace around the value is only trimmed when lenient.
+        assert!(::HeaderMapExt::typed_get_with::<Warning>(&map, ::ParseMode::Strict).is_none());
+        let warning = test_decode::<Warning>(&["\t199 - \" a  b \""]).unwrap();
+        assert_eq!(warning.text(), "a b");
+    }
+
+    #[test]
+    fn decode_invalid() {
+        for s in &[
+            "",
+            "299",
+            "299 -",
+            "299 - text",
+            "299 - \"unterminated",
+            "299 - \"text\" trailing",
+            "299 - \"text\" \"not a date\"",
+            "29 - \"text\"",
+            "2999 - \"text\"",
+            "099 - \"text\"",
+            "abc - \"text\"",
+            "299  - \"text\"",
+        ] {
+            assert_eq!(test_decode::<Warning>(&[s]), None, "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn encode() {
+        let warning = Warning::new(199, "-", "a \"quoted\" \\ text")
+            .with_date(UNIX_EPOCH + Duration::from_secs(1345937685));
+        let headers = test_encode(warning.clone());
+        assert_eq!(headers["warning"], "199 - \"a \\\"quoted\\\" \\\\ text\" \"Sat, 25 Aug 2012 23:34:45 GMT\"");
+        assert_eq!(test_decode::<Warning>(&[headers["warning"].to_str().unwrap()]), Some(warning));
+    }
+
+    #[test]
+    fn constructors() {
+        let warning = Warning::misc_persistent("api.example.com", "Deprecated, use /v2");
+        assert_eq!(test_encode(warning)["warning"], "299 api.example.com \"Deprecated, use /v2\"");
+        assert_eq!(test_encode(Warning::stale("-"))["warning"], "110 - \"Response is Stale\"");
+    }
+
+    #[test]
+    fn classification() {
+        let stale = Warning::stale("-");
+        assert!(!stale.is_persistent());
+        assert!(stale.should_be_deleted_on_revalidation());
+
+        for &code in &[Warning::MISCELLANEOUS_WARNING, Warning::HEURISTIC_EXPIRATION] {
+            let warning = Warning::new(code, "-", "");
+            assert!(!warning.is_persistent());
+            assert!(warning.should_be_deleted_on_revalidation());
+        }
+        for &code in &[Warning::TRANSFORMATION_APPLIED, Warning::MISCELLANEOUS_PERSISTENT_WARNING] {
+            let warning = Warning::new(code, "-", "");
+            assert!(warning.is_persistent());
+            assert!(!warning.should_be_deleted_on_revalidation());
+        }
+        // Unassigned classes are neither.
+        let warning = Warning::new(399, "-", "");
+        assert!(!warning.is_persistent());
+        assert!(!warning.should_be_deleted_on_revalidation());
+    }
+
+    #[test]
+    fn try_insert_rejects_injection() {
+        use {HeaderMap, HeaderMapExt};
+
+        let mut map = HeaderMap::new();
+        for warning in &[
+            Warning::misc_persistent("-", "Deprecated\r\nSet-Cookie: a=b"),
+            Warning::misc_persistent("-", "Deprecated\n"),
+            Warning::misc_persistent("api.example.com\r\nx-injected:", "text"),
+            Warning::misc_persistent("two words", "text"),
+            Warning::misc_persistent("", "text"),
+            Warning::new(1000, "-", "text"),
+        ] {
+            assert!(map.typed_try_insert(warning.clone()).is_err(), "{:?}", warning);
+        }
+        assert!(map.is_empty());
+
+        // Quotes are escaped, so they're fine.
+        assert!(map.typed_try_insert(Warning::misc_persistent("-", "use \"v2\"")).is_ok());
+        assert_eq!(map["warning"], "299 - \"use \\\"v2\\\"\"");
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_panics_on_invalid() {
+        use {HeaderMap, HeaderMapExt};
+
+        HeaderMap::new().typed_insert(Warning::misc_persistent("-", "a\nb"));
     }
 }
@@ -0,0 +1,356 @@
+use std::error::Error as StdError;
+use std::fmt;
+use std::time::Duration;
+
+use util::{digits, token, HeaderString, Seconds};
+
+/// `X-Accel-Redirect` header, read by nginx from the responses of the
+/// servers it proxies.
+///
+/// The `X-Accel-Redirect` header field has nginx serve another URI of its
+/// own instead of the response, usually an
+/// [`internal`](https://nginx.org/en/docs/http/ngx_http_core_module.html#internal)
+/// location of protected files. The application checks access, and nginx
+/// sends the file.
+///
+/// # ABNF
+///
+/// ```text
+/// X-Accel-Redirect = absolute-path [ "?" query ]
+/// ```
+///
+/// # Example values
+///
+/// * `/protected/report.pdf`
+/// * `/internal/download?id=42`
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::{HeaderMap, HeaderMapExt, XAccelBuffering, XAccelRedirect};
+///
+/// let mut map = HeaderMap::new();
+/// map.typed_insert(XAccelRedirect::try_from_str("/protected/report.pdf").unwrap());
+/// map.typed_insert(XAccelBuffering::No);
+/// assert_eq!(map["x-accel-redirect"], "/protected/report.pdf");
+/// assert_eq!(map["x-accel-buffering"], "no");
+///
+/// assert!(XAccelRedirect::try_from_str("protected/report.pdf").is_err());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct XAccelRedirect(HeaderString);
+
+/// An error returned when an `X-Accel-Redirect` path doesn't start with
+/// `/`, or isn't a valid header value.
+#[derive(Debug)]
+pub struct InvalidRedirectPath(());
+
+impl XAccelRedirect {
+    /// Create an `X-Accel-Redirect` from a static path.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the path doesn't start with `/`, or is not a legal
+    /// header value.
+    pub fn from_static(path: &'static str) -> XAccelRedirect {
+        assert!(path.starts_with('/'), "XAccelRedirect::from_static with relative path: {:?}", path);
+        XAccelRedirect(HeaderString::from_static(path))
+    }
+
+    /// Create an `X-Accel-Redirect` from a path, failing if it doesn't
+    /// start with `/` or isn't a valid header value.
+    pub fn try_from_str(path: &str) -> Result<XAccelRedirect, InvalidRedirectPath> {
+        if !path.starts_with('/') {
+            return Err(InvalidRedirectPath(()));
+        }
+        path.parse().map(XAccelRedirect).map_err(|_| InvalidRedirectPath(()))
+    }
+
+    /// View the path as a `&str`, with its query if it has one.
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl ::Header for XAccelRedirect {
+    fn name() -> &'static ::HeaderName {
+        static_header_name!("x-accel-redirect")
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        HeaderString::from_val(values.next()?)
+            .filter(|path| path.starts_with('/'))
+            .map(XAccelRedirect)
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        values.append((&self.0).into());
+    }
+}
+
+impl fmt::Display for InvalidRedirectPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid X-Accel-Redirect path")
+    }
+}
+
+impl StdError for InvalidRedirectPath {}
+
+/// `X-Accel-Buffering` header, read by nginx from the responses of the
+/// servers it proxies.
+///
+/// The `X-Accel-Buffering` header field turns the buffering of the
+/// response by nginx on or off, overriding
+/// [`proxy_buffering`](https://nginx.org/en/docs/http/ngx_http_proxy_module.html#proxy_buffering).
+/// Streamed responses, such as server-sent events, turn it off.
+///
+/// # ABNF
+///
+/// ```text
+/// X-Accel-Buffering = "yes" / "no"
+/// ```
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::XAccelBuffering;
+///
+/// assert_eq!(XAccelBuffering::from(false), XAccelBuffering::No);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Header)]
+#[header(name = "x-accel-buffering")]
+pub enum XAccelBuffering {
+    /// `yes`, nginx buffers the response.
+    #[header(value = "yes")]
+    Yes,
+    /// `no`, nginx passes the response on as it receives it.
+    #[header(value = "no")]
+    No,
+}
+
+impl XAccelBuffering {
+    /// Check if the response is buffered.
+    pub fn is_buffered(&self) -> bool {
+        *self == XAccelBuffering::Yes
+    }
+}
+
+impl From<bool> for XAccelBuffering {
+    /// `Yes` if `buffered`, else `No`.
+    fn from(buffered: bool) -> XAccelBuffering {
+        if buffered {
+            XAccelBuffering::Yes
+        } else {
+            XAccelBuffering::No
+        }
+    }
+}
+
+/// `X-Accel-Limit-Rate` header, read by nginx from the responses of the
+/// servers it proxies.
+///
+/// The `X-Accel-Limit-Rate` header field limits the rate nginx sends the
+/// response at, in bytes per second, overriding
+/// [`limit_rate`](https://nginx.org/en/docs/http/ngx_http_core_module.html#limit_rate).
+/// A rate of 0 is no limit.
+///
+/// # ABNF
+///
+/// ```text
+/// X-Accel-Limit-Rate = 1*DIGIT
+/// ```
+///
+/// # Example values
+///
+/// * `102400`
+/// * `0`
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::XAccelLimitRate;
+///
+/// let rate = XAccelLimitRate::new(100 * 1024);
+/// assert_eq!(rate.bytes_per_sec(), 102400);
+/// assert!(XAccelLimitRate::unlimited().is_unlimited());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct XAccelLimitRate(u64);
+
+impl XAccelLimitRate {
+    /// Limit the rate to `bytes_per_sec`, where 0 is no limit.
+    pub fn new(bytes_per_sec: u64) -> XAccelLimitRate {
+        XAccelLimitRate(bytes_per_sec)
+    }
+
+    /// `X-Accel-Limit-Rate: 0`, lifting the limit of the configuration.
+    pub fn unlimited() -> XAccelLimitRate {
+        XAccelLimitRate(0)
+    }
+
+    /// Get the limit, in bytes per second.
+    pub fn bytes_per_sec(&self) -> u64 {
+        self.0
+    }
+
+    /// Check if the rate isn't limited.
+    pub fn is_unlimited(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl ::Header for XAccelLimitRate {
+    fn name() -> &'static ::HeaderName {
+        static_header_name!("x-accel-limit-rate")
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        let value = token::trim_ows(values.next()?.to_str().ok()?);
+        digits::parse_u64(value.as_bytes()).map(XAccelLimitRate)
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        values.append_fmt(self.0);
+    }
+}
+
+/// `X-Accel-Expires` header, read by nginx from the responses of the
+/// servers it proxies.
+///
+/// The `X-Accel-Expires` header field sets how long nginx caches the
+/// response, taking precedence over `Expires` and `Cache-Control`, which
+/// are still sent on to the client. `off` doesn't cache it.
+///
+/// # ABNF
+///
+/// ```text
+/// X-Accel-Expires = "off" / delta-seconds
+/// ```
+///
+/// # Example values
+///
+/// * `3600`
+/// * `off`
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use std::time::Duration;
+/// use headers::XAccelExpires;
+///
+/// let expires = XAccelExpires::from(Duration::from_secs(3600));
+/// assert_eq!(expires.duration(), Some(Duration::from_secs(3600)));
+/// assert!(XAccelExpires::off().is_off());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct XAccelExpires(Option<Seconds>);
+
+impl XAccelExpires {
+    /// `X-Accel-Expires: off`, nginx doesn't cache the response.
+    pub fn off() -> XAccelExpires {
+        XAccelExpires(None)
+    }
+
+    /// Get how long the response is cached, or `None` if it's `off`.
+    pub fn duration(&self) -> Option<Duration> {
+        self.0.map(Duration::from)
+    }
+
+    /// Check if caching is `off`.
+    pub fn is_off(&self) -> bool {
+        self.0.is_none()
+    }
+}
+
+impl From<Duration> for XAccelExpires {
+    fn from(dur: Duration) -> XAccelExpires {
+        XAccelExpires(Some(dur.into()))
+    }
+}
+
+impl ::Header for XAccelExpires {
+    fn name() -> &'static ::HeaderName {
+        static_header_name!("x-accel-expires")
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        let value = values.next()?;
+        if token::eq_ignore_ascii_case_trimmed(value.as_bytes(), "off") {
+            return Some(XAccelExpires::off());
+        }
+        let value = token::trim_ows(value.to_str().ok()?);
+        Seconds::parse(value.as_bytes()).map(|secs| XAccelExpires(Some(secs)))
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        match self.0 {
+            Some(secs) => values.append((&secs).into()),
+            None => values.append(::HeaderValue::from_static("off")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{XAccelBuffering, XAccelExpires, XAccelLimitRate, XAccelRedirect};
+    use super::super::{test_decode, test_encode};
+
+    #[test]
+    fn redirect() {
+        let redirect = test_decode::<XAccelRedirect>(&["/protected/report.pdf?dl=1"]).unwrap();
+        assert_eq!(redirect.as_str(), "/protected/report.pdf?dl=1");
+        assert_eq!(redirect, XAccelRedirect::from_static("/protected/report.pdf?dl=1"));
+        assert_eq!(test_encode(redirect)["x-accel-redirect"], "/protected/report.pdf?dl=1");
+
+        for value in &["", "protected/report.pdf", "https://example.com/report.pdf"] {
+            assert_eq!(test_decode::<XAccelRedirect>(&[value]), None, "{:?}", value);
+            assert!(XAccelRedirect::try_from_str(value).is_err(), "{:?}", value);
+        }
+        assert!(XAccelRedirect::try_from_str("/a\r\nb").is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn redirect_from_static_panics_on_relative() {
+        let _ = XAccelRedirect::from_static("protected/report.pdf");
+    }
+
+    #[test]
+    fn buffering() {
+        assert_eq!(test_decode::<XAccelBuffering>(&["yes"]), Some(XAccelBuffering::Yes));
+        assert_eq!(test_decode::<XAccelBuffering>(&[" No"]), Some(XAccelBuffering::No));
+        assert_eq!(test_decode::<XAccelBuffering>(&["off"]), None);
+        assert!(XAccelBuffering::from(true).is_buffered());
+        assert_eq!(test_encode(XAccelBuffering::No)["x-accel-buffering"], "no");
+    }
+
+    #[test]
+    fn limit_rate() {
+        assert_eq!(test_decode::<XAccelLimitRate>(&["102400"]), Some(XAccelLimitRate::new(102400)));
+        assert_eq!(test_decode::<XAccelLimitRate>(&["0"]), Some(XAccelLimitRate::unlimited()));
+        for value in &["", "-1", "100k", "18446744073709551616"] {
+            assert_eq!(test_decode::<XAccelLimitRate>(&[value]), None, "{:?}", value);
+        }
+        assert_eq!(test_encode(XAccelLimitRate::new(512))["x-accel-limit-rate"], "512");
+    }
+
+    #[test]
+    fn expires() {
+        let hour = XAccelExpires::from(Duration::from_secs(3600));
+        assert_eq!(test_decode::<XAccelExpires>(&["3600"]), Some(hour));
+        assert_eq!(test_decode::<XAccelExpires>(&["off"]), Some(XAccelExpires::off()));
+        assert_eq!(test_decode::<XAccelExpires>(&["OFF "]), Some(XAccelExpires::off()));
+        assert_eq!(test_decode::<XAccelExpires>(&["0"]).unwrap().duration(), Some(Duration::from_secs(0)));
+        for value in &["", "on", "1h", "-5"] {
+            assert_eq!(test_decode::<XAccelExpires>(&[value]), None, "{:?}", value);
+        }
+        assert_eq!(test_encode(hour)["x-accel-expires"], "3600");
+        assert_eq!(test_encode(XAccelExpires::off())["x-accel-expires"], "off");
+    }
+}
@@ -0,0 +1,74 @@
+use util::token;
+
+/// `X-Content-Type-Options` header, defined in the
+/// [Fetch Standard](https://fetch.spec.whatwg.org/#x-content-type-options-header)
+///
+/// The only option, `nosniff`, tells browsers to trust the
+/// `Content-Type` of the response instead of guessing from its contents.
+///
+/// # ABNF
+///
+/// ```text
+/// X-Content-Type-Options = "nosniff"
+/// ```
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::XContentTypeOptions;
+///
+/// let nosniff = XContentTypeOptions::NOSNIFF;
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct XContentTypeOptions(());
+
+impl XContentTypeOptions {
+    /// `nosniff`
+    pub const NOSNIFF: XContentTypeOptions = XContentTypeOptions(());
+
+    /// `X-Content-Type-Options: nosniff`
+    pub fn nosniff() -> XContentTypeOptions {
+        XContentTypeOptions::NOSNIFF
+    }
+}
+
+impl ::Header for XContentTypeOptions {
+    fn name() -> &'static ::HeaderName {
+        &::http::header::X_CONTENT_TYPE_OPTIONS
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        // Browsers only look at the first element of the first value.
+        let first = values.next()?.as_bytes();
+        let first = first.split(|&b| b == b',').next().unwrap_or(first);
+        if token::eq_ignore_ascii_case_trimmed(first, "nosniff") {
+            Some(XContentTypeOptions(()))
+        } else {
+            None
+        }
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        values.append(::HeaderValue::from_static("nosniff"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::XContentTypeOptions;
+    use super::super::{test_decode, test_encode};
+
+    #[test]
+    fn decode() {
+        assert_eq!(test_decode::<XContentTypeOptions>(&["nosniff"]), Some(XContentTypeOptions::nosniff()));
+        assert_eq!(test_decode::<XContentTypeOptions>(&["NoSniff, foo"]), Some(XContentTypeOptions::nosniff()));
+        assert_eq!(test_decode::<XContentTypeOptions>(&["sniff"]), None);
+    }
+
+    #[test]
+    fn encode() {
+        let headers = test_encode(XContentTypeOptions::nosniff());
+        assert_eq!(headers["x-content-type-options"], "nosniff");
+    }
+}
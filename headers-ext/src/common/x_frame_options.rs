@@ -0,0 +1,59 @@
+/// `X-Frame-Options` header, defined in
+/// [RFC7034](https://tools.ietf.org/html/rfc7034)
+///
+/// The `X-Frame-Options` header field indicates whether a browser may
+/// display the response in a frame, to protect against clickjacking.
+///
+/// The obsolete `ALLOW-FROM` option isn't supported, since browsers ignore
+/// it. New code should prefer the `frame-ancestors` directive of
+/// `Content-Security-Policy`.
+///
+/// # ABNF
+///
+/// ```text
+/// X-Frame-Options = "DENY" / "SAMEORIGIN"
+/// ```
+///
+/// # Example values
+///
+/// * `DENY`
+/// * `SAMEORIGIN`
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::XFrameOptions;
+///
+/// let xfo = XFrameOptions::Deny;
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Header)]
+pub enum XFrameOptions {
+    /// `DENY`, the response can't be displayed in a frame.
+    #[header(value = "DENY")]
+    Deny,
+    /// `SAMEORIGIN`, the response can only be framed by the same origin.
+    #[header(value = "SAMEORIGIN")]
+    SameOrigin,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::XFrameOptions;
+    use super::super::{test_decode, test_encode};
+
+    #[test]
+    fn decode() {
+        assert_eq!(test_decode::<XFrameOptions>(&["DENY"]), Some(XFrameOptions::Deny));
+        assert_eq!(test_decode::<XFrameOptions>(&["sameorigin"]), Some(XFrameOptions::SameOrigin));
+        assert_eq!(test_decode::<XFrameOptions>(&[" Deny\t"]), Some(XFrameOptions::Deny));
+        assert_eq!(test_decode::<XFrameOptions>(&["ALLOW-FROM https://example.com"]), None);
+        assert_eq!(test_decode::<XFrameOptions>(&["DENY, SAMEORIGIN"]), None);
+    }
+
+    #[test]
+    fn encode() {
+        let headers = test_encode(XFrameOptions::SameOrigin);
+        assert_eq!(headers["x-frame-options"], "SAMEORIGIN");
+    }
+}
@@ -0,0 +1,94 @@
+use std::net::IpAddr;
+
+/// `X-Real-IP` header, a de-facto standard set by reverse proxies such as
+/// nginx.
+///
+/// The `X-Real-IP` header field gives the address of the client a proxy
+/// received the request from. Like any header, a client can send it too,
+/// so it can only be believed when set by a trusted proxy, as
+/// [`proxy::client_ip`](proxy/fn.client_ip.html) checks.
+///
+/// # ABNF
+///
+/// ```text
+/// X-Real-IP = IPv4address / IPv6address
+/// ```
+///
+/// # Example values
+///
+/// * `192.0.2.60`
+/// * `2001:db8::1`
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use headers::XRealIp;
+///
+/// let real_ip = XRealIp(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 60)));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct XRealIp(pub IpAddr);
+
+impl ::Header for XRealIp {
+    fn name() -> &'static ::HeaderName {
+        static_header_name!("x-real-ip")
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        values
+            .next()?
+            .to_str()
+            .ok()?
+            .trim_matches(|c| c == ' ' || c == '\t')
+            .parse()
+            .ok()
+            .map(XRealIp)
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        values.append_fmt(self.0);
+    }
+}
+
+impl From<IpAddr> for XRealIp {
+    fn from(addr: IpAddr) -> XRealIp {
+        XRealIp(addr)
+    }
+}
+
+impl From<XRealIp> for IpAddr {
+    fn from(real_ip: XRealIp) -> IpAddr {
+        real_ip.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    use super::XRealIp;
+    use super::super::{test_decode, test_encode};
+
+    #[test]
+    fn decode() {
+        let v4 = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 60));
+        assert_eq!(test_decode(&["192.0.2.60"]), Some(XRealIp(v4)));
+        assert_eq!(test_decode(&[" 192.0.2.60 "]), Some(XRealIp(v4)));
+
+        let v6 = IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1));
+        assert_eq!(test_decode(&["2001:DB8::1"]), Some(XRealIp(v6)));
+
+        for &value in &["", "example.com", "192.0.2.60:80", "[2001:db8::1]", "192.0.2.60, 192.0.2.61"] {
+            assert_eq!(test_decode::<XRealIp>(&[value]), None, "{:?}", value);
+        }
+        assert_eq!(test_decode::<XRealIp>(&["192.0.2.60", "192.0.2.61"]), None);
+    }
+
+    #[test]
+    fn encode() {
+        let real_ip = XRealIp("2001:db8::1".parse().unwrap());
+        assert_eq!(test_encode(real_ip)["x-real-ip"], "2001:db8::1");
+    }
+}
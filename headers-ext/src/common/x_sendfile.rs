@@ -0,0 +1,109 @@
+use std::path::Path;
+
+use util::{HeaderString, InvalidHeaderString};
+
+/// `X-Sendfile` header, read by Apache's
+/// [mod_xsendfile](https://tn123.org/mod_xsendfile/) and lighttpd from the
+/// responses of the applications they run.
+///
+/// The `X-Sendfile` header field has the server send the file at a path
+/// on its disk instead of the response body, so the application can check
+/// access without reading the file itself. nginx has
+/// [`XAccelRedirect`](struct.XAccelRedirect.html) instead.
+///
+/// # ABNF
+///
+/// ```text
+/// X-Sendfile = path
+/// ```
+///
+/// # Example values
+///
+/// * `/var/www/private/report.pdf`
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use std::path::Path;
+/// use headers::{HeaderMap, HeaderMapExt, XSendfile};
+///
+/// let mut map = HeaderMap::new();
+/// map.typed_insert(XSendfile::try_from_path("/var/www/private/report.pdf").unwrap());
+/// assert_eq!(map["x-sendfile"], "/var/www/private/report.pdf");
+///
+/// let sendfile = map.typed_get::<XSendfile>().unwrap();
+/// assert_eq!(sendfile.as_path(), Path::new("/var/www/private/report.pdf"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct XSendfile(HeaderString);
+
+impl XSendfile {
+    /// Create an `X-Sendfile` from a static path.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the path is not a legal header value.
+    pub fn from_static(path: &'static str) -> XSendfile {
+        XSendfile(HeaderString::from_static(path))
+    }
+
+    /// Create an `X-Sendfile` from a path, failing if it isn't ASCII or
+    /// isn't a valid header value, such as if it contains a line break.
+    pub fn try_from_path<P: AsRef<Path>>(path: P) -> Result<XSendfile, InvalidHeaderString> {
+        let path = path.as_ref().to_str().ok_or(InvalidHeaderString(()))?;
+        path.parse().map(XSendfile)
+    }
+
+    /// View the path as a `&str`.
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    /// View the path as a `Path`.
+    pub fn as_path(&self) -> &Path {
+        Path::new(self.as_str())
+    }
+}
+
+impl ::Header for XSendfile {
+    fn name() -> &'static ::HeaderName {
+        static_header_name!("x-sendfile")
+    }
+
+    fn decode(values: &mut ::Values) -> Option<Self> {
+        HeaderString::from_val(values.next()?)
+            .filter(|path| !path.is_empty())
+            .map(XSendfile)
+    }
+
+    fn encode(&self, values: &mut ::ToValues) {
+        values.append((&self.0).into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::XSendfile;
+    use super::super::{test_decode, test_encode};
+
+    #[test]
+    fn decode() {
+        let sendfile = test_decode::<XSendfile>(&["/var/www/private/report.pdf"]).unwrap();
+        assert_eq!(sendfile.as_path(), Path::new("/var/www/private/report.pdf"));
+        assert_eq!(sendfile, XSendfile::from_static("/var/www/private/report.pdf"));
+        assert_eq!(test_decode::<XSendfile>(&[""]), None);
+    }
+
+    #[test]
+    fn try_from_path() {
+        let sendfile = XSendfile::try_from_path(Path::new("/srv/files").join("a b.txt")).unwrap();
+        assert_eq!(test_encode(sendfile)["x-sendfile"], "/srv/files/a b.txt");
+
+        for path in &["/srv/a\r\nb", "/srv/caf\u{e9}"] {
+            assert!(XSendfile::try_from_path(path).is_err(), "{:?}", path);
+        }
+    }
+}
@@ -0,0 +1,306 @@
+//! Headers for conditional requests, and the validators they compare.
+//!
+//! [`evaluate`](fn.evaluate.html) combines the precondition headers of a
+//! request, in the order defined by
+//! [RFC7232 section 6](https://tools.ietf.org/html/rfc7232#section-6).
+
+use std::time::SystemTime;
+
+use http::header::{IF_MATCH, IF_NONE_MATCH, IF_RANGE};
+use http::Method;
+
+pub use common::{
+    ETag,
+    IfMatch,
+    IfModifiedSince,
+    IfNoneMatch,
+    IfRange,
+    IfUnmodifiedSince,
+    LastModified,
+};
+pub use util::{EntityTag, InvalidEntityTag};
+
+use {HeaderMap, HeaderMapExt};
+
+/// The outcome of evaluating the preconditions of a request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConditionalResult {
+    /// Every precondition passed, or there weren't any.
+    Proceed,
+    /// Respond with `304 Not Modified`.
+    NotModified,
+    /// Respond with `412 Precondition Failed`.
+    PreconditionFailed,
+}
+
+/// Evaluate the preconditions of a request against the current state of
+/// the target resource.
+///
+/// `etag` and `last_modified` are the validators of the current
+/// representation. The resource is considered to exist if it has either
+/// of them, which is what `If-Match: *` and `If-None-Match: *` check, so
+/// pass at least one for a resource that exists.
+///
+/// Following the RFC:
+///
+/// - `If-Unmodified-Since` is ignored when there's an `If-Match`, and
+///   `If-Modified-Since` when there's an `If-None-Match`.
+/// - `If-Modified-Since` only applies to `GET` and `HEAD`.
+/// - An `If-None-Match` that matches means `304 Not Modified` for `GET`
+///   and `HEAD`, and `412 Precondition Failed` for anything else, such as
+///   a `PUT` with `If-None-Match: *` to a resource that already exists.
+/// - Dates are ignored when the resource has no modification date, and so
+///   are dates that fail to decode.
+/// - An `If-Match` or `If-None-Match` that fails to decode means `412
+///   Precondition Failed`, since ignoring it would make a request the
+///   client meant to be conditional unconditional.
+///
+/// This doesn't check whether a failed precondition of a state-changing
+/// request was really because the change had already been applied, which
+/// the RFC allows responding to with success instead.
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// extern crate http;
+///
+/// use headers::{ETag, HeaderMapExt, IfNoneMatch};
+/// use headers::conditional::{evaluate, ConditionalResult};
+/// use http::{HeaderMap, Method};
+///
+/// # fn main() {
+/// let etag = "\"xyzzy\"".parse::<ETag>().unwrap();
+///
+/// let mut req = HeaderMap::new();
+/// req.typed_insert(IfNoneMatch::from(etag.clone()));
+///
+/// assert_eq!(evaluate(&req, Some(&etag), None, &Method::GET), ConditionalResult::NotModified);
+/// assert_eq!(evaluate(&req, Some(&etag), None, &Method::PUT), ConditionalResult::PreconditionFailed);
+/// # }
+/// ```
+pub fn evaluate(
+    headers: &HeaderMap,
+    etag: Option<&ETag>,
+    last_modified: Option<SystemTime>,
+    method: &Method,
+) -> ConditionalResult {
+    let exists = etag.is_some() || last_modified.is_some();
+    let is_get_or_head = *method == Method::GET || *method == Method::HEAD;
+
+    if headers.contains_key(IF_MATCH) {
+        let passes = headers.typed_get::<IfMatch>().is_some_and(|if_match| {
+            if if_match.is_any() {
+                exists
+            } else {
+                etag.is_some_and(|etag| if_match.precondition_passes(etag))
+            }
+        });
+        if !passes {
+            return ConditionalResult::PreconditionFailed;
+        }
+    } else if let Some(since) = headers.typed_get::<IfUnmodifiedSince>() {
+        if last_modified.is_some_and(|time| !since.precondition_passes(time)) {
+            return ConditionalResult::PreconditionFailed;
+        }
+    }
+
+    if headers.contains_key(IF_NONE_MATCH) {
+        let if_none_match = match headers.typed_get::<IfNoneMatch>() {
+            Some(if_none_match) => if_none_match,
+            None => return ConditionalResult::PreconditionFailed,
+        };
+        let matches = if if_none_match.is_any() {
+            exists
+        } else {
+            etag.is_some_and(|etag| !if_none_match.precondition_passes(etag))
+        };
+        if matches {
+            return if is_get_or_head {
+                ConditionalResult::NotModified
+            } else {
+                ConditionalResult::PreconditionFailed
+            };
+        }
+    } else if is_get_or_head {
+        if let Some(since) = headers.typed_get::<IfModifiedSince>() {
+            if last_modified.is_some_and(|time| !since.is_modified(time)) {
+                return ConditionalResult::NotModified;
+            }
+        }
+    }
+
+    ConditionalResult::Proceed
+}
+
+/// Check whether the `Range` of a request can be served, following its
+/// `If-Range` header.
+///
+/// This is the last step of the evaluation order, once
+/// [`evaluate`](fn.evaluate.html) returned `Proceed`. Without an
+/// `If-Range` the range can always be served, and with one that fails to
+/// decode it never is, so the full representation is sent. Whether the
+/// request is a `GET` with a `Range` at all isn't checked.
+pub fn range_applies(
+    headers: &HeaderMap,
+    etag: Option<&ETag>,
+    last_modified: Option<SystemTime>,
+) -> bool {
+    if !headers.contains_key(IF_RANGE) {
+        return true;
+    }
+    let last_modified = last_modified.map(LastModified::from);
+    headers
+        .typed_get::<IfRange>()
+        .is_some_and(|if_range| !if_range.is_modified(etag, last_modified.as_ref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use http::Method;
+
+    use super::{evaluate, range_applies, ConditionalResult};
+    use super::ConditionalResult::{NotModified, PreconditionFailed, Proceed};
+    use {HeaderMap, HeaderValue};
+
+    fn req(headers: &[(&'static str, &str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for &(name, value) in headers {
+            map.append(name, HeaderValue::from_str(value).unwrap());
+        }
+        map
+    }
+
+    fn etag() -> ::ETag {
+        "\"xyzzy\"".parse().unwrap()
+    }
+
+    fn weak_etag() -> ::ETag {
+        "W/\"xyzzy\"".parse().unwrap()
+    }
+
+    // Sun, 06 Nov 1994 08:49:37 GMT
+    fn last_modified() -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(784111777)
+    }
+
+    const BEFORE: &str = "Sun, 06 Nov 1994 08:49:36 GMT";
+    const AT: &str = "Sun, 06 Nov 1994 08:49:37 GMT";
+
+    fn eval(headers: &[(&'static str, &str)], method: Method) -> ConditionalResult {
+        evaluate(&req(headers), Some(&etag()), Some(last_modified()), &method)
+    }
+
+    #[test]
+    fn no_preconditions() {
+        assert_eq!(eval(&[], Method::GET), Proceed);
+        assert_eq!(evaluate(&req(&[]), None, None, &Method::PUT), Proceed);
+    }
+
+    #[test]
+    fn step_1_if_match() {
+        assert_eq!(eval(&[("if-match", "\"xyzzy\"")], Method::PUT), Proceed);
+        assert_eq!(eval(&[("if-match", "\"other\", \"xyzzy\"")], Method::PUT), Proceed);
+        assert_eq!(eval(&[("if-match", "\"other\"")], Method::PUT), PreconditionFailed);
+        assert_eq!(eval(&[("if-match", "*")], Method::PUT), Proceed);
+        assert_eq!(evaluate(&req(&[("if-match", "*")]), None, None, &Method::PUT), PreconditionFailed);
+
+        // If-Match uses the strong comparison.
+        assert_eq!(eval(&[("if-match", "W/\"xyzzy\"")], Method::PUT), PreconditionFailed);
+        let weak = weak_etag();
+        let headers = req(&[("if-match", "\"xyzzy\"")]);
+        assert_eq!(evaluate(&headers, Some(&weak), None, &Method::PUT), PreconditionFailed);
+    }
+
+    #[test]
+    fn step_2_if_unmodified_since() {
+        assert_eq!(eval(&[("if-unmodified-since", AT)], Method::PUT), Proceed);
+        assert_eq!(eval(&[("if-unmodified-since", BEFORE)], Method::PUT), PreconditionFailed);
+
+        // ignored without a modification date
+        let headers = req(&[("if-unmodified-since", BEFORE)]);
+        assert_eq!(evaluate(&headers, Some(&etag()), None, &Method::PUT), Proceed);
+    }
+
+    #[test]
+    fn if_match_takes_precedence_over_if_unmodified_since() {
+        let headers = &[("if-match", "\"xyzzy\""), ("if-unmodified-since", BEFORE)];
+        assert_eq!(eval(headers, Method::PUT), Proceed);
+    }
+
+    #[test]
+    fn step_3_if_none_match() {
+        assert_eq!(eval(&[("if-none-match", "\"xyzzy\"")], Method::GET), NotModified);
+        assert_eq!(eval(&[("if-none-match", "\"xyzzy\"")], Method::HEAD), NotModified);
+        assert_eq!(eval(&[("if-none-match", "\"xyzzy\"")], Method::DELETE), PreconditionFailed);
+        assert_eq!(eval(&[("if-none-match", "\"other\"")], Method::GET), Proceed);
+
+        // If-None-Match uses the weak comparison.
+        assert_eq!(eval(&[("if-none-match", "W/\"xyzzy\"")], Method::GET), NotModified);
+    }
+
+    #[test]
+    fn if_none_match_any_for_put() {
+        let headers = req(&[("if-none-match", "*")]);
+        assert_eq!(evaluate(&headers, None, None, &Method::PUT), Proceed);
+        assert_eq!(evaluate(&headers, Some(&etag()), None, &Method::PUT), PreconditionFailed);
+        assert_eq!(evaluate(&headers, None, Some(last_modified()), &Method::PUT), PreconditionFailed);
+    }
+
+    #[test]
+    fn step_4_if_modified_since() {
+        assert_eq!(eval(&[("if-modified-since", AT)], Method::GET), NotModified);
+        assert_eq!(eval(&[("if-modified-since", BEFORE)], Method::GET), Proceed);
+
+        // only for GET and HEAD
+        assert_eq!(eval(&[("if-modified-since", AT)], Method::HEAD), NotModified);
+        assert_eq!(eval(&[("if-modified-since", AT)], Method::POST), Proceed);
+    }
+
+    #[test]
+    fn if_none_match_takes_precedence_over_if_modified_since() {
+        let headers = &[("if-none-match", "\"other\""), ("if-modified-since", AT)];
+        assert_eq!(eval(headers, Method::GET), Proceed);
+    }
+
+    #[test]
+    fn failed_if_match_wins_over_if_none_match() {
+        let headers = &[("if-match", "\"other\""), ("if-none-match", "\"xyzzy\"")];
+        assert_eq!(eval(headers, Method::GET), PreconditionFailed);
+    }
+
+    #[test]
+    fn malformed_dates_are_ignored() {
+        assert_eq!(eval(&[("if-modified-since", "yesterday")], Method::GET), Proceed);
+        assert_eq!(eval(&[("if-unmodified-since", "yesterday")], Method::PUT), Proceed);
+    }
+
+    #[test]
+    fn malformed_entity_tags_fail() {
+        for &value in &["w/\"xyzzy\"", "\"xyzzy\", *", "xyzzy"] {
+            assert_eq!(eval(&[("if-match", value)], Method::PUT), PreconditionFailed, "{:?}", value);
+            assert_eq!(eval(&[("if-none-match", value)], Method::GET), PreconditionFailed, "{:?}", value);
+            assert_eq!(eval(&[("if-none-match", value)], Method::PUT), PreconditionFailed, "{:?}", value);
+        }
+
+        // Even when If-Unmodified-Since would pass.
+        let headers = &[("if-match", "w/\"xyzzy\""), ("if-unmodified-since", AT)];
+        assert_eq!(eval(headers, Method::PUT), PreconditionFailed);
+    }
+
+    #[test]
+    fn step_5_if_range() {
+        let tag = etag();
+        assert!(range_applies(&req(&[]), Some(&tag), None));
+        assert!(range_applies(&req(&[("if-range", "\"xyzzy\"")]), Some(&tag), None));
+        assert!(!range_applies(&req(&[("if-range", "\"other\"")]), Some(&tag), None));
+        assert!(!range_applies(&req(&[("if-range", "W/\"xyzzy\"")]), Some(&tag), None));
+        assert!(range_applies(&req(&[("if-range", AT)]), None, Some(last_modified())));
+        assert!(!range_applies(&req(&[("if-range", BEFORE)]), None, Some(last_modified())));
+        // a date is only a match when it's exactly the last modification
+        assert!(!range_applies(&req(&[("if-range", "Sun, 06 Nov 1994 08:49:38 GMT")]), None, Some(last_modified())));
+        assert!(!range_applies(&req(&[("if-range", "nope")]), Some(&tag), None));
+    }
+}
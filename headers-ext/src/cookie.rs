@@ -0,0 +1,470 @@
+//! Cookies, and a minimal jar to keep them between requests.
+//!
+//! [`CookieJar`](struct.CookieJar.html) follows the storage and retrieval
+//! algorithms of [RFC6265 section 5](https://tools.ietf.org/html/rfc6265#section-5),
+//! for clients that need to send back the cookies they're given. It
+//! isn't a full cookie store: cookies only live in memory, and there's no
+//! public suffix list, so a site can set cookies for a whole top-level
+//! domain such as `com`.
+
+use std::time::SystemTime;
+
+use http::Uri;
+
+pub use common::{
+    Cookie,
+    CookieError,
+    HostPrefixedBuilder,
+    SameSite,
+    SetCookie,
+    SetCookieBuilder,
+};
+
+use common::{check_prefix, SetCookieLine};
+use Origin;
+
+/// Cookies received in `Set-Cookie` headers, to send in the `Cookie`
+/// header of later requests.
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// # extern crate http;
+/// use std::time::SystemTime;
+/// use http::Uri;
+/// use headers::{HeaderMap, HeaderMapExt, HeaderValue, Origin, SetCookie};
+/// use headers::cookie::CookieJar;
+///
+/// # fn main() {
+/// let request_uri = "https://example.com/login".parse::<Uri>().unwrap();
+/// let now = SystemTime::now();
+///
+/// let mut response = HeaderMap::new();
+/// response.append("set-cookie", HeaderValue::from_static("SID=31d4d96e407aad42; Path=/; Secure; HttpOnly"));
+/// response.append("set-cookie", HeaderValue::from_static("lang=en-US; Path=/; Domain=example.com"));
+///
+/// let mut jar = CookieJar::new();
+/// jar.apply(&response.typed_get::<SetCookie>().unwrap(), &request_uri, now);
+///
+/// let origin = "https://example.com".parse::<Origin>().unwrap();
+/// let cookie = jar.cookie_header(&origin, "/", now).unwrap();
+/// assert_eq!(cookie.get("SID"), Some("31d4d96e407aad42"));
+/// assert_eq!(cookie.get("lang"), Some("en-US"));
+///
+/// let www = "http://www.example.com".parse::<Origin>().unwrap();
+/// let cookie = jar.cookie_header(&www, "/", now).unwrap();
+/// assert_eq!(cookie.len(), 1);
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CookieJar {
+    cookies: Vec<StoredCookie>,
+    // Incremented for each cookie stored, to order cookies by creation.
+    created: u64,
+}
+
+#[derive(Clone, Debug)]
+struct StoredCookie {
+    name: String,
+    value: String,
+    domain: String,
+    host_only: bool,
+    path: String,
+    secure: bool,
+    expires: Option<SystemTime>,
+    created: u64,
+}
+
+impl CookieJar {
+    /// Create an empty jar.
+    pub fn new() -> CookieJar {
+        CookieJar::default()
+    }
+
+    /// Store the cookies of a response to a request to `request_uri`.
+    ///
+    /// A cookie replaces any stored one with the same name, domain and
+    /// path, and a cookie that's already expired only removes it. Cookies
+    /// without a `Path` get the directory of the request path. Cookies
+    /// are ignored if they:
+    ///
+    /// - can't be parsed,
+    /// - have a `Domain` that the request's host doesn't domain-match,
+    /// - are `Secure` but the request isn't, or break the rules of the
+    ///   `__Secure-` and `__Host-` prefixes.
+    ///
+    /// All of them are ignored if `request_uri` has no host.
+    pub fn apply(&mut self, set_cookie: &SetCookie, request_uri: &Uri, now: SystemTime) {
+        if request_uri.host().is_none() {
+            return;
+        }
+
+        for line in set_cookie.lines() {
+            let line = match line.to_str().ok().and_then(SetCookieLine::parse) {
+                Some(line) => line,
+                None => continue,
+            };
+            if let Some(cookie) = self.store(&line, request_uri, now) {
+                self.insert(cookie, now);
+            }
+        }
+    }
+
+    /// Build the `Cookie` header to send in a request to `path` on
+    /// `origin`, if any stored cookie applies.
+    ///
+    /// Cookies with longer paths come first, then the ones created
+    /// earlier.
+    pub fn cookie_header(&self, origin: &Origin, path: &str, now: SystemTime) -> Option<Cookie> {
+        if origin.is_null() {
+            return None;
+        }
+        let host = origin.hostname().to_ascii_lowercase();
+        let secure_origin = is_secure(origin.scheme());
+
+        let mut cookies = self
+            .cookies
+            .iter()
+            .filter(|cookie| !cookie.is_expired(now))
+            .filter(|cookie| {
+                if cookie.host_only {
+                    host == cookie.domain
+                } else {
+                    domain_match(&host, &cookie.domain)
+                }
+            })
+            .filter(|cookie| path_match(path, &cookie.path))
+            .filter(|cookie| !cookie.secure || secure_origin)
+            .collect::<Vec<_>>();
+        cookies.sort_by(|a, b| b.path.len().cmp(&a.path.len()).then(a.created.cmp(&b.created)));
+
+        Cookie::from_pairs(cookies.iter().map(|cookie| (&*cookie.name, &*cookie.value)))
+    }
+
+    // RFC6265 section 5.3, steps 2 to 10.
+    fn store(&self, line: &SetCookieLine, request_uri: &Uri, now: SystemTime) -> Option<StoredCookie> {
+        let host = request_uri.host()?.to_ascii_lowercase();
+        let secure_origin = request_uri.scheme_str().is_some_and(is_secure);
+
+        let mut domain = None;
+        let mut path = None;
+        let mut secure = false;
+
+        for (name, value) in line.attributes() {
+            if name.eq_ignore_ascii_case("domain") {
+                if !value.is_empty() {
+                    let value = value.strip_prefix('.').unwrap_or(value);
+                    domain = Some(value.to_ascii_lowercase());
+                }
+            } else if name.eq_ignore_ascii_case("path") {
+                path = Some(value);
+            } else if name.eq_ignore_ascii_case("secure") {
+                secure = true;
+            }
+        }
+
+        if secure && !secure_origin {
+            return None;
+        }
+        check_prefix(line.name, secure, domain.is_some(), path).ok()?;
+
+        let expires = line.expires_at(now);
+
+        let (domain, host_only) = match domain {
+            Some(domain) => {
+                if !domain_match(&host, &domain) {
+                    return None;
+                }
+                (domain, false)
+            },
+            None => (host, true),
+        };
+        let path = match path {
+            Some(path) if path.starts_with('/') => path,
+            _ => default_path(request_uri.path()),
+        };
+
+        Some(StoredCookie {
+            name: line.name.to_owned(),
+            value: line.value.to_owned(),
+            domain,
+            host_only,
+            path: path.to_owned(),
+            secure,
+            expires,
+            created: 0,
+        })
+    }
+
+    fn insert(&mut self, mut cookie: StoredCookie, now: SystemTime) {
+        let existing = self.cookies.iter().position(|stored| {
+            stored.name == cookie.name && stored.domain == cookie.domain && stored.path == cookie.path
+        });
+        // A replaced cookie keeps its creation time.
+        cookie.created = match existing {
+            Some(i) => self.cookies.remove(i).created,
+            None => {
+                self.created += 1;
+                self.created
+            },
+        };
+        if !cookie.is_expired(now) {
+            self.cookies.push(cookie);
+        }
+    }
+}
+
+impl StoredCookie {
+    fn is_expired(&self, now: SystemTime) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+}
+
+fn is_secure(scheme: &str) -> bool {
+    scheme == "https" || scheme == "wss"
+}
+
+/// The default-path of a cookie, as in
+/// [RFC6265 section 5.1.4](https://tools.ietf.org/html/rfc6265#section-5.1.4):
+/// the request path up to, but not including, its last `/`.
+fn default_path(request_path: &str) -> &str {
+    if !request_path.starts_with('/') {
+        return "/";
+    }
+    match request_path.rfind('/') {
+        Some(0) | None => "/",
+        Some(end) => &request_path[..end],
+    }
+}
+
+/// Domain matching, as in
+/// [RFC6265 section 5.1.3](https://tools.ietf.org/html/rfc6265#section-5.1.3).
+///
+/// Both are lowercase.
+fn domain_match(host: &str, domain: &str) -> bool {
+    if host == domain {
+        return true;
+    }
+    let is_ip = host.contains(':') || host.starts_with('[') || host.parse::<::std::net::Ipv4Addr>().is_ok();
+    !is_ip
+        && host.len() > domain.len()
+        && host.ends_with(domain)
+        && host.as_bytes()[host.len() - domain.len() - 1] == b'.'
+}
+
+/// Path matching, as in
+/// [RFC6265 section 5.1.4](https://tools.ietf.org/html/rfc6265#section-5.1.4).
+fn path_match(request_path: &str, cookie_path: &str) -> bool {
+    request_path == cookie_path
+        || (request_path.starts_with(cookie_path)
+            && (cookie_path.ends_with('/') || request_path.as_bytes()[cookie_path.len()] == b'/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use http::Uri;
+
+    use super::{default_path, domain_match, path_match, CookieJar};
+    use {HeaderMap, HeaderValue, HeaderMapExt, Origin, SetCookie};
+
+    fn origin(s: &str) -> Origin {
+        s.parse().unwrap()
+    }
+
+    fn uri(s: &str) -> Uri {
+        s.parse().unwrap()
+    }
+
+    fn set_cookie(lines: &[&'static str]) -> SetCookie {
+        let mut map = HeaderMap::new();
+        for &line in lines {
+            map.append("set-cookie", HeaderValue::from_static(line));
+        }
+        map.typed_get().unwrap()
+    }
+
+    // Sun, 06 Nov 1994 08:49:37 GMT
+    fn now() -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(784111777)
+    }
+
+    fn header(jar: &CookieJar, o: &str, path: &str, now: SystemTime) -> Option<String> {
+        jar.cookie_header(&origin(o), path, now).map(|cookie| {
+            cookie
+                .iter()
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect::<Vec<_>>()
+                .join("; ")
+        })
+    }
+
+    #[test]
+    fn rfc_6265_examples() {
+        // Section 3.1
+        let mut jar = CookieJar::new();
+        let example = uri("https://example.com");
+
+        jar.apply(&set_cookie(&["SID=31d4d96e407aad42"]), &example, now());
+        assert_eq!(header(&jar, "https://example.com", "/", now()).unwrap(), "SID=31d4d96e407aad42");
+        // host-only
+        assert_eq!(header(&jar, "https://www.example.com", "/", now()), None);
+
+        jar.apply(
+            &set_cookie(&["SID=31d4d96e407aad42; Path=/; Secure; HttpOnly", "lang=en-US; Path=/; Domain=example.com"]),
+            &example,
+            now(),
+        );
+        assert_eq!(header(&jar, "https://example.com", "/", now()).unwrap(), "SID=31d4d96e407aad42; lang=en-US");
+        assert_eq!(header(&jar, "https://www.example.com", "/", now()).unwrap(), "lang=en-US");
+        // Secure
+        assert_eq!(header(&jar, "http://example.com", "/", now()).unwrap(), "lang=en-US");
+
+        // Removal, with the same name, domain and path.
+        jar.apply(&set_cookie(&["lang=; Expires=Sun, 06 Nov 1994 08:49:37 GMT"]), &example, now());
+        assert_eq!(header(&jar, "https://example.com", "/", now()).unwrap(), "SID=31d4d96e407aad42");
+
+        // Expires, and the host-only flag isn't part of the identity.
+        jar.apply(&set_cookie(&["lang=en-US; Domain=example.com", "lang=fr; Expires=Wed, 09 Jun 2021 10:18:14 GMT"]), &example, now());
+        let before = UNIX_EPOCH + Duration::from_secs(1623233000);
+        let after = UNIX_EPOCH + Duration::from_secs(1623234000);
+        assert_eq!(header(&jar, "https://example.com", "/", before).unwrap(), "SID=31d4d96e407aad42; lang=fr");
+        assert_eq!(header(&jar, "https://www.example.com", "/", before), None);
+        assert_eq!(header(&jar, "https://example.com", "/", after).unwrap(), "SID=31d4d96e407aad42");
+    }
+
+    #[test]
+    fn expiry() {
+        let mut jar = CookieJar::new();
+        let example = uri("https://example.com");
+        jar.apply(
+            &set_cookie(&["a=1; Max-Age=60", "b=2; Expires=Sun, 06 Nov 1994 08:50:37 GMT", "c=3"]),
+            &example,
+            now(),
+        );
+        assert_eq!(header(&jar, "https://example.com", "/", now()).unwrap(), "a=1; b=2; c=3");
+        let later = now() + Duration::from_secs(60);
+        assert_eq!(header(&jar, "https://example.com", "/", later).unwrap(), "c=3");
+    }
+
+    #[test]
+    fn max_age_precedence() {
+        let mut jar = CookieJar::new();
+        let example = uri("https://example.com");
+        // Max-Age wins over Expires, wherever it is.
+        jar.apply(
+            &set_cookie(&[
+                "a=1; Max-Age=120; Expires=Sun, 06 Nov 1994 08:49:37 GMT",
+                "b=2; Expires=Sun, 06 Nov 1994 09:49:37 GMT; Max-Age=-1",
+                "c=3; Max-Age=soon",
+                "d=4; Max-Age=99999999999999999999999",
+            ]),
+            &example,
+            now(),
+        );
+        assert_eq!(header(&jar, "https://example.com", "/", now()).unwrap(), "a=1; c=3; d=4");
+    }
+
+    #[test]
+    fn overwrite_by_name_domain_and_path() {
+        let mut jar = CookieJar::new();
+        let example = uri("https://example.com");
+        jar.apply(&set_cookie(&["a=1", "a=2; Path=/docs", "b=3; Domain=example.com"]), &example, now());
+        assert_eq!(header(&jar, "https://example.com", "/docs", now()).unwrap(), "a=2; a=1; b=3");
+
+        // A replaced cookie keeps its place.
+        jar.apply(&set_cookie(&["a=4; Domain=example.com", "a=5; Path=/docs"]), &example, now());
+        assert_eq!(header(&jar, "https://example.com", "/docs", now()).unwrap(), "a=5; a=4; b=3");
+        assert_eq!(header(&jar, "https://www.example.com", "/", now()).unwrap(), "a=4; b=3");
+    }
+
+    #[test]
+    fn rejected_domains() {
+        let mut jar = CookieJar::new();
+        jar.apply(
+            &set_cookie(&["a=1; Domain=bar.example.com", "b=2; Domain=ample.com", "c=3; Domain=.Example.COM"]),
+            &uri("https://foo.example.com"),
+            now(),
+        );
+        assert_eq!(header(&jar, "https://foo.example.com", "/", now()).unwrap(), "c=3");
+        assert_eq!(header(&jar, "https://bar.example.com", "/", now()).unwrap(), "c=3");
+        assert_eq!(header(&jar, "https://example.com", "/", now()).unwrap(), "c=3");
+        assert_eq!(header(&jar, "https://ample.com", "/", now()), None);
+    }
+
+    #[test]
+    fn secure_and_prefixes() {
+        let mut jar = CookieJar::new();
+        jar.apply(&set_cookie(&["a=1; Secure", "__Secure-b=2; Secure"]), &uri("http://example.com"), now());
+        assert_eq!(header(&jar, "https://example.com", "/", now()), None);
+
+        jar.apply(
+            &set_cookie(&["__Secure-b=2", "__Host-c=3; Secure; Path=/; Domain=example.com", "__Host-d=4; Secure; Path=/"]),
+            &uri("https://example.com"),
+            now(),
+        );
+        assert_eq!(header(&jar, "https://example.com", "/", now()).unwrap(), "__Host-d=4");
+    }
+
+    #[test]
+    fn ignores_malformed() {
+        let mut jar = CookieJar::new();
+        jar.apply(&set_cookie(&["nope", "=1", "a=1"]), &uri("https://example.com"), now());
+        assert_eq!(header(&jar, "https://example.com", "/", now()).unwrap(), "a=1");
+        assert_eq!(header(&jar, "https://example.com", "/", now()).unwrap(), "a=1");
+        jar.apply(&set_cookie(&["b=1"]), &uri("/"), now());
+        assert!(jar.cookie_header(&Origin::NULL, "/", now()).is_none());
+    }
+
+    #[test]
+    fn ordering() {
+        let mut jar = CookieJar::new();
+        jar.apply(&set_cookie(&["a=1", "b=2; Path=/docs/web", "c=3; Path=/docs"]), &uri("https://example.com"), now());
+        assert_eq!(header(&jar, "https://example.com", "/docs/web/x", now()).unwrap(), "b=2; c=3; a=1");
+    }
+
+    #[test]
+    fn path_defaults_to_request_directory() {
+        let mut jar = CookieJar::new();
+        jar.apply(&set_cookie(&["a=1", "b=2; Path=docs", "c=3; Path=/"]), &uri("https://example.com/docs/web/page?q=1"), now());
+        assert_eq!(header(&jar, "https://example.com", "/docs/web/x", now()).unwrap(), "a=1; b=2; c=3");
+        assert_eq!(header(&jar, "https://example.com", "/docs/web", now()).unwrap(), "a=1; b=2; c=3");
+        assert_eq!(header(&jar, "https://example.com", "/docs", now()).unwrap(), "c=3");
+
+        assert_eq!(default_path("/docs/web/page"), "/docs/web");
+        assert_eq!(default_path("/docs/web/"), "/docs/web");
+        assert_eq!(default_path("/page"), "/");
+        assert_eq!(default_path("/"), "/");
+        assert_eq!(default_path(""), "/");
+        assert_eq!(default_path("*"), "/");
+    }
+
+    #[test]
+    fn domain_matching() {
+        assert!(domain_match("example.com", "example.com"));
+        assert!(domain_match("www.example.com", "example.com"));
+        assert!(domain_match("a.b.example.com", "example.com"));
+        assert!(domain_match("www.example.com", "com"));
+        assert!(!domain_match("example.com", "www.example.com"));
+        assert!(!domain_match("badexample.com", "example.com"));
+        assert!(domain_match("127.0.0.1", "127.0.0.1"));
+        assert!(!domain_match("10.0.0.1", "0.0.1"));
+        assert!(!domain_match("[::1]", "1]"));
+    }
+
+    #[test]
+    fn path_matching() {
+        assert!(path_match("/", "/"));
+        assert!(path_match("/docs", "/"));
+        assert!(path_match("/docs", "/docs"));
+        assert!(path_match("/docs/", "/docs"));
+        assert!(path_match("/docs/web", "/docs"));
+        assert!(path_match("/docs/web", "/docs/"));
+        assert!(!path_match("/docsets", "/docs"));
+        assert!(!path_match("/doc", "/docs"));
+        assert!(!path_match("/", "/docs"));
+        assert!(!path_match("/Docs", "/docs"));
+    }
+}
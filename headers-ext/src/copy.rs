@@ -0,0 +1,335 @@
+//! Copying headers from one map to another, such as a proxy forwarding
+//! the end-to-end headers of a request.
+
+use http::header::{
+    CONNECTION,
+    PROXY_AUTHENTICATE,
+    PROXY_AUTHORIZATION,
+    TE,
+    TRAILER,
+    TRANSFER_ENCODING,
+    UPGRADE,
+};
+
+use {Header, HeaderMap, HeaderMapExt, HeaderName};
+
+/// Copy typed headers from one `HeaderMap` to another.
+///
+/// Each header is decoded from the source and inserted into the
+/// destination, replacing what it had. This uses the default
+/// [`CopyHeaders`](copy/struct.CopyHeaders.html), skipping headers that
+/// fail to decode and hop-by-hop headers. Pass another one after the
+/// list to change that.
+///
+/// # Example
+///
+/// ```
+/// # #[macro_use] extern crate headers_ext as headers;
+/// use headers::{CacheControl, ContentLength, ContentType, HeaderMap, HeaderMapExt};
+///
+/// # fn main() {
+/// let mut inbound = HeaderMap::new();
+/// inbound.typed_insert(ContentLength(3));
+/// inbound.typed_insert(ContentType::text());
+///
+/// let mut outbound = HeaderMap::new();
+/// typed_copy!(&inbound, &mut outbound, [CacheControl, ContentType, ContentLength]);
+///
+/// assert_eq!(outbound.len(), 2);
+/// assert_eq!(outbound.typed_get(), Some(ContentLength(3)));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! typed_copy {
+    ($src:expr, $dst:expr, [$($ty:ty),* $(,)*]) => {
+        typed_copy!($src, $dst, [$($ty),*], $crate::copy::CopyHeaders::new())
+    };
+    ($src:expr, $dst:expr, [$($ty:ty),* $(,)*], $copy:expr) => {{
+        let copy: &$crate::copy::CopyHeaders = &$copy;
+        let src: &$crate::HeaderMap = $src;
+        let dst: &mut $crate::HeaderMap = $dst;
+        let listed = copy.listed(src);
+        $(
+            copy.typed_listed::<$ty>(src, dst, &listed);
+        )*
+    }};
+}
+
+/// How to copy headers between maps.
+///
+/// By default, headers that fail to decode aren't copied, and neither are
+/// hop-by-hop headers: the ones that only apply to a single connection,
+/// and any others the source's `Connection` header lists.
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::{HeaderMap, HeaderName, HeaderValue};
+/// use headers::copy::CopyHeaders;
+///
+/// let mut inbound = HeaderMap::new();
+/// inbound.insert("connection", HeaderValue::from_static("x-trace"));
+/// inbound.insert("x-trace", HeaderValue::from_static("1"));
+/// inbound.insert("x-request-id", HeaderValue::from_static("abc"));
+///
+/// let names = ["x-trace", "x-request-id"].iter().map(|n| HeaderName::from_static(n)).collect::<Vec<_>>();
+/// let mut outbound = HeaderMap::new();
+/// CopyHeaders::new().names(&inbound, &mut outbound, &names);
+///
+/// assert_eq!(outbound.len(), 1);
+/// assert_eq!(outbound["x-request-id"], "abc");
+/// ```
+#[derive(Clone, Debug)]
+pub struct CopyHeaders {
+    hop_by_hop: bool,
+    invalid: bool,
+}
+
+impl CopyHeaders {
+    /// Create the default `CopyHeaders`.
+    pub fn new() -> CopyHeaders {
+        CopyHeaders {
+            hop_by_hop: false,
+            invalid: false,
+        }
+    }
+
+    /// Set whether hop-by-hop headers are copied too.
+    pub fn with_hop_by_hop(mut self, copy: bool) -> Self {
+        self.hop_by_hop = copy;
+        self
+    }
+
+    /// Set whether a typed header that fails to decode has its values
+    /// copied as they are, instead of being skipped.
+    pub fn with_invalid(mut self, copy: bool) -> Self {
+        self.invalid = copy;
+        self
+    }
+
+    /// Copy the header `H` from `src` to `dst`, replacing any values `dst`
+    /// had.
+    ///
+    /// Returns whether it was copied.
+    pub fn typed<H: Header>(&self, src: &HeaderMap, dst: &mut HeaderMap) -> bool {
+        self.typed_listed::<H>(src, dst, &self.listed(src))
+    }
+
+    #[doc(hidden)]
+    pub fn typed_listed<H: Header>(&self, src: &HeaderMap, dst: &mut HeaderMap, listed: &Listed) -> bool {
+        if self.skip(listed, H::name()) {
+            return false;
+        }
+        src.typed_clone_into::<H>(dst) || (self.invalid && copy_values(src, dst, H::name()))
+    }
+
+    /// Copy the values of each of `names` from `src` to `dst` as they are,
+    /// replacing any values `dst` had for them.
+    pub fn names<'a, I>(&self, src: &HeaderMap, dst: &mut HeaderMap, names: I)
+    where
+        I: IntoIterator<Item = &'a HeaderName>,
+    {
+        let listed = self.listed(src);
+        for name in names {
+            if !self.skip(&listed, name) {
+                copy_values(src, dst, name);
+            }
+        }
+    }
+
+    // `Connection` is only read when hop-by-hop headers are skipped.
+    #[doc(hidden)]
+    pub fn listed(&self, src: &HeaderMap) -> Listed {
+        if self.hop_by_hop {
+            Listed(Vec::new())
+        } else {
+            Listed::new(src)
+        }
+    }
+
+    fn skip(&self, listed: &Listed, name: &HeaderName) -> bool {
+        !self.hop_by_hop && (is_always_hop_by_hop(name) || listed.contains(name))
+    }
+}
+
+impl Default for CopyHeaders {
+    fn default() -> CopyHeaders {
+        CopyHeaders::new()
+    }
+}
+
+/// Check whether `name` is a hop-by-hop header in `map`.
+///
+/// That's either one defined to be, such as `Connection` and
+/// `Transfer-Encoding`, or one listed in the `Connection` header of `map`.
+/// A malformed `Connection` still lists every name in it, so that a
+/// header meant for a single hop isn't forwarded by mistake.
+pub fn is_hop_by_hop(map: &HeaderMap, name: &HeaderName) -> bool {
+    is_always_hop_by_hop(name) || Listed::new(map).contains(name)
+}
+
+fn is_always_hop_by_hop(name: &HeaderName) -> bool {
+    let always = [
+        CONNECTION,
+        PROXY_AUTHENTICATE,
+        PROXY_AUTHORIZATION,
+        TE,
+        TRAILER,
+        TRANSFER_ENCODING,
+        UPGRADE,
+    ];
+    always.contains(name) || name == "keep-alive" || name == "proxy-connection"
+}
+
+/// The header names the `Connection` header of a map lists, read once per
+/// copy.
+///
+/// The raw lines are split on commas and whitespace rather than decoded,
+/// so a malformed `Connection` still lists every name it could mean.
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct Listed(Vec<HeaderName>);
+
+impl Listed {
+    fn new(map: &HeaderMap) -> Listed {
+        let names = map
+            .get_all(CONNECTION)
+            .iter()
+            .flat_map(|line| line.as_bytes().split(|&b| b == b',' || b == b' ' || b == b'\t'))
+            .filter_map(|name| HeaderName::from_bytes(name).ok())
+            .collect();
+        Listed(names)
+    }
+
+    fn contains(&self, name: &HeaderName) -> bool {
+        self.0.contains(name)
+    }
+}
+
+fn copy_values(src: &HeaderMap, dst: &mut HeaderMap, name: &HeaderName) -> bool {
+    let mut values = src.get_all(name).iter();
+    let first = match values.next() {
+        Some(first) => first,
+        None => return false,
+    };
+    dst.insert(name.clone(), first.clone());
+    for value in values {
+        dst.append(name.clone(), value.clone());
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_hop_by_hop, CopyHeaders};
+    use {CacheControl, Connection, ContentLength, HeaderMap, HeaderMapExt, HeaderName, HeaderValue, SetCookie, Te};
+
+    fn map(headers: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for &(name, value) in headers {
+            map.append(name, HeaderValue::from_static(value));
+        }
+        map
+    }
+
+    fn values<'a>(map: &'a HeaderMap, name: &str) -> Vec<&'a str> {
+        map.get_all(name).iter().map(|v| v.to_str().unwrap()).collect()
+    }
+
+    #[test]
+    fn typed_copy_macro() {
+        let src = map(&[
+            ("cache-control", "no-cache"),
+            ("cache-control", "max-age=0"),
+            ("content-length", "3"),
+            ("content-length", "4"),
+            ("set-cookie", "a=1"),
+            ("set-cookie", "b=2"),
+        ]);
+        let mut dst = map(&[("cache-control", "public"), ("x-kept", "1")]);
+
+        typed_copy!(&src, &mut dst, [CacheControl, ContentLength, SetCookie]);
+
+        // multiple values decode into one, or stay multiple
+        assert_eq!(values(&dst, "cache-control"), ["no-cache, max-age=0"]);
+        assert_eq!(values(&dst, "set-cookie"), ["a=1", "b=2"]);
+        // conflicting lengths fail to decode
+        assert!(dst.typed_get::<ContentLength>().is_none());
+        assert_eq!(values(&dst, "x-kept"), ["1"]);
+    }
+
+    #[test]
+    fn invalid_values() {
+        let src = map(&[("content-length", "nope")]);
+
+        let mut dst = HeaderMap::new();
+        assert!(!CopyHeaders::new().typed::<ContentLength>(&src, &mut dst));
+        assert!(dst.is_empty());
+
+        typed_copy!(&src, &mut dst, [ContentLength], CopyHeaders::new().with_invalid(true));
+        assert_eq!(values(&dst, "content-length"), ["nope"]);
+    }
+
+    #[test]
+    fn hop_by_hop() {
+        let src = map(&[
+            ("connection", "keep-alive, X-Trace"),
+            ("keep-alive", "timeout=5"),
+            ("te", "trailers"),
+            ("x-trace", "1"),
+            ("x-request-id", "abc"),
+            ("x-request-id", "def"),
+        ]);
+        let names = ["connection", "keep-alive", "te", "x-trace", "x-request-id"]
+            .iter()
+            .map(|n| HeaderName::from_static(n))
+            .collect::<Vec<_>>();
+
+        let mut dst = HeaderMap::new();
+        CopyHeaders::new().names(&src, &mut dst, &names);
+        assert!(!CopyHeaders::new().typed::<Te>(&src, &mut dst));
+        assert_eq!(dst.len(), 2);
+        assert_eq!(values(&dst, "x-request-id"), ["abc", "def"]);
+
+        let mut dst = HeaderMap::new();
+        let copy = CopyHeaders::new().with_hop_by_hop(true);
+        copy.names(&src, &mut dst, &names);
+        assert!(copy.typed::<Te>(&src, &mut dst));
+        assert_eq!(dst.len(), 6);
+    }
+
+    #[test]
+    fn is_hop_by_hop_names() {
+        let src = map(&[("connection", "close")]);
+        assert!(is_hop_by_hop(&src, &HeaderName::from_static("transfer-encoding")));
+        assert!(is_hop_by_hop(&src, &HeaderName::from_static("proxy-connection")));
+        assert!(!is_hop_by_hop(&src, &HeaderName::from_static("content-type")));
+
+        let src = map(&[("connection", "content-type")]);
+        assert!(is_hop_by_hop(&src, &HeaderName::from_static("content-type")));
+    }
+
+    #[test]
+    fn malformed_connection_is_stripped() {
+        let mut src = map(&[
+            ("connection", "x-trace, b@d"),
+            ("x-trace", "1"),
+            ("x-debug", "1"),
+            ("x-request-id", "abc"),
+        ]);
+        src.append("connection", HeaderValue::from_bytes(b"x-debug caf\xe9").unwrap());
+        assert!(src.typed_get::<Connection>().is_none());
+        assert!(is_hop_by_hop(&src, &HeaderName::from_static("x-trace")));
+        assert!(is_hop_by_hop(&src, &HeaderName::from_static("x-debug")));
+
+        let names = ["x-trace", "x-debug", "x-request-id"]
+            .iter()
+            .map(|n| HeaderName::from_static(n))
+            .collect::<Vec<_>>();
+        let mut dst = HeaderMap::new();
+        CopyHeaders::new().names(&src, &mut dst, &names);
+        assert_eq!(dst.len(), 1);
+        assert_eq!(values(&dst, "x-request-id"), ["abc"]);
+    }
+}
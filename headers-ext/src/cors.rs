@@ -0,0 +1,267 @@
+//! Headers for Cross-Origin Resource Sharing, as defined by the
+//! [Fetch Standard](https://fetch.spec.whatwg.org/#http-cors-protocol),
+//! and answering preflight requests with them.
+//!
+//! A server that allows whatever a preflight asks for, such as during
+//! development, can echo the request through conversions alone:
+//!
+//! ```
+//! # extern crate headers_ext as headers;
+//! use headers::{HeaderMap, HeaderMapExt, HeaderValue};
+//! use headers::cors::*;
+//!
+//! let mut req = HeaderMap::new();
+//! req.insert("origin", HeaderValue::from_static("https://app.example.com"));
+//! req.insert("access-control-request-method", HeaderValue::from_static("PUT"));
+//! req.insert("access-control-request-headers", HeaderValue::from_static("content-type, x-token"));
+//!
+//! let mut res = HeaderMap::new();
+//! res.typed_insert(AccessControlAllowOrigin::from(req.typed_get::<Origin>().unwrap()));
+//! res.typed_insert(AccessControlAllowMethods::from(
+//!     req.typed_get::<AccessControlRequestMethod>().unwrap(),
+//! ));
+//! res.typed_insert(AccessControlAllowHeaders::from(
+//!     req.typed_get::<AccessControlRequestHeaders>().unwrap(),
+//! ));
+//!
+//! assert_eq!(res["access-control-allow-origin"], "https://app.example.com");
+//! assert_eq!(res["access-control-allow-methods"], "PUT");
+//! assert_eq!(res["access-control-allow-headers"], "content-type, x-token");
+//! ```
+//!
+//! `preflight` checks the request against what's allowed instead.
+
+use std::time::Duration;
+
+use http::Method;
+
+pub use common::{
+    AccessControlAllowCredentials,
+    AccessControlAllowHeaders,
+    AccessControlAllowMethods,
+    AccessControlAllowOrigin,
+    AccessControlExposeHeaders,
+    AccessControlMaxAge,
+    AccessControlRequestHeaders,
+    AccessControlRequestMethod,
+    InvalidOriginPattern,
+    Origin,
+    OriginPattern,
+};
+
+use {HeaderMap, HeaderMapExt, HeaderValue};
+
+/// Answer a preflight request, for requests without credentials.
+///
+/// `req_headers` are the headers of the `OPTIONS` request. It's a
+/// preflight if it has an `Origin` and an `Access-Control-Request-Method`.
+/// The actual request is allowed if:
+///
+/// - `allow_origin` accepts its origin, which may be `null`,
+/// - its method is one of `allowed_methods`,
+/// - and every header in `Access-Control-Request-Headers` is in
+///   `allowed_headers`. A `*` allows any header but `Authorization`.
+///
+/// Returns the headers of the response: the origin echoed in
+/// `Access-Control-Allow-Origin`, with `Vary: Origin`,
+/// `Access-Control-Allow-Methods`, `Access-Control-Allow-Headers`, and
+/// `Access-Control-Max-Age` if `max_age` is set. Returns `None` if the
+/// request isn't a valid preflight, or isn't allowed, in which case the
+/// response should have no CORS headers.
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// # extern crate http;
+/// use std::time::Duration;
+/// use headers::{HeaderMap, HeaderValue};
+/// use headers::cors::{self, AccessControlAllowHeaders, OriginPattern};
+/// use http::Method;
+///
+/// let mut req = HeaderMap::new();
+/// req.insert("origin", HeaderValue::from_static("https://app.example.com"));
+/// req.insert("access-control-request-method", HeaderValue::from_static("PUT"));
+/// req.insert("access-control-request-headers", HeaderValue::from_static("content-type"));
+///
+/// let allowed = OriginPattern::parse("https://*.example.com").unwrap();
+/// let res = cors::preflight(
+///     &req,
+///     &[Method::GET, Method::PUT],
+///     &"content-type".parse::<AccessControlAllowHeaders>().unwrap(),
+///     |origin| origin.matches(&allowed),
+///     Some(Duration::from_secs(600)),
+/// ).unwrap();
+///
+/// assert_eq!(res["access-control-allow-origin"], "https://app.example.com");
+/// assert_eq!(res["access-control-allow-methods"], "GET, PUT");
+/// assert_eq!(res["access-control-max-age"], "600");
+/// ```
+pub fn preflight<F>(
+    req_headers: &HeaderMap,
+    allowed_methods: &[Method],
+    allowed_headers: &AccessControlAllowHeaders,
+    allow_origin: F,
+    max_age: Option<Duration>,
+) -> Option<HeaderMap>
+where
+    F: Fn(&Origin) -> bool,
+{
+    respond(req_headers, allowed_methods, allowed_headers, allow_origin, max_age, false)
+}
+
+/// Answer a preflight request, like `preflight`, for requests with
+/// credentials, such as cookies.
+///
+/// The response also has `Access-Control-Allow-Credentials: true`.
+/// Browsers take a `*` as a literal name for these requests, so an
+/// `allowed_headers` of `*` allows no header, and isn't sent.
+pub fn preflight_with_credentials<F>(
+    req_headers: &HeaderMap,
+    allowed_methods: &[Method],
+    allowed_headers: &AccessControlAllowHeaders,
+    allow_origin: F,
+    max_age: Option<Duration>,
+) -> Option<HeaderMap>
+where
+    F: Fn(&Origin) -> bool,
+{
+    respond(req_headers, allowed_methods, allowed_headers, allow_origin, max_age, true)
+}
+
+fn respond<F>(
+    req_headers: &HeaderMap,
+    allowed_methods: &[Method],
+    allowed_headers: &AccessControlAllowHeaders,
+    allow_origin: F,
+    max_age: Option<Duration>,
+    credentials: bool,
+) -> Option<HeaderMap>
+where
+    F: Fn(&Origin) -> bool,
+{
+    let origin = req_headers.typed_get::<Origin>()?;
+    let method = req_headers.typed_get::<AccessControlRequestMethod>()?;
+    if !allow_origin(&origin) || !allowed_methods.contains(method.method()) {
+        return None;
+    }
+
+    let with_headers = !(credentials && allowed_headers.is_any());
+    if let Some(requested) = req_headers.typed_get::<AccessControlRequestHeaders>() {
+        if !with_headers || !allowed_headers.is_superset_of(&requested) {
+            return None;
+        }
+    }
+
+    let mut res = HeaderMap::new();
+    res.typed_insert(AccessControlAllowOrigin::from(origin));
+    res.insert(::http::header::VARY, HeaderValue::from_static("origin"));
+    if credentials {
+        res.typed_insert(AccessControlAllowCredentials);
+    }
+    res.typed_insert(allowed_methods.iter().cloned().collect::<AccessControlAllowMethods>());
+    if with_headers && (allowed_headers.is_any() || allowed_headers.iter().next().is_some()) {
+        res.typed_insert(allowed_headers.clone());
+    }
+    if let Some(max_age) = max_age {
+        res.typed_insert(AccessControlMaxAge::from(max_age));
+    }
+    Some(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use http::Method;
+
+    use super::*;
+
+    fn request(origin: &'static str, method: &'static str, headers: Option<&'static str>) -> HeaderMap {
+        let mut req = HeaderMap::new();
+        req.insert("origin", HeaderValue::from_static(origin));
+        req.insert("access-control-request-method", HeaderValue::from_static(method));
+        if let Some(headers) = headers {
+            req.insert("access-control-request-headers", HeaderValue::from_static(headers));
+        }
+        req
+    }
+
+    fn example(origin: &Origin) -> bool {
+        origin.matches(&OriginPattern::parse("https://*.example.com").unwrap())
+    }
+
+    fn names(s: &str) -> AccessControlAllowHeaders {
+        s.parse().unwrap()
+    }
+
+    const METHODS: &[Method] = &[Method::DELETE, Method::GET, Method::POST];
+
+    #[test]
+    fn allowed() {
+        let req = request("https://app.example.com", "DELETE", Some("X-Token, content-type"));
+        let res = preflight(&req, METHODS, &names("content-type, x-token"), example, None).unwrap();
+
+        assert_eq!(res["access-control-allow-origin"], "https://app.example.com");
+        assert_eq!(res["vary"], "origin");
+        assert_eq!(res["access-control-allow-methods"], "DELETE, GET, POST");
+        assert_eq!(res["access-control-allow-headers"], "content-type, x-token");
+        assert!(!res.contains_key("access-control-allow-credentials"));
+        assert!(!res.contains_key("access-control-max-age"));
+        assert_eq!(res.len(), 4);
+    }
+
+    #[test]
+    fn forbidden() {
+        let allowed = names("content-type");
+        let check = |req: HeaderMap| preflight(&req, METHODS, &allowed, example, None);
+
+        assert!(check(request("https://app.example.com", "GET", None)).is_some());
+        assert!(check(request("https://evil.com", "GET", None)).is_none());
+        assert!(check(request("null", "GET", None)).is_none());
+        assert!(check(request("https://app.example.com", "PUT", None)).is_none());
+        assert!(check(request("https://app.example.com", "GET", Some("content-type, x-other"))).is_none());
+
+        // Not a preflight.
+        let mut req = request("https://app.example.com", "GET", None);
+        req.remove("access-control-request-method");
+        assert!(check(req).is_none());
+        let mut req = request("https://app.example.com", "GET", None);
+        req.remove("origin");
+        assert!(check(req).is_none());
+    }
+
+    #[test]
+    fn wildcard_headers() {
+        let req = request("https://app.example.com", "POST", Some("x-anything"));
+        let res = preflight(&req, METHODS, &AccessControlAllowHeaders::any(), example, None).unwrap();
+        assert_eq!(res["access-control-allow-headers"], "*");
+
+        // `*` doesn't cover `Authorization`.
+        let req = request("https://app.example.com", "POST", Some("authorization"));
+        assert!(preflight(&req, METHODS, &AccessControlAllowHeaders::any(), example, None).is_none());
+        assert!(preflight(&req, METHODS, &names("authorization"), example, None).is_some());
+    }
+
+    #[test]
+    fn credentialed() {
+        let max_age = Some(Duration::from_secs(3600));
+        let req = request("https://app.example.com", "POST", Some("authorization"));
+        let res = preflight_with_credentials(&req, METHODS, &names("authorization"), example, max_age).unwrap();
+
+        assert_eq!(res["access-control-allow-origin"], "https://app.example.com");
+        assert_eq!(res["access-control-allow-credentials"], "true");
+        assert_eq!(res["access-control-allow-headers"], "authorization");
+        assert_eq!(res["access-control-max-age"], "3600");
+
+        // `*` is literal with credentials.
+        let any = AccessControlAllowHeaders::any();
+        let req = request("https://app.example.com", "POST", Some("x-anything"));
+        assert!(preflight_with_credentials(&req, METHODS, &any, example, None).is_none());
+
+        let req = request("https://app.example.com", "POST", None);
+        let res = preflight_with_credentials(&req, METHODS, &any, example, None).unwrap();
+        assert!(!res.contains_key("access-control-allow-headers"));
+        assert_eq!(res["access-control-allow-credentials"], "true");
+    }
+}
@@ -0,0 +1,155 @@
+//! Entry point for fuzzing the decoders, used by the targets in `fuzz/`.
+
+use std::slice;
+
+use headers_core::decode::from_values_with;
+
+use ::{DecodeLimits, Header, HeaderMap, HeaderMapExt, HeaderValue, ParseMode};
+use registry::HEADERS;
+
+/// Separates the values in the fuzzer's input. It's never valid inside a
+/// `HeaderValue`.
+const SENTINEL: u8 = b'\n';
+
+/// Decode `data` as the values of a header, with every registered decoder.
+///
+/// `data` is split on newlines into multiple values, skipping any that
+/// aren't valid header values. `name_hint` picks a single decoder to run,
+/// or all of them if it's out of range.
+///
+/// Each is decoded in both `ParseMode`s, within the default `DecodeLimits`
+/// and far larger ones. Anything decoded is encoded and decoded again,
+/// which must succeed. This should never panic, whatever the input.
+#[doc(hidden)]
+pub fn fuzz_decode_all(name_hint: u8, data: &[u8]) {
+    let values = data
+        .split(|&b| b == SENTINEL)
+        .filter_map(|value| HeaderValue::from_bytes(value).ok())
+        .collect::<Vec<_>>();
+
+    let entries = match HEADERS.get(name_hint as usize) {
+        Some(entry) => slice::from_ref(entry),
+        None => HEADERS,
+    };
+
+    for entry in entries {
+        (entry.fuzz)(&values);
+    }
+}
+
+pub(crate) fn decode<H: Header>(values: &[HeaderValue]) {
+    let large = DecodeLimits {
+        max_value_len: 1 << 20,
+        max_list_items: 1 << 20,
+    };
+    for &mode in &[ParseMode::Lenient, ParseMode::Strict] {
+        for &limits in &[DecodeLimits::default(), large] {
+            if let Ok(header) = from_values_with::<H>(values, mode, limits) {
+                let mut map = HeaderMap::new();
+                map.typed_insert(header);
+                let encoded = map.get_all(H::name()).iter().cloned().collect::<Vec<_>>();
+                // Encoding can grow a value a little, such as by quoting
+                // an entity tag, so more than `limits` may be needed.
+                assert!(
+                    from_values_with::<H>(&encoded, mode, large).is_ok(),
+                    "{} failed to decode its own encoding of {:?}",
+                    H::name(),
+                    values,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic;
+
+    use ::HeaderValue;
+    use registry::HEADERS;
+    use super::fuzz_decode_all;
+
+    #[test]
+    fn decodes_with_every_decoder() {
+        for hint in 0..=HEADERS.len() as u8 {
+            fuzz_decode_all(hint, b"");
+            fuzz_decode_all(hint, b"\n\n");
+            fuzz_decode_all(hint, b"bytes=0-1, 2-\n\"tag\", W/\"\"\nmax-age=60");
+            fuzz_decode_all(hint, b"\xff\xfe; q=0.5, ,=\r\n\t*");
+        }
+    }
+
+    /// Values that have tripped up parsers: truncated syntax, huge
+    /// numbers, bytes that aren't UTF-8 and values at the length limit.
+    fn garbage() -> Vec<HeaderValue> {
+        let mut values = [
+            "", " ", "\t", ",", ", ,", ";", ";;", "=", "==", "*", "-", "/", "\\",
+            "\"", "\"\"", "\"abc", "abc\"", "\"\\", "\"\\\"", "'", "''",
+            "W/", "W/\"", "w/\"\"", "W/W/\"x\"", "W/\"\\\"",
+            "%", "%%", "%z", "%zz", "%E2%82", "%FF%FE", "UTF-8''%", "UTF-8''%e2%82%ac%",
+            "utf-8'", "a*=", "filename*=UTF-8''%", "attachment; filename*=utf-8'en'%",
+            "0", "-0", "-1", "+1", "1e3", "0x10", "1.5", "1,2", "1, ,1",
+            "18446744073709551615", "18446744073709551616", "99999999999999999999999999999",
+            "max-age=18446744073709551616", "max-age=-1", "max-age=\"", "max-age=\"1",
+            "bytes=", "bytes=-", "bytes=--1", "bytes=1-0", "bytes=18446744073709551615-",
+            "bytes=0-18446744073709551616", "bytes=-18446744073709551616", "bytes */",
+            "bytes 1-2/", "bytes 2-1/3", "bytes 0-18446744073709551615/18446744073709551615",
+            "bytes */18446744073709551616",
+            "Basic", "Basic ", "Basic =", "Basic ====", "Basic !!!", "Bearer", "Bearer  ",
+            "Mon, 32 Jan 2018 25:61:61 GMT", "Sun, 06 Nov 1994 08:49:37", "99999-01-01",
+            "Thu, 01 Jan 99999 00:00:00 GMT",
+            "http://", "https://[", "http://[::1", "http://a:99999", "//", "#", "?",
+            "null", "file://", "a=b; Expires=", "a=b; Max-Age=18446744073709551616",
+            "a=b; Max-Age=-18446744073709551616", "=; =", "a=\"", "__Host-=",
+            "1; url=", "0;url=\"", "q=", ";q=", "a;q=1.0001", "a;q=-0", "a;q=NaN",
+            "text/", "/html", "text/html; charset=\"", "multipart/form-data; boundary=",
+            "1.1", "1.1 ", "HTTP/", "HTTP/1.1 a (", "a (", "a (\\", "(((((",
+            "?1", "?2", "\"a\";", "a;b=", "a=(", "a=(1", "@", ":", ":a:", ":=:",
+        ].iter().map(|s| HeaderValue::from_str(s).unwrap()).collect::<Vec<_>>();
+
+        // obs-text, which isn't UTF-8.
+        for &bytes in &[&b"\xff"[..], b"\x80\x80", b"W/\"\xff\"", b"\xe2\x82", b"a=\xff", b"\"\xc3"] {
+            values.push(HeaderValue::from_bytes(bytes).unwrap());
+        }
+        for &chunk in &[&b"a"[..], b"a,", b"\"", b"W/", b"%", b";", b"1", b"\xc3\xa9", b"a=b; "] {
+            let long = chunk.repeat((64 << 10) / chunk.len());
+            values.push(HeaderValue::from_bytes(&long).unwrap());
+        }
+        values
+    }
+
+    #[test]
+    fn garbage_never_panics() {
+        // Keep the output readable, the failures are collected below.
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(|_| ()));
+
+        let garbage = garbage();
+        let mut failures = Vec::new();
+        for entry in HEADERS {
+            for (i, value) in garbage.iter().enumerate() {
+                let mut cases = vec![vec![value.clone()]];
+                // And each after another, as separate field lines.
+                let next = &garbage[(i + 1) % garbage.len()];
+                if value.len() + next.len() < 1024 {
+                    cases.push(vec![value.clone(), next.clone()]);
+                }
+                for lines in &cases {
+                    if let Err(err) = panic::catch_unwind(|| (entry.fuzz)(lines)) {
+                        let msg = err.downcast_ref::<String>().cloned()
+                            .or_else(|| err.downcast_ref::<&str>().map(|s| s.to_string()))
+                            .unwrap_or_default();
+                        let shown = lines.iter()
+                            .map(|v| format!("{:?}", v).chars().take(40).collect::<String>())
+                            .collect::<Vec<_>>();
+                        let msg = msg.chars().take(80).collect::<String>();
+                        failures.push(format!("{}: {}: {}", entry.type_name, shown.join(" + "), msg));
+                    }
+                }
+            }
+        }
+
+        panic::set_hook(hook);
+        assert!(failures.is_empty(), "decoding panicked:\n{}", failures.join("\n"));
+    }
+}
@@ -5,20 +5,41 @@
 //! dox
 
 extern crate base64;
+#[cfg(feature = "caching")]
 #[macro_use]
 extern crate bitflags;
 extern crate bytes;
+#[cfg(feature = "chrono")]
+extern crate chrono;
+#[macro_use]
 extern crate headers_core;
 #[macro_use]
 extern crate headers_derive;
 extern crate http;
+#[cfg(feature = "md5")]
+extern crate md5;
 extern crate mime;
+#[cfg(feature = "websocket")]
 extern crate sha1;
 extern crate time;
+#[cfg(feature = "time03")]
+extern crate time03;
+#[cfg(feature = "tower")]
+extern crate tower_layer;
+#[cfg(feature = "tower")]
+extern crate tower_service;
 
 pub use headers_core::{
+    BorrowedHeader,
+    DecodeError,
+    DecodeLimits,
     Header,
     HeaderMapExt,
+    InvalidHeader,
+    MergeableHeader,
+    ParseMode,
+    RedactedHeaders,
+    SingleValueHeader,
     ToValues,
     Values,
 };
@@ -30,7 +51,106 @@ pub use http::header::{
     HeaderValue,
 };
 
+pub mod body;
+#[cfg(feature = "caching")]
+pub mod cache;
 mod common;
+#[cfg(feature = "conditional")]
+pub mod conditional;
+pub mod cookie;
+#[cfg(feature = "proxy")]
+pub mod copy;
+#[cfg(feature = "cors")]
+pub mod cors;
+mod fuzz;
+pub mod list;
+pub mod middleware;
+pub mod multipart;
+pub mod negotiate;
+pub mod opaque;
+#[cfg(feature = "proxy")]
+pub mod proxy;
+#[cfg(feature = "conditional")]
+pub mod range;
+pub mod raw;
+pub mod registry;
+#[cfg(feature = "security")]
+pub mod security;
+#[cfg(feature = "auth")]
+pub mod signature;
 mod util;
+pub mod validate;
 
 pub use self::common::*;
+#[doc(hidden)]
+pub use self::fuzz::fuzz_decode_all;
+// For `opaque_header!`, in crates that don't depend on headers-core.
+#[doc(hidden)]
+pub use headers_core::static_header_name;
+#[cfg(feature = "conditional")]
+pub use self::util::{EntityTag, InvalidEntityTag};
+pub use self::util::{
+    ContentCoding,
+    DateOutOfRange,
+    HeaderString,
+    HttpDate,
+    InvalidContentCoding,
+    InvalidHeaderString,
+    InvalidQuality,
+    InvalidRangeUnit,
+    Quality,
+    QualityValue,
+    RangeUnit,
+    Seconds,
+};
+
+/// The traits needed to use typed headers, for glob importing.
+///
+/// Unlike importing everything from the crate root, this doesn't bring in
+/// header types such as `Host` or `Range`, which are likely to clash with
+/// names of your own.
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::prelude::*;
+/// use headers::{ContentLength, HeaderMap};
+///
+/// let mut map = HeaderMap::new();
+/// map.typed_insert(ContentLength(3));
+/// assert_eq!(map.typed_get(), Some(ContentLength(3)));
+/// ```
+pub mod prelude {
+    #[cfg(feature = "auth")]
+    pub use Credentials;
+    pub use {BorrowedHeader, Header, HeaderMapExt, MergeableHeader, SingleValueHeader};
+}
+
+/// Headers for authentication, and HTTP message signatures.
+///
+/// See also [`signature::signature_base`](../signature/fn.signature_base.html).
+#[cfg(feature = "auth")]
+pub mod auth {
+    pub use common::{
+        Authorization,
+        Basic,
+        Bearer,
+        Credentials,
+        InvalidApiKey,
+        ProxyAuthorization,
+        Signature,
+        SignatureComponent,
+        SignatureInput,
+        SignatureParams,
+        XApiKey,
+    };
+}
+
+/// Headers that control how responses are cached.
+///
+/// See also the [`cache`](../cache/index.html) helpers, which combine them.
+#[cfg(feature = "caching")]
+pub mod caching {
+    pub use common::{Age, CacheControl, CacheStatus, CacheStatusEntry, Expires, Pragma, Vary, Warning};
+}
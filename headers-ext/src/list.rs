@@ -0,0 +1,506 @@
+//! Choosing how list headers are split into field lines when encoded.
+//!
+//! HTTP allows a list header to be sent as one line, `Vary: a, b`, or as
+//! several, `Vary: a` and `Vary: b`, and both mean the same. Some peers
+//! only handle one of them well, so wrapping a header in
+//! [`Compact`](struct.Compact.html) or [`MultiLine`](struct.MultiLine.html)
+//! picks the form, instead of the header's own.
+//!
+//! Headers that are repeated with independent values, such as
+//! `Set-Cookie`, can be handled as a list of the typed header instead with
+//! [`Repeated`](struct.Repeated.html).
+//!
+//! List headers with typed elements, such as `Allow`, iterate them as an
+//! [`Iter`](struct.Iter.html), or move them out as an
+//! [`IntoIter`](struct.IntoIter.html).
+//!
+//! # Example
+//!
+//! ```
+//! # extern crate headers_ext as headers;
+//! use headers::{HeaderMap, HeaderMapExt, Vary};
+//! use headers::list::MultiLine;
+//!
+//! let vary = "accept-encoding, accept-language".parse::<Vary>().unwrap();
+//!
+//! let mut map = HeaderMap::new();
+//! map.typed_insert(MultiLine(vary.clone()));
+//!
+//! let lines = map.get_all("vary").iter().collect::<Vec<_>>();
+//! assert_eq!(lines, ["accept-encoding", "accept-language"]);
+//!
+//! // Either form decodes the same.
+//! assert_eq!(map.typed_get(), Some(vary));
+//! ```
+
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::Deref;
+use std::slice;
+use std::vec;
+
+use headers_core::decode::from_value_with;
+use headers_core::encode::to_values;
+use util::{small_list, split_element_bytes};
+
+use {Header, HeaderName, HeaderValue, InvalidHeader, ParseMode, ToValues, Values};
+
+/// A header whose value is a comma-separated list, which can be split
+/// into several field lines or joined into one without changing its
+/// meaning.
+pub trait ListHeader: Header {}
+
+/// Encodes a list header as a single field line, with its elements
+/// joined by commas.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Compact<H>(pub H);
+
+/// Encodes a list header as a field line per element.
+///
+/// A header with no elements, such as an empty `Allow`, is still encoded
+/// as its one empty line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MultiLine<H>(pub H);
+
+impl<H: ListHeader> Header for Compact<H> {
+    fn name() -> &'static HeaderName {
+        H::name()
+    }
+
+    fn decode(values: &mut Values) -> Option<Self> {
+        H::decode(values).map(Compact)
+    }
+
+    fn encode(&self, values: &mut ToValues) {
+        let lines = to_values(&self.0);
+        if lines.len() > 1 {
+            let joined = lines
+                .iter()
+                .flat_map(|line| split_element_bytes(line.as_bytes()))
+                .collect::<Vec<_>>()
+                .join(&b", "[..]);
+            values.append(HeaderValue::from_bytes(&joined).expect("comma separated HeaderValues are valid"));
+        } else {
+            lines.into_iter().for_each(|line| values.append(line));
+        }
+    }
+
+    fn validate(&self) -> Result<(), InvalidHeader> {
+        self.0.validate()
+    }
+}
+
+impl<H: ListHeader> Header for MultiLine<H> {
+    fn name() -> &'static HeaderName {
+        H::name()
+    }
+
+    fn decode(values: &mut Values) -> Option<Self> {
+        H::decode(values).map(MultiLine)
+    }
+
+    fn encode(&self, values: &mut ToValues) {
+        let lines = to_values(&self.0);
+        let elements = lines
+            .iter()
+            .flat_map(|line| split_element_bytes(line.as_bytes()))
+            .map(|element| HeaderValue::from_bytes(element).expect("an element of a HeaderValue is a valid HeaderValue"))
+            .collect::<Vec<_>>();
+        if elements.is_empty() {
+            lines.into_iter().for_each(|line| values.append(line));
+        } else {
+            elements.into_iter().for_each(|element| values.append(element));
+        }
+    }
+
+    fn validate(&self) -> Result<(), InvalidHeader> {
+        self.0.validate()
+    }
+}
+
+/// A header repeated with independent values, decoded as one `H` per
+/// field line.
+///
+/// Each element is encoded as its own field lines, in order, and decoding
+/// keeps the order of the lines. What happens to a line that fails to
+/// decode depends on the `ParseMode`: it fails the whole header when
+/// strict, and is skipped when lenient, which is the default. Either way,
+/// a header with no line that decodes doesn't decode, as if it were
+/// missing.
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::{ETag, HeaderMap, HeaderMapExt, HeaderValue, ParseMode};
+/// use headers::list::Repeated;
+///
+/// let mut map = HeaderMap::new();
+/// map.append("etag", HeaderValue::from_static("\"a\""));
+/// map.append("etag", HeaderValue::from_static("nope\""));
+/// map.append("etag", HeaderValue::from_static("W/\"b\""));
+///
+/// let etags = map.typed_get::<Repeated<ETag>>().unwrap();
+/// assert_eq!(etags.len(), 2);
+/// assert!(etags[1].entity_tag().is_weak());
+///
+/// assert_eq!(map.typed_get_with::<Repeated<ETag>>(ParseMode::Strict), None);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Repeated<H>(pub Vec<H>);
+
+impl<H: Header> Header for Repeated<H> {
+    fn name() -> &'static HeaderName {
+        H::name()
+    }
+
+    fn decode(values: &mut Values) -> Option<Self> {
+        let mode = values.mode();
+        let max = values.limits().max_list_items;
+        let mut list = Vec::new();
+        for (i, value) in values.by_ref().enumerate() {
+            if i == max {
+                values.set_too_large();
+                return None;
+            }
+            match from_value_with::<H>(value, mode) {
+                Ok(header) => list.push(header),
+                Err(_) if mode == ParseMode::Lenient => (),
+                Err(_) => return None,
+            }
+        }
+        if list.is_empty() {
+            None
+        } else {
+            Some(Repeated(list))
+        }
+    }
+
+    fn encode(&self, values: &mut ToValues) {
+        for header in &self.0 {
+            to_values(header).into_iter().for_each(|line| values.append(line));
+        }
+    }
+
+    fn validate(&self) -> Result<(), InvalidHeader> {
+        self.0.iter().try_for_each(Header::validate)
+    }
+}
+
+impl<H> Deref for Repeated<H> {
+    type Target = [H];
+
+    fn deref(&self) -> &[H] {
+        &self.0
+    }
+}
+
+impl<H> From<Vec<H>> for Repeated<H> {
+    fn from(list: Vec<H>) -> Repeated<H> {
+        Repeated(list)
+    }
+}
+
+impl<H> FromIterator<H> for Repeated<H> {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = H>,
+    {
+        Repeated(iter.into_iter().collect())
+    }
+}
+
+impl<H> IntoIterator for Repeated<H> {
+    type Item = H;
+    type IntoIter = vec::IntoIter<H>;
+
+    fn into_iter(self) -> vec::IntoIter<H> {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, H> IntoIterator for &'a Repeated<H> {
+    type Item = &'a H;
+    type IntoIter = slice::Iter<'a, H>;
+
+    fn into_iter(self) -> slice::Iter<'a, H> {
+        self.0.iter()
+    }
+}
+
+/// An iterator over the elements of a list header, such as `Allow`.
+pub struct Iter<'a, T: 'a>(small_list::Iter<'a, T>);
+
+/// An iterator that moves the elements out of a list header.
+pub struct IntoIter<T>(small_list::IntoIter<T>);
+
+impl<'a, T> Iter<'a, T> {
+    pub(crate) fn new<I: Into<small_list::Iter<'a, T>>>(iter: I) -> Iter<'a, T> {
+        Iter(iter.into())
+    }
+
+    /// No elements, such as for a `*` wildcard.
+    #[cfg(feature = "conditional")]
+    pub(crate) fn empty() -> Iter<'a, T> {
+        Iter::new([].iter())
+    }
+}
+
+impl<T> IntoIter<T> {
+    pub(crate) fn new<I: Into<small_list::IntoIter<T>>>(iter: I) -> IntoIter<T> {
+        IntoIter(iter.into())
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<&'a T> {
+        self.0.nth(n)
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        self.0.next_back()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> Clone for Iter<'a, T> {
+    fn clone(&self) -> Iter<'a, T> {
+        Iter(self.0.clone())
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for Iter<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Iter").field(&self.0.clone().collect::<Vec<_>>()).finish()
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.0.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> fmt::Debug for IntoIter<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("IntoIter").field("remaining", &self.len()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Compact, ListHeader, MultiLine, Repeated};
+    use {AccessControlAllowHeaders, Allow, ETag, Header, HeaderMap, HeaderMapExt, HeaderValue, ParseMode, SetCookie, Vary, Via};
+
+    fn lines<H: Header>(header: H) -> Vec<String> {
+        let mut map = HeaderMap::new();
+        map.typed_insert(header);
+        map.get_all(H::name())
+            .iter()
+            .map(|line| line.to_str().unwrap().to_owned())
+            .collect()
+    }
+
+    fn decode<H: Header>(lines: &[&'static str]) -> H {
+        let mut map = HeaderMap::new();
+        for &line in lines {
+            map.append(H::name(), HeaderValue::from_static(line));
+        }
+        map.typed_get().unwrap()
+    }
+
+    // `multi` and `compact` are the same list, in each form.
+    fn check<H: ListHeader + Clone>(multi: &'static [&'static str], compact: &'static str) {
+        for header in &[decode::<H>(multi), decode::<H>(&[compact])] {
+            assert_eq!(lines(Compact(header.clone())), [compact]);
+            assert_eq!(lines(MultiLine(header.clone())), multi);
+        }
+        // and the wrappers decode like the header
+        assert_eq!(lines(decode::<MultiLine<H>>(&[compact]).0), [compact]);
+    }
+
+    #[test]
+    fn vary() {
+        check::<Vary>(&["accept-encoding", "accept-language"], "accept-encoding, accept-language");
+        check::<Vary>(&["*"], "*");
+    }
+
+    #[test]
+    fn access_control_allow_headers() {
+        check::<AccessControlAllowHeaders>(&["content-type", "x-custom"], "content-type, x-custom");
+        check::<AccessControlAllowHeaders>(&["*"], "*");
+    }
+
+    #[test]
+    fn via() {
+        check::<Via>(
+            &["1.0 fred", "1.1 p.example.net (squid, v4)"],
+            "1.0 fred, 1.1 p.example.net (squid, v4)",
+        );
+    }
+
+    #[test]
+    fn list_headers() {
+        use {
+            AccessControlAllowMethods,
+            AccessControlExposeHeaders,
+            AccessControlRequestHeaders,
+            CacheControl,
+            Connection,
+            ContentEncoding,
+            IfMatch,
+            IfNoneMatch,
+            Te,
+            TransferEncoding,
+        };
+
+        check::<AccessControlAllowMethods>(&["GET", "POST"], "GET, POST");
+        check::<AccessControlExposeHeaders>(&["etag", "x-custom"], "etag, x-custom");
+        check::<AccessControlRequestHeaders>(&["etag", "x-custom"], "etag, x-custom");
+        check::<Allow>(&["GET", "POST"], "GET, POST");
+        check::<CacheControl>(&["no-cache", "max-age=0"], "no-cache, max-age=0");
+        check::<Connection>(&["close", "x-trace"], "close, x-trace");
+        check::<ContentEncoding>(&["gzip", "br"], "gzip, br");
+        check::<IfMatch>(&["\"a\"", "W/\"b,c\""], "\"a\", W/\"b,c\"");
+        check::<IfNoneMatch>(&["\"a\"", "\"b\""], "\"a\", \"b\"");
+        check::<Te>(&["trailers", "gzip;q=0.5"], "trailers, gzip;q=0.5");
+        check::<TransferEncoding>(&["gzip", "chunked"], "gzip, chunked");
+    }
+
+    // The lines of a list, kept as they are, however they're encoded.
+    #[derive(Clone)]
+    struct RawList(Vec<HeaderValue>);
+
+    impl Header for RawList {
+        fn name() -> &'static ::HeaderName {
+            &::http::header::VIA
+        }
+
+        fn decode(values: &mut ::Values) -> Option<Self> {
+            Some(RawList(values.cloned().collect()))
+        }
+
+        fn encode(&self, values: &mut ::ToValues) {
+            self.0.iter().cloned().for_each(|line| values.append(line));
+        }
+    }
+
+    impl ListHeader for RawList {}
+
+    #[test]
+    fn obs_text_lines_are_kept() {
+        let list = RawList(vec![
+            HeaderValue::from_bytes(b"1.0 caf\xe9, 1.1 p").unwrap(),
+            HeaderValue::from_static("1.1 q"),
+        ]);
+        let raw_lines = |map: &HeaderMap| {
+            map.get_all("via").iter().map(|line| line.as_bytes().to_vec()).collect::<Vec<_>>()
+        };
+
+        let mut map = HeaderMap::new();
+        map.typed_insert(Compact(list.clone()));
+        assert_eq!(raw_lines(&map), [&b"1.0 caf\xe9, 1.1 p, 1.1 q"[..]]);
+
+        let mut map = HeaderMap::new();
+        map.typed_insert(MultiLine(list));
+        assert_eq!(raw_lines(&map), [&b"1.0 caf\xe9"[..], b"1.1 p", b"1.1 q"]);
+    }
+
+    #[test]
+    fn default_style_is_unchanged() {
+        let vary = decode::<Vary>(&["accept-encoding", "accept-language"]);
+        assert_eq!(lines(vary), ["accept-encoding, accept-language"]);
+    }
+
+    #[test]
+    fn multi_line_empty_list() {
+        let allow = decode::<Allow>(&[""]);
+        assert_eq!(lines(MultiLine(allow)), [""]);
+    }
+
+    #[test]
+    fn repeated_encode_keeps_order() {
+        let cookies = ["b=2", "a=1", "c=3"]
+            .iter()
+            .map(|&c| decode::<SetCookie>(&[c]))
+            .collect::<Repeated<_>>();
+        assert_eq!(lines(cookies), ["b=2", "a=1", "c=3"]);
+
+        // An element encoding to several lines keeps them together.
+        let cookies = Repeated(vec![decode::<SetCookie>(&["b=2", "a=1"]), decode::<SetCookie>(&["c=3"])]);
+        assert_eq!(lines(cookies), ["b=2", "a=1", "c=3"]);
+    }
+
+    #[test]
+    fn repeated_decode_keeps_order() {
+        let etags = decode::<Repeated<ETag>>(&["\"b\"", "\"a\"", "W/\"c\""]);
+        let tags = etags.iter().map(|etag| etag.entity_tag().tag()).collect::<Vec<_>>();
+        assert_eq!(tags, [&b"b"[..], b"a", b"c"]);
+
+        let cookies = decode::<Repeated<SetCookie>>(&["b=2", "a=1"]);
+        assert_eq!(cookies.len(), 2);
+        assert_eq!(lines(cookies), ["b=2", "a=1"]);
+    }
+
+    #[test]
+    fn repeated_invalid_lines() {
+        let mut map = HeaderMap::new();
+        for &line in &["\"a\"", "nope\"", "\"b\""] {
+            map.append("etag", HeaderValue::from_static(line));
+        }
+
+        let lenient = map.typed_get::<Repeated<ETag>>().unwrap();
+        assert_eq!(lines(lenient), ["\"a\"", "\"b\""]);
+        assert_eq!(map.typed_get_with::<Repeated<ETag>>(ParseMode::Strict), None);
+
+        let mut map = HeaderMap::new();
+        map.insert("etag", HeaderValue::from_static("nope\""));
+        assert_eq!(map.typed_get::<Repeated<ETag>>(), None);
+        assert_eq!(map.typed_get_with::<Repeated<ETag>>(ParseMode::Strict), None);
+    }
+
+    #[test]
+    fn validate_forwards() {
+        use Warning;
+
+        let mut map = HeaderMap::new();
+        let warnings = Repeated(vec![Warning::stale("-"), Warning::misc_persistent("-", "a\r\nb")]);
+        assert!(map.typed_try_insert(warnings).is_err());
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn repeated_into_iter() {
+        let etags = decode::<Repeated<ETag>>(&["\"a\"", "\"b\""]);
+        assert_eq!((&etags).into_iter().count(), 2);
+        let owned = etags.into_iter().collect::<Vec<ETag>>();
+        assert_eq!(owned[1], "\"b\"".parse::<ETag>().unwrap());
+    }
+}
@@ -0,0 +1,356 @@
+//! Setting a typed header on every response, as middleware.
+//!
+//! [`set_typed_header`](fn.set_typed_header.html) inserts a header into a
+//! `HeaderMap` in one of three [`InsertMode`](enum.InsertMode.html)s. With
+//! the `tower` feature, [`SetTypedHeader`](struct.SetTypedHeader.html) is
+//! a `tower` layer doing it to the responses of the service it wraps.
+
+#[cfg(feature = "tower")]
+use std::future::Future;
+#[cfg(feature = "tower")]
+use std::pin::Pin;
+#[cfg(feature = "tower")]
+use std::task::{Context, Poll};
+
+use headers_core::encode;
+#[cfg(feature = "tower")]
+use http::Response;
+#[cfg(feature = "tower")]
+use tower_layer::Layer;
+#[cfg(feature = "tower")]
+use tower_service::Service;
+
+use {Header, HeaderMap};
+
+/// How `set_typed_header` treats the values a map already has for the
+/// header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum InsertMode {
+    /// Replace them, as `HeaderMapExt::typed_insert` does.
+    ///
+    /// For headers that only have one value, such as `ContentType`.
+    Overwrite,
+    /// Keep them, and add the field lines of the header that aren't
+    /// already there.
+    ///
+    /// For list headers, such as `Vary`, whose field lines make one list.
+    /// Header that only have one value end up with several.
+    Append,
+    /// Keep them, and only insert the header if the map has none.
+    ///
+    /// For defaults that the response can override.
+    IfAbsent,
+}
+
+/// Insert `header` into `map`, treating the values it already has for it
+/// as `mode` says.
+///
+/// # Panics
+///
+/// Panics if the header isn't valid, as `HeaderMapExt::typed_insert` does.
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// # extern crate http;
+/// use http::header::{ACCEPT_ENCODING, COOKIE};
+/// use headers::{ContentType, HeaderMap, HeaderMapExt, Vary};
+/// use headers::middleware::{set_typed_header, InsertMode};
+///
+/// let mut map = HeaderMap::new();
+/// map.typed_insert(ContentType::text());
+/// map.typed_insert(vec![COOKIE].into_iter().collect::<Vary>());
+///
+/// set_typed_header(&mut map, &ContentType::json(), InsertMode::IfAbsent);
+/// set_typed_header(&mut map, &vec![ACCEPT_ENCODING].into_iter().collect::<Vary>(), InsertMode::Append);
+///
+/// assert_eq!(map["content-type"], "text/plain");
+/// assert_eq!(map.get_all("vary").iter().collect::<Vec<_>>(), ["cookie", "accept-encoding"]);
+/// ```
+pub fn set_typed_header<H: Header>(map: &mut HeaderMap, header: &H, mode: InsertMode) {
+    if let Err(err) = header.validate() {
+        panic!("{} for {:?}", err, H::name());
+    }
+    let name = H::name();
+    match mode {
+        InsertMode::Overwrite => {
+            map.remove(name);
+        },
+        InsertMode::Append => (),
+        InsertMode::IfAbsent => if map.contains_key(name) {
+            return;
+        },
+    }
+    for value in encode::to_values(header) {
+        if mode == InsertMode::Append && map.get_all(name).iter().any(|existing| *existing == value) {
+            continue;
+        }
+        map.append(name, value);
+    }
+}
+
+/// A `tower` layer setting a typed header on every response, in an
+/// `InsertMode`.
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// # extern crate tower_layer;
+/// use headers::{CacheControl, XContentTypeOptions};
+/// use headers::middleware::SetTypedHeader;
+/// use tower_layer::Stack;
+///
+/// let layers = Stack::new(
+///     SetTypedHeader::overwriting(XContentTypeOptions::nosniff()),
+///     SetTypedHeader::if_absent(CacheControl::new().with_no_store()),
+/// );
+/// ```
+#[cfg(feature = "tower")]
+#[derive(Clone, Debug)]
+pub struct SetTypedHeader<H> {
+    header: H,
+    mode: InsertMode,
+}
+
+#[cfg(feature = "tower")]
+impl<H: Header> SetTypedHeader<H> {
+    /// Set `header` in `mode`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the header isn't valid, rather than on the first
+    /// response.
+    pub fn new(header: H, mode: InsertMode) -> SetTypedHeader<H> {
+        if let Err(err) = header.validate() {
+            panic!("{} for {:?}", err, H::name());
+        }
+        SetTypedHeader {
+            header,
+            mode,
+        }
+    }
+
+    /// Set `header`, replacing the values of the response.
+    pub fn overwriting(header: H) -> SetTypedHeader<H> {
+        SetTypedHeader::new(header, InsertMode::Overwrite)
+    }
+
+    /// Set `header`, adding its field lines to the values of the response.
+    pub fn appending(header: H) -> SetTypedHeader<H> {
+        SetTypedHeader::new(header, InsertMode::Append)
+    }
+
+    /// Set `header`, unless the response already has it.
+    pub fn if_absent(header: H) -> SetTypedHeader<H> {
+        SetTypedHeader::new(header, InsertMode::IfAbsent)
+    }
+}
+
+#[cfg(feature = "tower")]
+impl<S, H: Clone> Layer<S> for SetTypedHeader<H> {
+    type Service = SetTypedHeaderService<S, H>;
+
+    fn layer(&self, inner: S) -> SetTypedHeaderService<S, H> {
+        SetTypedHeaderService {
+            inner,
+            header: self.header.clone(),
+            mode: self.mode,
+        }
+    }
+}
+
+/// The service of a `SetTypedHeader` layer.
+#[cfg(feature = "tower")]
+#[derive(Clone, Debug)]
+pub struct SetTypedHeaderService<S, H> {
+    inner: S,
+    header: H,
+    mode: InsertMode,
+}
+
+#[cfg(feature = "tower")]
+impl<S, H, Req, B> Service<Req> for SetTypedHeaderService<S, H>
+where
+    S: Service<Req, Response = Response<B>>,
+    H: Header + Clone,
+{
+    type Response = Response<B>;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future, H>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), S::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Req) -> ResponseFuture<S::Future, H> {
+        ResponseFuture {
+            future: self.inner.call(req),
+            header: self.header.clone(),
+            mode: self.mode,
+        }
+    }
+}
+
+/// The response future of a `SetTypedHeaderService`.
+#[cfg(feature = "tower")]
+#[derive(Debug)]
+pub struct ResponseFuture<F, H> {
+    future: F,
+    header: H,
+    mode: InsertMode,
+}
+
+#[cfg(feature = "tower")]
+impl<F, H, B, E> Future for ResponseFuture<F, H>
+where
+    F: Future<Output = Result<Response<B>, E>>,
+    H: Header,
+{
+    type Output = Result<Response<B>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Only `future` is pinned, and it's never moved out.
+        let this = unsafe { self.get_unchecked_mut() };
+        let future = unsafe { Pin::new_unchecked(&mut this.future) };
+        match future.poll(cx) {
+            Poll::Ready(Ok(mut res)) => {
+                set_typed_header(res.headers_mut(), &this.header, this.mode);
+                Poll::Ready(Ok(res))
+            },
+            other => other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::header::{ACCEPT_ENCODING, COOKIE, ORIGIN};
+
+    use super::{set_typed_header, InsertMode};
+    use {ContentType, HeaderMap, HeaderMapExt, HeaderName, Vary};
+
+    fn vary(names: Vec<HeaderName>) -> Vary {
+        names.into_iter().collect()
+    }
+
+    fn lines<'a>(map: &'a HeaderMap, name: &str) -> Vec<&'a str> {
+        map.get_all(name).iter().map(|v| v.to_str().unwrap()).collect()
+    }
+
+    // `Cookie` on one line, `Origin` on another.
+    fn varying() -> HeaderMap {
+        let mut map = HeaderMap::new();
+        map.append("vary", "cookie".parse().unwrap());
+        map.append("vary", "origin".parse().unwrap());
+        map
+    }
+
+    #[test]
+    fn overwrite() {
+        let mut map = varying();
+        set_typed_header(&mut map, &vary(vec![ACCEPT_ENCODING]), InsertMode::Overwrite);
+        assert_eq!(lines(&map, "vary"), ["accept-encoding"]);
+
+        map.typed_insert(ContentType::text());
+        set_typed_header(&mut map, &ContentType::json(), InsertMode::Overwrite);
+        assert_eq!(lines(&map, "content-type"), ["application/json"]);
+        assert_eq!(map.typed_get(), Some(ContentType::json()));
+    }
+
+    #[test]
+    fn append() {
+        let mut map = varying();
+        set_typed_header(&mut map, &vary(vec![ACCEPT_ENCODING]), InsertMode::Append);
+        assert_eq!(lines(&map, "vary"), ["cookie", "origin", "accept-encoding"]);
+        let merged = map.typed_get::<Vary>().unwrap();
+        assert_eq!(merged.iter_strs().collect::<Vec<_>>(), ["cookie", "origin", "accept-encoding"]);
+
+        // Setting it again, by every response of a service, adds nothing.
+        set_typed_header(&mut map, &vary(vec![ACCEPT_ENCODING]), InsertMode::Append);
+        assert_eq!(lines(&map, "vary"), ["cookie", "origin", "accept-encoding"]);
+
+        // A header with one value ends up with two, failing to decode.
+        map.typed_insert(ContentType::text());
+        set_typed_header(&mut map, &ContentType::text(), InsertMode::Append);
+        assert_eq!(lines(&map, "content-type"), ["text/plain"]);
+        set_typed_header(&mut map, &ContentType::json(), InsertMode::Append);
+        assert_eq!(lines(&map, "content-type"), ["text/plain", "application/json"]);
+        assert_eq!(map.typed_get::<ContentType>(), None);
+    }
+
+    #[test]
+    fn if_absent() {
+        let mut map = varying();
+        set_typed_header(&mut map, &vary(vec![ACCEPT_ENCODING]), InsertMode::IfAbsent);
+        assert_eq!(lines(&map, "vary"), ["cookie", "origin"]);
+
+        set_typed_header(&mut map, &ContentType::json(), InsertMode::IfAbsent);
+        assert_eq!(map.typed_get(), Some(ContentType::json()));
+        set_typed_header(&mut map, &ContentType::text(), InsertMode::IfAbsent);
+        assert_eq!(map.typed_get(), Some(ContentType::json()));
+    }
+
+    #[test]
+    fn into_empty_map() {
+        for &mode in &[InsertMode::Overwrite, InsertMode::Append, InsertMode::IfAbsent] {
+            let mut map = HeaderMap::new();
+            set_typed_header(&mut map, &vary(vec![COOKIE, ORIGIN]), mode);
+            assert_eq!(lines(&map, "vary"), ["cookie, origin"], "{:?}", mode);
+        }
+    }
+
+    #[cfg(feature = "tower")]
+    #[test]
+    fn layer() {
+        use std::convert::Infallible;
+        use std::future::{self, Future, Ready};
+        use std::pin::Pin;
+        use std::task::{Context, Poll, Waker};
+
+        use http::Response;
+        use tower_layer::Layer;
+        use tower_service::Service;
+
+        use super::SetTypedHeader;
+
+        // Responds with `text/plain`, and a `Vary: cookie`.
+        struct App;
+
+        impl Service<()> for App {
+            type Response = Response<()>;
+            type Error = Infallible;
+            type Future = Ready<Result<Response<()>, Infallible>>;
+
+            fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+                Poll::Ready(Ok(()))
+            }
+
+            fn call(&mut self, _: ()) -> Self::Future {
+                let mut res = Response::new(());
+                res.headers_mut().typed_insert(ContentType::text());
+                res.headers_mut().typed_insert(vary(vec![COOKIE]));
+                future::ready(Ok(res))
+            }
+        }
+
+        fn respond<S: Service<(), Response = Response<()>, Error = Infallible>>(mut service: S) -> HeaderMap {
+            let mut cx = Context::from_waker(Waker::noop());
+            assert!(service.poll_ready(&mut cx).is_ready());
+            let mut future = Box::pin(service.call(()));
+            match Pin::as_mut(&mut future).poll(&mut cx) {
+                Poll::Ready(Ok(res)) => res.into_parts().0.headers,
+                _ => panic!("App is always ready"),
+            }
+        }
+
+        let headers = respond(SetTypedHeader::overwriting(ContentType::json()).layer(App));
+        assert_eq!(headers.typed_get(), Some(ContentType::json()));
+        let headers = respond(SetTypedHeader::if_absent(ContentType::json()).layer(App));
+        assert_eq!(headers.typed_get(), Some(ContentType::text()));
+
+        let headers = respond(SetTypedHeader::appending(vary(vec![ACCEPT_ENCODING])).layer(App));
+        assert_eq!(lines(&headers, "vary"), ["cookie", "accept-encoding"]);
+    }
+}
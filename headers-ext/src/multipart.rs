@@ -0,0 +1,50 @@
+//! Using typed headers in the parts of a `multipart` body.
+//!
+//! Each part of a `multipart/form-data` body, as defined in
+//! [RFC7578](https://tools.ietf.org/html/rfc7578), has its own block of
+//! headers, which a multipart parser can hand over as a `HeaderMap`. The
+//! same typed headers decode them:
+//!
+//! - [`ContentDisposition`](../struct.ContentDisposition.html) has the
+//!   `form-data` type, the `name` of the field and, for a file, its
+//!   `filename`.
+//! - [`ContentType`](../struct.ContentType.html) is the media type of the
+//!   part, `text/plain` when it's missing.
+//! - [`ContentTransferEncoding`](../enum.ContentTransferEncoding.html) is
+//!   deprecated for HTTP, and `7bit` when missing, but older clients still
+//!   send it.
+//!
+//! # Example
+//!
+//! ```
+//! # extern crate headers_ext as headers;
+//! extern crate mime;
+//!
+//! use headers::{ContentDisposition, ContentTransferEncoding, ContentType, HeaderMap, HeaderMapExt};
+//! use mime::Mime;
+//!
+//! # fn main() {
+//! // The `Content-Type` of the request has the boundary between parts.
+//! let mut req = HeaderMap::new();
+//! req.insert("content-type", "multipart/form-data; boundary=XyZ".parse().unwrap());
+//! let mime = Mime::from(req.typed_get::<ContentType>().unwrap());
+//! assert_eq!(mime.get_param(mime::BOUNDARY).unwrap(), "XyZ");
+//!
+//! // The headers of one part, from a multipart parser.
+//! let mut part = HeaderMap::new();
+//! part.insert("content-disposition", "form-data; name=\"avatar\"; filename=\"me.png\"".parse().unwrap());
+//! part.insert("content-type", "image/png".parse().unwrap());
+//! part.insert("content-transfer-encoding", "binary".parse().unwrap());
+//!
+//! let disposition = part.typed_get::<ContentDisposition>().unwrap();
+//! assert!(disposition.is_form_data());
+//! assert_eq!(disposition.name().unwrap(), "avatar");
+//! assert_eq!(disposition.filename().unwrap(), "me.png");
+//!
+//! let content_type = part.typed_get::<ContentType>().unwrap_or_else(ContentType::text);
+//! assert_eq!(Mime::from(content_type), mime::IMAGE_PNG);
+//!
+//! let cte = part.typed_get::<ContentTransferEncoding>().unwrap_or_default();
+//! assert!(cte.is_identity());
+//! # }
+//! ```
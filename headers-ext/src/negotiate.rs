@@ -0,0 +1,371 @@
+//! Content negotiation, picking what to send from the `Accept` family of
+//! request headers.
+//!
+//! Each function takes the request's headers and what the server can
+//! produce, in its order of preference. A missing header means anything is
+//! acceptable, as [RFC7231 section 5.3](https://tools.ietf.org/html/rfc7231#section-5.3)
+//! defines, and so does a malformed one: a client sending garbage
+//! shouldn't be refused everything. When several choices are equally
+//! acceptable, the server's order of preference decides.
+
+use http::header::{ACCEPT, ACCEPT_ENCODING, ACCEPT_LANGUAGE};
+use mime::Mime;
+
+use util::FlatCsv;
+use {ContentCoding, HeaderMap, HeaderName, Quality, QualityValue};
+
+/// Pick the media type to respond with, following the `Accept` header.
+///
+/// Each of `supported` gets the quality of the most specific media range
+/// matching it, so `text/html;level=1` takes precedence over `text/html`,
+/// which takes precedence over `text/*` and `*/*`.
+///
+/// Returns `None` if none of `supported` is acceptable, which is when a
+/// server would usually respond with `406 Not Acceptable`.
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// extern crate http;
+/// extern crate mime;
+///
+/// use http::header::{ACCEPT, HeaderMap, HeaderValue};
+///
+/// # fn main() {
+/// let mut map = HeaderMap::new();
+/// map.insert(ACCEPT, HeaderValue::from_static("text/*;q=0.5, application/json"));
+///
+/// let supported = [mime::TEXT_HTML, mime::APPLICATION_JSON];
+/// assert_eq!(headers::negotiate::content_type(&map, &supported), Some(mime::APPLICATION_JSON));
+/// # }
+/// ```
+pub fn content_type(headers: &HeaderMap, supported: &[Mime]) -> Option<Mime> {
+    let ranges = match parse::<Mime>(headers, ACCEPT) {
+        Some(ranges) => ranges,
+        None => return supported.first().cloned(),
+    };
+
+    best(supported, |mime| {
+        ranges
+            .iter()
+            .filter_map(|range| {
+                media_range_specificity(range.value(), mime).map(|spec| (spec, range.quality()))
+            })
+            .max_by_key(|&(spec, _)| spec)
+            .map(|(_, quality)| quality)
+    })
+    .cloned()
+}
+
+/// Pick the content coding to respond with, following the `Accept-Encoding`
+/// header.
+///
+/// Codings are compared as [`ContentCoding`](../enum.ContentCoding.html)s,
+/// so case is ignored and `x-gzip` and `x-compress` are `gzip` and
+/// `compress`. Falls back to `identity` if none of `supported` is
+/// acceptable, or if the header is missing, since not every client that
+/// leaves it out can decode anything else.
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// extern crate http;
+///
+/// use http::header::{ACCEPT_ENCODING, HeaderMap, HeaderValue};
+/// use headers::ContentCoding;
+///
+/// # fn main() {
+/// let mut map = HeaderMap::new();
+/// map.insert(ACCEPT_ENCODING, HeaderValue::from_static("x-gzip;q=0.8, br"));
+///
+/// let supported = [ContentCoding::Gzip, ContentCoding::Br];
+/// assert_eq!(headers::negotiate::encoding(&map, &supported), ContentCoding::Br);
+/// assert_eq!(headers::negotiate::encoding(&map, &supported[..1]), ContentCoding::Gzip);
+/// assert_eq!(headers::negotiate::encoding(&HeaderMap::new(), &supported), ContentCoding::Identity);
+/// # }
+/// ```
+pub fn encoding(headers: &HeaderMap, supported: &[ContentCoding]) -> ContentCoding {
+    let codings = match parse::<ContentCoding>(headers, ACCEPT_ENCODING) {
+        Some(codings) => codings,
+        None => return ContentCoding::Identity,
+    };
+
+    best(supported, |coding| {
+        codings
+            .iter()
+            .find(|c| c.value() == coding)
+            .or_else(|| codings.iter().find(|c| c.value().as_str() == "*"))
+            .map(QualityValue::quality)
+            .or_else(|| {
+                // identity is acceptable unless it's excluded explicitly.
+                if *coding == ContentCoding::Identity {
+                    Some(Quality::default())
+                } else {
+                    None
+                }
+            })
+    })
+    .cloned()
+    .unwrap_or(ContentCoding::Identity)
+}
+
+/// Pick the language to respond with, following the `Accept-Language`
+/// header.
+///
+/// Ranges match with "basic filtering" from
+/// [RFC4647 section 3.3.1](https://tools.ietf.org/html/rfc4647#section-3.3.1):
+/// `en` matches `en` and `en-GB` but not `eng`, and `*` matches any tag.
+/// Each of `supported` gets the quality of the longest range matching it.
+///
+/// Returns `None` if none of `supported` is acceptable.
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// extern crate http;
+///
+/// use http::header::{ACCEPT_LANGUAGE, HeaderMap, HeaderValue};
+///
+/// # fn main() {
+/// let mut map = HeaderMap::new();
+/// map.insert(ACCEPT_LANGUAGE, HeaderValue::from_static("da, en;q=0.7"));
+///
+/// assert_eq!(headers::negotiate::language(&map, &["en-GB", "fr"]), Some("en-GB"));
+/// # }
+/// ```
+pub fn language<'a>(headers: &HeaderMap, supported: &[&'a str]) -> Option<&'a str> {
+    let ranges = match parse::<String>(headers, ACCEPT_LANGUAGE) {
+        Some(ranges) if ranges.iter().all(|r| is_language_range(r.value())) => ranges,
+        _ => return supported.first().cloned(),
+    };
+
+    best(supported, |tag| {
+        ranges
+            .iter()
+            .filter(|range| language_range_matches(range.value(), tag))
+            .max_by_key(|range| range.value().len())
+            .map(QualityValue::quality)
+    })
+    .cloned()
+}
+
+/// Parse every value of `name` as a list of quality values.
+///
+/// Returns `None` if the header is missing or any part is malformed.
+fn parse<T: ::std::str::FromStr>(headers: &HeaderMap, name: HeaderName) -> Option<Vec<QualityValue<T>>> {
+    let values = headers.get_all(name);
+    let mut values = values.iter().peekable();
+    values.peek()?;
+
+    let mut items = Vec::new();
+    for value in values {
+        value.to_str().ok()?;
+        let csv: FlatCsv = value.clone().into();
+        for item in csv.iter() {
+            items.push(item.parse().ok()?);
+        }
+    }
+    Some(items)
+}
+
+/// Find the first of `supported` with the highest quality above zero.
+fn best<T, F>(supported: &[T], quality: F) -> Option<&T>
+where
+    F: Fn(&T) -> Option<Quality>,
+{
+    let mut best: Option<(&T, Quality)> = None;
+    for candidate in supported {
+        let q = match quality(candidate) {
+            Some(q) if q.as_u16() > 0 => q,
+            _ => continue,
+        };
+        if best.is_none_or(|(_, best_q)| q > best_q) {
+            best = Some((candidate, q));
+        }
+    }
+    best.map(|(candidate, _)| candidate)
+}
+
+/// How specifically `range` matches `mime`, or `None` if it doesn't.
+fn media_range_specificity(range: &Mime, mime: &Mime) -> Option<usize> {
+    if range.type_() == mime::STAR {
+        return Some(0);
+    }
+    if range.type_() != mime.type_() {
+        return None;
+    }
+    if range.subtype() == mime::STAR {
+        return Some(1);
+    }
+    if range.subtype() != mime.subtype() {
+        return None;
+    }
+
+    let mut params = 0;
+    for (name, value) in range.params() {
+        match mime.get_param(name) {
+            Some(ref v) if *v == value => params += 1,
+            _ => return None,
+        }
+    }
+    Some(2 + params)
+}
+
+/// `language-range` from RFC4647: `*` or subtags of 1 to 8 alphanumerics.
+fn is_language_range(range: &str) -> bool {
+    range == "*"
+        || range.split('-').enumerate().all(|(i, subtag)| {
+            (1..=8).contains(&subtag.len())
+                && subtag.bytes().all(|b| b.is_ascii_alphabetic() || (i > 0 && b.is_ascii_digit()))
+        })
+}
+
+fn language_range_matches(range: &str, tag: &str) -> bool {
+    if range == "*" {
+        return true;
+    }
+    tag.len() >= range.len()
+        && tag.as_bytes()[..range.len()].eq_ignore_ascii_case(range.as_bytes())
+        && (tag.len() == range.len() || tag.as_bytes()[range.len()] == b'-')
+}
+
+#[cfg(test)]
+mod tests {
+    use http::header::{ACCEPT, ACCEPT_ENCODING, ACCEPT_LANGUAGE};
+    use mime::{self, Mime};
+
+    use super::{content_type, encoding, language};
+    use {ContentCoding, HeaderMap, HeaderName, HeaderValue};
+
+    fn map(name: HeaderName, values: &[&'static str]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for &value in values {
+            map.append(name.clone(), HeaderValue::from_static(value));
+        }
+        map
+    }
+
+    fn mimes(strs: &[&str]) -> Vec<Mime> {
+        strs.iter().map(|s| s.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn content_type_rfc7231_examples() {
+        // RFC7231 section 5.3.2, and the quality each media type gets.
+        let accept = map(ACCEPT, &[
+            "text/*;q=0.3, text/html;q=0.7, text/html;level=1, text/html;level=2;q=0.4, */*;q=0.5",
+        ]);
+        let table = &[
+            ("text/html;level=1", "text/html;level=1"),
+            ("text/html", "text/html"),
+            ("text/plain", "image/jpeg"),
+            ("image/jpeg", "image/jpeg"),
+            ("text/html;level=2", "image/jpeg"),
+            ("text/html;level=3", "text/html;level=3"),
+        ];
+        for &(first, chosen) in table {
+            // Each against image/jpeg, at q=0.5 from */*.
+            let supported = mimes(&[first, "image/jpeg"]);
+            assert_eq!(content_type(&accept, &supported), Some(chosen.parse().unwrap()), "{}", first);
+        }
+
+        let accept = map(ACCEPT, &["audio/*; q=0.2, audio/basic"]);
+        let supported = mimes(&["audio/mpeg", "audio/basic"]);
+        assert_eq!(content_type(&accept, &supported), Some("audio/basic".parse().unwrap()));
+
+        let accept = map(ACCEPT, &["text/plain; q=0.5, text/html, text/x-dvi; q=0.8, text/x-c"]);
+        let supported = mimes(&["text/plain", "text/x-dvi", "text/x-c"]);
+        assert_eq!(content_type(&accept, &supported), Some("text/x-c".parse().unwrap()));
+    }
+
+    #[test]
+    fn content_type_missing_or_malformed() {
+        let supported = [mime::APPLICATION_JSON, mime::TEXT_HTML];
+        assert_eq!(content_type(&HeaderMap::new(), &supported), Some(mime::APPLICATION_JSON));
+        assert_eq!(content_type(&map(ACCEPT, &["not a mime"]), &supported), Some(mime::APPLICATION_JSON));
+        assert_eq!(content_type(&HeaderMap::new(), &[]), None);
+    }
+
+    #[test]
+    fn content_type_not_acceptable() {
+        let supported = [mime::APPLICATION_JSON];
+        assert_eq!(content_type(&map(ACCEPT, &["text/html"]), &supported), None);
+        assert_eq!(content_type(&map(ACCEPT, &["application/json;q=0, */*"]), &supported), None);
+    }
+
+    #[test]
+    fn content_type_across_values() {
+        let accept = map(ACCEPT, &["text/html;q=0.5", "application/json"]);
+        let supported = [mime::TEXT_HTML, mime::APPLICATION_JSON];
+        assert_eq!(content_type(&accept, &supported), Some(mime::APPLICATION_JSON));
+    }
+
+    #[test]
+    fn encoding_rfc7231_examples() {
+        // RFC7231 section 5.3.4.
+        let table: &[(&[&'static str], &[&str], &str)] = &[
+            (&["compress, gzip"], &["br", "gzip", "compress"], "gzip"),
+            (&[""], &["gzip"], "identity"),
+            (&["*"], &["br", "gzip"], "br"),
+            (&["compress;q=0.5, gzip;q=1.0"], &["compress", "gzip"], "gzip"),
+            (&["gzip;q=1.0, identity; q=0.5, *;q=0"], &["br", "identity", "gzip"], "gzip"),
+            (&["gzip;q=1.0, identity; q=0.5, *;q=0"], &["br", "identity"], "identity"),
+            (&["gzip;q=1.0, identity; q=0.5, *;q=0"], &["br"], "identity"),
+        ];
+        for &(values, supported, chosen) in table {
+            let chosen = chosen.parse::<ContentCoding>().unwrap();
+            assert_eq!(encoding(&map(ACCEPT_ENCODING, values), &codings(supported)), chosen, "{:?}", values);
+        }
+    }
+
+    fn codings(codings: &[&str]) -> Vec<ContentCoding> {
+        codings.iter().map(|c| c.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn encoding_details() {
+        let gzip = [ContentCoding::Gzip];
+        assert_eq!(encoding(&HeaderMap::new(), &gzip), ContentCoding::Identity);
+        assert_eq!(encoding(&map(ACCEPT_ENCODING, &["gzip;q=nope"]), &gzip), ContentCoding::Identity);
+        assert_eq!(encoding(&map(ACCEPT_ENCODING, &["x-gzip"]), &gzip), ContentCoding::Gzip);
+        assert_eq!(encoding(&map(ACCEPT_ENCODING, &["GZIP"]), &gzip), ContentCoding::Gzip);
+        assert_eq!(encoding(&map(ACCEPT_ENCODING, &["gzip;q=0"]), &gzip), ContentCoding::Identity);
+        assert_eq!(
+            encoding(&map(ACCEPT_ENCODING, &["*;q=0, br"]), &codings(&["identity", "br"])),
+            ContentCoding::Br,
+        );
+        assert_eq!(
+            encoding(&map(ACCEPT_ENCODING, &["X-Custom"]), &codings(&["br", "x-custom"])),
+            ContentCoding::Other("x-custom".into()),
+        );
+    }
+
+    #[test]
+    fn language_rfc7231_examples() {
+        // RFC7231 section 5.3.5.
+        let accept = map(ACCEPT_LANGUAGE, &["da, en-gb;q=0.8, en;q=0.7"]);
+        let table: &[(&[&str], Option<&str>)] = &[
+            (&["en", "da"], Some("da")),
+            (&["en-US", "en-GB"], Some("en-GB")),
+            (&["en-US", "fr"], Some("en-US")),
+            (&["en-GB-oed", "en"], Some("en-GB-oed")),
+            (&["eng", "fr"], None),
+        ];
+        for &(supported, chosen) in table {
+            assert_eq!(language(&accept, supported), chosen, "{:?}", supported);
+        }
+    }
+
+    #[test]
+    fn language_details() {
+        let supported = &["fr", "de"];
+        assert_eq!(language(&HeaderMap::new(), supported), Some("fr"));
+        assert_eq!(language(&map(ACCEPT_LANGUAGE, &["en_US"]), supported), Some("fr"));
+        assert_eq!(language(&map(ACCEPT_LANGUAGE, &["*;q=0.1, DE"]), supported), Some("de"));
+        assert_eq!(language(&map(ACCEPT_LANGUAGE, &["*, fr;q=0"]), supported), Some("de"));
+        assert_eq!(language(&map(ACCEPT_LANGUAGE, &["en"]), supported), None);
+    }
+}
@@ -0,0 +1,324 @@
+//! Custom headers whose value is an opaque string, such as a tenant or
+//! trace id.
+//!
+//! [`opaque_header!`](../macro.opaque_header.html) defines one in a line:
+//!
+//! ```
+//! # #[macro_use] extern crate headers_ext as headers;
+//! use headers::{HeaderMap, HeaderMapExt};
+//!
+//! opaque_header!(XTenantId, "x-tenant-id");
+//!
+//! # fn main() {
+//! let mut map = HeaderMap::new();
+//! map.typed_insert(XTenantId::from("acme"));
+//! assert_eq!(map["x-tenant-id"], "acme");
+//! assert_eq!(map.typed_get::<XTenantId>().unwrap().as_str(), "acme");
+//! # }
+//! ```
+//!
+//! The headers it defines wrap an [`Opaque`](struct.Opaque.html), which
+//! holds the value and implements `Header` once for all of them. It can
+//! also be used directly, with a type naming the header.
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+use util::{HeaderString, InvalidHeaderString};
+use {Header, HeaderName, ToValues, Values};
+
+/// Names the header of an `Opaque`.
+pub trait HeaderNameProvider {
+    /// The name of the header.
+    fn name() -> &'static HeaderName;
+}
+
+/// A header whose value is any string of visible ASCII, spaces and tabs,
+/// named by `N`.
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// # extern crate http;
+/// use headers::{HeaderMap, HeaderMapExt, HeaderName};
+/// use headers::opaque::{HeaderNameProvider, Opaque};
+///
+/// struct FromName;
+///
+/// impl HeaderNameProvider for FromName {
+///     fn name() -> &'static HeaderName {
+///         &http::header::FROM
+///     }
+/// }
+///
+/// let mut map = HeaderMap::new();
+/// map.typed_insert(Opaque::<FromName>::from("webmaster@example.org"));
+/// assert_eq!(map["from"], "webmaster@example.org");
+/// ```
+pub struct Opaque<N> {
+    value: HeaderString,
+    name: PhantomData<fn() -> N>,
+}
+
+impl<N> Opaque<N> {
+    /// Create from a string, failing if it isn't a valid header value,
+    /// such as if it contains a line break.
+    pub fn from_string(value: String) -> Result<Opaque<N>, InvalidHeaderString> {
+        HeaderString::try_from(value).map(Opaque::from)
+    }
+
+    /// View the value as a `str`.
+    pub fn as_str(&self) -> &str {
+        self.value.as_str()
+    }
+
+    /// Get the value as a `HeaderString`.
+    pub fn as_header_string(&self) -> &HeaderString {
+        &self.value
+    }
+}
+
+impl<N: HeaderNameProvider> Header for Opaque<N> {
+    fn name() -> &'static HeaderName {
+        N::name()
+    }
+
+    fn decode(values: &mut Values) -> Option<Self> {
+        decode(values).map(Opaque::from)
+    }
+
+    fn encode(&self, values: &mut ToValues) {
+        values.append((&self.value).into());
+    }
+}
+
+// Kept out of the generic impl, so each header doesn't get a copy.
+fn decode(values: &mut Values) -> Option<HeaderString> {
+    HeaderString::from_val(values.next()?)
+}
+
+impl<N> From<HeaderString> for Opaque<N> {
+    fn from(value: HeaderString) -> Opaque<N> {
+        Opaque {
+            value,
+            name: PhantomData,
+        }
+    }
+}
+
+impl<N> From<&'static str> for Opaque<N> {
+    /// Create from a static string, without copying it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` isn't a valid header value, such as if it
+    /// contains a line break.
+    fn from(value: &'static str) -> Opaque<N> {
+        Opaque::from(HeaderString::from(value))
+    }
+}
+
+impl<N> FromStr for Opaque<N> {
+    type Err = InvalidHeaderString;
+
+    fn from_str(s: &str) -> Result<Opaque<N>, InvalidHeaderString> {
+        s.parse::<HeaderString>().map(Opaque::from)
+    }
+}
+
+// Implemented by hand, so `N` needn't implement them.
+impl<N> Clone for Opaque<N> {
+    fn clone(&self) -> Opaque<N> {
+        Opaque::from(self.value.clone())
+    }
+}
+
+impl<N> PartialEq for Opaque<N> {
+    fn eq(&self, other: &Opaque<N>) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<N> Eq for Opaque<N> {}
+
+impl<N> PartialOrd for Opaque<N> {
+    fn partial_cmp(&self, other: &Opaque<N>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N> Ord for Opaque<N> {
+    fn cmp(&self, other: &Opaque<N>) -> Ordering {
+        self.value.cmp(&other.value)
+    }
+}
+
+impl<N> Hash for Opaque<N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.hash(state);
+    }
+}
+
+impl<N> fmt::Debug for Opaque<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Opaque").field(&self.as_str()).finish()
+    }
+}
+
+impl<N> fmt::Display for Opaque<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Define a header whose value is an opaque string, as a newtype of
+/// [`opaque::Opaque`](opaque/struct.Opaque.html).
+///
+/// `opaque_header!(XTenantId, "x-tenant-id");` defines a public
+/// `XTenantId`, with attributes such as doc comments given before its
+/// name. The name must be lowercase, and panics the first time it's used
+/// if it's invalid.
+///
+/// The header implements `Header` and `SingleValueHeader`, `Display` and
+/// `FromStr`, `From<&'static str>`, which panics on an invalid value, and
+/// has `as_str` and a fallible `from_string`.
+///
+/// # Example
+///
+/// ```
+/// # #[macro_use] extern crate headers_ext as headers;
+/// use headers::{HeaderMap, HeaderMapExt};
+///
+/// opaque_header! {
+///     /// `X-Correlation-Id`, shared by every request of a transaction.
+///     XCorrelationId, "x-correlation-id"
+/// }
+///
+/// # fn main() {
+/// let id = XCorrelationId::from_string(format!("tx-{}", 42)).unwrap();
+/// assert_eq!(id.to_string(), "tx-42");
+/// assert!(XCorrelationId::from_string("a\r\nb".into()).is_err());
+///
+/// let mut map = HeaderMap::new();
+/// map.typed_insert(id.clone());
+/// assert_eq!(map.typed_get(), Some(id));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! opaque_header {
+    ($(#[$attr:meta])* $ty:ident, $name:expr) => {
+        $(#[$attr])*
+        #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $ty($crate::opaque::Opaque<$ty>);
+
+        impl $ty {
+            /// Create from a string, failing if it isn't a valid header
+            /// value, such as if it contains a line break.
+            pub fn from_string(value: String) -> Result<$ty, $crate::InvalidHeaderString> {
+                $crate::opaque::Opaque::from_string(value).map($ty)
+            }
+
+            /// View the value as a `str`.
+            pub fn as_str(&self) -> &str {
+                self.0.as_str()
+            }
+        }
+
+        impl $crate::opaque::HeaderNameProvider for $ty {
+            fn name() -> &'static $crate::HeaderName {
+                $crate::static_header_name!($name)
+            }
+        }
+
+        impl $crate::Header for $ty {
+            fn name() -> &'static $crate::HeaderName {
+                <$ty as $crate::opaque::HeaderNameProvider>::name()
+            }
+
+            fn decode(values: &mut $crate::Values) -> Option<$ty> {
+                $crate::Header::decode(values).map($ty)
+            }
+
+            fn encode(&self, values: &mut $crate::ToValues) {
+                $crate::Header::encode(&self.0, values)
+            }
+        }
+
+        impl $crate::SingleValueHeader for $ty {}
+
+        impl From<&'static str> for $ty {
+            fn from(value: &'static str) -> $ty {
+                $ty($crate::opaque::Opaque::from(value))
+            }
+        }
+
+        impl ::std::str::FromStr for $ty {
+            type Err = $crate::InvalidHeaderString;
+
+            fn from_str(s: &str) -> Result<$ty, $crate::InvalidHeaderString> {
+                s.parse().map($ty)
+            }
+        }
+
+        impl ::std::fmt::Debug for $ty {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                f.debug_tuple(stringify!($ty)).field(&self.as_str()).finish()
+            }
+        }
+
+        impl ::std::fmt::Display for $ty {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                ::std::fmt::Display::fmt(&self.0, f)
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use {Header, HeaderMap, HeaderMapExt, HeaderValue, ParseMode};
+
+    opaque_header!(XTenantId, "x-tenant-id");
+
+    #[test]
+    fn decode() {
+        let mut map = HeaderMap::new();
+        map.insert("x-tenant-id", HeaderValue::from_static("acme corp"));
+        let tenant = map.typed_get::<XTenantId>().unwrap();
+        assert_eq!(tenant.as_str(), "acme corp");
+        assert_eq!(format!("{:?}", tenant), "XTenantId(\"acme corp\")");
+        assert_eq!(map.typed_get_with::<XTenantId>(ParseMode::Strict), Some(tenant));
+
+        map.insert("x-tenant-id", HeaderValue::from_bytes(b"caf\xc3\xa9").unwrap());
+        assert_eq!(map.typed_get::<XTenantId>(), None);
+    }
+
+    #[test]
+    fn encode() {
+        let mut map = HeaderMap::new();
+        map.typed_insert(XTenantId::from("acme"));
+        assert_eq!(map["x-tenant-id"], "acme");
+        assert_eq!(XTenantId::name(), "x-tenant-id");
+    }
+
+    #[test]
+    fn construct() {
+        assert_eq!(XTenantId::from_string("acme".into()).unwrap(), XTenantId::from("acme"));
+        assert_eq!("acme".parse::<XTenantId>().unwrap().to_string(), "acme");
+        for s in &["a\r\nb", "nul\0", "caf\u{e9}"] {
+            assert!(XTenantId::from_string(s.to_string()).is_err(), "{:?}", s);
+            assert!(s.parse::<XTenantId>().is_err(), "{:?}", s);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_static_panics_on_invalid() {
+        let _ = XTenantId::from("a\nb");
+    }
+}
@@ -0,0 +1,374 @@
+//! Finding the address of the client behind reverse proxies.
+//!
+//! Proxies report the address they received a request from in
+//! `Forwarded`, `X-Forwarded-For` or `X-Real-IP`. Clients can send these
+//! headers too, so only what trusted proxies added can be believed, and
+//! only in the header they're configured to set.
+//!
+//! # Example
+//!
+//! ```
+//! # extern crate headers_ext as headers;
+//! use headers::HeaderMap;
+//! use headers::proxy::{self, IpNetwork, ProxyHeader};
+//!
+//! let trusted: Vec<IpNetwork> = vec!["10.0.0.0/8".parse().unwrap()];
+//!
+//! // The client claims to be 127.0.0.1, and our proxy appended the
+//! // address it really came from.
+//! let mut map = HeaderMap::new();
+//! map.insert("x-forwarded-for", "127.0.0.1, 203.0.113.7".parse().unwrap());
+//!
+//! let peer = "10.1.2.3".parse().unwrap();
+//! assert_eq!(
+//!     proxy::client_ip(&map, peer, &trusted, ProxyHeader::XForwardedFor),
+//!     Some("203.0.113.7".parse().unwrap()),
+//! );
+//!
+//! // Headers from a peer that isn't a trusted proxy are ignored.
+//! let peer = "198.51.100.1".parse().unwrap();
+//! assert_eq!(proxy::client_ip(&map, peer, &trusted, ProxyHeader::XForwardedFor), Some(peer));
+//! ```
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::str::FromStr;
+
+use util::quoted::unquote;
+use util::split_quoted;
+use {Header, HeaderMap, HeaderMapExt, XRealIp};
+
+/// The header trusted proxies report the client's address in, for
+/// [`client_ip`](fn.client_ip.html).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ProxyHeader {
+    /// `Forwarded`, with the `for` parameter of each element.
+    Forwarded,
+    /// `X-Forwarded-For`.
+    XForwardedFor,
+    /// `X-Real-IP`, which a proxy sets rather than appends to.
+    XRealIp,
+}
+
+/// Find the address of the client of a request that came through the
+/// proxies in `trusted`, which report it in `header`.
+///
+/// `peer` is the address the connection came from. If it isn't a trusted
+/// proxy, it's the client, and the headers are ignored, since anyone
+/// could have sent them. Otherwise only `header` is read. The others may
+/// have come from the client as they are, since the proxies don't touch
+/// them.
+///
+/// Proxies append to `Forwarded` and `X-Forwarded-For`, so the addresses
+/// are walked from the right, skipping trusted proxies, and the first
+/// other address is the client. Those to its left came from the client,
+/// and aren't believed. If every address is a trusted proxy, the
+/// left-most is returned.
+///
+/// Returns `None` if the client's address is hidden or malformed, such as
+/// `for=unknown` or `for=_hidden`. Ports are ignored. If a trusted proxy
+/// didn't send `header`, `peer` is returned.
+pub fn client_ip(headers: &HeaderMap, peer: IpAddr, trusted: &[IpNetwork], header: ProxyHeader) -> Option<IpAddr> {
+    let is_trusted = |addr: IpAddr| trusted.iter().any(|net| net.contains(addr));
+    if !is_trusted(peer) {
+        return Some(peer);
+    }
+
+    let hops = match header {
+        ProxyHeader::Forwarded => hops(headers, "forwarded", forwarded_for),
+        ProxyHeader::XForwardedFor => hops(headers, "x-forwarded-for", parse_node),
+        ProxyHeader::XRealIp => {
+            if !headers.contains_key(XRealIp::name()) {
+                return Some(peer);
+            }
+            return headers.typed_get::<XRealIp>().map(|real_ip| real_ip.0);
+        },
+    };
+    if hops.is_empty() {
+        return Some(peer);
+    }
+
+    let mut leftmost = None;
+    for hop in hops.into_iter().rev() {
+        let addr = hop?;
+        if !is_trusted(addr) {
+            return Some(addr);
+        }
+        leftmost = Some(addr);
+    }
+    leftmost
+}
+
+/// The address of each element of the `name` header, in order, or `None`
+/// for those that don't have one.
+fn hops(headers: &HeaderMap, name: &str, node: fn(&str) -> Option<IpAddr>) -> Vec<Option<IpAddr>> {
+    let mut hops = Vec::new();
+    for value in headers.get_all(name) {
+        match value.to_str() {
+            Ok(value) => hops.extend(split_quoted(value, b',').map(node)),
+            Err(_) => hops.push(None),
+        }
+    }
+    hops
+}
+
+/// The address in the `for` parameter of a `Forwarded` element.
+fn forwarded_for(element: &str) -> Option<IpAddr> {
+    let node = split_quoted(element, b';').find_map(|pair| {
+        let (name, node) = pair.split_once('=')?;
+        if name.trim_end().eq_ignore_ascii_case("for") {
+            Some(node.trim_start())
+        } else {
+            None
+        }
+    })?;
+    parse_node(&unquote(node)?)
+}
+
+/// Parse an address, which can have a port, and IPv6 addresses brackets.
+fn parse_node(node: &str) -> Option<IpAddr> {
+    node.parse::<IpAddr>()
+        .ok()
+        .or_else(|| node.parse::<SocketAddr>().ok().map(|addr| addr.ip()))
+        .or_else(|| {
+            let v6 = node.strip_prefix('[')?.strip_suffix(']')?;
+            v6.parse::<Ipv6Addr>().ok().map(IpAddr::V6)
+        })
+}
+
+/// A range of IP addresses, such as `10.0.0.0/8`, for listing trusted
+/// proxies.
+///
+/// IPv4 addresses mapped to IPv6, like `::ffff:10.0.0.1`, are in the IPv4
+/// networks that contain them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct IpNetwork {
+    addr: IpAddr,
+    prefix_len: u8,
+}
+
+/// An error returned when an `IpNetwork` is invalid.
+#[derive(Debug)]
+pub struct InvalidIpNetwork(&'static str);
+
+impl IpNetwork {
+    /// Create the network of the addresses starting with the first
+    /// `prefix_len` bits of `addr`.
+    ///
+    /// Fails if `prefix_len` is longer than the address. The other bits of
+    /// `addr` are ignored.
+    pub fn new(addr: IpAddr, prefix_len: u8) -> Result<IpNetwork, InvalidIpNetwork> {
+        let addr = match addr {
+            IpAddr::V4(v4) if prefix_len <= 32 => {
+                IpAddr::V4(Ipv4Addr::from(u32::from(v4) & mask(prefix_len, 32) as u32))
+            },
+            IpAddr::V6(v6) if prefix_len <= 128 => IpAddr::V6(Ipv6Addr::from(u128::from(v6) & mask(prefix_len, 128))),
+            _ => return Err(InvalidIpNetwork("prefix is too long")),
+        };
+        Ok(IpNetwork { addr, prefix_len })
+    }
+
+    /// The first address of the network.
+    pub fn addr(&self) -> IpAddr {
+        self.addr
+    }
+
+    /// The number of bits its addresses share.
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+
+    /// Check if `addr` is in the network.
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        match (self.addr, addr.to_canonical()) {
+            (IpAddr::V4(net), IpAddr::V4(addr)) => {
+                u32::from(addr) & mask(self.prefix_len, 32) as u32 == u32::from(net)
+            },
+            (IpAddr::V6(net), IpAddr::V6(addr)) => u128::from(addr) & mask(self.prefix_len, 128) == u128::from(net),
+            _ => false,
+        }
+    }
+}
+
+/// The mask of the first `prefix_len` bits of a `bits` long address.
+fn mask(prefix_len: u8, bits: u32) -> u128 {
+    let host_bits = bits - u32::from(prefix_len);
+    (!0u128).checked_shl(host_bits).unwrap_or(0) & (!0u128 >> (128 - bits))
+}
+
+impl From<IpAddr> for IpNetwork {
+    fn from(addr: IpAddr) -> IpNetwork {
+        let prefix_len = if addr.is_ipv4() { 32 } else { 128 };
+        IpNetwork { addr, prefix_len }
+    }
+}
+
+impl FromStr for IpNetwork {
+    type Err = InvalidIpNetwork;
+
+    /// Parse a network like `10.0.0.0/8` or `2001:db8::/32`, or a single
+    /// address.
+    fn from_str(s: &str) -> Result<IpNetwork, InvalidIpNetwork> {
+        let (addr, prefix_len) = match s.split_once('/') {
+            Some((addr, prefix_len)) => (addr, Some(prefix_len)),
+            None => (s, None),
+        };
+        let addr = addr
+            .parse::<IpAddr>()
+            .map_err(|_| InvalidIpNetwork("has an invalid address"))?;
+        match prefix_len {
+            Some(prefix_len) => {
+                let valid = !prefix_len.is_empty() && prefix_len.bytes().all(|b| b.is_ascii_digit());
+                let prefix_len = prefix_len
+                    .parse()
+                    .ok()
+                    .filter(|_| valid)
+                    .ok_or(InvalidIpNetwork("has an invalid prefix"))?;
+                IpNetwork::new(addr, prefix_len)
+            },
+            None => Ok(IpNetwork::from(addr)),
+        }
+    }
+}
+
+impl fmt::Display for IpNetwork {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix_len)
+    }
+}
+
+impl fmt::Display for InvalidIpNetwork {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid IP network: {}", self.0)
+    }
+}
+
+impl StdError for InvalidIpNetwork {}
+
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+
+    use super::{client_ip, IpNetwork, ProxyHeader};
+    use HeaderMap;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    fn map(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for &(name, value) in pairs {
+            map.append(name, value.parse().unwrap());
+        }
+        map
+    }
+
+    fn trusted() -> Vec<IpNetwork> {
+        vec!["10.0.0.0/8".parse().unwrap(), "2001:db8:ffff::/48".parse().unwrap()]
+    }
+
+    #[test]
+    fn network() {
+        let net: IpNetwork = "10.1.2.3/8".parse().unwrap();
+        assert_eq!(net.addr(), ip("10.0.0.0"));
+        assert_eq!(net.to_string(), "10.0.0.0/8");
+        assert!(net.contains(ip("10.255.0.1")));
+        assert!(net.contains(ip("::ffff:10.0.0.1")));
+        assert!(!net.contains(ip("11.0.0.1")));
+        assert!(!net.contains(ip("::a00:1")));
+
+        let net: IpNetwork = "2001:db8::1".parse().unwrap();
+        assert_eq!(net.prefix_len(), 128);
+        assert!(net.contains(ip("2001:db8::1")));
+        assert!(!net.contains(ip("2001:db8::2")));
+
+        let any: IpNetwork = "0.0.0.0/0".parse().unwrap();
+        assert!(any.contains(ip("192.0.2.1")));
+        assert!(!any.contains(ip("2001:db8::1")));
+        assert!("::/0".parse::<IpNetwork>().unwrap().contains(ip("2001:db8::1")));
+
+        for &s in &["", "10.0.0.0/", "10.0.0.0/33", "::/129", "10.0.0.0/+8", "10.0.0/8", "example.com"] {
+            assert!(s.parse::<IpNetwork>().is_err(), "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn untrusted_peer_is_the_client() {
+        let map = map(&[("x-forwarded-for", "10.0.0.1"), ("x-real-ip", "10.0.0.1")]);
+        assert_eq!(client_ip(&map, ip("192.0.2.1"), &trusted(), ProxyHeader::XForwardedFor), Some(ip("192.0.2.1")));
+        assert_eq!(client_ip(&map, ip("10.0.0.7"), &[], ProxyHeader::XForwardedFor), Some(ip("10.0.0.7")));
+    }
+
+    #[test]
+    fn x_forwarded_for_from_the_right() {
+        let peer = ip("10.0.0.7");
+
+        // The left-most entry is spoofed by the client.
+        let map = map(&[("x-forwarded-for", "1.2.3.4, 192.0.2.1, 10.0.0.2")]);
+        assert_eq!(client_ip(&map, peer, &trusted(), ProxyHeader::XForwardedFor), Some(ip("192.0.2.1")));
+
+        let split = self::map(&[("x-forwarded-for", "1.2.3.4"), ("x-forwarded-for", "192.0.2.1,10.0.0.2")]);
+        assert_eq!(client_ip(&split, peer, &trusted(), ProxyHeader::XForwardedFor), Some(ip("192.0.2.1")));
+
+        let all_trusted = self::map(&[("x-forwarded-for", "10.0.0.1, 10.0.0.2")]);
+        assert_eq!(client_ip(&all_trusted, peer, &trusted(), ProxyHeader::XForwardedFor), Some(ip("10.0.0.1")));
+
+        let ports = self::map(&[("x-forwarded-for", "[2001:db8::1]:4711, 10.0.0.2:80")]);
+        assert_eq!(client_ip(&ports, peer, &trusted(), ProxyHeader::XForwardedFor), Some(ip("2001:db8::1")));
+
+        let garbage = self::map(&[("x-forwarded-for", "192.0.2.1, nope, 10.0.0.2")]);
+        assert_eq!(client_ip(&garbage, peer, &trusted(), ProxyHeader::XForwardedFor), None);
+
+        // Garbage to the left of the client doesn't matter.
+        let spoofed = self::map(&[("x-forwarded-for", "nope, 192.0.2.1")]);
+        assert_eq!(client_ip(&spoofed, peer, &trusted(), ProxyHeader::XForwardedFor), Some(ip("192.0.2.1")));
+    }
+
+    #[test]
+    fn forwarded() {
+        let peer = ip("2001:db8:ffff::1");
+        let map = map(&[
+            ("forwarded", "for=1.2.3.4, For=\"[2001:db8:cafe::17]:4711\";proto=https"),
+            ("forwarded", "by=10.0.0.1;for=10.0.0.2;host=example.com"),
+            ("x-forwarded-for", "1.2.3.4"),
+            ("x-real-ip", "1.2.3.4"),
+        ]);
+        assert_eq!(client_ip(&map, peer, &trusted(), ProxyHeader::Forwarded), Some(ip("2001:db8:cafe::17")));
+
+        let hidden = self::map(&[("forwarded", "for=192.0.2.1, for=_hidden, for=10.0.0.2")]);
+        assert_eq!(client_ip(&hidden, peer, &trusted(), ProxyHeader::Forwarded), None);
+
+        let unknown = self::map(&[("forwarded", "for=unknown")]);
+        assert_eq!(client_ip(&unknown, peer, &trusted(), ProxyHeader::Forwarded), None);
+
+        let missing = self::map(&[("forwarded", "for=192.0.2.1, proto=http")]);
+        assert_eq!(client_ip(&missing, peer, &trusted(), ProxyHeader::Forwarded), None);
+    }
+
+    #[test]
+    fn other_headers_are_ignored() {
+        let peer = ip("10.0.0.1");
+
+        // The client sent its own Forwarded, our proxy only appends to
+        // X-Forwarded-For.
+        let map = map(&[("forwarded", "for=1.2.3.4"), ("x-forwarded-for", "203.0.113.7")]);
+        assert_eq!(client_ip(&map, peer, &trusted(), ProxyHeader::XForwardedFor), Some(ip("203.0.113.7")));
+
+        let injected = self::map(&[("x-forwarded-for", "1.2.3.4"), ("x-real-ip", "1.2.3.4")]);
+        assert_eq!(client_ip(&injected, peer, &trusted(), ProxyHeader::Forwarded), Some(peer));
+    }
+
+    #[test]
+    fn x_real_ip() {
+        let peer = ip("10.0.0.7");
+        let map = map(&[("x-real-ip", "192.0.2.1")]);
+        assert_eq!(client_ip(&map, peer, &trusted(), ProxyHeader::XRealIp), Some(ip("192.0.2.1")));
+
+        let invalid = self::map(&[("x-real-ip", "192.0.2.1:80")]);
+        assert_eq!(client_ip(&invalid, peer, &trusted(), ProxyHeader::XRealIp), None);
+
+        assert_eq!(client_ip(&HeaderMap::new(), peer, &trusted(), ProxyHeader::XRealIp), Some(peer));
+    }
+}
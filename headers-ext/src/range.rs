@@ -0,0 +1,333 @@
+//! Headers for range requests, and resolving which bytes to serve.
+//!
+//! [`resolve`](fn.resolve.html) combines the `Range` and `If-Range`
+//! headers of a request with the validators and length of the
+//! representation, following
+//! [RFC7233](https://tools.ietf.org/html/rfc7233). It only resolves the
+//! ranges: writing the `multipart/byteranges` body for several of them is
+//! left to the caller.
+
+use std::ops::{self, Bound};
+use std::time::SystemTime;
+
+pub use common::{AcceptRanges, ContentRange, IfRange, InvalidRange, Range};
+pub use util::RangeUnit;
+
+use {ETag, LastModified};
+
+/// How many ranges are served at most, by default.
+pub const DEFAULT_MAX_RANGES: usize = 64;
+
+/// What to send in response to a range request.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RangeOutcome {
+    /// Send the full representation, with `200 OK`.
+    ///
+    /// That's when the `If-Range` doesn't match, or the `Range` is invalid
+    /// or asks for too many ranges.
+    Full,
+    /// Send these ranges, in order and without overlaps, with
+    /// `206 Partial Content`.
+    ///
+    /// The `ContentRange` is the one of a response with a single range.
+    /// With several, it spans all of them, and each part of the multipart
+    /// body needs its own instead.
+    Partial(Vec<ops::Range<u64>>, ContentRange),
+    /// Respond with `416 Range Not Satisfiable`, and this `ContentRange`.
+    Unsatisfiable(ContentRange),
+}
+
+/// Resolve a range request, serving at most
+/// [`DEFAULT_MAX_RANGES`](constant.DEFAULT_MAX_RANGES.html) ranges.
+///
+/// See [`RangeResolver::resolve`](struct.RangeResolver.html#method.resolve).
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::range::{resolve, ContentRange, Range, RangeOutcome};
+///
+/// let range = "bytes=0-99".parse::<Range>().unwrap();
+/// assert_eq!(
+///     resolve(&range, None, None, None, 50),
+///     RangeOutcome::Partial(vec![0..50], ContentRange::bytes(0, 49, 50)),
+/// );
+/// ```
+pub fn resolve(
+    range: &Range,
+    if_range: Option<&IfRange>,
+    etag: Option<&ETag>,
+    last_modified: Option<SystemTime>,
+    len: u64,
+) -> RangeOutcome {
+    RangeResolver::new().resolve(range, if_range, etag, last_modified, len)
+}
+
+/// How to resolve range requests.
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::range::{Range, RangeOutcome, RangeResolver};
+///
+/// let range = "bytes=0-0,10-10,20-20".parse::<Range>().unwrap();
+/// let resolver = RangeResolver::new().with_max_ranges(2);
+/// assert_eq!(resolver.resolve(&range, None, None, None, 100), RangeOutcome::Full);
+/// ```
+#[derive(Clone, Debug)]
+pub struct RangeResolver {
+    max_ranges: usize,
+}
+
+impl RangeResolver {
+    /// Create the default `RangeResolver`.
+    pub fn new() -> RangeResolver {
+        RangeResolver {
+            max_ranges: DEFAULT_MAX_RANGES,
+        }
+    }
+
+    /// Set how many ranges are served at most, once overlapping ones are
+    /// merged. A request for more gets the full representation.
+    pub fn with_max_ranges(mut self, max: usize) -> Self {
+        self.max_ranges = max;
+        self
+    }
+
+    /// Resolve the ranges of a representation of `len` bytes to serve.
+    ///
+    /// `etag` and `last_modified` are the validators of the representation,
+    /// for the `If-Range` of the request. It only matches an entity tag
+    /// using the strong comparison, or exactly the last modification date,
+    /// otherwise the full representation is sent.
+    ///
+    /// The satisfiable ranges are sorted, and the ones that overlap or are
+    /// adjacent are merged. If none is satisfiable, such as a range
+    /// starting after the end, the request can't be served.
+    ///
+    /// Only `bytes` ranges are resolved. The full representation is sent
+    /// for other units, as a server doesn't understand them.
+    pub fn resolve(
+        &self,
+        range: &Range,
+        if_range: Option<&IfRange>,
+        etag: Option<&ETag>,
+        last_modified: Option<SystemTime>,
+        len: u64,
+    ) -> RangeOutcome {
+        if let Some(if_range) = if_range {
+            let last_modified = last_modified.map(LastModified::from);
+            if if_range.is_modified(etag, last_modified.as_ref()) {
+                return RangeOutcome::Full;
+            }
+        }
+
+        if !range.unit().is_bytes() {
+            return RangeOutcome::Full;
+        }
+
+        let mut ranges = Vec::new();
+        for bounds in range.iter() {
+            match satisfiable(bounds, len) {
+                Ok(Some(range)) => ranges.push(range),
+                Ok(None) => (),
+                // An invalid range set is ignored.
+                Err(()) => return RangeOutcome::Full,
+            }
+        }
+        let ranges = coalesce(ranges);
+
+        if ranges.is_empty() {
+            return if range.iter().next().is_some() {
+                RangeOutcome::Unsatisfiable(ContentRange::unsatisfied_bytes(len))
+            } else {
+                RangeOutcome::Full
+            };
+        }
+        if ranges.len() > self.max_ranges {
+            return RangeOutcome::Full;
+        }
+
+        let first = ranges[0].start;
+        let last = ranges[ranges.len() - 1].end - 1;
+        RangeOutcome::Partial(ranges, ContentRange::bytes(first, last, len))
+    }
+}
+
+impl Default for RangeResolver {
+    fn default() -> RangeResolver {
+        RangeResolver::new()
+    }
+}
+
+// The range of `len` bytes that `bounds` selects, if any, or an error if
+// `bounds` isn't a valid `byte-range-spec`.
+fn satisfiable(bounds: (Bound<u64>, Bound<u64>), len: u64) -> Result<Option<ops::Range<u64>>, ()> {
+    let range = match bounds {
+        (Bound::Included(first), Bound::Included(last)) => {
+            if last < first {
+                return Err(());
+            }
+            first..last.saturating_add(1).min(len)
+        },
+        (Bound::Included(first), Bound::Unbounded) => first..len,
+        (Bound::Unbounded, Bound::Included(suffix)) => len.saturating_sub(suffix)..len,
+        _ => return Err(()),
+    };
+    if range.start < range.end {
+        Ok(Some(range))
+    } else {
+        Ok(None)
+    }
+}
+
+// Sort the ranges, merging the ones that overlap or are adjacent.
+fn coalesce(mut ranges: Vec<ops::Range<u64>>) -> Vec<ops::Range<u64>> {
+    ranges.sort_by_key(|range| range.start);
+    let mut merged: Vec<ops::Range<u64>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use super::{resolve, ContentRange, IfRange, Range, RangeOutcome, RangeResolver};
+    use ETag;
+
+    fn range(s: &str) -> Range {
+        s.parse().unwrap()
+    }
+
+    // The ranges as `(start, end)`, for shorter comparisons.
+    fn partial(s: &str, len: u64) -> Vec<(u64, u64)> {
+        match resolve(&range(s), None, None, None, len) {
+            RangeOutcome::Partial(ranges, _) => ranges.iter().map(|r| (r.start, r.end)).collect(),
+            outcome => panic!("{:?} resolved to {:?}", s, outcome),
+        }
+    }
+
+    // Sun, 06 Nov 1994 08:49:37 GMT
+    fn last_modified() -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(784111777)
+    }
+
+    #[test]
+    fn single_ranges() {
+        assert_eq!(partial("bytes=0-499", 10000), [(0, 500)]);
+        match resolve(&range("bytes=0-499"), None, None, None, 10000) {
+            RangeOutcome::Partial(_, content_range) => {
+                assert_eq!(content_range, ContentRange::bytes(0, 499, 10000));
+            },
+            outcome => panic!("{:?}", outcome),
+        }
+        assert_eq!(partial("bytes=500-999", 10000), [(500, 1000)]);
+        assert_eq!(partial("bytes=-500", 10000), [(9500, 10000)]);
+        assert_eq!(partial("bytes=9500-", 10000), [(9500, 10000)]);
+        // Clamped to the length.
+        assert_eq!(partial("bytes=9500-20000", 10000), [(9500, 10000)]);
+        assert_eq!(partial("bytes=-20000", 10000), [(0, 10000)]);
+        assert_eq!(partial("bytes=0-18446744073709551615", 10), [(0, 10)]);
+    }
+
+    #[test]
+    fn coalesces() {
+        assert_eq!(partial("bytes=0-0,-1", 10000), [(0, 1), (9999, 10000)]);
+        assert_eq!(partial("bytes=500-600,601-999", 10000), [(500, 1000)]);
+        assert_eq!(partial("bytes=500-700,601-999", 10000), [(500, 1000)]);
+        assert_eq!(partial("bytes=9000-,0-10,5-20,-100", 10000), [(0, 21), (9000, 10000)]);
+        assert_eq!(partial("bytes=0-0,0-0,0-0", 10), [(0, 1)]);
+
+        match resolve(&range("bytes=20-29,0-9"), None, None, None, 100) {
+            RangeOutcome::Partial(ranges, content_range) => {
+                assert_eq!(ranges, [0..10, 20..30]);
+                assert_eq!(content_range, ContentRange::bytes(0, 29, 100));
+            },
+            outcome => panic!("{:?}", outcome),
+        }
+    }
+
+    #[test]
+    fn unsatisfiable() {
+        for s in &["bytes=10000-", "bytes=10000-10010", "bytes=-0", "bytes=10000-,-0"] {
+            assert_eq!(
+                resolve(&range(s), None, None, None, 10000),
+                RangeOutcome::Unsatisfiable(ContentRange::unsatisfied_bytes(10000)),
+                "{:?}",
+                s,
+            );
+        }
+        // Unsatisfiable ranges are dropped from the others.
+        assert_eq!(partial("bytes=10000-,0-0", 10000), [(0, 1)]);
+        // Nothing can be served of an empty representation.
+        assert_eq!(
+            resolve(&range("bytes=-10"), None, None, None, 0),
+            RangeOutcome::Unsatisfiable(ContentRange::unsatisfied_bytes(0)),
+        );
+    }
+
+    #[test]
+    fn invalid_is_ignored() {
+        for s in &["bytes=10-5", "bytes=-", "bytes=0-0,10-5", "bytes=a-b"] {
+            assert_eq!(resolve(&range(s), None, None, None, 10000), RangeOutcome::Full, "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn other_units_are_ignored() {
+        assert_eq!(resolve(&range("items=0-49"), None, None, None, 10000), RangeOutcome::Full);
+    }
+
+    #[test]
+    fn max_ranges() {
+        let many = (0..65).map(|i| format!("{}-{}", i * 10, i * 10)).collect::<Vec<_>>().join(",");
+        let many = range(&format!("bytes={}", many));
+        assert_eq!(resolve(&many, None, None, None, 10000), RangeOutcome::Full);
+
+        let resolver = RangeResolver::new().with_max_ranges(65);
+        match resolver.resolve(&many, None, None, None, 10000) {
+            RangeOutcome::Partial(ranges, _) => assert_eq!(ranges.len(), 65),
+            outcome => panic!("{:?}", outcome),
+        }
+
+        // Counted once merged.
+        let overlapping = (0..100).map(|i| format!("{}-{}", i, i + 10)).collect::<Vec<_>>().join(",");
+        assert_eq!(partial(&format!("bytes={}", overlapping), 10000), [(0, 110)]);
+    }
+
+    #[test]
+    fn if_range() {
+        let etag = "\"xyzzy\"".parse::<ETag>().unwrap();
+        let bytes = range("bytes=0-9");
+        let resolve_if = |if_range: &IfRange| {
+            resolve(&bytes, Some(if_range), Some(&etag), Some(last_modified()), 100)
+        };
+
+        assert_ne!(resolve_if(&IfRange::etag(etag.clone())), RangeOutcome::Full);
+        assert_eq!(resolve_if(&IfRange::etag("\"other\"".parse().unwrap())), RangeOutcome::Full);
+        assert_eq!(resolve_if(&IfRange::etag("W/\"xyzzy\"".parse().unwrap())), RangeOutcome::Full);
+
+        assert_ne!(resolve_if(&IfRange::date(last_modified())), RangeOutcome::Full);
+        let earlier = last_modified() - Duration::from_secs(1);
+        let later = last_modified() + Duration::from_secs(1);
+        assert_eq!(resolve_if(&IfRange::date(earlier)), RangeOutcome::Full);
+        assert_eq!(resolve_if(&IfRange::date(later)), RangeOutcome::Full);
+
+        // Without the validator to compare, nothing matches.
+        let if_range = IfRange::etag(etag.clone());
+        assert_eq!(resolve(&bytes, Some(&if_range), None, None, 100), RangeOutcome::Full);
+        // And an `If-Range` that matches still needs a satisfiable range.
+        assert_eq!(
+            resolve(&range("bytes=100-"), Some(&if_range), Some(&etag), None, 100),
+            RangeOutcome::Unsatisfiable(ContentRange::unsatisfied_bytes(100)),
+        );
+    }
+}
@@ -0,0 +1,143 @@
+//! Keeping the values a header was decoded from.
+
+use headers_core::decode::from_values_with;
+use headers_core::DecodeError;
+
+use {Header, HeaderName, HeaderValue, ToValues, Values};
+
+/// A decoded header along with the exact values it was decoded from.
+///
+/// This is meant for logging what a peer literally sent next to what it
+/// was understood as. Decoding is the same as for `H`, in the same
+/// `ParseMode`, and encoding sends the original values verbatim, so a
+/// `WithRaw` can be forwarded without being canonicalized.
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::{CacheControl, HeaderMap, HeaderMapExt, HeaderValue};
+/// use headers::raw::WithRaw;
+///
+/// let mut map = HeaderMap::new();
+/// map.insert("cache-control", HeaderValue::from_static("Max-Age=60,  Public"));
+///
+/// let cc = map.typed_get::<WithRaw<CacheControl>>().unwrap();
+/// assert!(cc.parsed().public());
+/// assert_eq!(cc.raw(), ["Max-Age=60,  Public"]);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct WithRaw<H> {
+    parsed: H,
+    raw: Vec<HeaderValue>,
+}
+
+impl<H> WithRaw<H> {
+    /// The decoded header.
+    pub fn parsed(&self) -> &H {
+        &self.parsed
+    }
+
+    /// The values of the field lines the header was decoded from, in order.
+    pub fn raw(&self) -> &[HeaderValue] {
+        &self.raw
+    }
+
+    /// Take the decoded header, dropping the values.
+    pub fn into_parsed(self) -> H {
+        self.parsed
+    }
+}
+
+impl<H: Header> Header for WithRaw<H> {
+    fn name() -> &'static HeaderName {
+        H::name()
+    }
+
+    fn decode(values: &mut Values) -> Option<Self> {
+        // The values are cheap to clone, sharing their `Bytes`.
+        let raw = values.by_ref().cloned().collect::<Vec<_>>();
+        match from_values_with(&raw, values.mode(), values.limits()) {
+            Ok(parsed) => Some(WithRaw { parsed, raw }),
+            Err(DecodeError::TooLarge) => {
+                values.set_too_large();
+                None
+            },
+            Err(_) => None,
+        }
+    }
+
+    fn encode(&self, values: &mut ToValues) {
+        self.raw.iter().cloned().for_each(|value| values.append(value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WithRaw;
+    use {
+        CacheControl,
+        ContentLength,
+        DecodeError,
+        DecodeLimits,
+        HeaderMap,
+        HeaderMapExt,
+        HeaderValue,
+        ParseMode,
+        Vary,
+    };
+
+    fn map(name: &'static str, lines: &[&'static str]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for &line in lines {
+            map.append(name, HeaderValue::from_static(line));
+        }
+        map
+    }
+
+    #[test]
+    fn raw_is_preserved() {
+        let original = map("cache-control", &["NO-CACHE ,max-age=\"30\"", "  private"]);
+        let cc = original.typed_get::<WithRaw<CacheControl>>().unwrap();
+        assert!(cc.parsed().no_cache());
+        assert!(cc.parsed().private());
+        assert_eq!(cc.raw(), ["NO-CACHE ,max-age=\"30\"", "  private"]);
+
+        // The parsed form encodes differently, the wrapper doesn't.
+        let mut canonical = HeaderMap::new();
+        canonical.typed_insert(cc.parsed().clone());
+        assert_ne!(canonical, original);
+
+        let mut forwarded = HeaderMap::new();
+        forwarded.typed_insert(cc);
+        assert_eq!(forwarded, original);
+    }
+
+    #[test]
+    fn decodes_like_the_header() {
+        let vary = map("vary", &["accept", "origin"]);
+        let parsed = vary.typed_get::<WithRaw<Vary>>().unwrap().into_parsed();
+        assert_eq!(Some(parsed), vary.typed_get::<Vary>());
+
+        let differing = map("content-length", &["5", "6"]);
+        assert_eq!(differing.typed_get::<WithRaw<ContentLength>>(), None);
+        assert_eq!(HeaderMap::new().typed_get::<WithRaw<ContentLength>>(), None);
+    }
+
+    #[test]
+    fn mode_and_limits_are_forwarded() {
+        let repeated = map("content-length", &["5", "5"]);
+        assert!(repeated.typed_get::<WithRaw<ContentLength>>().is_some());
+        assert_eq!(repeated.typed_get_with::<WithRaw<ContentLength>>(ParseMode::Strict), None);
+
+        let limits = DecodeLimits {
+            max_list_items: 1,
+            ..DecodeLimits::default()
+        };
+        let cc = map("cache-control", &["no-cache, private"]);
+        assert_eq!(
+            cc.typed_get_with_limits::<WithRaw<CacheControl>>(limits),
+            Err(DecodeError::TooLarge),
+        );
+    }
+}
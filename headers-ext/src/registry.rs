@@ -0,0 +1,422 @@
+//! Every header this crate decodes, for tools that work on any of them,
+//! such as linters and debuggers.
+//!
+//! The same table is behind `validate::scan` and the fuzzing entry point.
+//!
+//! # Example
+//!
+//! ```
+//! # extern crate headers_ext as headers;
+//! use headers::HeaderMap;
+//! use headers::registry;
+//!
+//! let date = registry::lookup(&"date".parse().unwrap()).unwrap();
+//! assert!(date.is_single_value());
+//! assert_eq!(
+//!     date.canonicalize(&"Sunday, 06-Nov-94 08:49:37 GMT".parse().unwrap()).unwrap(),
+//!     "Sun, 06 Nov 1994 08:49:37 GMT",
+//! );
+//!
+//! assert!(registry::lookup(&"x-custom".parse().unwrap()).is_none());
+//!
+//! let mut map = HeaderMap::new();
+//! map.insert("date", "Sunday, 06-Nov-94 08:49:37 GMT".parse().unwrap());
+//! map.insert("content-length", "ten".parse().unwrap());
+//! registry::canonicalize_map(&mut map);
+//! assert_eq!(map["date"], "Sun, 06 Nov 1994 08:49:37 GMT");
+//! assert_eq!(map["content-length"], "ten");
+//! ```
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use headers_core::decode::from_value;
+use headers_core::encode::to_values;
+
+use ::{Header, HeaderMap, HeaderName, HeaderValue, SingleValueHeader};
+use common::*;
+use list::ListHeader;
+use validate::IssueKind;
+
+/// What the crate knows of a header, from [`lookup`](fn.lookup.html).
+pub struct HeaderMeta {
+    /// The name of the type, such as `Authorization<Basic>`.
+    #[cfg(test)]
+    pub(crate) type_name: &'static str,
+    pub(crate) name: fn() -> &'static HeaderName,
+    /// Whether every valid value of the header decodes as this type.
+    ///
+    /// This isn't so of `Authorization<Basic>`, which fails on other
+    /// schemes.
+    pub(crate) complete: bool,
+    list: fn() -> bool,
+    single_value: fn() -> bool,
+    canonicalize: fn(&HeaderValue) -> Option<HeaderValue>,
+    pub(crate) fuzz: fn(&[HeaderValue]),
+    pub(crate) check: fn(&HeaderMap) -> Option<IssueKind>,
+}
+
+impl HeaderMeta {
+    /// The name of the header.
+    pub fn name(&self) -> &'static HeaderName {
+        (self.name)()
+    }
+
+    /// Check if the header is a comma-separated list, whose field lines
+    /// can be split or joined, as `list::ListHeader` says.
+    pub fn is_list(&self) -> bool {
+        (self.list)()
+    }
+
+    /// Check if the header encodes to a single field line, as
+    /// `SingleValueHeader` says.
+    ///
+    /// Most lists do too, joining their elements with commas. A header
+    /// that's neither, such as `Set-Cookie`, has a field line per item.
+    pub fn is_single_value(&self) -> bool {
+        (self.single_value)()
+    }
+
+    /// Decode a value of the header and encode it again, returning `None`
+    /// if it doesn't decode.
+    ///
+    /// Types that keep the value they decoded, such as `ContentType`,
+    /// encode it unchanged.
+    ///
+    /// Some headers have several types, such as `Authorization`, which has
+    /// one per scheme. The value is decoded with the first that can.
+    pub fn canonicalize(&self, value: &HeaderValue) -> Option<HeaderValue> {
+        let name = self.name();
+        HEADERS
+            .iter()
+            .filter(|meta| meta.name() == name)
+            .find_map(|meta| (meta.canonicalize)(value))
+    }
+}
+
+impl fmt::Debug for HeaderMeta {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("HeaderMeta")
+            .field("name", self.name())
+            .field("list", &self.is_list())
+            .field("single_value", &self.is_single_value())
+            .finish()
+    }
+}
+
+/// Find what the crate knows of the header `name`, if it has a type for
+/// it.
+pub fn lookup(name: &HeaderName) -> Option<&'static HeaderMeta> {
+    HEADERS.iter().find(|meta| meta.name() == name)
+}
+
+/// Replace every value of a header the crate has a type for with its
+/// canonical form, as `HeaderMeta::canonicalize` does.
+///
+/// Each field line is canonicalized on its own, so their number doesn't
+/// change. Headers the crate doesn't know, and values that don't decode,
+/// are left as they are.
+pub fn canonicalize_map(map: &mut HeaderMap) {
+    let names = map.keys().cloned().collect::<Vec<_>>();
+    for name in names {
+        let meta = match lookup(&name) {
+            Some(meta) => meta,
+            None => continue,
+        };
+        let values = map
+            .get_all(&name)
+            .iter()
+            .map(|value| meta.canonicalize(value).unwrap_or_else(|| value.clone()))
+            .collect::<Vec<_>>();
+        let mut values = values.into_iter();
+        if let Some(first) = values.next() {
+            map.insert(name.clone(), first);
+        }
+        for value in values {
+            map.append(name.clone(), value);
+        }
+    }
+}
+
+fn canonicalize<H: Header>(value: &HeaderValue) -> Option<HeaderValue> {
+    let header = from_value::<H>(value).ok()?;
+    let mut values = to_values(&header);
+    if values.len() == 1 {
+        values.pop()
+    } else {
+        None
+    }
+}
+
+// Finds whether a header type implements `ListHeader` or
+// `SingleValueHeader`, by calling a method on a `&Probe<H>` of the
+// concrete type. The impls for `Probe<H>` take `&self` and only apply with
+// the trait, and are picked over the fallbacks for `&Probe<H>`, which
+// take `&&self`.
+struct Probe<H>(PhantomData<H>);
+
+trait IsList {
+    fn is_list(&self) -> bool {
+        true
+    }
+}
+
+impl<H: ListHeader> IsList for Probe<H> {}
+
+trait NotList {
+    fn is_list(&self) -> bool {
+        false
+    }
+}
+
+impl<H> NotList for &Probe<H> {}
+
+trait IsSingleValue {
+    fn is_single_value(&self) -> bool {
+        true
+    }
+}
+
+impl<H: SingleValueHeader> IsSingleValue for Probe<H> {}
+
+trait NotSingleValue {
+    fn is_single_value(&self) -> bool {
+        false
+    }
+}
+
+impl<H> NotSingleValue for &Probe<H> {}
+
+/// Register header types, in `HEADERS`.
+///
+/// Types that only decode some values of their header are marked
+/// `=> partial`.
+macro_rules! registry {
+    (@complete) => { true };
+    (@complete partial) => { false };
+    ($($(#[$attr:meta])* $ty:ty $(=> $partial:ident)?,)+) => {
+        pub(crate) static HEADERS: &[HeaderMeta] = &[
+            $($(#[$attr])* HeaderMeta {
+                #[cfg(test)]
+                type_name: stringify!($ty),
+                name: <$ty as Header>::name,
+                complete: registry!(@complete $($partial)?),
+                list: || (&Probe::<$ty>(PhantomData)).is_list(),
+                single_value: || (&Probe::<$ty>(PhantomData)).is_single_value(),
+                canonicalize: canonicalize::<$ty>,
+                fuzz: ::fuzz::decode::<$ty>,
+                check: ::validate::check::<$ty>,
+            },)+
+        ];
+    };
+}
+
+registry! {
+    AcceptCh,
+    AcceptPatch,
+    AcceptPost,
+    AcceptRanges,
+    #[cfg(feature = "cors")]
+    AccessControlAllowCredentials,
+    #[cfg(feature = "cors")]
+    AccessControlAllowHeaders,
+    #[cfg(feature = "cors")]
+    AccessControlAllowMethods,
+    #[cfg(feature = "cors")]
+    AccessControlAllowOrigin,
+    #[cfg(feature = "cors")]
+    AccessControlExposeHeaders,
+    #[cfg(feature = "cors")]
+    AccessControlMaxAge,
+    #[cfg(feature = "cors")]
+    AccessControlRequestHeaders,
+    #[cfg(feature = "cors")]
+    AccessControlRequestMethod,
+    #[cfg(feature = "caching")]
+    Age,
+    Allow,
+    #[cfg(feature = "auth")]
+    Authorization<Basic> => partial,
+    #[cfg(feature = "auth")]
+    Authorization<Bearer> => partial,
+    #[cfg(feature = "caching")]
+    CacheControl,
+    #[cfg(feature = "caching")]
+    CacheStatus,
+    Connection,
+    ContentDisposition,
+    ContentEncoding,
+    ContentLength,
+    ContentLocation,
+    ContentMd5,
+    ContentRange,
+    #[cfg(feature = "security")]
+    ContentSecurityPolicy,
+    ContentTransferEncoding,
+    ContentType,
+    Cookie,
+    CriticalCh,
+    Date,
+    #[cfg(feature = "conditional")]
+    ETag,
+    Expect,
+    #[cfg(feature = "security")]
+    ExpectCt,
+    #[cfg(feature = "caching")]
+    Expires,
+    FromEmail,
+    Host,
+    #[cfg(feature = "conditional")]
+    IfMatch,
+    #[cfg(feature = "conditional")]
+    IfModifiedSince,
+    #[cfg(feature = "conditional")]
+    IfNoneMatch,
+    #[cfg(feature = "conditional")]
+    IfRange,
+    #[cfg(feature = "conditional")]
+    IfUnmodifiedSince,
+    KeepAlive,
+    #[cfg(feature = "conditional")]
+    LastModified,
+    Link,
+    Location,
+    MimeVersion,
+    Origin,
+    #[cfg(feature = "caching")]
+    Pragma,
+    Priority,
+    #[cfg(feature = "auth")]
+    ProxyAuthorization<Basic> => partial,
+    #[cfg(feature = "auth")]
+    ProxyAuthorization<Bearer> => partial,
+    #[cfg(feature = "proxy")]
+    ProxyStatus,
+    Range,
+    Referer,
+    #[cfg(feature = "security")]
+    ReferrerPolicy,
+    Refresh,
+    RetryAfter,
+    SecChUa,
+    SecChUaMobile,
+    SecChUaPlatform,
+    #[cfg(feature = "websocket")]
+    SecWebsocketAccept,
+    #[cfg(feature = "websocket")]
+    SecWebsocketKey,
+    #[cfg(feature = "websocket")]
+    SecWebsocketVersion,
+    Server,
+    SetCookie,
+    #[cfg(feature = "auth")]
+    Signature,
+    #[cfg(feature = "auth")]
+    SignatureInput,
+    SoapAction,
+    SourceMap,
+    #[cfg(feature = "security")]
+    StrictTransportSecurity,
+    Te,
+    TransferEncoding,
+    Upgrade,
+    UserAgent,
+    #[cfg(feature = "caching")]
+    Vary,
+    #[cfg(feature = "proxy")]
+    Via,
+    #[cfg(feature = "caching")]
+    Warning,
+    #[cfg(feature = "proxy")]
+    XAccelBuffering,
+    #[cfg(feature = "proxy")]
+    XAccelExpires,
+    #[cfg(feature = "proxy")]
+    XAccelLimitRate,
+    #[cfg(feature = "proxy")]
+    XAccelRedirect,
+    #[cfg(feature = "auth")]
+    XApiKey,
+    #[cfg(feature = "security")]
+    XContentTypeOptions,
+    #[cfg(feature = "security")]
+    XFrameOptions,
+    #[cfg(feature = "proxy")]
+    XRealIp,
+    #[cfg(feature = "proxy")]
+    XSendfile,
+    XSourceMap,
+}
+
+#[cfg(test)]
+mod tests {
+    use ::{HeaderMap, HeaderName, HeaderValue};
+    use super::{canonicalize_map, lookup, HEADERS};
+
+    fn name(s: &'static str) -> HeaderName {
+        HeaderName::from_static(s)
+    }
+
+    fn value(s: &'static str) -> HeaderValue {
+        HeaderValue::from_static(s)
+    }
+
+    #[test]
+    fn kinds() {
+        let allow = lookup(&name("allow")).unwrap();
+        assert_eq!(allow.name(), "allow");
+        assert!(allow.is_list());
+        assert!(allow.is_single_value());
+
+        let length = lookup(&name("content-length")).unwrap();
+        assert!(!length.is_list());
+        assert!(length.is_single_value());
+
+        let set_cookie = lookup(&name("set-cookie")).unwrap();
+        assert!(!set_cookie.is_list());
+        assert!(!set_cookie.is_single_value());
+
+        assert!(lookup(&name("x-custom")).is_none());
+    }
+
+    #[test]
+    fn only_set_cookie_has_several_lines() {
+        let neither = HEADERS
+            .iter()
+            .filter(|meta| !meta.is_list() && !meta.is_single_value())
+            .map(|meta| meta.type_name)
+            .collect::<Vec<_>>();
+        assert_eq!(neither, ["SetCookie"]);
+    }
+
+    #[test]
+    fn canonicalize() {
+        let length = lookup(&name("content-length")).unwrap();
+        assert_eq!(length.canonicalize(&value("010")).unwrap(), "10");
+        assert_eq!(length.canonicalize(&value("ten")), None);
+
+        let ct = lookup(&name("content-type")).unwrap();
+        assert_eq!(ct.canonicalize(&value("Text/HTML")).unwrap(), "Text/HTML");
+
+        // Tries each scheme's type.
+        let auth = lookup(&name("authorization")).unwrap();
+        assert!(auth.canonicalize(&value("Bearer abc")).is_some());
+        assert!(auth.canonicalize(&value("Basic YWxhZGRpbjpvcGVuc2VzYW1l")).is_some());
+        assert_eq!(auth.canonicalize(&value("Digest abc")), None);
+    }
+
+    #[test]
+    fn canonicalize_map_keeps_lines() {
+        let mut map = HeaderMap::new();
+        map.append("content-length", value("010"));
+        map.append("content-length", value("ten"));
+        map.append("date", value("Sun Nov  6 08:49:37 1994"));
+        map.append("x-custom", value("010"));
+        canonicalize_map(&mut map);
+
+        let lengths = map.get_all("content-length").iter().collect::<Vec<_>>();
+        assert_eq!(lengths, [&value("10"), &value("ten")]);
+        assert_eq!(map["date"], "Sun, 06 Nov 1994 08:49:37 GMT");
+        assert_eq!(map["x-custom"], "010");
+    }
+}
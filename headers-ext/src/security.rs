@@ -0,0 +1,198 @@
+//! Security-related response headers, and a bundle to set them together.
+
+use std::time::Duration;
+
+pub use common::{
+    ContentSecurityPolicy,
+    ReferrerPolicy,
+    StrictTransportSecurity,
+    XContentTypeOptions,
+    XFrameOptions,
+};
+
+use {Header, HeaderMap, HeaderMapExt};
+
+/// A set of security headers to add to responses.
+///
+/// Only the headers that were set are applied, and by default, any a
+/// response already has are left alone, so handlers can still choose
+/// their own.
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::{HeaderMap, HeaderMapExt, XFrameOptions};
+/// use headers::security::SecurityHeaders;
+///
+/// let security = SecurityHeaders::strict();
+///
+/// let mut map = HeaderMap::new();
+/// map.typed_insert(XFrameOptions::SameOrigin);
+/// security.apply(&mut map);
+///
+/// assert_eq!(map["x-frame-options"], "SAMEORIGIN");
+/// assert_eq!(map["x-content-type-options"], "nosniff");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SecurityHeaders {
+    hsts: Option<StrictTransportSecurity>,
+    frame_options: Option<XFrameOptions>,
+    content_type_options: Option<XContentTypeOptions>,
+    referrer_policy: Option<ReferrerPolicy>,
+    csp: Option<ContentSecurityPolicy>,
+    overwrite: bool,
+}
+
+impl SecurityHeaders {
+    /// Create an empty set, which applies nothing.
+    pub fn new() -> SecurityHeaders {
+        SecurityHeaders::default()
+    }
+
+    /// Create a set with strict defaults:
+    ///
+    /// - `Strict-Transport-Security: max-age=63072000; includeSubdomains`
+    /// - `X-Frame-Options: DENY`
+    /// - `X-Content-Type-Options: nosniff`
+    /// - `Referrer-Policy: no-referrer`
+    ///
+    /// There's no default `Content-Security-Policy`, since it depends on
+    /// what the responses load. Add one with `with_csp`.
+    pub fn strict() -> SecurityHeaders {
+        SecurityHeaders::new()
+            .with_hsts(Duration::from_secs(2 * 365 * 24 * 60 * 60), true)
+            .with_frame_options(XFrameOptions::Deny)
+            .with_nosniff()
+            .with_referrer_policy(ReferrerPolicy::NO_REFERRER)
+    }
+
+    /// Set the `Strict-Transport-Security` header.
+    pub fn with_hsts(mut self, max_age: Duration, include_subdomains: bool) -> Self {
+        self.hsts = Some(if include_subdomains {
+            StrictTransportSecurity::including_subdomains(max_age)
+        } else {
+            StrictTransportSecurity::excluding_subdomains(max_age)
+        });
+        self
+    }
+
+    /// Set the `X-Frame-Options` header.
+    pub fn with_frame_options(mut self, frame_options: XFrameOptions) -> Self {
+        self.frame_options = Some(frame_options);
+        self
+    }
+
+    /// Set `X-Content-Type-Options: nosniff`.
+    pub fn with_nosniff(mut self) -> Self {
+        self.content_type_options = Some(XContentTypeOptions::nosniff());
+        self
+    }
+
+    /// Set the `Referrer-Policy` header.
+    pub fn with_referrer_policy(mut self, policy: ReferrerPolicy) -> Self {
+        self.referrer_policy = Some(policy);
+        self
+    }
+
+    /// Set the `Content-Security-Policy` header.
+    pub fn with_csp(mut self, csp: ContentSecurityPolicy) -> Self {
+        self.csp = Some(csp);
+        self
+    }
+
+    /// Set whether `apply` replaces headers the map already contains.
+    pub fn with_overwrite(mut self, overwrite: bool) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    /// Insert the headers that were set into `map`.
+    ///
+    /// Unless overwriting, a header the map already contains, with any
+    /// value, is kept as is.
+    pub fn apply(&self, map: &mut HeaderMap) {
+        self.insert(map, &self.hsts);
+        self.insert(map, &self.frame_options);
+        self.insert(map, &self.content_type_options);
+        self.insert(map, &self.referrer_policy);
+        self.insert(map, &self.csp);
+    }
+
+    fn insert<H: Header + Clone>(&self, map: &mut HeaderMap, header: &Option<H>) {
+        if let Some(ref header) = *header {
+            if self.overwrite || !map.contains_key(H::name()) {
+                map.typed_insert(header.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use HeaderValue;
+
+    #[test]
+    fn strict() {
+        let mut map = HeaderMap::new();
+        SecurityHeaders::strict().apply(&mut map);
+
+        assert_eq!(map.len(), 4);
+        assert_eq!(map["strict-transport-security"], "max-age=63072000; includeSubdomains");
+        assert_eq!(map["x-frame-options"], "DENY");
+        assert_eq!(map["x-content-type-options"], "nosniff");
+        assert_eq!(map["referrer-policy"], "no-referrer");
+    }
+
+    #[test]
+    fn empty_applies_nothing() {
+        let mut map = HeaderMap::new();
+        SecurityHeaders::new().apply(&mut map);
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn only_what_was_set() {
+        let mut map = HeaderMap::new();
+        SecurityHeaders::new()
+            .with_hsts(Duration::from_secs(60), false)
+            .with_csp(ContentSecurityPolicy::from_static("default-src 'self'"))
+            .apply(&mut map);
+
+        assert_eq!(map.len(), 2);
+        assert_eq!(map["strict-transport-security"], "max-age=60");
+        assert_eq!(map["content-security-policy"], "default-src 'self'");
+    }
+
+    #[test]
+    fn keeps_existing_headers() {
+        let mut map = HeaderMap::new();
+        map.typed_insert(XFrameOptions::SameOrigin);
+        // even ones that don't decode
+        map.insert("referrer-policy", HeaderValue::from_static("nope"));
+
+        SecurityHeaders::strict().apply(&mut map);
+
+        assert_eq!(map["x-frame-options"], "SAMEORIGIN");
+        assert_eq!(map["referrer-policy"], "nope");
+        assert_eq!(map["x-content-type-options"], "nosniff");
+        assert_eq!(map.len(), 4);
+    }
+
+    #[test]
+    fn overwrite() {
+        let mut map = HeaderMap::new();
+        map.append("x-frame-options", HeaderValue::from_static("SAMEORIGIN"));
+        map.append("x-frame-options", HeaderValue::from_static("ALLOW-FROM https://example.com"));
+        map.insert("x-powered-by", HeaderValue::from_static("hyper"));
+
+        SecurityHeaders::strict().with_overwrite(true).apply(&mut map);
+
+        let values = map.get_all("x-frame-options").iter().collect::<Vec<_>>();
+        assert_eq!(values, ["DENY"]);
+        assert_eq!(map["x-powered-by"], "hyper");
+    }
+}
@@ -0,0 +1,459 @@
+//! Building the signature base of HTTP message signatures, defined in
+//! [RFC9421](https://tools.ietf.org/html/rfc9421).
+//!
+//! The signature base is the string a signature signs: a line for each
+//! component of the message listed in `Signature-Input`, with its value.
+//! Signing it, and checking a `Signature` against it, is left to the
+//! cryptography library of your choice.
+//!
+//! # Example
+//!
+//! ```
+//! # extern crate headers_ext as headers;
+//! # extern crate http;
+//! use headers::{HeaderMap, HeaderMapExt, SignatureComponent, SignatureInput, SignatureParams};
+//! use headers::signature::signature_base;
+//! use http::{Method, Uri};
+//!
+//! # fn main() {
+//! let mut map = HeaderMap::new();
+//! map.insert("host", "example.com".parse().unwrap());
+//! map.insert("content-digest", "sha-256=:X48E9qOokqqrvdts8nOJRJN3OWDUoyWxBf7kbu9DBPE=:".parse().unwrap());
+//!
+//! let params = SignatureParams::new(vec![
+//!     SignatureComponent::new("@method"),
+//!     SignatureComponent::new("@authority"),
+//!     SignatureComponent::new("content-digest"),
+//! ])
+//!     .with_created(1618884473)
+//!     .with_keyid("k1");
+//! map.typed_insert(SignatureInput::new().with("sig1", params));
+//!
+//! let target: Uri = "/foo?a=b".parse().unwrap();
+//! let base = signature_base(&map, &Method::POST, &target, "sig1").unwrap();
+//! assert_eq!(base, "\
+//!     \"@method\": POST\n\
+//!     \"@authority\": example.com\n\
+//!     \"content-digest\": sha-256=:X48E9qOokqqrvdts8nOJRJN3OWDUoyWxBf7kbu9DBPE=:\n\
+//!     \"@signature-params\": (\"@method\" \"@authority\" \"content-digest\");created=1618884473;keyid=\"k1\"\
+//! ");
+//! # }
+//! ```
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::fmt::Write;
+
+use base64;
+use http::header::HOST;
+use http::{Method, Uri};
+
+use util::structured::{self, BareItem, List};
+use {HeaderMap, HeaderMapExt, HeaderName, SignatureComponent, SignatureInput};
+
+/// Build the signature base of the request signature labeled `label` in
+/// the `Signature-Input` of `headers`.
+///
+/// `method` and `target` are those of the request, and give the derived
+/// components, such as `@method` and `@path`. `@target-uri` and `@scheme`
+/// need `target` to be absolute. `@authority` is taken from `target`, or
+/// the `Host` header if it has none.
+///
+/// To sign a request, insert its `SignatureInput` first, then sign the
+/// returned string.
+///
+/// # Errors
+///
+/// Fails if the label isn't in a valid `Signature-Input`, if a covered
+/// component is missing from the request or listed twice, or if it's one
+/// that isn't supported: `@status`, which is only in responses, and
+/// components with the `req` or `tr` parameters.
+pub fn signature_base(
+    headers: &HeaderMap,
+    method: &Method,
+    target: &Uri,
+    label: &str,
+) -> Result<String, SignatureBaseError> {
+    let input = headers
+        .typed_get::<SignatureInput>()
+        .ok_or(SignatureBaseError::new("no valid Signature-Input"))?;
+    let params = input
+        .get(label)
+        .ok_or(SignatureBaseError::new("label isn't in Signature-Input"))?;
+
+    let request = Request { headers, method, target };
+    let mut base = String::new();
+    let mut seen = Vec::new();
+    for component in params.components() {
+        let id = component.to_string();
+        if seen.contains(&id) {
+            return Err(SignatureBaseError::new("component is repeated").with(component));
+        }
+        for value in request.values(component)? {
+            // Values can't contain newlines, so lines can't be forged.
+            writeln!(base, "{}: {}", id, value).expect("writing to a String can't fail");
+        }
+        seen.push(id);
+    }
+    write!(base, "\"@signature-params\": {}", params).expect("writing to a String can't fail");
+    Ok(base)
+}
+
+/// An error returned when a signature base can't be built.
+#[derive(Debug)]
+pub struct SignatureBaseError {
+    reason: &'static str,
+    component: Option<String>,
+}
+
+impl SignatureBaseError {
+    fn new(reason: &'static str) -> SignatureBaseError {
+        SignatureBaseError {
+            reason,
+            component: None,
+        }
+    }
+
+    fn with(mut self, component: &SignatureComponent) -> SignatureBaseError {
+        self.component = Some(component.to_string());
+        self
+    }
+}
+
+impl fmt::Display for SignatureBaseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "can't build signature base: {}", self.reason)?;
+        match self.component {
+            Some(ref component) => write!(f, ": {}", component),
+            None => Ok(()),
+        }
+    }
+}
+
+impl StdError for SignatureBaseError {}
+
+struct Request<'a> {
+    headers: &'a HeaderMap,
+    method: &'a Method,
+    target: &'a Uri,
+}
+
+impl<'a> Request<'a> {
+    /// Get the values of `component`, one per line of the base.
+    ///
+    /// Only `@query-param` can have several, for a repeated parameter.
+    fn values(&self, component: &SignatureComponent) -> Result<Vec<String>, SignatureBaseError> {
+        let unsupported = || SignatureBaseError::new("component isn't supported").with(component);
+        let missing = || SignatureBaseError::new("component is missing").with(component);
+        let invalid = || SignatureBaseError::new("field value is invalid").with(component);
+
+        let derived = component.is_derived();
+        for (key, value) in component.params() {
+            let valid = match key {
+                "name" => derived && value.as_str().is_some(),
+                "key" => !derived && value.as_str().is_some(),
+                "sf" | "bs" => !derived && *value == BareItem::Boolean(true),
+                _ => false,
+            };
+            if !valid {
+                return Err(unsupported());
+            }
+        }
+
+        if derived {
+            if component.name() != "@query-param" && component.query_param_name().is_some() {
+                return Err(unsupported());
+            }
+            return self.derived(component)?.ok_or_else(missing);
+        }
+
+        let name = HeaderName::from_bytes(component.name().as_bytes()).map_err(|_| unsupported())?;
+        let lines = self.headers.get_all(&name);
+        if lines.iter().next().is_none() {
+            return Err(missing());
+        }
+        let trim = |bytes: &'a [u8]| {
+            let start = bytes.iter().position(|&b| b != b' ' && b != b'\t').unwrap_or(bytes.len());
+            let end = bytes.iter().rposition(|&b| b != b' ' && b != b'\t').map_or(start, |i| i + 1);
+            &bytes[start..end]
+        };
+
+        let value = if component.is_bs() {
+            if component.is_sf() || component.key().is_some() {
+                return Err(unsupported());
+            }
+            let lines = lines
+                .iter()
+                .map(|line| format!(":{}:", base64::encode(trim(line.as_bytes()))))
+                .collect::<Vec<_>>();
+            lines.join(", ")
+        } else if let Some(key) = component.key() {
+            let dict = structured::parse_dictionary(lines.iter()).ok_or_else(invalid)?;
+            dict.get(key).ok_or_else(missing)?.to_string()
+        } else if component.is_sf() {
+            match structured::parse_dictionary(lines.iter()) {
+                Some(dict) => dict.to_string(),
+                None => {
+                    let list = structured::parse_list(lines.iter()).ok_or_else(invalid)?;
+                    List(&list).to_string()
+                },
+            }
+        } else {
+            let mut value = String::new();
+            for (i, line) in lines.iter().enumerate() {
+                let line = ::std::str::from_utf8(trim(line.as_bytes())).map_err(|_| invalid())?;
+                if i > 0 {
+                    value.push_str(", ");
+                }
+                value.push_str(line);
+            }
+            value
+        };
+        Ok(vec![value])
+    }
+
+    /// Get the values of a derived component, or `None` if the request
+    /// doesn't have it.
+    fn derived(&self, component: &SignatureComponent) -> Result<Option<Vec<String>>, SignatureBaseError> {
+        let target = self.target;
+        let value = match component.name() {
+            "@method" => Some(self.method.as_str().to_owned()),
+            "@target-uri" => {
+                if target.scheme_part().is_some() && target.authority_part().is_some() {
+                    Some(target.to_string())
+                } else {
+                    None
+                }
+            },
+            "@authority" => {
+                let host = self.headers.get(HOST).and_then(|host| host.to_str().ok());
+                target.authority_part().map(|authority| authority.as_str()).or(host).map(str::to_ascii_lowercase)
+            },
+            "@scheme" => target.scheme_str().map(str::to_ascii_lowercase),
+            "@request-target" => Some(match target.path_and_query() {
+                Some(path_and_query) => path_and_query.as_str().to_owned(),
+                None => target.path().to_owned(),
+            }),
+            "@path" => Some(match target.path() {
+                "" => "/".to_owned(),
+                path => path.to_owned(),
+            }),
+            "@query" => Some(format!("?{}", target.query().unwrap_or(""))),
+            "@query-param" => {
+                let name = component
+                    .query_param_name()
+                    .ok_or_else(|| SignatureBaseError::new("@query-param has no name").with(component))?;
+                let name = form_reencode(name);
+                let values = target
+                    .query()
+                    .unwrap_or("")
+                    .split('&')
+                    .filter(|pair| !pair.is_empty())
+                    .filter_map(|pair| {
+                        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                        if form_reencode(key) == name {
+                            Some(form_reencode(value))
+                        } else {
+                            None
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                return Ok(if values.is_empty() { None } else { Some(values) });
+            },
+            _ => return Err(SignatureBaseError::new("component isn't supported").with(component)),
+        };
+        Ok(value.map(|value| vec![value]))
+    }
+}
+
+/// Decode a part of an `application/x-www-form-urlencoded` query and
+/// encode it again, percent-encoding every byte but alphanumerics and
+/// `*-._`, as the RFC normalizes `@query-param`.
+fn form_reencode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = |i: usize| bytes.get(i).and_then(|&b| (b as char).to_digit(16));
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' => match (hex(i + 1), hex(i + 2)) {
+                (Some(hi), Some(lo)) => {
+                    decoded.push((hi << 4 | lo) as u8);
+                    i += 2;
+                },
+                _ => decoded.push(b'%'),
+            },
+            b => decoded.push(b),
+        }
+        i += 1;
+    }
+
+    let mut out = String::with_capacity(decoded.len());
+    for b in decoded {
+        if b.is_ascii_alphanumeric() || b"*-._".contains(&b) {
+            out.push(b as char);
+        } else {
+            write!(out, "%{:02X}", b).expect("writing to a String can't fail");
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use http::{Method, Uri};
+
+    use super::signature_base;
+    use {HeaderMap, HeaderMapExt, SignatureComponent, SignatureInput, SignatureParams};
+
+    fn request(pairs: &[(&'static str, &str)], input: &str) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for &(name, value) in pairs {
+            map.append(name, value.parse().unwrap());
+        }
+        map.append("signature-input", input.parse().unwrap());
+        map
+    }
+
+    fn base(map: &HeaderMap, target: &str) -> Result<String, String> {
+        let target = target.parse::<Uri>().unwrap();
+        signature_base(map, &Method::POST, &target, "sig").map_err(|e| e.to_string())
+    }
+
+    const DIGEST: &str = "sha-512=:WZDPaVn/7XgHaAy8pmojAkGWoRx2UFChF41A2svX+TaPm+AbwAgBWnrIiYllu7BNNyealdVLvRwEmTHWXvJwew==:";
+
+    #[test]
+    fn rfc_examples() {
+        // RFC 9421, appendix B.2.2.
+        let map = request(
+            &[("host", "example.com"), ("content-digest", DIGEST)],
+            "sig=(\"@authority\" \"content-digest\" \"@query-param\";name=\"Pet\")\
+             ;created=1618884473;keyid=\"test-key-rsa-pss\";tag=\"header-example\"",
+        );
+        assert_eq!(base(&map, "/foo?param=Value&Pet=dog").unwrap(), format!(
+            "\"@authority\": example.com\n\
+             \"content-digest\": {}\n\
+             \"@query-param\";name=\"Pet\": dog\n\
+             \"@signature-params\": (\"@authority\" \"content-digest\" \"@query-param\";name=\"Pet\")\
+             ;created=1618884473;keyid=\"test-key-rsa-pss\";tag=\"header-example\"",
+            DIGEST,
+        ));
+
+        // Appendix B.2.1, covering nothing.
+        let map = request(&[], "sig=();created=1618884473;keyid=\"test-key-rsa-pss\";nonce=\"b3k2pp5k7z-50gnwp.yemd\"");
+        assert_eq!(
+            base(&map, "/").unwrap(),
+            "\"@signature-params\": ();created=1618884473;keyid=\"test-key-rsa-pss\";nonce=\"b3k2pp5k7z-50gnwp.yemd\"",
+        );
+    }
+
+    #[test]
+    fn derived_components() {
+        let map = request(
+            &[("host", "Example.COM")],
+            "sig=(\"@method\" \"@target-uri\" \"@authority\" \"@scheme\" \"@request-target\" \"@path\" \"@query\")",
+        );
+        assert_eq!(base(&map, "https://www.example.com/path?param=value").unwrap(), "\
+            \"@method\": POST\n\
+            \"@target-uri\": https://www.example.com/path?param=value\n\
+            \"@authority\": www.example.com\n\
+            \"@scheme\": https\n\
+            \"@request-target\": /path?param=value\n\
+            \"@path\": /path\n\
+            \"@query\": ?param=value\n\
+            \"@signature-params\": (\"@method\" \"@target-uri\" \"@authority\" \"@scheme\" \"@request-target\" \"@path\" \"@query\")\
+        ");
+
+        let map = request(&[("host", "Example.COM")], "sig=(\"@authority\" \"@query\")");
+        assert_eq!(
+            base(&map, "/path").unwrap(),
+            "\"@authority\": example.com\n\"@query\": ?\n\"@signature-params\": (\"@authority\" \"@query\")",
+        );
+
+        let map = request(&[], "sig=(\"@target-uri\")");
+        assert!(base(&map, "/path").unwrap_err().contains("missing"));
+    }
+
+    #[test]
+    fn query_params() {
+        // RFC 9421, section 2.2.8.
+        let map = request(&[], "sig=(\"@query-param\";name=\"var\" \"@query-param\";name=\"bar\" \
+                                \"@query-param\";name=\"fa%C3%A7ade%22%3A%20\")");
+        let target = "/parameters?var=this%20is%20a%20big%0Avalue&bar=with+plus+whitespace&fa%C3%A7ade%22%3A%20=something";
+        assert_eq!(base(&map, target).unwrap(), "\
+            \"@query-param\";name=\"var\": this%20is%20a%20big%0Avalue\n\
+            \"@query-param\";name=\"bar\": with%20plus%20whitespace\n\
+            \"@query-param\";name=\"fa%C3%A7ade%22%3A%20\": something\n\
+            \"@signature-params\": (\"@query-param\";name=\"var\" \"@query-param\";name=\"bar\" \
+            \"@query-param\";name=\"fa%C3%A7ade%22%3A%20\")\
+        ");
+
+        let map = request(&[], "sig=(\"@query-param\";name=\"a\")");
+        assert_eq!(
+            base(&map, "/?a=1&b=2&a=&a=x%2").unwrap(),
+            "\"@query-param\";name=\"a\": 1\n\
+             \"@query-param\";name=\"a\": \n\
+             \"@query-param\";name=\"a\": x%252\n\
+             \"@signature-params\": (\"@query-param\";name=\"a\")",
+        );
+        assert!(base(&map, "/?b=1").unwrap_err().contains("missing"));
+    }
+
+    #[test]
+    fn fields() {
+        // RFC 9421, section 2.1.
+        let mut map = request(
+            &[
+                ("x-ows-header", "   Leading and trailing whitespace.   "),
+                ("example-header", "value, with, lots"),
+                ("example-header", "of, commas"),
+                ("example-dict", " a=1,    b=2;x=1;y=2,   c=(a   b   c)"),
+            ],
+            "",
+        );
+        let params = SignatureParams::new(vec![
+            SignatureComponent::new("x-ows-header"),
+            SignatureComponent::new("example-header"),
+            SignatureComponent::new("example-header").with_bs(),
+            SignatureComponent::new("example-dict").with_key("c"),
+            SignatureComponent::new("example-dict").with_sf(),
+        ]);
+        map.typed_insert(SignatureInput::new().with("sig", params));
+
+        assert_eq!(base(&map, "/").unwrap(), "\
+            \"x-ows-header\": Leading and trailing whitespace.\n\
+            \"example-header\": value, with, lots, of, commas\n\
+            \"example-header\";bs: :dmFsdWUsIHdpdGgsIGxvdHM=:, :b2YsIGNvbW1hcw==:\n\
+            \"example-dict\";key=\"c\": (a b c)\n\
+            \"example-dict\";sf: a=1, b=2;x=1;y=2, c=(a b c)\n\
+            \"@signature-params\": (\"x-ows-header\" \"example-header\" \"example-header\";bs \
+            \"example-dict\";key=\"c\" \"example-dict\";sf)\
+        ");
+    }
+
+    #[test]
+    fn errors() {
+        let check = |pairs: &[(&'static str, &str)], input: &str, error: &str| {
+            let map = request(pairs, input);
+            assert_eq!(base(&map, "/").unwrap_err(), error, "{:?}", input);
+        };
+
+        check(&[], "other=()", "can't build signature base: label isn't in Signature-Input");
+        check(&[], "sig=(\"date\")", "can't build signature base: component is missing: \"date\"");
+        check(&[("date", "now")], "sig=(\"date\" \"date\")", "can't build signature base: component is repeated: \"date\"");
+        check(&[], "sig=(\"@status\")", "can't build signature base: component isn't supported: \"@status\"");
+        check(&[("date", "now")], "sig=(\"date\";req)", "can't build signature base: component isn't supported: \"date\";req");
+        check(&[("date", "now")], "sig=(\"date\";tr)", "can't build signature base: component isn't supported: \"date\";tr");
+        check(&[("date", "now")], "sig=(\"date\";sf;bs)", "can't build signature base: component isn't supported: \"date\";sf;bs");
+        check(&[], "sig=(\"@method\";key=\"a\")", "can't build signature base: component isn't supported: \"@method\";key=\"a\"");
+        check(&[], "sig=(\"@method\";name=\"a\")", "can't build signature base: component isn't supported: \"@method\";name=\"a\"");
+        check(&[], "sig=(\"@query-param\")", "can't build signature base: @query-param has no name: \"@query-param\"");
+        check(&[], "sig=(\"bad header\")", "can't build signature base: component isn't supported: \"bad header\"");
+        check(&[("x-dict", "a=1")], "sig=(\"x-dict\";key=\"b\")", "can't build signature base: component is missing: \"x-dict\";key=\"b\"");
+        check(&[("x-dict", "a=")], "sig=(\"x-dict\";sf)", "can't build signature base: field value is invalid: \"x-dict\";sf");
+
+        let map = HeaderMap::new();
+        assert_eq!(base(&map, "/").unwrap_err(), "can't build signature base: no valid Signature-Input");
+    }
+}
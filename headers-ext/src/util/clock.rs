@@ -0,0 +1,66 @@
+//! The current time, as used by constructors like `Date::now`.
+//!
+//! It's always `SystemTime::now`, except in this crate's tests, which can
+//! freeze it on their thread with `freeze`.
+
+#[cfg(not(test))]
+pub(crate) fn now() -> ::std::time::SystemTime {
+    ::std::time::SystemTime::now()
+}
+
+#[cfg(test)]
+pub(crate) use self::frozen::{freeze, now};
+
+#[cfg(test)]
+mod frozen {
+    use std::cell::Cell;
+    use std::time::SystemTime;
+
+    thread_local! {
+        static FROZEN: Cell<Option<SystemTime>> = const { Cell::new(None) };
+    }
+
+    pub(crate) fn now() -> SystemTime {
+        FROZEN.with(Cell::get).unwrap_or_else(SystemTime::now)
+    }
+
+    /// Make `now` return `time` on this thread, until the guard is dropped.
+    pub(crate) fn freeze(time: SystemTime) -> Frozen {
+        Frozen(FROZEN.with(|frozen| frozen.replace(Some(time))))
+    }
+
+    pub(crate) struct Frozen(Option<SystemTime>);
+
+    impl Drop for Frozen {
+        fn drop(&mut self) {
+            FROZEN.with(|frozen| frozen.set(self.0));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use super::{freeze, now};
+    use Date;
+
+    #[test]
+    fn freeze_until_dropped() {
+        let time = UNIX_EPOCH + Duration::from_secs(784_111_777);
+        {
+            let _frozen = freeze(time);
+            assert_eq!(now(), time);
+            assert_eq!(Date::now(), Date::from(time));
+
+            let later = time + Duration::from_secs(60);
+            let nested = freeze(later);
+            assert_eq!(now(), later);
+            drop(nested);
+            assert_eq!(now(), time);
+        }
+        assert!(now() > time);
+        assert!(Date::now() > Date::from(time));
+        assert!(now() <= SystemTime::now());
+    }
+}
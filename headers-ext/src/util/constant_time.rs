@@ -0,0 +1,34 @@
+use std::hint::black_box;
+
+/// Compare `a` and `b` in time that depends only on their lengths, not on
+/// where they first differ.
+///
+/// This is best-effort: it folds every byte into one difference, which
+/// the optimizer is kept from short-circuiting, but nothing stops a
+/// compiler or CPU from doing otherwise. Whether the lengths are equal is
+/// leaked.
+pub(crate) fn eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let diff = a
+        .iter()
+        .zip(b)
+        .fold(0u8, |diff, (x, y)| black_box(diff | (x ^ y)));
+    diff == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::eq;
+
+    #[test]
+    fn compares_bytes() {
+        assert!(eq(b"", b""));
+        assert!(eq(b"secret", b"secret"));
+        assert!(!eq(b"secret", b"secreT"));
+        assert!(!eq(b"secret", b"Secret"));
+        assert!(!eq(b"secret", b"secret!"));
+        assert!(!eq(b"", b"a"));
+    }
+}
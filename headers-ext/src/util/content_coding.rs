@@ -0,0 +1,222 @@
+use std::error::Error as StdError;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+use super::token;
+
+/// A content coding, as used by `Accept-Encoding`, `Content-Encoding` and
+/// `TE`, defined in
+/// [RFC7231](https://tools.ietf.org/html/rfc7231#section-3.1.2.1).
+///
+/// Codings are case-insensitive tokens. Parsing lowercases them, and maps
+/// the `x-gzip` and `x-compress` aliases to `Gzip` and `Compress`, as
+/// [RFC7230](https://tools.ietf.org/html/rfc7230#section-4.2) asks.
+/// Comparing does the same, so an `Other("GZIP")` equals `Gzip`.
+///
+/// # Compression crates
+///
+/// Each coding is the format of:
+///
+/// | Coding     | `flate2`              | Others                       |
+/// |------------|-----------------------|------------------------------|
+/// | `Gzip`     | `GzEncoder`           | `async-compression` `Gzip*`  |
+/// | `Deflate`  | `ZlibEncoder`         | `async-compression` `Zlib*`  |
+/// | `Br`       |                       | `brotli`, `async-compression` `Brotli*` |
+/// | `Zstd`     |                       | `zstd`, `async-compression` `Zstd*` |
+/// | `Compress` |                       | LZW, rarely supported        |
+///
+/// Note that the `deflate` coding is the zlib format, with a header and a
+/// checksum, not the raw DEFLATE of `flate2::DeflateEncoder`.
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::ContentCoding;
+///
+/// let coding = "X-Gzip".parse::<ContentCoding>().unwrap();
+/// assert_eq!(coding, ContentCoding::Gzip);
+/// assert_eq!(coding.as_str(), "gzip");
+///
+/// match "zstd".parse().unwrap() {
+///     ContentCoding::Gzip | ContentCoding::Zstd => (),
+///     other => panic!("unexpected {}", other),
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub enum ContentCoding {
+    /// `gzip`, or its alias `x-gzip`.
+    Gzip,
+    /// `deflate`, the zlib format.
+    Deflate,
+    /// `br`, Brotli.
+    Br,
+    /// `zstd`, Zstandard.
+    Zstd,
+    /// `identity`, meaning no coding.
+    Identity,
+    /// `compress`, or its alias `x-compress`, the LZW format of UNIX
+    /// `compress`.
+    Compress,
+    /// Any other coding.
+    Other(String),
+}
+
+/// An error returned when a content coding isn't a token.
+#[derive(Debug)]
+pub struct InvalidContentCoding(());
+
+impl ContentCoding {
+    /// Get the token of this coding.
+    pub fn as_str(&self) -> &str {
+        match *self {
+            ContentCoding::Gzip => "gzip",
+            ContentCoding::Deflate => "deflate",
+            ContentCoding::Br => "br",
+            ContentCoding::Zstd => "zstd",
+            ContentCoding::Identity => "identity",
+            ContentCoding::Compress => "compress",
+            ContentCoding::Other(ref coding) => coding,
+        }
+    }
+
+    // The token that's compared, ignoring case.
+    fn key(&self) -> &str {
+        match *self {
+            ContentCoding::Other(ref coding) => canonical(coding),
+            ref coding => coding.as_str(),
+        }
+    }
+}
+
+fn known(coding: &str) -> Option<ContentCoding> {
+    let known = [
+        ("gzip", ContentCoding::Gzip),
+        ("x-gzip", ContentCoding::Gzip),
+        ("deflate", ContentCoding::Deflate),
+        ("br", ContentCoding::Br),
+        ("zstd", ContentCoding::Zstd),
+        ("identity", ContentCoding::Identity),
+        ("compress", ContentCoding::Compress),
+        ("x-compress", ContentCoding::Compress),
+    ];
+    known
+        .iter()
+        .find(|&&(name, _)| name.eq_ignore_ascii_case(coding))
+        .map(|(_, known)| known.clone())
+}
+
+/// The coding `coding` names, with the `x-gzip` and `x-compress` aliases
+/// replaced, in any case.
+fn canonical(coding: &str) -> &str {
+    match known(coding) {
+        Some(ContentCoding::Gzip) => "gzip",
+        Some(ContentCoding::Compress) => "compress",
+        _ => coding,
+    }
+}
+
+impl PartialEq for ContentCoding {
+    fn eq(&self, other: &ContentCoding) -> bool {
+        self.key().eq_ignore_ascii_case(other.key())
+    }
+}
+
+impl Eq for ContentCoding {}
+
+impl Hash for ContentCoding {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for b in self.key().bytes() {
+            state.write_u8(b.to_ascii_lowercase());
+        }
+    }
+}
+
+impl FromStr for ContentCoding {
+    type Err = InvalidContentCoding;
+
+    fn from_str(s: &str) -> Result<ContentCoding, InvalidContentCoding> {
+        if !token::is_token(s.as_bytes()) {
+            return Err(InvalidContentCoding(()));
+        }
+        Ok(known(s).unwrap_or_else(|| ContentCoding::Other(s.to_ascii_lowercase())))
+    }
+}
+
+impl fmt::Display for ContentCoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Display for InvalidContentCoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid content coding")
+    }
+}
+
+impl StdError for InvalidContentCoding {}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::ContentCoding;
+
+    fn parse(s: &str) -> ContentCoding {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_known() {
+        let cases = [
+            ("gzip", ContentCoding::Gzip),
+            ("GZip", ContentCoding::Gzip),
+            ("x-gzip", ContentCoding::Gzip),
+            ("X-GZIP", ContentCoding::Gzip),
+            ("deflate", ContentCoding::Deflate),
+            ("br", ContentCoding::Br),
+            ("BR", ContentCoding::Br),
+            ("zstd", ContentCoding::Zstd),
+            ("identity", ContentCoding::Identity),
+            ("compress", ContentCoding::Compress),
+            ("x-compress", ContentCoding::Compress),
+        ];
+        for &(s, ref coding) in &cases {
+            let parsed = parse(s);
+            assert_eq!(parsed, *coding, "{:?}", s);
+            assert_eq!(parsed.as_str(), coding.as_str(), "{:?}", s);
+            assert!(!matches!(parsed, ContentCoding::Other(_)), "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn parse_other() {
+        let coding = parse("AES128GCM");
+        assert_eq!(coding, ContentCoding::Other("aes128gcm".into()));
+        assert_eq!(coding.to_string(), "aes128gcm");
+        assert_eq!(parse("x-deflate").as_str(), "x-deflate");
+
+        for s in &["", "gzip,br", "g zip", "gzip;q=1", "\"gzip\""] {
+            assert!(s.parse::<ContentCoding>().is_err(), "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn eq_and_hash_normalize() {
+        assert_eq!(ContentCoding::Other("GZIP".into()), ContentCoding::Gzip);
+        assert_eq!(ContentCoding::Other("x-gzip".into()), ContentCoding::Gzip);
+        assert_eq!(ContentCoding::Other("Foo".into()), ContentCoding::Other("foo".into()));
+        assert_ne!(ContentCoding::Gzip, ContentCoding::Deflate);
+        assert_ne!(ContentCoding::Other("foo".into()), ContentCoding::Identity);
+
+        let set = vec![
+            ContentCoding::Gzip,
+            ContentCoding::Other("X-Gzip".into()),
+            ContentCoding::Other("FOO".into()),
+            ContentCoding::Other("foo".into()),
+        ].into_iter().collect::<HashSet<_>>();
+        assert_eq!(set.len(), 2);
+    }
+}
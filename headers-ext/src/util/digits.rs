@@ -0,0 +1,58 @@
+//! Parsing `1*DIGIT` numbers straight from header bytes.
+//!
+//! These only accept ASCII digits, unlike `str::parse`, which also allows a
+//! leading `+`. Working on bytes skips the UTF-8 check of `to_str`.
+
+/// Parse a non-empty run of ASCII digits, failing if it overflows a `u64`.
+pub(crate) fn parse_u64(s: &[u8]) -> Option<u64> {
+    if s.is_empty() {
+        return None;
+    }
+
+    let mut n: u64 = 0;
+    for &b in s {
+        if !b.is_ascii_digit() {
+            return None;
+        }
+        n = n.checked_mul(10)?.checked_add(u64::from(b - b'0'))?;
+    }
+    Some(n)
+}
+
+/// Parse a non-empty run of ASCII digits, saturating at `u64::MAX`.
+pub(crate) fn parse_u64_saturating(s: &[u8]) -> Option<u64> {
+    if s.is_empty() {
+        return None;
+    }
+
+    let mut n: u64 = 0;
+    for &b in s {
+        if !b.is_ascii_digit() {
+            return None;
+        }
+        n = n.saturating_mul(10).saturating_add(u64::from(b - b'0'));
+    }
+    Some(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_u64, parse_u64_saturating};
+
+    #[test]
+    fn only_digits() {
+        for s in &["", "+1", "-1", "1.5", " 1", "1 ", "1e3", "0x10", "١"] {
+            assert_eq!(parse_u64(s.as_bytes()), None, "{:?}", s);
+            assert_eq!(parse_u64_saturating(s.as_bytes()), None, "{:?}", s);
+        }
+        assert_eq!(parse_u64(b"0"), Some(0));
+        assert_eq!(parse_u64(b"007"), Some(7));
+    }
+
+    #[test]
+    fn overflow() {
+        assert_eq!(parse_u64(b"18446744073709551615"), Some(u64::MAX));
+        assert_eq!(parse_u64(b"18446744073709551616"), None);
+        assert_eq!(parse_u64_saturating(b"18446744073709551616"), Some(u64::MAX));
+    }
+}
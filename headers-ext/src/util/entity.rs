@@ -1,4 +1,10 @@
+use std::error::Error as StdError;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use {HeaderValue};
+use super::{FlatCsv, ParsedCsv};
 
 
 /// An entity tag, defined in [RFC7232](https://tools.ietf.org/html/rfc7232#section-2.3)
@@ -31,34 +37,92 @@ use {HeaderValue};
 /// | `W/"1"` | `W/"2"` | no match          | no match        |
 /// | `W/"1"` | `"1"`   | no match          | match           |
 /// | `"1"`   | `"1"`   | match             | match           |
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::EntityTag;
+///
+/// let tag: EntityTag = "W/\"xyzzy\"".parse().unwrap();
+/// assert!(tag.is_weak());
+/// assert!(tag.weak_eq(&EntityTag::strong("xyzzy").unwrap()));
+/// assert_eq!(tag.to_string(), "W/\"xyzzy\"");
+/// ```
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub(crate) struct EntityTag(HeaderValue);
+pub struct EntityTag(HeaderValue);
+
+/// An error returned when an entity tag is malformed.
+#[derive(Debug)]
+pub struct InvalidEntityTag(());
 
 impl EntityTag {
-    /*
-    /// Constructs a new EntityTag.
-    /// # Panics
-    /// If the tag contains invalid characters.
-    pub fn new(weak: bool, tag: String) -> EntityTag {
-        assert!(check_slice_validity(&tag), "Invalid tag: {:?}", tag);
-        EntityTag { weak: weak, tag: tag }
+    /// Constructs a new strong `EntityTag` with the given opaque tag,
+    /// without the surrounding quotes.
+    ///
+    /// Fails if the tag contains a double quote or a control character.
+    pub fn strong(tag: &str) -> Result<EntityTag, InvalidEntityTag> {
+        EntityTag::new(false, tag)
     }
 
-    /// Constructs a new weak EntityTag.
-    /// # Panics
-    /// If the tag contains invalid characters.
-    pub fn weak(tag: String) -> EntityTag {
+    /// Constructs a new weak `EntityTag` with the given opaque tag,
+    /// without the surrounding quotes.
+    ///
+    /// Fails if the tag contains a double quote or a control character.
+    pub fn weak(tag: &str) -> Result<EntityTag, InvalidEntityTag> {
         EntityTag::new(true, tag)
     }
 
-    /// Constructs a new strong EntityTag.
-    /// # Panics
-    /// If the tag contains invalid characters.
-    pub fn strong(tag: String) -> EntityTag {
-        EntityTag::new(false, tag)
+    fn new(weak: bool, tag: &str) -> Result<EntityTag, InvalidEntityTag> {
+        if !check_slice_validity(tag.as_bytes()) {
+            return Err(InvalidEntityTag(()));
+        }
+
+        let full = if weak {
+            format!("W/\"{}\"", tag)
+        } else {
+            format!("\"{}\"", tag)
+        };
+        HeaderValue::from_bytes(full.as_bytes())
+            .map(EntityTag)
+            .map_err(|_| InvalidEntityTag(()))
+    }
+
+    /// Constructs a weak `EntityTag` from a file's length and modification
+    /// time, which is how many servers generate entity tags for static
+    /// files.
+    ///
+    /// The tag is weak since the modification time is only used with a
+    /// precision of one second.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # extern crate headers_ext as headers;
+    /// use std::fs;
+    /// use headers::{EntityTag, ETag};
+    ///
+    /// # fn main() -> std::io::Result<()> {
+    /// let meta = fs::metadata("index.html")?;
+    /// let tag = EntityTag::from_file_meta(meta.len(), meta.modified()?);
+    /// let etag = ETag::from(tag);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_file_meta(len: u64, modified: SystemTime) -> EntityTag {
+        let secs = modified
+            .duration_since(UNIX_EPOCH)
+            .map(|dur| dur.as_secs())
+            .unwrap_or(0);
+        let full = format!("W/\"{:x}-{:x}\"", secs, len);
+        EntityTag(HeaderValue::from_str(&full).expect("hex digits are a valid entity tag"))
     }
-    */
 
+    /// Constructs an `EntityTag` from its wire form, such as `"xyzzy"` or
+    /// `W/"xyzzy"`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string isn't a valid entity tag.
     pub fn from_static(bytes: &'static str) -> EntityTag {
         let val = HeaderValue::from_static(bytes);
         match EntityTag::from_val(&val) {
@@ -110,6 +174,24 @@ impl EntityTag {
         !self.weak_eq(other)
     }
 
+    /// Parse a tag missing its quotes, such as `xyzzy` or `W/xyzzy`.
+    fn from_unquoted(val: &HeaderValue) -> Option<EntityTag> {
+        let slice = val.as_bytes();
+        let (weak, tag) = match slice {
+            [b'W', b'/', tag @ ..] => (true, tag),
+            tag => (false, tag),
+        };
+        if tag.is_empty() || tag.contains(&b'"') || !check_slice_validity(tag) {
+            return None;
+        }
+
+        let mut quoted = Vec::with_capacity(slice.len() + 2);
+        quoted.extend_from_slice(if weak { b"W/\"" } else { b"\"" });
+        quoted.extend_from_slice(tag);
+        quoted.push(b'"');
+        HeaderValue::from_bytes(&quoted).ok().map(EntityTag)
+    }
+
     pub(crate) fn from_val(val: &HeaderValue) -> Option<EntityTag> {
         let slice = val.as_bytes();
         let length = slice.len();
@@ -123,13 +205,7 @@ impl EntityTag {
             // "<tag>"
             b'"' => 1,
             // W/"<tag>"
-            b'W' => {
-                if length >= 4 && slice[1] == b'/' && slice[2] == b'"' {
-                    3
-                } else {
-                    return None;
-                }
-            },
+            b'W' if length >= 4 && slice[1] == b'/' && slice[2] == b'"' => 3,
             _ => return None,
         };
 
@@ -141,9 +217,42 @@ impl EntityTag {
     }
 }
 
+impl FromStr for EntityTag {
+    type Err = InvalidEntityTag;
+
+    fn from_str(s: &str) -> Result<EntityTag, InvalidEntityTag> {
+        HeaderValue::from_str(s)
+            .ok()
+            .and_then(|val| EntityTag::from_val(&val))
+            .ok_or(InvalidEntityTag(()))
+    }
+}
+
+impl fmt::Display for EntityTag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Tags built from a `&str` are always UTF-8, but decoded ones may
+        // contain arbitrary obs-text.
+        f.write_str(&String::from_utf8_lossy(self.0.as_bytes()))
+    }
+}
+
+impl fmt::Display for InvalidEntityTag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid entity tag")
+    }
+}
+
+impl StdError for InvalidEntityTag {}
+
 impl ::headers_core::decode::TryFromValues for EntityTag {
     fn try_from_values(values: &mut ::Values) -> Option<Self> {
-        EntityTag::from_val(values.next()?)
+        let mode = values.mode();
+        let val = values.next()?;
+        EntityTag::from_val(val).or_else(|| match mode {
+            // Some servers forget the quotes.
+            ::ParseMode::Lenient => EntityTag::from_unquoted(val),
+            ::ParseMode::Strict => None,
+        })
     }
 }
 
@@ -160,27 +269,85 @@ impl<'a> From<&'a EntityTag> for HeaderValue {
 }
 
 
+/// The value of `If-Match` and `If-None-Match`: `*`, or a list of entity
+/// tags, which may be spread over several field lines.
+///
+/// Decoding fails if an item isn't a valid tag, with no leniency for a
+/// lowercase `w/`, or if `*` is combined with anything.
+// Boxing `Tags` would cost the allocation its inline list avoids.
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug)]
+pub(crate) enum EntityTagRange {
+    Any,
+    Tags(ParsedCsv<EntityTag>),
+}
+
+impl EntityTagRange {
+    /// The listed tags, none for `*`.
+    pub(crate) fn tags(&self) -> ::list::Iter<'_, EntityTag> {
+        match *self {
+            EntityTagRange::Any => ::list::Iter::empty(),
+            EntityTagRange::Tags(ref tags) => ::list::Iter::new(tags.iter()),
+        }
+    }
+
+    pub(crate) fn is_any(&self) -> bool {
+        match *self {
+            EntityTagRange::Any => true,
+            EntityTagRange::Tags(_) => false,
+        }
+    }
+}
+
+impl From<EntityTag> for EntityTagRange {
+    fn from(tag: EntityTag) -> EntityTagRange {
+        EntityTagRange::Tags(ParsedCsv::from_items(Some(tag), |tag: &EntityTag| tag.into()))
+    }
+}
+
+// The same tags are equal, however they were split into lines or spaced.
+impl PartialEq for EntityTagRange {
+    fn eq(&self, other: &EntityTagRange) -> bool {
+        self.is_any() == other.is_any() && self.tags().eq(other.tags())
+    }
+}
+
+impl ::headers_core::decode::TryFromValues for EntityTagRange {
+    fn try_from_values(values: &mut ::Values) -> Option<Self> {
+        let csv = FlatCsv::try_from_values(values)?;
+        if csv.iter().any(|item| item == "*") {
+            return if csv.iter().nth(1).is_none() {
+                Some(EntityTagRange::Any)
+            } else {
+                None
+            };
+        }
+        ParsedCsv::strict(csv).map(EntityTagRange::Tags)
+    }
+}
+
+impl<'a> From<&'a EntityTagRange> for HeaderValue {
+    fn from(range: &'a EntityTagRange) -> HeaderValue {
+        match *range {
+            EntityTagRange::Any => HeaderValue::from_static("*"),
+            EntityTagRange::Tags(ref tags) => tags.into(),
+        }
+    }
+}
+
 /// check that each char in the slice is either:
 /// 1. `%x21`, or
 /// 2. in the range `%x23` to `%x7E`, or
 /// 3. above `%x80`
 fn check_slice_validity(slice: &[u8]) -> bool {
-    slice.iter().all(|&c| {
-        // HeaderValue already validates that this doesnt contain control
-        // characters, so we only need to look for DQUOTE (`"`).
-        //
-        // The debug_assert is just in case we use check_slice_validity in
-        // some new context that didnt come from a HeaderValue.
-        debug_assert!(
-            (c >= b'\x21' && c <= b'\x7e') | (c >= b'\x80'),
-            "EntityTag expects HeaderValue to have check for control characters"
-        );
-        c != b'"'
-    })
+    // HeaderValue already rejects control characters, but not spaces or
+    // tabs, which aren't allowed in a tag either.
+    slice.iter().all(|&c| c == b'\x21' || (b'\x23'..=b'\x7e').contains(&c) || c >= b'\x80')
 }
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
     use super::*;
 
     fn parse(slice: &[u8]) -> Option<EntityTag> {
@@ -216,18 +383,48 @@ mod tests {
         fails!(b"\"unmatched-dquotes1");
         fails!(b"unmatched-dquotes2\"");
         fails!(b"\"inner\"quotes\"");
+        fails!(b"\"inner space\"");
+        fails!(b"W/\"inner\ttab\"");
     }
 
-    /*
     #[test]
     fn test_etag_fmt() {
-        assert_eq!(format!("{}", EntityTag::strong("foobar".to_owned())), "\"foobar\"");
-        assert_eq!(format!("{}", EntityTag::strong("".to_owned())), "\"\"");
-        assert_eq!(format!("{}", EntityTag::weak("weak-etag".to_owned())), "W/\"weak-etag\"");
-        assert_eq!(format!("{}", EntityTag::weak("\u{0065}".to_owned())), "W/\"\x65\"");
-        assert_eq!(format!("{}", EntityTag::weak("".to_owned())), "W/\"\"");
+        assert_eq!(EntityTag::strong("foobar").unwrap().to_string(), "\"foobar\"");
+        assert_eq!(EntityTag::strong("").unwrap().to_string(), "\"\"");
+        assert_eq!(EntityTag::weak("weak-etag").unwrap().to_string(), "W/\"weak-etag\"");
+        assert_eq!(EntityTag::weak("\u{0065}").unwrap().to_string(), "W/\"\x65\"");
+        assert_eq!(EntityTag::weak("").unwrap().to_string(), "W/\"\"");
+        assert_eq!(EntityTag::strong("café").unwrap().to_string(), "\"café\"");
+    }
+
+    #[test]
+    fn test_etag_new_invalid() {
+        assert!(EntityTag::strong("inner\"quote").is_err());
+        assert!(EntityTag::weak("space inside").is_err());
+        assert!(EntityTag::strong("tab\t").is_err());
+    }
+
+    #[test]
+    fn test_etag_from_str() {
+        let tag: EntityTag = "W/\"xyzzy\"".parse().unwrap();
+        assert_eq!(tag, EntityTag::weak("xyzzy").unwrap());
+        let tag: EntityTag = "\"\"".parse().unwrap();
+        assert_eq!(tag, EntityTag::strong("").unwrap());
+
+        assert!("xyzzy".parse::<EntityTag>().is_err());
+        assert!("w/\"xyzzy\"".parse::<EntityTag>().is_err());
+        assert!("\"a\nb\"".parse::<EntityTag>().is_err());
+    }
+
+    #[test]
+    fn test_etag_from_file_meta() {
+        let modified = UNIX_EPOCH + Duration::from_secs(0x5c00_0000);
+        let tag = EntityTag::from_file_meta(1024, modified);
+        assert!(tag.is_weak());
+        assert_eq!(tag.to_string(), "W/\"5c000000-400\"");
+        assert!(tag.weak_eq(&EntityTag::from_file_meta(1024, modified)));
+        assert!(!tag.weak_eq(&EntityTag::from_file_meta(1025, modified)));
     }
-    */
 
     #[test]
     fn test_cmp() {
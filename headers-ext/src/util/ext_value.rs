@@ -0,0 +1,105 @@
+//! Extended parameter values, as defined in
+//! [RFC8187](https://tools.ietf.org/html/rfc8187#section-3.2).
+//!
+//! ```text
+//! ext-value     = charset  "'" [ language ] "'" value-chars
+//! value-chars   = *( pct-encoded / attr-char )
+//! pct-encoded   = "%" HEXDIG HEXDIG
+//! attr-char     = ALPHA / DIGIT
+//!               / "!" / "#" / "$" / "&" / "+" / "-" / "."
+//!               / "^" / "_" / "`" / "|" / "~"
+//! ```
+//!
+//! Only the `UTF-8` charset is supported, as recommended by the RFC.
+
+use std::fmt::Write;
+
+fn is_attr_char(b: u8) -> bool {
+    match b {
+        b'!' | b'#' | b'$' | b'&' | b'+' | b'-' | b'.' | b'^' | b'_' | b'`' | b'|' | b'~' => true,
+        _ => b.is_ascii_alphanumeric(),
+    }
+}
+
+/// Encode `value` as a `UTF-8` ext-value without a language tag.
+pub(crate) fn encode(value: &str) -> String {
+    let mut out = String::with_capacity("UTF-8''".len() + value.len());
+    out.push_str("UTF-8''");
+    for &b in value.as_bytes() {
+        if is_attr_char(b) {
+            out.push(b as char);
+        } else {
+            write!(out, "%{:02X}", b).expect("writing to a String can't fail");
+        }
+    }
+    out
+}
+
+/// Decode an ext-value, ignoring its language tag.
+///
+/// Returns `None` if the charset isn't `UTF-8`, or if the value contains
+/// characters that should have been percent-encoded, invalid percent
+/// sequences, or doesn't decode to valid UTF-8.
+pub(crate) fn decode(s: &str) -> Option<String> {
+    let mut parts = s.splitn(3, '\'');
+    let charset = parts.next()?;
+    let _language = parts.next()?;
+    let value = parts.next()?;
+
+    if !charset.eq_ignore_ascii_case("utf-8") {
+        return None;
+    }
+
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut iter = value.bytes();
+    while let Some(b) = iter.next() {
+        if b == b'%' {
+            let hi = hex(iter.next()?)?;
+            let lo = hex(iter.next()?)?;
+            bytes.push(hi << 4 | lo);
+        } else if is_attr_char(b) {
+            bytes.push(b);
+        } else {
+            return None;
+        }
+    }
+
+    String::from_utf8(bytes).ok()
+}
+
+fn hex(b: u8) -> Option<u8> {
+    (b as char).to_digit(16).map(|d| d as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, encode};
+
+    #[test]
+    fn round_trips() {
+        for s in &["plain.txt", "black and white.csv", "100% real", "£ and € rates", "日本語.pdf", ""] {
+            assert_eq!(decode(&encode(s)).as_deref(), Some(*s));
+        }
+    }
+
+    #[test]
+    fn encode_escapes_non_attr_chars() {
+        assert_eq!(encode("a b%c'd"), "UTF-8''a%20b%25c%27d");
+        assert_eq!(encode("€"), "UTF-8''%E2%82%AC");
+    }
+
+    #[test]
+    fn decode_accepts_language_and_lowercase() {
+        assert_eq!(decode("utf-8'en'%c2%a3%20rates").unwrap(), "£ rates");
+    }
+
+    #[test]
+    fn decode_invalid() {
+        assert_eq!(decode("ISO-8859-1''%A3"), None);
+        assert_eq!(decode("UTF-8''%A"), None);
+        assert_eq!(decode("UTF-8''%zz"), None);
+        assert_eq!(decode("UTF-8''a b"), None);
+        assert_eq!(decode("UTF-8''%FF"), None);
+        assert_eq!(decode("UTF-8'foo"), None);
+    }
+}
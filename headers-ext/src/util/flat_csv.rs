@@ -5,8 +5,16 @@ use std::marker::PhantomData;
 use bytes::{Bytes, BytesMut};
 use headers_core::decode::TryFromValues;
 use ::HeaderValue;
+use super::token::{trim_ows, trim_ows_bytes};
 
 // A single `HeaderValue` that can flatten multiple values with commas.
+//
+// Decoding follows the list rules of RFC7230 section 7: empty elements are
+// skipped, and items are trimmed of optional whitespace (spaces and tabs).
+// A value with no elements at all, such as `` or ` , `, doesn't decode,
+// since most list headers are defined as `1#element`. The few defined as
+// `#element`, where an empty list means something, check for this
+// themselves.
 #[derive(Clone, PartialEq, Eq, Hash)]
 pub(crate) struct FlatCsv<Sep = Comma> {
     pub(crate) value: HeaderValue,
@@ -32,23 +40,150 @@ impl Separator for SemiColon {
 }
 
 impl<Sep: Separator> FlatCsv<Sep> {
+    /// Iterates the items trimmed of optional whitespace, skipping empty
+    /// ones.
+    ///
+    /// Separators inside a double-quoted string, which may contain
+    /// backslash escapes, don't split the item.
     pub(crate) fn iter(&self) -> impl Iterator<Item = &str> {
         self
             .value
             .to_str()
             .ok()
             .into_iter()
-            .flat_map(|value_str| {
-                value_str
-                    .split(Sep::SEP as char)
-                    .map(|item| item.trim())
-            })
+            .flat_map(|value_str| split_quoted(value_str, Sep::SEP))
+    }
+
+    /// Whether there are no items at all, once empty ones are skipped.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    /// Add the items of `other` that aren't already in this list, as
+    /// compared by `eq`, keeping the order of both.
+    pub(crate) fn union<F>(&self, other: &FlatCsv<Sep>, eq: F) -> FlatCsv<Sep>
+    where
+        F: Fn(&str, &str) -> bool,
+    {
+        let mut added: Vec<&str> = Vec::new();
+        for item in other.iter() {
+            if !self.iter().chain(added.iter().cloned()).any(|have| eq(have, item)) {
+                added.push(item);
+            }
+        }
+        if added.is_empty() {
+            return self.value.clone().into();
+        }
+
+        let first = if self.is_empty() { None } else { Some(self.value.clone()) };
+        first
+            .into_iter()
+            .chain(added.into_iter().map(|item| {
+                HeaderValue::from_str(item).expect("an item of a HeaderValue is a valid HeaderValue")
+            }))
+            .collect()
+    }
+
+    /// Join the values being decoded, failing if they have more items than
+    /// `DecodeLimits::max_list_items`.
+    ///
+    /// Unlike `try_from_values`, a list with no items decodes.
+    pub(crate) fn from_values(values: &mut ::Values) -> Option<Self> {
+        let max = values.limits().max_list_items;
+        let csv: FlatCsv<Sep> = values.by_ref().collect();
+        // Each item but the last takes at least a byte and a separator, so
+        // a shorter value can't have too many and needn't be counted.
+        if csv.value.len() > max.saturating_mul(2) && csv.iter().nth(max).is_some() {
+            values.set_too_large();
+            return None;
+        }
+        Some(csv)
+    }
+}
+
+/// Split `value` on `sep`, like `FlatCsv::iter`, for a value that has
+/// already been turned into a `str`.
+pub(crate) fn split_quoted(value: &str, sep: u8) -> impl Iterator<Item = &str> {
+    let mut in_quotes = false;
+    let mut escaped = false;
+    value
+        .split(move |c: char| {
+            if escaped {
+                escaped = false;
+            } else if in_quotes && c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_quotes = !in_quotes;
+            } else {
+                return !in_quotes && c == sep as char;
+            }
+            false
+        })
+        .map(trim_ows)
+        .filter(|item| !item.is_empty())
+}
+
+/// Split a comma-separated list, like `FlatCsv::iter`, where elements can
+/// also contain comments, such as `1.1 proxy (squid, v4)` in `Via`, or
+/// a `<URI-Reference>`, as in `Link`.
+///
+/// Commas inside a quoted string, a comment, which may be nested, or a
+/// `<URI-Reference>` don't split the element.
+pub(crate) fn split_elements(value: &str) -> impl Iterator<Item = &str> {
+    let mut is_separator = element_separator();
+    // Only ASCII is special, so any other character stands for itself.
+    value
+        .split(move |c: char| is_separator(if c.is_ascii() { c as u8 } else { 0x80 }))
+        .map(trim_ows)
+        .filter(|item| !item.is_empty())
+}
+
+/// Split the raw bytes of a field line like `split_elements`, so that a
+/// line with `obs-text` splits too.
+pub(crate) fn split_element_bytes(value: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let mut is_separator = element_separator();
+    value
+        .split(move |&b| is_separator(b))
+        .map(trim_ows_bytes)
+        .filter(|item| !item.is_empty())
+}
+
+// Whether each byte, fed in order, is a comma separating two elements.
+fn element_separator() -> impl FnMut(u8) -> bool {
+    let mut in_quotes = false;
+    let mut in_uri = false;
+    let mut escaped = false;
+    let mut depth = 0usize;
+    move |b| {
+        if in_uri {
+            in_uri = b != b'>';
+        } else if escaped {
+            escaped = false;
+        } else if (in_quotes || depth > 0) && b == b'\\' {
+            escaped = true;
+        } else if b == b'"' && depth == 0 {
+            in_quotes = !in_quotes;
+        } else if b == b'(' && !in_quotes {
+            depth += 1;
+        } else if b == b')' && !in_quotes && depth > 0 {
+            depth -= 1;
+        } else if b == b'<' && !in_quotes && depth == 0 {
+            in_uri = true;
+        } else {
+            return !in_quotes && depth == 0 && b == b',';
+        }
+        false
     }
 }
 
 impl<Sep: Separator> TryFromValues for FlatCsv<Sep> {
     fn try_from_values(values: &mut ::Values) -> Option<Self> {
-        Some(values.collect())
+        let csv = FlatCsv::from_values(values)?;
+        if csv.is_empty() {
+            None
+        } else {
+            Some(csv)
+        }
     }
 }
 
@@ -95,7 +230,7 @@ impl<'a, Sep: Separator> FromIterator<&'a HeaderValue> for FlatCsv<Sep> {
             .next()
             .cloned()
             .map(Bytes::from)
-            .unwrap_or_else(|| Bytes::new());
+            .unwrap_or_default();
 
         let mut buf = BytesMut::from(bytes);
 
@@ -131,7 +266,7 @@ impl<Sep: Separator> FromIterator<HeaderValue> for FlatCsv<Sep> {
         let bytes = values
             .next()
             .map(Bytes::from)
-            .unwrap_or_else(|| Bytes::new());
+            .unwrap_or_default();
 
         let mut buf = BytesMut::from(bytes);
 
@@ -147,3 +282,92 @@ impl<Sep: Separator> FromIterator<HeaderValue> for FlatCsv<Sep> {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn csv<Sep: Separator>(values: &[&'static str]) -> FlatCsv<Sep> {
+        values
+            .iter()
+            .map(|&v| HeaderValue::from_static(v))
+            .collect()
+    }
+
+    #[test]
+    fn union() {
+        let eq = |a: &str, b: &str| a.eq_ignore_ascii_case(b);
+        let union = csv::<Comma>(&["a, b"]).union(&csv(&["B, c", "c, d"]), eq);
+        assert_eq!(union.value, "a, b, c, d");
+
+        let same = csv::<Comma>(&["a ,b"]).union(&csv(&["A"]), eq);
+        assert_eq!(same.value, "a ,b");
+
+        let empty = csv::<Comma>(&[" , "]).union(&csv(&["a"]), eq);
+        assert_eq!(empty.value, "a");
+    }
+
+    #[test]
+    fn comma() {
+        let flat = csv::<Comma>(&["foo, bar", "baz"]);
+        assert_eq!(flat.iter().collect::<Vec<_>>(), ["foo", "bar", "baz"]);
+        assert_eq!(flat.value, "foo, bar, baz");
+    }
+
+    #[test]
+    fn semicolon() {
+        let flat = csv::<SemiColon>(&["a=1; b=2"]);
+        assert_eq!(flat.iter().collect::<Vec<_>>(), ["a=1", "b=2"]);
+    }
+
+    #[test]
+    fn skips_empty_items() {
+        let flat = csv::<Comma>(&[", foo,, ,bar,", ""]);
+        assert_eq!(flat.iter().collect::<Vec<_>>(), ["foo", "bar"]);
+    }
+
+    #[test]
+    fn trims_ows() {
+        let flat = csv::<Comma>(&[" \tfoo\t , bar "]);
+        assert_eq!(flat.iter().collect::<Vec<_>>(), ["foo", "bar"]);
+    }
+
+    #[test]
+    fn is_empty() {
+        assert!(csv::<Comma>(&["", " , ,\t"]).is_empty());
+        assert!(!csv::<Comma>(&[" , ,foo, "]).is_empty());
+    }
+
+    #[test]
+    fn quoted_separators() {
+        let flat = csv::<Comma>(&[r#"a="x, y", b="\", c", c"#]);
+        assert_eq!(flat.iter().collect::<Vec<_>>(), [r#"a="x, y""#, r#"b="\", c""#, "c"]);
+
+        let flat = csv::<SemiColon>(&[r#"a="x; y"; b"#]);
+        assert_eq!(flat.iter().collect::<Vec<_>>(), [r#"a="x; y""#, "b"]);
+    }
+
+    #[test]
+    fn split_elements_with_comments() {
+        let items = split_elements(r#"1.0 fred, 1.1 p (squid, v4 (x\), y)), "a, b", c"#);
+        assert_eq!(
+            items.collect::<Vec<_>>(),
+            ["1.0 fred", r#"1.1 p (squid, v4 (x\), y))"#, r#""a, b""#, "c"],
+        );
+    }
+
+    #[test]
+    fn split_elements_with_uris() {
+        let items = split_elements(r#"</a,b(>; rel=preload, <>; title="<, >", </c>"#);
+        assert_eq!(
+            items.collect::<Vec<_>>(),
+            ["</a,b(>; rel=preload", r#"<>; title="<, >""#, "</c>"],
+        );
+    }
+
+    #[test]
+    fn escapes_outside_quotes() {
+        let flat = csv::<Comma>(&[r#"a\,b"#]);
+        assert_eq!(flat.iter().collect::<Vec<_>>(), [r#"a\"#, "b"]);
+    }
+}
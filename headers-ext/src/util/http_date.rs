@@ -1,12 +1,40 @@
+use std::cmp::Ordering;
+#[cfg(any(feature = "chrono", feature = "time03"))]
+use std::convert::TryFrom;
+use std::error::Error as StdError;
 use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::str::FromStr;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use bytes::Bytes;
+use bytes::BytesMut;
 use http::header::HeaderValue;
 use time;
 
+use ParseMode;
+
 /// A timestamp with HTTP formatting and parsing
+///
+/// It has second granularity, like the wire format. Arithmetic uses Unix
+/// time, where every day has 86400 seconds, so leap seconds are ignored.
+///
+/// Dates are always in the years 1000 to 9999, which have the 4 digits the
+/// format needs. Converting a `SystemTime` outside of those saturates, and
+/// converting other date types fails with `DateOutOfRange`.
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use std::time::{Duration, SystemTime};
+/// use headers::HttpDate;
+///
+/// let now = SystemTime::now();
+/// let date = HttpDate::from(now - Duration::from_secs(60));
+/// assert!(date < now);
+/// assert!(date.elapsed_since(now).unwrap() >= Duration::from_secs(60));
+/// assert!(date.checked_add(Duration::from_secs(3600)).unwrap() > now);
+/// ```
 //   Prior to 1995, there were three different formats commonly used by
 //   servers to communicate timestamps.  For compatibility with old
 //   implementations, all three are defined here.  The preferred format is
@@ -29,10 +57,37 @@ use time;
 //   header field that contains one or more timestamps defined as
 //   HTTP-date, the sender MUST generate those timestamps in the
 //   IMF-fixdate format.
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub(crate) struct HttpDate(time::Tm);
+#[derive(Clone, Copy)]
+pub struct HttpDate(time::Tm);
+
+/// `Fri, 31 Dec 9999 23:59:59 GMT`, the latest date an IMF-fixdate can hold.
+const MAX_SECS: i64 = 253_402_300_799;
+
+/// `Wed, 01 Jan 1000 00:00:00 GMT`, the earliest date an IMF-fixdate can hold.
+const MIN_SECS: i64 = -30_610_224_000;
 
 impl HttpDate {
+    /// Parse a date with the given `ParseMode`.
+    ///
+    /// Strictly, only the preferred IMF-fixdate format is accepted, which
+    /// is the only one senders may generate. Leniently, the two obsolete
+    /// formats recipients must accept are too, and so is an IMF-fixdate
+    /// missing its `GMT`.
+    pub(crate) fn parse(s: &str, mode: ParseMode) -> Option<Self> {
+        match mode {
+            ParseMode::Strict => time::strptime(s, "%a, %d %b %Y %T GMT")
+                .ok()
+                .filter(|tm| tm.tm_year + 1900 >= 1000)
+                .map(HttpDate),
+            ParseMode::Lenient => s.parse().ok().or_else(|| {
+                time::strptime(s, "%a, %d %b %Y %T")
+                    .ok()
+                    .filter(|tm| tm.tm_year + 1900 >= 1000)
+                    .map(HttpDate)
+            }),
+        }
+    }
+
     pub(crate) fn from_val(val: &HeaderValue) -> Option<Self> {
         val.to_str()
             .ok()?
@@ -40,6 +95,118 @@ impl HttpDate {
             .ok()
 
     }
+
+    /// Add `duration`, dropping its fractional seconds.
+    ///
+    /// Returns `None` if the result is later than
+    /// `Fri, 31 Dec 9999 23:59:59 GMT`, which can't be sent.
+    pub fn checked_add(&self, duration: Duration) -> Option<HttpDate> {
+        let secs = self.secs().checked_add(secs(duration)?)?;
+        if secs > MAX_SECS {
+            return None;
+        }
+        Some(HttpDate::from_secs(secs))
+    }
+
+    /// Add `duration`, dropping its fractional seconds, and saturating at
+    /// `Fri, 31 Dec 9999 23:59:59 GMT`, the latest date that can be sent.
+    pub fn saturating_add(&self, duration: Duration) -> HttpDate {
+        self.checked_add(duration)
+            .unwrap_or_else(|| HttpDate::from_secs(MAX_SECS))
+    }
+
+    /// Subtract `duration`, dropping its fractional seconds.
+    ///
+    /// Returns `None` if the result is earlier than
+    /// `Wed, 01 Jan 1000 00:00:00 GMT`, which can't be sent.
+    pub fn checked_sub(&self, duration: Duration) -> Option<HttpDate> {
+        let secs = self.secs().checked_sub(secs(duration)?)?;
+        if secs < MIN_SECS {
+            return None;
+        }
+        Some(HttpDate::from_secs(secs))
+    }
+
+    /// Get how long ago this date was, as of `now`.
+    ///
+    /// Returns `None` if this date is after `now`.
+    pub fn elapsed_since(&self, now: SystemTime) -> Option<Duration> {
+        now.duration_since(SystemTime::from(*self)).ok()
+    }
+
+    /// Create a date from seconds since the epoch, if it is in the years
+    /// 1000 to 9999.
+    #[cfg(any(feature = "chrono", feature = "time03"))]
+    fn try_from_secs(secs: i64) -> Result<HttpDate, DateOutOfRange> {
+        if !(MIN_SECS..=MAX_SECS).contains(&secs) {
+            return Err(DateOutOfRange(()));
+        }
+        Ok(HttpDate::from_secs(secs))
+    }
+
+    fn secs(&self) -> i64 {
+        self.0.to_timespec().sec
+    }
+
+    fn from_secs(secs: i64) -> HttpDate {
+        HttpDate(time::at_utc(time::Timespec::new(secs, 0)))
+    }
+}
+
+fn secs(duration: Duration) -> Option<i64> {
+    if duration.as_secs() > i64::MAX as u64 {
+        None
+    } else {
+        Some(duration.as_secs() as i64)
+    }
+}
+
+/// An error converting a date outside of the years 1000 to 9999, which an
+/// `HttpDate` can't represent.
+#[derive(Debug)]
+pub struct DateOutOfRange(());
+
+impl fmt::Display for DateOutOfRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("date outside of the years 1000 to 9999 an HTTP-date can represent")
+    }
+}
+
+impl StdError for DateOutOfRange {}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<chrono::DateTime<chrono::Utc>> for HttpDate {
+    type Error = DateOutOfRange;
+
+    /// Convert a `DateTime`, dropping its fractional seconds.
+    fn try_from(date: chrono::DateTime<chrono::Utc>) -> Result<HttpDate, DateOutOfRange> {
+        HttpDate::try_from_secs(date.timestamp())
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<HttpDate> for chrono::DateTime<chrono::Utc> {
+    fn from(date: HttpDate) -> chrono::DateTime<chrono::Utc> {
+        chrono::DateTime::from_timestamp(date.secs(), 0).expect("HttpDate is in chrono's range")
+    }
+}
+
+#[cfg(feature = "time03")]
+impl TryFrom<time03::OffsetDateTime> for HttpDate {
+    type Error = DateOutOfRange;
+
+    /// Convert an `OffsetDateTime`, in any offset, dropping its fractional
+    /// seconds.
+    fn try_from(date: time03::OffsetDateTime) -> Result<HttpDate, DateOutOfRange> {
+        HttpDate::try_from_secs(date.unix_timestamp())
+    }
+}
+
+#[cfg(feature = "time03")]
+impl From<HttpDate> for time03::OffsetDateTime {
+    fn from(date: HttpDate) -> time03::OffsetDateTime {
+        time03::OffsetDateTime::from_unix_timestamp(date.secs()).expect("HttpDate is in time's range")
+    }
 }
 
 // TODO: remove this and FromStr?
@@ -48,7 +215,8 @@ pub struct Error(());
 
 impl ::headers_core::decode::TryFromValues for HttpDate {
     fn try_from_values(values: &mut ::Values) -> Option<Self> {
-        HttpDate::from_val(values.next()?)
+        let mode = values.mode();
+        HttpDate::parse(values.next()?.to_str().ok()?, mode)
     }
 }
 
@@ -60,10 +228,12 @@ impl From<HttpDate> for HeaderValue {
 
 impl<'a> From<&'a HttpDate> for HeaderValue {
     fn from(date: &'a HttpDate) -> HeaderValue {
-        // TODO: could be just BytesMut instead of String
-        let s = date.to_string();
-        let bytes = Bytes::from(s);
-        HeaderValue::from_shared(bytes)
+        use std::fmt::Write;
+
+        // IMF-fixdate is 29 bytes, which fits inline in a `Bytes`.
+        let mut buf = BytesMut::with_capacity(29);
+        write!(buf, "{}", date).expect("IMF-fixdate is 29 bytes");
+        HeaderValue::from_shared(buf.freeze())
             .expect("HttpDate always is a valid value")
     }
 }
@@ -77,8 +247,66 @@ impl FromStr for HttpDate {
             }).or_else(|_| {
                 time::strptime(s, "%c")
             })
+            .ok()
+            // Years are formatted without padding, so ones before 1000
+            // wouldn't be a valid IMF-fixdate when encoded again.
+            .filter(|tm| tm.tm_year + 1900 >= 1000)
             .map(HttpDate)
-            .map_err(|_| Error(()))
+            .ok_or(Error(()))
+    }
+}
+
+// A `Tm` also has fields like the day of the year, which parsing doesn't
+// fill in, so dates are compared by the instant they represent.
+impl PartialEq for HttpDate {
+    fn eq(&self, other: &HttpDate) -> bool {
+        self.0.to_timespec() == other.0.to_timespec()
+    }
+}
+
+impl Eq for HttpDate {}
+
+impl PartialOrd for HttpDate {
+    fn partial_cmp(&self, other: &HttpDate) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for HttpDate {
+    fn cmp(&self, other: &HttpDate) -> Ordering {
+        self.0.to_timespec().cmp(&other.0.to_timespec())
+    }
+}
+
+// A `SystemTime` compares as the `HttpDate` it converts to, so without its
+// fractional seconds.
+impl PartialEq<SystemTime> for HttpDate {
+    fn eq(&self, other: &SystemTime) -> bool {
+        *self == HttpDate::from(*other)
+    }
+}
+
+impl PartialEq<HttpDate> for SystemTime {
+    fn eq(&self, other: &HttpDate) -> bool {
+        other == self
+    }
+}
+
+impl PartialOrd<SystemTime> for HttpDate {
+    fn partial_cmp(&self, other: &SystemTime) -> Option<Ordering> {
+        Some(self.cmp(&HttpDate::from(*other)))
+    }
+}
+
+impl PartialOrd<HttpDate> for SystemTime {
+    fn partial_cmp(&self, other: &HttpDate) -> Option<Ordering> {
+        other.partial_cmp(self).map(Ordering::reverse)
+    }
+}
+
+impl Hash for HttpDate {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.to_timespec().hash(state)
     }
 }
 
@@ -94,36 +322,38 @@ impl fmt::Display for HttpDate {
     }
 }
 
+// Fractional seconds are dropped, rounding down, and times outside the
+// years 1000 to 9999 saturate.
 impl From<SystemTime> for HttpDate {
     fn from(sys: SystemTime) -> HttpDate {
-        let tmspec = match sys.duration_since(UNIX_EPOCH) {
-            Ok(dur) => {
-                // subsec nanos always dropped
-                time::Timespec::new(dur.as_secs() as i64, 0)
-            },
+        let secs = match sys.duration_since(UNIX_EPOCH) {
+            Ok(dur) => secs(dur).unwrap_or(MAX_SECS),
             Err(err) => {
                 let neg = err.duration();
-                // subsec nanos always dropped
-                time::Timespec::new(-(neg.as_secs() as i64), 0)
+                let floor = if neg.subsec_nanos() > 0 { 1 } else { 0 };
+                secs(neg).map_or(MIN_SECS, |secs| -secs - floor)
             },
         };
-        HttpDate(time::at_utc(tmspec))
+        HttpDate::from_secs(secs.clamp(MIN_SECS, MAX_SECS))
     }
 }
 
 impl From<HttpDate> for SystemTime {
     fn from(date: HttpDate) -> SystemTime {
-        let spec = date.0.to_timespec();
-        if spec.sec >= 0 {
-            UNIX_EPOCH + Duration::new(spec.sec as u64, spec.nsec as u32)
+        // Dates never have fractional seconds.
+        let secs = date.secs();
+        if secs >= 0 {
+            UNIX_EPOCH + Duration::from_secs(secs as u64)
         } else {
-            UNIX_EPOCH - Duration::new(spec.sec as u64, spec.nsec as u32)
+            UNIX_EPOCH - Duration::from_secs(secs.unsigned_abs())
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
     use time::Tm;
     use super::HttpDate;
 
@@ -159,5 +389,134 @@ mod tests {
     #[test]
     fn test_no_date() {
         assert!("this-is-no-date".parse::<HttpDate>().is_err());
+        assert!("Sun, 06 Nov 0194 08:49:37 GMT".parse::<HttpDate>().is_err());
+    }
+
+    #[test]
+    fn test_eq_from_system_time() {
+        let time = UNIX_EPOCH + Duration::from_secs(784_198_117);
+        assert_eq!(HttpDate::from(time), NOV_07);
+        assert!(HttpDate::from(time + Duration::from_secs(1)) > NOV_07);
+    }
+
+    #[test]
+    fn test_cmp_system_time() {
+        let time = UNIX_EPOCH + Duration::from_secs(784_198_117);
+        assert_eq!(NOV_07, time);
+        assert_eq!(time, NOV_07);
+        // Fractional seconds are dropped.
+        assert_eq!(NOV_07, time + Duration::from_millis(999));
+        assert!(NOV_07 < time + Duration::from_secs(1));
+        assert!(time + Duration::from_secs(1) > NOV_07);
+        assert!(NOV_07 > time - Duration::from_millis(1));
+        assert!(time - Duration::from_millis(1) < NOV_07);
+    }
+
+    #[test]
+    fn test_add_sub() {
+        let next = NOV_07.checked_add(Duration::from_millis(86_400_999)).unwrap();
+        assert_eq!(next.to_string(), "Tue, 08 Nov 1994 08:48:37 GMT");
+        assert_eq!(next.checked_sub(Duration::from_secs(86_400)).unwrap(), NOV_07);
+        assert_eq!(NOV_07.checked_add(Duration::from_secs(u64::MAX)), None);
+        assert_eq!(NOV_07.checked_sub(Duration::from_secs(u64::MAX)), None);
+    }
+
+    #[test]
+    fn test_max() {
+        let max = "Fri, 31 Dec 9999 23:59:59 GMT".parse::<HttpDate>().unwrap();
+        let year = Duration::from_secs(365 * 86_400);
+
+        assert_eq!(max.checked_add(Duration::from_secs(1)), None);
+        assert_eq!(max.checked_sub(Duration::from_secs(1)).unwrap().checked_add(Duration::from_secs(1)), Some(max));
+        assert_eq!(max.saturating_add(year), max);
+        assert_eq!(max.checked_sub(year).unwrap().saturating_add(year * 2), max);
+        assert_eq!(NOV_07.saturating_add(Duration::from_secs(u64::MAX)), max);
+        assert_eq!(NOV_07.saturating_add(year).to_string(), "Tue, 07 Nov 1995 08:48:37 GMT");
+    }
+
+    #[test]
+    fn test_before_epoch() {
+        let epoch = HttpDate::from(UNIX_EPOCH);
+        let before = epoch.checked_sub(Duration::from_secs(1)).unwrap();
+        assert_eq!(before.to_string(), "Wed, 31 Dec 1969 23:59:59 GMT");
+        assert_eq!(SystemTime::from(before), UNIX_EPOCH - Duration::from_secs(1));
+        assert_eq!(HttpDate::from(UNIX_EPOCH - Duration::from_secs(1)), before);
+        assert_eq!(before.elapsed_since(UNIX_EPOCH), Some(Duration::from_secs(1)));
+        assert_eq!(epoch.elapsed_since(SystemTime::from(before)), None);
+
+        let min = "Wed, 01 Jan 1000 00:00:00 GMT".parse::<HttpDate>().unwrap();
+        assert_eq!(min.checked_sub(Duration::from_secs(1)), None);
+        assert!(min.checked_add(Duration::from_secs(1)).unwrap() > min);
+    }
+
+    #[test]
+    fn test_saturating_from_system_time() {
+        let max = "Fri, 31 Dec 9999 23:59:59 GMT".parse::<HttpDate>().unwrap();
+        let min = "Wed, 01 Jan 1000 00:00:00 GMT".parse::<HttpDate>().unwrap();
+        let eons = Duration::from_secs(1 << 40);
+        assert_eq!(HttpDate::from(UNIX_EPOCH + eons), max);
+        assert_eq!(HttpDate::from(UNIX_EPOCH - eons), min);
+
+        // Rounding down, before the epoch too.
+        let before = UNIX_EPOCH - Duration::from_millis(500);
+        assert_eq!(HttpDate::from(before).to_string(), "Wed, 31 Dec 1969 23:59:59 GMT");
+    }
+
+    #[test]
+    fn test_elapsed_since() {
+        let time = UNIX_EPOCH + Duration::from_secs(784_198_117);
+        assert_eq!(NOV_07.elapsed_since(time), Some(Duration::from_secs(0)));
+        assert_eq!(NOV_07.elapsed_since(time + Duration::from_millis(1500)), Some(Duration::from_millis(1500)));
+        assert_eq!(NOV_07.elapsed_since(time - Duration::from_secs(1)), None);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_chrono() {
+        use std::convert::TryFrom;
+        use chrono::{DateTime, TimeZone, Utc};
+
+        let dt = Utc.with_ymd_and_hms(1994, 11, 7, 8, 48, 37).unwrap();
+        assert_eq!(HttpDate::try_from(dt).unwrap(), NOV_07);
+        assert_eq!(DateTime::<Utc>::from(NOV_07), dt);
+
+        let fraction = dt + chrono::Duration::milliseconds(999);
+        assert_eq!(HttpDate::try_from(fraction).unwrap(), NOV_07);
+
+        let before = Utc.with_ymd_and_hms(1969, 12, 31, 23, 59, 59).unwrap();
+        assert_eq!(SystemTime::from(HttpDate::try_from(before).unwrap()), UNIX_EPOCH - Duration::from_secs(1));
+
+        let max = Utc.with_ymd_and_hms(9999, 12, 31, 23, 59, 59).unwrap();
+        assert_eq!(DateTime::<Utc>::from(HttpDate::try_from(max).unwrap()), max);
+        assert!(HttpDate::try_from(max + chrono::Duration::seconds(1)).is_err());
+        assert!(HttpDate::try_from(Utc.with_ymd_and_hms(999, 12, 31, 23, 59, 59).unwrap()).is_err());
+    }
+
+    #[cfg(feature = "time03")]
+    #[test]
+    fn test_time03() {
+        use std::convert::TryFrom;
+        use time03::{Date, Month, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
+
+        let at = |year, month, day, h, m, s| {
+            let date = Date::from_calendar_date(year, month, day).unwrap();
+            PrimitiveDateTime::new(date, Time::from_hms(h, m, s).unwrap()).assume_utc()
+        };
+
+        let dt = at(1994, Month::November, 7, 8, 48, 37);
+        assert_eq!(HttpDate::try_from(dt).unwrap(), NOV_07);
+        assert_eq!(OffsetDateTime::from(NOV_07), dt);
+
+        // Other offsets are the same instant.
+        let offset = dt.to_offset(UtcOffset::from_hms(2, 0, 0).unwrap());
+        assert_eq!(HttpDate::try_from(offset).unwrap(), NOV_07);
+
+        let fraction = dt + time03::Duration::milliseconds(999);
+        assert_eq!(HttpDate::try_from(fraction).unwrap(), NOV_07);
+
+        let max = at(9999, Month::December, 31, 23, 59, 59);
+        assert_eq!(OffsetDateTime::from(HttpDate::try_from(max).unwrap()), max);
+        assert!(HttpDate::try_from(at(999, Month::December, 31, 23, 59, 59)).is_err());
+        assert!(HttpDate::try_from(max.to_offset(UtcOffset::from_hms(-1, 0, 0).unwrap())).is_ok());
     }
 }
@@ -0,0 +1,109 @@
+use std::fmt;
+use std::iter::FromIterator;
+use std::{slice, vec};
+
+use mime::Mime;
+
+use util::FlatCsv;
+
+/// A non-empty comma-separated list of `Mime`s, such as in `Accept-Patch`.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct MimeList(Vec<Mime>);
+
+impl MimeList {
+    pub(crate) fn iter(&self) -> slice::Iter<'_, Mime> {
+        self.0.iter()
+    }
+
+    /// Checks if any `Mime` in the list matches the `pattern`.
+    ///
+    /// The essences must be equal, and every parameter in the pattern
+    /// must also be present on the matched `Mime`. Parameters of the
+    /// listed `Mime` that aren't in the pattern are ignored.
+    pub(crate) fn contains(&self, pattern: &Mime) -> bool {
+        self.0.iter().any(|mime| {
+            mime.essence_str() == pattern.essence_str()
+                && pattern.params().all(|(name, value)| {
+                    mime
+                        .get_param(name.as_str())
+                        .map(|v| value == v.as_str())
+                        .unwrap_or(false)
+                })
+        })
+    }
+}
+
+impl ::headers_core::decode::TryFromValues for MimeList {
+    fn try_from_values(values: &mut ::Values) -> Option<Self> {
+        let csv: FlatCsv = FlatCsv::from_values(values)?;
+        let mimes = csv
+            .iter()
+            .map(|item| item.parse().ok())
+            .collect::<Option<Vec<Mime>>>()?;
+
+        if mimes.is_empty() {
+            None
+        } else {
+            Some(MimeList(mimes))
+        }
+    }
+}
+
+impl FromIterator<Mime> for MimeList {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = Mime>,
+    {
+        MimeList(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for MimeList {
+    type Item = Mime;
+    type IntoIter = vec::IntoIter<Mime>;
+
+    fn into_iter(self) -> vec::IntoIter<Mime> {
+        self.0.into_iter()
+    }
+}
+
+impl fmt::Display for MimeList {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        ::headers_core::encode::comma_delimited(f, self.0.iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(s: &str) -> MimeList {
+        s.split(',')
+            .map(|m| m.trim().parse().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn contains_ignores_params_not_in_pattern() {
+        let mimes = list("text/plain; charset=utf-8, application/json");
+        assert!(mimes.contains(&"text/plain".parse().unwrap()));
+        assert!(mimes.contains(&"application/json".parse().unwrap()));
+        assert!(!mimes.contains(&"text/html".parse().unwrap()));
+    }
+
+    #[test]
+    fn contains_checks_params_in_pattern() {
+        let mimes = list("text/plain; charset=utf-8");
+        assert!(mimes.contains(&"text/plain; charset=UTF-8".parse().unwrap()));
+        assert!(!mimes.contains(&"text/plain; charset=latin1".parse().unwrap()));
+
+        let mimes = list("text/plain");
+        assert!(!mimes.contains(&"text/plain; charset=utf-8".parse().unwrap()));
+    }
+
+    #[test]
+    fn display() {
+        let mimes = list("text/plain; charset=utf-8, application/json");
+        assert_eq!(mimes.to_string(), "text/plain; charset=utf-8, application/json");
+    }
+}
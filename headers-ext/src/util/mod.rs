@@ -1,18 +1,46 @@
 //pub use self::charset::Charset;
 //pub use self::encoding::Encoding;
-pub(crate) use self::entity::EntityTag;
-pub(crate) use self::flat_csv::{FlatCsv, SemiColon};
-pub(crate) use self::http_date::HttpDate;
+pub use self::content_coding::{ContentCoding, InvalidContentCoding};
+#[cfg(feature = "conditional")]
+pub use self::entity::{EntityTag, InvalidEntityTag};
+#[cfg(feature = "conditional")]
+pub(crate) use self::entity::EntityTagRange;
+pub(crate) use self::flat_csv::{split_element_bytes, split_elements, split_quoted, FlatCsv, SemiColon};
+pub use self::http_date::{DateOutOfRange, HttpDate};
+pub(crate) use self::mime_list::MimeList;
+pub(crate) use self::parsed_csv::ParsedCsv;
+pub(crate) use self::parsed_csv::parse as parse_item;
+#[cfg(feature = "cors")]
+pub(crate) use self::parsed_csv::{join_wildcard, split_wildcard};
+pub(crate) use headers_core::quoted;
 //pub use language_tags::LanguageTag;
-//pub use self::quality_value::{Quality, QualityValue};
-pub(crate) use self::seconds::Seconds;
-pub(crate) use self::value_string::HeaderValueString;
+pub use self::quality_value::{InvalidQuality, Quality, QualityValue};
+pub use self::range_unit::{InvalidRangeUnit, RangeUnit};
+pub use self::seconds::Seconds;
+pub(crate) use self::small_list::SmallList;
+pub use self::value_string::{HeaderString, InvalidHeaderString};
+#[cfg(feature = "caching")]
+pub(crate) use self::value_string::unfold;
 
 //mod charset;
 //mod encoding;
+pub(crate) mod clock;
+#[cfg(feature = "auth")]
+pub(crate) mod constant_time;
+mod content_coding;
+pub(crate) mod digits;
+#[cfg(feature = "conditional")]
 mod entity;
+pub(crate) mod ext_value;
 mod flat_csv;
 mod http_date;
-//mod quality_value;
+mod mime_list;
+mod parsed_csv;
+mod quality_value;
+mod range_unit;
 mod seconds;
+pub(crate) mod small_list;
+pub(crate) mod structured;
+pub(crate) mod token;
+pub(crate) mod uri_reference;
 mod value_string;
@@ -0,0 +1,210 @@
+use std::str::FromStr;
+
+use headers_core::decode::TryFromValues;
+
+use ::HeaderValue;
+use super::{FlatCsv, SmallList};
+use super::small_list::{IntoIter, Iter};
+
+/// A `FlatCsv` along with its items, parsed once when it's decoded or
+/// built.
+///
+/// Iterating only borrows the parsed items, and encoding reuses the joined
+/// value.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct ParsedCsv<T> {
+    csv: FlatCsv,
+    items: SmallList<T>,
+}
+
+impl<T: FromStr> ParsedCsv<T> {
+    /// Parse the items of `csv`, skipping any that are invalid.
+    pub(crate) fn lossy(csv: FlatCsv) -> ParsedCsv<T> {
+        let items = csv.iter().filter_map(parse).collect();
+        ParsedCsv { csv, items }
+    }
+
+    /// Parse the items of `csv`, failing if any of them is invalid.
+    #[cfg(any(feature = "cors", feature = "conditional"))]
+    pub(crate) fn strict(csv: FlatCsv) -> Option<ParsedCsv<T>> {
+        let items = csv.iter().map(parse).collect::<Option<_>>()?;
+        Some(ParsedCsv { csv, items })
+    }
+}
+
+/// Items this long are never valid, and `HeaderName` panics on them
+/// instead of failing to parse.
+const MAX_ITEM_LEN: usize = 64 * 1024;
+
+/// Parse one item the way the lists do, so it can't panic on overlong ones.
+pub(crate) fn parse<T: FromStr>(s: &str) -> Option<T> {
+    if s.len() >= MAX_ITEM_LEN {
+        return None;
+    }
+    s.parse().ok()
+}
+
+impl<T> ParsedCsv<T> {
+    /// Build from items, joining the values `to_value` gives for each.
+    pub(crate) fn from_items<I, F>(iter: I, to_value: F) -> ParsedCsv<T>
+    where
+        I: IntoIterator<Item = T>,
+        F: Fn(&T) -> HeaderValue,
+    {
+        let items = iter.into_iter().collect::<SmallList<T>>();
+        let csv = items.iter().map(to_value).collect();
+        ParsedCsv { csv, items }
+    }
+
+    pub(crate) fn iter(&self) -> Iter<'_, T> {
+        self.items.iter()
+    }
+}
+
+impl<T> IntoIterator for ParsedCsv<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        self.items.into_iter()
+    }
+}
+
+impl<T: Clone + PartialEq> ParsedCsv<T> {
+    /// Drop repeated items, keeping the first of each.
+    ///
+    /// If any was dropped, the value is joined again from the values
+    /// `to_value` gives for the rest.
+    pub(crate) fn dedup<F>(self, to_value: F) -> ParsedCsv<T>
+    where
+        F: Fn(&T) -> HeaderValue,
+    {
+        let mut unique = Vec::<T>::with_capacity(self.items.len());
+        for item in self.iter() {
+            if !unique.contains(item) {
+                unique.push(item.clone());
+            }
+        }
+        if unique.len() == self.items.len() {
+            self
+        } else {
+            ParsedCsv::from_items(unique, to_value)
+        }
+    }
+}
+
+impl<T: Clone> ParsedCsv<T> {
+    /// The items sorted byte-wise by `key`, without repeats, joined again
+    /// from the values `to_value` gives.
+    ///
+    /// If none of the items parsed, the value is kept as it was, since
+    /// joining no items would give an empty value that doesn't decode.
+    pub(crate) fn canonical<K, F>(&self, key: K, to_value: F) -> ParsedCsv<T>
+    where
+        K: Fn(&T) -> &str,
+        F: Fn(&T) -> HeaderValue,
+    {
+        if self.items.is_empty() {
+            return self.clone();
+        }
+        let mut items = self.iter().collect::<Vec<_>>();
+        items.sort_by(|a, b| key(a).cmp(key(b)));
+        items.dedup_by(|a, b| key(a) == key(b));
+        ParsedCsv::from_items(items.into_iter().cloned(), to_value)
+    }
+}
+
+/// Split the `*` members out of a CORS list, where `*` is a wildcard that
+/// can be listed along with items it doesn't cover.
+///
+/// Returns whether there was one, and the other items.
+#[cfg(feature = "cors")]
+pub(crate) fn split_wildcard(csv: FlatCsv) -> (bool, FlatCsv) {
+    if !csv.iter().any(|item| item == "*") {
+        return (false, csv);
+    }
+    let rest = csv
+        .iter()
+        .filter(|&item| item != "*")
+        .map(|item| HeaderValue::from_str(item).expect("an item of a HeaderValue is a valid HeaderValue"))
+        .collect();
+    (true, rest)
+}
+
+/// Join a `*` wildcard and the other items of a CORS list, the reverse of
+/// `split_wildcard`.
+#[cfg(feature = "cors")]
+pub(crate) fn join_wildcard(rest: HeaderValue) -> HeaderValue {
+    if rest.is_empty() {
+        return HeaderValue::from_static("*");
+    }
+    let csv: FlatCsv = vec![HeaderValue::from_static("*"), rest].into_iter().collect();
+    csv.value
+}
+
+impl<T: FromStr> TryFromValues for ParsedCsv<T> {
+    fn try_from_values(values: &mut ::Values) -> Option<Self> {
+        FlatCsv::try_from_values(values).map(ParsedCsv::lossy)
+    }
+}
+
+impl<'a, T> From<&'a ParsedCsv<T>> for HeaderValue {
+    fn from(parsed: &'a ParsedCsv<T>) -> HeaderValue {
+        (&parsed.csv).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::Method;
+
+    use ::HeaderValue;
+    use super::ParsedCsv;
+
+    #[test]
+    fn lossy_and_strict() {
+        let csv = || HeaderValue::from_static("GET, ,P O S T,PUT").into();
+
+        let lossy = ParsedCsv::<Method>::lossy(csv());
+        assert_eq!(lossy.iter().collect::<Vec<_>>(), [Method::GET, Method::PUT]);
+        assert_eq!(HeaderValue::from(&lossy), "GET, ,P O S T,PUT");
+
+        assert!(ParsedCsv::<Method>::strict(csv()).is_none());
+    }
+
+    #[test]
+    fn from_items() {
+        let parsed = ParsedCsv::from_items(vec![Method::GET, Method::HEAD], |m| {
+            HeaderValue::from_str(m.as_str()).unwrap()
+        });
+        assert_eq!(HeaderValue::from(&parsed), "GET, HEAD");
+        assert_eq!(parsed.iter().count(), 2);
+    }
+
+    #[test]
+    fn dedup() {
+        let to_value = |m: &Method| HeaderValue::from_str(m.as_str()).unwrap();
+
+        let parsed = ParsedCsv::<Method>::lossy(HeaderValue::from_static("GET,  HEAD").into()).dedup(to_value);
+        assert_eq!(HeaderValue::from(&parsed), "GET,  HEAD");
+
+        let parsed = ParsedCsv::<Method>::lossy(HeaderValue::from_static("GET, HEAD, get, GET").into()).dedup(to_value);
+        assert_eq!(parsed.iter().collect::<Vec<_>>(), ["GET", "HEAD", "get"]);
+        assert_eq!(HeaderValue::from(&parsed), "GET, HEAD, get");
+    }
+
+    #[test]
+    fn canonical() {
+        let to_value = |m: &Method| HeaderValue::from_str(m.as_str()).unwrap();
+
+        let parsed = ParsedCsv::<Method>::lossy(HeaderValue::from_static("PUT, get,GET, , PUT").into());
+        let canonical = parsed.canonical(Method::as_str, to_value);
+        assert_eq!(canonical.iter().collect::<Vec<_>>(), ["GET", "PUT", "get"]);
+        assert_eq!(HeaderValue::from(&canonical), "GET, PUT, get");
+        assert_eq!(canonical.canonical(Method::as_str, to_value), canonical);
+
+        // With no items, the value is kept rather than left empty.
+        let parsed = ParsedCsv::<Method>::lossy(HeaderValue::from_static(";").into());
+        assert_eq!(HeaderValue::from(&parsed.canonical(Method::as_str, to_value)), ";");
+    }
+}
@@ -1,39 +1,141 @@
-#[allow(unused, deprecated)]
-use std::ascii::AsciiExt;
 use std::cmp;
-use std::default::Default;
+use std::convert::TryFrom;
+use std::error::Error as StdError;
 use std::fmt;
-use std::str;
-
-#[cfg(test)]
-use self::internal::IntoQuality;
+use std::str::FromStr;
 
 /// Represents a quality used in quality values.
 ///
-/// Can be created with the `q` function.
-///
 /// # Implementation notes
 ///
 /// The quality value is defined as a number between 0 and 1 with three decimal places. This means
 /// there are 1001 possible values. Since floating point numbers are not exact and the smallest
-/// floating point data type (`f32`) consumes four bytes, hyper uses an `u16` value to store the
-/// quality internally. For performance reasons you may set quality directly to a value between
-/// 0 and 1000 e.g. `Quality(532)` matches the quality `q=0.532`.
+/// floating point data type (`f32`) consumes four bytes, the quality is stored as a `u16` number
+/// of thousandths, e.g. `Quality::from_u16(532)` matches the quality `q=0.532`.
 ///
 /// [RFC7231 Section 5.3.1](https://tools.ietf.org/html/rfc7231#section-5.3.1)
 /// gives more information on quality values in HTTP header fields.
-#[derive(Copy, Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use std::convert::TryFrom;
+/// use headers::Quality;
+///
+/// let q = Quality::from_u16(800).unwrap();
+/// assert_eq!(q, Quality::try_from(0.8).unwrap());
+/// assert_eq!(q.to_string(), "0.8");
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Quality(u16);
 
+/// An error returned when a quality value is out of range or malformed.
+#[derive(Debug)]
+pub struct InvalidQuality(());
+
+impl Quality {
+    /// Create a `Quality` from a number of thousandths, between 0 and 1000.
+    ///
+    /// Returns `None` if `millis` is greater than 1000.
+    pub fn from_u16(millis: u16) -> Option<Quality> {
+        if millis <= 1000 {
+            Some(Quality(millis))
+        } else {
+            None
+        }
+    }
+
+    /// Get the quality as a number of thousandths.
+    pub fn as_u16(&self) -> u16 {
+        self.0
+    }
+
+    /// Parses a `qvalue`, the part after `q=`.
+    pub(crate) fn parse(s: &str) -> Option<Quality> {
+        let bytes = s.as_bytes();
+        let (&int, rest) = bytes.split_first()?;
+        let frac = match rest.split_first() {
+            None => &[][..],
+            Some((&b'.', frac)) if frac.len() <= 3 => frac,
+            Some(_) => return None,
+        };
+
+        if !frac.iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+
+        let mut millis = 0;
+        for i in 0..3 {
+            millis = millis * 10 + frac.get(i).map(|&d| (d - b'0') as u16).unwrap_or(0);
+        }
+
+        match int {
+            b'0' => Some(Quality(millis)),
+            b'1' if millis == 0 => Some(Quality(1000)),
+            _ => None,
+        }
+    }
+}
+
 impl Default for Quality {
     fn default() -> Quality {
         Quality(1000)
     }
 }
 
+impl TryFrom<f32> for Quality {
+    type Error = InvalidQuality;
+
+    fn try_from(f: f32) -> Result<Quality, InvalidQuality> {
+        // NaN fails both comparisons.
+        if (0.0..=1.0).contains(&f) {
+            Ok(Quality((f * 1000.0).round() as u16))
+        } else {
+            Err(InvalidQuality(()))
+        }
+    }
+}
+
+impl FromStr for Quality {
+    type Err = InvalidQuality;
+
+    fn from_str(s: &str) -> Result<Quality, InvalidQuality> {
+        Quality::parse(s).ok_or(InvalidQuality(()))
+    }
+}
+
+impl fmt::Display for Quality {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            1000 => f.write_str("1"),
+            0 => f.write_str("0"),
+            x => write!(f, "0.{}", format!("{:03}", x).trim_end_matches('0')),
+        }
+    }
+}
+
+impl fmt::Display for InvalidQuality {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid quality value")
+    }
+}
+
+impl StdError for InvalidQuality {}
+
 /// Represents an item with a quality value as defined in
 /// [RFC7231](https://tools.ietf.org/html/rfc7231#section-5.3.1).
-#[derive(Clone, PartialEq, Debug)]
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::{Quality, QualityValue};
+///
+/// let gzip = QualityValue::new("gzip", Quality::from_u16(800).unwrap());
+/// assert_eq!(gzip.to_string(), "gzip; q=0.8");
+/// ```
+#[derive(Clone, Debug, PartialEq)]
 pub struct QualityValue<T> {
     /// The actual contents of the field.
     value: T,
@@ -50,19 +152,20 @@ impl<T> QualityValue<T> {
         }
     }
 
-    /*
-    /// Convenience function to set a `Quality` from a float or integer.
-    ///
-    /// Implemented for `u16` and `f32`.
-    ///
-    /// # Panic
-    ///
-    /// Panics if value is out of range.
-    pub fn with_q<Q: IntoQuality>(mut self, q: Q) -> QualityValue<T> {
-        self.quality = q.into_quality();
-        self
+    /// Get a reference to the value.
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// Get the quality of the value.
+    pub fn quality(&self) -> Quality {
+        self.quality
+    }
+
+    /// Consume the `QualityValue`, returning the value.
+    pub fn into_value(self) -> T {
+        self.value
     }
-    */
 }
 
 impl<T> From<T> for QualityValue<T> {
@@ -83,102 +186,43 @@ impl<T: PartialEq> cmp::PartialOrd for QualityValue<T> {
 impl<T: fmt::Display> fmt::Display for QualityValue<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         fmt::Display::fmt(&self.value, f)?;
-        match self.quality.0 {
-            1000 => Ok(()),
-            0 => f.write_str("; q=0"),
-            x => write!(f, "; q=0.{}", format!("{:03}", x).trim_right_matches('0'))
+        if self.quality.0 == 1000 {
+            Ok(())
+        } else {
+            write!(f, "; q={}", self.quality)
         }
     }
 }
 
-impl<T: str::FromStr> str::FromStr for QualityValue<T> {
-    type Err = ::Error;
-    fn from_str(s: &str) -> ::Result<QualityValue<T>> {
-        // Set defaults used if parsing fails.
+impl<T: FromStr> FromStr for QualityValue<T> {
+    type Err = InvalidQuality;
+
+    /// Parses `value` or `value;q=qvalue`.
+    ///
+    /// Fails with `InvalidQuality` if either the quality or the value is
+    /// malformed.
+    fn from_str(s: &str) -> Result<QualityValue<T>, InvalidQuality> {
         let mut raw_item = s;
-        let mut quality = 1f32;
+        let mut quality = Quality::default();
 
-        let parts: Vec<&str> = s.rsplitn(2, ';').map(|x| x.trim()).collect();
-        if parts.len() == 2 {
-            if parts[0].len() < 2 {
-                return Err(::Error::invalid());
+        let mut parts = s.rsplitn(2, ';');
+        if let (Some(param), Some(item)) = (parts.next(), parts.next()) {
+            let param = param.trim();
+            if param.len() < 2 {
+                return Err(InvalidQuality(()));
             }
-            if parts[0].starts_with("q=") || parts[0].starts_with("Q=") {
-                let q_part = &parts[0][2..parts[0].len()];
-                if q_part.len() > 5 {
-                    return Err(::Error::invalid());
-                }
-                match q_part.parse::<f32>() {
-                    Ok(q_value) => {
-                        if 0f32 <= q_value && q_value <= 1f32 {
-                            quality = q_value;
-                            raw_item = parts[1];
-                            } else {
-                                return Err(::Error::invalid());
-                            }
-                        },
-                    Err(_) => {
-                        return Err(::Error::invalid())
-                    },
-                }
+            if param.as_bytes()[..2].eq_ignore_ascii_case(b"q=") {
+                quality = param[2..].parse()?;
+                raw_item = item;
             }
         }
-        match raw_item.parse::<T>() {
-            // we already checked above that the quality is within range
-            Ok(item) => Ok(QualityValue::new(item, from_f32(quality))),
-            Err(_) => {
-                Err(::Error::invalid())
-            },
-        }
-    }
-}
-
-#[inline]
-fn from_f32(f: f32) -> Quality {
-    // this function is only used internally. A check that `f` is within range
-    // should be done before calling this method. Just in case, this
-    // debug_assert should catch if we were forgetful
-    debug_assert!(f >= 0f32 && f <= 1f32, "q value must be between 0.0 and 1.0");
-    Quality((f * 1000f32) as u16)
-}
-
-#[cfg(test)]
-fn q<T: IntoQuality>(val: T) -> Quality {
-    val.into_quality()
-}
-
-mod internal {
-    use super::Quality;
-
-    // TryFrom is probably better, but it's not stable. For now, we want to
-    // keep the functionality of the `q` function, while allowing it to be
-    // generic over `f32` and `u16`.
-    //
-    // `q` would panic before, so keep that behavior. `TryFrom` can be
-    // introduced later for a non-panicking conversion.
 
-    pub trait IntoQuality: Sealed + Sized {
-        fn into_quality(self) -> Quality;
+        raw_item
+            .trim()
+            .parse()
+            .map(|value| QualityValue::new(value, quality))
+            .map_err(|_| InvalidQuality(()))
     }
-
-    impl IntoQuality for f32 {
-        fn into_quality(self) -> Quality {
-            assert!(self >= 0f32 && self <= 1f32, "float must be between 0.0 and 1.0");
-            super::from_f32(self)
-        }
-    }
-
-    impl IntoQuality for u16 {
-        fn into_quality(self) -> Quality {
-            assert!(self <= 1000, "u16 must be between 0 and 1000");
-            Quality(self)
-        }
-    }
-
-
-    pub trait Sealed {}
-    impl Sealed for u16 {}
-    impl Sealed for f32 {}
 }
 
 #[cfg(test)]
@@ -224,7 +268,7 @@ mod tests {
     }
     #[test]
     fn test_quality_item_from_str4() {
-        let x: QualityValue<String> = "gzip; q=0.273".parse().unwrap();
+        let x: QualityValue<String> = "gzip;Q=0.273".parse().unwrap();
         assert_eq!(x, QualityValue { value: "gzip".to_owned(), quality: Quality(273), });
     }
     #[test]
@@ -244,25 +288,37 @@ mod tests {
     }
 
     #[test]
-    fn test_quality() {
-        assert_eq!(q(0.5), Quality(500));
+    fn test_quality_parse() {
+        assert_eq!(Quality::parse("0.8"), Some(Quality(800)));
+        assert_eq!(Quality::parse("1.000"), Some(Quality(1000)));
+        assert_eq!(Quality::parse("1."), Some(Quality(1000)));
+        assert_eq!(Quality::parse("0"), Some(Quality(0)));
+        assert_eq!(Quality::parse("1.001"), None);
+        assert_eq!(Quality::parse("0.0001"), None);
+        assert_eq!(Quality::parse(".5"), None);
+        assert_eq!(Quality::parse("0,5"), None);
+        assert_eq!(Quality::parse(""), None);
     }
 
     #[test]
-    #[should_panic]
-    fn test_quality_invalid() {
-        q(-1.0);
+    fn test_quality_from_u16() {
+        assert_eq!(Quality::from_u16(1000), Some(Quality(1000)));
+        assert_eq!(Quality::from_u16(1001), None);
     }
 
     #[test]
-    #[should_panic]
-    fn test_quality_invalid2() {
-        q(2.0);
+    fn test_quality_try_from_f32() {
+        assert_eq!(Quality::try_from(0.5).unwrap(), Quality(500));
+        assert_eq!(Quality::try_from(0.333).unwrap(), Quality(333));
+        assert!(Quality::try_from(-1.0).is_err());
+        assert!(Quality::try_from(1.5).is_err());
+        assert!(Quality::try_from(f32::NAN).is_err());
     }
 
     #[test]
     fn test_fuzzing_bugs() {
         assert!("99999;".parse::<QualityValue<String>>().is_err());
+        assert!("gzip;q=".parse::<QualityValue<String>>().is_err());
         assert!("\x0d;;;=\u{d6aa}==".parse::<QualityValue<String>>().is_ok())
     }
 }
@@ -0,0 +1,129 @@
+use std::error::Error as StdError;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+use super::token;
+
+/// A range unit, as used by `Accept-Ranges`, `Content-Range` and `Range`,
+/// defined in [RFC7233](https://tools.ietf.org/html/rfc7233#section-2).
+///
+/// Units are case-insensitive tokens. Only `bytes` is registered, but an
+/// API can define its own, such as `items` for paging through a
+/// collection. Parsing lowercases other units, and comparing ignores their
+/// case.
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::RangeUnit;
+///
+/// assert_eq!("Bytes".parse::<RangeUnit>().unwrap(), RangeUnit::Bytes);
+/// assert_eq!("items".parse::<RangeUnit>().unwrap(), RangeUnit::Other("items".into()));
+/// assert!("two words".parse::<RangeUnit>().is_err());
+/// ```
+#[derive(Clone, Debug)]
+pub enum RangeUnit {
+    /// `bytes`
+    Bytes,
+    /// `none`, only used by `Accept-Ranges`, when no unit is supported.
+    None,
+    /// Any other unit.
+    Other(String),
+}
+
+/// An error returned when a range unit isn't a token.
+#[derive(Debug)]
+pub struct InvalidRangeUnit(());
+
+impl RangeUnit {
+    /// Get the token of this unit.
+    pub fn as_str(&self) -> &str {
+        match *self {
+            RangeUnit::Bytes => "bytes",
+            RangeUnit::None => "none",
+            RangeUnit::Other(ref unit) => unit,
+        }
+    }
+
+    /// Check if this is the `bytes` unit.
+    pub fn is_bytes(&self) -> bool {
+        *self == RangeUnit::Bytes
+    }
+}
+
+impl PartialEq for RangeUnit {
+    fn eq(&self, other: &RangeUnit) -> bool {
+        self.as_str().eq_ignore_ascii_case(other.as_str())
+    }
+}
+
+impl Eq for RangeUnit {}
+
+impl Hash for RangeUnit {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for b in self.as_str().bytes() {
+            state.write_u8(b.to_ascii_lowercase());
+        }
+    }
+}
+
+impl FromStr for RangeUnit {
+    type Err = InvalidRangeUnit;
+
+    fn from_str(s: &str) -> Result<RangeUnit, InvalidRangeUnit> {
+        if !token::is_token(s.as_bytes()) {
+            return Err(InvalidRangeUnit(()));
+        }
+        if s.eq_ignore_ascii_case("bytes") {
+            Ok(RangeUnit::Bytes)
+        } else if s.eq_ignore_ascii_case("none") {
+            Ok(RangeUnit::None)
+        } else {
+            Ok(RangeUnit::Other(s.to_ascii_lowercase()))
+        }
+    }
+}
+
+impl fmt::Display for RangeUnit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Display for InvalidRangeUnit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid range unit")
+    }
+}
+
+impl StdError for InvalidRangeUnit {}
+
+#[cfg(test)]
+mod tests {
+    use super::RangeUnit;
+
+    #[test]
+    fn parse() {
+        assert_eq!("bytes".parse::<RangeUnit>().unwrap(), RangeUnit::Bytes);
+        assert!(matches!("BYTES".parse::<RangeUnit>().unwrap(), RangeUnit::Bytes));
+        assert!(matches!("None".parse::<RangeUnit>().unwrap(), RangeUnit::None));
+
+        let items = "Items".parse::<RangeUnit>().unwrap();
+        assert!(matches!(items, RangeUnit::Other(ref unit) if unit == "items"));
+        assert_eq!(items.to_string(), "items");
+
+        for s in &["", "a b", "bytes=", "caf\u{e9}"] {
+            assert!(s.parse::<RangeUnit>().is_err(), "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn eq_ignores_case() {
+        assert_eq!(RangeUnit::Other("Items".into()), RangeUnit::Other("items".into()));
+        assert_eq!(RangeUnit::Other("BYTES".into()), RangeUnit::Bytes);
+        assert_ne!(RangeUnit::Other("items".into()), RangeUnit::Bytes);
+        assert!(!RangeUnit::None.is_bytes());
+    }
+}
@@ -2,23 +2,51 @@ use std::fmt;
 use std::time::Duration;
 
 use {HeaderValue};
+use super::digits;
 
+/// A whole number of seconds, as used by the `delta-seconds` values of
+/// headers like `Age`, `Access-Control-Max-Age` and `Retry-After`.
+///
+/// # ABNF
+///
+/// ```text
+/// delta-seconds = 1*DIGIT
+/// ```
+///
+/// Values too large to be represented saturate to `u64::MAX` seconds.
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use std::time::Duration;
+/// use headers::Seconds;
+///
+/// let secs = Seconds::from(Duration::from_millis(2_500));
+/// assert_eq!(secs.as_secs(), 2);
+/// assert_eq!(Duration::from(secs), Duration::from_secs(2));
+/// ```
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub(crate) struct Seconds(Duration);
+pub struct Seconds(u64);
 
 impl Seconds {
-    pub(crate) fn from_val(val: &HeaderValue) -> Option<Self> {
-        let secs = val
-            .to_str()
-            .ok()?
-            .parse()
-            .ok()?;
+    /// Create a `Seconds` from a number of seconds.
+    pub fn from_secs(secs: u64) -> Seconds {
+        Seconds(secs)
+    }
 
-        Some(Seconds(Duration::from_secs(secs)))
+    /// Get the number of seconds.
+    pub fn as_secs(&self) -> u64 {
+        self.0
     }
 
-    pub(crate) fn as_u64(&self) -> u64 {
-        self.0.as_secs()
+    /// Parses `delta-seconds`, only accepting ASCII digits.
+    pub(crate) fn parse(s: &[u8]) -> Option<Seconds> {
+        digits::parse_u64_saturating(s).map(Seconds)
+    }
+
+    pub(crate) fn from_val(val: &HeaderValue) -> Option<Self> {
+        Seconds::parse(val.as_bytes())
     }
 }
 
@@ -30,30 +58,64 @@ impl ::headers_core::decode::TryFromValues for Seconds {
 
 impl<'a> From<&'a Seconds> for HeaderValue {
     fn from(secs: &'a Seconds) -> HeaderValue {
-        secs.0.as_secs().into()
+        secs.0.into()
     }
 }
 
+/// Truncates any fractional part of the `Duration`.
 impl From<Duration> for Seconds {
     fn from(dur: Duration) -> Seconds {
-        Seconds(dur)
+        Seconds(dur.as_secs())
     }
 }
 
 impl From<Seconds> for Duration {
     fn from(secs: Seconds) -> Duration {
-        secs.0
+        Duration::from_secs(secs.0)
     }
 }
 
 impl fmt::Debug for Seconds {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}s", self.0.as_secs())
+        write!(f, "{}s", self.0)
     }
 }
 
 impl fmt::Display for Seconds {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        fmt::Display::fmt(&self.0.as_secs(), f)
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use super::Seconds;
+
+    #[test]
+    fn parse_strict_decimal() {
+        assert_eq!(Seconds::parse(b"0"), Some(Seconds(0)));
+        assert_eq!(Seconds::parse(b"0123"), Some(Seconds(123)));
+        assert_eq!(Seconds::parse(b""), None);
+        assert_eq!(Seconds::parse(b"+1"), None);
+        assert_eq!(Seconds::parse(b"-1"), None);
+        assert_eq!(Seconds::parse(b"1.5"), None);
+        assert_eq!(Seconds::parse(b" 1"), None);
+        assert_eq!(Seconds::parse(b"1 "), None);
+        assert_eq!(Seconds::parse(b"1e3"), None);
+    }
+
+    #[test]
+    fn parse_saturates() {
+        assert_eq!(Seconds::parse(b"18446744073709551615"), Some(Seconds(u64::MAX)));
+        assert_eq!(Seconds::parse(b"18446744073709551616"), Some(Seconds(u64::MAX)));
+        assert_eq!(Seconds::parse("9".repeat(100).as_bytes()), Some(Seconds(u64::MAX)));
+        assert_eq!(Seconds::parse(b"99999999999999999999x"), None);
+    }
+
+    #[test]
+    fn duration_truncates() {
+        assert_eq!(Seconds::from(Duration::from_millis(1_999)), Seconds(1));
+        assert_eq!(Duration::from(Seconds(30)), Duration::from_secs(30));
     }
 }
@@ -0,0 +1,285 @@
+use std::fmt;
+use std::iter::{FromIterator, Take};
+use std::{array, slice, vec};
+
+/// How many items a `SmallList` stores before it spills to the heap.
+const INLINE_CAP: usize = 4;
+
+/// A list that stores up to 4 items inline before spilling to a `Vec`.
+///
+/// Most list headers only ever contain one or two items, so this saves an
+/// allocation in the common case.
+pub(crate) struct SmallList<T>(Repr<T>);
+
+enum Repr<T> {
+    Inline(usize, [Option<T>; INLINE_CAP]),
+    Heap(Vec<T>),
+}
+
+impl<T> SmallList<T> {
+    pub(crate) fn new() -> SmallList<T> {
+        SmallList(Repr::Inline(0, [None, None, None, None]))
+    }
+
+    pub(crate) fn push(&mut self, item: T) {
+        let vec = match self.0 {
+            Repr::Inline(ref mut len, ref mut items) => {
+                if *len < INLINE_CAP {
+                    items[*len] = Some(item);
+                    *len += 1;
+                    return;
+                }
+
+                let mut vec = Vec::with_capacity(INLINE_CAP * 2);
+                vec.extend(items.iter_mut().filter_map(Option::take));
+                vec.push(item);
+                vec
+            },
+            Repr::Heap(ref mut vec) => {
+                vec.push(item);
+                return;
+            },
+        };
+        self.0 = Repr::Heap(vec);
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        match self.0 {
+            Repr::Inline(len, _) => len,
+            Repr::Heap(ref vec) => vec.len(),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub(crate) fn iter(&self) -> Iter<'_, T> {
+        match self.0 {
+            Repr::Inline(len, ref items) => Iter::Inline(items[..len].iter()),
+            Repr::Heap(ref vec) => Iter::Heap(vec.iter()),
+        }
+    }
+
+    #[cfg(any(feature = "caching", feature = "proxy"))]
+    pub(crate) fn last(&self) -> Option<&T> {
+        self.iter().next_back()
+    }
+
+    #[cfg(test)]
+    fn spilled(&self) -> bool {
+        match self.0 {
+            Repr::Inline(..) => false,
+            Repr::Heap(_) => true,
+        }
+    }
+}
+
+/// An iterator over the items of a `SmallList`.
+pub(crate) enum Iter<'a, T: 'a> {
+    Inline(slice::Iter<'a, Option<T>>),
+    Heap(slice::Iter<'a, T>),
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        match *self {
+            Iter::Inline(ref mut iter) => iter.next().map(inline_item),
+            Iter::Heap(ref mut iter) => iter.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match *self {
+            Iter::Inline(ref iter) => iter.size_hint(),
+            Iter::Heap(ref iter) => iter.size_hint(),
+        }
+    }
+
+    fn nth(&mut self, n: usize) -> Option<&'a T> {
+        match *self {
+            Iter::Inline(ref mut iter) => iter.nth(n).map(inline_item),
+            Iter::Heap(ref mut iter) => iter.nth(n),
+        }
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        match *self {
+            Iter::Inline(ref mut iter) => iter.next_back().map(inline_item),
+            Iter::Heap(ref mut iter) => iter.next_back(),
+        }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+fn inline_item<T>(item: &Option<T>) -> &T {
+    item.as_ref().expect("SmallList inline items up to len are set")
+}
+
+impl<'a, T> Clone for Iter<'a, T> {
+    fn clone(&self) -> Iter<'a, T> {
+        match *self {
+            Iter::Inline(ref iter) => Iter::Inline(iter.clone()),
+            Iter::Heap(ref iter) => Iter::Heap(iter.clone()),
+        }
+    }
+}
+
+impl<'a, T> From<slice::Iter<'a, T>> for Iter<'a, T> {
+    fn from(iter: slice::Iter<'a, T>) -> Iter<'a, T> {
+        Iter::Heap(iter)
+    }
+}
+
+/// An iterator that moves the items out of a `SmallList`.
+pub(crate) enum IntoIter<T> {
+    Inline(Take<array::IntoIter<Option<T>, INLINE_CAP>>),
+    Heap(vec::IntoIter<T>),
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        match *self {
+            IntoIter::Inline(ref mut iter) => iter.next().map(inline_owned),
+            IntoIter::Heap(ref mut iter) => iter.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match *self {
+            IntoIter::Inline(ref iter) => iter.size_hint(),
+            IntoIter::Heap(ref iter) => iter.size_hint(),
+        }
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        match *self {
+            IntoIter::Inline(ref mut iter) => iter.next_back().map(inline_owned),
+            IntoIter::Heap(ref mut iter) => iter.next_back(),
+        }
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+fn inline_owned<T>(item: Option<T>) -> T {
+    item.expect("SmallList inline items up to len are set")
+}
+
+impl<T> From<vec::IntoIter<T>> for IntoIter<T> {
+    fn from(iter: vec::IntoIter<T>) -> IntoIter<T> {
+        IntoIter::Heap(iter)
+    }
+}
+
+impl<T> IntoIterator for SmallList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        match self.0 {
+            // `IntoIterator::into_iter` moves out of the array in any
+            // edition.
+            Repr::Inline(len, items) => IntoIter::Inline(IntoIterator::into_iter(items).take(len)),
+            Repr::Heap(vec) => IntoIter::Heap(vec.into_iter()),
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a SmallList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T> FromIterator<T> for SmallList<T> {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut list = SmallList::new();
+        for item in iter {
+            list.push(item);
+        }
+        list
+    }
+}
+
+impl<T: Clone> Clone for SmallList<T> {
+    fn clone(&self) -> SmallList<T> {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: PartialEq> PartialEq for SmallList<T> {
+    fn eq(&self, other: &SmallList<T>) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for SmallList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Repr, SmallList};
+
+    #[test]
+    fn inline_up_to_four() {
+        let list = (0..4).collect::<SmallList<u32>>();
+        assert!(!list.spilled());
+        assert_eq!(list.len(), 4);
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), [0, 1, 2, 3]);
+        assert_eq!(list.last(), Some(&3));
+        assert_eq!(format!("{:?}", list), "[0, 1, 2, 3]");
+
+        let empty = SmallList::<u32>::new();
+        assert!(empty.is_empty());
+        assert_eq!(empty.last(), None);
+    }
+
+    #[test]
+    fn spills_to_heap() {
+        let list = (0..12).map(|i| i.to_string()).collect::<SmallList<String>>();
+        assert!(list.spilled());
+        assert_eq!(list.len(), 12);
+        assert_eq!(list.iter().map(String::as_str).collect::<Vec<_>>(), [
+            "0", "1", "2", "3", "4", "5", "6", "7", "8", "9", "10", "11",
+        ]);
+        assert_eq!(list.last().map(String::as_str), Some("11"));
+        assert_eq!(list.clone(), list);
+    }
+
+    #[test]
+    fn into_iter_moves_items() {
+        for &len in &[0, 3, 4, 9] {
+            let list = (0..len).map(|i| i.to_string()).collect::<SmallList<String>>();
+            assert_eq!(list.iter().nth(2).is_some(), len > 2);
+            let owned = list.into_iter();
+            assert_eq!(owned.len(), len);
+            assert_eq!(owned.rev().collect::<Vec<_>>(), (0..len).rev().map(|i| i.to_string()).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn eq_ignores_representation() {
+        let heap = SmallList(Repr::Heap(vec![0, 1, 2]));
+        assert_eq!(heap, (0..3).collect::<SmallList<u32>>());
+        assert_ne!(heap, (0..4).collect::<SmallList<u32>>());
+    }
+}
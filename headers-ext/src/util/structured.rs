@@ -0,0 +1,814 @@
+//! Structured Field Values, defined in
+//! [RFC8941](https://tools.ietf.org/html/rfc8941).
+//!
+//! Parsing is strict: anything that doesn't match the grammar makes the
+//! whole field invalid, as the RFC requires. Serializing always produces
+//! the canonical form.
+
+// Tokens are only used by `CacheStatus` and `ProxyStatus`, but the rest is
+// always needed by `Priority`.
+#![cfg_attr(not(any(feature = "caching", feature = "proxy")), allow(dead_code))]
+
+use std::fmt;
+
+use base64;
+
+use HeaderValue;
+
+/// A bare item, without parameters.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum BareItem {
+    Integer(i64),
+    /// A decimal, stored as a number of thousandths since the grammar
+    /// only allows three fractional digits.
+    Decimal(i64),
+    String(String),
+    Token(String),
+    ByteSequence(Vec<u8>),
+    Boolean(bool),
+}
+
+/// An item with its parameters, in order.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Item {
+    pub(crate) bare: BareItem,
+    pub(crate) params: Parameters,
+}
+
+/// A parenthesized list of items, with its own parameters.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct InnerList {
+    pub(crate) items: Vec<Item>,
+    pub(crate) params: Parameters,
+}
+
+/// A member of a list or a dictionary.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Member {
+    Item(Item),
+    InnerList(InnerList),
+}
+
+/// An ordered map of parameter keys to bare items.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Parameters(Vec<(String, BareItem)>);
+
+/// An ordered map of keys to members.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Dictionary(Vec<(String, Member)>);
+
+/// The largest absolute value of an `sf-integer`.
+const MAX_INTEGER: i64 = 999_999_999_999_999;
+
+/// The largest absolute value of an `sf-decimal`, in thousandths.
+const MAX_DECIMAL: i64 = 999_999_999_999_999;
+
+impl BareItem {
+    pub(crate) fn as_integer(&self) -> Option<i64> {
+        match *self {
+            BareItem::Integer(i) => Some(i),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match *self {
+            BareItem::String(ref s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_token(&self) -> Option<&str> {
+        match *self {
+            BareItem::Token(ref s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_bool(&self) -> Option<bool> {
+        match *self {
+            BareItem::Boolean(b) => Some(b),
+            _ => None,
+        }
+    }
+}
+
+impl Item {
+    pub(crate) fn new(bare: BareItem) -> Item {
+        Item {
+            bare,
+            params: Parameters::default(),
+        }
+    }
+}
+
+impl Member {
+    pub(crate) fn into_item(self) -> Option<Item> {
+        match self {
+            Member::Item(item) => Some(item),
+            Member::InnerList(_) => None,
+        }
+    }
+
+    pub(crate) fn as_item(&self) -> Option<&Item> {
+        match *self {
+            Member::Item(ref item) => Some(item),
+            Member::InnerList(_) => None,
+        }
+    }
+}
+
+impl Parameters {
+    pub(crate) fn get(&self, key: &str) -> Option<&BareItem> {
+        self.0
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    #[cfg(feature = "auth")]
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&str, &BareItem)> {
+        self.0.iter().map(|(k, v)| (k.as_str(), v))
+    }
+
+    /// Sets the parameter, replacing the value in place if `key` is
+    /// already present.
+    pub(crate) fn insert(&mut self, key: &str, value: BareItem) {
+        debug_assert!(is_key(key), "invalid structured field key: {:?}", key);
+        if let Some(slot) = self.0.iter_mut().find(|(k, _)| k == key) {
+            slot.1 = value;
+            return;
+        }
+        self.0.push((key.to_owned(), value));
+    }
+}
+
+impl Dictionary {
+    pub(crate) fn get(&self, key: &str) -> Option<&Member> {
+        self.0
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    #[cfg(feature = "auth")]
+    pub(crate) fn into_members(self) -> impl Iterator<Item = (String, Member)> {
+        self.0.into_iter()
+    }
+
+    /// Sets the member, replacing the value in place if `key` is
+    /// already present.
+    pub(crate) fn insert(&mut self, key: &str, value: Member) {
+        debug_assert!(is_key(key), "invalid structured field key: {:?}", key);
+        if let Some(slot) = self.0.iter_mut().find(|(k, _)| k == key) {
+            slot.1 = value;
+            return;
+        }
+        self.0.push((key.to_owned(), value));
+    }
+}
+
+/// Parses an `sf-item`.
+///
+/// Multiple field lines are combined first, so only a single line can
+/// make a valid item.
+pub(crate) fn parse_item<'a, I>(values: I) -> Option<Item>
+where
+    I: IntoIterator<Item = &'a HeaderValue>,
+{
+    let mut values = values.into_iter();
+    let value = values.next()?;
+    if values.next().is_some() {
+        return None;
+    }
+
+    let mut parser = Parser::new(value.as_bytes());
+    parser.discard_sp();
+    let item = parser.parse_item()?;
+    parser.discard_sp();
+    if parser.is_empty() {
+        Some(item)
+    } else {
+        None
+    }
+}
+
+/// Parses an `sf-list`, combining multiple field lines.
+pub(crate) fn parse_list<'a, I>(values: I) -> Option<Vec<Member>>
+where
+    I: IntoIterator<Item = &'a HeaderValue>,
+{
+    let mut members = Vec::new();
+    for value in values {
+        let mut parser = Parser::new(value.as_bytes());
+        parser.discard_sp();
+        while !parser.is_empty() {
+            members.push(parser.parse_member()?);
+            if !parser.parse_separator()? {
+                break;
+            }
+        }
+    }
+    Some(members)
+}
+
+/// Parses an `sf-dictionary`, combining multiple field lines.
+///
+/// When a key is repeated, the last value wins but keeps the position
+/// of the first.
+pub(crate) fn parse_dictionary<'a, I>(values: I) -> Option<Dictionary>
+where
+    I: IntoIterator<Item = &'a HeaderValue>,
+{
+    let mut dict = Dictionary::default();
+    for value in values {
+        let mut parser = Parser::new(value.as_bytes());
+        parser.discard_sp();
+        while !parser.is_empty() {
+            let key = parser.parse_key()?;
+            let member = if parser.peek() == Some(b'=') {
+                parser.pos += 1;
+                parser.parse_member()?
+            } else {
+                let params = parser.parse_parameters()?;
+                Member::Item(Item {
+                    bare: BareItem::Boolean(true),
+                    params,
+                })
+            };
+            dict.insert(&key, member);
+            if !parser.parse_separator()? {
+                break;
+            }
+        }
+    }
+    Some(dict)
+}
+
+/// Serializes a list of members.
+pub(crate) struct List<I>(pub(crate) I);
+
+impl<I> fmt::Display for List<I>
+where
+    I: IntoIterator + Copy,
+    I::Item: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        ::headers_core::encode::comma_delimited(f, self.0.into_iter())
+    }
+}
+
+impl fmt::Display for Dictionary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, (key, member)) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            f.write_str(key)?;
+            match *member {
+                Member::Item(Item { bare: BareItem::Boolean(true), ref params }) => {
+                    fmt::Display::fmt(params, f)?;
+                },
+                ref member => write!(f, "={}", member)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Member {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Member::Item(ref item) => fmt::Display::fmt(item, f),
+            Member::InnerList(ref list) => fmt::Display::fmt(list, f),
+        }
+    }
+}
+
+impl fmt::Display for InnerList {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("(")?;
+        for (i, item) in self.items.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            fmt::Display::fmt(item, f)?;
+        }
+        f.write_str(")")?;
+        fmt::Display::fmt(&self.params, f)
+    }
+}
+
+impl fmt::Display for Item {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.bare, f)?;
+        fmt::Display::fmt(&self.params, f)
+    }
+}
+
+impl fmt::Display for Parameters {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (key, value) in &self.0 {
+            write!(f, ";{}", key)?;
+            if *value != BareItem::Boolean(true) {
+                write!(f, "={}", value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for BareItem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BareItem::Integer(i) => {
+                debug_assert!(i.abs() <= MAX_INTEGER, "sf-integer out of range: {}", i);
+                fmt::Display::fmt(&i, f)
+            },
+            BareItem::Decimal(millis) => {
+                debug_assert!(millis.abs() <= MAX_DECIMAL, "sf-decimal out of range: {}", millis);
+                if millis < 0 {
+                    f.write_str("-")?;
+                }
+                let abs = millis.abs();
+                let frac = format!("{:03}", abs % 1000);
+                let frac = frac.trim_end_matches('0');
+                write!(f, "{}.{}", abs / 1000, if frac.is_empty() { "0" } else { frac })
+            },
+            BareItem::String(ref s) => {
+                f.write_str("\"")?;
+                for c in s.chars() {
+                    if c == '"' || c == '\\' {
+                        f.write_str("\\")?;
+                    }
+                    write!(f, "{}", c)?;
+                }
+                f.write_str("\"")
+            },
+            BareItem::Token(ref t) => f.write_str(t),
+            BareItem::ByteSequence(ref bytes) => write!(f, ":{}:", base64::encode(bytes)),
+            BareItem::Boolean(b) => f.write_str(if b { "?1" } else { "?0" }),
+        }
+    }
+}
+
+/// Checks if `s` is a valid `key`.
+pub(crate) fn is_key(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    match bytes.first() {
+        Some(&c) if c.is_ascii_lowercase() || c == b'*' => (),
+        _ => return false,
+    }
+    bytes[1..].iter().all(|&c| is_key_char(c))
+}
+
+/// Checks if `s` is a valid `sf-token`.
+pub(crate) fn is_token(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    match bytes.first() {
+        Some(&c) if c.is_ascii_alphabetic() || c == b'*' => (),
+        _ => return false,
+    }
+    bytes[1..].iter().all(|&c| is_token_char(c))
+}
+
+/// Checks if `s` can be serialized as an `sf-string`.
+pub(crate) fn is_string(s: &str) -> bool {
+    s.bytes().all(|c| (0x20..0x7F).contains(&c))
+}
+
+/// Serializes an identifier as an `sf-token` if it can be one, and
+/// otherwise as an `sf-string`.
+pub(crate) fn identifier(s: &str) -> BareItem {
+    if is_token(s) {
+        BareItem::Token(s.into())
+    } else {
+        assert!(is_string(s), "invalid identifier: {:?}", s);
+        BareItem::String(s.into())
+    }
+}
+
+fn is_key_char(c: u8) -> bool {
+    c.is_ascii_lowercase() || c.is_ascii_digit() || b"_-.*".contains(&c)
+}
+
+fn is_token_char(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~:/".contains(&c)
+}
+
+fn is_base64_char(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'+' || c == b'/' || c == b'='
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a [u8]) -> Parser<'a> {
+        Parser {
+            input,
+            pos: 0,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos >= self.input.len()
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).cloned()
+    }
+
+    fn bump(&mut self) -> Option<u8> {
+        let c = self.peek()?;
+        self.pos += 1;
+        Some(c)
+    }
+
+    fn expect(&mut self, c: u8) -> Option<()> {
+        if self.bump()? == c {
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    fn discard_sp(&mut self) {
+        while self.peek() == Some(b' ') {
+            self.pos += 1;
+        }
+    }
+
+    fn discard_ows(&mut self) {
+        while let Some(b' ') | Some(b'\t') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    /// Consumes the `,` between list or dictionary members.
+    ///
+    /// Returns `Some(false)` at the end of the input, and `None` if the
+    /// separator is missing or trailing.
+    fn parse_separator(&mut self) -> Option<bool> {
+        self.discard_ows();
+        if self.is_empty() {
+            return Some(false);
+        }
+        self.expect(b',')?;
+        self.discard_ows();
+        if self.is_empty() {
+            // trailing comma
+            return None;
+        }
+        Some(true)
+    }
+
+    fn parse_member(&mut self) -> Option<Member> {
+        if self.peek() == Some(b'(') {
+            self.parse_inner_list().map(Member::InnerList)
+        } else {
+            self.parse_item().map(Member::Item)
+        }
+    }
+
+    fn parse_inner_list(&mut self) -> Option<InnerList> {
+        self.expect(b'(')?;
+        let mut items = Vec::new();
+        loop {
+            self.discard_sp();
+            if self.peek()? == b')' {
+                self.pos += 1;
+                break;
+            }
+            items.push(self.parse_item()?);
+            match self.peek()? {
+                b' ' | b')' => (),
+                _ => return None,
+            }
+        }
+        let params = self.parse_parameters()?;
+        Some(InnerList {
+            items,
+            params,
+        })
+    }
+
+    fn parse_item(&mut self) -> Option<Item> {
+        let bare = self.parse_bare_item()?;
+        let params = self.parse_parameters()?;
+        Some(Item {
+            bare,
+            params,
+        })
+    }
+
+    fn parse_parameters(&mut self) -> Option<Parameters> {
+        let mut params = Parameters::default();
+        while self.peek() == Some(b';') {
+            self.pos += 1;
+            self.discard_sp();
+            let key = self.parse_key()?;
+            let value = if self.peek() == Some(b'=') {
+                self.pos += 1;
+                self.parse_bare_item()?
+            } else {
+                BareItem::Boolean(true)
+            };
+            params.insert(&key, value);
+        }
+        Some(params)
+    }
+
+    fn parse_key(&mut self) -> Option<String> {
+        match self.peek() {
+            Some(c) if c.is_ascii_lowercase() || c == b'*' => (),
+            _ => return None,
+        }
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if !is_key_char(c) {
+                break;
+            }
+            self.pos += 1;
+        }
+        Some(self.slice_from(start).to_owned())
+    }
+
+    fn parse_bare_item(&mut self) -> Option<BareItem> {
+        match self.peek()? {
+            b'-' | b'0'..=b'9' => self.parse_number(),
+            b'"' => self.parse_string(),
+            b':' => self.parse_byte_sequence(),
+            b'?' => self.parse_boolean(),
+            c if c.is_ascii_alphabetic() || c == b'*' => self.parse_token(),
+            _ => None,
+        }
+    }
+
+    fn parse_number(&mut self) -> Option<BareItem> {
+        let neg = if self.peek() == Some(b'-') {
+            self.pos += 1;
+            true
+        } else {
+            false
+        };
+
+        let start = self.pos;
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.pos += 1;
+        }
+        let int_digits = self.pos - start;
+        if int_digits == 0 {
+            return None;
+        }
+        let int = self.slice_from(start).parse::<i64>().ok();
+
+        if self.peek() != Some(b'.') {
+            if int_digits > 15 {
+                return None;
+            }
+            let int = int?;
+            return Some(BareItem::Integer(if neg { -int } else { int }));
+        }
+
+        if int_digits > 12 {
+            return None;
+        }
+        self.pos += 1;
+        let frac_start = self.pos;
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.pos += 1;
+        }
+        let frac = self.slice_from(frac_start);
+        if frac.is_empty() || frac.len() > 3 {
+            return None;
+        }
+
+        let mut millis = int? * 1000;
+        let scale = [100, 10, 1];
+        for (digit, scale) in frac.bytes().zip(scale.iter()) {
+            millis += i64::from(digit - b'0') * scale;
+        }
+        Some(BareItem::Decimal(if neg { -millis } else { millis }))
+    }
+
+    fn parse_string(&mut self) -> Option<BareItem> {
+        self.expect(b'"')?;
+        let mut out = String::new();
+        loop {
+            match self.bump()? {
+                b'\\' => match self.bump()? {
+                    c @ b'"' | c @ b'\\' => out.push(c as char),
+                    _ => return None,
+                },
+                b'"' => return Some(BareItem::String(out)),
+                c if (0x20..0x7F).contains(&c) => out.push(c as char),
+                _ => return None,
+            }
+        }
+    }
+
+    fn parse_token(&mut self) -> Option<BareItem> {
+        let start = self.pos;
+        self.pos += 1;
+        while let Some(c) = self.peek() {
+            if !is_token_char(c) {
+                break;
+            }
+            self.pos += 1;
+        }
+        Some(BareItem::Token(self.slice_from(start).to_owned()))
+    }
+
+    fn parse_byte_sequence(&mut self) -> Option<BareItem> {
+        self.expect(b':')?;
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if !is_base64_char(c) {
+                break;
+            }
+            self.pos += 1;
+        }
+        let encoded = self.slice_from(start);
+        self.expect(b':')?;
+        base64::decode(encoded).ok().map(BareItem::ByteSequence)
+    }
+
+    fn parse_boolean(&mut self) -> Option<BareItem> {
+        self.expect(b'?')?;
+        match self.bump()? {
+            b'0' => Some(BareItem::Boolean(false)),
+            b'1' => Some(BareItem::Boolean(true)),
+            _ => None,
+        }
+    }
+
+    fn slice_from(&self, start: usize) -> &'a str {
+        // Only called after consuming ASCII characters.
+        ::std::str::from_utf8(&self.input[start..self.pos])
+            .expect("structured field parser only consumes ASCII")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Option<Vec<Member>> {
+        parse_list(&[HeaderValue::from_str(s).unwrap()])
+    }
+
+    fn parse_items(s: &str) -> Option<Vec<Item>> {
+        parse(s)?.into_iter().map(Member::into_item).collect()
+    }
+
+    fn dict(s: &str) -> Option<Dictionary> {
+        parse_dictionary(&[HeaderValue::from_str(s).unwrap()])
+    }
+
+    fn item(s: &str) -> Option<Item> {
+        parse_item(&[HeaderValue::from_str(s).unwrap()])
+    }
+
+    fn roundtrip(s: &str) -> String {
+        List(&parse(s).unwrap()).to_string()
+    }
+
+    #[test]
+    fn parse_bare_items() {
+        let items = parse_items("42, -7, 1.5, -0.25, \"a \\\"b\\\"\", tok/en:1, :aGVsbG8=:, ?0, *star").unwrap();
+        let bare = items.into_iter().map(|i| i.bare).collect::<Vec<_>>();
+        assert_eq!(bare, vec![
+            BareItem::Integer(42),
+            BareItem::Integer(-7),
+            BareItem::Decimal(1500),
+            BareItem::Decimal(-250),
+            BareItem::String("a \"b\"".into()),
+            BareItem::Token("tok/en:1".into()),
+            BareItem::ByteSequence(b"hello".to_vec()),
+            BareItem::Boolean(false),
+            BareItem::Token("*star".into()),
+        ]);
+    }
+
+    #[test]
+    fn parse_parameters() {
+        let items = parse_items("abc;a=1;b;c=\"x\";a=2").unwrap();
+        assert_eq!(items.len(), 1);
+        let params = &items[0].params;
+        assert_eq!(params.get("a"), Some(&BareItem::Integer(2)));
+        assert_eq!(params.get("b"), Some(&BareItem::Boolean(true)));
+        assert_eq!(params.get("c"), Some(&BareItem::String("x".into())));
+        assert_eq!(params.0.iter().map(|(k, _)| k.as_str()).collect::<Vec<_>>(), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn parse_multiple_lines() {
+        let items = parse_list(&[
+            HeaderValue::from_static("a, b"),
+            HeaderValue::from_static("c"),
+        ]).unwrap();
+        assert_eq!(items.len(), 3);
+    }
+
+    #[test]
+    fn parse_inner_lists() {
+        let members = parse("(a b;x=1);y, (), c").unwrap();
+        assert_eq!(members.len(), 3);
+        match members[0] {
+            Member::InnerList(ref list) => {
+                assert_eq!(list.items.len(), 2);
+                assert_eq!(list.items[1].params.get("x"), Some(&BareItem::Integer(1)));
+                assert_eq!(list.params.get("y"), Some(&BareItem::Boolean(true)));
+            },
+            ref other => panic!("expected inner list: {:?}", other),
+        }
+        assert_eq!(members[1], Member::InnerList(InnerList {
+            items: Vec::new(),
+            params: Parameters::default(),
+        }));
+    }
+
+    #[test]
+    fn parse_dictionaries() {
+        let d = dict("u=3, i, a=(1 2), b;p=?0, u=5").unwrap();
+        assert_eq!(d.get("u"), Some(&Member::Item(Item::new(BareItem::Integer(5)))));
+        assert_eq!(d.get("i"), Some(&Member::Item(Item::new(BareItem::Boolean(true)))));
+        assert!(matches!(d.get("a"), Some(&Member::InnerList(_))));
+        assert_eq!(d.get("b").and_then(Member::as_item).unwrap().params.get("p"), Some(&BareItem::Boolean(false)));
+        assert_eq!(d.to_string(), "u=5, i, a=(1 2), b;p=?0");
+
+        assert_eq!(dict(""), Some(Dictionary::default()));
+    }
+
+    #[test]
+    fn parse_single_items() {
+        assert_eq!(item("  ?1;a "), Some(Item {
+            bare: BareItem::Boolean(true),
+            params: {
+                let mut params = Parameters::default();
+                params.insert("a", BareItem::Boolean(true));
+                params
+            },
+        }));
+        assert_eq!(item("1, 2"), None);
+        assert_eq!(parse_item(&[HeaderValue::from_static("1"), HeaderValue::from_static("2")]), None);
+    }
+
+    #[test]
+    fn parse_failures() {
+        assert_eq!(parse("a,"), None);
+        assert_eq!(parse("a,,b"), None);
+        assert_eq!(parse("a b"), None);
+        assert_eq!(parse("\"unterminated"), None);
+        assert_eq!(parse("\"bad \\escape\""), None);
+        assert_eq!(parse("1234567890123456"), None);
+        assert_eq!(parse("1."), None);
+        assert_eq!(parse("1.2345"), None);
+        assert_eq!(parse("1234567890123.5"), None);
+        assert_eq!(parse(":not base64!:"), None);
+        assert_eq!(parse(":aGVsbG8="), None);
+        assert_eq!(parse("?2"), None);
+        assert_eq!(parse("a;B=1"), None);
+        assert_eq!(parse("(a b"), None);
+        assert_eq!(parse("(a,b)"), None);
+        assert_eq!(dict("A=1"), None);
+        assert_eq!(dict("a=1,"), None);
+        assert_eq!(dict("a ;b"), None);
+    }
+
+    #[test]
+    fn serialize_canonical() {
+        assert_eq!(roundtrip("a;x=?1;y=?0,   \"s\"  ,\t7"), "a;x;y=?0, \"s\", 7");
+        assert_eq!(roundtrip("\"q\\\"\\\\\""), "\"q\\\"\\\\\"");
+        assert_eq!(roundtrip("1.500, -0.050, 2.0"), "1.5, -0.05, 2.0");
+        assert_eq!(roundtrip("(  a   b  );p, :AQID:"), "(a b);p, :AQID:");
+    }
+
+    #[test]
+    fn round_trips_are_stable() {
+        let lists = [
+            "1, 2.5, \"s\", t, :AA==:, ?0",
+            "a;b=1;c, (x;y z);w=\"v\"",
+            "  (  ), -999999999999999 ,\t999999999999.999",
+        ];
+        for &s in &lists {
+            let parsed = parse(s).unwrap();
+            let serialized = List(&parsed).to_string();
+            // Serialize then parse is the identity...
+            assert_eq!(parse(&serialized).as_ref(), Some(&parsed), "{:?}", s);
+            // ...and parse then serialize is canonical.
+            assert_eq!(roundtrip(&serialized), serialized, "{:?}", s);
+        }
+
+        let dicts = ["a=1, b, c=(1 2);x, d=:AQID:;y=4.5", " z;q=?0  "];
+        for &s in &dicts {
+            let parsed = dict(s).unwrap();
+            let serialized = parsed.to_string();
+            assert_eq!(dict(&serialized).as_ref(), Some(&parsed), "{:?}", s);
+            assert_eq!(dict(&serialized).unwrap().to_string(), serialized, "{:?}", s);
+        }
+    }
+}
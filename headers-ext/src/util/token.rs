@@ -0,0 +1,77 @@
+//! Tokens, as defined in
+//! [RFC7230](https://tools.ietf.org/html/rfc7230#section-3.2.6).
+//!
+//! ```text
+//! token = 1*tchar
+//! tchar = "!" / "#" / "$" / "%" / "&" / "'" / "*"
+//!       / "+" / "-" / "." / "^" / "_" / "`" / "|" / "~"
+//!       / DIGIT / ALPHA
+//! ```
+
+fn is_tchar(b: u8) -> bool {
+    match b {
+        b'!' | b'#' | b'$' | b'%' | b'&' | b'\'' | b'*' | b'+' | b'-' | b'.' | b'^' | b'_'
+        | b'`' | b'|' | b'~' => true,
+        _ => b.is_ascii_alphanumeric(),
+    }
+}
+
+/// Check if `s` is a valid token.
+pub(crate) fn is_token(s: &[u8]) -> bool {
+    !s.is_empty() && s.iter().cloned().all(is_tchar)
+}
+
+fn is_ows(b: &u8) -> bool {
+    *b == b' ' || *b == b'\t'
+}
+
+/// Trim optional whitespace, which is only spaces and horizontal tabs, from
+/// both ends of `s`.
+pub(crate) fn trim_ows(s: &str) -> &str {
+    s.trim_matches(|c| c == ' ' || c == '\t')
+}
+
+/// Trim optional whitespace from both ends of the bytes `s`, like
+/// `trim_ows`.
+pub(crate) fn trim_ows_bytes(s: &[u8]) -> &[u8] {
+    let start = s.iter().position(|b| !is_ows(b)).unwrap_or(s.len());
+    let end = s.iter().rposition(|b| !is_ows(b)).map_or(start, |i| i + 1);
+    &s[start..end]
+}
+
+/// Compare `a`, ignoring any surrounding optional whitespace, to the token
+/// `b`, ignoring ASCII case.
+///
+/// Most tokens, such as transfer codings and cache directives, are
+/// case-insensitive.
+pub(crate) fn eq_ignore_ascii_case_trimmed(a: &[u8], b: &str) -> bool {
+    trim_ows_bytes(a).eq_ignore_ascii_case(b.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{eq_ignore_ascii_case_trimmed, is_token};
+
+    #[test]
+    fn tokens() {
+        assert!(is_token(b"gzip"));
+        assert!(is_token(b"100-continue"));
+        assert!(is_token(b"!#$%&'*+-.^_`|~"));
+        assert!(!is_token(b""));
+        assert!(!is_token(b"a b"));
+        assert!(!is_token(b"a,b"));
+        assert!(!is_token(b"\"a\""));
+        assert!(!is_token("caf\u{e9}".as_bytes()));
+    }
+
+    #[test]
+    fn eq_trims_ows_and_ignores_case() {
+        assert!(eq_ignore_ascii_case_trimmed(b"chunked", "chunked"));
+        assert!(eq_ignore_ascii_case_trimmed(b" \tChunKed ", "chunked"));
+        assert!(eq_ignore_ascii_case_trimmed(b"", ""));
+        assert!(eq_ignore_ascii_case_trimmed(b"  ", ""));
+        assert!(!eq_ignore_ascii_case_trimmed(b"chunked,", "chunked"));
+        assert!(!eq_ignore_ascii_case_trimmed(b"\nchunked", "chunked"));
+        assert!(!eq_ignore_ascii_case_trimmed(b"chunk ed", "chunked"));
+    }
+}
@@ -0,0 +1,490 @@
+//! URI references, as defined in
+//! [RFC3986](https://tools.ietf.org/html/rfc3986#section-4.1).
+//!
+//! ```text
+//! URI-reference = URI / relative-ref
+//! URI           = scheme ":" hier-part [ "?" query ] [ "#" fragment ]
+//! relative-ref  = relative-part [ "?" query ] [ "#" fragment ]
+//! ```
+//!
+//! Unlike `http::Uri`, this keeps relative references such as `../up/one`
+//! and fragments, so headers like `Location` can be resolved the way a
+//! client following them would.
+
+use std::fmt;
+
+use http::Uri;
+
+use util::HeaderString;
+
+/// A parsed URI reference, split into its five components.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct UriReference {
+    scheme: Option<String>,
+    authority: Option<String>,
+    path: String,
+    query: Option<String>,
+    fragment: Option<String>,
+}
+
+/// Parse a header value as a URI reference.
+pub(crate) fn parse_reference(value: &HeaderString) -> Option<UriReference> {
+    UriReference::parse(value.as_str())
+}
+
+fn is_unreserved(b: u8) -> bool {
+    match b {
+        b'-' | b'.' | b'_' | b'~' => true,
+        _ => b.is_ascii_alphanumeric(),
+    }
+}
+
+fn is_uri_char(b: u8) -> bool {
+    match b {
+        // gen-delims, without `#`, which only starts the fragment.
+        b':' | b'/' | b'?' | b'[' | b']' | b'@' => true,
+        // sub-delims
+        b'!' | b'$' | b'&' | b'\'' | b'(' | b')' | b'*' | b'+' | b',' | b';' | b'=' => true,
+        b'%' => true,
+        _ => is_unreserved(b),
+    }
+}
+
+/// Check that `s` only has characters allowed in a URI, and that every `%`
+/// starts a percent-encoded octet.
+fn is_valid_component(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    bytes.iter().enumerate().all(|(i, &b)| {
+        if b == b'%' {
+            bytes.get(i + 1..i + 3).is_some_and(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+        } else {
+            is_uri_char(b)
+        }
+    })
+}
+
+fn is_scheme(s: &str) -> bool {
+    let mut bytes = s.bytes();
+    bytes.next().is_some_and(|b| b.is_ascii_alphabetic())
+        && bytes.all(|b| b.is_ascii_alphanumeric() || b == b'+' || b == b'-' || b == b'.')
+}
+
+impl UriReference {
+    /// Parse a URI reference, splitting it as in
+    /// [RFC3986, appendix B](https://tools.ietf.org/html/rfc3986#appendix-B).
+    pub(crate) fn parse(s: &str) -> Option<UriReference> {
+        let (rest, fragment) = match s.find('#') {
+            Some(i) => (&s[..i], Some(&s[i + 1..])),
+            None => (s, None),
+        };
+        let (rest, query) = match rest.find('?') {
+            Some(i) => (&rest[..i], Some(&rest[i + 1..])),
+            None => (rest, None),
+        };
+        let (scheme, rest) = match rest.find([':', '/']) {
+            Some(i) if rest.as_bytes()[i] == b':' => {
+                if !is_scheme(&rest[..i]) {
+                    return None;
+                }
+                (Some(&rest[..i]), &rest[i + 1..])
+            }
+            _ => (None, rest),
+        };
+        let (authority, path) = if let Some(rest) = rest.strip_prefix("//") {
+            let end = rest.find('/').unwrap_or(rest.len());
+            (Some(&rest[..end]), &rest[end..])
+        } else {
+            (None, rest)
+        };
+
+        let components = [authority, Some(path), query, fragment];
+        if !components.iter().flatten().all(|c| is_valid_component(c)) {
+            return None;
+        }
+
+        Some(UriReference {
+            scheme: scheme.map(ToOwned::to_owned),
+            authority: authority.map(ToOwned::to_owned),
+            path: path.to_owned(),
+            query: query.map(ToOwned::to_owned),
+            fragment: fragment.map(ToOwned::to_owned),
+        })
+    }
+
+    /// Parse the reference held by a `Uri`.
+    pub(crate) fn from_uri(uri: &Uri) -> Option<UriReference> {
+        UriReference::parse(&uri.to_string())
+    }
+
+    /// Remove the fragment.
+    pub(crate) fn without_fragment(mut self) -> UriReference {
+        self.fragment = None;
+        self
+    }
+
+    /// Remove the userinfo, such as a user name and password, from the
+    /// authority.
+    pub(crate) fn without_userinfo(mut self) -> UriReference {
+        if let Some(ref mut authority) = self.authority {
+            if let Some(i) = authority.rfind('@') {
+                authority.drain(..=i);
+            }
+        }
+        self
+    }
+
+    /// Resolve this reference against `base`, as in
+    /// [RFC3986, section 5.2](https://tools.ietf.org/html/rfc3986#section-5.2).
+    ///
+    /// The fragment is dropped, since a `Uri` can't hold one. Returns `None`
+    /// if the result isn't a valid `Uri`, such as when `base` is only an
+    /// authority.
+    pub(crate) fn resolve(&self, base: &Uri) -> Option<Uri> {
+        let base = UriReference::from_uri(base)?;
+        self.resolve_reference(&base)
+            .without_fragment()
+            .to_string()
+            .parse()
+            .ok()
+    }
+
+    /// Check if this reference, resolved against `base`, refers to the same
+    /// resource as `uri`, once both are normalized.
+    pub(crate) fn resolves_to(&self, base: &Uri, uri: &Uri) -> bool {
+        match (UriReference::from_uri(base), UriReference::from_uri(uri)) {
+            (Some(base), Some(uri)) => {
+                let target = self.resolve_reference(&base).without_fragment();
+                target.normalize() == uri.normalize()
+            }
+            _ => false,
+        }
+    }
+
+    /// Resolve this reference against another, keeping fragments.
+    pub(crate) fn resolve_reference(&self, base: &UriReference) -> UriReference {
+        let mut target = if self.scheme.is_some() {
+            UriReference {
+                path: remove_dot_segments(&self.path),
+                ..self.clone()
+            }
+        } else if self.authority.is_some() {
+            UriReference {
+                scheme: base.scheme.clone(),
+                path: remove_dot_segments(&self.path),
+                ..self.clone()
+            }
+        } else if self.path.is_empty() {
+            UriReference {
+                query: self.query.clone().or_else(|| base.query.clone()),
+                ..base.clone()
+            }
+        } else {
+            let path = if self.path.starts_with('/') {
+                self.path.clone()
+            } else {
+                merge(base, &self.path)
+            };
+            UriReference {
+                path: remove_dot_segments(&path),
+                query: self.query.clone(),
+                ..base.clone()
+            }
+        };
+        target.fragment = self.fragment.clone();
+        target
+    }
+
+    /// Normalize this reference for comparison, as in
+    /// [RFC3986, section 6.2.2](https://tools.ietf.org/html/rfc3986#section-6.2.2).
+    ///
+    /// This lowercases the scheme and host, elides the scheme's default
+    /// port, normalizes percent-encoding, and removes dot segments unless
+    /// the path is relative, where they are still meaningful.
+    pub(crate) fn normalize(&self) -> UriReference {
+        let scheme = self.scheme.as_ref().map(|s| s.to_ascii_lowercase());
+        let authority = self
+            .authority
+            .as_ref()
+            .map(|a| normalize_authority(a, scheme.as_deref()));
+
+        let mut path = normalize_percent_encoding(&self.path);
+        if scheme.is_some() || authority.is_some() || path.starts_with('/') {
+            path = remove_dot_segments(&path);
+        }
+        if path.is_empty() && authority.is_some() {
+            path.push('/');
+        }
+
+        UriReference {
+            scheme,
+            authority,
+            path,
+            query: self.query.as_ref().map(|q| normalize_percent_encoding(q)),
+            fragment: self.fragment.as_ref().map(|f| normalize_percent_encoding(f)),
+        }
+    }
+}
+
+impl fmt::Display for UriReference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ref scheme) = self.scheme {
+            write!(f, "{}:", scheme)?;
+        }
+        if let Some(ref authority) = self.authority {
+            write!(f, "//{}", authority)?;
+        }
+        f.write_str(&self.path)?;
+        if let Some(ref query) = self.query {
+            write!(f, "?{}", query)?;
+        }
+        if let Some(ref fragment) = self.fragment {
+            write!(f, "#{}", fragment)?;
+        }
+        Ok(())
+    }
+}
+
+/// Merge a relative path with the path of `base`, as in
+/// [RFC3986, section 5.2.3](https://tools.ietf.org/html/rfc3986#section-5.2.3).
+fn merge(base: &UriReference, path: &str) -> String {
+    if base.authority.is_some() && base.path.is_empty() {
+        format!("/{}", path)
+    } else {
+        let dir = base.path.rfind('/').map_or("", |i| &base.path[..=i]);
+        format!("{}{}", dir, path)
+    }
+}
+
+/// Remove `.` and `..` segments, as in
+/// [RFC3986, section 5.2.4](https://tools.ietf.org/html/rfc3986#section-5.2.4).
+fn remove_dot_segments(path: &str) -> String {
+    let mut input = path;
+    let mut output = String::with_capacity(path.len());
+
+    while !input.is_empty() {
+        if input.starts_with("../") {
+            input = &input[3..];
+        } else if input.starts_with("./") || input.starts_with("/./") {
+            input = &input[2..];
+        } else if input == "/." {
+            input = "/";
+        } else if input.starts_with("/../") || input == "/.." {
+            input = if input.len() == 3 { "/" } else { &input[3..] };
+            let last = output.rfind('/').unwrap_or(0);
+            output.truncate(last);
+        } else if input == "." || input == ".." {
+            input = "";
+        } else {
+            let start = if input.starts_with('/') { 1 } else { 0 };
+            let end = input[start..].find('/').map_or(input.len(), |i| i + start);
+            output.push_str(&input[..end]);
+            input = &input[end..];
+        }
+    }
+
+    output
+}
+
+fn normalize_authority(authority: &str, scheme: Option<&str>) -> String {
+    let (userinfo, host_port) = match authority.rfind('@') {
+        Some(i) => (Some(&authority[..i]), &authority[i + 1..]),
+        None => (None, authority),
+    };
+    // The port follows the last `:`, unless that is inside an IPv6 literal.
+    let (host, port) = match host_port.rfind(':') {
+        Some(i) if !host_port[i..].contains(']') => (&host_port[..i], Some(&host_port[i + 1..])),
+        _ => (host_port, None),
+    };
+    let default_port = match scheme {
+        Some("http") | Some("ws") => Some("80"),
+        Some("https") | Some("wss") => Some("443"),
+        _ => None,
+    };
+
+    let mut normalized = String::with_capacity(authority.len());
+    if let Some(userinfo) = userinfo {
+        normalized.push_str(&normalize_percent_encoding(userinfo));
+        normalized.push('@');
+    }
+    normalized.push_str(&normalize_percent_encoding(&host.to_ascii_lowercase()));
+    match port {
+        Some(port) if !port.is_empty() && Some(port) != default_port => {
+            normalized.push(':');
+            normalized.push_str(port);
+        }
+        _ => (),
+    }
+    normalized
+}
+
+/// Decode percent-encoded unreserved characters, and uppercase the hex
+/// digits of the rest.
+fn normalize_percent_encoding(s: &str) -> String {
+    let mut normalized = String::with_capacity(s.len());
+    let mut rest = s;
+
+    while let Some(i) = rest.find('%') {
+        normalized.push_str(&rest[..i]);
+        // `parse` validated that `%` is followed by 2 hex digits.
+        let hex = &rest[i + 1..i + 3];
+        let octet = u8::from_str_radix(hex, 16).expect("validated hex digits");
+        if is_unreserved(octet) {
+            normalized.push(octet as char);
+        } else {
+            normalized.push('%');
+            normalized.push_str(&hex.to_ascii_uppercase());
+        }
+        rest = &rest[i + 3..];
+    }
+
+    normalized.push_str(rest);
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UriReference;
+
+    fn parse(s: &str) -> UriReference {
+        UriReference::parse(s).unwrap_or_else(|| panic!("invalid reference: {:?}", s))
+    }
+
+    fn resolve(s: &str) -> String {
+        let base = parse("http://a/b/c/d;p?q");
+        parse(s).resolve_reference(&base).to_string()
+    }
+
+    fn normalize(s: &str) -> String {
+        parse(s).normalize().to_string()
+    }
+
+    #[test]
+    fn parse_components() {
+        let r = parse("https://user@example.com:8080/a/b?x=1#frag");
+        assert_eq!(r.scheme.as_ref().unwrap(), "https");
+        assert_eq!(r.authority.as_ref().unwrap(), "user@example.com:8080");
+        assert_eq!(r.path, "/a/b");
+        assert_eq!(r.query.as_ref().unwrap(), "x=1");
+        assert_eq!(r.fragment.as_ref().unwrap(), "frag");
+
+        let r = parse("../up/one");
+        assert_eq!(r.scheme, None);
+        assert_eq!(r.authority, None);
+        assert_eq!(r.path, "../up/one");
+        assert_eq!(r.to_string(), "../up/one");
+
+        assert_eq!(parse("").to_string(), "");
+        assert_eq!(parse("//example.com").to_string(), "//example.com");
+        assert_eq!(parse("mailto:a@example.com").to_string(), "mailto:a@example.com");
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert_eq!(UriReference::parse("not a uri"), None);
+        assert_eq!(UriReference::parse("/100%"), None);
+        assert_eq!(UriReference::parse("/%zz"), None);
+        assert_eq!(UriReference::parse("/a#b#c"), None);
+        assert_eq!(UriReference::parse("1http://example.com"), None);
+        assert_eq!(UriReference::parse("/caf\u{e9}"), None);
+    }
+
+    // https://tools.ietf.org/html/rfc3986#section-5.4.1
+    #[test]
+    fn resolve_normal_examples() {
+        let examples = [
+            ("g:h", "g:h"),
+            ("g", "http://a/b/c/g"),
+            ("./g", "http://a/b/c/g"),
+            ("g/", "http://a/b/c/g/"),
+            ("/g", "http://a/g"),
+            ("//g", "http://g"),
+            ("?y", "http://a/b/c/d;p?y"),
+            ("g?y", "http://a/b/c/g?y"),
+            ("#s", "http://a/b/c/d;p?q#s"),
+            ("g#s", "http://a/b/c/g#s"),
+            ("g?y#s", "http://a/b/c/g?y#s"),
+            (";x", "http://a/b/c/;x"),
+            ("g;x", "http://a/b/c/g;x"),
+            ("g;x?y#s", "http://a/b/c/g;x?y#s"),
+            ("", "http://a/b/c/d;p?q"),
+            (".", "http://a/b/c/"),
+            ("./", "http://a/b/c/"),
+            ("..", "http://a/b/"),
+            ("../", "http://a/b/"),
+            ("../g", "http://a/b/g"),
+            ("../..", "http://a/"),
+            ("../../", "http://a/"),
+            ("../../g", "http://a/g"),
+        ];
+        for &(reference, target) in &examples {
+            assert_eq!(resolve(reference), target, "resolving {:?}", reference);
+        }
+    }
+
+    // https://tools.ietf.org/html/rfc3986#section-5.4.2
+    #[test]
+    fn resolve_abnormal_examples() {
+        let examples = [
+            ("../../../g", "http://a/g"),
+            ("../../../../g", "http://a/g"),
+            ("/./g", "http://a/g"),
+            ("/../g", "http://a/g"),
+            ("g.", "http://a/b/c/g."),
+            (".g", "http://a/b/c/.g"),
+            ("g..", "http://a/b/c/g.."),
+            ("..g", "http://a/b/c/..g"),
+            ("./../g", "http://a/b/g"),
+            ("./g/.", "http://a/b/c/g/"),
+            ("g/./h", "http://a/b/c/g/h"),
+            ("g/../h", "http://a/b/c/h"),
+            ("g;x=1/./y", "http://a/b/c/g;x=1/y"),
+            ("g;x=1/../y", "http://a/b/c/y"),
+            ("g?y/./x", "http://a/b/c/g?y/./x"),
+            ("g?y/../x", "http://a/b/c/g?y/../x"),
+            ("g#s/./x", "http://a/b/c/g#s/./x"),
+            ("g#s/../x", "http://a/b/c/g#s/../x"),
+            ("http:g", "http:g"),
+        ];
+        for &(reference, target) in &examples {
+            assert_eq!(resolve(reference), target, "resolving {:?}", reference);
+        }
+    }
+
+    #[test]
+    fn resolve_uri() {
+        let base = "http://example.com/a/b?q".parse().unwrap();
+        let uri = parse("../up/one#frag").resolve(&base).unwrap();
+        assert_eq!(uri, "http://example.com/up/one");
+    }
+
+    #[test]
+    fn resolves_to() {
+        let base = "http://example.com/a/b".parse().unwrap();
+        let same = "HTTP://Example.com:80/a/%63".parse().unwrap();
+        assert!(parse("c#frag").resolves_to(&base, &same));
+        assert!(parse("./x/../c").resolves_to(&base, &same));
+        assert!(!parse("C").resolves_to(&base, &same));
+    }
+
+    #[test]
+    fn without_userinfo() {
+        let r = parse("http://user:pw@example.com/#frag").without_userinfo().without_fragment();
+        assert_eq!(r.to_string(), "http://example.com/");
+    }
+
+    #[test]
+    fn normalization() {
+        assert_eq!(normalize("HTTP://User@Example.COM:80"), "http://User@example.com/");
+        assert_eq!(normalize("https://example.com:443/a/./b/../c"), "https://example.com/a/c");
+        assert_eq!(normalize("https://example.com:8443/"), "https://example.com:8443/");
+        assert_eq!(normalize("http://[::1]:80/"), "http://[::1]/");
+        assert_eq!(normalize("http://[::1]/"), "http://[::1]/");
+        assert_eq!(normalize("/%7euser/a%2fb%c3%a9"), "/~user/a%2Fb%C3%A9");
+        assert_eq!(normalize("../up/./one"), "../up/./one");
+
+        assert_eq!(
+            parse("http://EXAMPLE.com:80/%7Ea").normalize(),
+            parse("http://example.com/~a").normalize()
+        );
+    }
+}
@@ -1,21 +1,46 @@
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::error::Error as StdError;
+use std::ops::Deref;
 use std::{fmt, str::{self, FromStr}};
 
 use bytes::Bytes;
 use http::header::HeaderValue;
 
-/// A value that is both a valid `HeaderValue` and `String`.
+/// A header value that is also a `str`, for headers that keep their value
+/// as a string.
+///
+/// Cloning it is cheap, as it shares the bytes of the `HeaderValue`.
+///
+/// # Example
+///
+/// ```
+/// # extern crate headers_ext as headers;
+/// use headers::HeaderString;
+///
+/// let s = HeaderString::from("hyper/0.12.2");
+/// assert!(s.starts_with("hyper/"));
+/// assert_eq!("curl/8.5.0".parse::<HeaderString>().unwrap().len(), 10);
+/// assert!("a\nb".parse::<HeaderString>().is_err());
+/// assert!("caf\u{e9}".parse::<HeaderString>().is_err());
+/// ```
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub(crate) struct HeaderValueString {
-    /// Care must be taken to only set this value when it is also
-    /// a valid `String`, since `as_str` will convert to a `&str`
-    /// in an unchecked manner.
+pub struct HeaderString {
+    /// Only ever holds UTF-8, since `as_str` doesn't check it again. Every
+    /// constructor says why its value is.
     value: HeaderValue,
 }
 
-impl HeaderValueString {
+/// An error returned when a string isn't a valid header value, of visible
+/// ASCII, spaces and tabs.
+#[derive(Debug)]
+pub struct InvalidHeaderString(pub(crate) ());
+
+impl HeaderString {
     pub(crate) fn from_val(val: &HeaderValue) -> Option<Self> {
+        // `to_str` only accepts visible ASCII.
         if val.to_str().is_ok() {
-            Some(HeaderValueString {
+            Some(HeaderString {
                 value: val.clone(),
             })
         } else {
@@ -23,63 +48,182 @@ impl HeaderValueString {
         }
     }
 
+    /// Like `from_val`, but with folded whitespace collapsed, as by
+    /// `unfold`.
+    pub(crate) fn from_val_unfolded(val: &HeaderValue) -> Option<Self> {
+        match unfold(val.to_str().ok()?) {
+            Cow::Borrowed(_) => HeaderString::from_val(val),
+            Cow::Owned(unfolded) => HeaderString::from_string(unfolded),
+        }
+    }
+
     pub(crate) fn from_string(src: String) -> Option<Self> {
-        // A valid `str` (the argument)...
+        // The bytes of a `String`.
         let bytes = Bytes::from(src);
         HeaderValue::from_shared(bytes)
             .ok()
-            .map(|value| HeaderValueString {
+            .map(|value| HeaderString {
                 value,
             })
     }
 
-    pub(crate) fn from_static(src: &'static str) -> HeaderValueString {
-        // A valid `str` (the argument)...
-        HeaderValueString {
+    pub(crate) fn from_static(src: &'static str) -> HeaderString {
+        // The bytes of a `str`.
+        HeaderString {
             value: HeaderValue::from_static(src),
         }
     }
 
-    pub(crate) fn as_str(&self) -> &str {
-        // HeaderValueString is only created from HeaderValues
-        // that have validated they are also UTF-8 strings.
+    /// View this value as a `str`.
+    pub fn as_str(&self) -> &str {
+        let bytes = self.value.as_bytes();
+        debug_assert!(str::from_utf8(bytes).is_ok(), "HeaderString isn't UTF-8: {:?}", self.value);
         unsafe {
-            str::from_utf8_unchecked(self.value.as_bytes())
+            str::from_utf8_unchecked(bytes)
+        }
+    }
+}
+
+/// Collapse each run of spaces, tabs and line breaks into a single space,
+/// and trim them from both ends.
+///
+/// This undoes obs-fold, the continuation lines of
+/// [RFC7230](https://tools.ietf.org/html/rfc7230#section-3.2.4), for free
+/// text. Parsers that unfold it themselves often leave the `CRLF`, or the
+/// tabs that started the lines.
+pub(crate) fn unfold(s: &str) -> Cow<'_, str> {
+    let is_fold = |b: u8| matches!(b, b' ' | b'\t' | b'\r' | b'\n');
+    let bytes = s.as_bytes();
+    let folded = bytes.first().is_some_and(|&b| is_fold(b))
+        || bytes.last().is_some_and(|&b| is_fold(b))
+        || bytes.windows(2).any(|w| is_fold(w[0]) && (w[0] != b' ' || is_fold(w[1])));
+    if !folded {
+        return Cow::Borrowed(s);
+    }
+
+    let mut unfolded = String::with_capacity(s.len());
+    for word in s.split(|c: char| c.is_ascii() && is_fold(c as u8)).filter(|word| !word.is_empty()) {
+        if !unfolded.is_empty() {
+            unfolded.push(' ');
         }
+        unfolded.push_str(word);
+    }
+    Cow::Owned(unfolded)
+}
+
+impl Deref for HeaderString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for HeaderString {
+    fn as_ref(&self) -> &str {
+        self.as_str()
     }
 }
 
-impl fmt::Debug for HeaderValueString {
+impl From<&'static str> for HeaderString {
+    /// Make a `HeaderString` of a static string, without copying it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `src` isn't a valid header value, such as if it contains
+    /// a line break.
+    fn from(src: &'static str) -> HeaderString {
+        HeaderString::from_static(src)
+    }
+}
+
+impl fmt::Debug for HeaderString {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         fmt::Debug::fmt(&self.value, f)
     }
 }
 
-impl ::headers_core::decode::TryFromValues for HeaderValueString {
+impl fmt::Display for HeaderString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl ::headers_core::decode::TryFromValues for HeaderString {
     fn try_from_values(values: &mut ::Values) -> Option<Self> {
-        HeaderValueString::from_val(values.next()?)
+        HeaderString::from_val(values.next()?)
     }
 }
 
-impl<'a> From<&'a HeaderValueString> for HeaderValue {
-    fn from(src: &'a HeaderValueString) -> HeaderValue {
+impl<'a> From<&'a HeaderString> for HeaderValue {
+    fn from(src: &'a HeaderString) -> HeaderValue {
         src.value.clone()
     }
 }
 
-#[derive(Debug)]
-pub(crate) struct FromStrError(());
+impl From<HeaderString> for HeaderValue {
+    fn from(src: HeaderString) -> HeaderValue {
+        src.value
+    }
+}
+
+impl TryFrom<String> for HeaderString {
+    type Error = InvalidHeaderString;
+
+    /// Make a `HeaderString` of a `String`, without copying it.
+    fn try_from(src: String) -> Result<Self, Self::Error> {
+        if !src.is_ascii() {
+            return Err(InvalidHeaderString(()));
+        }
+        HeaderString::from_string(src).ok_or(InvalidHeaderString(()))
+    }
+}
 
-impl FromStr for HeaderValueString {
-    type Err = FromStrError;
+impl FromStr for HeaderString {
+    type Err = InvalidHeaderString;
 
     fn from_str(src: &str) -> Result<Self, Self::Err> {
-        // A valid `str` (the argument)...
+        // Only ASCII, which decodes again, as `HeaderValue` takes other
+        // bytes too.
+        if !src.is_ascii() {
+            return Err(InvalidHeaderString(()));
+        }
+        // The bytes of a `str`.
         src
             .parse()
-            .map(|value| HeaderValueString {
+            .map(|value| HeaderString {
                 value,
             })
-            .map_err(|_| FromStrError(()))
+            .map_err(|_| InvalidHeaderString(()))
+    }
+}
+
+impl fmt::Display for InvalidHeaderString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid header string")
+    }
+}
+
+impl StdError for InvalidHeaderString {}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use super::unfold;
+
+    #[test]
+    fn unfold_collapses_whitespace() {
+        assert_eq!(unfold("Mozilla/5.0\r\n (X11)"), "Mozilla/5.0 (X11)");
+        assert_eq!(unfold("a\r\n\tb  c\t\td"), "a b c d");
+        assert_eq!(unfold("\ta b "), "a b");
+        assert_eq!(unfold(" \t "), "");
+    }
+
+    #[test]
+    fn unfold_borrows_unfolded() {
+        for s in &["", "curl/8.5.0", "a b c"] {
+            assert!(matches!(unfold(s), Cow::Borrowed(b) if b == *s), "{:?}", s);
+        }
     }
 }
@@ -0,0 +1,318 @@
+//! Checking every header of a map at once.
+//!
+//! A gateway rejecting a request can report all of its malformed headers
+//! together, instead of finding them one `typed_get` at a time.
+//!
+//! # Example
+//!
+//! ```
+//! # extern crate headers_ext as headers;
+//! use headers::HeaderMap;
+//! use headers::validate::{self, IssueKind};
+//!
+//! let mut map = HeaderMap::new();
+//! map.insert("content-length", "ten".parse().unwrap());
+//! map.insert("x-custom", "anything".parse().unwrap());
+//!
+//! let issues = validate::scan(&map);
+//! assert_eq!(issues.len(), 1);
+//! assert_eq!(issues[0].name(), "content-length");
+//! assert_eq!(issues[0].kind(), IssueKind::Invalid);
+//! assert_eq!(issues[0].to_string(), "invalid content-length header");
+//! ```
+//!
+//! A server can then answer with the status and message each issue
+//! suggests:
+//!
+//! ```
+//! # extern crate headers_ext as headers;
+//! # extern crate http;
+//! use headers::{ContentLength, DecodeLimits, HeaderMap, HeaderMapExt};
+//! use headers::validate::HeaderIssue;
+//! use http::StatusCode;
+//!
+//! # fn main() {
+//! let map = HeaderMap::new();
+//! let error = map.typed_get_with_limits::<ContentLength>(DecodeLimits::default()).unwrap_err();
+//! let issue = HeaderIssue::new(http::header::CONTENT_LENGTH, error.into());
+//! assert_eq!(issue.suggested_status(), StatusCode::LENGTH_REQUIRED);
+//! assert_eq!(issue.public_message(), "length required");
+//! # }
+//! ```
+
+use std::fmt;
+
+use headers_core::decode::from_value;
+use http::header;
+use http::StatusCode;
+
+use ::{DecodeError, DecodeLimits, Header, HeaderMap, HeaderMapExt, HeaderName};
+use registry::HEADERS;
+
+/// Decode every header of `map` the crate has a type for, reporting those
+/// that fail, in the order of the map.
+///
+/// Headers are decoded as `HeaderMapExt::typed_get` does, with the default
+/// `ParseMode` and `DecodeLimits`. Names the crate doesn't know, and those
+/// like `Authorization` whose types only cover some of their values, aren't
+/// checked.
+pub fn scan(map: &HeaderMap) -> Vec<HeaderIssue> {
+    let mut issues = Vec::new();
+    for name in map.keys() {
+        let entries = HEADERS
+            .iter()
+            .filter(|entry| entry.complete && (entry.name)() == name);
+        for entry in entries {
+            if let Some(kind) = (entry.check)(map) {
+                issues.push(HeaderIssue {
+                    name: name.clone(),
+                    kind,
+                });
+            }
+        }
+    }
+    issues
+}
+
+/// A header that `scan` found to be malformed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeaderIssue {
+    name: HeaderName,
+    kind: IssueKind,
+}
+
+impl HeaderIssue {
+    /// Create an issue, such as for a `DecodeError` from
+    /// `HeaderMapExt::typed_get_with_limits`.
+    pub fn new(name: HeaderName, kind: IssueKind) -> HeaderIssue {
+        HeaderIssue {
+            name,
+            kind,
+        }
+    }
+
+    /// The name of the header.
+    pub fn name(&self) -> &HeaderName {
+        &self.name
+    }
+
+    /// What's wrong with it.
+    pub fn kind(&self) -> IssueKind {
+        self.kind
+    }
+
+    /// The status a server could reject a request with for this issue.
+    ///
+    /// Most are `400 Bad Request`, and headers over the limits are `431
+    /// Request Header Fields Too Large`. Some headers have their own:
+    ///
+    /// - a missing `Content-Length` is `411 Length Required`,
+    /// - a missing `If-Match` or `If-Unmodified-Since` is `428
+    ///   Precondition Required`, for servers that need them,
+    /// - an invalid `If-Match`, `If-None-Match` or `If-Range` is `412
+    ///   Precondition Failed`,
+    /// - an invalid `Range` is `416 Range Not Satisfiable`.
+    ///
+    /// RFC7232 asks servers to ignore an `If-Modified-Since` or
+    /// `If-Unmodified-Since` that isn't a valid date, rather than reject
+    /// the request, so they only get the generic `400`.
+    pub fn suggested_status(&self) -> StatusCode {
+        self.response().0
+    }
+
+    /// A short message to send with `suggested_status`, such as
+    /// `"precondition failed"`.
+    ///
+    /// It's the same for any value, so nothing sent by the client is
+    /// echoed back.
+    pub fn public_message(&self) -> &'static str {
+        self.response().1
+    }
+
+    fn response(&self) -> (StatusCode, &'static str) {
+        let name = &self.name;
+        match self.kind {
+            IssueKind::TooLarge => (StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE, "request header fields too large"),
+            IssueKind::Missing if name == header::CONTENT_LENGTH => (StatusCode::LENGTH_REQUIRED, "length required"),
+            IssueKind::Missing if name == header::IF_MATCH || name == header::IF_UNMODIFIED_SINCE => {
+                (StatusCode::PRECONDITION_REQUIRED, "precondition required")
+            },
+            IssueKind::Missing => (StatusCode::BAD_REQUEST, "missing request header"),
+            _ if name == header::RANGE => (StatusCode::RANGE_NOT_SATISFIABLE, "range not satisfiable"),
+            _ if name == header::IF_MATCH || name == header::IF_NONE_MATCH || name == header::IF_RANGE => {
+                (StatusCode::PRECONDITION_FAILED, "precondition failed")
+            },
+            IssueKind::Duplicate => (StatusCode::BAD_REQUEST, "duplicate request header"),
+            IssueKind::Invalid => (StatusCode::BAD_REQUEST, "malformed request header"),
+        }
+    }
+}
+
+impl fmt::Display for HeaderIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            IssueKind::Invalid => write!(f, "invalid {} header", self.name),
+            IssueKind::Duplicate => write!(f, "duplicate {} header", self.name),
+            IssueKind::TooLarge => write!(f, "{} header too large", self.name),
+            IssueKind::Missing => write!(f, "missing {} header", self.name),
+        }
+    }
+}
+
+/// What's wrong with a header in a `HeaderIssue`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum IssueKind {
+    /// The header fails to decode.
+    Invalid,
+    /// The header can only have one value, but has several that are each
+    /// valid.
+    Duplicate,
+    /// The header is larger than the default `DecodeLimits` allow.
+    TooLarge,
+    /// The header isn't there. `scan` never reports it, but a server can
+    /// for a header it requires.
+    Missing,
+}
+
+impl From<DecodeError> for IssueKind {
+    fn from(error: DecodeError) -> IssueKind {
+        match error {
+            DecodeError::Missing => IssueKind::Missing,
+            DecodeError::Invalid => IssueKind::Invalid,
+            DecodeError::TooLarge => IssueKind::TooLarge,
+        }
+    }
+}
+
+pub(crate) fn check<H: Header>(map: &HeaderMap) -> Option<IssueKind> {
+    match map.typed_get_with_limits::<H>(DecodeLimits::default()) {
+        Ok(_) | Err(DecodeError::Missing) => None,
+        Err(DecodeError::TooLarge) => Some(IssueKind::TooLarge),
+        Err(DecodeError::Invalid) => {
+            let values = map.get_all(H::name());
+            let several = values.iter().nth(1).is_some();
+            if several && values.iter().all(|value| from_value::<H>(value).is_ok()) {
+                Some(IssueKind::Duplicate)
+            } else {
+                Some(IssueKind::Invalid)
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+
+    use super::{scan, HeaderIssue, IssueKind};
+    use ::{DecodeError, HeaderMap, HeaderMapExt, HeaderName};
+
+    fn map(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for &(name, value) in pairs {
+            map.append(name, value.parse().unwrap());
+        }
+        map
+    }
+
+    fn issues(map: &HeaderMap) -> Vec<(String, IssueKind)> {
+        scan(map)
+            .into_iter()
+            .map(|issue| (issue.name().to_string(), issue.kind()))
+            .collect()
+    }
+
+    #[test]
+    fn reports_every_broken_header() {
+        let map = map(&[
+            ("host", "example.com"),
+            ("content-length", "ten"),
+            ("accept-ranges", "bytes"),
+            ("if-modified-since", "yesterday"),
+            ("cache-control", "max-age=lolz"),
+            ("x-custom", "\"anything"),
+        ]);
+        assert_eq!(issues(&map), [
+            ("content-length".to_owned(), IssueKind::Invalid),
+            ("if-modified-since".to_owned(), IssueKind::Invalid),
+            ("cache-control".to_owned(), IssueKind::Invalid),
+        ]);
+    }
+
+    #[test]
+    fn valid_map_has_no_issues() {
+        let map = map(&[
+            ("content-type", "text/html"),
+            ("set-cookie", "a=1"),
+            ("set-cookie", "b=2"),
+            ("vary", "accept"),
+            ("vary", "origin"),
+            ("authorization", "Digest username=\"a\""),
+        ]);
+        assert!(scan(&map).is_empty());
+        assert!(scan(&HeaderMap::new()).is_empty());
+    }
+
+    #[test]
+    fn duplicate_single_value() {
+        let map = map(&[("host", "a.example"), ("host", "b.example"), ("date", "Sun, 06 Nov 1994 08:49:37 GMT")]);
+        assert_eq!(issues(&map), [("host".to_owned(), IssueKind::Duplicate)]);
+
+        // One of them is invalid anyway.
+        let map = self::map(&[("content-type", "text/html"), ("content-type", "nope")]);
+        assert_eq!(issues(&map), [("content-type".to_owned(), IssueKind::Invalid)]);
+    }
+
+    #[test]
+    fn too_large() {
+        let long = format!("a={}", "b".repeat(64 * 1024));
+        let map = map(&[("cookie", &long)]);
+        assert_eq!(issues(&map), [("cookie".to_owned(), IssueKind::TooLarge)]);
+    }
+
+    #[test]
+    fn suggested_responses() {
+        let response = |name: &'static str, kind: IssueKind| {
+            let issue = HeaderIssue::new(HeaderName::from_static(name), kind);
+            (issue.suggested_status().as_u16(), issue.public_message())
+        };
+        let table = [
+            ("host", IssueKind::Invalid, 400, "malformed request header"),
+            ("host", IssueKind::Duplicate, 400, "duplicate request header"),
+            ("host", IssueKind::Missing, 400, "missing request header"),
+            ("cookie", IssueKind::TooLarge, 431, "request header fields too large"),
+            ("content-length", IssueKind::Invalid, 400, "malformed request header"),
+            ("content-length", IssueKind::Duplicate, 400, "duplicate request header"),
+            ("content-length", IssueKind::Missing, 411, "length required"),
+            ("content-length", IssueKind::TooLarge, 431, "request header fields too large"),
+            ("range", IssueKind::Invalid, 416, "range not satisfiable"),
+            ("range", IssueKind::Duplicate, 416, "range not satisfiable"),
+            ("range", IssueKind::Missing, 400, "missing request header"),
+            ("if-match", IssueKind::Invalid, 412, "precondition failed"),
+            ("if-match", IssueKind::Missing, 428, "precondition required"),
+            ("if-none-match", IssueKind::Invalid, 412, "precondition failed"),
+            ("if-none-match", IssueKind::Missing, 400, "missing request header"),
+            ("if-range", IssueKind::Duplicate, 412, "precondition failed"),
+            ("if-unmodified-since", IssueKind::Missing, 428, "precondition required"),
+            ("if-modified-since", IssueKind::Invalid, 400, "malformed request header"),
+            ("if-match", IssueKind::TooLarge, 431, "request header fields too large"),
+        ];
+        for &(name, kind, status, message) in &table {
+            assert_eq!(response(name, kind), (status, message), "{} {:?}", name, kind);
+        }
+    }
+
+    #[test]
+    fn issue_from_decode_error() {
+        let map = map(&[("range", "none=1-2")]);
+        let error = map.typed_get_with_limits::<::Range>(::DecodeLimits::default()).unwrap_err();
+        assert_eq!(error, DecodeError::Invalid);
+        let issue = HeaderIssue::new(::http::header::RANGE, error.into());
+        assert_eq!(issue.suggested_status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(issue.to_string(), "invalid range header");
+
+        let missing = HeaderIssue::new(::http::header::HOST, DecodeError::Missing.into());
+        assert_eq!(missing.to_string(), "missing host header");
+        assert_eq!(missing.suggested_status(), StatusCode::BAD_REQUEST);
+    }
+}
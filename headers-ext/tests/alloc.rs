@@ -0,0 +1,84 @@
+//! Checks that encoding headers with constant or short values doesn't
+//! allocate.
+//!
+//! This lives in its own test binary, as it needs a global allocator that
+//! counts allocations, and has a single test so nothing runs concurrently.
+
+extern crate headers_ext as headers;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::SystemTime;
+
+use headers::{Header, HeaderMap, HeaderMapExt};
+
+struct Counting;
+
+static ALLOCS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCS.fetch_add(1, Ordering::SeqCst);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn allocs_to_insert<H: Header>(map: &mut HeaderMap, header: H) -> usize {
+    let before = ALLOCS.load(Ordering::SeqCst);
+    map.typed_insert(header);
+    ALLOCS.load(Ordering::SeqCst) - before
+}
+
+#[test]
+fn constant_headers_encode_without_allocating() {
+    // Reserve up front, so only the encoding is counted.
+    let mut map = HeaderMap::with_capacity(32);
+
+    macro_rules! assert_no_allocs {
+        ($($header:expr,)+) => {
+            $(
+                assert_eq!(
+                    allocs_to_insert(&mut map, $header),
+                    0,
+                    "{}",
+                    stringify!($header),
+                );
+            )+
+        };
+    }
+
+    assert_no_allocs! {
+        headers::AcceptRanges::bytes(),
+        headers::AccessControlAllowCredentials,
+        headers::AccessControlAllowHeaders::any(),
+        headers::AccessControlAllowMethods::any(),
+        headers::AccessControlAllowOrigin::ANY,
+        headers::AccessControlExposeHeaders::any(),
+        headers::CacheControl::new().with_no_store(),
+        headers::Connection::close(),
+        headers::ContentEncoding::gzip(),
+        headers::Expect::CONTINUE,
+        headers::Origin::NULL,
+        headers::Pragma::no_cache(),
+        headers::ReferrerPolicy::NO_REFERRER,
+        headers::ReferrerPolicy::STRICT_ORIGIN_WHEN_CROSS_ORIGIN,
+        headers::SecWebsocketVersion::V13,
+        headers::TransferEncoding::chunked(),
+        headers::Upgrade::websocket(),
+        headers::Vary::any(),
+    }
+
+    // Short formatted values fit inline in their `HeaderValue`.
+    assert_no_allocs! {
+        headers::CacheControl::new().with_no_store().with_private(),
+        headers::ContentLength(1_048_576),
+        headers::Date::from(SystemTime::now()),
+    }
+}
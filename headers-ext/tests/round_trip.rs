@@ -0,0 +1,473 @@
+//! Property tests that every typed header survives a round trip through
+//! its header values.
+//!
+//! Each header supplies a strategy that builds random values of it, and is
+//! checked for two properties:
+//!
+//! - Encoding and then decoding gives back an equal value.
+//! - Decoding is stable: decoding a value, encoding it again, and decoding
+//!   that gives the same result. This is also checked for encoded values
+//!   that have been mangled into a non-canonical form, such as with extra
+//!   whitespace or different letter case.
+//!
+//! The cases are generated from a fixed seed, so failures are reproducible.
+
+extern crate headers_ext as headers;
+extern crate http;
+extern crate mime;
+
+use std::fmt::Debug;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use headers::{Header, HeaderMap, HeaderMapExt, HeaderValue};
+use http::Method;
+
+const CASES: u64 = 256;
+
+/// A small xorshift generator, seeded per case.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Rng {
+        Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    fn bool(&mut self) -> bool {
+        self.next() & 1 == 1
+    }
+
+    fn pick<T: Clone>(&mut self, items: &[T]) -> T {
+        items[self.below(items.len() as u64) as usize].clone()
+    }
+
+    fn string(&mut self, alphabet: &[char], max_len: u64) -> String {
+        let len = 1 + self.below(max_len);
+        (0..len).map(|_| self.pick(alphabet)).collect()
+    }
+
+    fn duration(&mut self) -> Duration {
+        // Mostly small values, sometimes ones that need many digits.
+        match self.below(3) {
+            0 => Duration::from_secs(self.below(10)),
+            1 => Duration::from_secs(self.below(100_000)),
+            _ => Duration::from_secs(self.below(u64::from(u32::MAX))),
+        }
+    }
+
+    fn time(&mut self) -> SystemTime {
+        // Anywhere from 1970 to 2100, in whole seconds.
+        UNIX_EPOCH + Duration::from_secs(self.below(4_102_444_800))
+    }
+}
+
+const TOKEN: &[char] = &[
+    'a', 'b', 'z', 'A', 'Z', '0', '9', '!', '#', '$', '%', '&', '\'', '*', '+', '-', '.', '^',
+    '_', '`', '|', '~',
+];
+
+const LOWER_TOKEN: &[char] = &['a', 'k', 'z', '0', '9', '-', '_'];
+
+/// Printable ASCII that needs care when quoted.
+const TRICKY: &[char] = &['a', 'Z', '0', ' ', '"', '\\', ',', ';', '=', '*', '\'', '%'];
+
+const UNICODE: &[char] = &['a', ' ', '"', '%', 'é', 'ü', 'ß', '日', '本', '🦀'];
+
+fn encode<H: Header + Clone>(header: &H) -> Vec<HeaderValue> {
+    let mut map = HeaderMap::new();
+    map.typed_insert(header.clone());
+    map.get_all(H::name()).iter().cloned().collect()
+}
+
+fn decode<H: Header>(values: &[HeaderValue]) -> Option<H> {
+    let mut map = HeaderMap::new();
+    for value in values {
+        map.append(H::name(), value.clone());
+    }
+    map.typed_get()
+}
+
+/// Mangle an encoded value into a non-canonical, but maybe still valid,
+/// form.
+fn mangle(rng: &mut Rng, value: &HeaderValue) -> Option<HeaderValue> {
+    let mut s = value.to_str().ok()?.to_owned();
+    match rng.below(4) {
+        0 => {
+            let i = rng.below(s.len() as u64 + 1) as usize;
+            if let Some(c) = s.get(i..i + 1).and_then(|c| c.chars().next()) {
+                let flipped = if c.is_ascii_uppercase() {
+                    c.to_ascii_lowercase()
+                } else {
+                    c.to_ascii_uppercase()
+                };
+                s.replace_range(i..i + 1, &flipped.to_string());
+            }
+        },
+        1 => s = s.replace(',', " ,\t").replace(';', " ; "),
+        2 => s = format!(" , {}, ", s),
+        _ => s = format!("\t{} ", s),
+    }
+    HeaderValue::from_str(&s).ok()
+}
+
+fn check<H, S, E>(name: &str, strategy: S, eq: E)
+where
+    H: Header + Clone + Debug,
+    S: Fn(&mut Rng) -> H,
+    E: Fn(&H, &H) -> bool,
+{
+    for seed in 0..CASES {
+        let mut rng = Rng::new(seed);
+        let header = strategy(&mut rng);
+
+        let encoded = encode(&header);
+        let decoded = decode::<H>(&encoded)
+            .unwrap_or_else(|| panic!("{} seed {}: {:?} didn't decode", name, seed, encoded));
+        assert!(
+            eq(&header, &decoded),
+            "{} seed {}: {:?} decoded as {:?}",
+            name,
+            seed,
+            header,
+            decoded,
+        );
+
+        let mangled = encoded
+            .iter()
+            .map(|value| mangle(&mut rng, value))
+            .collect::<Option<Vec<_>>>();
+        for values in vec![encoded.clone()].into_iter().chain(mangled) {
+            if let Some(first) = decode::<H>(&values) {
+                let reencoded = encode(&first);
+                let second = decode::<H>(&reencoded).unwrap_or_else(|| {
+                    panic!("{} seed {}: {:?} didn't decode again", name, seed, reencoded)
+                });
+                assert!(
+                    eq(&first, &second),
+                    "{} seed {}: {:?} decoded as {:?}, then {:?}",
+                    name,
+                    seed,
+                    values,
+                    first,
+                    second,
+                );
+                assert_eq!(encode(&second), reencoded, "{} seed {}", name, seed);
+            }
+        }
+    }
+}
+
+/// Declare a round trip test for each header, given a strategy to build
+/// values of it.
+///
+/// Headers are compared with `PartialEq`, unless an `eq` function is given.
+macro_rules! round_trip {
+    () => {};
+    ($test:ident: $ty:ty = $strategy:expr, eq = $eq:expr; $($rest:tt)*) => {
+        #[test]
+        fn $test() {
+            check::<$ty, _, _>(stringify!($ty), $strategy, $eq);
+        }
+        round_trip!($($rest)*);
+    };
+    ($test:ident: $ty:ty = $strategy:expr; $($rest:tt)*) => {
+        round_trip!($test: $ty = $strategy, eq = |a: &$ty, b: &$ty| a == b; $($rest)*);
+    };
+}
+
+const METHODS: &[Method] = &[
+    Method::GET,
+    Method::HEAD,
+    Method::POST,
+    Method::PUT,
+    Method::DELETE,
+    Method::OPTIONS,
+    Method::PATCH,
+];
+
+fn methods(rng: &mut Rng) -> Vec<Method> {
+    (0..1 + rng.below(4)).map(|_| rng.pick(METHODS)).collect()
+}
+
+fn header_names(rng: &mut Rng) -> Vec<http::header::HeaderName> {
+    (0..1 + rng.below(4))
+        .map(|_| rng.string(LOWER_TOKEN, 12).parse().unwrap())
+        .collect()
+}
+
+fn entity_tag(rng: &mut Rng) -> headers::EntityTag {
+    let tag = rng.string(&['a', 'Z', '0', '!', '#', '/', '\\', ',', '~'], 12);
+    if rng.bool() {
+        headers::EntityTag::weak(&tag).unwrap()
+    } else {
+        headers::EntityTag::strong(&tag).unwrap()
+    }
+}
+
+fn any_cache_control(rng: &mut Rng) -> headers::CacheControl {
+    let mut cc = headers::CacheControl::new();
+    for _ in 0..1 + rng.below(5) {
+        cc = match rng.below(10) {
+            0 => cc.with_no_cache(),
+            1 => cc.with_no_store(),
+            2 => cc.with_no_transform(),
+            3 => cc.with_only_if_cached(),
+            4 => cc.with_private(),
+            5 => cc.with_public(),
+            6 => cc.with_max_age(rng.duration()),
+            7 => cc.with_max_stale(rng.duration()),
+            8 => cc.with_min_fresh(rng.duration()),
+            _ => cc.with_s_max_age(rng.duration()),
+        };
+    }
+    cc
+}
+
+fn any_content_disposition(rng: &mut Rng) -> headers::ContentDisposition {
+    match rng.below(3) {
+        0 => headers::ContentDisposition::inline(),
+        1 => headers::ContentDisposition::attachment(&rng.string(TRICKY, 16)),
+        _ => headers::ContentDisposition::attachment(&rng.string(UNICODE, 16)),
+    }
+}
+
+fn same_disposition(a: &headers::ContentDisposition, b: &headers::ContentDisposition) -> bool {
+    a.is_inline() == b.is_inline()
+        && a.is_attachment() == b.is_attachment()
+        && a.filename() == b.filename()
+}
+
+fn any_content_range(rng: &mut Rng) -> headers::ContentRange {
+    let first = rng.below(1 << 40);
+    let last = first + rng.below(1 << 20);
+    match rng.below(3) {
+        0 => headers::ContentRange::bytes(first, last, None),
+        1 => headers::ContentRange::bytes(first, last, last + 1 + rng.below(1 << 20)),
+        _ => headers::ContentRange::unsatisfied_bytes(rng.below(1 << 40)),
+    }
+}
+
+fn any_content_type(rng: &mut Rng) -> headers::ContentType {
+    let essence = rng.pick(&["text/html", "application/json", "image/png", "multipart/form-data"]);
+    let mime = match rng.below(3) {
+        0 => essence.to_owned(),
+        1 => format!("{}; charset={}", essence, rng.pick(&["utf-8", "UTF-8", "us-ascii"])),
+        _ => format!("{}; {}=\"{}\"", essence, rng.string(LOWER_TOKEN, 6), rng.string(TOKEN, 6)),
+    };
+    mime.parse::<mime::Mime>().unwrap().into()
+}
+
+fn any_cookie(rng: &mut Rng) -> headers::Cookie {
+    let pairs = (0..1 + rng.below(5))
+        .map(|_| format!("{}={}", rng.string(LOWER_TOKEN, 8), rng.string(TOKEN, 8)))
+        .collect::<Vec<_>>();
+    pairs.join("; ").parse().unwrap()
+}
+
+fn same_cookies(a: &headers::Cookie, b: &headers::Cookie) -> bool {
+    a.iter().eq(b.iter())
+}
+
+/// Compare the items of two lists, as sets with the `canonical` feature,
+/// since it sorts them and drops repeats when encoding.
+fn same_items<'a, T, I>(a: I, b: I) -> bool
+where
+    T: AsRef<str> + 'a,
+    I: Iterator<Item = &'a T>,
+{
+    let items = |iter: I| {
+        let mut items = iter.map(AsRef::as_ref).collect::<Vec<&str>>();
+        if cfg!(feature = "canonical") {
+            items.sort();
+            items.dedup();
+        }
+        items
+    };
+    items(a) == items(b)
+}
+
+fn link_uri(rng: &mut Rng) -> String {
+    match rng.below(3) {
+        0 => format!("/{}", rng.string(LOWER_TOKEN, 12)),
+        1 => format!("https://{}.example/{}", rng.string(LOWER_TOKEN, 8), rng.string(LOWER_TOKEN, 8)),
+        _ => format!("/{}?page={}", rng.string(LOWER_TOKEN, 8), rng.below(100)),
+    }
+}
+
+fn any_link_value(rng: &mut Rng) -> headers::LinkValue {
+    use headers::RelationType;
+
+    let mut value = headers::LinkValue::new(link_uri(rng));
+    for _ in 0..rng.below(3) {
+        value = value.push_rel(match rng.below(5) {
+            0 => RelationType::Next,
+            1 => RelationType::Prev,
+            2 => RelationType::Alternate,
+            3 => RelationType::Stylesheet,
+            _ => RelationType::ExtRelType(format!("https://rel.example/{}", rng.string(LOWER_TOKEN, 8))),
+        });
+    }
+    if rng.bool() {
+        value = value.set_title(rng.string(TRICKY, 16));
+    }
+    if rng.bool() {
+        value = value.push_href_lang(rng.pick(&["en", "de-CH", "fr"]));
+    }
+    value
+}
+
+const AS_ATTRIBUTES: &[headers::AsAttribute] = &[
+    headers::AsAttribute::Audio,
+    headers::AsAttribute::Document,
+    headers::AsAttribute::Fetch,
+    headers::AsAttribute::Font,
+    headers::AsAttribute::Image,
+    headers::AsAttribute::Script,
+    headers::AsAttribute::Style,
+    headers::AsAttribute::Worker,
+];
+
+/// The link-values of `103 Early Hints`: preloads, preconnects and
+/// module preloads, with their CORS and priority hints.
+fn any_preload(rng: &mut Rng) -> headers::LinkValue {
+    let mut value = match rng.below(3) {
+        0 => headers::LinkValue::preload(link_uri(rng), rng.pick(AS_ATTRIBUTES)),
+        1 => headers::LinkValue::preconnect(format!("https://{}.example", rng.string(LOWER_TOKEN, 8))),
+        _ => headers::LinkValue::modulepreload(link_uri(rng)),
+    };
+    if rng.bool() {
+        value = value.set_crossorigin(rng.pick(&[
+            headers::CrossOrigin::Anonymous,
+            headers::CrossOrigin::UseCredentials,
+        ]));
+    }
+    if rng.bool() {
+        value = value.set_fetch_priority(rng.pick(&[
+            headers::FetchPriority::High,
+            headers::FetchPriority::Low,
+            headers::FetchPriority::Auto,
+        ]));
+    }
+    value
+}
+
+fn any_link(rng: &mut Rng) -> headers::Link {
+    let mut link = headers::Link::new(
+        (0..1 + rng.below(3))
+            .map(|_| if rng.bool() { any_link_value(rng) } else { any_preload(rng) })
+            .collect(),
+    );
+    if rng.bool() {
+        let preloads = (0..1 + rng.below(3))
+            .map(|_| (link_uri(rng), rng.pick(AS_ATTRIBUTES)))
+            .collect::<Vec<_>>();
+        link.extend_preloads(preloads);
+    }
+    link
+}
+
+fn any_range(rng: &mut Rng) -> headers::Range {
+    let specs = (0..1 + rng.below(3))
+        .map(|_| {
+            let from = rng.below(1 << 40);
+            match rng.below(3) {
+                0 => format!("{}-{}", from, from + rng.below(1 << 20)),
+                1 => format!("{}-", from),
+                _ => format!("-{}", from),
+            }
+        })
+        .collect::<Vec<_>>();
+    let value = HeaderValue::from_str(&format!("bytes={}", specs.join(","))).unwrap();
+    decode(&[value]).unwrap()
+}
+
+fn any_expect_ct(rng: &mut Rng) -> headers::ExpectCt {
+    let mut ect = headers::ExpectCt::new(rng.duration()).with_enforce(rng.bool());
+    if rng.bool() {
+        let uri = format!("https://{}.example/report", rng.string(LOWER_TOKEN, 8));
+        ect = ect.with_report_uri(uri.parse().unwrap());
+    }
+    ect
+}
+
+fn any_keep_alive(rng: &mut Rng) -> headers::KeepAlive {
+    let mut ka = headers::KeepAlive::new();
+    if rng.bool() {
+        ka = ka.with_timeout(rng.duration());
+    }
+    if rng.bool() || ka == headers::KeepAlive::new() {
+        ka = ka.with_max(rng.next() as u32);
+    }
+    ka
+}
+
+fn any_host(rng: &mut Rng) -> headers::Host {
+    let authority = if rng.bool() {
+        rng.string(LOWER_TOKEN, 12)
+    } else {
+        format!("{}:{}", rng.string(LOWER_TOKEN, 12), rng.below(65536))
+    };
+    authority.parse::<http::uri::Authority>().unwrap().into()
+}
+
+round_trip! {
+    access_control_allow_headers: headers::AccessControlAllowHeaders =
+        |rng| header_names(rng).into_iter().collect(),
+        eq = |a, b| same_items(a.iter(), b.iter());
+    access_control_allow_methods: headers::AccessControlAllowMethods =
+        |rng| methods(rng).into_iter().collect(),
+        eq = |a, b| same_items(a.iter(), b.iter());
+    access_control_max_age: headers::AccessControlMaxAge = |rng| rng.duration().into();
+    age: headers::Age = |rng| rng.duration().into();
+    allow: headers::Allow = |rng| methods(rng).into_iter().collect(),
+        eq = |a, b| same_items(a.iter(), b.iter());
+    cache_control: headers::CacheControl = any_cache_control;
+    content_disposition: headers::ContentDisposition = any_content_disposition,
+        eq = same_disposition;
+    content_length: headers::ContentLength = |rng| headers::ContentLength(rng.next());
+    content_range: headers::ContentRange = any_content_range;
+    content_type: headers::ContentType = any_content_type;
+    cookie: headers::Cookie = any_cookie, eq = same_cookies;
+    date: headers::Date = |rng| rng.time().into();
+    etag: headers::ETag = |rng| entity_tag(rng).into();
+    expect_ct: headers::ExpectCt = any_expect_ct;
+    expires: headers::Expires = |rng| rng.time().into();
+    host: headers::Host = any_host;
+    if_none_match: headers::IfNoneMatch = |rng| headers::ETag::from(entity_tag(rng)).into();
+    keep_alive: headers::KeepAlive = any_keep_alive;
+    last_modified: headers::LastModified = |rng| rng.time().into();
+    link: headers::Link = any_link;
+    link_multi_line: headers::list::MultiLine<headers::Link> =
+        |rng| headers::list::MultiLine(any_link(rng));
+    range: headers::Range = any_range;
+    retry_after: headers::RetryAfter = |rng| if rng.bool() {
+        headers::RetryAfter::delay(rng.duration())
+    } else {
+        headers::RetryAfter::date(rng.time())
+    };
+    referrer_policy: headers::ReferrerPolicy = |rng| rng.pick(&[
+        headers::ReferrerPolicy::NO_REFERRER,
+        headers::ReferrerPolicy::NO_REFERRER_WHEN_DOWNGRADE,
+        headers::ReferrerPolicy::SAME_ORIGIN,
+        headers::ReferrerPolicy::ORIGIN,
+        headers::ReferrerPolicy::ORIGIN_WHEN_CROSS_ORIGIN,
+        headers::ReferrerPolicy::UNSAFE_URL,
+        headers::ReferrerPolicy::STRICT_ORIGIN,
+        headers::ReferrerPolicy::STRICT_ORIGIN_WHEN_CROSS_ORIGIN,
+    ]);
+    strict_transport_security: headers::StrictTransportSecurity = |rng| if rng.bool() {
+        headers::StrictTransportSecurity::including_subdomains(rng.duration())
+    } else {
+        headers::StrictTransportSecurity::excluding_subdomains(rng.duration())
+    };
+}